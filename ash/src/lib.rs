@@ -6,7 +6,7 @@
 //! ```ignore
 //! use {
 //!     ash::{vk, DefaultEntryLoader, DeviceLoader, InstanceLoader},
-//!     gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
+//!     gpu_alloc::{Config, GpuAllocator, Request, ResourceKind, UsageFlags},
 //!     gpu_alloc_ash::{device_properties, AshMemoryDevice},
 //!     std::ffi::CStr,
 //! };
@@ -60,6 +60,7 @@
 //!                 align_mask: 1,
 //!                 usage: UsageFlags::HOST_ACCESS,
 //!                 memory_types: !0,
+//!                 kind: ResourceKind::Linear,
 //!             },
 //!         )
 //!     }?;
@@ -84,6 +85,7 @@
 //!                 align_mask: 1,
 //!                 usage: UsageFlags::HOST_ACCESS,
 //!                 memory_types: !0,
+//!                 kind: ResourceKind::Linear,
 //!             },
 //!         )
 //!     }?;
@@ -103,16 +105,37 @@
 //! ```
 //!
 
+mod memory_report;
+mod resource;
+
+pub use self::{
+    memory_report::{memory_report_callback, memory_report_create_info, memory_report_event},
+    resource::{
+        alloc_for_resource, create_buffer, create_image, destroy_buffer, destroy_image,
+        ResourceError,
+    },
+};
+
 use {
-    ash::{vk, Device, Instance},
+    ash::{
+        vk::{self, Handle as _},
+        Device, Instance,
+    },
     gpu_alloc_types::{
-        AllocationFlags, DeviceMapError, DeviceProperties, MappedMemoryRange, MemoryDevice,
-        MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory,
+        AllocationFlags, DedicatedAllocationInfo, DeviceDiagnostic, DeviceMapError,
+        DeviceProperties, MappedMemoryRange, MemoryDevice, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, OutOfMemory,
     },
-    std::ptr::NonNull,
+    std::{borrow::Cow, cell::Cell, ptr::NonNull},
     tinyvec::TinyVec,
 };
 
+std::thread_local! {
+    /// Result of the last failed `vkAllocateMemory` call on this thread.
+    /// `AshMemoryDevice` is a transparent wrapper and can't keep it by itself.
+    static ALLOCATION_ERROR: Cell<Option<vk::Result>> = const { Cell::new(None) };
+}
+
 #[repr(transparent)]
 pub struct AshMemoryDevice {
     device: Device,
@@ -126,6 +149,59 @@ impl AshMemoryDevice {
             &*(device as *const Device as *const Self)
         }
     }
+
+    /// Allocates memory object, dedicated to the resource if `dedicated` is specified.
+    /// Dedicated allocation requires Vulkan 1.1 or "VK_KHR_dedicated_allocation" extension enabled.
+    unsafe fn allocate(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        dedicated: Option<DedicatedAllocationInfo>,
+    ) -> Result<vk::DeviceMemory, OutOfMemory> {
+        assert!((flags & !(AllocationFlags::DEVICE_ADDRESS)).is_empty());
+
+        let mut info = vk::MemoryAllocateInfo::default()
+            .allocation_size(size)
+            .memory_type_index(memory_type);
+
+        let mut info_flags;
+
+        if flags.contains(AllocationFlags::DEVICE_ADDRESS) {
+            info_flags = vk::MemoryAllocateFlagsInfo::default()
+                .flags(vk::MemoryAllocateFlags::DEVICE_ADDRESS);
+            info = info.push_next(&mut info_flags);
+        }
+
+        let mut info_dedicated;
+
+        if let Some(dedicated) = dedicated {
+            info_dedicated = match dedicated {
+                DedicatedAllocationInfo::Buffer(buffer) => {
+                    vk::MemoryDedicatedAllocateInfo::default().buffer(vk::Buffer::from_raw(buffer))
+                }
+                DedicatedAllocationInfo::Image(image) => {
+                    vk::MemoryDedicatedAllocateInfo::default().image(vk::Image::from_raw(image))
+                }
+            };
+            info = info.push_next(&mut info_dedicated);
+        }
+
+        let err = match self.device.allocate_memory(&info, None) {
+            Ok(memory) => return Ok(memory),
+            Err(err) => err,
+        };
+
+        let oom = match err {
+            vk::Result::ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory::OutOfDeviceMemory,
+            vk::Result::ERROR_OUT_OF_HOST_MEMORY => OutOfMemory::OutOfHostMemory,
+            vk::Result::ERROR_TOO_MANY_OBJECTS => panic!("Too many objects"),
+            err => panic!("Unexpected Vulkan error: `{}`", err),
+        };
+
+        ALLOCATION_ERROR.with(|last| last.set(Some(err)));
+        Err(oom)
+    }
 }
 
 impl AsRef<AshMemoryDevice> for Device {
@@ -152,27 +228,18 @@ impl MemoryDevice<vk::DeviceMemory> for AshMemoryDevice {
         memory_type: u32,
         flags: AllocationFlags,
     ) -> Result<vk::DeviceMemory, OutOfMemory> {
-        assert!((flags & !(AllocationFlags::DEVICE_ADDRESS)).is_empty());
-
-        let mut info = vk::MemoryAllocateInfo::default()
-            .allocation_size(size)
-            .memory_type_index(memory_type);
-
-        let mut info_flags;
-
-        if flags.contains(AllocationFlags::DEVICE_ADDRESS) {
-            info_flags = vk::MemoryAllocateFlagsInfo::default()
-                .flags(vk::MemoryAllocateFlags::DEVICE_ADDRESS);
-            info = info.push_next(&mut info_flags);
-        }
+        self.allocate(size, memory_type, flags, None)
+    }
 
-        match self.device.allocate_memory(&info, None) {
-            Ok(memory) => Ok(memory),
-            Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(OutOfMemory::OutOfDeviceMemory),
-            Err(vk::Result::ERROR_OUT_OF_HOST_MEMORY) => Err(OutOfMemory::OutOfHostMemory),
-            Err(vk::Result::ERROR_TOO_MANY_OBJECTS) => panic!("Too many objects"),
-            Err(err) => panic!("Unexpected Vulkan error: `{}`", err),
-        }
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        info: DedicatedAllocationInfo,
+    ) -> Result<vk::DeviceMemory, OutOfMemory> {
+        self.allocate(size, memory_type, flags, Some(info))
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
@@ -254,6 +321,14 @@ impl MemoryDevice<vk::DeviceMemory> for AshMemoryDevice {
                 err => panic!("Unexpected Vulkan error: `{}`", err),
             })
     }
+
+    fn take_allocation_diagnostic(&self) -> Option<DeviceDiagnostic> {
+        let err = ALLOCATION_ERROR.with(Cell::take)?;
+        Some(DeviceDiagnostic {
+            code: err.as_raw().into(),
+            message: Cow::Owned(err.to_string()),
+        })
+    }
 }
 
 /// Returns `DeviceProperties` from ash's `InstanceLoader` for specified `PhysicalDevice`, required to create `GpuAllocator`.
@@ -265,6 +340,9 @@ impl MemoryDevice<vk::DeviceMemory> for AshMemoryDevice {
 /// feature `PhysicalDeviceBufferDeviceAddressFeatures::buffer_derive_address`  must be enabled explicitly on device creation
 /// and extension "VK_KHR_buffer_device_address" for Vulkan prior 1.2.
 /// Otherwise the field must be set to false before passing to `GpuAllocator::new`.
+///
+/// Budget and usage of memory heaps are queried when "VK_EXT_memory_budget" is supported
+/// and Vulkan version is at least 1.1.
 pub unsafe fn device_properties(
     instance: &Instance,
     version: u32,
@@ -290,10 +368,32 @@ pub unsafe fn device_properties(
             false
         };
 
+    let memory_budget =
+        if vk::api_version_major(version) >= 1 && vk::api_version_minor(version) >= 1 {
+            let extensions = instance.enumerate_device_extension_properties(physical_device)?;
+            let memory_budget_available = extensions
+                .iter()
+                .any(|ext| ext.extension_name_as_c_str() == Ok(vk::EXT_MEMORY_BUDGET_NAME));
+
+            if memory_budget_available {
+                let mut properties = vk::PhysicalDeviceMemoryProperties2::default();
+                let mut budget = vk::PhysicalDeviceMemoryBudgetPropertiesEXT::default();
+                properties.p_next =
+                    &mut budget as *mut vk::PhysicalDeviceMemoryBudgetPropertiesEXT as *mut _;
+                instance.get_physical_device_memory_properties2(physical_device, &mut properties);
+                Some(budget)
+            } else {
+                None
+            }
+        } else {
+            None
+        };
+
     Ok(DeviceProperties {
         max_memory_allocation_count: limits.max_memory_allocation_count,
-        max_memory_allocation_size: u64::max_value(), // FIXME: Can query this information if instance is v1.1
+        max_memory_allocation_size: u64::MAX, // FIXME: Can query this information if instance is v1.1
         non_coherent_atom_size: limits.non_coherent_atom_size,
+        buffer_image_granularity: limits.buffer_image_granularity,
         memory_types: memory_properties.memory_types
             [..memory_properties.memory_type_count as usize]
             .iter()
@@ -305,8 +405,15 @@ pub unsafe fn device_properties(
         memory_heaps: memory_properties.memory_heaps
             [..memory_properties.memory_heap_count as usize]
             .iter()
-            .map(|&memory_heap| MemoryHeap {
+            .enumerate()
+            .map(|(index, &memory_heap)| MemoryHeap {
                 size: memory_heap.size,
+                budget: memory_budget
+                    .as_ref()
+                    .map(|budget| budget.heap_budget[index]),
+                usage: memory_budget
+                    .as_ref()
+                    .map(|budget| budget.heap_usage[index]),
             })
             .collect(),
         buffer_device_address,
@@ -0,0 +1,85 @@
+use {
+    ash::vk,
+    gpu_alloc::{DeviceMemoryReport, MemoryReportEvent},
+    std::{ffi::c_void, sync::Mutex},
+};
+
+/// Converts `VK_EXT_device_memory_report` callback data into event.
+///
+/// Returns `None` for events about objects other than `VkDeviceMemory`,
+/// which are driver internal allocations that allocator can't account.
+pub fn memory_report_event(
+    data: &vk::DeviceMemoryReportCallbackDataEXT<'_>,
+) -> Option<MemoryReportEvent> {
+    if data.object_type != vk::ObjectType::DEVICE_MEMORY {
+        return None;
+    }
+
+    let event = match data.ty {
+        vk::DeviceMemoryReportEventTypeEXT::ALLOCATE => MemoryReportEvent::Allocate {
+            id: data.memory_object_id,
+            handle: data.object_handle,
+            size: data.size,
+            heap: data.heap_index,
+        },
+        vk::DeviceMemoryReportEventTypeEXT::FREE => MemoryReportEvent::Free {
+            id: data.memory_object_id,
+        },
+        vk::DeviceMemoryReportEventTypeEXT::IMPORT => MemoryReportEvent::Import {
+            id: data.memory_object_id,
+            handle: data.object_handle,
+            size: data.size,
+            heap: data.heap_index,
+        },
+        vk::DeviceMemoryReportEventTypeEXT::UNIMPORT => MemoryReportEvent::Unimport {
+            id: data.memory_object_id,
+        },
+        vk::DeviceMemoryReportEventTypeEXT::ALLOCATION_FAILED => {
+            MemoryReportEvent::AllocationFailed {
+                size: data.size,
+                heap: data.heap_index,
+            }
+        }
+        _ => return None,
+    };
+
+    Some(event)
+}
+
+/// `VK_EXT_device_memory_report` callback that records events
+/// into `Mutex<DeviceMemoryReport>` passed as user data.
+///
+/// # Safety
+///
+/// * `data` must point to valid callback data.
+/// * `user_data` must point to `Mutex<DeviceMemoryReport>` that outlives the device.
+pub unsafe extern "system" fn memory_report_callback(
+    data: *const vk::DeviceMemoryReportCallbackDataEXT<'_>,
+    user_data: *mut c_void,
+) {
+    let report = &*(user_data as *const Mutex<DeviceMemoryReport>);
+
+    if let Some(event) = memory_report_event(&*data) {
+        // Panicking across FFI boundary aborts, report stays consistent after poisoning.
+        let mut report = match report.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        report.record(event);
+    }
+}
+
+/// Returns structure to chain into `vk::DeviceCreateInfo`
+/// so that driver reports memory objects of created device into `report`.
+///
+/// `VK_EXT_device_memory_report` extension must be enabled on the device
+/// and `deviceMemoryReport` feature must be supported.
+///
+/// `report` must outlive the device.
+pub fn memory_report_create_info(
+    report: &Mutex<DeviceMemoryReport>,
+) -> vk::DeviceDeviceMemoryReportCreateInfoEXT<'_> {
+    vk::DeviceDeviceMemoryReportCreateInfoEXT::default()
+        .pfn_user_callback(Some(memory_report_callback))
+        .user_data(report as *const Mutex<DeviceMemoryReport> as *mut c_void)
+}
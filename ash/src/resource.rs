@@ -0,0 +1,244 @@
+use {
+    crate::AshMemoryDevice,
+    ash::{
+        vk::{self, Handle as _},
+        Device,
+    },
+    gpu_alloc::{
+        AllocationError, DedicatedAllocationInfo, GpuAllocator, MemoryBlock, MemoryDevice, Request,
+        ResourceKind, UsageFlags,
+    },
+    std::fmt,
+};
+
+/// Error of creating resource bound to allocated memory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResourceError {
+    /// Vulkan call failed.
+    Vulkan(vk::Result),
+
+    /// Memory block for the resource could not be allocated.
+    Allocation(AllocationError),
+}
+
+impl From<vk::Result> for ResourceError {
+    fn from(err: vk::Result) -> Self {
+        ResourceError::Vulkan(err)
+    }
+}
+
+impl From<AllocationError> for ResourceError {
+    fn from(err: AllocationError) -> Self {
+        ResourceError::Allocation(err)
+    }
+}
+
+impl fmt::Display for ResourceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResourceError::Vulkan(err) => write!(f, "Vulkan error: {}", err),
+            ResourceError::Allocation(err) => write!(f, "Allocation error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ResourceError {}
+
+/// Creates buffer, allocates memory block for it and binds them.
+///
+/// Memory requirements are queried with `vkGetBufferMemoryRequirements2`.
+/// Buffers for which driver requires or prefers dedicated allocation
+/// get their own memory object allocated with `VkMemoryDedicatedAllocateInfo`.
+///
+/// On failure every object created by this function is destroyed.
+///
+/// # Safety
+///
+/// * `device` must be created with Vulkan 1.1 or later.
+/// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+/// * `info` must be valid buffer create info.
+pub unsafe fn create_buffer(
+    device: &Device,
+    allocator: &mut GpuAllocator<vk::DeviceMemory>,
+    info: &vk::BufferCreateInfo<'_>,
+    usage: UsageFlags,
+) -> Result<(vk::Buffer, MemoryBlock<vk::DeviceMemory>), ResourceError> {
+    let buffer = device.create_buffer(info, None)?;
+
+    let mut dedicated = vk::MemoryDedicatedRequirements::default();
+    let mut requirements = vk::MemoryRequirements2::default().push_next(&mut dedicated);
+    device.get_buffer_memory_requirements2(
+        &vk::BufferMemoryRequirementsInfo2::default().buffer(buffer),
+        &mut requirements,
+    );
+    let requirements = requirements.memory_requirements;
+
+    let block = match alloc_for_resource(
+        allocator,
+        AshMemoryDevice::wrap(device),
+        &requirements,
+        &dedicated,
+        usage,
+        ResourceKind::Linear,
+        DedicatedAllocationInfo::Buffer(buffer.as_raw()),
+    ) {
+        Ok(block) => block,
+        Err(err) => {
+            device.destroy_buffer(buffer, None);
+            return Err(err.into());
+        }
+    };
+
+    if let Err(err) = device.bind_buffer_memory(buffer, *block.memory(), block.offset()) {
+        allocator.dealloc(AshMemoryDevice::wrap(device), block);
+        device.destroy_buffer(buffer, None);
+        return Err(err.into());
+    }
+
+    Ok((buffer, block))
+}
+
+/// Creates image, allocates memory block for it and binds them.
+///
+/// Memory requirements are queried with `vkGetImageMemoryRequirements2`.
+/// Images for which driver requires or prefers dedicated allocation
+/// get their own memory object allocated with `VkMemoryDedicatedAllocateInfo`.
+/// Images with optimal tiling are allocated as non-linear resources,
+/// so they don't share `bufferImageGranularity` page with buffers.
+///
+/// On failure every object created by this function is destroyed.
+///
+/// # Safety
+///
+/// * `device` must be created with Vulkan 1.1 or later.
+/// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+/// * `info` must be valid image create info without `DISJOINT` flag.
+pub unsafe fn create_image(
+    device: &Device,
+    allocator: &mut GpuAllocator<vk::DeviceMemory>,
+    info: &vk::ImageCreateInfo<'_>,
+    usage: UsageFlags,
+) -> Result<(vk::Image, MemoryBlock<vk::DeviceMemory>), ResourceError> {
+    let image = device.create_image(info, None)?;
+
+    let mut dedicated = vk::MemoryDedicatedRequirements::default();
+    let mut requirements = vk::MemoryRequirements2::default().push_next(&mut dedicated);
+    device.get_image_memory_requirements2(
+        &vk::ImageMemoryRequirementsInfo2::default().image(image),
+        &mut requirements,
+    );
+    let requirements = requirements.memory_requirements;
+
+    // Optimal tiling is implementation-defined and must not share granularity page with buffers.
+    let kind = if info.tiling == vk::ImageTiling::OPTIMAL {
+        ResourceKind::NonLinear
+    } else {
+        ResourceKind::Linear
+    };
+
+    let block = match alloc_for_resource(
+        allocator,
+        AshMemoryDevice::wrap(device),
+        &requirements,
+        &dedicated,
+        usage,
+        kind,
+        DedicatedAllocationInfo::Image(image.as_raw()),
+    ) {
+        Ok(block) => block,
+        Err(err) => {
+            device.destroy_image(image, None);
+            return Err(err.into());
+        }
+    };
+
+    if let Err(err) = device.bind_image_memory(image, *block.memory(), block.offset()) {
+        allocator.dealloc(AshMemoryDevice::wrap(device), block);
+        device.destroy_image(image, None);
+        return Err(err.into());
+    }
+
+    Ok((image, block))
+}
+
+/// Destroys buffer created with [`create_buffer`] and deallocates its memory block.
+///
+/// # Safety
+///
+/// * `buffer` and `block` must be returned together by [`create_buffer`]
+///   with the same `device` and `allocator`.
+/// * `buffer` must not be in use by the device.
+pub unsafe fn destroy_buffer(
+    device: &Device,
+    allocator: &mut GpuAllocator<vk::DeviceMemory>,
+    buffer: vk::Buffer,
+    block: MemoryBlock<vk::DeviceMemory>,
+) {
+    device.destroy_buffer(buffer, None);
+    allocator.dealloc(AshMemoryDevice::wrap(device), block);
+}
+
+/// Destroys image created with [`create_image`] and deallocates its memory block.
+///
+/// # Safety
+///
+/// * `image` and `block` must be returned together by [`create_image`]
+///   with the same `device` and `allocator`.
+/// * `image` must not be in use by the device.
+pub unsafe fn destroy_image(
+    device: &Device,
+    allocator: &mut GpuAllocator<vk::DeviceMemory>,
+    image: vk::Image,
+    block: MemoryBlock<vk::DeviceMemory>,
+) {
+    device.destroy_image(image, None);
+    allocator.dealloc(AshMemoryDevice::wrap(device), block);
+}
+
+/// Allocates memory block for resource with specified memory requirements,
+/// as queried with `vkGetBufferMemoryRequirements2` or `vkGetImageMemoryRequirements2`.
+///
+/// When driver requires or prefers dedicated allocation, memory object is allocated
+/// for the `resource` specifically with [`GpuAllocator::alloc_dedicated_for`].
+/// `kind` must be `ResourceKind::NonLinear` for images with optimal tiling
+/// and `ResourceKind::Linear` otherwise.
+///
+/// Used by [`create_buffer`] and [`create_image`],
+/// useful when resource is created by the application.
+///
+/// # Safety
+///
+/// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+/// * Same `device` instance must be used for all interactions with `allocator`
+///   and memory blocks allocated from it.
+/// * `resource` must be valid buffer or image that is not bound to memory
+///   and `requirements` and `dedicated` must be queried for it.
+pub unsafe fn alloc_for_resource<M, MD>(
+    allocator: &mut GpuAllocator<M>,
+    device: &impl AsRef<MD>,
+    requirements: &vk::MemoryRequirements,
+    dedicated: &vk::MemoryDedicatedRequirements<'_>,
+    usage: UsageFlags,
+    kind: ResourceKind,
+    resource: DedicatedAllocationInfo,
+) -> Result<MemoryBlock<M>, AllocationError>
+where
+    M: fmt::Debug + 'static,
+    MD: MemoryDevice<M>,
+{
+    let request = Request {
+        size: requirements.size,
+        align_mask: requirements.alignment - 1,
+        usage,
+        memory_types: requirements.memory_type_bits,
+        kind,
+    };
+
+    if dedicated.requires_dedicated_allocation != vk::FALSE
+        || dedicated.prefers_dedicated_allocation != vk::FALSE
+    {
+        allocator.alloc_dedicated_for(device, request, resource)
+    } else {
+        allocator.alloc(device, request)
+    }
+}
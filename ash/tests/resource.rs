@@ -0,0 +1,117 @@
+use {
+    ash::vk,
+    gpu_alloc::{
+        Config, DedicatedAllocationInfo, DeviceProperties, GpuAllocator, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, ResourceKind, UsageFlags,
+    },
+    gpu_alloc_ash::alloc_for_resource,
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+const GRANULARITY: u64 = 4096;
+
+fn device() -> MockMemoryDevice {
+    MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 1024 * 1024 * 1024,
+            budget: None,
+            usage: None,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 1024 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_image_granularity: GRANULARITY,
+        buffer_device_address: false,
+    })
+}
+
+fn requirements(size: u64) -> vk::MemoryRequirements {
+    vk::MemoryRequirements {
+        size,
+        alignment: 256,
+        memory_type_bits: 1,
+    }
+}
+
+#[test]
+fn linear_and_non_linear_resources_do_not_share_page() {
+    let device = device();
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+    let dedicated = vk::MemoryDedicatedRequirements::default();
+
+    let mut blocks = Vec::new();
+    let mut resources = Vec::new();
+    for (handle, kind) in [
+        (1, ResourceKind::Linear),
+        (2, ResourceKind::NonLinear),
+        (3, ResourceKind::Linear),
+        (4, ResourceKind::NonLinear),
+    ] {
+        let block = unsafe {
+            alloc_for_resource(
+                &mut allocator,
+                &device,
+                &requirements(600),
+                &dedicated,
+                UsageFlags::FAST_DEVICE_ACCESS,
+                kind,
+                DedicatedAllocationInfo::Buffer(handle),
+            )
+        }
+        .unwrap();
+
+        // Panics if resources of different kind share granularity page.
+        resources.push(device.bind_resource(*block.memory(), block.offset(), 600, kind));
+        blocks.push(block);
+    }
+
+    for resource in resources {
+        device.unbind_resource(resource);
+    }
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+}
+
+#[test]
+fn dedicated_requirements_pass_resource_to_device() {
+    let device = device();
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let required = vk::MemoryDedicatedRequirements {
+        requires_dedicated_allocation: vk::TRUE,
+        ..Default::default()
+    };
+    let preferred = vk::MemoryDedicatedRequirements {
+        prefers_dedicated_allocation: vk::TRUE,
+        ..Default::default()
+    };
+
+    for (dedicated, info) in [
+        (required, DedicatedAllocationInfo::Buffer(7)),
+        (preferred, DedicatedAllocationInfo::Image(9)),
+    ] {
+        let block = unsafe {
+            alloc_for_resource(
+                &mut allocator,
+                &device,
+                &requirements(600),
+                &dedicated,
+                UsageFlags::FAST_DEVICE_ACCESS,
+                ResourceKind::Linear,
+                info,
+            )
+        }
+        .unwrap();
+
+        assert_eq!(device.dedicated_info(*block.memory()), Some(info));
+        unsafe { allocator.dealloc(&device, block) };
+    }
+    unsafe { allocator.cleanup(&device) };
+}
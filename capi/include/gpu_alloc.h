@@ -0,0 +1,179 @@
+/*
+ * C API for gpu-alloc.
+ *
+ * Allocator and memory blocks are opaque handles.
+ * Device is provided as table of callbacks,
+ * memory objects are 64-bit handles, such as `VkDeviceMemory`.
+ */
+
+#ifndef GPU_ALLOC_H
+#define GPU_ALLOC_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Zero is success, negative values are errors. */
+typedef int32_t GpuAllocResult;
+
+#define GPU_ALLOC_SUCCESS 0
+#define GPU_ALLOC_ERROR_OUT_OF_DEVICE_MEMORY -1
+#define GPU_ALLOC_ERROR_OUT_OF_HOST_MEMORY -2
+#define GPU_ALLOC_ERROR_MAP_FAILED -3
+#define GPU_ALLOC_ERROR_NO_COMPATIBLE_MEMORY_TYPES -4
+#define GPU_ALLOC_ERROR_TOO_MANY_OBJECTS -5
+#define GPU_ALLOC_ERROR_OUT_OF_BUDGET -6
+#define GPU_ALLOC_ERROR_INVALID_ALIGNMENT -7
+#define GPU_ALLOC_ERROR_SIZE_OVERFLOW -8
+#define GPU_ALLOC_ERROR_NON_HOST_VISIBLE -9
+#define GPU_ALLOC_ERROR_ALREADY_MAPPED -10
+#define GPU_ALLOC_ERROR_OUT_OF_BOUNDS -11
+#define GPU_ALLOC_ERROR_UNADDRESSABLE -12
+#define GPU_ALLOC_ERROR_INVALID_ARGUMENT -13
+
+/* Values of `GpuAllocRequest::dedicated`. */
+#define GPU_ALLOC_DEDICATED_NONE 0
+#define GPU_ALLOC_DEDICATED_REQUIRED 1
+#define GPU_ALLOC_DEDICATED_PREFERRED 2
+
+/* Values of `GpuAllocRequest::kind`. */
+#define GPU_ALLOC_RESOURCE_LINEAR 0
+#define GPU_ALLOC_RESOURCE_NON_LINEAR 1
+
+/* Bits of `GpuAllocRequest::usage`. */
+#define GPU_ALLOC_USAGE_FAST_DEVICE_ACCESS 0x01
+#define GPU_ALLOC_USAGE_HOST_ACCESS 0x02
+#define GPU_ALLOC_USAGE_DOWNLOAD 0x04
+#define GPU_ALLOC_USAGE_UPLOAD 0x08
+#define GPU_ALLOC_USAGE_TRANSIENT 0x10
+#define GPU_ALLOC_USAGE_DEVICE_ADDRESS 0x20
+
+/* Bits of `flags` argument of `allocate_memory` callback. */
+#define GPU_ALLOC_ALLOCATION_DEVICE_ADDRESS 0x01
+
+typedef struct GpuAllocAllocator GpuAllocAllocator;
+typedef struct GpuAllocBlock GpuAllocBlock;
+
+typedef struct GpuAllocMemoryType {
+    /* Same bits as `VkMemoryPropertyFlags`. */
+    uint32_t props;
+    uint32_t heap;
+} GpuAllocMemoryType;
+
+typedef struct GpuAllocMemoryHeap {
+    uint64_t size;
+} GpuAllocMemoryHeap;
+
+typedef struct GpuAllocDeviceProperties {
+    const GpuAllocMemoryType *memory_types;
+    uint32_t memory_type_count;
+    const GpuAllocMemoryHeap *memory_heaps;
+    uint32_t memory_heap_count;
+    uint32_t max_memory_allocation_count;
+    uint64_t max_memory_allocation_size;
+    uint64_t non_coherent_atom_size;
+    uint64_t buffer_image_granularity;
+    bool buffer_device_address;
+} GpuAllocDeviceProperties;
+
+typedef struct GpuAllocConfig {
+    uint64_t dedicated_threshold;
+    uint64_t preferred_dedicated_threshold;
+    uint64_t transient_dedicated_threshold;
+    uint64_t starting_free_list_chunk;
+    uint64_t final_free_list_chunk;
+    uint64_t minimal_buddy_size;
+    uint64_t initial_buddy_dedicated_size;
+    uint32_t buddy_chunk_keep_alive;
+    bool force_dedicated;
+    uint64_t bar_heap_size;
+    uint8_t bar_usage_limit;
+} GpuAllocConfig;
+
+typedef struct GpuAllocMappedRange {
+    uint64_t memory;
+    uint64_t offset;
+    uint64_t size;
+} GpuAllocMappedRange;
+
+/* Device callbacks. Each receives `user_data` as first argument. */
+typedef struct GpuAllocDeviceCallbacks {
+    void *user_data;
+    GpuAllocResult (*allocate_memory)(void *user_data, uint64_t size, uint32_t memory_type,
+                                      uint32_t flags, uint64_t *memory);
+    void (*deallocate_memory)(void *user_data, uint64_t memory);
+    GpuAllocResult (*map_memory)(void *user_data, uint64_t memory, uint64_t offset,
+                                 uint64_t size, void **ptr);
+    void (*unmap_memory)(void *user_data, uint64_t memory);
+    GpuAllocResult (*invalidate_memory_ranges)(void *user_data, const GpuAllocMappedRange *ranges,
+                                               uint32_t count);
+    GpuAllocResult (*flush_memory_ranges)(void *user_data, const GpuAllocMappedRange *ranges,
+                                          uint32_t count);
+} GpuAllocDeviceCallbacks;
+
+typedef struct GpuAllocRequest {
+    uint64_t size;
+    uint64_t align_mask;
+    /* Bits of `GPU_ALLOC_USAGE_*`. */
+    uint32_t usage;
+    uint32_t memory_types;
+    /* One of `GPU_ALLOC_DEDICATED_*`. */
+    uint32_t dedicated;
+    /* One of `GPU_ALLOC_RESOURCE_*`. */
+    uint32_t kind;
+} GpuAllocRequest;
+
+typedef struct GpuAllocBlockInfo {
+    uint64_t memory;
+    uint64_t offset;
+    uint64_t size;
+    uint32_t memory_type;
+    uint32_t props;
+} GpuAllocBlockInfo;
+
+typedef struct GpuAllocHeapStats {
+    uint64_t size;
+    uint64_t used;
+    uint64_t peak_used;
+    uint64_t blocks;
+    uint64_t memory_objects;
+} GpuAllocHeapStats;
+
+void gpu_alloc_config_prototyping(GpuAllocConfig *config);
+void gpu_alloc_config_potato(GpuAllocConfig *config);
+
+/* Returns NULL if any argument is NULL or any callback is missing. */
+GpuAllocAllocator *gpu_alloc_create(const GpuAllocDeviceProperties *props,
+                                    const GpuAllocConfig *config,
+                                    const GpuAllocDeviceCallbacks *callbacks);
+
+/* All blocks must be deallocated before. */
+void gpu_alloc_destroy(GpuAllocAllocator *allocator);
+void gpu_alloc_cleanup(GpuAllocAllocator *allocator);
+
+GpuAllocResult gpu_alloc_alloc(GpuAllocAllocator *allocator, const GpuAllocRequest *request,
+                               GpuAllocBlock **block);
+void gpu_alloc_dealloc(GpuAllocAllocator *allocator, GpuAllocBlock *block);
+void gpu_alloc_block_info(const GpuAllocBlock *block, GpuAllocBlockInfo *info);
+
+GpuAllocResult gpu_alloc_map(GpuAllocAllocator *allocator, GpuAllocBlock *block, uint64_t offset,
+                             size_t size, void **ptr);
+bool gpu_alloc_unmap(GpuAllocAllocator *allocator, GpuAllocBlock *block);
+GpuAllocResult gpu_alloc_write_bytes(GpuAllocAllocator *allocator, GpuAllocBlock *block,
+                                     uint64_t offset, const void *data, size_t size);
+GpuAllocResult gpu_alloc_read_bytes(GpuAllocAllocator *allocator, GpuAllocBlock *block,
+                                    uint64_t offset, void *data, size_t size);
+
+uint32_t gpu_alloc_heap_count(const GpuAllocAllocator *allocator);
+GpuAllocResult gpu_alloc_heap_stats(const GpuAllocAllocator *allocator, uint32_t heap,
+                                    GpuAllocHeapStats *stats);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GPU_ALLOC_H */
@@ -0,0 +1,681 @@
+//! C API for [`gpu-alloc`].
+//!
+//! Exposes allocator and memory blocks as opaque handles,
+//! so that engines written in C or C++ can use the allocator.
+//! Device is provided as table of callbacks, memory objects are 64-bit handles,
+//! such as `VkDeviceMemory`.
+//!
+//! Declarations are in `include/gpu_alloc.h`.
+//!
+//! [`gpu-alloc`]: gpu_alloc
+
+#![allow(clippy::missing_safety_doc)]
+
+use {
+    gpu_alloc::{
+        AllocationError, AllocationFlags, Config, Dedicated, DeviceMapError, DeviceProperties,
+        GpuAllocator, MapError, MappedMemoryRange, MemoryBlock, MemoryDevice, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, OutOfMemory, Request, ResourceKind, UsageFlags,
+    },
+    std::{borrow::Cow, ffi::c_void, ptr::NonNull, slice},
+};
+
+/// Result code returned by functions and device callbacks.
+/// Zero is success, negative values are errors.
+pub type GpuAllocResult = i32;
+
+pub const GPU_ALLOC_SUCCESS: GpuAllocResult = 0;
+pub const GPU_ALLOC_ERROR_OUT_OF_DEVICE_MEMORY: GpuAllocResult = -1;
+pub const GPU_ALLOC_ERROR_OUT_OF_HOST_MEMORY: GpuAllocResult = -2;
+pub const GPU_ALLOC_ERROR_MAP_FAILED: GpuAllocResult = -3;
+pub const GPU_ALLOC_ERROR_NO_COMPATIBLE_MEMORY_TYPES: GpuAllocResult = -4;
+pub const GPU_ALLOC_ERROR_TOO_MANY_OBJECTS: GpuAllocResult = -5;
+pub const GPU_ALLOC_ERROR_OUT_OF_BUDGET: GpuAllocResult = -6;
+pub const GPU_ALLOC_ERROR_INVALID_ALIGNMENT: GpuAllocResult = -7;
+pub const GPU_ALLOC_ERROR_SIZE_OVERFLOW: GpuAllocResult = -8;
+pub const GPU_ALLOC_ERROR_NON_HOST_VISIBLE: GpuAllocResult = -9;
+pub const GPU_ALLOC_ERROR_ALREADY_MAPPED: GpuAllocResult = -10;
+pub const GPU_ALLOC_ERROR_OUT_OF_BOUNDS: GpuAllocResult = -11;
+pub const GPU_ALLOC_ERROR_UNADDRESSABLE: GpuAllocResult = -12;
+pub const GPU_ALLOC_ERROR_INVALID_ARGUMENT: GpuAllocResult = -13;
+
+/// Values of [`GpuAllocRequest::dedicated`].
+pub const GPU_ALLOC_DEDICATED_NONE: u32 = 0;
+pub const GPU_ALLOC_DEDICATED_REQUIRED: u32 = 1;
+pub const GPU_ALLOC_DEDICATED_PREFERRED: u32 = 2;
+
+/// Values of [`GpuAllocRequest::kind`].
+pub const GPU_ALLOC_RESOURCE_LINEAR: u32 = 0;
+pub const GPU_ALLOC_RESOURCE_NON_LINEAR: u32 = 1;
+
+fn allocation_result(err: AllocationError) -> GpuAllocResult {
+    match err {
+        AllocationError::OutOfDeviceMemory => GPU_ALLOC_ERROR_OUT_OF_DEVICE_MEMORY,
+        AllocationError::OutOfHostMemory => GPU_ALLOC_ERROR_OUT_OF_HOST_MEMORY,
+        AllocationError::NoCompatibleMemoryTypes => GPU_ALLOC_ERROR_NO_COMPATIBLE_MEMORY_TYPES,
+        AllocationError::TooManyObjects => GPU_ALLOC_ERROR_TOO_MANY_OBJECTS,
+        AllocationError::OutOfBudget => GPU_ALLOC_ERROR_OUT_OF_BUDGET,
+        AllocationError::InvalidAlignment => GPU_ALLOC_ERROR_INVALID_ALIGNMENT,
+        AllocationError::SizeOverflow => GPU_ALLOC_ERROR_SIZE_OVERFLOW,
+    }
+}
+
+fn map_result(err: MapError) -> GpuAllocResult {
+    match err {
+        MapError::OutOfDeviceMemory => GPU_ALLOC_ERROR_OUT_OF_DEVICE_MEMORY,
+        MapError::OutOfHostMemory => GPU_ALLOC_ERROR_OUT_OF_HOST_MEMORY,
+        MapError::NonHostVisible => GPU_ALLOC_ERROR_NON_HOST_VISIBLE,
+        MapError::MapFailed => GPU_ALLOC_ERROR_MAP_FAILED,
+        MapError::AlreadyMapped => GPU_ALLOC_ERROR_ALREADY_MAPPED,
+        MapError::OutOfBounds => GPU_ALLOC_ERROR_OUT_OF_BOUNDS,
+        MapError::Unaddressable => GPU_ALLOC_ERROR_UNADDRESSABLE,
+    }
+}
+
+fn out_of_memory(result: GpuAllocResult) -> OutOfMemory {
+    match result {
+        GPU_ALLOC_ERROR_OUT_OF_DEVICE_MEMORY => OutOfMemory::OutOfDeviceMemory,
+        _ => OutOfMemory::OutOfHostMemory,
+    }
+}
+
+/// Memory type of the device.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GpuAllocMemoryType {
+    /// Memory property flags with the same bits as `VkMemoryPropertyFlags`.
+    pub props: u32,
+
+    /// Index of memory heap.
+    pub heap: u32,
+}
+
+/// Memory heap of the device.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GpuAllocMemoryHeap {
+    /// Size of the heap.
+    pub size: u64,
+}
+
+/// Properties of the device that allocator is created for.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GpuAllocDeviceProperties {
+    pub memory_types: *const GpuAllocMemoryType,
+    pub memory_type_count: u32,
+    pub memory_heaps: *const GpuAllocMemoryHeap,
+    pub memory_heap_count: u32,
+    pub max_memory_allocation_count: u32,
+    pub max_memory_allocation_size: u64,
+    pub non_coherent_atom_size: u64,
+    pub buffer_image_granularity: u64,
+    pub buffer_device_address: bool,
+}
+
+/// Mirror of allocator [`Config`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GpuAllocConfig {
+    pub dedicated_threshold: u64,
+    pub preferred_dedicated_threshold: u64,
+    pub transient_dedicated_threshold: u64,
+    pub starting_free_list_chunk: u64,
+    pub final_free_list_chunk: u64,
+    pub minimal_buddy_size: u64,
+    pub initial_buddy_dedicated_size: u64,
+    pub buddy_chunk_keep_alive: u32,
+    pub force_dedicated: bool,
+    pub bar_heap_size: u64,
+    pub bar_usage_limit: u8,
+}
+
+impl From<Config> for GpuAllocConfig {
+    fn from(config: Config) -> Self {
+        GpuAllocConfig {
+            dedicated_threshold: config.dedicated_threshold,
+            preferred_dedicated_threshold: config.preferred_dedicated_threshold,
+            transient_dedicated_threshold: config.transient_dedicated_threshold,
+            starting_free_list_chunk: config.starting_free_list_chunk,
+            final_free_list_chunk: config.final_free_list_chunk,
+            minimal_buddy_size: config.minimal_buddy_size,
+            initial_buddy_dedicated_size: config.initial_buddy_dedicated_size,
+            buddy_chunk_keep_alive: config.buddy_chunk_keep_alive,
+            force_dedicated: config.force_dedicated,
+            bar_heap_size: config.bar_heap_size,
+            bar_usage_limit: config.bar_usage_limit,
+        }
+    }
+}
+
+impl From<GpuAllocConfig> for Config {
+    fn from(config: GpuAllocConfig) -> Self {
+        Config {
+            dedicated_threshold: config.dedicated_threshold,
+            preferred_dedicated_threshold: config.preferred_dedicated_threshold,
+            transient_dedicated_threshold: config.transient_dedicated_threshold,
+            starting_free_list_chunk: config.starting_free_list_chunk,
+            final_free_list_chunk: config.final_free_list_chunk,
+            minimal_buddy_size: config.minimal_buddy_size,
+            initial_buddy_dedicated_size: config.initial_buddy_dedicated_size,
+            buddy_chunk_keep_alive: config.buddy_chunk_keep_alive,
+            force_dedicated: config.force_dedicated,
+            bar_heap_size: config.bar_heap_size,
+            bar_usage_limit: config.bar_usage_limit,
+        }
+    }
+}
+
+/// Range of mapped memory object passed to flush and invalidate callbacks.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GpuAllocMappedRange {
+    pub memory: u64,
+    pub offset: u64,
+    pub size: u64,
+}
+
+/// Device callbacks. Each receives `user_data` as first argument.
+///
+/// Callbacks follow requirements of the same functions of [`MemoryDevice`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GpuAllocDeviceCallbacks {
+    pub user_data: *mut c_void,
+    pub allocate_memory: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            size: u64,
+            memory_type: u32,
+            flags: u32,
+            memory: *mut u64,
+        ) -> GpuAllocResult,
+    >,
+    pub deallocate_memory: Option<unsafe extern "C" fn(user_data: *mut c_void, memory: u64)>,
+    pub map_memory: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            memory: u64,
+            offset: u64,
+            size: u64,
+            ptr: *mut *mut c_void,
+        ) -> GpuAllocResult,
+    >,
+    pub unmap_memory: Option<unsafe extern "C" fn(user_data: *mut c_void, memory: u64)>,
+    pub invalidate_memory_ranges: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            ranges: *const GpuAllocMappedRange,
+            count: u32,
+        ) -> GpuAllocResult,
+    >,
+    pub flush_memory_ranges: Option<
+        unsafe extern "C" fn(
+            user_data: *mut c_void,
+            ranges: *const GpuAllocMappedRange,
+            count: u32,
+        ) -> GpuAllocResult,
+    >,
+}
+
+impl GpuAllocDeviceCallbacks {
+    fn is_complete(&self) -> bool {
+        self.allocate_memory.is_some()
+            && self.deallocate_memory.is_some()
+            && self.map_memory.is_some()
+            && self.unmap_memory.is_some()
+            && self.invalidate_memory_ranges.is_some()
+            && self.flush_memory_ranges.is_some()
+    }
+}
+
+/// Device implemented with callbacks.
+/// All callbacks are checked to be present on allocator creation.
+struct CallbackDevice {
+    callbacks: GpuAllocDeviceCallbacks,
+}
+
+impl CallbackDevice {
+    unsafe fn ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, u64>],
+        f: unsafe extern "C" fn(*mut c_void, *const GpuAllocMappedRange, u32) -> GpuAllocResult,
+    ) -> Result<(), OutOfMemory> {
+        let ranges: Vec<GpuAllocMappedRange> = ranges
+            .iter()
+            .map(|range| GpuAllocMappedRange {
+                memory: *range.memory,
+                offset: range.offset,
+                size: range.size,
+            })
+            .collect();
+
+        match f(
+            self.callbacks.user_data,
+            ranges.as_ptr(),
+            ranges.len() as u32,
+        ) {
+            GPU_ALLOC_SUCCESS => Ok(()),
+            result => Err(out_of_memory(result)),
+        }
+    }
+}
+
+impl AsRef<CallbackDevice> for CallbackDevice {
+    fn as_ref(&self) -> &CallbackDevice {
+        self
+    }
+}
+
+impl MemoryDevice<u64> for CallbackDevice {
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<u64, OutOfMemory> {
+        let f = self.callbacks.allocate_memory.unwrap();
+        let mut memory = 0;
+        match f(
+            self.callbacks.user_data,
+            size,
+            memory_type,
+            flags.bits() as u32,
+            &mut memory,
+        ) {
+            GPU_ALLOC_SUCCESS => Ok(memory),
+            result => Err(out_of_memory(result)),
+        }
+    }
+
+    unsafe fn deallocate_memory(&self, memory: u64) {
+        let f = self.callbacks.deallocate_memory.unwrap();
+        f(self.callbacks.user_data, memory)
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut u64,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        let f = self.callbacks.map_memory.unwrap();
+        let mut ptr = std::ptr::null_mut();
+        match f(self.callbacks.user_data, *memory, offset, size, &mut ptr) {
+            GPU_ALLOC_SUCCESS => NonNull::new(ptr as *mut u8).ok_or(DeviceMapError::MapFailed),
+            GPU_ALLOC_ERROR_OUT_OF_DEVICE_MEMORY => Err(DeviceMapError::OutOfDeviceMemory),
+            GPU_ALLOC_ERROR_OUT_OF_HOST_MEMORY => Err(DeviceMapError::OutOfHostMemory),
+            _ => Err(DeviceMapError::MapFailed),
+        }
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut u64) {
+        let f = self.callbacks.unmap_memory.unwrap();
+        f(self.callbacks.user_data, *memory)
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, u64>],
+    ) -> Result<(), OutOfMemory> {
+        self.ranges(ranges, self.callbacks.invalidate_memory_ranges.unwrap())
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, u64>],
+    ) -> Result<(), OutOfMemory> {
+        self.ranges(ranges, self.callbacks.flush_memory_ranges.unwrap())
+    }
+}
+
+/// Opaque allocator handle that owns the device callbacks.
+pub struct GpuAllocAllocator {
+    allocator: GpuAllocator<u64>,
+    device: CallbackDevice,
+}
+
+/// Opaque memory block handle.
+pub struct GpuAllocBlock {
+    block: MemoryBlock<u64>,
+}
+
+/// Memory allocation request.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GpuAllocRequest {
+    pub size: u64,
+    pub align_mask: u64,
+    /// Bits of `UsageFlags`.
+    pub usage: u32,
+    pub memory_types: u32,
+    /// One of `GPU_ALLOC_DEDICATED_*` values.
+    pub dedicated: u32,
+    /// One of `GPU_ALLOC_RESOURCE_*` values.
+    pub kind: u32,
+}
+
+/// Description of memory block.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GpuAllocBlockInfo {
+    pub memory: u64,
+    pub offset: u64,
+    pub size: u64,
+    pub memory_type: u32,
+    pub props: u32,
+}
+
+/// Statistics of memory heap.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct GpuAllocHeapStats {
+    pub size: u64,
+    pub used: u64,
+    pub peak_used: u64,
+    pub blocks: u64,
+    pub memory_objects: u64,
+}
+
+/// Writes configuration suitable for prototyping to `config`.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_config_prototyping(config: *mut GpuAllocConfig) {
+    *config = Config::i_am_prototyping().into();
+}
+
+/// Writes configuration for devices with little memory to `config`.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_config_potato(config: *mut GpuAllocConfig) {
+    *config = Config::i_am_potato().into();
+}
+
+/// Creates allocator for the device.
+///
+/// Returns null if any argument is null or any callback is missing.
+/// Arrays in `props` are copied.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_create(
+    props: *const GpuAllocDeviceProperties,
+    config: *const GpuAllocConfig,
+    callbacks: *const GpuAllocDeviceCallbacks,
+) -> *mut GpuAllocAllocator {
+    if props.is_null() || config.is_null() || callbacks.is_null() || !(*callbacks).is_complete() {
+        return std::ptr::null_mut();
+    }
+
+    let props = &*props;
+    if (props.memory_type_count > 0 && props.memory_types.is_null())
+        || (props.memory_heap_count > 0 && props.memory_heaps.is_null())
+    {
+        return std::ptr::null_mut();
+    }
+
+    let memory_types = slice_or_empty(props.memory_types, props.memory_type_count as usize)
+        .iter()
+        .map(|memory_type| MemoryType {
+            heap: memory_type.heap,
+            props: MemoryPropertyFlags::from_bits_truncate(memory_type.props as u8),
+        })
+        .collect::<Vec<_>>();
+
+    let memory_heaps = slice_or_empty(props.memory_heaps, props.memory_heap_count as usize)
+        .iter()
+        .map(|heap| MemoryHeap {
+            size: heap.size,
+            budget: None,
+            usage: None,
+        })
+        .collect::<Vec<_>>();
+
+    let allocator = GpuAllocator::new(
+        (*config).into(),
+        DeviceProperties {
+            memory_types: Cow::Owned(memory_types),
+            memory_heaps: Cow::Owned(memory_heaps),
+            max_memory_allocation_count: props.max_memory_allocation_count,
+            max_memory_allocation_size: props.max_memory_allocation_size,
+            non_coherent_atom_size: props.non_coherent_atom_size,
+            buffer_image_granularity: props.buffer_image_granularity,
+            buffer_device_address: props.buffer_device_address,
+        },
+    );
+
+    Box::into_raw(Box::new(GpuAllocAllocator {
+        allocator,
+        device: CallbackDevice {
+            callbacks: *callbacks,
+        },
+    }))
+}
+
+unsafe fn slice_or_empty<'a, T>(ptr: *const T, len: usize) -> &'a [T] {
+    if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(ptr, len)
+    }
+}
+
+/// Deallocates leftover memory objects and destroys allocator.
+///
+/// All blocks must be deallocated before.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_destroy(allocator: *mut GpuAllocAllocator) {
+    if allocator.is_null() {
+        return;
+    }
+    let mut allocator = Box::from_raw(allocator);
+    let GpuAllocAllocator { allocator, device } = &mut *allocator;
+    allocator.cleanup(device);
+}
+
+/// Allocates memory block.
+///
+/// On success writes new block handle to `block`.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_alloc(
+    allocator: *mut GpuAllocAllocator,
+    request: *const GpuAllocRequest,
+    block: *mut *mut GpuAllocBlock,
+) -> GpuAllocResult {
+    if allocator.is_null() || request.is_null() || block.is_null() {
+        return GPU_ALLOC_ERROR_INVALID_ARGUMENT;
+    }
+
+    let GpuAllocAllocator { allocator, device } = &mut *allocator;
+    let request = &*request;
+    let dedicated = match request.dedicated {
+        GPU_ALLOC_DEDICATED_NONE => None,
+        GPU_ALLOC_DEDICATED_REQUIRED => Some(Dedicated::Required),
+        GPU_ALLOC_DEDICATED_PREFERRED => Some(Dedicated::Preferred),
+        _ => return GPU_ALLOC_ERROR_INVALID_ARGUMENT,
+    };
+    let kind = match request.kind {
+        GPU_ALLOC_RESOURCE_LINEAR => ResourceKind::Linear,
+        GPU_ALLOC_RESOURCE_NON_LINEAR => ResourceKind::NonLinear,
+        _ => return GPU_ALLOC_ERROR_INVALID_ARGUMENT,
+    };
+    let request = Request {
+        size: request.size,
+        align_mask: request.align_mask,
+        usage: UsageFlags::from_bits_truncate(request.usage as u8),
+        memory_types: request.memory_types,
+        kind,
+    };
+
+    let result = match dedicated {
+        None => allocator.alloc(device, request),
+        Some(dedicated) => allocator.alloc_with_dedicated(device, request, dedicated),
+    };
+
+    match result {
+        Ok(new_block) => {
+            *block = Box::into_raw(Box::new(GpuAllocBlock { block: new_block }));
+            GPU_ALLOC_SUCCESS
+        }
+        Err(err) => allocation_result(err),
+    }
+}
+
+/// Deallocates memory block. Block handle becomes invalid.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_dealloc(
+    allocator: *mut GpuAllocAllocator,
+    block: *mut GpuAllocBlock,
+) {
+    if allocator.is_null() || block.is_null() {
+        return;
+    }
+    let GpuAllocAllocator { allocator, device } = &mut *allocator;
+    let block = Box::from_raw(block);
+    allocator.dealloc(device, block.block);
+}
+
+/// Writes description of memory block to `info`.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_block_info(
+    block: *const GpuAllocBlock,
+    info: *mut GpuAllocBlockInfo,
+) {
+    if block.is_null() || info.is_null() {
+        return;
+    }
+    let block = &(*block).block;
+    *info = GpuAllocBlockInfo {
+        memory: *block.memory(),
+        offset: block.offset(),
+        size: block.size(),
+        memory_type: block.memory_type(),
+        props: block.props().bits() as u32,
+    };
+}
+
+/// Maps range of memory block and writes pointer to `ptr`.
+/// Same as `MemoryBlock::map`.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_map(
+    allocator: *mut GpuAllocAllocator,
+    block: *mut GpuAllocBlock,
+    offset: u64,
+    size: usize,
+    ptr: *mut *mut c_void,
+) -> GpuAllocResult {
+    if allocator.is_null() || block.is_null() || ptr.is_null() {
+        return GPU_ALLOC_ERROR_INVALID_ARGUMENT;
+    }
+    match (*block).block.map(&(*allocator).device, offset, size) {
+        Ok(mapped) => {
+            *ptr = mapped.as_ptr() as *mut c_void;
+            GPU_ALLOC_SUCCESS
+        }
+        Err(err) => map_result(err),
+    }
+}
+
+/// Unmaps memory block. Returns `false` if block was not mapped.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_unmap(
+    allocator: *mut GpuAllocAllocator,
+    block: *mut GpuAllocBlock,
+) -> bool {
+    if allocator.is_null() || block.is_null() {
+        return false;
+    }
+    (*block).block.unmap(&(*allocator).device)
+}
+
+/// Copies `size` bytes from `data` to memory block at `offset`.
+/// Same as `MemoryBlock::write_bytes`.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_write_bytes(
+    allocator: *mut GpuAllocAllocator,
+    block: *mut GpuAllocBlock,
+    offset: u64,
+    data: *const c_void,
+    size: usize,
+) -> GpuAllocResult {
+    if allocator.is_null() || block.is_null() || (data.is_null() && size > 0) {
+        return GPU_ALLOC_ERROR_INVALID_ARGUMENT;
+    }
+    let data = slice_or_empty(data as *const u8, size);
+    match (*block)
+        .block
+        .write_bytes(&(*allocator).device, offset, data)
+    {
+        Ok(()) => GPU_ALLOC_SUCCESS,
+        Err(err) => map_result(err),
+    }
+}
+
+/// Copies `size` bytes from memory block at `offset` to `data`.
+/// Same as `MemoryBlock::read_bytes`.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_read_bytes(
+    allocator: *mut GpuAllocAllocator,
+    block: *mut GpuAllocBlock,
+    offset: u64,
+    data: *mut c_void,
+    size: usize,
+) -> GpuAllocResult {
+    if allocator.is_null() || block.is_null() || (data.is_null() && size > 0) {
+        return GPU_ALLOC_ERROR_INVALID_ARGUMENT;
+    }
+    let data: &mut [u8] = if size == 0 {
+        &mut []
+    } else {
+        slice::from_raw_parts_mut(data as *mut u8, size)
+    };
+    match (*block)
+        .block
+        .read_bytes(&(*allocator).device, offset, data)
+    {
+        Ok(()) => GPU_ALLOC_SUCCESS,
+        Err(err) => map_result(err),
+    }
+}
+
+/// Returns number of memory heaps.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_heap_count(allocator: *const GpuAllocAllocator) -> u32 {
+    if allocator.is_null() {
+        return 0;
+    }
+    (*allocator).allocator.stats().heaps.len() as u32
+}
+
+/// Writes statistics of memory heap to `stats`.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_heap_stats(
+    allocator: *const GpuAllocAllocator,
+    heap: u32,
+    stats: *mut GpuAllocHeapStats,
+) -> GpuAllocResult {
+    if allocator.is_null() || stats.is_null() {
+        return GPU_ALLOC_ERROR_INVALID_ARGUMENT;
+    }
+    let all = (*allocator).allocator.stats();
+    let heap = match all.heaps.get(heap as usize) {
+        Some(heap) => heap,
+        None => return GPU_ALLOC_ERROR_INVALID_ARGUMENT,
+    };
+    let total = heap.total();
+    *stats = GpuAllocHeapStats {
+        size: heap.size,
+        used: heap.used,
+        peak_used: heap.peak_used,
+        blocks: total.blocks,
+        memory_objects: total.memory_objects,
+    };
+    GPU_ALLOC_SUCCESS
+}
+
+/// Deallocates memory objects that are not used by any block.
+/// Same as `GpuAllocator::cleanup`.
+#[no_mangle]
+pub unsafe extern "C" fn gpu_alloc_cleanup(allocator: *mut GpuAllocAllocator) {
+    if allocator.is_null() {
+        return;
+    }
+    let GpuAllocAllocator { allocator, device } = &mut *allocator;
+    allocator.cleanup(device);
+}
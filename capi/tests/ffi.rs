@@ -0,0 +1,235 @@
+use {
+    gpu_alloc_capi::*,
+    std::{cell::RefCell, collections::HashMap, ffi::c_void, ptr},
+};
+
+/// Memory objects backed by host memory.
+#[derive(Default)]
+struct HostMemory {
+    next: u64,
+    objects: HashMap<u64, Vec<u8>>,
+}
+
+unsafe fn host<'a>(user_data: *mut c_void) -> &'a RefCell<HostMemory> {
+    &*(user_data as *const RefCell<HostMemory>)
+}
+
+unsafe extern "C" fn allocate_memory(
+    user_data: *mut c_void,
+    size: u64,
+    _memory_type: u32,
+    _flags: u32,
+    memory: *mut u64,
+) -> GpuAllocResult {
+    let mut host = host(user_data).borrow_mut();
+    host.next += 1;
+    let handle = host.next;
+    host.objects.insert(handle, vec![0; size as usize]);
+    *memory = handle;
+    GPU_ALLOC_SUCCESS
+}
+
+unsafe extern "C" fn deallocate_memory(user_data: *mut c_void, memory: u64) {
+    host(user_data)
+        .borrow_mut()
+        .objects
+        .remove(&memory)
+        .unwrap();
+}
+
+unsafe extern "C" fn map_memory(
+    user_data: *mut c_void,
+    memory: u64,
+    offset: u64,
+    _size: u64,
+    ptr: *mut *mut c_void,
+) -> GpuAllocResult {
+    let mut host = host(user_data).borrow_mut();
+    let object = host.objects.get_mut(&memory).unwrap();
+    *ptr = object.as_mut_ptr().add(offset as usize) as *mut c_void;
+    GPU_ALLOC_SUCCESS
+}
+
+unsafe extern "C" fn unmap_memory(_user_data: *mut c_void, _memory: u64) {}
+
+unsafe extern "C" fn ranges(
+    _user_data: *mut c_void,
+    _ranges: *const GpuAllocMappedRange,
+    _count: u32,
+) -> GpuAllocResult {
+    GPU_ALLOC_SUCCESS
+}
+
+fn callbacks(host: &RefCell<HostMemory>) -> GpuAllocDeviceCallbacks {
+    GpuAllocDeviceCallbacks {
+        user_data: host as *const RefCell<HostMemory> as *mut c_void,
+        allocate_memory: Some(allocate_memory),
+        deallocate_memory: Some(deallocate_memory),
+        map_memory: Some(map_memory),
+        unmap_memory: Some(unmap_memory),
+        invalidate_memory_ranges: Some(ranges),
+        flush_memory_ranges: Some(ranges),
+    }
+}
+
+const MEMORY_TYPES: [GpuAllocMemoryType; 2] = [
+    // DEVICE_LOCAL
+    GpuAllocMemoryType {
+        props: 0x1,
+        heap: 0,
+    },
+    // HOST_VISIBLE | HOST_COHERENT
+    GpuAllocMemoryType {
+        props: 0x6,
+        heap: 1,
+    },
+];
+
+const MEMORY_HEAPS: [GpuAllocMemoryHeap; 2] = [
+    GpuAllocMemoryHeap { size: 64 << 20 },
+    GpuAllocMemoryHeap { size: 16 << 20 },
+];
+
+fn properties() -> GpuAllocDeviceProperties {
+    GpuAllocDeviceProperties {
+        memory_types: MEMORY_TYPES.as_ptr(),
+        memory_type_count: MEMORY_TYPES.len() as u32,
+        memory_heaps: MEMORY_HEAPS.as_ptr(),
+        memory_heap_count: MEMORY_HEAPS.len() as u32,
+        max_memory_allocation_count: 64,
+        max_memory_allocation_size: 16 << 20,
+        non_coherent_atom_size: 64,
+        buffer_image_granularity: 1,
+        buffer_device_address: false,
+    }
+}
+
+unsafe fn create(host: &RefCell<HostMemory>) -> *mut GpuAllocAllocator {
+    let mut config = std::mem::zeroed();
+    gpu_alloc_config_potato(&mut config);
+    let allocator = gpu_alloc_create(&properties(), &config, &callbacks(host));
+    assert!(!allocator.is_null());
+    allocator
+}
+
+#[test]
+fn write_and_read_through_c_api() {
+    let host = RefCell::new(HostMemory::default());
+
+    unsafe {
+        let allocator = create(&host);
+        assert_eq!(gpu_alloc_heap_count(allocator), 2);
+
+        let request = GpuAllocRequest {
+            size: 1024,
+            align_mask: 0,
+            usage: 0x02, // HOST_ACCESS
+            memory_types: 0b11,
+            dedicated: GPU_ALLOC_DEDICATED_NONE,
+            kind: GPU_ALLOC_RESOURCE_LINEAR,
+        };
+        let mut block = ptr::null_mut();
+        assert_eq!(
+            gpu_alloc_alloc(allocator, &request, &mut block),
+            GPU_ALLOC_SUCCESS
+        );
+
+        let mut info = std::mem::zeroed();
+        gpu_alloc_block_info(block, &mut info);
+        assert_eq!(info.memory_type, 1);
+        assert_eq!(info.size, 1024);
+
+        let data = [1u8, 2, 3, 4];
+        assert_eq!(
+            gpu_alloc_write_bytes(allocator, block, 16, data.as_ptr() as *const c_void, 4),
+            GPU_ALLOC_SUCCESS
+        );
+        let mut read = [0u8; 4];
+        assert_eq!(
+            gpu_alloc_read_bytes(allocator, block, 16, read.as_mut_ptr() as *mut c_void, 4),
+            GPU_ALLOC_SUCCESS
+        );
+        assert_eq!(read, data);
+
+        let start = (info.offset + 16) as usize;
+        assert_eq!(host.borrow().objects[&info.memory][start..start + 4], data);
+
+        let mut stats = std::mem::zeroed();
+        assert_eq!(
+            gpu_alloc_heap_stats(allocator, 1, &mut stats),
+            GPU_ALLOC_SUCCESS
+        );
+        assert_eq!(stats.blocks, 1);
+        assert_eq!(stats.memory_objects, 1);
+        assert_eq!(
+            gpu_alloc_heap_stats(allocator, 2, &mut stats),
+            GPU_ALLOC_ERROR_INVALID_ARGUMENT
+        );
+
+        gpu_alloc_dealloc(allocator, block);
+        gpu_alloc_destroy(allocator);
+    }
+
+    assert!(host.borrow().objects.is_empty());
+}
+
+#[test]
+fn errors_are_reported_as_codes() {
+    let host = RefCell::new(HostMemory::default());
+
+    unsafe {
+        let allocator = create(&host);
+
+        let mut request = GpuAllocRequest {
+            size: 1024,
+            align_mask: 0,
+            usage: 0,
+            memory_types: 0,
+            dedicated: GPU_ALLOC_DEDICATED_NONE,
+            kind: GPU_ALLOC_RESOURCE_LINEAR,
+        };
+        let mut block = ptr::null_mut();
+        assert_eq!(
+            gpu_alloc_alloc(allocator, &request, &mut block),
+            GPU_ALLOC_ERROR_NO_COMPATIBLE_MEMORY_TYPES
+        );
+
+        request.memory_types = 0b01;
+        request.dedicated = 3;
+        assert_eq!(
+            gpu_alloc_alloc(allocator, &request, &mut block),
+            GPU_ALLOC_ERROR_INVALID_ARGUMENT
+        );
+
+        request.dedicated = GPU_ALLOC_DEDICATED_NONE;
+        assert_eq!(
+            gpu_alloc_alloc(allocator, &request, &mut block),
+            GPU_ALLOC_SUCCESS
+        );
+
+        let mut mapped = ptr::null_mut();
+        assert_eq!(
+            gpu_alloc_map(allocator, block, 0, 16, &mut mapped),
+            GPU_ALLOC_ERROR_NON_HOST_VISIBLE
+        );
+        assert!(!gpu_alloc_unmap(allocator, block));
+
+        gpu_alloc_dealloc(allocator, block);
+        gpu_alloc_destroy(allocator);
+    }
+
+    assert!(host.borrow().objects.is_empty());
+}
+
+#[test]
+fn incomplete_callbacks_are_rejected() {
+    let host = RefCell::new(HostMemory::default());
+    let mut callbacks = callbacks(&host);
+    callbacks.flush_memory_ranges = None;
+
+    unsafe {
+        let mut config = std::mem::zeroed();
+        gpu_alloc_config_prototyping(&mut config);
+        assert!(gpu_alloc_create(&properties(), &config, &callbacks).is_null());
+    }
+}
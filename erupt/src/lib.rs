@@ -6,7 +6,7 @@
 //! ```ignore
 //! use {
 //!     erupt::{vk1_0, DeviceLoader, EntryLoader, InstanceLoader},
-//!     gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
+//!     gpu_alloc::{Config, GpuAllocator, Request, ResourceKind, UsageFlags},
 //!     gpu_alloc_erupt::{device_properties, EruptMemoryDevice},
 //!     std::ffi::CStr,
 //! };
@@ -64,6 +64,7 @@
 //!                 align_mask: 1,
 //!                 usage: UsageFlags::HOST_ACCESS,
 //!                 memory_types: !0,
+//!                 kind: ResourceKind::Linear,
 //!             },
 //!         )
 //!     }?;
@@ -88,6 +89,7 @@
 //!                 align_mask: 1,
 //!                 usage: UsageFlags::HOST_ACCESS,
 //!                 memory_types: !0,
+//!                 kind: ResourceKind::Linear,
 //!             },
 //!         )
 //!     }?;
@@ -111,8 +113,8 @@ use std::ptr::NonNull;
 
 use erupt::{vk::MemoryMapFlags, vk1_0, vk1_1, DeviceLoader, ExtendableFrom, InstanceLoader};
 use gpu_alloc_types::{
-    AllocationFlags, DeviceMapError, DeviceProperties, MappedMemoryRange, MemoryDevice, MemoryHeap,
-    MemoryPropertyFlags, MemoryType, OutOfMemory,
+    AllocationFlags, DedicatedAllocationInfo, DeviceMapError, DeviceProperties, MappedMemoryRange,
+    MemoryDevice, MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory,
 };
 use tinyvec::TinyVec;
 
@@ -129,29 +131,15 @@ impl EruptMemoryDevice {
             &*(device as *const DeviceLoader as *const Self)
         }
     }
-}
-
-impl AsRef<EruptMemoryDevice> for DeviceLoader {
-    #[inline(always)]
-    fn as_ref(&self) -> &EruptMemoryDevice {
-        EruptMemoryDevice::wrap(self)
-    }
-}
 
-impl AsRef<EruptMemoryDevice> for EruptMemoryDevice {
-    #[inline(always)]
-    fn as_ref(&self) -> &EruptMemoryDevice {
-        self
-    }
-}
-
-impl MemoryDevice<vk1_0::DeviceMemory> for EruptMemoryDevice {
-    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
-    unsafe fn allocate_memory(
+    /// Allocates memory object, dedicated to the resource if `dedicated` is specified.
+    /// Dedicated allocation requires Vulkan 1.1 or "VK_KHR_dedicated_allocation" extension enabled.
+    unsafe fn allocate(
         &self,
         size: u64,
         memory_type: u32,
         flags: AllocationFlags,
+        dedicated: Option<DedicatedAllocationInfo>,
     ) -> Result<vk1_0::DeviceMemory, OutOfMemory> {
         assert!((flags & !(AllocationFlags::DEVICE_ADDRESS)).is_empty());
 
@@ -167,6 +155,20 @@ impl MemoryDevice<vk1_0::DeviceMemory> for EruptMemoryDevice {
             info = info.extend_from(&mut info_flags);
         }
 
+        let mut info_dedicated;
+
+        if let Some(dedicated) = dedicated {
+            info_dedicated = match dedicated {
+                DedicatedAllocationInfo::Buffer(buffer) => {
+                    vk1_1::MemoryDedicatedAllocateInfoBuilder::new().buffer(vk1_0::Buffer(buffer))
+                }
+                DedicatedAllocationInfo::Image(image) => {
+                    vk1_1::MemoryDedicatedAllocateInfoBuilder::new().image(vk1_0::Image(image))
+                }
+            };
+            info = info.extend_from(&mut info_dedicated);
+        }
+
         match self.device.allocate_memory(&info, None).result() {
             Ok(memory) => Ok(memory),
             Err(vk1_0::Result::ERROR_OUT_OF_DEVICE_MEMORY) => Err(OutOfMemory::OutOfDeviceMemory),
@@ -175,6 +177,43 @@ impl MemoryDevice<vk1_0::DeviceMemory> for EruptMemoryDevice {
             Err(err) => panic!("Unexpected Vulkan error: `{}`", err),
         }
     }
+}
+
+impl AsRef<EruptMemoryDevice> for DeviceLoader {
+    #[inline(always)]
+    fn as_ref(&self) -> &EruptMemoryDevice {
+        EruptMemoryDevice::wrap(self)
+    }
+}
+
+impl AsRef<EruptMemoryDevice> for EruptMemoryDevice {
+    #[inline(always)]
+    fn as_ref(&self) -> &EruptMemoryDevice {
+        self
+    }
+}
+
+impl MemoryDevice<vk1_0::DeviceMemory> for EruptMemoryDevice {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<vk1_0::DeviceMemory, OutOfMemory> {
+        self.allocate(size, memory_type, flags, None)
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        info: DedicatedAllocationInfo,
+    ) -> Result<vk1_0::DeviceMemory, OutOfMemory> {
+        self.allocate(size, memory_type, flags, Some(info))
+    }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
     unsafe fn deallocate_memory(&self, memory: vk1_0::DeviceMemory) {
@@ -271,14 +310,23 @@ impl MemoryDevice<vk1_0::DeviceMemory> for EruptMemoryDevice {
 /// feature `PhysicalDeviceBufferDeviceAddressFeatures::buffer_derive_address`  must be enabled explicitly on device creation
 /// and extension "VK_KHR_buffer_device_address" for Vulkan prior 1.2.
 /// Otherwise the field must be set to false before passing to `GpuAllocator::new`.
+///
+/// Budget and usage of memory heaps are queried when "VK_EXT_memory_budget" is supported
+/// and instance is created for Vulkan 1.1 or later.
 pub unsafe fn device_properties(
     instance: &InstanceLoader,
     physical_device: vk1_0::PhysicalDevice,
 ) -> Result<DeviceProperties<'static>, vk1_0::Result> {
     use {
         erupt::{
-            extensions::khr_buffer_device_address::KHR_BUFFER_DEVICE_ADDRESS_EXTENSION_NAME,
-            vk1_1::PhysicalDeviceFeatures2, vk1_2::PhysicalDeviceBufferDeviceAddressFeatures,
+            extensions::{
+                ext_memory_budget::{
+                    PhysicalDeviceMemoryBudgetPropertiesEXT, EXT_MEMORY_BUDGET_EXTENSION_NAME,
+                },
+                khr_buffer_device_address::KHR_BUFFER_DEVICE_ADDRESS_EXTENSION_NAME,
+            },
+            vk1_1::{PhysicalDeviceFeatures2, PhysicalDeviceMemoryProperties2},
+            vk1_2::PhysicalDeviceBufferDeviceAddressFeatures,
         },
         std::ffi::CStr,
     };
@@ -326,10 +374,34 @@ pub unsafe fn device_properties(
             false
         };
 
+    let memory_budget = if instance.enabled().vk1_1 {
+        let extensions = instance
+            .enumerate_device_extension_properties(physical_device, None, None)
+            .result()?;
+
+        let memory_budget_available = extensions.iter().any(|ext| {
+            CStr::from_ptr(ext.extension_name.as_ptr())
+                == CStr::from_ptr(EXT_MEMORY_BUDGET_EXTENSION_NAME)
+        });
+
+        if memory_budget_available {
+            let properties = PhysicalDeviceMemoryProperties2::default().into_builder();
+            let mut budget = PhysicalDeviceMemoryBudgetPropertiesEXT::default();
+            let mut properties = properties.extend_from(&mut budget);
+            instance.get_physical_device_memory_properties2(physical_device, &mut properties);
+            Some(budget)
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+
     Ok(DeviceProperties {
         max_memory_allocation_count: limits.max_memory_allocation_count,
-        max_memory_allocation_size: u64::max_value(), // FIXME: Can query this information if instance is v1.1
+        max_memory_allocation_size: u64::MAX, // FIXME: Can query this information if instance is v1.1
         non_coherent_atom_size: limits.non_coherent_atom_size,
+        buffer_image_granularity: limits.buffer_image_granularity,
         memory_types: memory_properties.memory_types
             [..memory_properties.memory_type_count as usize]
             .iter()
@@ -341,8 +413,15 @@ pub unsafe fn device_properties(
         memory_heaps: memory_properties.memory_heaps
             [..memory_properties.memory_heap_count as usize]
             .iter()
-            .map(|&memory_heap| MemoryHeap {
+            .enumerate()
+            .map(|(index, &memory_heap)| MemoryHeap {
                 size: memory_heap.size,
+                budget: memory_budget
+                    .as_ref()
+                    .map(|budget| budget.heap_budget[index]),
+                usage: memory_budget
+                    .as_ref()
+                    .map(|budget| budget.heap_usage[index]),
             })
             .collect(),
         buffer_device_address,
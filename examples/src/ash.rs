@@ -1,7 +1,7 @@
 use {
     ash::{vk, Entry},
-    gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
-    gpu_alloc_ash::{device_properties, AshMemoryDevice},
+    gpu_alloc::{Config, GpuAllocator, Request, ResourceKind, UsageFlags},
+    gpu_alloc_ash::{create_buffer, destroy_buffer, device_properties, AshMemoryDevice},
     std::ffi::CStr,
 };
 
@@ -10,14 +10,13 @@ fn main() -> eyre::Result<()> {
 
     let entry = unsafe { Entry::load() }?;
 
-    let version = entry
-        .try_enumerate_instance_version()?
+    let version = unsafe { entry.try_enumerate_instance_version() }?
         .unwrap_or(vk::make_api_version(0, 1, 0, 0));
 
     let instance = unsafe {
         entry.create_instance(
-            &vk::InstanceCreateInfo::builder().application_info(
-                &vk::ApplicationInfo::builder()
+            &vk::InstanceCreateInfo::default().application_info(
+                &vk::ApplicationInfo::default()
                     .engine_name(CStr::from_bytes_with_nul(b"GpuAlloc\0").unwrap())
                     .engine_version(1)
                     .application_name(CStr::from_bytes_with_nul(b"GpuAllocApp\0").unwrap())
@@ -36,11 +35,10 @@ fn main() -> eyre::Result<()> {
     let device = unsafe {
         instance.create_device(
             physical_device,
-            &vk::DeviceCreateInfo::builder().queue_create_infos(&[
-                vk::DeviceQueueCreateInfo::builder()
+            &vk::DeviceCreateInfo::default().queue_create_infos(&[
+                vk::DeviceQueueCreateInfo::default()
                     .queue_family_index(0)
-                    .queue_priorities(&[0f32])
-                    .build(),
+                    .queue_priorities(&[0f32]),
             ]),
             None,
         )
@@ -58,6 +56,7 @@ fn main() -> eyre::Result<()> {
                 align_mask: 1,
                 usage: UsageFlags::HOST_ACCESS,
                 memory_types: !0,
+                kind: ResourceKind::Linear,
             },
         )
     }?;
@@ -82,6 +81,7 @@ fn main() -> eyre::Result<()> {
                 align_mask: 1,
                 usage: UsageFlags::HOST_ACCESS,
                 memory_types: !0,
+                kind: ResourceKind::Linear,
             },
         )
     }?;
@@ -96,5 +96,22 @@ fn main() -> eyre::Result<()> {
 
     unsafe { allocator.dealloc(&device, block) }
 
+    // Buffer creation, memory allocation and binding in one call.
+    // Requires Vulkan 1.1 to query memory requirements.
+    if version >= vk::API_VERSION_1_1 {
+        let (buffer, block) = unsafe {
+            create_buffer(
+                &device,
+                &mut allocator,
+                &vk::BufferCreateInfo::default()
+                    .size(1024)
+                    .usage(vk::BufferUsageFlags::TRANSFER_SRC),
+                UsageFlags::UPLOAD,
+            )
+        }?;
+
+        unsafe { destroy_buffer(&device, &mut allocator, buffer, block) }
+    }
+
     Ok(())
 }
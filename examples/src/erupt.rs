@@ -1,6 +1,6 @@
 use {
     erupt::{vk1_0, DeviceLoader, EntryLoader, InstanceLoader},
-    gpu_alloc::{Config, GpuAllocator, Request, UsageFlags},
+    gpu_alloc::{Config, GpuAllocator, Request, ResourceKind, UsageFlags},
     gpu_alloc_erupt::{device_properties, EruptMemoryDevice},
     std::ffi::CStr,
 };
@@ -56,6 +56,7 @@ fn main() -> eyre::Result<()> {
                 align_mask: 1,
                 usage: UsageFlags::HOST_ACCESS,
                 memory_types: !0,
+                kind: ResourceKind::Linear,
             },
         )
     }?;
@@ -80,6 +81,7 @@ fn main() -> eyre::Result<()> {
                 align_mask: 1,
                 usage: UsageFlags::HOST_ACCESS,
                 memory_types: !0,
+                kind: ResourceKind::Linear,
             },
         )
     }?;
@@ -1,7 +1,7 @@
 use {
     gpu_alloc::{
         Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
-        Request, UsageFlags,
+        Request, ResourceKind, UsageFlags,
     },
     gpu_alloc_mock::MockMemoryDevice,
     std::borrow::Cow,
@@ -15,10 +15,15 @@ fn main() -> eyre::Result<()> {
             heap: 0,
             props: MemoryPropertyFlags::HOST_VISIBLE,
         }]),
-        memory_heaps: Cow::Borrowed(&[MemoryHeap { size: 1024 * 1024 }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 1024 * 1024,
+            budget: None,
+            usage: None,
+        }]),
         max_memory_allocation_count: 32,
         max_memory_allocation_size: 1024 * 1024,
         non_coherent_atom_size: 8,
+        buffer_image_granularity: 1,
         buffer_device_address: false,
     });
 
@@ -34,6 +39,7 @@ fn main() -> eyre::Result<()> {
                 align_mask: 1,
                 usage: UsageFlags::HOST_ACCESS,
                 memory_types: !0,
+                kind: ResourceKind::Linear,
             },
         )
     }?;
@@ -46,6 +52,7 @@ fn main() -> eyre::Result<()> {
                 align_mask: 1,
                 usage: UsageFlags::HOST_ACCESS,
                 memory_types: !0,
+                kind: ResourceKind::Linear,
             },
         )
     }?;
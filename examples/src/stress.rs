@@ -0,0 +1,396 @@
+//!
+//! Configurable stress test for `GpuAllocator` on mock device.
+//!
+//! Runs synthetic workload and prints fragmentation, peak usage and memory object counts.
+//! Run with `--help` to see available parameters.
+//!
+
+use {
+    eyre::{bail, eyre, WrapErr as _},
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryBlock, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{borrow::Cow, cmp::Reverse, collections::BinaryHeap, sync::Mutex, thread},
+};
+
+const HELP: &str = "\
+Usage: stress [OPTIONS]
+
+Options:
+    --threads <N>         Number of threads sharing the allocator [default: 1]
+    --ticks <N>           Number of ticks each thread runs [default: 10000]
+    --rate <N>            Allocations per tick per thread [default: 4]
+    --size <DIST>         Size distribution in bytes [default: log:256:1048576]
+    --lifetime <DIST>     Lifetime distribution in ticks [default: exp:100]
+    --align <N>           Alignment of requests, power of two [default: 256]
+    --usage <USAGE>       Comma separated usage flags: host, upload, download,
+                          transient, fast [default: fast]
+    --config <CONFIG>     Allocator configuration: potato, prototyping [default: prototyping]
+    --heap <BYTES>        Size of the device heap [default: 4294967296]
+    --max-objects <N>     Memory object count limit [default: 4096]
+    --seed <N>            Random seed [default: 1]
+
+Distributions:
+    fixed:<V>             Always V
+    uniform:<MIN>:<MAX>   Uniform in [MIN, MAX]
+    log:<MIN>:<MAX>       Log-uniform in [MIN, MAX]
+    exp:<MEAN>            Exponential with mean MEAN
+";
+
+#[derive(Clone, Copy, Debug)]
+enum Distribution {
+    Fixed(u64),
+    Uniform(u64, u64),
+    LogUniform(u64, u64),
+    Exponential(u64),
+}
+
+impl Distribution {
+    fn parse(s: &str) -> eyre::Result<Self> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let num = |i: usize| -> eyre::Result<u64> {
+            let part = parts
+                .get(i)
+                .ok_or_else(|| eyre!("Missing parameter in distribution `{}`", s))?;
+            part.parse()
+                .wrap_err_with(|| format!("Invalid number `{}` in distribution `{}`", part, s))
+        };
+
+        let dist = match parts[0] {
+            "fixed" => Distribution::Fixed(num(1)?),
+            "uniform" => Distribution::Uniform(num(1)?, num(2)?),
+            "log" => Distribution::LogUniform(num(1)?.max(1), num(2)?),
+            "exp" => Distribution::Exponential(num(1)?),
+            kind => bail!("Unknown distribution kind `{}`", kind),
+        };
+
+        match dist {
+            Distribution::Uniform(min, max) | Distribution::LogUniform(min, max) if min > max => {
+                bail!("Invalid distribution `{}`: min is greater than max", s)
+            }
+            _ => Ok(dist),
+        }
+    }
+
+    fn sample(&self, rng: &mut XorShift) -> u64 {
+        match *self {
+            Distribution::Fixed(value) => value,
+            Distribution::Uniform(min, max) => min + rng.next() % (max - min + 1),
+            Distribution::LogUniform(min, max) => {
+                let (min, max) = ((min as f64).ln(), (max as f64).ln());
+                (min + rng.unit() * (max - min)).exp() as u64
+            }
+            Distribution::Exponential(mean) => (-(1.0 - rng.unit()).ln() * mean as f64) as u64,
+        }
+    }
+}
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        XorShift(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns value in `[0, 1)`.
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Params {
+    threads: usize,
+    ticks: u64,
+    rate: u64,
+    size: Distribution,
+    lifetime: Distribution,
+    align: u64,
+    usage: UsageFlags,
+    config: Config,
+    heap: u64,
+    max_objects: u32,
+    seed: u64,
+}
+
+impl Params {
+    fn parse() -> eyre::Result<Option<Self>> {
+        let mut params = Params {
+            threads: 1,
+            ticks: 10000,
+            rate: 4,
+            size: Distribution::LogUniform(256, 1024 * 1024),
+            lifetime: Distribution::Exponential(100),
+            align: 256,
+            usage: UsageFlags::FAST_DEVICE_ACCESS,
+            config: Config::i_am_prototyping(),
+            heap: 4 * 1024 * 1024 * 1024,
+            max_objects: 4096,
+            seed: 1,
+        };
+
+        let mut args = std::env::args().skip(1);
+
+        while let Some(arg) = args.next() {
+            if arg == "--help" || arg == "-h" {
+                return Ok(None);
+            }
+
+            let value = args
+                .next()
+                .ok_or_else(|| eyre!("Missing value for `{}`", arg))?;
+
+            let number = || {
+                value
+                    .parse::<u64>()
+                    .wrap_err_with(|| format!("Invalid value `{}` for `{}`", value, arg))
+            };
+
+            match &*arg {
+                "--threads" => params.threads = number()?.max(1) as usize,
+                "--ticks" => params.ticks = number()?,
+                "--rate" => params.rate = number()?,
+                "--size" => params.size = Distribution::parse(&value)?,
+                "--lifetime" => params.lifetime = Distribution::parse(&value)?,
+                "--align" => {
+                    params.align = number()?;
+                    if !params.align.is_power_of_two() {
+                        bail!("Alignment must be power of two");
+                    }
+                }
+                "--usage" => {
+                    params.usage = UsageFlags::empty();
+                    for flag in value.split(',') {
+                        params.usage |= match flag {
+                            "host" => UsageFlags::HOST_ACCESS,
+                            "upload" => UsageFlags::UPLOAD,
+                            "download" => UsageFlags::DOWNLOAD,
+                            "transient" => UsageFlags::TRANSIENT,
+                            "fast" => UsageFlags::FAST_DEVICE_ACCESS,
+                            flag => bail!("Unknown usage flag `{}`", flag),
+                        };
+                    }
+                }
+                "--config" => {
+                    params.config = match &*value {
+                        "potato" => Config::i_am_potato(),
+                        "prototyping" => Config::i_am_prototyping(),
+                        config => bail!("Unknown config `{}`", config),
+                    }
+                }
+                "--heap" => params.heap = number()?,
+                "--max-objects" => params.max_objects = number()? as u32,
+                "--seed" => params.seed = number()?,
+                _ => bail!("Unknown argument `{}`", arg),
+            }
+        }
+
+        Ok(Some(params))
+    }
+}
+
+/// Allocator shared between threads with its device and statistics.
+struct Shared {
+    device: MockMemoryDevice,
+    allocator: GpuAllocator<usize>,
+    live_requested: u64,
+    peak_requested: u64,
+    peak_usage: u64,
+    peak_objects: u32,
+    failures: u64,
+}
+
+impl Shared {
+    fn usage(&self) -> u64 {
+        (0..self.device.props().memory_heaps.len() as u32)
+            .map(|heap| self.allocator.heap_usage(heap))
+            .sum()
+    }
+
+    fn objects(&self, max_objects: u32) -> u32 {
+        max_objects - self.allocator.remaining_allocations()
+    }
+
+    fn dealloc(&mut self, block: MemoryBlock<usize>, size: u64) {
+        unsafe { self.allocator.dealloc(&self.device, block) }
+        self.live_requested -= size;
+    }
+}
+
+fn run_thread(shared: &Mutex<Shared>, params: &Params, index: usize) {
+    let mut rng = XorShift::new(params.seed.wrapping_add(index as u64));
+
+    // Live blocks ordered by expiration tick.
+    let mut live: BinaryHeap<Reverse<(u64, usize)>> = BinaryHeap::new();
+    let mut blocks: Vec<Option<(MemoryBlock<usize>, u64)>> = Vec::new();
+    let mut free_slots = Vec::new();
+
+    for tick in 0..params.ticks {
+        let mut shared = shared.lock().unwrap();
+
+        while let Some(&Reverse((expires, slot))) = live.peek() {
+            if expires > tick {
+                break;
+            }
+            live.pop();
+            let (block, size) = blocks[slot].take().unwrap();
+            shared.dealloc(block, size);
+            free_slots.push(slot);
+        }
+
+        for _ in 0..params.rate {
+            let request = Request {
+                size: params.size.sample(&mut rng).max(1),
+                align_mask: params.align - 1,
+                usage: params.usage,
+                memory_types: !0,
+                kind: ResourceKind::Linear,
+            };
+
+            let Shared {
+                device, allocator, ..
+            } = &mut *shared;
+
+            match unsafe { allocator.alloc(&*device, request) } {
+                Ok(block) => {
+                    shared.live_requested += request.size;
+
+                    let slot = match free_slots.pop() {
+                        Some(slot) => {
+                            blocks[slot] = Some((block, request.size));
+                            slot
+                        }
+                        None => {
+                            blocks.push(Some((block, request.size)));
+                            blocks.len() - 1
+                        }
+                    };
+
+                    let lifetime = params.lifetime.sample(&mut rng).max(1);
+                    live.push(Reverse((tick + lifetime, slot)));
+                }
+                Err(err) => {
+                    tracing::debug!("Allocation failed: {}", err);
+                    shared.failures += 1;
+                }
+            }
+        }
+
+        shared.peak_requested = shared.peak_requested.max(shared.live_requested);
+        shared.peak_usage = shared.peak_usage.max(shared.usage());
+        shared.peak_objects = shared.peak_objects.max(shared.objects(params.max_objects));
+    }
+
+    let mut shared = shared.lock().unwrap();
+    for (block, size) in blocks.into_iter().flatten() {
+        shared.dealloc(block, size);
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    tracing_subscriber::fmt()
+        .with_env_filter(tracing_subscriber::EnvFilter::from_default_env())
+        .init();
+
+    let params = match Params::parse()? {
+        Some(params) => params,
+        None => {
+            print!("{}", HELP);
+            return Ok(());
+        }
+    };
+
+    let device = MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Owned(vec![
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Owned(vec![
+            MemoryHeap {
+                size: params.heap,
+                budget: None,
+                usage: None,
+            },
+            MemoryHeap {
+                size: params.heap,
+                budget: None,
+                usage: None,
+            },
+        ]),
+        max_memory_allocation_count: params.max_objects,
+        max_memory_allocation_size: params.heap,
+        non_coherent_atom_size: 64,
+        buffer_image_granularity: 1,
+        buffer_device_address: false,
+    });
+
+    let allocator = GpuAllocator::new(params.config, device.props());
+
+    let shared = Mutex::new(Shared {
+        device,
+        allocator,
+        live_requested: 0,
+        peak_requested: 0,
+        peak_usage: 0,
+        peak_objects: 0,
+        failures: 0,
+    });
+
+    let start = std::time::Instant::now();
+
+    thread::scope(|scope| {
+        for index in 0..params.threads {
+            let shared = &shared;
+            let params = &params;
+            scope.spawn(move || run_thread(shared, params, index));
+        }
+    });
+
+    let elapsed = start.elapsed();
+    let mut shared = shared.into_inner().unwrap();
+
+    let allocations = shared.device.total_allocations();
+    unsafe { shared.allocator.cleanup(&shared.device) }
+
+    let fragmentation = if shared.peak_usage > 0 {
+        1.0 - shared.peak_requested as f64 / shared.peak_usage as f64
+    } else {
+        0.0
+    };
+
+    println!("{:#?}", params);
+    println!("Elapsed:                   {:?}", elapsed);
+    println!(
+        "Requests:                  {}",
+        params.ticks * params.rate * params.threads as u64
+    );
+    println!("Failed requests:           {}", shared.failures);
+    println!("Peak requested:            {} bytes", shared.peak_requested);
+    println!("Peak usage:                {} bytes", shared.peak_usage);
+    println!("Fragmentation at peak:     {:.2}%", fragmentation * 100.0);
+    println!("Peak memory objects:       {}", shared.peak_objects);
+    println!("Memory objects allocated:  {}", allocations);
+    println!(
+        "Memory objects leaked:     {}",
+        shared.device.live_allocations()
+    );
+
+    Ok(())
+}
@@ -1,7 +1,7 @@
 use {
     gpu_alloc::{
         Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
-        Request, UsageFlags,
+        Request, ResourceKind, UsageFlags,
     },
     gpu_alloc_mock::MockMemoryDevice,
     std::{borrow::Cow, collections::VecDeque},
@@ -26,10 +26,13 @@ fn main() -> eyre::Result<()> {
         }]),
         memory_heaps: Cow::Borrowed(&[MemoryHeap {
             size: 32 * 1024 * 1024,
+            budget: None,
+            usage: None,
         }]),
         max_memory_allocation_count: 5,
         max_memory_allocation_size: 1024 * 1024,
         non_coherent_atom_size: 8,
+        buffer_image_granularity: 1,
         buffer_device_address: false,
     });
 
@@ -58,6 +61,7 @@ fn main() -> eyre::Result<()> {
                     align_mask: 0,
                     usage: UsageFlags::HOST_ACCESS | UsageFlags::TRANSIENT,
                     memory_types: !0,
+                    kind: ResourceKind::Linear,
                 },
             )
         }?;
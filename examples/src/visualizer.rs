@@ -0,0 +1,513 @@
+//!
+//! Live memory visualizer for `GpuAllocator`.
+//!
+//! Runs synthetic workload on mock device and renders chunks, blocks, tags
+//! and heap budgets in egui window, updating every frame.
+//! The view is built only from `GpuAllocator::dump`, `GpuAllocator::report`
+//! and events received from `GpuAllocator::subscribe`,
+//! so the same code can be attached to allocator of a real application.
+//!
+
+use {
+    eframe::egui,
+    gpu_alloc::{
+        AllocEvent, AllocatorDump, AllocatorReport, ChunkKind, Config, DeviceProperties,
+        GpuAllocator, MemoryBlock, MemoryHeap, MemoryPropertyFlags, MemoryType, Request,
+        ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{
+        borrow::Cow,
+        cmp::Reverse,
+        collections::{BinaryHeap, VecDeque},
+        sync::mpsc::Receiver,
+    },
+};
+
+const TAGS: [(&str, egui::Color32); 4] = [
+    ("mesh", egui::Color32::from_rgb(86, 156, 214)),
+    ("texture", egui::Color32::from_rgb(78, 201, 176)),
+    ("staging", egui::Color32::from_rgb(220, 170, 90)),
+    ("uniform", egui::Color32::from_rgb(197, 134, 192)),
+];
+
+const UNTAGGED: egui::Color32 = egui::Color32::from_rgb(160, 160, 160);
+const FREE: egui::Color32 = egui::Color32::from_rgb(40, 40, 40);
+
+const DEVICE_HEAP: u64 = 1024 * 1024 * 1024;
+const HOST_HEAP: u64 = 256 * 1024 * 1024;
+
+/// Number of recent events shown in the log.
+const EVENT_LOG: usize = 64;
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn new(seed: u64) -> Self {
+        XorShift(seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns value in `[0, 1)`.
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Counters of events received from allocator.
+#[derive(Default)]
+struct EventCounts {
+    allocs: u64,
+    deallocs: u64,
+    chunks_created: u64,
+    chunks_destroyed: u64,
+    failures: u64,
+}
+
+struct Visualizer {
+    device: MockMemoryDevice,
+    allocator: GpuAllocator<usize>,
+    events: Receiver<AllocEvent>,
+
+    /// Live blocks ordered by tick at which they are deallocated.
+    blocks: BinaryHeap<(Reverse<u64>, usize)>,
+    slots: Vec<Option<MemoryBlock<usize>>>,
+    rng: XorShift,
+    tick: u64,
+
+    paused: bool,
+    rate: u64,
+    mean_lifetime: u64,
+    max_size: u64,
+    device_budget: u64,
+
+    counts: EventCounts,
+    log: VecDeque<String>,
+    dump: AllocatorDump,
+    report: AllocatorReport,
+}
+
+impl Visualizer {
+    fn new() -> Self {
+        let device = MockMemoryDevice::new(DeviceProperties {
+            memory_types: Cow::Borrowed(&[
+                MemoryType {
+                    heap: 0,
+                    props: MemoryPropertyFlags::DEVICE_LOCAL,
+                },
+                MemoryType {
+                    heap: 1,
+                    props: MemoryPropertyFlags::HOST_VISIBLE
+                        .union(MemoryPropertyFlags::HOST_COHERENT),
+                },
+            ]),
+            memory_heaps: Cow::Borrowed(&[
+                MemoryHeap {
+                    size: DEVICE_HEAP,
+                    budget: None,
+                    usage: None,
+                },
+                MemoryHeap {
+                    size: HOST_HEAP,
+                    budget: None,
+                    usage: None,
+                },
+            ]),
+            max_memory_allocation_count: 4096,
+            max_memory_allocation_size: DEVICE_HEAP,
+            non_coherent_atom_size: 64,
+            buffer_image_granularity: 1,
+            buffer_device_address: false,
+        });
+
+        let mut allocator = GpuAllocator::new(Config::i_am_prototyping(), device.props());
+        let events = allocator.subscribe();
+        let dump = allocator.dump();
+        let report = allocator.report();
+
+        Visualizer {
+            device,
+            allocator,
+            events,
+            blocks: BinaryHeap::new(),
+            slots: Vec::new(),
+            rng: XorShift::new(1),
+            tick: 0,
+            paused: false,
+            rate: 4,
+            mean_lifetime: 300,
+            max_size: 4 * 1024 * 1024,
+            device_budget: DEVICE_HEAP,
+            counts: EventCounts::default(),
+            log: VecDeque::new(),
+            dump,
+            report,
+        }
+    }
+
+    /// Runs one tick of synthetic workload.
+    fn step(&mut self) {
+        self.tick += 1;
+
+        while let Some(&(Reverse(expires), slot)) = self.blocks.peek() {
+            if expires > self.tick {
+                break;
+            }
+            self.blocks.pop();
+            let block = self.slots[slot].take().expect("Slot of live block");
+            unsafe { self.allocator.dealloc(&self.device, block) }
+        }
+
+        for _ in 0..self.rate {
+            let (tag, _) = TAGS[(self.rng.next() % TAGS.len() as u64) as usize];
+            let usage = match tag {
+                "staging" => UsageFlags::UPLOAD | UsageFlags::TRANSIENT,
+                "uniform" => UsageFlags::UPLOAD | UsageFlags::HOST_ACCESS,
+                _ => UsageFlags::FAST_DEVICE_ACCESS,
+            };
+
+            // Log-uniform size between 256 bytes and `max_size`.
+            let log_min = 256f64.ln();
+            let log_max = (self.max_size.max(256) as f64).ln();
+            let size = (log_min + self.rng.unit() * (log_max - log_min)).exp() as u64;
+            let lifetime = (-(1.0 - self.rng.unit()).ln() * self.mean_lifetime as f64) as u64;
+
+            let request = Request {
+                size,
+                align_mask: 255,
+                usage,
+                memory_types: !0,
+                kind: ResourceKind::Linear,
+            };
+
+            // Failures are reported through event stream.
+            if let Ok(block) = unsafe { self.allocator.alloc_tagged(&self.device, request, tag) } {
+                let slot = match self.slots.iter().position(Option::is_none) {
+                    Some(slot) => slot,
+                    None => {
+                        self.slots.push(None);
+                        self.slots.len() - 1
+                    }
+                };
+                self.slots[slot] = Some(block);
+                self.blocks
+                    .push((Reverse(self.tick + lifetime.max(1)), slot));
+            }
+        }
+    }
+
+    fn receive_events(&mut self) {
+        for event in self.events.try_iter() {
+            let line = match event {
+                // Blocks are too frequent to be logged.
+                AllocEvent::Alloc(_) => {
+                    self.counts.allocs += 1;
+                    continue;
+                }
+                AllocEvent::Dealloc(_) => {
+                    self.counts.deallocs += 1;
+                    continue;
+                }
+                AllocEvent::ChunkCreated(chunk) => {
+                    self.counts.chunks_created += 1;
+                    format!(
+                        "+ {:?} chunk {} of type {}, {}",
+                        chunk.kind,
+                        chunk.id,
+                        chunk.memory_type,
+                        bytes(chunk.size)
+                    )
+                }
+                AllocEvent::ChunkDestroyed(chunk) => {
+                    self.counts.chunks_destroyed += 1;
+                    format!(
+                        "- {:?} chunk {} of type {}, {}",
+                        chunk.kind,
+                        chunk.id,
+                        chunk.memory_type,
+                        bytes(chunk.size)
+                    )
+                }
+                AllocEvent::Failure(failure) => {
+                    self.counts.failures += 1;
+                    format!(
+                        "! {} bytes for {:?}: {}",
+                        failure.request.size,
+                        failure.tag.unwrap_or("untagged"),
+                        failure.error
+                    )
+                }
+                _ => continue,
+            };
+
+            if self.log.len() == EVENT_LOG {
+                self.log.pop_front();
+            }
+            self.log.push_back(line);
+        }
+    }
+
+    fn free_all(&mut self) {
+        self.blocks.clear();
+        for block in self.slots.drain(..).flatten() {
+            unsafe { self.allocator.dealloc(&self.device, block) }
+        }
+    }
+
+    fn controls_ui(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.paused, "Paused");
+            if ui.button("Step").clicked() {
+                self.step();
+            }
+            if ui.button("Free all").clicked() {
+                self.free_all();
+            }
+            if ui.button("Cleanup").clicked() {
+                unsafe { self.allocator.cleanup(&self.device) }
+            }
+            if ui.button("Reset watermarks").clicked() {
+                self.allocator.reset_watermarks();
+            }
+        });
+
+        ui.add(egui::Slider::new(&mut self.rate, 0..=64).text("allocations per frame"));
+        ui.add(
+            egui::Slider::new(&mut self.mean_lifetime, 1..=10_000)
+                .logarithmic(true)
+                .text("mean lifetime in frames"),
+        );
+        ui.add(
+            egui::Slider::new(&mut self.max_size, 256..=256 * 1024 * 1024)
+                .logarithmic(true)
+                .text("max block size"),
+        );
+
+        let budget = ui.add(
+            egui::Slider::new(&mut self.device_budget, 1024 * 1024..=DEVICE_HEAP)
+                .logarithmic(true)
+                .text("device heap budget"),
+        );
+        if budget.changed() {
+            self.allocator.set_heap_budget(0, self.device_budget);
+        }
+    }
+
+    fn heaps_ui(&self, ui: &mut egui::Ui) {
+        let stats = self.allocator.stats();
+
+        for (heap, stats) in self.report.heaps.iter().zip(&stats.heaps) {
+            ui.label(format!(
+                "Heap {}: {} used of {} allocated, budget {}, peak {}, {} memory objects",
+                heap.index,
+                bytes(heap.used_bytes),
+                bytes(heap.allocated_bytes),
+                bytes(stats.budget),
+                bytes(stats.peak_used),
+                heap.memory_objects,
+            ));
+            ui.add(
+                egui::ProgressBar::new(heap.allocated_bytes as f32 / stats.budget.max(1) as f32)
+                    .text(format!(
+                        "{:.1}% of budget",
+                        100.0 * heap.allocated_bytes as f64 / stats.budget.max(1) as f64
+                    )),
+            );
+        }
+    }
+
+    fn tags_ui(&self, ui: &mut egui::Ui) {
+        egui::Grid::new("tags").striped(true).show(ui, |ui| {
+            ui.strong("Tag");
+            ui.strong("Blocks");
+            ui.strong("Bytes");
+            ui.strong("Peak");
+            ui.end_row();
+
+            for tag in &self.dump.tags {
+                ui.colored_label(tag_color(Some(&tag.tag)), &*tag.tag);
+                ui.label(tag.blocks.to_string());
+                ui.label(bytes(tag.bytes));
+                ui.label(bytes(tag.peak_bytes));
+                ui.end_row();
+            }
+        });
+    }
+
+    fn events_ui(&self, ui: &mut egui::Ui) {
+        let counts = &self.counts;
+        ui.label(format!(
+            "{} allocations, {} deallocations, {} chunks created, {} destroyed, {} failures",
+            counts.allocs,
+            counts.deallocs,
+            counts.chunks_created,
+            counts.chunks_destroyed,
+            counts.failures,
+        ));
+
+        egui::ScrollArea::vertical()
+            .id_salt("events")
+            .max_height(160.0)
+            .stick_to_bottom(true)
+            .show(ui, |ui| {
+                for line in &self.log {
+                    ui.monospace(line);
+                }
+            });
+    }
+
+    fn chunks_ui(&self, ui: &mut egui::Ui) {
+        for memory_type in &self.dump.types {
+            if memory_type.chunks.is_empty() {
+                continue;
+            }
+
+            let report = &self.report.types[memory_type.index as usize];
+            egui::CollapsingHeader::new(format!(
+                "Memory type {} ({:?}) in heap {}: {} chunks, {} used of {}, largest free region {}",
+                memory_type.index,
+                memory_type.props,
+                memory_type.heap,
+                memory_type.chunks.len(),
+                bytes(report.used_bytes),
+                bytes(report.allocated_bytes),
+                bytes(report.largest_free_region),
+            ))
+            .id_salt(memory_type.index)
+            .default_open(true)
+            .show(ui, |ui| {
+                // Strips are scaled to the largest chunk of the type.
+                let largest = memory_type
+                    .chunks
+                    .iter()
+                    .map(|chunk| chunk.size)
+                    .max()
+                    .unwrap_or(1);
+
+                for chunk in &memory_type.chunks {
+                    let width = ui.available_width();
+                    let (rect, response) =
+                        ui.allocate_exact_size(egui::vec2(width, 14.0), egui::Sense::hover());
+                    let painter = ui.painter_at(rect);
+                    let scale = rect.width() / largest as f32;
+                    let span = |offset: u64, size: u64| {
+                        let left = rect.left() + offset as f32 * scale;
+                        egui::Rect::from_min_max(
+                            egui::pos2(left, rect.top()),
+                            egui::pos2((left + size as f32 * scale).max(left + 1.0), rect.bottom()),
+                        )
+                    };
+
+                    painter.rect_filled(span(0, chunk.size), 0.0, FREE);
+                    for block in &chunk.blocks {
+                        painter.rect_filled(
+                            span(block.offset, block.size),
+                            0.0,
+                            tag_color(block.tag.as_deref()),
+                        );
+                    }
+
+                    let used: u64 = chunk.blocks.iter().map(|block| block.size).sum();
+                    response.on_hover_text(format!(
+                        "{} chunk {}: {} blocks, {} used of {}, {} free regions",
+                        kind_name(chunk.kind),
+                        chunk.id,
+                        chunk.blocks.len(),
+                        bytes(used),
+                        bytes(chunk.size),
+                        chunk.free.len(),
+                    ));
+                }
+            });
+        }
+    }
+}
+
+impl eframe::App for Visualizer {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if !self.paused {
+            self.step();
+        }
+
+        self.receive_events();
+        self.dump = self.allocator.dump();
+        self.report = self.allocator.report();
+
+        egui::SidePanel::left("summary")
+            .min_width(360.0)
+            .show(ctx, |ui| {
+                ui.heading("Workload");
+                self.controls_ui(ui);
+                ui.separator();
+                ui.heading("Heaps");
+                self.heaps_ui(ui);
+                ui.separator();
+                ui.heading("Tags");
+                self.tags_ui(ui);
+                ui.separator();
+                ui.heading("Events");
+                self.events_ui(ui);
+            });
+
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.heading("Chunks");
+            egui::ScrollArea::vertical().show(ui, |ui| self.chunks_ui(ui));
+        });
+
+        ctx.request_repaint();
+    }
+
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.free_all();
+        unsafe { self.allocator.cleanup(&self.device) }
+    }
+}
+
+fn tag_color(tag: Option<&str>) -> egui::Color32 {
+    TAGS.iter()
+        .find(|&&(name, _)| Some(name) == tag)
+        .map_or(UNTAGGED, |&(_, color)| color)
+}
+
+fn kind_name(kind: ChunkKind) -> &'static str {
+    match kind {
+        ChunkKind::Dedicated => "Dedicated",
+        ChunkKind::Buddy => "Buddy",
+        ChunkKind::FreeList => "Free-list",
+        ChunkKind::Ring => "Ring",
+    }
+}
+
+fn bytes(size: u64) -> String {
+    const UNITS: [&str; 4] = ["B", "KiB", "MiB", "GiB"];
+
+    let mut value = size as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", size, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    eframe::run_native(
+        "gpu-alloc visualizer",
+        eframe::NativeOptions::default(),
+        Box::new(|_| Ok(Box::new(Visualizer::new()))),
+    )
+    .map_err(|err| eyre::eyre!("{}", err))
+}
@@ -0,0 +1,10 @@
+#![no_main]
+
+use {
+    gpu_alloc_fuzz::{Fuzzer, Operation, Setup},
+    libfuzzer_sys::fuzz_target,
+};
+
+fuzz_target!(|ops: Vec<Operation>| {
+    Fuzzer::new(Setup::dedicated()).run(&ops);
+});
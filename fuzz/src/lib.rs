@@ -0,0 +1,332 @@
+//!
+//! Shared machinery for `gpu-alloc` fuzz targets.
+//!
+//! Each target feeds a sequence of [`Operation`]s into a [`Fuzzer`]
+//! which drives `GpuAllocator` against `MockMemoryDevice`
+//! and checks allocator and device invariants after every step.
+//!
+
+use {
+    arbitrary::Arbitrary,
+    gpu_alloc::{
+        Config, Dedicated, DeviceProperties, GpuAllocator, MemoryBlock, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, Request, ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+/// Allocation strategy hint.
+#[derive(Arbitrary, Clone, Copy, Debug)]
+pub enum Hint {
+    /// Let allocator choose strategy.
+    Auto,
+
+    /// Use `Dedicated::Required`.
+    Required,
+
+    /// Use `Dedicated::Preferred`.
+    Preferred,
+}
+
+/// Single step of fuzzed allocator state machine.
+#[derive(Arbitrary, Clone, Copy, Debug)]
+pub enum Operation {
+    /// Allocate new memory block.
+    Alloc {
+        /// Log2 of the base of the requested size.
+        size_class: u8,
+
+        /// Added to the base of the requested size.
+        size_remainder: u32,
+
+        /// Log2 of requested alignment.
+        align_shift: u8,
+
+        /// Bits of `UsageFlags`.
+        usage: u8,
+
+        /// Allowed memory types mask.
+        memory_types: u32,
+
+        /// Strategy hint.
+        hint: Hint,
+    },
+
+    /// Deallocate one of live memory blocks.
+    Dealloc {
+        /// Index of the block among live blocks, wrapped around.
+        index: u16,
+    },
+
+    /// Call `GpuAllocator::cleanup`.
+    Cleanup,
+}
+
+/// Shape of the fuzzed device and allocator.
+#[derive(Clone, Copy, Debug)]
+pub struct Setup {
+    /// Largest allowed size class.
+    pub max_size_class: u8,
+
+    /// Usage flags added to every request.
+    pub implicit_usage: UsageFlags,
+
+    /// Limit on memory objects count.
+    pub max_memory_allocation_count: u32,
+
+    /// Allocator configuration.
+    pub config: Config,
+}
+
+impl Setup {
+    /// Mixed workload over all strategies.
+    pub fn mixed() -> Self {
+        Setup {
+            max_size_class: 22,
+            implicit_usage: UsageFlags::empty(),
+            max_memory_allocation_count: 64,
+            config: Config::i_am_potato(),
+        }
+    }
+
+    /// Workload focused on transient requests served by free-list allocator.
+    pub fn transient() -> Self {
+        Setup {
+            max_size_class: 16,
+            implicit_usage: UsageFlags::TRANSIENT | UsageFlags::HOST_ACCESS,
+            max_memory_allocation_count: 32,
+            config: Config::i_am_potato(),
+        }
+    }
+
+    /// Workload with low thresholds that prefers dedicated allocations
+    /// and exhausts memory objects limit quickly.
+    pub fn dedicated() -> Self {
+        let mut config = Config::i_am_potato();
+        config.dedicated_threshold = 1024;
+        config.preferred_dedicated_threshold = 256;
+        config.transient_dedicated_threshold = 4096;
+
+        Setup {
+            max_size_class: 20,
+            implicit_usage: UsageFlags::empty(),
+            max_memory_allocation_count: 16,
+            config,
+        }
+    }
+}
+
+/// Device properties used by fuzz targets.
+pub fn device_properties(max_memory_allocation_count: u32) -> DeviceProperties<'static> {
+    DeviceProperties {
+        memory_types: Cow::Owned(vec![
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_CACHED,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL
+                    | MemoryPropertyFlags::HOST_VISIBLE
+                    | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Owned(vec![
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+                budget: None,
+                usage: None,
+            },
+            MemoryHeap {
+                size: 32 * 1024 * 1024,
+                budget: None,
+                usage: None,
+            },
+        ]),
+        max_memory_allocation_count,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_image_granularity: 1,
+        buffer_device_address: true,
+    }
+}
+
+/// Drives allocator with fuzzed operations and checks invariants.
+pub struct Fuzzer {
+    setup: Setup,
+    device: MockMemoryDevice,
+    allocator: GpuAllocator<usize>,
+    blocks: Vec<(MemoryBlock<usize>, Request)>,
+}
+
+impl Fuzzer {
+    pub fn new(setup: Setup) -> Self {
+        let device = MockMemoryDevice::new(device_properties(setup.max_memory_allocation_count));
+        let allocator = GpuAllocator::new(setup.config, device.props());
+
+        Fuzzer {
+            setup,
+            device,
+            allocator,
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Runs all operations, then deallocates everything and checks that no memory is leaked.
+    pub fn run(mut self, ops: &[Operation]) {
+        for &op in ops {
+            self.step(op);
+            self.check();
+        }
+        self.finish();
+    }
+
+    /// Performs single operation.
+    pub fn step(&mut self, op: Operation) {
+        match op {
+            Operation::Alloc {
+                size_class,
+                size_remainder,
+                align_shift,
+                usage,
+                memory_types,
+                hint,
+            } => {
+                let size_class = size_class % (self.setup.max_size_class + 1);
+                let size = (1u64 << size_class) + u64::from(size_remainder) % (1u64 << size_class);
+
+                let request = Request {
+                    size,
+                    align_mask: (1u64 << (align_shift % 12)) - 1,
+                    usage: UsageFlags::from_bits_truncate(usage) | self.setup.implicit_usage,
+                    memory_types,
+                    kind: ResourceKind::Linear,
+                };
+
+                let result = unsafe {
+                    match hint {
+                        Hint::Auto => self.allocator.alloc(&self.device, request),
+                        Hint::Required => self.allocator.alloc_with_dedicated(
+                            &self.device,
+                            request,
+                            Dedicated::Required,
+                        ),
+                        Hint::Preferred => self.allocator.alloc_with_dedicated(
+                            &self.device,
+                            request,
+                            Dedicated::Preferred,
+                        ),
+                    }
+                };
+
+                if let Ok(block) = result {
+                    self.blocks.push((block, request));
+                }
+            }
+            Operation::Dealloc { index } => {
+                if !self.blocks.is_empty() {
+                    let index = usize::from(index) % self.blocks.len();
+                    let (block, _) = self.blocks.swap_remove(index);
+                    unsafe { self.allocator.dealloc(&self.device, block) }
+                }
+            }
+            Operation::Cleanup => unsafe { self.allocator.cleanup(&self.device) },
+        }
+    }
+
+    /// Checks heap accounting, device invariants and placement of live blocks.
+    pub fn check(&self) {
+        self.device.check_invariants();
+
+        let props = self.device.props();
+
+        for heap in 0..props.memory_heaps.len() as u32 {
+            assert_eq!(
+                self.allocator.heap_usage(heap),
+                self.device.allocated_memory(heap),
+                "Heap {} usage diverged from device",
+                heap
+            );
+        }
+
+        assert_eq!(
+            u64::from(self.allocator.remaining_allocations()) + self.device.live_allocations(),
+            u64::from(props.max_memory_allocation_count),
+            "Memory objects count diverged from device",
+        );
+
+        let mut ranges = Vec::with_capacity(self.blocks.len());
+
+        for (block, request) in &self.blocks {
+            assert_ne!(
+                request.memory_types & (1 << block.memory_type()),
+                0,
+                "Block allocated from memory type not allowed by request"
+            );
+
+            if request
+                .usage
+                .intersects(UsageFlags::HOST_ACCESS | UsageFlags::UPLOAD | UsageFlags::DOWNLOAD)
+            {
+                assert!(
+                    block.props().contains(MemoryPropertyFlags::HOST_VISIBLE),
+                    "Host access requested but block is not host-visible"
+                );
+            }
+
+            assert!(block.size() >= request.size, "Block is too small");
+            assert_eq!(
+                block.offset() & request.align_mask,
+                0,
+                "Block is misaligned"
+            );
+
+            let memory = *block.memory();
+            let memory_size = self
+                .device
+                .memory_size(memory)
+                .expect("Block references deallocated memory object");
+
+            assert!(
+                block.offset() + block.size() <= memory_size,
+                "Block is out of memory object bounds"
+            );
+
+            ranges.push((memory, block.offset(), block.offset() + block.size()));
+        }
+
+        ranges.sort_unstable();
+
+        for pair in ranges.windows(2) {
+            if pair[0].0 == pair[1].0 {
+                assert!(
+                    pair[0].2 <= pair[1].1,
+                    "Blocks {:?} and {:?} overlap",
+                    pair[0],
+                    pair[1]
+                );
+            }
+        }
+    }
+
+    /// Deallocates all blocks, cleans allocator up and checks that all memory objects are freed.
+    pub fn finish(mut self) {
+        for (block, _) in self.blocks.drain(..) {
+            unsafe { self.allocator.dealloc(&self.device, block) }
+        }
+
+        unsafe { self.allocator.cleanup(&self.device) }
+        self.check();
+
+        assert_eq!(self.device.live_allocations(), 0, "Memory objects leaked");
+    }
+}
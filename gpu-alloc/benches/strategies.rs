@@ -0,0 +1,305 @@
+//!
+//! Compares throughput and memory overhead of allocation strategies
+//! on size distributions typical for real applications.
+//!
+//! Memory overhead is printed once per strategy and distribution
+//! as ratio of bytes allocated from device to bytes requested.
+//!
+
+use {
+    criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput},
+    gpu_alloc::{
+        Config, Dedicated, DeviceProperties, GpuAllocator, MemoryBlock, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, Request, ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    std::{borrow::Cow, hint::black_box},
+};
+
+/// Number of live blocks in each workload round.
+const BLOCKS: usize = 256;
+
+#[derive(Clone, Copy, Debug)]
+enum Strategy {
+    Buddy,
+    FreeList,
+    Dedicated,
+}
+
+impl Strategy {
+    const ALL: [Strategy; 3] = [Strategy::Buddy, Strategy::FreeList, Strategy::Dedicated];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Strategy::Buddy => "buddy",
+            Strategy::FreeList => "freelist",
+            Strategy::Dedicated => "dedicated",
+        }
+    }
+
+    /// Configuration that routes all requests of the distribution to this strategy.
+    fn config(&self) -> Config {
+        let mut config = Config::i_am_prototyping();
+        config.dedicated_threshold = 64 * 1024 * 1024;
+        config.preferred_dedicated_threshold = 64 * 1024 * 1024;
+        config.transient_dedicated_threshold = 64 * 1024 * 1024;
+        config.final_free_list_chunk = 64 * 1024 * 1024;
+        config
+    }
+
+    fn usage(&self) -> UsageFlags {
+        match self {
+            Strategy::FreeList => UsageFlags::TRANSIENT,
+            _ => UsageFlags::FAST_DEVICE_ACCESS,
+        }
+    }
+
+    fn dedicated(&self) -> Option<Dedicated> {
+        match self {
+            Strategy::Dedicated => Some(Dedicated::Required),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Distribution {
+    /// Small uniform buffers.
+    Uniforms,
+
+    /// Medium to large images with coarse alignment.
+    Textures,
+
+    /// Host-visible upload buffers.
+    Staging,
+}
+
+impl Distribution {
+    const ALL: [Distribution; 3] = [
+        Distribution::Uniforms,
+        Distribution::Textures,
+        Distribution::Staging,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Distribution::Uniforms => "uniforms",
+            Distribution::Textures => "textures",
+            Distribution::Staging => "staging",
+        }
+    }
+
+    fn request(&self, rng: &mut XorShift, strategy: Strategy) -> Request {
+        let (min, max, align_mask, usage) = match self {
+            Distribution::Uniforms => (64, 64 * 1024, 255, UsageFlags::empty()),
+            Distribution::Textures => (64 * 1024, 4 * 1024 * 1024, 65535, UsageFlags::empty()),
+            Distribution::Staging => (4 * 1024, 1024 * 1024, 63, UsageFlags::UPLOAD),
+        };
+
+        Request {
+            size: min + rng.next() % (max - min),
+            align_mask,
+            usage: usage | strategy.usage(),
+            memory_types: !0,
+            kind: ResourceKind::Linear,
+        }
+    }
+}
+
+/// Small deterministic generator to keep workloads identical across runs.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+fn device_properties() -> DeviceProperties<'static> {
+    DeviceProperties {
+        memory_types: Cow::Owned(vec![
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+        ]),
+        memory_heaps: Cow::Owned(vec![
+            MemoryHeap {
+                size: 16 * 1024 * 1024 * 1024,
+                budget: None,
+                usage: None,
+            },
+            MemoryHeap {
+                size: 16 * 1024 * 1024 * 1024,
+                budget: None,
+                usage: None,
+            },
+        ]),
+        max_memory_allocation_count: 4096,
+        max_memory_allocation_size: 1024 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_image_granularity: 1,
+        buffer_device_address: false,
+    }
+}
+
+struct Workload {
+    device: MockMemoryDevice,
+    allocator: GpuAllocator<usize>,
+    requests: Vec<Request>,
+    order: Vec<usize>,
+    dedicated: Option<Dedicated>,
+}
+
+impl Workload {
+    fn new(strategy: Strategy, distribution: Distribution) -> Self {
+        let device = MockMemoryDevice::new(device_properties());
+        let allocator = GpuAllocator::new(strategy.config(), device.props());
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+
+        let requests = (0..BLOCKS)
+            .map(|_| distribution.request(&mut rng, strategy))
+            .collect();
+
+        // Deallocate in shuffled order to exercise merging.
+        let mut order: Vec<usize> = (0..BLOCKS).collect();
+        for i in (1..BLOCKS).rev() {
+            order.swap(i, rng.next() as usize % (i + 1));
+        }
+
+        Workload {
+            device,
+            allocator,
+            requests,
+            order,
+            dedicated: strategy.dedicated(),
+        }
+    }
+
+    fn alloc_all(&mut self) -> Vec<Option<MemoryBlock<usize>>> {
+        let Workload {
+            device,
+            allocator,
+            requests,
+            dedicated,
+            ..
+        } = self;
+
+        requests
+            .iter()
+            .map(|&request| unsafe {
+                let result = match *dedicated {
+                    None => allocator.alloc(&*device, request),
+                    Some(dedicated) => allocator.alloc_with_dedicated(&*device, request, dedicated),
+                };
+                Some(result.expect("Benchmark heaps must be large enough"))
+            })
+            .collect()
+    }
+
+    fn dealloc_all(&mut self, mut blocks: Vec<Option<MemoryBlock<usize>>>) {
+        for &index in &self.order {
+            let block = blocks[index].take().unwrap();
+            unsafe { self.allocator.dealloc(&self.device, block) }
+        }
+    }
+
+    fn round(&mut self) {
+        let blocks = self.alloc_all();
+        black_box(&blocks);
+        self.dealloc_all(blocks);
+    }
+
+    /// Ratio of bytes allocated from device to bytes requested while all blocks are live.
+    fn overhead(&mut self) -> f64 {
+        let blocks = self.alloc_all();
+
+        let requested: u64 = self.requests.iter().map(|request| request.size).sum();
+        let allocated: u64 = (0..2).map(|heap| self.allocator.heap_usage(heap)).sum();
+
+        self.dealloc_all(blocks);
+        allocated as f64 / requested as f64
+    }
+}
+
+impl Drop for Workload {
+    fn drop(&mut self) {
+        unsafe { self.allocator.cleanup(&self.device) }
+    }
+}
+
+fn strategies(c: &mut Criterion) {
+    for distribution in Distribution::ALL {
+        let mut group = c.benchmark_group(distribution.name());
+        group.throughput(Throughput::Elements(BLOCKS as u64));
+
+        for strategy in Strategy::ALL {
+            let mut workload = Workload::new(strategy, distribution);
+
+            println!(
+                "{}/{}: memory overhead {:.3}",
+                distribution.name(),
+                strategy.name(),
+                workload.overhead(),
+            );
+
+            group.bench_function(BenchmarkId::new("alloc-dealloc", strategy.name()), |b| {
+                b.iter(|| workload.round())
+            });
+        }
+
+        group.finish();
+    }
+}
+
+/// Number of live blocks in buddy metadata workload.
+const SMALL_BLOCKS: usize = 64 * 1024;
+
+/// Allocates and deallocates many minimal buddy blocks,
+/// where cost is dominated by pair metadata rather than device calls.
+fn buddy_small_blocks(c: &mut Criterion) {
+    let device = MockMemoryDevice::new(device_properties());
+    let mut allocator = GpuAllocator::new(Strategy::Buddy.config(), device.props());
+    let request = Request {
+        size: 256,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: !0,
+        kind: ResourceKind::Linear,
+    };
+
+    let mut group = c.benchmark_group("small-blocks");
+    group.throughput(Throughput::Elements(SMALL_BLOCKS as u64));
+    group.sample_size(20);
+
+    let mut blocks = Vec::with_capacity(SMALL_BLOCKS);
+    group.bench_function(BenchmarkId::new("alloc-dealloc", "buddy"), |b| {
+        b.iter(|| {
+            for _ in 0..SMALL_BLOCKS {
+                let block = unsafe { allocator.alloc(&device, request) };
+                blocks.push(block.expect("Benchmark heaps must be large enough"));
+            }
+            black_box(&blocks);
+
+            // Deallocate in interleaved order so that pairs are merged late.
+            let mut index = 0;
+            while !blocks.is_empty() {
+                index = (index + 7919) % blocks.len();
+                unsafe { allocator.dealloc(&device, blocks.swap_remove(index)) }
+            }
+        })
+    });
+
+    group.finish();
+    unsafe { allocator.cleanup(&device) }
+}
+
+criterion_group!(benches, strategies, buddy_small_blocks);
+criterion_main!(benches);
@@ -0,0 +1,294 @@
+use {
+    alloc::{collections::BTreeMap, vec::Vec},
+    core::fmt,
+};
+
+/// Thresholds of heuristics checked by allocator advisor.
+///
+/// See [`GpuAllocator::enable_advisor`].
+///
+/// [`GpuAllocator::enable_advisor`]: crate::GpuAllocator::enable_advisor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdvisorConfig {
+    /// Number of frames after which live transient block is reported.
+    pub transient_max_frames: u64,
+
+    /// Number of dedicated allocations below dedicated threshold
+    /// in one frame and memory type that is reported.
+    pub small_dedicated_per_frame: u32,
+
+    /// Number of allocations and deallocations of the same size
+    /// in one frame and memory type that is reported.
+    pub repeated_size_per_frame: u32,
+
+    /// Number of memory objects both created and destroyed
+    /// in one frame and memory type that is reported.
+    pub chunk_churn_per_frame: u32,
+}
+
+impl Default for AdvisorConfig {
+    fn default() -> Self {
+        AdvisorConfig {
+            transient_max_frames: 8,
+            small_dedicated_per_frame: 4,
+            repeated_size_per_frame: 16,
+            chunk_churn_per_frame: 2,
+        }
+    }
+}
+
+/// Suboptimal usage pattern detected by allocator advisor.
+///
+/// `Display` implementation describes the problem and suggests a fix.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Advice {
+    /// Block requested with `UsageFlags::TRANSIENT` lives for many frames.
+    LongLivedTransient {
+        /// Memory type of the block.
+        memory_type: u32,
+        /// Requested size of the block.
+        size: u64,
+        /// Number of frames the block is alive.
+        frames: u64,
+    },
+
+    /// Many blocks smaller than dedicated threshold
+    /// got dedicated memory objects in one frame.
+    FrequentSmallDedicated {
+        /// Memory type of the blocks.
+        memory_type: u32,
+        /// Number of such blocks allocated in the frame.
+        count: u32,
+        /// Largest requested size among them.
+        largest: u64,
+        /// Configured `Config::dedicated_threshold`.
+        dedicated_threshold: u64,
+    },
+
+    /// Blocks of the same size were allocated and deallocated repeatedly in one frame.
+    RepeatedSize {
+        /// Memory type of the blocks.
+        memory_type: u32,
+        /// Requested size of the blocks.
+        size: u64,
+        /// Number of allocation and deallocation pairs in the frame.
+        count: u32,
+    },
+
+    /// Memory objects were both created and destroyed repeatedly in one frame.
+    ChunkThrashing {
+        /// Memory type of the memory objects.
+        memory_type: u32,
+        /// Number of memory objects created in the frame.
+        created: u32,
+        /// Number of memory objects destroyed in the frame.
+        destroyed: u32,
+    },
+}
+
+impl fmt::Display for Advice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Advice::LongLivedTransient {
+                memory_type,
+                size,
+                frames,
+            } => write!(
+                f,
+                "Transient block of {} bytes in memory type {} is alive for {} frames. \
+                 Remove `UsageFlags::TRANSIENT` from requests for long-lived resources",
+                size, memory_type, frames
+            ),
+            Advice::FrequentSmallDedicated {
+                memory_type,
+                count,
+                largest,
+                dedicated_threshold,
+            } => write!(
+                f,
+                "{} blocks up to {} bytes in memory type {} got dedicated memory objects in one frame, \
+                 while dedicated threshold is {} bytes. \
+                 Raise `Config::preferred_dedicated_threshold` above {} or stop preferring dedicated memory for them",
+                count, largest, memory_type, dedicated_threshold, largest
+            ),
+            Advice::RepeatedSize {
+                memory_type,
+                size,
+                count,
+            } => write!(
+                f,
+                "Blocks of {} bytes in memory type {} were allocated and deallocated {} times in one frame. \
+                 Reuse blocks or pool resources of this size",
+                size, memory_type, count
+            ),
+            Advice::ChunkThrashing {
+                memory_type,
+                created,
+                destroyed,
+            } => write!(
+                f,
+                "Memory type {} created {} and destroyed {} memory objects in one frame. \
+                 Increase `Config::starting_free_list_chunk` and `Config::initial_buddy_dedicated_size` \
+                 or call `GpuAllocator::cleanup` less often",
+                memory_type, created, destroyed
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct TransientBlock {
+    memory_type: u32,
+    size: u64,
+    frame: u64,
+    reported: bool,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct SizeChurn {
+    allocs: u32,
+    deallocs: u32,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct SmallDedicated {
+    count: u32,
+    largest: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct ChunkChurn {
+    created: u32,
+    destroyed: u32,
+}
+
+/// Collects allocation events over a frame and turns them into advice.
+#[derive(Debug)]
+pub(crate) struct Advisor {
+    config: AdvisorConfig,
+    frame: u64,
+    transient: BTreeMap<usize, TransientBlock>,
+    small_dedicated: BTreeMap<u32, SmallDedicated>,
+    sizes: BTreeMap<(u32, u64), SizeChurn>,
+    chunks: BTreeMap<u32, ChunkChurn>,
+    advice: Vec<Advice>,
+}
+
+impl Advisor {
+    pub fn new(config: AdvisorConfig) -> Self {
+        Advisor {
+            config,
+            frame: 0,
+            transient: BTreeMap::new(),
+            small_dedicated: BTreeMap::new(),
+            sizes: BTreeMap::new(),
+            chunks: BTreeMap::new(),
+            advice: Vec::new(),
+        }
+    }
+
+    pub fn alloc(&mut self, id: usize, memory_type: u32, size: u64, transient: bool) {
+        if transient {
+            self.transient.insert(
+                id,
+                TransientBlock {
+                    memory_type,
+                    size,
+                    frame: self.frame,
+                    reported: false,
+                },
+            );
+        }
+
+        self.sizes.entry((memory_type, size)).or_default().allocs += 1;
+    }
+
+    pub fn dealloc(&mut self, id: usize, memory_type: u32, size: u64) {
+        self.transient.remove(&id);
+        self.sizes.entry((memory_type, size)).or_default().deallocs += 1;
+    }
+
+    pub fn small_dedicated(&mut self, memory_type: u32, size: u64) {
+        let small = self.small_dedicated.entry(memory_type).or_default();
+        small.count += 1;
+        small.largest = small.largest.max(size);
+    }
+
+    pub fn chunks_created(&mut self, memory_type: u32, count: u32) {
+        if count > 0 {
+            self.chunks.entry(memory_type).or_default().created += count;
+        }
+    }
+
+    pub fn chunks_destroyed(&mut self, memory_type: u32, count: u32) {
+        if count > 0 {
+            self.chunks.entry(memory_type).or_default().destroyed += count;
+        }
+    }
+
+    /// Checks heuristics over the finished frame and starts next one.
+    pub fn end_frame(&mut self, dedicated_threshold: u64) {
+        let config = self.config;
+
+        #[cfg(feature = "tracing")]
+        let first = self.advice.len();
+
+        for block in self.transient.values_mut() {
+            let frames = self.frame - block.frame;
+            if !block.reported && frames >= config.transient_max_frames {
+                block.reported = true;
+                self.advice.push(Advice::LongLivedTransient {
+                    memory_type: block.memory_type,
+                    size: block.size,
+                    frames,
+                });
+            }
+        }
+
+        for (memory_type, small) in core::mem::take(&mut self.small_dedicated) {
+            if small.count >= config.small_dedicated_per_frame {
+                self.advice.push(Advice::FrequentSmallDedicated {
+                    memory_type,
+                    count: small.count,
+                    largest: small.largest,
+                    dedicated_threshold,
+                });
+            }
+        }
+
+        for ((memory_type, size), churn) in core::mem::take(&mut self.sizes) {
+            let count = churn.allocs.min(churn.deallocs);
+            if count >= config.repeated_size_per_frame {
+                self.advice.push(Advice::RepeatedSize {
+                    memory_type,
+                    size,
+                    count,
+                });
+            }
+        }
+
+        for (memory_type, churn) in core::mem::take(&mut self.chunks) {
+            if churn.created.min(churn.destroyed) >= config.chunk_churn_per_frame {
+                self.advice.push(Advice::ChunkThrashing {
+                    memory_type,
+                    created: churn.created,
+                    destroyed: churn.destroyed,
+                });
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        for advice in &self.advice[first..] {
+            tracing::warn!("{}", advice);
+        }
+
+        self.frame += 1;
+    }
+
+    pub fn take(&mut self) -> Vec<Advice> {
+        core::mem::take(&mut self.advice)
+    }
+}
@@ -1,43 +1,132 @@
 use {
     crate::{
-        align_down,
+        advisor::{Advice, Advisor, AdvisorConfig},
+        align_down, align_up,
+        atomic::{AtomicU32, Relaxed},
         block::{MemoryBlock, MemoryBlockFlavor},
         buddy::{BuddyAllocator, BuddyBlock},
+        cache::BlockCache,
+        cleanup::CleanupOnDrop,
         config::Config,
+        defrag::Defragmentation,
+        dump::{AllocatorDump, BlockDump, ChunkDump, ChunkKind, MemoryTypeDump, RegionDump},
         error::AllocationError,
+        failure::{AttemptOutcome, DiagnosedDevice, FailureDetails, HeapBudget, MemoryTypeAttempt},
         freelist::{FreeListAllocator, FreeListBlock},
         heap::Heap,
-        usage::{MemoryForUsage, UsageFlags},
-        MemoryBounds, Request,
+        observer::{self, AllocatorObserver, BlockEvent, FailureEvent, ObserverSlot},
+        pool::{Pool, PoolConfig, PoolId, Strategies},
+        rebalance::BlockMove,
+        registry::{BlockRecord, ChunkId, Registry},
+        report::{AllocatorReport, HeapReport, TypeReport},
+        ring::{RingAllocator, RingBlock},
+        selector::{MemoryTypeCandidate, MemoryTypeSelector, Selection, SelectorSlot},
+        snapshot::{AllocatorSnapshot, SnapshotBlock},
+        stats::{
+            AccountingDrift, AllocatorStats, ConcurrentStats, FragmentationStats, HeapStats,
+            TagStats, TagUsage, TypeStats,
+        },
+        usage::{DefaultScoring, MemoryForUsage, MemoryTypeScoring, UsageFlags},
+        util::{compact, uncompact, Table},
+        MemoryBounds, Request, ResourceKind,
     },
-    alloc::boxed::Box,
-    core::convert::TryFrom as _,
+    alloc::{borrow::Cow, boxed::Box, collections::BTreeMap, vec::Vec},
+    core::{convert::TryFrom as _, fmt},
     gpu_alloc_types::{
-        AllocationFlags, DeviceProperties, MemoryDevice, MemoryPropertyFlags, MemoryType,
+        AllocationFlags, DedicatedAllocationInfo, DeviceDiagnostic, DeviceMapError,
+        DeviceProperties, MappedMemoryRange, MemoryDevice, MemoryPropertyFlags, MemoryType,
         OutOfMemory,
     },
 };
 
+#[cfg(feature = "stats")]
+use crate::stats::SizeHistogram;
+
+#[cfg(all(feature = "stats", feature = "std"))]
+use crate::stats::{AllocLatency, TimedDevice};
+
+#[cfg(feature = "chrome-trace")]
+use crate::trace::AllocationTrace;
+
+#[cfg(feature = "metrics")]
+use crate::telemetry;
+
+#[cfg(feature = "poison")]
+use crate::poison;
+
+#[cfg(feature = "guard-bands")]
+use crate::guard;
+
+#[cfg(all(
+    target_has_atomic = "ptr",
+    any(target_has_atomic = "64", feature = "portable-atomic")
+))]
+use crate::budget::BudgetTracker;
+
+#[cfg(feature = "std")]
+use crate::{
+    observer::AllocEvent,
+    stats::{BlockAge, BlockAges, LeakReport, LiveBlocks},
+};
+
+/// Key of chunk in [`GpuAllocator::dump`]: owning pool, strategy and chunk id.
+type ChunkKey = (Option<PoolId>, ChunkKind, u64);
+
 /// Memory allocator for Vulkan-like APIs.
+///
+/// Host memory is allocated only when a chunk is created
+/// or when bookkeeping grows past its previous peak number of blocks.
+/// Allocating and deallocating blocks in steady state, as well as `cleanup`,
+/// performs no host allocations unless advisor or allocation trace is enabled.
 #[derive(Debug)]
 pub struct GpuAllocator<M> {
-    dedicated_threshold: u64,
-    preferred_dedicated_threshold: u64,
-    transient_dedicated_threshold: u64,
     max_memory_allocation_size: u64,
     memory_for_usage: MemoryForUsage,
-    memory_types: Box<[MemoryType]>,
-    memory_heaps: Box<[Heap]>,
-    allocations_remains: u32,
+    memory_types: Table<MemoryType>,
+    memory_heaps: Table<Heap>,
+    allocations_remains: AtomicU32,
     non_coherent_atom_mask: u64,
-    starting_free_list_chunk: u64,
-    final_free_list_chunk: u64,
-    minimal_buddy_size: u64,
-    initial_buddy_dedicated_size: u64,
+    buffer_image_granularity_mask: u64,
     buffer_device_address: bool,
+    bar_types: u32,
+    bar_usage_limit: u8,
+
+    /// Strategy allocators of the allocator itself,
+    /// swapped with ones of a pool while the pool is used.
+    strategies: Strategies<M>,
+    block_cache: BlockCache<M>,
+    pools: Vec<Option<Pool<M>>>,
+    /// Pool whose strategies are currently swapped in.
+    current_pool: Option<PoolId>,
+    /// Last frame started with `begin_frame` and whether it is not ended yet.
+    frame: Option<(u64, bool)>,
+    /// Blocks deallocated with `dealloc_deferred` paired with timeline values they wait for.
+    deferred: Vec<(u64, MemoryBlock<M>)>,
+
+    type_stats: Table<TypeStats>,
+    concurrent_stats: Table<ConcurrentStats>,
+    attempts: Table<MemoryTypeAttempt>,
+    last_failure: Option<FailureDetails>,
+    tags: BTreeMap<&'static str, TagUsage>,
+    registry: Registry,
+    advisor: Option<Advisor>,
+    observer: ObserverSlot,
+    selector: SelectorSlot,
+
+    #[cfg(feature = "guard-bands")]
+    guard_band: u64,
+
+    #[cfg(feature = "debug-checks")]
+    instance: u64,
+
+    #[cfg(feature = "stats")]
+    size_histograms: Box<[SizeHistogram]>,
+
+    #[cfg(all(feature = "stats", feature = "std"))]
+    latency: AllocLatency,
 
-    buddy_allocators: Box<[Option<BuddyAllocator<M>>]>,
-    freelist_allocators: Box<[Option<FreeListAllocator<M>>]>,
+    #[cfg(feature = "chrome-trace")]
+    trace: Option<AllocationTrace>,
 }
 
 /// Hints for allocator to decide on allocation strategy.
@@ -78,39 +167,75 @@ where
         );
 
         GpuAllocator {
-            dedicated_threshold: config.dedicated_threshold,
-            preferred_dedicated_threshold: config
-                .preferred_dedicated_threshold
-                .min(config.dedicated_threshold),
-
-            transient_dedicated_threshold: config
-                .transient_dedicated_threshold
-                .max(config.dedicated_threshold),
-
             max_memory_allocation_size: props.max_memory_allocation_size,
 
-            memory_for_usage: MemoryForUsage::new(props.memory_types.as_ref()),
+            memory_for_usage: MemoryForUsage::new(props.memory_types.as_ref(), &DefaultScoring),
 
             memory_types: props.memory_types.as_ref().iter().copied().collect(),
             memory_heaps: props
                 .memory_heaps
                 .as_ref()
                 .iter()
-                .map(|heap| Heap::new(heap.size))
+                .map(|heap| Heap::new(heap.size, heap.available()))
                 .collect(),
 
             buffer_device_address: props.buffer_device_address,
+            bar_types: small_bar_types(&props, config.bar_heap_size),
+            bar_usage_limit: config.bar_usage_limit.min(100),
 
-            allocations_remains: props.max_memory_allocation_count,
+            allocations_remains: AtomicU32::new(props.max_memory_allocation_count),
             non_coherent_atom_mask: props.non_coherent_atom_size - 1,
+            buffer_image_granularity_mask: props.buffer_image_granularity.max(1) - 1,
+
+            strategies: Strategies::new(&config, props.memory_types.as_ref().len()),
+            block_cache: BlockCache::new(props.memory_types.as_ref().len()),
+            pools: Vec::new(),
+            current_pool: None,
+            frame: None,
+            deferred: Vec::new(),
+
+            type_stats: props
+                .memory_types
+                .as_ref()
+                .iter()
+                .map(|memory_type| TypeStats {
+                    heap: memory_type.heap,
+                    ..TypeStats::default()
+                })
+                .collect(),
+            concurrent_stats: props
+                .memory_types
+                .as_ref()
+                .iter()
+                .map(|_| ConcurrentStats::default())
+                .collect(),
+            attempts: Table::new(),
+            last_failure: None,
+            tags: BTreeMap::new(),
+            registry: Registry::new(),
+            advisor: None,
+            observer: ObserverSlot::default(),
+            selector: SelectorSlot::default(),
+
+            #[cfg(feature = "guard-bands")]
+            guard_band: 0,
+
+            #[cfg(feature = "debug-checks")]
+            instance: crate::util::instance_id(),
+
+            #[cfg(feature = "stats")]
+            size_histograms: props
+                .memory_types
+                .as_ref()
+                .iter()
+                .map(|_| SizeHistogram::default())
+                .collect(),
 
-            starting_free_list_chunk: config.starting_free_list_chunk,
-            final_free_list_chunk: config.final_free_list_chunk,
-            minimal_buddy_size: config.minimal_buddy_size,
-            initial_buddy_dedicated_size: config.initial_buddy_dedicated_size,
+            #[cfg(all(feature = "stats", feature = "std"))]
+            latency: AllocLatency::default(),
 
-            buddy_allocators: props.memory_types.as_ref().iter().map(|_| None).collect(),
-            freelist_allocators: props.memory_types.as_ref().iter().map(|_| None).collect(),
+            #[cfg(feature = "chrome-trace")]
+            trace: None,
         }
     }
 
@@ -122,6 +247,7 @@ where
     /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
     ///   and memory blocks allocated from it.
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    #[cfg_attr(feature = "track-caller", track_caller)]
     pub unsafe fn alloc<MD>(
         &mut self,
         device: &impl AsRef<MD>,
@@ -130,7 +256,7 @@ where
     where
         MD: MemoryDevice<M>,
     {
-        self.alloc_internal(device.as_ref(), request, None)
+        self.alloc_internal(device.as_ref(), request, None, None)
     }
 
     /// Allocates memory block from specified `device` according to the `request`.
@@ -144,6 +270,7 @@ where
     /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
     ///   and memory blocks allocated from it.
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    #[cfg_attr(feature = "track-caller", track_caller)]
     pub unsafe fn alloc_with_dedicated<MD>(
         &mut self,
         device: &impl AsRef<MD>,
@@ -153,459 +280,4100 @@ where
     where
         MD: MemoryDevice<M>,
     {
-        self.alloc_internal(device.as_ref(), request, Some(dedicated))
+        self.alloc_internal(device.as_ref(), request, Some(dedicated), None)
     }
 
-    unsafe fn alloc_internal(
+    /// Allocates memory block from specified `device` according to the `request`
+    /// and marks it with `tag`.
+    ///
+    /// Tagged blocks are accounted per tag in [`GpuAllocator::stats`].
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub unsafe fn alloc_tagged<MD>(
         &mut self,
-        device: &impl MemoryDevice<M>,
-        mut request: Request,
-        dedicated: Option<Dedicated>,
-    ) -> Result<MemoryBlock<M>, AllocationError> {
-        enum Strategy {
-            Buddy,
-            Dedicated,
-            FreeList,
-        }
+        device: &impl AsRef<MD>,
+        request: Request,
+        tag: &'static str,
+    ) -> Result<MemoryBlock<M>, AllocationError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        self.alloc_internal(device.as_ref(), request, None, Some(tag))
+    }
 
-        request.usage = with_implicit_usage_flags(request.usage);
+    /// Allocates memory block from specified `device` according to the `request`
+    /// and marks it with `tag`.
+    /// This function allows user to force specific allocation strategy.
+    /// Improper use can lead to suboptimal performance or too large overhead.
+    /// Prefer `GpuAllocator::alloc_tagged` if doubt.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub unsafe fn alloc_with_dedicated_tagged<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        request: Request,
+        dedicated: Dedicated,
+        tag: &'static str,
+    ) -> Result<MemoryBlock<M>, AllocationError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        self.alloc_internal(device.as_ref(), request, Some(dedicated), Some(tag))
+    }
 
-        if request.usage.contains(UsageFlags::DEVICE_ADDRESS) {
-            assert!(self.buffer_device_address, "`DEVICE_ADDRESS` cannot be requested when `DeviceProperties::buffer_device_address` is false");
-        }
+    /// Allocates dedicated memory object for single buffer or image described by `info`
+    /// according to the `request`.
+    ///
+    /// Device receives `info` through [`MemoryDevice::allocate_dedicated_memory`],
+    /// so resources for which driver requires or prefers dedicated allocation
+    /// get memory allocated for them specifically.
+    /// Memory object is not shared with other resources, so `request.kind` is ignored.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    /// * Resource described by `info` must be valid, must not be bound to memory
+    ///   and `request.size` must be equal to its memory requirements.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub unsafe fn alloc_dedicated_for<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        request: Request,
+        info: DedicatedAllocationInfo,
+    ) -> Result<MemoryBlock<M>, AllocationError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        let device = DedicatedDevice {
+            device: device.as_ref(),
+            info,
+        };
 
-        if request.size > self.max_memory_allocation_size {
-            return Err(AllocationError::OutOfDeviceMemory);
-        }
+        let request = Request {
+            kind: ResourceKind::Linear,
+            ..request
+        };
 
-        if let Some(Dedicated::Required) = dedicated {
-            if self.allocations_remains == 0 {
-                return Err(AllocationError::TooManyObjects);
+        self.alloc_internal(&device, request, Some(Dedicated::Required), None)
+    }
+
+    /// Creates memory pool with its own chunks and configuration.
+    ///
+    /// Blocks allocated with [`GpuAllocator::alloc_in_pool`] are sub-allocated from chunks of the pool,
+    /// so subsystems with different allocation patterns do not fragment each other's memory.
+    /// Heap budgets and memory object limit are shared with the allocator.
+    pub fn create_pool(&mut self, config: PoolConfig) -> PoolId {
+        let id = PoolId(u32::try_from(self.pools.len()).expect("Too many pools"));
+        self.pools.push(Some(Pool {
+            strategies: Strategies::new(&config.config, self.memory_types.len()),
+            memory_types: config.memory_types,
+            budget: config.budget,
+            used: 0,
+            blocks: 0,
+        }));
+        id
+    }
+
+    /// Allocates memory block from specified `device` according to the `request`,
+    /// from chunks of the `pool`.
+    ///
+    /// Fails with [`AllocationError::OutOfBudget`] if block would exceed budget of the pool.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `pool` was destroyed.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub unsafe fn alloc_in_pool<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        pool: PoolId,
+        request: Request,
+    ) -> Result<MemoryBlock<M>, AllocationError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        profile_scope!("GpuAllocator::alloc_in_pool");
+
+        let state = self.pool(pool);
+        let request = Request {
+            memory_types: request.memory_types & state.memory_types,
+            ..request
+        };
+
+        if let Some(budget) = state.budget {
+            if !matches!(state.used.checked_add(request.size), Some(used) if used <= budget) {
+                return Err(AllocationError::OutOfBudget);
             }
         }
 
-        if 0 == self.memory_for_usage.mask(request.usage) & request.memory_types {
-            #[cfg(feature = "tracing")]
-            tracing::error!(
-                "Cannot serve request {:?}, no memory among bitset `{}` support usage {:?}",
-                request,
-                request.memory_types,
-                request.usage
-            );
+        let block = self.with_pool(pool, |allocator| {
+            allocator.alloc_internal(device.as_ref(), request, None, None)
+        })?;
 
-            return Err(AllocationError::NoCompatibleMemoryTypes);
+        let state = self.pools[pool.0 as usize].as_mut().expect("Pool exists");
+        state.used += block.allocated_range().1;
+        state.blocks += 1;
+
+        // Block may occupy more than requested, charged size is checked again.
+        if let Some(budget) = state.budget {
+            if state.used > budget {
+                self.dealloc(device, block);
+                return Err(AllocationError::OutOfBudget);
+            }
         }
+        Ok(block)
+    }
 
-        let transient = request.usage.contains(UsageFlags::TRANSIENT);
+    /// Returns number of bytes occupied by live blocks of the `pool`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `pool` was destroyed.
+    pub fn pool_usage(&self, pool: PoolId) -> u64 {
+        self.pool(pool).used
+    }
 
-        for &index in self.memory_for_usage.types(request.usage) {
-            if 0 == request.memory_types & (1 << index) {
-                // Skip memory type incompatible with the request.
-                continue;
+    /// Destroys memory pool, releasing its chunks.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `pool` was destroyed or has live blocks.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn destroy_pool<MD>(&mut self, device: &impl AsRef<MD>, pool: PoolId)
+    where
+        MD: MemoryDevice<M>,
+    {
+        let state = self.pool(pool);
+        assert_eq!(
+            state.blocks, 0,
+            "All blocks of {} must be deallocated before it is destroyed",
+            pool
+        );
+
+        // Retained buddy chunks are released after `buddy_chunk_keep_alive` cleanups.
+        let cleanups = state.strategies.buddy_chunk_keep_alive.max(1);
+        let device = device.as_ref();
+        self.with_pool(pool, |allocator| {
+            for _ in 0..cleanups {
+                allocator.cleanup_strategies(device);
             }
+        });
 
-            let memory_type = &self.memory_types[index as usize];
-            let heap = memory_type.heap;
-            let heap = &mut self.memory_heaps[heap as usize];
+        self.pools[pool.0 as usize] = None;
+    }
 
-            if request.size > heap.size() {
-                // Impossible to use memory type from this heap.
-                continue;
-            }
+    fn pool(&self, pool: PoolId) -> &Pool<M> {
+        self.pools
+            .get(pool.0 as usize)
+            .and_then(Option::as_ref)
+            .expect("Pool does not exist")
+    }
 
-            let atom_mask = if host_visible_non_coherent(memory_type.props) {
-                self.non_coherent_atom_mask
-            } else {
-                0
-            };
+    /// Swaps strategy allocators of the `pool` in for the duration of `f`.
+    fn with_pool<R>(&mut self, pool: PoolId, f: impl FnOnce(&mut Self) -> R) -> R {
+        let state = self.pools[pool.0 as usize]
+            .as_mut()
+            .expect("Pool does not exist");
+        core::mem::swap(&mut self.strategies, &mut state.strategies);
+        self.current_pool = Some(pool);
 
-            let flags = if self.buffer_device_address {
-                AllocationFlags::DEVICE_ADDRESS
-            } else {
-                AllocationFlags::empty()
-            };
+        let result = f(self);
 
-            let strategy = match (dedicated, transient) {
-                (Some(Dedicated::Required), _) => Strategy::Dedicated,
-                (Some(Dedicated::Preferred), _)
-                    if request.size >= self.preferred_dedicated_threshold =>
-                {
-                    Strategy::Dedicated
-                }
-                (_, true) => {
-                    let threshold = self.transient_dedicated_threshold.min(heap.size() / 32);
+        self.current_pool = None;
+        let state = self.pools[pool.0 as usize].as_mut().expect("Pool exists");
+        core::mem::swap(&mut self.strategies, &mut state.strategies);
+        result
+    }
 
-                    if request.size < threshold {
-                        Strategy::FreeList
-                    } else {
-                        Strategy::Dedicated
-                    }
-                }
-                (_, false) => {
-                    let threshold = self.dedicated_threshold.min(heap.size() / 32);
+    /// Allocates memory block of any size between `request.size` and `max_size`
+    /// that can be served from free space of existing memory objects, preferring larger.
+    /// Returns the block and size granted.
+    ///
+    /// Streaming caches and similar users can adapt their batch sizes
+    /// to whatever free space exists instead of forcing new device allocations.
+    /// New memory object is never allocated, so device is not needed.
+    /// When no free space fits even `request.size` bytes,
+    /// returns `AllocationError::OutOfDeviceMemory`.
+    /// Use [`GpuAllocator::alloc`] to fall back to allocating new memory object.
+    ///
+    /// Blocks served from free space are not surrounded by guard bands.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `request.size` is greater than `max_size`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self)))]
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub fn alloc_in_range(
+        &mut self,
+        request: Request,
+        max_size: u64,
+    ) -> Result<(MemoryBlock<M>, u64), AllocationError> {
+        assert!(
+            request.size <= max_size,
+            "Minimal size of the range must not exceed maximal size"
+        );
 
-                    if request.size < threshold {
-                        Strategy::Buddy
-                    } else {
-                        Strategy::Dedicated
-                    }
-                }
-            };
+        let existing = Request {
+            usage: with_implicit_usage_flags(request.usage),
+            ..request
+        };
 
-            match strategy {
-                Strategy::Dedicated => {
-                    #[cfg(feature = "tracing")]
-                    tracing::debug!(
-                        "Allocating memory object `{}@{:?}`",
-                        request.size,
-                        memory_type
-                    );
+        if existing.usage.contains(UsageFlags::DEVICE_ADDRESS) {
+            assert!(self.buffer_device_address, "`DEVICE_ADDRESS` cannot be requested when `DeviceProperties::buffer_device_address` is false");
+        }
 
-                    match device.allocate_memory(request.size, index, flags) {
-                        Ok(memory) => {
-                            self.allocations_remains -= 1;
-                            heap.alloc(request.size);
+        if !valid_align_mask(request.align_mask) {
+            return Err(AllocationError::InvalidAlignment);
+        }
 
-                            return Ok(MemoryBlock::new(
-                                index,
-                                memory_type.props,
-                                0,
-                                request.size,
-                                atom_mask,
-                                MemoryBlockFlavor::Dedicated { memory },
-                            ));
-                        }
-                        Err(OutOfMemory::OutOfDeviceMemory) => continue,
-                        Err(OutOfMemory::OutOfHostMemory) => {
-                            return Err(AllocationError::OutOfHostMemory)
-                        }
-                    }
-                }
-                Strategy::FreeList => {
-                    let allocator = match &mut self.freelist_allocators[index as usize] {
-                        Some(allocator) => allocator,
-                        slot => {
-                            let starting_free_list_chunk = match align_down(
-                                self.starting_free_list_chunk.min(heap.size() / 32),
-                                atom_mask,
-                            ) {
-                                0 => atom_mask,
-                                other => other,
-                            };
-
-                            let final_free_list_chunk = match align_down(
-                                self.final_free_list_chunk
-                                    .max(self.starting_free_list_chunk)
-                                    .max(self.transient_dedicated_threshold)
-                                    .min(heap.size() / 32),
-                                atom_mask,
-                            ) {
-                                0 => atom_mask,
-                                other => other,
-                            };
-
-                            slot.get_or_insert(FreeListAllocator::new(
-                                starting_free_list_chunk,
-                                final_free_list_chunk,
-                                index,
-                                memory_type.props,
-                                if host_visible_non_coherent(memory_type.props) {
-                                    self.non_coherent_atom_mask
-                                } else {
-                                    0
-                                },
-                            ))
-                        }
-                    };
-                    let result = allocator.alloc(
-                        device,
-                        request.size,
-                        request.align_mask,
-                        flags,
-                        heap,
-                        &mut self.allocations_remains,
-                    );
+        // Padded blocks are not extended, as that would break padding to whole pages.
+        let existing = self.apply_granularity(existing)?;
+        let max_size = match request.kind {
+            ResourceKind::NonLinear if self.buffer_image_granularity_mask != 0 => existing.size,
+            _ => max_size,
+        };
 
-                    match result {
-                        Ok(block) => {
-                            return Ok(MemoryBlock::new(
-                                index,
-                                memory_type.props,
-                                block.offset,
-                                block.size,
-                                atom_mask,
-                                MemoryBlockFlavor::FreeList {
-                                    chunk: block.chunk,
-                                    ptr: block.ptr,
-                                    memory: block.memory,
-                                },
-                            ))
-                        }
-                        Err(AllocationError::OutOfDeviceMemory) => continue,
-                        Err(err) => return Err(err),
-                    }
-                }
+        let mut selection = self.select_memory_types(&existing);
 
-                Strategy::Buddy => {
-                    let allocator = match &mut self.buddy_allocators[index as usize] {
-                        Some(allocator) => allocator,
-                        slot => {
-                            let minimal_buddy_size = self
-                                .minimal_buddy_size
-                                .min(heap.size() / 1024)
-                                .next_power_of_two();
-
-                            let initial_buddy_dedicated_size = self
-                                .initial_buddy_dedicated_size
-                                .min(heap.size() / 32)
-                                .next_power_of_two();
-
-                            slot.get_or_insert(BuddyAllocator::new(
-                                minimal_buddy_size,
-                                initial_buddy_dedicated_size,
-                                index,
-                                memory_type.props,
-                                if host_visible_non_coherent(memory_type.props) {
-                                    self.non_coherent_atom_mask
-                                } else {
-                                    0
-                                },
-                            ))
-                        }
-                    };
-                    let result = allocator.alloc(
-                        device,
-                        request.size,
-                        request.align_mask,
-                        flags,
-                        heap,
-                        &mut self.allocations_remains,
-                    );
+        for index in selection.sorted() {
+            let buddy = self.strategies.buddy_allocators[index as usize]
+                .as_ref()
+                .and_then(|allocator| {
+                    allocator.existing_block_size(existing.size, max_size, existing.align_mask)
+                });
 
-                    match result {
-                        Ok(block) => {
-                            return Ok(MemoryBlock::new(
-                                index,
-                                memory_type.props,
-                                block.offset,
-                                block.size,
-                                atom_mask,
-                                MemoryBlockFlavor::Buddy {
-                                    chunk: block.chunk,
-                                    ptr: block.ptr,
-                                    index: block.index,
-                                    memory: block.memory,
-                                },
-                            ))
-                        }
-                        Err(AllocationError::OutOfDeviceMemory) => continue,
-                        Err(err) => return Err(err),
-                    }
-                }
-            }
+            let free_list = self.strategies.freelist_allocators[index as usize]
+                .as_ref()
+                .and_then(|allocator| {
+                    allocator.existing_block_size(existing.size, max_size, existing.align_mask)
+                });
+
+            let kind = match (buddy, free_list) {
+                (None, None) => continue,
+                (Some(buddy), free_list) if buddy >= free_list.unwrap_or(0) => ChunkKind::Buddy,
+                _ => ChunkKind::FreeList,
+            };
+
+            // Free block is carved from existing chunk without touching device memory.
+            return Ok(unsafe {
+                self.alloc_existing(index, kind, &existing, max_size, None)
+                    .expect("Free block was found")
+            });
         }
 
         Err(AllocationError::OutOfDeviceMemory)
     }
 
-    /// Creates a memory block from an existing memory allocation, transferring ownership to the allocator.
+    /// Allocates memory block of `request.size` bytes or smaller, but not smaller than `min_size`.
+    /// Returns the block and size granted.
     ///
-    /// This function allows the [`GpuAllocator`] to manage memory allocated outside of the typical
-    /// [`GpuAllocator::alloc`] family of functions.
+    /// When allocation fails with [`AllocationError::OutOfDeviceMemory`]
+    /// or [`AllocationError::OutOfBudget`], requested size is halved and allocation is retried,
+    /// until halved size would be smaller than `min_size`.
+    /// Then last error is returned.
     ///
-    /// # Usage
+    /// Useful for streaming caches and scratch pools that can work with smaller buffers
+    /// when memory is scarce.
     ///
-    /// If you need to import external memory, such as a Win32 `HANDLE` or a Linux `dmabuf`, import the device
-    /// memory using the graphics api and platform dependent functions. Once that is done, call this function
-    /// to make the [`GpuAllocator`] take ownership of the imported memory.
+    /// # Panics
     ///
-    /// When calling this function, you **must** ensure there are [enough remaining allocations](GpuAllocator::remaining_allocations).
+    /// This function panics if `min_size` is greater than `request.size`.
     ///
     /// # Safety
     ///
-    /// - The `memory` must be allocated with the same device that was provided to create this [`GpuAllocator`]
-    ///   instance.
-    /// - The `memory` must be valid.
-    /// - The `props`, `offset` and `size` must match the properties, offset and size of the memory allocation.
-    /// - The memory must have been allocated with the specified `memory_type`.
-    /// - There must be enough remaining allocations.
-    /// - The memory allocation must not come from an existing memory block created by this allocator.
-    /// - The underlying memory object must be deallocated using the returned [`MemoryBlock`] with
-    ///   [`GpuAllocator::dealloc`].
-    pub unsafe fn import_memory(
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub unsafe fn alloc_at_most<MD>(
         &mut self,
-        memory: M,
-        memory_type: u32,
-        props: MemoryPropertyFlags,
-        offset: u64,
-        size: u64,
-    ) -> MemoryBlock<M> {
-        // Get the heap which the imported memory is from.
-        let heap = self
-            .memory_types
-            .get(memory_type as usize)
-            .expect("Invalid memory type specified when importing memory")
-            .heap;
-        let heap = &mut self.memory_heaps[heap as usize];
-
-        #[cfg(feature = "tracing")]
-        tracing::debug!(
-            "Importing memory object {:?} `{}@{:?}`",
-            memory,
-            size,
-            memory_type
-        );
-
-        assert_ne!(
-            self.allocations_remains, 0,
-            "Out of allocations when importing a memory block. Ensure you check GpuAllocator::remaining_allocations before import."
+        device: &impl AsRef<MD>,
+        mut request: Request,
+        min_size: u64,
+    ) -> Result<(MemoryBlock<M>, u64), AllocationError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        assert!(
+            min_size <= request.size,
+            "Minimal size must not exceed requested size"
         );
-        self.allocations_remains -= 1;
-
-        let atom_mask = if host_visible_non_coherent(props) {
-            self.non_coherent_atom_mask
-        } else {
-            0
-        };
 
-        heap.alloc(size);
+        let min_size = min_size.max(1);
 
-        MemoryBlock::new(
-            memory_type,
-            props,
-            offset,
-            size,
-            atom_mask,
-            MemoryBlockFlavor::Dedicated { memory },
-        )
+        loop {
+            match self.alloc_internal(device.as_ref(), request, None, None) {
+                Ok(block) => return Ok((block, request.size)),
+                Err(AllocationError::OutOfDeviceMemory | AllocationError::OutOfBudget)
+                    if request.size / 2 >= min_size =>
+                {
+                    request.size /= 2;
+                }
+                Err(err) => return Err(err),
+            }
+        }
     }
 
-    /// Deallocates memory block previously allocated from this `GpuAllocator` instance.
+    /// Allocates memory blocks for all `requests` from single memory object.
+    ///
+    /// Some interop and descriptor buffer schemes require set of related resources
+    /// to be bound to the same memory object.
+    /// This function allocates new memory object of memory type compatible with every request
+    /// and sub-allocates blocks from it, returning them in order of `requests`.
+    /// Either all blocks are allocated or none.
+    /// Memory type selector ranks memory types for single request combining all `requests`,
+    /// which is also reported by [`GpuAllocator::last_failure`] when allocation fails.
+    ///
+    /// Blocks are deallocated individually with [`GpuAllocator::dealloc`].
+    /// Memory object is released after all of them are deallocated.
     ///
     /// # Safety
     ///
-    /// * Memory block must have been allocated by this `GpuAllocator` instance
-    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
     /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
-    ///   and memory blocks allocated from it
+    ///   and memory blocks allocated from it.
     #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
-    pub unsafe fn dealloc<MD>(&mut self, device: &impl AsRef<MD>, block: MemoryBlock<M>)
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub unsafe fn alloc_group<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        requests: &[Request],
+    ) -> Result<Vec<MemoryBlock<M>>, AllocationError>
     where
         MD: MemoryDevice<M>,
     {
-        let device = device.as_ref();
-        let memory_type = block.memory_type();
-        let offset = block.offset();
-        let size = block.size();
-        let flavor = block.deallocate();
-        match flavor {
-            MemoryBlockFlavor::Dedicated { memory } => {
-                let heap = self.memory_types[memory_type as usize].heap;
-                device.deallocate_memory(memory);
-                self.allocations_remains += 1;
-                self.memory_heaps[heap as usize].dealloc(size);
+        profile_scope!("GpuAllocator::alloc_group");
+
+        if requests.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Single request describing the whole group, for memory type selection and failure reports.
+        let merged = requests.iter().fold(
+            Request {
+                size: 0,
+                align_mask: 0,
+                usage: UsageFlags::empty(),
+                memory_types: !0,
+                kind: ResourceKind::Linear,
+            },
+            |merged, request| Request {
+                size: merged.size.saturating_add(request.size),
+                align_mask: merged.align_mask | request.align_mask,
+                usage: merged.usage | request.usage,
+                memory_types: merged.memory_types & request.memory_types,
+                kind: match request.kind {
+                    ResourceKind::NonLinear => ResourceKind::NonLinear,
+                    ResourceKind::Linear => merged.kind,
+                },
+            },
+        );
+
+        self.attempts.clear();
+        let diagnosed = DiagnosedDevice::new(device.as_ref());
+        let result = self.alloc_group_blocks(&diagnosed, requests, merged);
+
+        if let Err(error) = result {
+            self.record_failure(merged, None, None, error, diagnosed.take_diagnostic());
+        }
+        result
+    }
+
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    unsafe fn alloc_group_blocks(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        requests: &[Request],
+        merged: Request,
+    ) -> Result<Vec<MemoryBlock<M>>, AllocationError> {
+        let mut layout = Vec::with_capacity(requests.len());
+
+        for request in requests {
+            let request_usage = with_implicit_usage_flags(request.usage);
+
+            if request_usage.contains(UsageFlags::DEVICE_ADDRESS) {
+                assert!(self.buffer_device_address, "`DEVICE_ADDRESS` cannot be requested when `DeviceProperties::buffer_device_address` is false");
             }
-            MemoryBlockFlavor::Buddy {
-                chunk,
-                ptr,
-                index,
-                memory,
-            } => {
-                let heap = self.memory_types[memory_type as usize].heap;
-                let heap = &mut self.memory_heaps[heap as usize];
 
-                let allocator = self.buddy_allocators[memory_type as usize]
-                    .as_mut()
-                    .expect("Allocator should exist");
+            if !valid_align_mask(request.align_mask) {
+                return Err(AllocationError::InvalidAlignment);
+            }
 
-                allocator.dealloc(
-                    device,
-                    BuddyBlock {
-                        memory,
-                        ptr,
-                        offset,
-                        size,
-                        chunk,
-                        index,
-                    },
-                    heap,
-                    &mut self.allocations_remains,
-                );
+            let padded = self.apply_granularity(*request)?;
+            layout.push((padded.size, padded.align_mask));
+        }
+
+        // Memory type must support usage of every request.
+        let usage = with_implicit_usage_flags(merged.usage);
+        let memory_types = requests.iter().fold(merged.memory_types, |mask, request| {
+            mask & self
+                .memory_for_usage
+                .mask(with_implicit_usage_flags(request.usage))
+        });
+
+        if memory_types == 0 {
+            return Err(AllocationError::NoCompatibleMemoryTypes);
+        }
+
+        let mut selection = self.select_memory_types(&Request {
+            usage,
+            memory_types,
+            ..merged
+        });
+
+        if selection.is_empty() {
+            // Selector vetoed all compatible memory types.
+            return Err(AllocationError::NoCompatibleMemoryTypes);
+        }
+
+        let flags = if self.buffer_device_address {
+            AllocationFlags::DEVICE_ADDRESS
+        } else {
+            AllocationFlags::empty()
+        };
+
+        let mut error = AllocationError::OutOfDeviceMemory;
+        let mut allocated = None;
+
+        for index in selection.sorted() {
+            let memory_type = &self.memory_types[index as usize];
+            let heap = &mut self.memory_heaps[memory_type.heap as usize];
+
+            let atom_mask = if host_visible_non_coherent(memory_type.props) {
+                self.non_coherent_atom_mask
+            } else {
+                0
+            };
+
+            let allocator = match &mut self.strategies.freelist_allocators[index as usize] {
+                Some(allocator) => allocator,
+                slot => slot.get_or_insert(new_free_list_allocator(
+                    self.strategies.starting_free_list_chunk,
+                    self.strategies
+                        .final_free_list_chunk
+                        .max(self.strategies.starting_free_list_chunk)
+                        .max(self.strategies.transient_dedicated_threshold),
+                    heap.size(),
+                    index,
+                    memory_type.props,
+                    atom_mask,
+                )),
+            };
+
+            let result = allocator.alloc_group(
+                device,
+                &layout,
+                self.max_memory_allocation_size.min(heap.size()),
+                flags,
+                heap,
+                self.allocations_remains.get_mut(),
+                self.observer.get(),
+            );
+
+            match result {
+                Ok(blocks) => {
+                    allocated = Some((index, memory_type.props, atom_mask, blocks));
+                    break;
+                }
+                Err(err) => {
+                    self.attempts.push(MemoryTypeAttempt {
+                        memory_type: index,
+                        heap: memory_type.heap,
+                        outcome: AttemptOutcome::Failed {
+                            strategy: ChunkKind::FreeList,
+                            error: err,
+                        },
+                    });
+
+                    match err {
+                        AllocationError::OutOfBudget => error = AllocationError::OutOfBudget,
+                        AllocationError::OutOfDeviceMemory => {}
+                        err => return Err(err),
+                    }
+                }
             }
-            MemoryBlockFlavor::FreeList { chunk, ptr, memory } => {
-                let heap = self.memory_types[memory_type as usize].heap;
-                let heap = &mut self.memory_heaps[heap as usize];
+        }
 
-                let allocator = self.freelist_allocators[memory_type as usize]
-                    .as_mut()
-                    .expect("Allocator should exist");
+        let (index, props, atom_mask, blocks) = allocated.ok_or(error)?;
+        let mut group = Vec::with_capacity(blocks.len());
 
-                allocator.dealloc(
-                    device,
-                    FreeListBlock {
-                        memory,
-                        ptr,
-                        chunk,
-                        offset,
-                        size,
-                    },
-                    heap,
-                    &mut self.allocations_remains,
+        for (block, request) in blocks.into_iter().zip(requests) {
+            self.type_stats[index as usize]
+                .free_list
+                .alloc(request.size, block.size);
+
+            #[cfg(feature = "stats")]
+            self.size_histograms[index as usize].record(request.size);
+
+            let block = MemoryBlock::new(
+                index,
+                props,
+                block.offset,
+                block.size,
+                request.size,
+                atom_mask,
+                MemoryBlockFlavor::FreeList {
+                    chunk: block.chunk,
+                    ptr: block.ptr,
+                    memory: block.memory,
+                },
+            );
+
+            let block = self.register(block, Some(request), None);
+
+            if let Some(advisor) = &mut self.advisor {
+                advisor.alloc(
+                    block.id(),
+                    index,
+                    request.size,
+                    request.usage.contains(UsageFlags::TRANSIENT),
                 );
             }
+
+            group.push(block);
         }
-    }
 
-    /// Returns the maximum allocation size supported.
-    pub fn max_allocation_size(&self) -> u64 {
-        self.max_memory_allocation_size
-    }
+        if let Some(advisor) = &mut self.advisor {
+            advisor.chunks_created(index, 1);
+        }
 
-    /// Returns the number of remaining available allocations.
-    ///
-    /// This may be useful if you need know if the allocator can allocate a number of allocations ahead of
-    /// time. This function is also useful for ensuring you do not allocate too much memory outside allocator
-    /// (such as external memory).
-    pub fn remaining_allocations(&self) -> u32 {
-        self.allocations_remains
+        Ok(group)
     }
 
-    /// Sets the number of remaining available allocations.
-    ///
-    /// # Safety
-    ///
-    /// The caller is responsible for ensuring the number of remaining allocations does not exceed how many
-    /// remaining allocations there actually are on the memory device.
-    pub unsafe fn set_remaining_allocations(&mut self, remaining: u32) {
-        self.allocations_remains = remaining;
-    }
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    unsafe fn alloc_internal(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        request: Request,
+        dedicated: Option<Dedicated>,
+        tag: Option<&'static str>,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        profile_scope!("GpuAllocator::alloc");
 
-    /// Deallocates leftover memory objects.
-    /// Should be used before dropping.
-    ///
-    /// # Safety
-    ///
-    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
-    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
-    ///   and memory blocks allocated from it
-    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
-    pub unsafe fn cleanup<MD>(&mut self, device: &impl AsRef<MD>)
+        let allocations_remains = *self.allocations_remains.get_mut();
+
+        let diagnosed = DiagnosedDevice::new(device);
+        let device = &diagnosed;
+
+        #[cfg(all(feature = "stats", feature = "std"))]
+        let (device, start) = (&TimedDevice::new(device), crate::time::Instant::now());
+
+        let result = self.alloc_block(device, request, dedicated);
+
+        #[cfg(all(feature = "stats", feature = "std"))]
+        self.latency.record(start.elapsed(), device.elapsed());
+
+        if let Err(error) = result {
+            self.record_failure(request, dedicated, tag, error, diagnosed.take_diagnostic());
+        }
+
+        let block = self.register(result?, Some(&request), tag);
+
+        if let Some(advisor) = &mut self.advisor {
+            let memory_type = block.memory_type();
+            let mut created =
+                allocations_remains.saturating_sub(*self.allocations_remains.get_mut());
+
+            if let MemoryBlockFlavor::Dedicated { .. } = block.flavor() {
+                created = created.saturating_sub(1);
+                if dedicated != Some(Dedicated::Required)
+                    && !self.strategies.force_dedicated
+                    && request.size < self.strategies.dedicated_threshold
+                {
+                    advisor.small_dedicated(memory_type, request.size);
+                }
+            }
+
+            advisor.chunks_created(memory_type, created);
+            advisor.alloc(
+                block.id(),
+                memory_type,
+                request.size,
+                request.usage.contains(UsageFlags::TRANSIENT),
+            );
+        }
+
+        Ok(block)
+    }
+
+    /// Records details of failed allocation for [`GpuAllocator::last_failure`]
+    /// and reports it to observer.
+    fn record_failure(
+        &mut self,
+        request: Request,
+        dedicated: Option<Dedicated>,
+        tag: Option<&'static str>,
+        error: AllocationError,
+        diagnostic: Option<DeviceDiagnostic>,
+    ) {
+        #[cfg(feature = "metrics")]
+        telemetry::failed_allocation(error);
+
+        self.last_failure = Some(FailureDetails {
+            request,
+            dedicated,
+            tag,
+            error,
+            attempts: self.attempts.clone(),
+            heaps: self
+                .memory_heaps
+                .iter()
+                .map(|heap| HeapBudget {
+                    size: heap.size(),
+                    budget: heap.budget(),
+                    used: heap.used(),
+                })
+                .collect(),
+            remaining_allocations: *self.allocations_remains.get_mut(),
+            diagnostic,
+        });
+
+        if let Some(observer) = self.observer.get() {
+            observer.on_failure(&FailureEvent {
+                request,
+                dedicated,
+                tag,
+                error,
+            });
+        }
+    }
+
+    /// Allocates the largest block between `request.size` and `max_size`
+    /// from free space of existing chunks of specified memory type and kind.
+    /// Returns `None` if no free space fits.
+    ///
+    /// `request.usage` must already include implicit usage flags.
+    unsafe fn alloc_existing(
+        &mut self,
+        index: u32,
+        kind: ChunkKind,
+        request: &Request,
+        max_size: u64,
+        tag: Option<&'static str>,
+    ) -> Option<(MemoryBlock<M>, u64)> {
+        let block = self.reserve_existing(index, kind, request, max_size)?;
+        Some(self.register_existing(block, request, tag))
+    }
+
+    /// Reserves the largest block between `request.size` and `max_size`
+    /// from free space of existing chunks, like [`GpuAllocator::alloc_existing`],
+    /// without registering and accounting it.
+    ///
+    /// Block must be either registered with `register_existing`
+    /// or returned with `release_existing`.
+    unsafe fn reserve_existing(
+        &mut self,
+        index: u32,
+        kind: ChunkKind,
+        request: &Request,
+        max_size: u64,
+    ) -> Option<MemoryBlock<M>> {
+        let memory_type = &self.memory_types[index as usize];
+        let atom_mask = if host_visible_non_coherent(memory_type.props) {
+            self.non_coherent_atom_mask
+        } else {
+            0
+        };
+
+        match kind {
+            ChunkKind::Buddy => {
+                let allocator = self.strategies.buddy_allocators[index as usize].as_mut()?;
+                let block = allocator.alloc_existing(request.size, max_size, request.align_mask)?;
+
+                Some(MemoryBlock::new(
+                    index,
+                    memory_type.props,
+                    block.offset,
+                    block.size,
+                    block.size.min(max_size),
+                    atom_mask,
+                    MemoryBlockFlavor::Buddy {
+                        chunk: compact(block.chunk),
+                        ptr: block.ptr,
+                        index: compact(block.index),
+                        memory: block.memory,
+                    },
+                ))
+            }
+            ChunkKind::FreeList => {
+                let allocator = self.strategies.freelist_allocators[index as usize].as_mut()?;
+                let size =
+                    allocator.existing_block_size(request.size, max_size, request.align_mask)?;
+                let block = allocator
+                    .alloc_existing(request.size, max_size, request.align_mask)
+                    .expect("Free region was found");
+
+                Some(MemoryBlock::new(
+                    index,
+                    memory_type.props,
+                    block.offset,
+                    block.size,
+                    size.min(max_size),
+                    atom_mask,
+                    MemoryBlockFlavor::FreeList {
+                        chunk: block.chunk,
+                        ptr: block.ptr,
+                        memory: block.memory,
+                    },
+                ))
+            }
+            ChunkKind::Dedicated | ChunkKind::Ring => None,
+        }
+    }
+
+    /// Accounts block reserved with `reserve_existing` and records it in registry.
+    /// Returns the block and size granted.
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    fn register_existing(
+        &mut self,
+        block: MemoryBlock<M>,
+        request: &Request,
+        tag: Option<&'static str>,
+    ) -> (MemoryBlock<M>, u64) {
+        let index = block.memory_type();
+        let existing = Request {
+            size: block.requested_size(),
+            ..*request
+        };
+
+        let stats = &mut self.type_stats[index as usize];
+        match block.flavor() {
+            MemoryBlockFlavor::Buddy { .. } => stats.buddy.alloc(existing.size, block.size()),
+            _ => stats.free_list.alloc(existing.size, block.size()),
+        }
+
+        #[cfg(feature = "stats")]
+        self.size_histograms[index as usize].record(existing.size);
+
+        let block = self.register(block, Some(&existing), tag);
+
+        if let Some(advisor) = &mut self.advisor {
+            advisor.alloc(
+                block.id(),
+                index,
+                existing.size,
+                existing.usage.contains(UsageFlags::TRANSIENT),
+            );
+        }
+
+        (block, existing.size)
+    }
+
+    /// Returns block reserved with `reserve_existing` to its strategy allocator,
+    /// bypassing block cache.
+    unsafe fn release_existing(&mut self, device: &impl MemoryDevice<M>, block: MemoryBlock<M>) {
+        let memory_type = block.memory_type();
+        let (offset, size) = block.allocated_range();
+        let heap = self.memory_types[memory_type as usize].heap;
+        let heap = &mut self.memory_heaps[heap as usize];
+
+        match block.deallocate() {
+            MemoryBlockFlavor::Buddy {
+                chunk,
+                ptr,
+                index,
+                memory,
+            } => {
+                let allocator = self.strategies.buddy_allocators[memory_type as usize]
+                    .as_mut()
+                    .expect("Allocator should exist");
+
+                allocator.dealloc(
+                    device,
+                    BuddyBlock {
+                        memory,
+                        ptr,
+                        offset,
+                        size,
+                        chunk: uncompact(chunk),
+                        index: uncompact(index),
+                    },
+                    heap,
+                    self.allocations_remains.get_mut(),
+                    self.observer.get(),
+                );
+            }
+            MemoryBlockFlavor::FreeList { chunk, ptr, memory } => {
+                let allocator = self.strategies.freelist_allocators[memory_type as usize]
+                    .as_mut()
+                    .expect("Allocator should exist");
+
+                allocator.dealloc(
+                    device,
+                    FreeListBlock {
+                        memory,
+                        ptr,
+                        chunk,
+                        offset,
+                        size,
+                    },
+                    heap,
+                    self.allocations_remains.get_mut(),
+                    self.observer.get(),
+                );
+            }
+            MemoryBlockFlavor::Dedicated { .. } | MemoryBlockFlavor::Ring { .. } => {
+                unreachable!("Only buddy and free-list blocks are reserved from existing chunks")
+            }
+        }
+    }
+
+    /// Records new block in registry and tag usage.
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    fn register(
+        &mut self,
+        mut block: MemoryBlock<M>,
+        request: Option<&Request>,
+        tag: Option<&'static str>,
+    ) -> MemoryBlock<M> {
+        let chunk = match block.flavor() {
+            MemoryBlockFlavor::Dedicated { .. } => ChunkId::Dedicated,
+            MemoryBlockFlavor::Buddy { chunk, .. } => ChunkId::Buddy(uncompact(*chunk)),
+            MemoryBlockFlavor::FreeList { chunk, .. } => ChunkId::FreeList(*chunk),
+            MemoryBlockFlavor::Ring { chunk, .. } => ChunkId::Ring(*chunk),
+        };
+
+        let (offset, size) = block.allocated_range();
+        let record = BlockRecord {
+            memory_type: block.memory_type(),
+            chunk,
+            offset,
+            size,
+            requested_size: block.requested_size(),
+            align_mask: request.map_or(0, |request| request.align_mask),
+            usage: request.map_or(UsageFlags::empty(), |request| request.usage),
+            memory_types: request.map_or(1 << block.memory_type(), |request| request.memory_types),
+            resource: request.map_or(ResourceKind::Linear, |request| request.kind),
+            tag,
+            pool: self.current_pool,
+            #[cfg(feature = "std")]
+            created: crate::time::Instant::now(),
+            #[cfg(feature = "track-caller")]
+            location: core::panic::Location::caller(),
+        };
+        let (id, generation) = self.registry.insert(record);
+
+        if let Some(tag) = tag {
+            self.tags.entry(tag).or_default().alloc(size);
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            if chunk == ChunkId::Dedicated {
+                tracing::debug!(
+                    memory_type = block.memory_type(),
+                    strategy = chunk.strategy(),
+                    chunk = chunk.id(id),
+                    size = offset + size,
+                    "chunk created"
+                );
+            }
+
+            tracing::trace!(
+                id,
+                memory_type = block.memory_type(),
+                strategy = chunk.strategy(),
+                chunk = chunk.id(id),
+                offset,
+                size,
+                requested_size = block.requested_size(),
+                tag = tag.unwrap_or(""),
+                "block allocated"
+            );
+        }
+
+        let mut observer = self.observer.get();
+        if chunk == ChunkId::Dedicated {
+            observer::chunk_created(
+                &mut observer,
+                record.memory_type,
+                ChunkKind::Dedicated,
+                chunk.id(id),
+                record.offset + record.size,
+            );
+        }
+        if let Some(observer) = observer {
+            observer.on_alloc(&BlockEvent::new(id, &record));
+        }
+
+        #[cfg(feature = "chrome-trace")]
+        if let Some(trace) = &mut self.trace {
+            let heap = self.memory_types[block.memory_type() as usize].heap;
+            trace.alloc(id, block.memory_type(), size, tag);
+            trace.heap_usage(heap, self.memory_heaps[heap as usize].used());
+        }
+
+        #[cfg(feature = "metrics")]
+        self.publish_metrics(self.memory_types[block.memory_type() as usize].heap);
+
+        block.register(id, generation, tag);
+
+        #[cfg(feature = "debug-checks")]
+        let block = block.with_instance(self.instance);
+
+        block
+    }
+
+    /// Returns memory types compatible with the request,
+    /// ranked by memory type selector if set.
+    fn select_memory_types(&mut self, request: &Request) -> Selection {
+        let memory_types = self.reserve_bar(request.usage, request.memory_types);
+
+        let mut selection = Selection::new();
+        for index in self.memory_for_usage.types(request.usage) {
+            if 0 == memory_types & (1 << index) {
+                // Skip memory type incompatible with the request.
+                continue;
+            }
+
+            let rank = match &mut self.selector.0 {
+                None => 0,
+                Some(selector) => {
+                    let memory_type = &self.memory_types[index as usize];
+                    let heap = &self.memory_heaps[memory_type.heap as usize];
+                    let candidate = MemoryTypeCandidate {
+                        memory_type: index,
+                        props: memory_type.props,
+                        heap: memory_type.heap,
+                        heap_size: heap.size(),
+                        heap_used: heap.used(),
+                    };
+
+                    match selector.rank(request, &candidate) {
+                        Some(rank) => rank,
+                        None => continue,
+                    }
+                }
+            };
+            selection.push(rank, index);
+        }
+        selection
+    }
+
+    /// Removes small BAR memory types from `memory_types`
+    /// unless request is a fast upload and BAR heap has room left,
+    /// or no other memory type compatible with the usage remains.
+    fn reserve_bar(&self, usage: UsageFlags, memory_types: u32) -> u32 {
+        let memory_types = memory_types & self.memory_for_usage.mask(usage);
+        let bar = memory_types & self.bar_types;
+        if bar == 0 {
+            return memory_types;
+        }
+
+        let reserved = if usage.contains(UsageFlags::FAST_DEVICE_ACCESS | UsageFlags::HOST_ACCESS) {
+            // Fall back to other host-visible memory once BAR heap is filled up to the limit.
+            (0..32)
+                .filter(|&index| bar & (1 << index) != 0)
+                .filter(|&index| {
+                    let heap = &self.memory_heaps[self.memory_types[index].heap as usize];
+                    u128::from(heap.used()) * 100
+                        >= u128::from(heap.size()) * u128::from(self.bar_usage_limit)
+                })
+                .fold(0, |mask, index| mask | 1 << index)
+        } else {
+            bar
+        };
+
+        if memory_types & !reserved == 0 {
+            memory_types
+        } else {
+            memory_types & !reserved
+        }
+    }
+
+    /// Pads request for non-linear resource to whole pages of `bufferImageGranularity`,
+    /// so the block never shares a page with blocks for linear resources.
+    fn apply_granularity(&self, request: Request) -> Result<Request, AllocationError> {
+        let mask = self.buffer_image_granularity_mask;
+        if mask == 0 || request.kind == ResourceKind::Linear {
+            return Ok(request);
+        }
+
+        Ok(Request {
+            size: align_up(request.size, mask).ok_or(AllocationError::SizeOverflow)?,
+            align_mask: request.align_mask | mask,
+            ..request
+        })
+    }
+
+    unsafe fn alloc_block(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        mut request: Request,
+        dedicated: Option<Dedicated>,
+    ) -> Result<MemoryBlock<M>, AllocationError> {
+        enum Strategy {
+            Buddy,
+            Dedicated,
+            FreeList,
+            Ring(u64),
+        }
+
+        self.attempts.clear();
+        request.usage = with_implicit_usage_flags(request.usage);
+        // Padding is not part of requested size.
+        let requested_size = request.size;
+        request = self.apply_granularity(request)?;
+
+        if request.usage.contains(UsageFlags::DEVICE_ADDRESS) {
+            assert!(self.buffer_device_address, "`DEVICE_ADDRESS` cannot be requested when `DeviceProperties::buffer_device_address` is false");
+        }
+
+        if !valid_align_mask(request.align_mask) {
+            return Err(AllocationError::InvalidAlignment);
+        }
+
+        if request.size > self.max_memory_allocation_size {
+            return Err(AllocationError::OutOfDeviceMemory);
+        }
+
+        if let Some(Dedicated::Required) = dedicated {
+            if *self.allocations_remains.get_mut() == 0 {
+                return Err(AllocationError::TooManyObjects);
+            }
+        }
+
+        if 0 == self.memory_for_usage.mask(request.usage) & request.memory_types {
+            #[cfg(feature = "tracing")]
+            tracing::error!(
+                "Cannot serve request {:?}, no memory among bitset `{}` support usage {:?}",
+                request,
+                request.memory_types,
+                request.usage
+            );
+
+            return Err(AllocationError::NoCompatibleMemoryTypes);
+        }
+
+        let transient = request.usage.contains(UsageFlags::TRANSIENT);
+        let frame = match self.frame {
+            Some((frame, true)) => Some(frame),
+            _ => None,
+        };
+
+        let mut selection = self.select_memory_types(&request);
+
+        if selection.is_empty() {
+            // Selector vetoed all compatible memory types.
+            return Err(AllocationError::NoCompatibleMemoryTypes);
+        }
+
+        for index in selection.sorted() {
+            let memory_type = &self.memory_types[index as usize];
+            let heap_index = memory_type.heap;
+            let heap = &mut self.memory_heaps[heap_index as usize];
+
+            let attempts = &mut self.attempts;
+            let mut attempt = |outcome| {
+                attempts.push(MemoryTypeAttempt {
+                    memory_type: index,
+                    heap: heap_index,
+                    outcome,
+                })
+            };
+
+            if request.size > heap.size() {
+                // Impossible to use memory type from this heap.
+                attempt(AttemptOutcome::HeapTooSmall);
+                continue;
+            }
+
+            let atom_mask = if host_visible_non_coherent(memory_type.props) {
+                self.non_coherent_atom_mask
+            } else {
+                0
+            };
+
+            let flags = if self.buffer_device_address {
+                AllocationFlags::DEVICE_ADDRESS
+            } else {
+                AllocationFlags::empty()
+            };
+
+            // Sub-allocations are surrounded by guard bands.
+            #[cfg(feature = "guard-bands")]
+            let guard = guard::band(self.guard_band, request.align_mask | atom_mask)
+                .ok_or(AllocationError::SizeOverflow)?;
+            #[cfg(not(feature = "guard-bands"))]
+            let guard = 0u64;
+
+            let guarded_size = guard
+                .checked_mul(2)
+                .and_then(|guards| request.size.checked_add(guards))
+                .ok_or(AllocationError::SizeOverflow)?;
+
+            let strategy = match (dedicated, transient) {
+                _ if self.strategies.force_dedicated => Strategy::Dedicated,
+                (Some(Dedicated::Required), _) => Strategy::Dedicated,
+                (Some(Dedicated::Preferred), _)
+                    if request.size >= self.strategies.preferred_dedicated_threshold =>
+                {
+                    Strategy::Dedicated
+                }
+                (_, true) if frame.is_some() => {
+                    let threshold = self
+                        .strategies
+                        .transient_dedicated_threshold
+                        .min(heap.size() / 32);
+
+                    if guarded_size < threshold {
+                        Strategy::Ring(frame.unwrap())
+                    } else {
+                        Strategy::Dedicated
+                    }
+                }
+                (_, true)
+                    if matches!(
+                        &self.strategies.freelist_allocators[index as usize],
+                        Some(allocator) if allocator.spilling()
+                    ) =>
+                {
+                    // Linear allocator chunks are pinned by long-lived blocks.
+                    // Serve further requests as if they weren't transient.
+                    let threshold = self.strategies.dedicated_threshold.min(heap.size() / 32);
+
+                    if guarded_size < threshold {
+                        Strategy::Buddy
+                    } else {
+                        Strategy::Dedicated
+                    }
+                }
+                (_, true) => {
+                    let threshold = self
+                        .strategies
+                        .transient_dedicated_threshold
+                        .min(heap.size() / 32);
+
+                    if guarded_size < threshold {
+                        Strategy::FreeList
+                    } else {
+                        Strategy::Dedicated
+                    }
+                }
+                (_, false) => {
+                    let threshold = self.strategies.dedicated_threshold.min(heap.size() / 32);
+
+                    if guarded_size < threshold {
+                        Strategy::Buddy
+                    } else {
+                        Strategy::Dedicated
+                    }
+                }
+            };
+
+            match strategy {
+                Strategy::Dedicated => {
+                    if *self.allocations_remains.get_mut() == 0 {
+                        attempt(AttemptOutcome::Failed {
+                            strategy: ChunkKind::Dedicated,
+                            error: AllocationError::TooManyObjects,
+                        });
+                        return Err(AllocationError::TooManyObjects);
+                    }
+
+                    if let Err(err) = heap.reserve(request.size) {
+                        attempt(AttemptOutcome::Failed {
+                            strategy: ChunkKind::Dedicated,
+                            error: err,
+                        });
+
+                        match err {
+                            AllocationError::OutOfBudget => continue,
+                            err => return Err(err),
+                        }
+                    }
+
+                    profile_scope!("GpuAllocator::alloc_dedicated");
+
+                    #[cfg(feature = "tracing")]
+                    tracing::debug!(
+                        "Allocating memory object `{}@{:?}`",
+                        request.size,
+                        memory_type
+                    );
+
+                    match device.allocate_memory(request.size, index, flags) {
+                        Ok(memory) => {
+                            *self.allocations_remains.get_mut() -= 1;
+                            heap.alloc(request.size);
+
+                            let stats = &mut self.type_stats[index as usize].dedicated;
+                            stats.alloc(requested_size, request.size);
+                            stats.memory_objects += 1;
+                            stats.memory_bytes += request.size;
+
+                            #[cfg(feature = "stats")]
+                            self.size_histograms[index as usize].record(requested_size);
+
+                            return Ok(MemoryBlock::new(
+                                index,
+                                memory_type.props,
+                                0,
+                                request.size,
+                                requested_size,
+                                atom_mask,
+                                MemoryBlockFlavor::Dedicated {
+                                    memory,
+                                    ranges: None,
+                                },
+                            ));
+                        }
+                        Err(err) => {
+                            heap.release(request.size);
+                            let err = AllocationError::from(err);
+                            attempt(AttemptOutcome::Failed {
+                                strategy: ChunkKind::Dedicated,
+                                error: err,
+                            });
+
+                            match err {
+                                AllocationError::OutOfDeviceMemory
+                                | AllocationError::OutOfBudget => continue,
+                                err => return Err(err),
+                            }
+                        }
+                    }
+                }
+                Strategy::FreeList => {
+                    let allocator = match &mut self.strategies.freelist_allocators[index as usize] {
+                        Some(allocator) => allocator,
+                        slot => slot.get_or_insert(new_free_list_allocator(
+                            self.strategies.starting_free_list_chunk,
+                            self.strategies
+                                .final_free_list_chunk
+                                .max(self.strategies.starting_free_list_chunk)
+                                .max(self.strategies.transient_dedicated_threshold),
+                            heap.size(),
+                            index,
+                            memory_type.props,
+                            atom_mask,
+                        )),
+                    };
+                    let result = allocator.alloc(
+                        device,
+                        guarded_size,
+                        request.align_mask,
+                        flags,
+                        heap,
+                        self.allocations_remains.get_mut(),
+                        self.observer.get(),
+                    );
+
+                    match result {
+                        Ok(block) => {
+                            self.type_stats[index as usize]
+                                .free_list
+                                .alloc(requested_size, block.size);
+
+                            #[cfg(feature = "stats")]
+                            self.size_histograms[index as usize].record(requested_size);
+
+                            #[cfg(feature = "guard-bands")]
+                            guard::fill(
+                                device,
+                                &guard::Guarded {
+                                    memory: &*block.memory,
+                                    ptr: block.ptr,
+                                    offset: block.offset,
+                                    size: block.size,
+                                    guard,
+                                },
+                                host_visible_non_coherent(memory_type.props),
+                            );
+
+                            let block = MemoryBlock::new(
+                                index,
+                                memory_type.props,
+                                block.offset,
+                                block.size,
+                                requested_size,
+                                atom_mask,
+                                MemoryBlockFlavor::FreeList {
+                                    chunk: block.chunk,
+                                    ptr: block.ptr,
+                                    memory: block.memory,
+                                },
+                            );
+
+                            #[cfg(feature = "guard-bands")]
+                            let block = block.with_guard(guard);
+
+                            return Ok(block);
+                        }
+                        Err(err) => {
+                            attempt(AttemptOutcome::Failed {
+                                strategy: ChunkKind::FreeList,
+                                error: err,
+                            });
+
+                            match err {
+                                AllocationError::OutOfDeviceMemory
+                                | AllocationError::OutOfBudget => continue,
+                                err => return Err(err),
+                            }
+                        }
+                    }
+                }
+
+                Strategy::Ring(frame) => {
+                    let allocator = match &mut self.strategies.ring_allocators[index as usize] {
+                        Some(allocator) => allocator,
+                        slot => slot.get_or_insert(new_ring_allocator(
+                            self.strategies.starting_free_list_chunk,
+                            self.strategies
+                                .final_free_list_chunk
+                                .max(self.strategies.starting_free_list_chunk)
+                                .max(self.strategies.transient_dedicated_threshold),
+                            heap.size(),
+                            index,
+                            memory_type.props,
+                            atom_mask,
+                        )),
+                    };
+                    let result = allocator.alloc(
+                        device,
+                        frame,
+                        guarded_size,
+                        request.align_mask,
+                        flags,
+                        heap,
+                        self.allocations_remains.get_mut(),
+                        self.observer.get(),
+                    );
+
+                    match result {
+                        Ok(block) => {
+                            self.type_stats[index as usize]
+                                .ring
+                                .alloc(requested_size, block.size);
+
+                            #[cfg(feature = "stats")]
+                            self.size_histograms[index as usize].record(requested_size);
+
+                            #[cfg(feature = "guard-bands")]
+                            guard::fill(
+                                device,
+                                &guard::Guarded {
+                                    memory: &*block.memory,
+                                    ptr: block.ptr,
+                                    offset: block.offset,
+                                    size: block.size,
+                                    guard,
+                                },
+                                host_visible_non_coherent(memory_type.props),
+                            );
+
+                            let block = MemoryBlock::new(
+                                index,
+                                memory_type.props,
+                                block.offset,
+                                block.size,
+                                requested_size,
+                                atom_mask,
+                                MemoryBlockFlavor::Ring {
+                                    chunk: block.chunk,
+                                    ptr: block.ptr,
+                                    memory: block.memory,
+                                },
+                            );
+
+                            #[cfg(feature = "guard-bands")]
+                            let block = block.with_guard(guard);
+
+                            return Ok(block);
+                        }
+                        Err(err) => {
+                            attempt(AttemptOutcome::Failed {
+                                strategy: ChunkKind::Ring,
+                                error: err,
+                            });
+
+                            match err {
+                                AllocationError::OutOfDeviceMemory
+                                | AllocationError::OutOfBudget => continue,
+                                err => return Err(err),
+                            }
+                        }
+                    }
+                }
+
+                Strategy::Buddy => {
+                    let allocator = match &mut self.strategies.buddy_allocators[index as usize] {
+                        Some(allocator) => allocator,
+                        slot => slot.get_or_insert(new_buddy_allocator(
+                            self.strategies.minimal_buddy_size,
+                            self.strategies.initial_buddy_dedicated_size,
+                            self.strategies.buddy_chunk_keep_alive,
+                            heap.size(),
+                            index,
+                            memory_type.props,
+                            atom_mask,
+                        )),
+                    };
+
+                    // Cached blocks belong to chunks of the allocator itself.
+                    let block_cache = &mut self.block_cache;
+                    let cached = match self.current_pool {
+                        None => allocator
+                            .block_size(guarded_size, request.align_mask)
+                            .and_then(|size| block_cache.take(index, size)),
+                        Some(_) => None,
+                    };
+
+                    let result = match cached {
+                        Some(block) => Ok(block),
+                        None => allocator.alloc(
+                            device,
+                            guarded_size,
+                            request.align_mask,
+                            flags,
+                            heap,
+                            self.allocations_remains.get_mut(),
+                            self.observer.get(),
+                        ),
+                    };
+
+                    match result {
+                        Ok(block) => {
+                            self.type_stats[index as usize]
+                                .buddy
+                                .alloc(requested_size, block.size);
+
+                            #[cfg(feature = "stats")]
+                            self.size_histograms[index as usize].record(requested_size);
+
+                            #[cfg(feature = "guard-bands")]
+                            guard::fill(
+                                device,
+                                &guard::Guarded {
+                                    memory: &*block.memory,
+                                    ptr: block.ptr,
+                                    offset: block.offset,
+                                    size: block.size,
+                                    guard,
+                                },
+                                host_visible_non_coherent(memory_type.props),
+                            );
+
+                            let block = MemoryBlock::new(
+                                index,
+                                memory_type.props,
+                                block.offset,
+                                block.size,
+                                requested_size,
+                                atom_mask,
+                                MemoryBlockFlavor::Buddy {
+                                    chunk: compact(block.chunk),
+                                    ptr: block.ptr,
+                                    index: compact(block.index),
+                                    memory: block.memory,
+                                },
+                            );
+
+                            #[cfg(feature = "guard-bands")]
+                            let block = block.with_guard(guard);
+
+                            return Ok(block);
+                        }
+                        Err(err) => {
+                            attempt(AttemptOutcome::Failed {
+                                strategy: ChunkKind::Buddy,
+                                error: err,
+                            });
+
+                            match err {
+                                AllocationError::OutOfDeviceMemory
+                                | AllocationError::OutOfBudget => continue,
+                                err => return Err(err),
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        // Blame budget only if device itself did not refuse any memory type.
+        let mut error = AllocationError::OutOfDeviceMemory;
+        for attempt in &self.attempts {
+            match attempt.outcome {
+                AttemptOutcome::Failed {
+                    error: AllocationError::OutOfDeviceMemory,
+                    ..
+                } => return Err(AllocationError::OutOfDeviceMemory),
+                AttemptOutcome::Failed {
+                    error: AllocationError::OutOfBudget,
+                    ..
+                } => error = AllocationError::OutOfBudget,
+                _ => {}
+            }
+        }
+
+        Err(error)
+    }
+
+    /// Allocates dedicated memory block through shared reference,
+    /// so that multiple threads can allocate concurrently without exclusive access to the allocator.
+    ///
+    /// Only heap usage and remaining memory object count account for such blocks.
+    /// They are not included in statistics, tags, dumps, traces and observer events
+    /// and are not reported as leaked when allocator is dropped.
+    ///
+    /// Blocks can be deallocated with either [`GpuAllocator::dealloc_concurrent`]
+    /// or [`GpuAllocator::dealloc`].
+    ///
+    /// Available on targets with native 64-bit atomics
+    /// or with `portable-atomic` feature enabled.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    #[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn alloc_dedicated_concurrent<MD>(
+        &self,
+        device: &impl AsRef<MD>,
+        mut request: Request,
+    ) -> Result<MemoryBlock<M>, AllocationError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        profile_scope!("GpuAllocator::alloc_dedicated_concurrent");
+
+        let device = device.as_ref();
+        request.usage = with_implicit_usage_flags(request.usage);
+
+        if request.usage.contains(UsageFlags::DEVICE_ADDRESS) {
+            assert!(self.buffer_device_address, "`DEVICE_ADDRESS` cannot be requested when `DeviceProperties::buffer_device_address` is false");
+        }
+
+        if !valid_align_mask(request.align_mask) {
+            return Err(AllocationError::InvalidAlignment);
+        }
+
+        if request.size > self.max_memory_allocation_size {
+            return Err(AllocationError::OutOfDeviceMemory);
+        }
+
+        if 0 == self.memory_for_usage.mask(request.usage) & request.memory_types {
+            return Err(AllocationError::NoCompatibleMemoryTypes);
+        }
+
+        // Reserve memory object before allocating it,
+        // so that concurrent allocations never exceed the limit.
+        if self
+            .allocations_remains
+            .fetch_update(Relaxed, Relaxed, |remains| remains.checked_sub(1))
+            .is_err()
+        {
+            return Err(AllocationError::TooManyObjects);
+        }
+
+        let flags = if self.buffer_device_address {
+            AllocationFlags::DEVICE_ADDRESS
+        } else {
+            AllocationFlags::empty()
+        };
+
+        let mut out_of_budget = false;
+        let mut out_of_device_memory = false;
+        let memory_types = self.reserve_bar(request.usage, request.memory_types);
+
+        for index in self.memory_for_usage.types(request.usage) {
+            if 0 == memory_types & (1 << index) {
+                continue;
+            }
+
+            let memory_type = &self.memory_types[index as usize];
+            let heap = &self.memory_heaps[memory_type.heap as usize];
+
+            if request.size > heap.size() {
+                continue;
+            }
+
+            // Reserve heap budget before allocating memory object.
+            match heap.try_alloc_concurrent(request.size) {
+                Ok(()) => {}
+                Err(AllocationError::OutOfBudget) => {
+                    out_of_budget = true;
+                    continue;
+                }
+                Err(err) => {
+                    self.allocations_remains.fetch_add(1, Relaxed);
+                    return Err(err);
+                }
+            }
+
+            match device.allocate_memory(request.size, index, flags) {
+                Ok(memory) => {
+                    let atom_mask = if host_visible_non_coherent(memory_type.props) {
+                        self.non_coherent_atom_mask
+                    } else {
+                        0
+                    };
+
+                    let block = MemoryBlock::new(
+                        index,
+                        memory_type.props,
+                        0,
+                        request.size,
+                        request.size,
+                        atom_mask,
+                        MemoryBlockFlavor::Dedicated {
+                            memory,
+                            ranges: None,
+                        },
+                    );
+
+                    #[cfg(feature = "debug-checks")]
+                    let block = block.with_instance(self.instance);
+
+                    self.concurrent_stats[index as usize].alloc(request.size);
+                    return Ok(block);
+                }
+                Err(OutOfMemory::OutOfDeviceMemory) => {
+                    heap.cancel_concurrent(request.size);
+                    out_of_device_memory = true;
+                }
+                Err(OutOfMemory::OutOfHostMemory) => {
+                    heap.cancel_concurrent(request.size);
+                    self.allocations_remains.fetch_add(1, Relaxed);
+                    return Err(AllocationError::OutOfHostMemory);
+                }
+            }
+        }
+
+        self.allocations_remains.fetch_add(1, Relaxed);
+
+        // Blame budget only if device itself did not refuse any memory type.
+        if out_of_budget && !out_of_device_memory {
+            Err(AllocationError::OutOfBudget)
+        } else {
+            Err(AllocationError::OutOfDeviceMemory)
+        }
+    }
+
+    /// Deallocates memory block allocated by [`GpuAllocator::alloc_dedicated_concurrent`]
+    /// through shared reference.
+    ///
+    /// # Panics
+    ///
+    /// Panics if block was allocated with exclusive access to allocator.
+    /// Such blocks must be deallocated with [`GpuAllocator::dealloc`].
+    ///
+    /// # Safety
+    ///
+    /// * Memory block must have been allocated by this `GpuAllocator` instance
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    #[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn dealloc_concurrent<MD>(&self, device: &impl AsRef<MD>, block: MemoryBlock<M>)
+    where
+        MD: MemoryDevice<M>,
+    {
+        profile_scope!("GpuAllocator::dealloc_concurrent");
+
+        assert!(
+            !block.is_registered(),
+            "Memory block was allocated with exclusive access to allocator and must be deallocated with `GpuAllocator::dealloc`"
+        );
+
+        #[cfg(feature = "debug-checks")]
+        assert_eq!(
+            block.instance(),
+            self.instance,
+            "Memory block is deallocated into allocator instance {:#x}, but it was allocated by instance {:#x}",
+            self.instance,
+            block.instance(),
+        );
+
+        let memory_type = block.memory_type();
+        let heap = self.memory_types[memory_type as usize].heap;
+        let size = block.size();
+
+        match block.deallocate() {
+            MemoryBlockFlavor::Dedicated { memory, .. } => {
+                device.as_ref().deallocate_memory(memory);
+                self.allocations_remains.fetch_add(1, Relaxed);
+                self.memory_heaps[heap as usize].dealloc_concurrent(size);
+                self.concurrent_stats[memory_type as usize].dealloc(size);
+            }
+            _ => unreachable!("Concurrently allocated blocks are always dedicated"),
+        }
+    }
+
+    /// Creates a memory block from an existing memory allocation, transferring ownership to the allocator.
+    ///
+    /// This function allows the [`GpuAllocator`] to manage memory allocated outside of the typical
+    /// [`GpuAllocator::alloc`] family of functions.
+    ///
+    /// # Usage
+    ///
+    /// If you need to import external memory, such as a Win32 `HANDLE` or a Linux `dmabuf`, import the device
+    /// memory using the graphics api and platform dependent functions. Once that is done, call this function
+    /// to make the [`GpuAllocator`] take ownership of the imported memory.
+    ///
+    /// When calling this function, you **must** ensure there are [enough remaining allocations](GpuAllocator::remaining_allocations).
+    ///
+    /// # Safety
+    ///
+    /// - The `memory` must be allocated with the same device that was provided to create this [`GpuAllocator`]
+    ///   instance.
+    /// - The `memory` must be valid.
+    /// - The `props`, `offset` and `size` must match the properties, offset and size of the memory allocation.
+    /// - The memory must have been allocated with the specified `memory_type`.
+    /// - There must be enough remaining allocations.
+    /// - The memory allocation must not come from an existing memory block created by this allocator.
+    /// - The underlying memory object must be deallocated using the returned [`MemoryBlock`] with
+    ///   [`GpuAllocator::dealloc`].
+    pub unsafe fn import_memory(
+        &mut self,
+        memory: M,
+        memory_type: u32,
+        props: MemoryPropertyFlags,
+        offset: u64,
+        size: u64,
+    ) -> MemoryBlock<M> {
+        // Get the heap which the imported memory is from.
+        let heap = self
+            .memory_types
+            .get(memory_type as usize)
+            .expect("Invalid memory type specified when importing memory")
+            .heap;
+        let heap = &mut self.memory_heaps[heap as usize];
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            "Importing memory object {:?} `{}@{:?}`",
+            memory,
+            size,
+            memory_type
+        );
+
+        assert_ne!(
+            *self.allocations_remains.get_mut(), 0,
+            "Out of allocations when importing a memory block. Ensure you check GpuAllocator::remaining_allocations before import."
+        );
+        *self.allocations_remains.get_mut() -= 1;
+
+        let atom_mask = if host_visible_non_coherent(props) {
+            self.non_coherent_atom_mask
+        } else {
+            0
+        };
+
+        heap.import(size);
+
+        let stats = &mut self.type_stats[memory_type as usize].dedicated;
+        stats.alloc(size, size);
+        stats.memory_objects += 1;
+        stats.memory_bytes += size;
+
+        let block = MemoryBlock::new(
+            memory_type,
+            props,
+            offset,
+            size,
+            size,
+            atom_mask,
+            MemoryBlockFlavor::Dedicated {
+                memory,
+                ranges: None,
+            },
+        );
+
+        self.register(block, None, None)
+    }
+
+    /// Deallocates memory block previously allocated from this `GpuAllocator` instance.
+    ///
+    /// # Safety
+    ///
+    /// * Memory block must have been allocated by this `GpuAllocator` instance
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    ///
+    /// # Panics
+    ///
+    /// Panics if block is already deallocated.
+    /// With `debug-checks` feature also panics if block was allocated by another `GpuAllocator` instance.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn dealloc<MD>(&mut self, device: &impl AsRef<MD>, block: MemoryBlock<M>)
+    where
+        MD: MemoryDevice<M>,
+    {
+        profile_scope!("GpuAllocator::dealloc");
+
+        #[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+        if !block.is_registered() {
+            return self.dealloc_concurrent(device, block);
+        }
+
+        #[cfg(feature = "debug-checks")]
+        assert_eq!(
+            block.instance(),
+            self.instance,
+            "Memory block is deallocated into allocator instance {:#x}, but it was allocated by instance {:#x}",
+            self.instance,
+            block.instance(),
+        );
+
+        // Blocks of pools are returned to strategy allocators of their pool.
+        if self.current_pool.is_none() {
+            let record = self.registry.try_get(block.id(), block.generation());
+            if let Some(pool) = record.and_then(|record| record.pool) {
+                return self.with_pool(pool, |allocator| allocator.dealloc(device, block));
+            }
+        }
+
+        let allocations_remains = *self.allocations_remains.get_mut();
+        let device = device.as_ref();
+        let memory_type = block.memory_type();
+        let (offset, size) = block.allocated_range();
+        let requested_size = block.requested_size();
+
+        let block_id = block.id();
+        let record = self.registry.remove(block_id, block.generation());
+        debug_assert_eq!(record.offset, offset);
+        debug_assert_eq!(record.size, size);
+
+        if let Some(pool) = record.pool {
+            let pool = self.pools[pool.0 as usize]
+                .as_mut()
+                .expect("Pool of the block exists");
+            pool.used -= size;
+            pool.blocks -= 1;
+        }
+
+        #[cfg(feature = "tracing")]
+        {
+            tracing::trace!(
+                id = block_id,
+                memory_type,
+                strategy = record.chunk.strategy(),
+                chunk = record.chunk.id(block_id),
+                offset,
+                size,
+                tag = record.tag.unwrap_or(""),
+                "block deallocated"
+            );
+
+            if record.chunk == ChunkId::Dedicated {
+                tracing::debug!(
+                    memory_type,
+                    strategy = record.chunk.strategy(),
+                    chunk = record.chunk.id(block_id),
+                    size = offset + size,
+                    "chunk destroyed"
+                );
+            }
+        }
+
+        if let Some(tag) = record.tag {
+            self.tags
+                .get_mut(tag)
+                .expect("Tag of the block is not registered")
+                .dealloc(size);
+        }
+
+        if let Some(observer) = self.observer.get() {
+            observer.on_dealloc(&BlockEvent::new(block_id, &record));
+        }
+
+        #[cfg(feature = "guard-bands")]
+        let guard = block.guard_band();
+
+        let flavor = block.deallocate();
+        let stats = &mut self.type_stats[memory_type as usize];
+        match flavor {
+            MemoryBlockFlavor::Dedicated { memory, .. } => {
+                profile_scope!("GpuAllocator::dealloc_dedicated");
+
+                let heap = self.memory_types[memory_type as usize].heap;
+                device.deallocate_memory(memory);
+                *self.allocations_remains.get_mut() += 1;
+                self.memory_heaps[heap as usize].dealloc(size);
+
+                stats.dedicated.dealloc(requested_size, size);
+                stats.dedicated.memory_objects -= 1;
+                stats.dedicated.memory_bytes -= size;
+
+                observer::chunk_destroyed(
+                    &mut self.observer.get(),
+                    memory_type,
+                    ChunkKind::Dedicated,
+                    record.chunk.id(block_id),
+                    offset + size,
+                );
+            }
+            MemoryBlockFlavor::Buddy {
+                chunk,
+                ptr,
+                index,
+                memory,
+            } => {
+                stats.buddy.dealloc(requested_size, size);
+
+                #[cfg(feature = "guard-bands")]
+                let ptr = guard::outer_ptr(ptr, guard);
+
+                #[cfg(feature = "guard-bands")]
+                guard::check(
+                    device,
+                    &guard::Guarded {
+                        memory: &*memory,
+                        ptr,
+                        offset,
+                        size,
+                        guard,
+                    },
+                    host_visible_non_coherent(self.memory_types[memory_type as usize].props),
+                    memory_type,
+                );
+
+                #[cfg(feature = "poison")]
+                poison::poison(
+                    device,
+                    &*memory,
+                    ptr,
+                    offset,
+                    size,
+                    host_visible_non_coherent(self.memory_types[memory_type as usize].props),
+                );
+
+                let heap = self.memory_types[memory_type as usize].heap;
+                let heap = &mut self.memory_heaps[heap as usize];
+
+                let block = BuddyBlock {
+                    memory,
+                    ptr,
+                    offset,
+                    size,
+                    chunk: uncompact(chunk),
+                    index: uncompact(index),
+                };
+
+                let block = match record.pool {
+                    None => self.block_cache.put(memory_type, block),
+                    Some(_) => Some(block),
+                };
+
+                if let Some(block) = block {
+                    let allocator = self.strategies.buddy_allocators[memory_type as usize]
+                        .as_mut()
+                        .expect("Allocator should exist");
+
+                    allocator.dealloc(
+                        device,
+                        block,
+                        heap,
+                        self.allocations_remains.get_mut(),
+                        self.observer.get(),
+                    );
+                }
+            }
+            MemoryBlockFlavor::FreeList { chunk, ptr, memory } => {
+                stats.free_list.dealloc(requested_size, size);
+
+                #[cfg(feature = "guard-bands")]
+                let ptr = guard::outer_ptr(ptr, guard);
+
+                #[cfg(feature = "guard-bands")]
+                guard::check(
+                    device,
+                    &guard::Guarded {
+                        memory: &*memory,
+                        ptr,
+                        offset,
+                        size,
+                        guard,
+                    },
+                    host_visible_non_coherent(self.memory_types[memory_type as usize].props),
+                    memory_type,
+                );
+
+                #[cfg(feature = "poison")]
+                poison::poison(
+                    device,
+                    &*memory,
+                    ptr,
+                    offset,
+                    size,
+                    host_visible_non_coherent(self.memory_types[memory_type as usize].props),
+                );
+
+                let heap = self.memory_types[memory_type as usize].heap;
+                let heap = &mut self.memory_heaps[heap as usize];
+
+                let allocator = self.strategies.freelist_allocators[memory_type as usize]
+                    .as_mut()
+                    .expect("Allocator should exist");
+
+                allocator.dealloc(
+                    device,
+                    FreeListBlock {
+                        memory,
+                        ptr,
+                        chunk,
+                        offset,
+                        size,
+                    },
+                    heap,
+                    self.allocations_remains.get_mut(),
+                    self.observer.get(),
+                );
+            }
+            MemoryBlockFlavor::Ring { chunk, ptr, memory } => {
+                stats.ring.dealloc(requested_size, size);
+
+                #[cfg(feature = "guard-bands")]
+                let ptr = guard::outer_ptr(ptr, guard);
+
+                #[cfg(feature = "guard-bands")]
+                guard::check(
+                    device,
+                    &guard::Guarded {
+                        memory: &*memory,
+                        ptr,
+                        offset,
+                        size,
+                        guard,
+                    },
+                    host_visible_non_coherent(self.memory_types[memory_type as usize].props),
+                    memory_type,
+                );
+
+                #[cfg(feature = "poison")]
+                poison::poison(
+                    device,
+                    &*memory,
+                    ptr,
+                    offset,
+                    size,
+                    host_visible_non_coherent(self.memory_types[memory_type as usize].props),
+                );
+
+                let allocator = self.strategies.ring_allocators[memory_type as usize]
+                    .as_mut()
+                    .expect("Allocator should exist");
+
+                allocator.dealloc(RingBlock {
+                    memory,
+                    ptr,
+                    chunk,
+                    offset,
+                    size,
+                });
+            }
+        }
+
+        #[cfg(feature = "chrome-trace")]
+        if let Some(trace) = &mut self.trace {
+            let heap = self.memory_types[memory_type as usize].heap;
+            trace.dealloc(block_id, memory_type, record.tag);
+            trace.heap_usage(heap, self.memory_heaps[heap as usize].used());
+        }
+
+        if let Some(advisor) = &mut self.advisor {
+            let mut destroyed = self
+                .allocations_remains
+                .get_mut()
+                .saturating_sub(allocations_remains);
+            if record.chunk == ChunkId::Dedicated {
+                destroyed = destroyed.saturating_sub(1);
+            }
+
+            advisor.chunks_destroyed(memory_type, destroyed);
+            advisor.dealloc(block_id, memory_type, requested_size);
+        }
+
+        #[cfg(feature = "metrics")]
+        self.publish_metrics(self.memory_types[memory_type as usize].heap);
+    }
+
+    /// Schedules deallocation of memory block once device reaches `timeline_value`.
+    ///
+    /// Block stays allocated until [`GpuAllocator::flush_deferred`] is called
+    /// with completed value not less than `timeline_value`,
+    /// so its memory is not reused while device may still access it.
+    /// Timeline values are opaque to the allocator,
+    /// typically they are values of a timeline semaphore or frame counters.
+    pub fn dealloc_deferred(&mut self, block: MemoryBlock<M>, timeline_value: u64) {
+        self.deferred.push((timeline_value, block));
+    }
+
+    /// Deallocates memory blocks scheduled with [`GpuAllocator::dealloc_deferred`]
+    /// for timeline values up to and including `completed_value`.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn flush_deferred<MD>(&mut self, device: &impl AsRef<MD>, completed_value: u64)
+    where
+        MD: MemoryDevice<M>,
+    {
+        profile_scope!("GpuAllocator::flush_deferred");
+
+        // Taken out to deallocate blocks while iterating, keeping capacity for reuse.
+        let mut deferred = core::mem::take(&mut self.deferred);
+
+        let mut index = 0;
+        while index < deferred.len() {
+            if deferred[index].0 <= completed_value {
+                let (_, block) = deferred.swap_remove(index);
+                self.dealloc(device, block);
+            } else {
+                index += 1;
+            }
+        }
+
+        self.deferred = deferred;
+    }
+
+    /// Returns number of memory blocks waiting in deferred deallocation queue.
+    pub fn deferred_blocks(&self) -> usize {
+        self.deferred.len()
+    }
+
+    /// Publishes gauges of the specified heap and of the whole allocator.
+    #[cfg(feature = "metrics")]
+    fn publish_metrics(&self, heap: u32) {
+        telemetry::heap_used(heap, self.memory_heaps[heap as usize].used());
+        telemetry::live_blocks(self.registry.len());
+
+        let objects = self
+            .current_type_stats()
+            .map(|stats| stats.total().memory_objects)
+            .sum();
+        telemetry::device_allocations(objects);
+    }
+
+    /// Returns the maximum allocation size supported.
+    pub fn max_allocation_size(&self) -> u64 {
+        self.max_memory_allocation_size
+    }
+
+    /// Returns number of bytes currently allocated from the device in the heap with specified index.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `heap` is not valid heap index.
+    pub fn heap_usage(&self, heap: u32) -> u64 {
+        self.memory_heaps[heap as usize].used()
+    }
+
+    /// Cross-checks usage counter of every memory heap
+    /// against sizes of chunks and dedicated memory objects alive in that heap.
+    ///
+    /// Returns first heap where they disagree.
+    /// Such drift is an accounting bug that otherwise surfaces only as premature out of memory error.
+    ///
+    /// With `debug-checks` feature this check runs on every [`GpuAllocator::cleanup`] call
+    /// and panics on drift.
+    pub fn reconcile(&self) -> Result<(), AccountingDrift> {
+        let mut derived: Table<u64> = self.memory_heaps.iter().map(|_| 0).collect();
+
+        for stats in self.current_type_stats() {
+            derived[stats.heap as usize] += stats.total().memory_bytes;
+        }
+
+        for (index, (heap, &derived)) in self.memory_heaps.iter().zip(&derived).enumerate() {
+            if heap.used() != derived {
+                return Err(AccountingDrift {
+                    heap: index as u32,
+                    accounted: heap.used(),
+                    derived,
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns statistics of memory types with memory object counters
+    /// filled from strategy allocators.
+    fn current_type_stats(&self) -> impl Iterator<Item = TypeStats> + '_ {
+        self.type_stats
+            .iter()
+            .enumerate()
+            .zip(self.concurrent_stats.iter())
+            .zip(self.strategies.buddy_allocators.iter())
+            .zip(self.strategies.freelist_allocators.iter())
+            .zip(self.strategies.ring_allocators.iter())
+            .map(
+                move |(((((index, stats), concurrent), buddy), freelist), ring)| {
+                    let mut stats = *stats;
+                    concurrent.add_to(&mut stats.dedicated);
+                    if let Some(buddy) = buddy {
+                        buddy.memory_stats(&mut stats.buddy);
+                        stats.buddy_chunk_oscillations = buddy.oscillations();
+                    }
+                    if let Some(freelist) = freelist {
+                        freelist.memory_stats(&mut stats.free_list);
+                    }
+                    if let Some(ring) = ring {
+                        ring.memory_stats(&mut stats.ring);
+                    }
+                    for pool in self.pools.iter().flatten() {
+                        pool.strategies.add_memory_stats(index, &mut stats);
+                    }
+                    stats
+                },
+            )
+    }
+
+    /// Returns statistics report of this allocator.
+    ///
+    /// Statistics are gathered incrementally,
+    /// so this function only walks memory types and heaps.
+    pub fn stats(&self) -> AllocatorStats {
+        let types: Vec<TypeStats> = self.current_type_stats().collect();
+
+        let mut heaps: Vec<HeapStats> = self
+            .memory_heaps
+            .iter()
+            .map(|heap| HeapStats {
+                size: heap.size(),
+                budget: heap.budget(),
+                used: heap.used(),
+                peak_used: heap.peak(),
+                watermark_used: heap.watermark(),
+                ..HeapStats::default()
+            })
+            .collect();
+
+        for stats in &types {
+            heaps[stats.heap as usize].add(stats);
+        }
+
+        let tags = self
+            .tags
+            .iter()
+            .map(|(&tag, usage)| usage.stats(tag))
+            .collect();
+
+        AllocatorStats { heaps, types, tags }
+    }
+
+    /// Returns summary of memory held by this allocator per heap and memory type.
+    ///
+    /// Walks free regions of sub-allocators to find the largest one,
+    /// use [`GpuAllocator::stats`] for cheap monitoring.
+    pub fn report(&self) -> AllocatorReport {
+        let stats = self.stats();
+
+        let types: Vec<TypeReport> = stats
+            .types
+            .iter()
+            .enumerate()
+            .map(|(index, stats)| {
+                let total = stats.total();
+                TypeReport {
+                    index: index as u32,
+                    heap: stats.heap,
+                    allocated_bytes: total.memory_bytes,
+                    used_bytes: total.allocated_bytes,
+                    blocks: total.blocks,
+                    chunks: total.memory_objects - stats.dedicated.memory_objects,
+                    memory_objects: total.memory_objects,
+                    largest_free_region: self.fragmentation(index as u32).largest_free_region,
+                }
+            })
+            .collect();
+
+        let heaps = stats
+            .heaps
+            .iter()
+            .enumerate()
+            .map(|(index, stats)| {
+                let total = stats.total();
+                let largest_free_region = types
+                    .iter()
+                    .filter(|memory_type| memory_type.heap == index as u32)
+                    .map(|memory_type| memory_type.largest_free_region)
+                    .max()
+                    .unwrap_or(0);
+
+                HeapReport {
+                    index: index as u32,
+                    size: stats.size,
+                    allocated_bytes: stats.used,
+                    used_bytes: total.allocated_bytes,
+                    blocks: total.blocks,
+                    chunks: total.memory_objects - stats.dedicated.memory_objects,
+                    memory_objects: total.memory_objects,
+                    largest_free_region,
+                }
+            })
+            .collect();
+
+        AllocatorReport {
+            memory_objects: types
+                .iter()
+                .map(|memory_type| memory_type.memory_objects)
+                .sum(),
+            remaining_allocations: self.allocations_remains.load(Relaxed),
+            heaps,
+            types,
+        }
+    }
+
+    /// Returns histogram of sizes of requests served from the memory type with specified index.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `memory_type` is not valid memory type index.
+    #[cfg(feature = "stats")]
+    pub fn size_histogram(&self, memory_type: u32) -> &SizeHistogram {
+        &self.size_histograms[memory_type as usize]
+    }
+
+    /// Returns histograms of allocation latency.
+    ///
+    /// Covers every allocation with [`GpuAllocator::alloc`] and its variants,
+    /// separating time spent in `MemoryDevice::allocate_memory`.
+    #[cfg(all(feature = "stats", feature = "std"))]
+    pub fn alloc_latency(&self) -> &AllocLatency {
+        &self.latency
+    }
+
+    /// Clears histograms of allocation latency.
+    #[cfg(all(feature = "stats", feature = "std"))]
+    pub fn reset_alloc_latency(&mut self) {
+        self.latency = AllocLatency::default();
+    }
+
+    /// Returns detailed description of allocator state
+    /// with every chunk, live block and free region.
+    ///
+    /// This function walks all live blocks and free regions,
+    /// use [`GpuAllocator::stats`] for cheap monitoring.
+    pub fn dump(&self) -> AllocatorDump {
+        let stats = self.stats();
+
+        let mut chunks: Vec<BTreeMap<ChunkKey, ChunkDump>> =
+            self.memory_types.iter().map(|_| BTreeMap::new()).collect();
+
+        self.dump_strategies(&self.strategies, None, &mut chunks);
+        for (index, pool) in self.pools.iter().enumerate() {
+            if let Some(pool) = pool {
+                self.dump_strategies(&pool.strategies, Some(PoolId(index as u32)), &mut chunks);
+            }
+        }
+
+        for (id, record) in self.registry.iter() {
+            let (kind, chunk) = (record.chunk.kind(), record.chunk.id(id));
+
+            let chunk = chunks[record.memory_type as usize]
+                .entry((record.pool, kind, chunk))
+                .or_insert_with(|| ChunkDump::new(kind, chunk, record.pool, 0));
+
+            match kind {
+                ChunkKind::Dedicated => chunk.size = record.offset + record.size,
+                ChunkKind::Buddy | ChunkKind::Ring => {}
+                ChunkKind::FreeList => chunk.size += record.size,
+            }
+
+            chunk.blocks.push(BlockDump {
+                offset: record.offset,
+                size: record.size,
+                requested_size: record.requested_size,
+                tag: record.tag.map(Cow::Borrowed),
+                #[cfg(feature = "track-caller")]
+                location: Some(Cow::Owned(alloc::format!("{}", record.location))),
+                #[cfg(not(feature = "track-caller"))]
+                location: None,
+            });
+        }
+
+        // Space of ring chunks not occupied by live blocks is free.
+        for chunk in chunks.iter_mut().flat_map(|chunks| chunks.values_mut()) {
+            if chunk.kind != ChunkKind::Ring {
+                continue;
+            }
+
+            let mut blocks: Vec<(u64, u64)> = chunk
+                .blocks
+                .iter()
+                .map(|block| (block.offset, block.size))
+                .collect();
+            blocks.sort_unstable();
+
+            let mut end = 0;
+            for (offset, size) in blocks.into_iter().chain(Some((chunk.size, 0))) {
+                if offset > end {
+                    chunk.free.push(RegionDump {
+                        offset: end,
+                        size: offset - end,
+                    });
+                }
+                end = offset + size;
+            }
+        }
+
+        let types = self
+            .memory_types
+            .iter()
+            .zip(stats.types)
+            .zip(chunks)
+            .enumerate()
+            .map(|(index, ((memory_type, stats), chunks))| MemoryTypeDump {
+                index: index as u32,
+                heap: memory_type.heap,
+                props: memory_type.props,
+                stats,
+                chunks: chunks
+                    .into_values()
+                    .map(|mut chunk| {
+                        chunk.blocks.sort_unstable_by_key(|block| block.offset);
+                        chunk.free.sort_unstable_by_key(|region| region.offset);
+                        chunk
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        AllocatorDump {
+            heaps: stats.heaps,
+            types,
+            tags: stats.tags,
+        }
+    }
+
+    /// Collects chunks and free regions of strategy allocators for [`GpuAllocator::dump`].
+    fn dump_strategies(
+        &self,
+        strategies: &Strategies<M>,
+        pool: Option<PoolId>,
+        chunks: &mut [BTreeMap<ChunkKey, ChunkDump>],
+    ) {
+        for (index, buddy) in strategies.buddy_allocators.iter().enumerate() {
+            if let Some(buddy) = buddy {
+                let chunks = &mut chunks[index];
+                for (chunk, size) in buddy.chunks() {
+                    let id = chunk as u64;
+                    chunks.insert(
+                        (pool, ChunkKind::Buddy, id),
+                        ChunkDump::new(ChunkKind::Buddy, id, pool, size),
+                    );
+                }
+
+                // Block cache holds blocks of the allocator's own buddy chunks only.
+                let cached = self
+                    .block_cache
+                    .blocks(index as u32)
+                    .filter(|_| pool.is_none())
+                    .map(|block| (block.chunk, block.offset, block.size));
+
+                for (chunk, offset, size) in buddy.free_blocks().chain(cached) {
+                    chunks
+                        .get_mut(&(pool, ChunkKind::Buddy, chunk as u64))
+                        .expect("Free buddy block references unknown chunk")
+                        .free
+                        .push(RegionDump { offset, size });
+                }
+            }
+        }
+
+        for (index, freelist) in strategies.freelist_allocators.iter().enumerate() {
+            if let Some(freelist) = freelist {
+                for (chunk, offset, size) in freelist.free_regions() {
+                    let chunk = chunks[index]
+                        .entry((pool, ChunkKind::FreeList, chunk))
+                        .or_insert_with(|| ChunkDump::new(ChunkKind::FreeList, chunk, pool, 0));
+                    chunk.size += size;
+                    chunk.free.push(RegionDump { offset, size });
+                }
+            }
+        }
+
+        for (index, ring) in strategies.ring_allocators.iter().enumerate() {
+            if let Some(ring) = ring {
+                for (chunk, size) in ring.chunks() {
+                    chunks[index].insert(
+                        (pool, ChunkKind::Ring, chunk),
+                        ChunkDump::new(ChunkKind::Ring, chunk, pool, size),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Returns logical layout of live blocks,
+    /// from which [`GpuAllocator::restore`] allocates equivalent blocks.
+    ///
+    /// Application that has to release all device memory, for example on suspend,
+    /// takes snapshot, deallocates all blocks and calls [`GpuAllocator::cleanup`].
+    /// After resume it restores blocks and re-uploads their content.
+    pub fn snapshot(&self) -> AllocatorSnapshot {
+        let mut blocks: Vec<((u32, ChunkKind, u64, u64), SnapshotBlock)> = self
+            .registry
+            .iter()
+            .map(|(id, record)| {
+                let kind = record.chunk.kind();
+                let key = (record.memory_type, kind, record.chunk.id(id), record.offset);
+                let block = SnapshotBlock {
+                    id,
+                    memory_type: record.memory_type,
+                    kind,
+                    size: record.requested_size,
+                    align_mask: record.align_mask,
+                    usage: record.usage,
+                    resource: record.resource,
+                    tag: record.tag,
+                };
+                (key, block)
+            })
+            .collect();
+
+        blocks.sort_unstable_by_key(|&(key, _)| key);
+
+        AllocatorSnapshot {
+            blocks: blocks.into_iter().map(|(_, block)| block).collect(),
+        }
+    }
+
+    /// Allocates blocks equivalent to ones in the snapshot taken with [`GpuAllocator::snapshot`].
+    ///
+    /// Each block is allocated from the same memory type, with the same size, alignment, usage and tag.
+    /// Blocks that were dedicated are allocated as dedicated.
+    /// Returns new blocks paired with ids they had in the snapshot.
+    ///
+    /// On failure all blocks restored so far are deallocated.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    pub unsafe fn restore<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        snapshot: &AllocatorSnapshot,
+    ) -> Result<Vec<(usize, MemoryBlock<M>)>, AllocationError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        let mut restored = Vec::with_capacity(snapshot.blocks.len());
+
+        for block in &snapshot.blocks {
+            let request = Request {
+                size: block.size,
+                align_mask: block.align_mask,
+                usage: block.usage,
+                memory_types: 1 << block.memory_type,
+                kind: block.resource,
+            };
+
+            let dedicated = match block.kind {
+                ChunkKind::Dedicated => Some(Dedicated::Required),
+                ChunkKind::Buddy | ChunkKind::FreeList | ChunkKind::Ring => None,
+            };
+
+            match self.alloc_internal(device.as_ref(), request, dedicated, block.tag) {
+                Ok(new) => restored.push((block.id, new)),
+                Err(err) => {
+                    for (_, block) in restored {
+                        self.dealloc(device, block);
+                    }
+                    return Err(err);
+                }
+            }
+        }
+
+        Ok(restored)
+    }
+
+    /// Suggests blocks to move out of heaps that use more than `target_budgets` bytes,
+    /// indexed by heap, for example after budget reported by the driver shrinks.
+    ///
+    /// Each block is moved to the most preferred memory type for its usage
+    /// among types allowed by its request, from another heap that stays within its target.
+    /// Dedicated blocks are suggested first, since moving them releases memory immediately,
+    /// then larger blocks first.
+    /// Memory of sub-allocated blocks is released only when their chunks become empty.
+    ///
+    /// Returns empty list if every heap is within its target.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `target_budgets` has fewer elements than there are memory heaps.
+    pub fn plan_rebalance(&self, target_budgets: &[u64]) -> Vec<BlockMove> {
+        assert!(
+            target_budgets.len() >= self.memory_heaps.len(),
+            "Target budget must be specified for every memory heap"
+        );
+
+        let mut used: Table<u64> = self.memory_heaps.iter().map(|heap| heap.used()).collect();
+
+        let mut candidates: Vec<(usize, &BlockRecord)> = self
+            .registry
+            .iter()
+            .filter(|(_, record)| {
+                let heap = self.memory_types[record.memory_type as usize].heap as usize;
+                used[heap] > target_budgets[heap]
+            })
+            .collect();
+
+        candidates.sort_unstable_by_key(|&(_, record)| {
+            (
+                record.chunk != ChunkId::Dedicated,
+                core::cmp::Reverse(record.size),
+            )
+        });
+
+        let mut moves = Vec::new();
+
+        for (id, record) in candidates {
+            let from_heap = self.memory_types[record.memory_type as usize].heap as usize;
+            if used[from_heap] <= target_budgets[from_heap] {
+                continue;
+            }
+
+            let usage = with_implicit_usage_flags(record.usage);
+            let to = self.memory_for_usage.types(usage).find(|&index| {
+                let heap = self.memory_types[index as usize].heap as usize;
+                record.memory_types & (1 << index) != 0
+                    && heap != from_heap
+                    && matches!(used[heap].checked_add(record.size), Some(used) if used <= target_budgets[heap])
+            });
+
+            if let Some(to) = to {
+                let to_heap = self.memory_types[to as usize].heap as usize;
+                used[from_heap] -= record.size;
+                used[to_heap] += record.size;
+
+                moves.push(BlockMove {
+                    id,
+                    from: record.memory_type,
+                    to,
+                    size: record.size,
+                });
+            }
+        }
+
+        moves
+    }
+
+    /// Plans defragmentation of buddy and free-list chunks
+    /// and allocates new blocks to move content of `blocks` to.
+    ///
+    /// Chunks are evacuated starting from least occupied ones.
+    /// Chunk is evacuated only if all its live blocks are in `blocks`
+    /// and each of them fits into free space of other chunks of the same memory type and strategy.
+    /// No new memory objects are allocated.
+    /// Chunks that receive moved blocks are not evacuated.
+    /// Blocks allocated from pools are never moved.
+    ///
+    /// Caller should copy content of every moved block to its new block,
+    /// for example with copy commands, rebind resources,
+    /// and then call [`GpuAllocator::finish_defragmentation`]
+    /// once the copies are complete, to release old blocks and get new blocks back.
+    /// Or call [`GpuAllocator::cancel_defragmentation`] to release new blocks instead.
+    ///
+    /// Memory of evacuated chunks is released once old blocks are deallocated,
+    /// after keep-alive cleanups for buddy chunks.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    /// * `blocks` must be allocated from this `GpuAllocator` instance.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device, blocks)))]
+    pub unsafe fn defragment<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        blocks: Vec<MemoryBlock<M>>,
+    ) -> Defragmentation<M>
+    where
+        MD: MemoryDevice<M>,
+    {
+        profile_scope!("GpuAllocator::defragment");
+
+        fn chunk_of<M>(block: &MemoryBlock<M>) -> ChunkId {
+            match block.flavor() {
+                MemoryBlockFlavor::Dedicated { .. } => ChunkId::Dedicated,
+                MemoryBlockFlavor::Buddy { chunk, .. } => ChunkId::Buddy(uncompact(*chunk)),
+                MemoryBlockFlavor::FreeList { chunk, .. } => ChunkId::FreeList(*chunk),
+                MemoryBlockFlavor::Ring { chunk, .. } => ChunkId::Ring(*chunk),
+            }
+        }
+
+        struct Chunk {
+            size: u64,
+            used: u64,
+            live: usize,
+            blocks: Vec<usize>,
+        }
+
+        let mut chunks: BTreeMap<(u32, ChunkId), Chunk> = BTreeMap::new();
+
+        for (index, allocator) in self.strategies.buddy_allocators.iter().enumerate() {
+            for (chunk, size) in allocator.iter().flat_map(|allocator| allocator.chunks()) {
+                chunks.insert(
+                    (index as u32, ChunkId::Buddy(chunk)),
+                    Chunk {
+                        size,
+                        used: 0,
+                        live: 0,
+                        blocks: Vec::new(),
+                    },
+                );
+            }
+        }
+
+        for (index, allocator) in self.strategies.freelist_allocators.iter().enumerate() {
+            for (chunk, size) in allocator.iter().flat_map(|allocator| allocator.chunks()) {
+                chunks.insert(
+                    (index as u32, ChunkId::FreeList(chunk)),
+                    Chunk {
+                        size,
+                        used: 0,
+                        live: 0,
+                        blocks: Vec::new(),
+                    },
+                );
+            }
+        }
+
+        // Chunks of pools are not defragmented.
+        for (_, record) in self
+            .registry
+            .iter()
+            .filter(|(_, record)| record.pool.is_none())
+        {
+            if let Some(chunk) = chunks.get_mut(&(record.memory_type, record.chunk)) {
+                chunk.used += record.size;
+                chunk.live += 1;
+            }
+        }
+
+        for (index, block) in blocks.iter().enumerate() {
+            if self
+                .registry
+                .get(block.id(), block.generation())
+                .pool
+                .is_some()
+            {
+                continue;
+            }
+            if let Some(chunk) = chunks.get_mut(&(block.memory_type(), chunk_of(block))) {
+                chunk.blocks.push(index);
+            }
+        }
+
+        let mut candidates: Vec<((u32, ChunkId), Chunk)> = chunks
+            .into_iter()
+            .filter(|(_, chunk)| chunk.live > 0 && chunk.blocks.len() == chunk.live)
+            .collect();
+
+        // Least occupied chunks first.
+        candidates.sort_by(|(_, lhs), (_, rhs)| {
+            (u128::from(lhs.used) * u128::from(rhs.size))
+                .cmp(&(u128::from(rhs.used) * u128::from(lhs.size)))
+        });
+
+        let mut moves: Vec<Option<MemoryBlock<M>>> = blocks.iter().map(|_| None).collect();
+        let mut destinations: Vec<(u32, ChunkId)> = Vec::new();
+        let mut evacuated: Vec<(u32, ChunkId)> = Vec::new();
+
+        for (key, chunk) in candidates {
+            if destinations.contains(&key) {
+                continue;
+            }
+
+            let (memory_type, id) = key;
+            let mut placed = Vec::new();
+            // Free space of evacuated chunks is held while looking for space elsewhere.
+            let mut held = Vec::new();
+            let mut complete = true;
+
+            for &index in &chunk.blocks {
+                let block = &blocks[index];
+                let record = self.registry.get(block.id(), block.generation());
+                let request = Request {
+                    size: block.size(),
+                    align_mask: record.align_mask,
+                    usage: with_implicit_usage_flags(record.usage),
+                    memory_types: 1 << memory_type,
+                    kind: record.resource,
+                };
+                let request = self
+                    .apply_granularity(request)
+                    .expect("Live block is already padded");
+                let tag = record.tag;
+
+                let new = loop {
+                    match self.reserve_existing(memory_type, id.kind(), &request, request.size) {
+                        None => break None,
+                        Some(new) => {
+                            let chunk = (memory_type, chunk_of(&new));
+                            if chunk == key || evacuated.contains(&chunk) {
+                                held.push(new);
+                            } else {
+                                break Some(new);
+                            }
+                        }
+                    }
+                };
+
+                match new {
+                    Some(new) => placed.push((index, new, request, tag)),
+                    None => {
+                        complete = false;
+                        break;
+                    }
+                }
+            }
+
+            // Probe blocks never become visible, so they bypass registry, cache and observers.
+            for block in held {
+                self.release_existing(device.as_ref(), block);
+            }
+
+            if !complete {
+                for (_, new, _, _) in placed {
+                    self.release_existing(device.as_ref(), new);
+                }
+                continue;
+            }
+
+            for (index, new, request, tag) in placed {
+                destinations.push((memory_type, chunk_of(&new)));
+                let (new, _) = self.register_existing(new, &request, tag);
+                moves[index] = Some(new);
+            }
+            evacuated.push(key);
+        }
+
+        Defragmentation {
+            blocks: blocks.into_iter().zip(moves).collect(),
+        }
+    }
+
+    /// Finishes defragmentation, deallocating old blocks of every move.
+    /// Returns blocks in order they were passed to [`GpuAllocator::defragment`],
+    /// with moved blocks replaced by new ones.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    /// * `defragmentation` must be returned by `defragment` of this `GpuAllocator` instance.
+    /// * Old blocks of moves must not be used by the device anymore.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, device, defragmentation))
+    )]
+    pub unsafe fn finish_defragmentation<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        defragmentation: Defragmentation<M>,
+    ) -> Vec<MemoryBlock<M>>
+    where
+        MD: MemoryDevice<M>,
+    {
+        defragmentation
+            .blocks
+            .into_iter()
+            .map(|(old, new)| match new {
+                Some(new) => {
+                    self.dealloc(device, old);
+                    new
+                }
+                None => old,
+            })
+            .collect()
+    }
+
+    /// Cancels defragmentation, deallocating new blocks of every move.
+    /// Returns blocks passed to [`GpuAllocator::defragment`] in their order.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    /// * `defragmentation` must be returned by `defragment` of this `GpuAllocator` instance.
+    /// * New blocks of moves must not be used by the device anymore.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self, device, defragmentation))
+    )]
+    pub unsafe fn cancel_defragmentation<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        defragmentation: Defragmentation<M>,
+    ) -> Vec<MemoryBlock<M>>
+    where
+        MD: MemoryDevice<M>,
+    {
+        defragmentation
+            .blocks
+            .into_iter()
+            .map(|(old, new)| {
+                if let Some(new) = new {
+                    self.dealloc(device, new);
+                }
+                old
+            })
+            .collect()
+    }
+
+    /// Returns detailed description of allocator state as JSON.
+    ///
+    /// See [`GpuAllocator::dump`] for details.
+    #[cfg(all(feature = "std", feature = "serde"))]
+    pub fn dump_json(&self) -> std::string::String {
+        serde_json::to_string_pretty(&self.dump()).expect("Allocator dump is always serializable")
+    }
+
+    /// Starts recording allocation timeline.
+    /// Previously recorded events are discarded.
+    ///
+    /// Recording continues until [`GpuAllocator::stop_trace`] is called.
+    #[cfg(feature = "chrome-trace")]
+    pub fn start_trace(&mut self) {
+        let mut trace = AllocationTrace::new();
+        for (heap, memory_heap) in self.memory_heaps.iter().enumerate() {
+            trace.heap_usage(heap as u32, memory_heap.used());
+        }
+        self.trace = Some(trace);
+    }
+
+    /// Stops recording allocation timeline and returns recorded trace.
+    /// Returns `None` if recording was not started.
+    #[cfg(feature = "chrome-trace")]
+    pub fn stop_trace(&mut self) -> Option<AllocationTrace> {
+        self.trace.take()
+    }
+
+    /// Returns live blocks and bytes per tag, sorted by tag.
+    /// Tags without live blocks are skipped.
+    ///
+    /// Usage of untagged blocks can be computed by subtracting tagged usage
+    /// from totals returned by [`GpuAllocator::stats`].
+    pub fn usage_by_tag(&self) -> impl Iterator<Item = TagStats> + '_ {
+        self.tags
+            .iter()
+            .filter(|(_, usage)| usage.blocks() > 0)
+            .map(|(&tag, usage)| usage.stats(tag))
+    }
+
+    /// Resets watermarks of heaps and tags to current usage.
+    ///
+    /// Peaks since allocator creation are not affected.
+    pub fn reset_watermarks(&mut self) {
+        for heap in self.memory_heaps.iter_mut() {
+            heap.reset_watermark();
+        }
+        for usage in self.tags.values_mut() {
+            usage.reset_watermark();
+        }
+    }
+
+    /// Sets observer notified about every allocation, deallocation,
+    /// memory object creation and destruction and failed allocation.
+    ///
+    /// Replaces previously set observer.
+    pub fn set_observer(&mut self, observer: Box<dyn AllocatorObserver>) {
+        self.observer.set(Some(observer));
+    }
+
+    /// Removes observer returning it.
+    pub fn take_observer(&mut self) -> Option<Box<dyn AllocatorObserver>> {
+        self.observer.take()
+    }
+
+    /// Sets selector that can veto or re-rank memory types
+    /// for every request allocated with [`GpuAllocator::alloc`] and its variants.
+    ///
+    /// Allocation fails with [`AllocationError::NoCompatibleMemoryTypes`]
+    /// if selector vetoes all memory types compatible with the request.
+    ///
+    /// Replaces previously set selector.
+    pub fn set_memory_type_selector(&mut self, selector: Box<dyn MemoryTypeSelector>) {
+        self.selector.0 = Some(selector);
+    }
+
+    /// Removes memory type selector returning it.
+    pub fn take_memory_type_selector(&mut self) -> Option<Box<dyn MemoryTypeSelector>> {
+        self.selector.0.take()
+    }
+
+    /// Replaces scoring that orders memory types compatible with each usage.
+    ///
+    /// Affects subsequent allocations only.
+    /// Memory type selector, if set, re-ranks memory types in order defined by the scoring.
+    pub fn set_memory_type_scoring(&mut self, scoring: &dyn MemoryTypeScoring) {
+        self.memory_for_usage = MemoryForUsage::new(&self.memory_types, scoring);
+    }
+
+    /// Sets number of bytes this allocator may allocate from the heap with specified index,
+    /// for example after querying `VK_EXT_memory_budget` each frame.
+    ///
+    /// Budget includes memory already allocated by this allocator, see [`HeapStats::used`].
+    /// With `VK_EXT_memory_budget` it is `heapBudget` less `heapUsage` plus that.
+    /// Budget less than that makes subsequent allocations from the heap fail
+    /// with [`AllocationError::OutOfBudget`] until memory is deallocated,
+    /// see [`GpuAllocator::plan_rebalance`] to move blocks out of the heap.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `heap_index` is not valid memory heap index.
+    pub fn set_heap_budget(&mut self, heap_index: u32, budget: u64) {
+        self.memory_heaps[heap_index as usize].set_budget(budget);
+    }
+
+    /// Makes this allocator share heap budgets and memory object count
+    /// with other allocators through `tracker`.
+    ///
+    /// Memory objects already allocated by this allocator are charged to the tracker,
+    /// even if that exceeds its limits, and discharged from previously set tracker.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if tracker has different number of heaps.
+    #[cfg(all(
+        target_has_atomic = "ptr",
+        any(target_has_atomic = "64", feature = "portable-atomic")
+    ))]
+    pub fn set_budget_tracker(&mut self, tracker: BudgetTracker) {
+        assert_eq!(
+            tracker.heap_count() as usize,
+            self.memory_heaps.len(),
+            "Budget tracker must be created for the same device"
+        );
+
+        for index in 0..self.memory_heaps.len() as u32 {
+            let objects = self
+                .current_type_stats()
+                .filter(|stats| stats.heap == index)
+                .map(|stats| stats.total().memory_objects)
+                .sum::<u64>();
+
+            self.memory_heaps[index as usize].set_tracker(tracker.clone(), index, objects as u32);
+        }
+    }
+
+    /// Returns receiver of allocation, deallocation, memory object and failure events
+    /// for monitors that update live instead of polling [`GpuAllocator::stats`].
+    ///
+    /// Events are sent to every receiver, in addition to observer set with [`GpuAllocator::set_observer`].
+    /// Dropped receiver is forgotten on the next event.
+    #[cfg(feature = "std")]
+    pub fn subscribe(&mut self) -> std::sync::mpsc::Receiver<AllocEvent> {
+        self.observer.subscribe()
+    }
+
+    /// Sets size of guard bands placed before and after each block
+    /// allocated by linear or buddy sub-allocator.
+    ///
+    /// Guard bands of host-visible memory are filled with [`GUARD_BYTE`]
+    /// and checked when block is deallocated, catching out-of-bounds writes.
+    /// Band size is rounded up to block alignment.
+    /// Zero, which is the default, disables guard bands.
+    ///
+    /// Affects only blocks allocated after the call.
+    ///
+    /// [`GUARD_BYTE`]: crate::GUARD_BYTE
+    #[cfg(feature = "guard-bands")]
+    pub fn set_guard_band(&mut self, size: u64) {
+        self.guard_band = size;
+    }
+
+    /// Sets number of recently freed blocks kept per memory type and size class
+    /// for reuse by following requests of the same size class.
+    ///
+    /// Cache serves workloads that allocate and free identically sized blocks every frame
+    /// without splitting and merging buddy blocks.
+    /// Only blocks of buddy allocator are cached.
+    /// Cached blocks keep their chunks alive until reused or returned by [`GpuAllocator::cleanup`].
+    ///
+    /// Cache is disabled by default. Zero `capacity` disables it,
+    /// already cached blocks are returned on next cleanup.
+    pub fn set_block_cache(&mut self, capacity: usize) {
+        self.block_cache.set_capacity(capacity);
+    }
+
+    /// Enables advisor that watches allocation patterns
+    /// and reports suboptimal ones at the end of each frame.
+    ///
+    /// Advisor is disabled by default as it adds bookkeeping to every allocation.
+    /// Replaces previous advisor if any, dropping its pending advice.
+    pub fn enable_advisor(&mut self, config: AdvisorConfig) {
+        self.advisor = Some(Advisor::new(config));
+    }
+
+    /// Disables advisor, dropping pending advice.
+    pub fn disable_advisor(&mut self) {
+        self.advisor = None;
+    }
+
+    /// Marks start of the frame with specified index.
+    ///
+    /// Until [`GpuAllocator::end_frame`] is called, requests with [`UsageFlags::TRANSIENT`]
+    /// that would be sub-allocated are served by ring allocator of the memory type.
+    /// Memory of such blocks is reclaimed in FIFO order with [`GpuAllocator::release_frame`],
+    /// instead of keeping whole chunks alive until every block in them is deallocated.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `frame_index` is not greater than index of previously started frame.
+    pub fn begin_frame(&mut self, frame_index: u64) {
+        if let Some((last, _)) = self.frame {
+            assert!(
+                frame_index > last,
+                "Frame index {} must be greater than index of previous frame {}",
+                frame_index,
+                last
+            );
+        }
+        self.frame = Some((frame_index, true));
+    }
+
+    /// Marks end of the frame.
+    ///
+    /// Transient requests are no longer served by ring allocator until next [`GpuAllocator::begin_frame`].
+    ///
+    /// Advisor, if enabled, checks events of the finished frame,
+    /// records advice and emits warnings with `tracing` feature.
+    pub fn end_frame(&mut self) {
+        if let Some((_, open)) = &mut self.frame {
+            *open = false;
+        }
+
+        if let Some(advisor) = &mut self.advisor {
+            advisor.end_frame(self.strategies.dedicated_threshold);
+        }
+    }
+
+    /// Declares frames up to `frame_index` inclusive done,
+    /// reclaiming ring memory of their blocks in FIFO order.
+    /// Ring chunks no longer used are released.
+    ///
+    /// Blocks of the frames should be deallocated before.
+    /// Memory of frame with live blocks is not reclaimed,
+    /// and neither is memory of later frames,
+    /// until this function is called again after those blocks are deallocated.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance.
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it.
+    /// * Device must not access blocks of released frames anymore.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn release_frame<MD>(&mut self, device: &impl AsRef<MD>, frame_index: u64)
+    where
+        MD: MemoryDevice<M>,
+    {
+        profile_scope!("GpuAllocator::release_frame");
+
+        let device = device.as_ref();
+
+        self.release_frame_strategies(device, frame_index);
+
+        for index in 0..self.pools.len() {
+            if self.pools[index].is_some() {
+                self.with_pool(PoolId(index as u32), |allocator| {
+                    allocator.release_frame_strategies(device, frame_index)
+                });
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        for heap in 0..self.memory_heaps.len() as u32 {
+            self.publish_metrics(heap);
+        }
+    }
+
+    /// Releases frames in ring allocators that are currently swapped in.
+    unsafe fn release_frame_strategies(&mut self, device: &impl MemoryDevice<M>, frame_index: u64) {
+        for (index, allocator) in self
+            .strategies
+            .ring_allocators
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, allocator)| Some((index, allocator.as_mut()?)))
+        {
+            let heap = self.memory_types[index].heap;
+            let heap = &mut self.memory_heaps[heap as usize];
+
+            let allocations_remains = *self.allocations_remains.get_mut();
+            allocator.release(
+                device,
+                frame_index,
+                heap,
+                self.allocations_remains.get_mut(),
+                self.observer.get(),
+            );
+
+            if let Some(advisor) = &mut self.advisor {
+                advisor.chunks_destroyed(
+                    index as u32,
+                    *self.allocations_remains.get_mut() - allocations_remains,
+                );
+            }
+        }
+    }
+
+    /// Returns advice recorded by advisor since last call.
+    pub fn take_advice(&mut self) -> Vec<Advice> {
+        match &mut self.advisor {
+            Some(advisor) => advisor.take(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns diagnostic context of the last failed allocation request,
+    /// or `None` if no request failed yet.
+    ///
+    /// Details list memory types tried with strategy chosen for each and error it returned,
+    /// usage of every heap and remaining memory objects count at the moment of failure.
+    /// Only requests served through exclusive reference are recorded.
+    pub fn last_failure(&self) -> Option<&FailureDetails> {
+        self.last_failure.as_ref()
+    }
+
+    /// Returns fragmentation measures of every memory type, indexed by memory type.
+    ///
+    /// Useful to decide when to call [`GpuAllocator::defragment`].
+    /// Walks free regions of all sub-allocators.
+    pub fn fragmentation_report(&self) -> Vec<FragmentationStats> {
+        (0..self.memory_types.len() as u32)
+            .map(|memory_type| self.fragmentation(memory_type))
+            .collect()
+    }
+
+    /// Returns fragmentation measures of the memory type with specified index.
+    ///
+    /// Unlike [`GpuAllocator::stats`] this function walks free regions
+    /// of sub-allocators, so it should not be called too frequently.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `memory_type` is not valid memory type index.
+    pub fn fragmentation(&self, memory_type: u32) -> FragmentationStats {
+        let index = memory_type as usize;
+        let total = self.type_stats[index].total();
+
+        let mut stats = FragmentationStats {
+            buddy_minimal_size: self.strategies.minimal_buddy_size,
+            unusable_bytes: total.allocated_bytes - total.requested_bytes,
+            buddy_rounding_bytes: self.type_stats[index].buddy.rounding_bytes(),
+            ..FragmentationStats::default()
+        };
+
+        if let Some(buddy) = &self.strategies.buddy_allocators[index] {
+            buddy.fragmentation_stats(&mut stats);
+
+            // Cached blocks are free for the purpose of fragmentation.
+            let minimal = stats.buddy_minimal_size.trailing_zeros();
+            for block in self.block_cache.blocks(memory_type) {
+                let class = (block.size.trailing_zeros() - minimal) as usize;
+                if stats.buddy_free_blocks.len() <= class {
+                    stats.buddy_free_blocks.resize(class + 1, 0);
+                }
+                stats.buddy_free_blocks[class] += 1;
+                stats.free_region(block.size, 1);
+            }
+        }
+        if let Some(freelist) = &self.strategies.freelist_allocators[index] {
+            freelist.fragmentation_stats(&mut stats);
+        }
+
+        for pool in self.pools.iter().flatten() {
+            let mut pool_stats = FragmentationStats {
+                buddy_minimal_size: pool.strategies.minimal_buddy_size,
+                ..FragmentationStats::default()
+            };
+            if let Some(buddy) = &pool.strategies.buddy_allocators[index] {
+                buddy.fragmentation_stats(&mut pool_stats);
+            }
+            if let Some(freelist) = &pool.strategies.freelist_allocators[index] {
+                freelist.fragmentation_stats(&mut pool_stats);
+            }
+            stats.merge(&pool_stats);
+        }
+
+        stats
+    }
+
+    /// Returns the number of remaining available allocations.
+    ///
+    /// This may be useful if you need know if the allocator can allocate a number of allocations ahead of
+    /// time. This function is also useful for ensuring you do not allocate too much memory outside allocator
+    /// (such as external memory).
+    pub fn remaining_allocations(&self) -> u32 {
+        self.allocations_remains.load(Relaxed)
+    }
+
+    /// Sets the number of remaining available allocations.
+    ///
+    /// # Safety
+    ///
+    /// The caller is responsible for ensuring the number of remaining allocations does not exceed how many
+    /// remaining allocations there actually are on the memory device.
+    pub unsafe fn set_remaining_allocations(&mut self, remaining: u32) {
+        *self.allocations_remains.get_mut() = remaining;
+    }
+
+    /// Returns deterministic human-readable description of allocator internal state:
+    /// heap counters, sub-allocator chunks and free lists.
+    ///
+    /// Output format is not stable and intended for debugging and snapshot tests.
+    /// Memory objects are printed with their `Debug` implementation.
+    pub fn debug_state(&self) -> DebugState<'_, M> {
+        DebugState { allocator: self }
+    }
+
+    /// Eagerly creates chunks for general purpose blocks of memory types in `memory_type_mask`,
+    /// at least `bytes` bytes for each memory type.
+    ///
+    /// Intended to be called at startup, so that first frames don't stall
+    /// on many memory object allocations.
+    /// Chunks are created for buddy allocator that serves blocks not marked [`UsageFlags::TRANSIENT`].
+    /// Until used they are retained like emptied chunks for [`Config::buddy_chunk_keep_alive`] cleanups,
+    /// but at least until the next [`GpuAllocator::cleanup`],
+    /// and can be released earlier with [`GpuAllocator::trim`].
+    ///
+    /// On error chunks created so far are kept.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn preallocate<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        memory_type_mask: u32,
+        bytes: u64,
+    ) -> Result<(), AllocationError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        profile_scope!("GpuAllocator::preallocate");
+
+        let device = device.as_ref();
+        let flags = if self.buffer_device_address {
+            AllocationFlags::DEVICE_ADDRESS
+        } else {
+            AllocationFlags::empty()
+        };
+
+        for index in 0..self.memory_types.len() as u32 {
+            if memory_type_mask & (1 << index) == 0 {
+                continue;
+            }
+
+            let memory_type = &self.memory_types[index as usize];
+            let heap = &mut self.memory_heaps[memory_type.heap as usize];
+            let atom_mask = if host_visible_non_coherent(memory_type.props) {
+                self.non_coherent_atom_mask
+            } else {
+                0
+            };
+
+            let allocator = match &mut self.strategies.buddy_allocators[index as usize] {
+                Some(allocator) => allocator,
+                slot => slot.get_or_insert(new_buddy_allocator(
+                    self.strategies.minimal_buddy_size,
+                    self.strategies.initial_buddy_dedicated_size,
+                    self.strategies.buddy_chunk_keep_alive,
+                    heap.size(),
+                    index,
+                    memory_type.props,
+                    atom_mask,
+                )),
+            };
+
+            let allocations_remains = *self.allocations_remains.get_mut();
+            let result = allocator.preallocate(
+                device,
+                bytes,
+                flags,
+                heap,
+                self.allocations_remains.get_mut(),
+                self.observer.get(),
+            );
+
+            if let Some(advisor) = &mut self.advisor {
+                advisor.chunks_created(
+                    index,
+                    allocations_remains - *self.allocations_remains.get_mut(),
+                );
+            }
+
+            result?;
+        }
+
+        Ok(())
+    }
+
+    /// Deallocates leftover memory objects.
+    /// Should be used before dropping.
+    ///
+    /// Can also be called periodically, e.g. once per frame.
+    /// Each call releases empty free-list and ring chunks
+    /// and counts down keep-alive of empty buddy chunks,
+    /// see [`Config::buddy_chunk_keep_alive`].
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn cleanup<MD>(&mut self, device: &impl AsRef<MD>)
+    where
+        MD: MemoryDevice<M>,
+    {
+        self.cleanup_internal(device.as_ref())
+    }
+
+    /// Releases empty chunks of sub-allocators, keeping at most `keep_bytes_per_type` bytes
+    /// in empty chunks of each memory type for reuse.
+    ///
+    /// Unlike [`GpuAllocator::cleanup`] buddy chunks are released regardless of keep-alive,
+    /// so memory can be returned to the driver under pressure without destroying the allocator.
+    /// Chunks with live blocks are never released.
+    /// Pools are trimmed as well, each with its own limit.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    pub unsafe fn trim<MD>(&mut self, device: &impl AsRef<MD>, keep_bytes_per_type: u64)
+    where
+        MD: MemoryDevice<M>,
+    {
+        profile_scope!("GpuAllocator::trim");
+
+        let device = device.as_ref();
+        self.trim_strategies(device, keep_bytes_per_type);
+
+        for index in 0..self.pools.len() {
+            if self.pools[index].is_some() {
+                self.with_pool(PoolId(index as u32), |allocator| {
+                    allocator.trim_strategies(device, keep_bytes_per_type)
+                });
+            }
+        }
+
+        self.after_release();
+    }
+
+    /// Wraps this allocator into guard that deallocates leftover memory objects
+    /// with specified `device` when dropped, instead of discarding them.
+    ///
+    /// Blocks that are still allocated are reported as usual.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
+    /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
+    ///   and memory blocks allocated from it
+    pub unsafe fn cleanup_on_drop<'a, MD>(
+        self,
+        device: &'a impl AsRef<MD>,
+    ) -> CleanupOnDrop<'a, M, MD>
     where
         MD: MemoryDevice<M>,
     {
+        CleanupOnDrop::new(self, device.as_ref())
+    }
+
+    /// Forgets all bookkeeping without deallocating memory objects
+    /// and without reporting live blocks.
+    ///
+    /// Intended for process exit fast paths where device memory is reclaimed
+    /// by the driver anyway. Host memory used for bookkeeping is leaked as well.
+    /// Live blocks should be forgotten with `core::mem::forget` to avoid reports on their drop.
+    pub fn leak_all(self) {
+        core::mem::forget(self);
+    }
+
+    pub(crate) unsafe fn cleanup_internal(&mut self, device: &impl MemoryDevice<M>) {
+        profile_scope!("GpuAllocator::cleanup");
+
+        self.cleanup_strategies(device);
+
+        for index in 0..self.pools.len() {
+            if self.pools[index].is_some() {
+                self.with_pool(PoolId(index as u32), |allocator| {
+                    allocator.cleanup_strategies(device)
+                });
+            }
+        }
+
+        self.after_release();
+    }
+
+    /// Checks and publishes heap counters after memory objects were released.
+    fn after_release(&mut self) {
+        #[cfg(feature = "debug-checks")]
+        if let Err(drift) = self.reconcile() {
+            panic!("Heap accounting drift: {}", drift);
+        }
+
+        #[cfg(feature = "chrome-trace")]
+        if let Some(trace) = &mut self.trace {
+            for (heap, memory_heap) in self.memory_heaps.iter().enumerate() {
+                trace.heap_usage(heap as u32, memory_heap.used());
+            }
+        }
+
+        #[cfg(feature = "metrics")]
+        for heap in 0..self.memory_heaps.len() as u32 {
+            self.publish_metrics(heap);
+        }
+    }
+
+    /// Trims strategy allocators that are currently swapped in.
+    unsafe fn trim_strategies(&mut self, device: &impl MemoryDevice<M>, keep_bytes: u64) {
+        let mut keep = Vec::new();
+        keep.resize(self.memory_types.len(), keep_bytes);
+
+        for (index, allocator) in self
+            .strategies
+            .buddy_allocators
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, allocator)| Some((index, allocator.as_mut()?)))
+        {
+            let heap = self.memory_types[index].heap;
+            let heap = &mut self.memory_heaps[heap as usize];
+
+            let allocations_remains = *self.allocations_remains.get_mut();
+            // Cached blocks keep their chunks from being released.
+            if self.current_pool.is_none() {
+                for block in self.block_cache.drain(index as u32) {
+                    allocator.dealloc(
+                        device,
+                        block,
+                        heap,
+                        self.allocations_remains.get_mut(),
+                        self.observer.get(),
+                    );
+                }
+            }
+
+            keep[index] -= allocator.trim(
+                device,
+                heap,
+                self.allocations_remains.get_mut(),
+                keep[index],
+                self.observer.get(),
+            );
+
+            if let Some(advisor) = &mut self.advisor {
+                advisor.chunks_destroyed(
+                    index as u32,
+                    *self.allocations_remains.get_mut() - allocations_remains,
+                );
+            }
+        }
+
+        for (index, allocator) in self
+            .strategies
+            .freelist_allocators
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, allocator)| Some((index, allocator.as_mut()?)))
+        {
+            let heap = self.memory_types[index].heap;
+            let heap = &mut self.memory_heaps[heap as usize];
+
+            let allocations_remains = *self.allocations_remains.get_mut();
+            keep[index] -= allocator.trim(
+                device,
+                heap,
+                self.allocations_remains.get_mut(),
+                keep[index],
+                self.observer.get(),
+            );
+
+            if let Some(advisor) = &mut self.advisor {
+                advisor.chunks_destroyed(
+                    index as u32,
+                    *self.allocations_remains.get_mut() - allocations_remains,
+                );
+            }
+        }
+
+        for (index, allocator) in self
+            .strategies
+            .ring_allocators
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, allocator)| Some((index, allocator.as_mut()?)))
+        {
+            let heap = self.memory_types[index].heap;
+            let heap = &mut self.memory_heaps[heap as usize];
+
+            let allocations_remains = *self.allocations_remains.get_mut();
+            keep[index] -= allocator.trim(
+                device,
+                heap,
+                self.allocations_remains.get_mut(),
+                keep[index],
+                self.observer.get(),
+            );
+
+            if let Some(advisor) = &mut self.advisor {
+                advisor.chunks_destroyed(
+                    index as u32,
+                    *self.allocations_remains.get_mut() - allocations_remains,
+                );
+            }
+        }
+    }
+
+    /// Cleans up strategy allocators that are currently swapped in.
+    unsafe fn cleanup_strategies(&mut self, device: &impl MemoryDevice<M>) {
+        for (index, allocator) in self
+            .strategies
+            .buddy_allocators
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, allocator)| Some((index, allocator.as_mut()?)))
+        {
+            let heap = self.memory_types[index].heap;
+            let heap = &mut self.memory_heaps[heap as usize];
+
+            let allocations_remains = *self.allocations_remains.get_mut();
+            // Cached blocks belong to chunks of the allocator itself.
+            if self.current_pool.is_none() {
+                for block in self.block_cache.drain(index as u32) {
+                    allocator.dealloc(
+                        device,
+                        block,
+                        heap,
+                        self.allocations_remains.get_mut(),
+                        self.observer.get(),
+                    );
+                }
+            }
+
+            allocator.cleanup(
+                device,
+                heap,
+                self.allocations_remains.get_mut(),
+                self.observer.get(),
+            );
+
+            if let Some(advisor) = &mut self.advisor {
+                advisor.chunks_destroyed(
+                    index as u32,
+                    *self.allocations_remains.get_mut() - allocations_remains,
+                );
+            }
+        }
+
         for (index, allocator) in self
+            .strategies
             .freelist_allocators
             .iter_mut()
             .enumerate()
             .filter_map(|(index, allocator)| Some((index, allocator.as_mut()?)))
         {
-            let device = device.as_ref();
             let memory_type = &self.memory_types[index];
             let heap = memory_type.heap;
             let heap = &mut self.memory_heaps[heap as usize];
 
-            allocator.cleanup(device, heap, &mut self.allocations_remains);
+            let allocations_remains = *self.allocations_remains.get_mut();
+            allocator.cleanup(
+                device,
+                heap,
+                self.allocations_remains.get_mut(),
+                self.observer.get(),
+            );
+
+            if let Some(advisor) = &mut self.advisor {
+                advisor.chunks_destroyed(
+                    index as u32,
+                    *self.allocations_remains.get_mut() - allocations_remains,
+                );
+            }
+        }
+
+        for (index, allocator) in self
+            .strategies
+            .ring_allocators
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, allocator)| Some((index, allocator.as_mut()?)))
+        {
+            let memory_type = &self.memory_types[index];
+            let heap = memory_type.heap;
+            let heap = &mut self.memory_heaps[heap as usize];
+
+            let allocations_remains = *self.allocations_remains.get_mut();
+            allocator.cleanup(
+                device,
+                heap,
+                self.allocations_remains.get_mut(),
+                self.observer.get(),
+            );
+
+            if let Some(advisor) = &mut self.advisor {
+                advisor.chunks_destroyed(
+                    index as u32,
+                    *self.allocations_remains.get_mut() - allocations_remains,
+                );
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M> GpuAllocator<M> {
+    /// Returns live blocks grouped by tag, allocation call site and memory type,
+    /// largest groups first.
+    ///
+    /// Useful to find which subsystem leaks memory, see [`GpuAllocator::alloc_tagged`].
+    /// Walks all live blocks, so it should not be called too frequently.
+    pub fn leak_report(&self) -> LeakReport {
+        let groups = self.live_blocks();
+
+        LeakReport {
+            blocks: groups.iter().map(|group| group.blocks).sum(),
+            bytes: groups.iter().map(|group| group.bytes).sum(),
+            groups,
+        }
+    }
+
+    fn live_blocks(&self) -> Vec<LiveBlocks> {
+        let mut groups = BTreeMap::new();
+
+        for (_, record) in self.registry.iter() {
+            #[cfg(feature = "track-caller")]
+            let location = Some(record.location);
+            #[cfg(not(feature = "track-caller"))]
+            let location = None;
+
+            let group = groups
+                .entry((record.tag, location, record.memory_type))
+                .or_insert(LiveBlocks {
+                    tag: record.tag,
+                    location,
+                    memory_type: record.memory_type,
+                    blocks: 0,
+                    bytes: 0,
+                    min_size: u64::MAX,
+                    max_size: 0,
+                });
+
+            group.blocks += 1;
+            group.bytes += record.size;
+            group.min_size = group.min_size.min(record.size);
+            group.max_size = group.max_size.max(record.size);
+        }
+
+        let mut groups: Vec<_> = groups.into_values().collect();
+        groups.sort_by_key(|group| core::cmp::Reverse(group.bytes));
+        groups
+    }
+
+    /// Returns age percentiles of live blocks
+    /// and up to `oldest` longest living blocks with their tags.
+    ///
+    /// Useful to find blocks that are never deallocated.
+    /// Walks all live blocks, so it should not be called too frequently.
+    pub fn block_ages(&self, oldest: usize) -> BlockAges {
+        let now = crate::time::Instant::now();
+
+        let ages = self
+            .registry
+            .iter()
+            .map(|(_, record)| BlockAge {
+                age: now.saturating_duration_since(record.created),
+                memory_type: record.memory_type,
+                size: record.size,
+                tag: record.tag,
+            })
+            .collect();
+
+        BlockAges::new(ages, oldest)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<M> Drop for GpuAllocator<M> {
+    fn drop(&mut self) {
+        if self.registry.len() == 0 {
+            return;
+        }
+
+        report_error_on_drop!(
+            "GpuAllocator dropped with {}\nLive block {}",
+            self.leak_report(),
+            self.block_ages(5)
+        );
+    }
+}
+
+/// Description of allocator internal state returned by [`GpuAllocator::debug_state`].
+pub struct DebugState<'a, M> {
+    allocator: &'a GpuAllocator<M>,
+}
+
+impl<M> fmt::Display for DebugState<'_, M>
+where
+    M: MemoryBounds + 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let allocator = self.allocator;
+
+        for (index, heap) in allocator.memory_heaps.iter().enumerate() {
+            writeln!(f, "heap {}: {}", index, heap)?;
+        }
+
+        writeln!(
+            f,
+            "memory objects remaining: {}",
+            allocator.allocations_remains.load(Relaxed)
+        )?;
+
+        write_strategies_debug_state(&allocator.strategies, f)?;
+
+        for (index, pool) in allocator.pools.iter().enumerate() {
+            if let Some(pool) = pool {
+                writeln!(
+                    f,
+                    "{}: {} blocks, {} bytes used",
+                    PoolId(index as u32),
+                    pool.blocks,
+                    pool.used
+                )?;
+                write_strategies_debug_state(&pool.strategies, f)?;
+            }
         }
+
+        Ok(())
+    }
+}
+
+fn write_strategies_debug_state<M>(
+    strategies: &Strategies<M>,
+    f: &mut fmt::Formatter<'_>,
+) -> fmt::Result
+where
+    M: MemoryBounds + 'static,
+{
+    for allocator in strategies.buddy_allocators.iter().flatten() {
+        allocator.write_debug_state(f)?;
+    }
+
+    for allocator in strategies.freelist_allocators.iter().flatten() {
+        allocator.write_debug_state(f)?;
+    }
+
+    for allocator in strategies.ring_allocators.iter().flatten() {
+        allocator.write_debug_state(f)?;
+    }
+
+    Ok(())
+}
+
+/// Creates free-list allocator for memory type,
+/// limiting chunk sizes by size of its heap.
+fn new_free_list_allocator<M>(
+    starting_chunk: u64,
+    final_chunk: u64,
+    heap_size: u64,
+    memory_type: u32,
+    props: MemoryPropertyFlags,
+    atom_mask: u64,
+) -> FreeListAllocator<M>
+where
+    M: MemoryBounds + 'static,
+{
+    let starting_chunk = match align_down(starting_chunk.min(heap_size / 32), atom_mask) {
+        0 => atom_mask,
+        other => other,
+    };
+
+    let final_chunk = match align_down(final_chunk.min(heap_size / 32), atom_mask) {
+        0 => atom_mask,
+        other => other,
+    };
+
+    FreeListAllocator::new(starting_chunk, final_chunk, memory_type, props, atom_mask)
+}
+
+/// Creates buddy allocator for memory type,
+/// limiting block and chunk sizes by size of its heap.
+fn new_buddy_allocator<M>(
+    minimal_size: u64,
+    initial_dedicated_size: u64,
+    keep_alive: u32,
+    heap_size: u64,
+    memory_type: u32,
+    props: MemoryPropertyFlags,
+    atom_mask: u64,
+) -> BuddyAllocator<M>
+where
+    M: MemoryBounds + 'static,
+{
+    let minimal_size = minimal_size.min(heap_size / 1024).next_power_of_two();
+    let initial_dedicated_size = initial_dedicated_size
+        .min(heap_size / 32)
+        .next_power_of_two();
+
+    BuddyAllocator::new(
+        minimal_size,
+        initial_dedicated_size,
+        memory_type,
+        props,
+        atom_mask,
+        keep_alive,
+    )
+}
+
+/// Creates ring allocator for memory type,
+/// with chunk sizes limited like free-list chunks.
+fn new_ring_allocator<M>(
+    starting_chunk: u64,
+    final_chunk: u64,
+    heap_size: u64,
+    memory_type: u32,
+    props: MemoryPropertyFlags,
+    atom_mask: u64,
+) -> RingAllocator<M>
+where
+    M: MemoryBounds + 'static,
+{
+    let starting_chunk = match align_down(starting_chunk.min(heap_size / 32), atom_mask) {
+        0 => atom_mask,
+        other => other,
+    };
+
+    let final_chunk = match align_down(final_chunk.min(heap_size / 32), atom_mask) {
+        0 => atom_mask,
+        other => other,
+    };
+
+    RingAllocator::new(starting_chunk, final_chunk, memory_type, props, atom_mask)
+}
+
+/// Returns mask of `DEVICE_LOCAL | HOST_VISIBLE` memory types
+/// in heaps not larger than `max_heap_size`.
+fn small_bar_types(props: &DeviceProperties<'_>, max_heap_size: u64) -> u32 {
+    let bar = MemoryPropertyFlags::DEVICE_LOCAL | MemoryPropertyFlags::HOST_VISIBLE;
+
+    props
+        .memory_types
+        .iter()
+        .enumerate()
+        .filter(|(_, memory_type)| {
+            memory_type.props.contains(bar)
+                && props.memory_heaps[memory_type.heap as usize].size <= max_heap_size
+        })
+        .fold(0, |mask, (index, _)| mask | 1 << index)
+}
+
+/// Checks that alignment mask is one less than power of two.
+pub(crate) fn valid_align_mask(align_mask: u64) -> bool {
+    align_mask & align_mask.wrapping_add(1) == 0
+}
+
+/// Device wrapper that allocates every memory object as dedicated to the resource described by `info`.
+struct DedicatedDevice<'a, D> {
+    device: &'a D,
+    info: DedicatedAllocationInfo,
+}
+
+impl<M, D> MemoryDevice<M> for DedicatedDevice<'_, D>
+where
+    D: MemoryDevice<M>,
+{
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<M, OutOfMemory> {
+        self.device
+            .allocate_dedicated_memory(size, memory_type, flags, self.info)
+    }
+
+    unsafe fn deallocate_memory(&self, memory: M) {
+        self.device.deallocate_memory(memory)
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut M,
+        offset: u64,
+        size: u64,
+    ) -> Result<core::ptr::NonNull<u8>, DeviceMapError> {
+        self.device.map_memory(memory, offset, size)
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut M) {
+        self.device.unmap_memory(memory)
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.invalidate_memory_ranges(ranges)
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.flush_memory_ranges(ranges)
+    }
+
+    fn take_allocation_diagnostic(&self) -> Option<DeviceDiagnostic> {
+        self.device.take_allocation_diagnostic()
     }
 }
 
@@ -0,0 +1,45 @@
+//! Atomic integers backing counters that are updated without exclusive access to allocator.
+//!
+//! Targets without native 64-bit atomics use `portable-atomic` when feature with the same name is enabled.
+//! Otherwise counters are plain integers and APIs that take shared reference to allocator are unavailable.
+
+pub(crate) use core::sync::atomic::Ordering::Relaxed;
+
+#[cfg(target_has_atomic = "64")]
+pub(crate) use core::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize};
+
+#[cfg(all(not(target_has_atomic = "64"), feature = "portable-atomic"))]
+pub(crate) use portable_atomic::{AtomicU32, AtomicU64, AtomicUsize};
+
+#[cfg(all(not(target_has_atomic = "64"), not(feature = "portable-atomic")))]
+pub(crate) use self::fallback::{AtomicU32, AtomicU64};
+
+/// Counters modified only through exclusive reference.
+#[cfg(all(not(target_has_atomic = "64"), not(feature = "portable-atomic")))]
+mod fallback {
+    use core::sync::atomic::Ordering;
+
+    macro_rules! counter {
+        ($name:ident, $int:ty) => {
+            #[derive(Debug, Default)]
+            pub(crate) struct $name($int);
+
+            impl $name {
+                pub(crate) const fn new(value: $int) -> Self {
+                    $name(value)
+                }
+
+                pub(crate) fn get_mut(&mut self) -> &mut $int {
+                    &mut self.0
+                }
+
+                pub(crate) fn load(&self, _: Ordering) -> $int {
+                    self.0
+                }
+            }
+        };
+    }
+
+    counter!(AtomicU32, u32);
+    counter!(AtomicU64, u64);
+}
@@ -1,12 +1,16 @@
 use {
-    crate::{align_down, align_up, error::MapError},
-    alloc::sync::Arc,
+    crate::{
+        align_down, align_up,
+        error::MapError,
+        util::{compact, host_addressable, uncompact, Compact, Generation},
+    },
+    alloc::{boxed::Box, sync::Arc, vec::Vec},
     core::{
         convert::TryFrom as _,
-        ptr::{copy_nonoverlapping, NonNull},
+        ptr::{copy_nonoverlapping, write_bytes, NonNull},
         // sync::atomic::{AtomicU8, Ordering::*},
     },
-    gpu_alloc_types::{MappedMemoryRange, MemoryDevice, MemoryPropertyFlags},
+    gpu_alloc_types::{MappedMemoryRange, MemoryDevice, MemoryPropertyFlags, OutOfMemory},
 };
 
 #[derive(Debug)]
@@ -25,7 +29,16 @@ pub struct MemoryBlock<M> {
     props: MemoryPropertyFlags,
     offset: u64,
     size: u64,
-    atom_mask: u64,
+    requested_size: u64,
+    /// Number of low bits in the non-coherent atom mask.
+    atom_bits: u8,
+    tag: Option<&'static str>,
+    id: Compact,
+    generation: Generation,
+    #[cfg(feature = "debug-checks")]
+    instance: u64,
+    #[cfg(feature = "guard-bands")]
+    guard: u64,
     mapped: bool,
     flavor: MemoryBlockFlavor<M>,
     relevant: Relevant,
@@ -37,22 +50,127 @@ impl<M> MemoryBlock<M> {
         props: MemoryPropertyFlags,
         offset: u64,
         size: u64,
+        requested_size: u64,
         atom_mask: u64,
         flavor: MemoryBlockFlavor<M>,
     ) -> Self {
         isize::try_from(atom_mask).expect("`atom_mask` is too large");
+        debug_assert_eq!(
+            atom_mask & (atom_mask + 1),
+            0,
+            "`atom_mask` must be mask of low bits"
+        );
         MemoryBlock {
             memory_type,
             props,
             offset,
             size,
-            atom_mask,
+            requested_size,
+            atom_bits: atom_mask.trailing_ones() as u8,
+            tag: None,
+            id: !0,
+            generation: 0,
+            #[cfg(feature = "debug-checks")]
+            instance: 0,
+            #[cfg(feature = "guard-bands")]
+            guard: 0,
             flavor,
             mapped: false,
             relevant: Relevant,
         }
     }
 
+    pub(crate) fn register(
+        &mut self,
+        id: usize,
+        generation: Generation,
+        tag: Option<&'static str>,
+    ) {
+        self.id = compact(id);
+        self.generation = generation;
+        self.tag = tag;
+    }
+
+    /// Returns id of this block, unique among live blocks of the allocator.
+    /// Matches [`BlockEvent::id`].
+    ///
+    /// Ids are reused after deallocation.
+    /// Blocks allocated through shared reference have id `usize::MAX`.
+    ///
+    /// [`BlockEvent::id`]: crate::BlockEvent::id
+    #[inline(always)]
+    pub fn id(&self) -> usize {
+        match self.id {
+            Compact::MAX => usize::MAX,
+            id => uncompact(id),
+        }
+    }
+
+    /// Returns `true` if block is registered in allocator.
+    /// Blocks allocated through shared reference are not.
+    #[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+    pub(crate) fn is_registered(&self) -> bool {
+        self.id != !0
+    }
+
+    fn atom_mask(&self) -> u64 {
+        (1 << self.atom_bits) - 1
+    }
+
+    pub(crate) fn generation(&self) -> Generation {
+        self.generation
+    }
+
+    /// Stamps block with id of allocator instance that allocated it.
+    #[cfg(feature = "debug-checks")]
+    pub(crate) fn with_instance(mut self, instance: u64) -> Self {
+        self.instance = instance;
+        self
+    }
+
+    #[cfg(feature = "debug-checks")]
+    pub(crate) fn instance(&self) -> u64 {
+        self.instance
+    }
+
+    /// Shrinks block to exclude guard bands of `guard` bytes at both ends.
+    #[cfg(feature = "guard-bands")]
+    pub(crate) fn with_guard(mut self, guard: u64) -> Self {
+        self.offset += guard;
+        self.size -= 2 * guard;
+        self.guard = guard;
+
+        match &mut self.flavor {
+            MemoryBlockFlavor::Dedicated { .. } => {
+                debug_assert_eq!(guard, 0, "Dedicated blocks have no guard bands")
+            }
+            MemoryBlockFlavor::Buddy { ptr, .. }
+            | MemoryBlockFlavor::FreeList { ptr, .. }
+            | MemoryBlockFlavor::Ring { ptr, .. } => {
+                *ptr = unsafe { crate::guard::inner_ptr(*ptr, guard) };
+            }
+        }
+        self
+    }
+
+    /// Returns offset and size of the range allocated for this block,
+    /// including guard bands.
+    pub(crate) fn allocated_range(&self) -> (u64, u64) {
+        #[cfg(feature = "guard-bands")]
+        {
+            (self.offset - self.guard, self.size + 2 * self.guard)
+        }
+
+        #[cfg(not(feature = "guard-bands"))]
+        {
+            (self.offset, self.size)
+        }
+    }
+
+    pub(crate) fn flavor(&self) -> &MemoryBlockFlavor<M> {
+        &self.flavor
+    }
+
     pub(crate) fn deallocate(self) -> MemoryBlockFlavor<M> {
         core::mem::forget(self.relevant);
         self.flavor
@@ -62,14 +180,154 @@ impl<M> MemoryBlock<M> {
 unsafe impl<M> Sync for MemoryBlock<M> where M: Sync {}
 unsafe impl<M> Send for MemoryBlock<M> where M: Send {}
 
+/// Raw parts of [`MemoryBlock`] produced by [`MemoryBlock::into_raw`].
+///
+/// Public fields may be stored apart from the token,
+/// for example to cross FFI boundary,
+/// but must be passed back unchanged to [`MemoryBlock::from_raw`].
+#[derive(Debug)]
+pub struct RawMemoryBlock<M> {
+    /// Index of type of parent memory object.
+    pub memory_type: u32,
+
+    /// Memory property flags for parent memory object.
+    pub props: MemoryPropertyFlags,
+
+    /// Offset in bytes from start of memory object to start of the block.
+    pub offset: u64,
+
+    /// Size of the block.
+    pub size: u64,
+
+    /// Parent memory object and allocator bookkeeping of the block.
+    pub token: MemoryBlockToken<M>,
+}
+
+/// Opaque part of [`RawMemoryBlock`] that owns parent memory object
+/// or reference to it.
+///
+/// Unlike [`MemoryBlock`] it is not reported when dropped,
+/// memory leaks silently if token is not turned back into block and deallocated.
+#[derive(Debug)]
+pub struct MemoryBlockToken<M> {
+    requested_size: u64,
+    atom_bits: u8,
+    tag: Option<&'static str>,
+    id: Compact,
+    generation: Generation,
+    #[cfg(feature = "debug-checks")]
+    instance: u64,
+    #[cfg(feature = "guard-bands")]
+    guard: u64,
+    mapped: bool,
+    flavor: MemoryBlockFlavor<M>,
+}
+
+impl<M> MemoryBlockToken<M> {
+    /// Returns reference to parent memory object.
+    #[inline(always)]
+    pub fn memory(&self) -> &M {
+        self.flavor.memory()
+    }
+}
+
+unsafe impl<M> Sync for MemoryBlockToken<M> where M: Sync {}
+unsafe impl<M> Send for MemoryBlockToken<M> where M: Send {}
+
+impl<M> MemoryBlock<M> {
+    /// Decomposes block into raw parts.
+    ///
+    /// Block can be reconstituted with [`MemoryBlock::from_raw`]
+    /// to be used or deallocated later.
+    /// Mapping state is preserved.
+    pub fn into_raw(self) -> RawMemoryBlock<M> {
+        let MemoryBlock {
+            memory_type,
+            props,
+            offset,
+            size,
+            requested_size,
+            atom_bits,
+            tag,
+            id,
+            generation,
+            #[cfg(feature = "debug-checks")]
+            instance,
+            #[cfg(feature = "guard-bands")]
+            guard,
+            mapped,
+            flavor,
+            relevant,
+        } = self;
+
+        core::mem::forget(relevant);
+
+        RawMemoryBlock {
+            memory_type,
+            props,
+            offset,
+            size,
+            token: MemoryBlockToken {
+                requested_size,
+                atom_bits,
+                tag,
+                id,
+                generation,
+                #[cfg(feature = "debug-checks")]
+                instance,
+                #[cfg(feature = "guard-bands")]
+                guard,
+                mapped,
+                flavor,
+            },
+        }
+    }
+
+    /// Reconstitutes block from raw parts returned by [`MemoryBlock::into_raw`].
+    ///
+    /// # Safety
+    ///
+    /// `raw` must have been returned by [`MemoryBlock::into_raw`]
+    /// and its public fields must not be modified.
+    pub unsafe fn from_raw(raw: RawMemoryBlock<M>) -> Self {
+        let RawMemoryBlock {
+            memory_type,
+            props,
+            offset,
+            size,
+            token,
+        } = raw;
+
+        MemoryBlock {
+            memory_type,
+            props,
+            offset,
+            size,
+            requested_size: token.requested_size,
+            atom_bits: token.atom_bits,
+            tag: token.tag,
+            id: token.id,
+            generation: token.generation,
+            #[cfg(feature = "debug-checks")]
+            instance: token.instance,
+            #[cfg(feature = "guard-bands")]
+            guard: token.guard,
+            mapped: token.mapped,
+            flavor: token.flavor,
+            relevant: Relevant,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub(crate) enum MemoryBlockFlavor<M> {
     Dedicated {
         memory: M,
+        ranges: Option<Box<MappedRanges>>,
     },
     Buddy {
-        chunk: usize,
-        index: usize,
+        chunk: Compact,
+        index: Compact,
         ptr: Option<NonNull<u8>>,
         memory: Arc<M>,
     },
@@ -78,18 +336,40 @@ pub(crate) enum MemoryBlockFlavor<M> {
         ptr: Option<NonNull<u8>>,
         memory: Arc<M>,
     },
+    Ring {
+        chunk: u64,
+        ptr: Option<NonNull<u8>>,
+        memory: Arc<M>,
+    },
 }
 
-impl<M> MemoryBlock<M> {
-    /// Returns reference to parent memory object.
+impl<M> MemoryBlockFlavor<M> {
     #[inline(always)]
-    pub fn memory(&self) -> &M {
-        match &self.flavor {
-            MemoryBlockFlavor::Dedicated { memory } => memory,
+    fn memory(&self) -> &M {
+        match self {
+            MemoryBlockFlavor::Dedicated { memory, .. } => memory,
             MemoryBlockFlavor::Buddy { memory, .. } => memory,
             MemoryBlockFlavor::FreeList { memory, .. } => memory,
+            MemoryBlockFlavor::Ring { memory, .. } => memory,
         }
     }
+}
+
+/// Disjoint ranges of dedicated block mapped with [`MemoryBlock::map_range`],
+/// sharing one device mapping of the whole block.
+#[derive(Debug)]
+pub(crate) struct MappedRanges {
+    ptr: NonNull<u8>,
+    /// Offset and size of each mapped range, sorted by offset.
+    ranges: Vec<(u64, u64)>,
+}
+
+impl<M> MemoryBlock<M> {
+    /// Returns reference to parent memory object.
+    #[inline(always)]
+    pub fn memory(&self) -> &M {
+        self.flavor.memory()
+    }
 
     /// Returns offset in bytes from start of memory object to start of this block.
     #[inline(always)]
@@ -103,6 +383,28 @@ impl<M> MemoryBlock<M> {
         self.size
     }
 
+    /// Returns size that was requested for this memory block.
+    #[inline(always)]
+    pub(crate) fn requested_size(&self) -> u64 {
+        self.requested_size
+    }
+
+    /// Returns tag this memory block was allocated with.
+    #[inline(always)]
+    pub fn tag(&self) -> Option<&'static str> {
+        self.tag
+    }
+
+    /// Returns size of each of guard bands placed before and after this block.
+    ///
+    /// Guard bands are not included in block's offset and size,
+    /// but count towards allocated memory in statistics.
+    #[cfg(feature = "guard-bands")]
+    #[inline(always)]
+    pub fn guard_band(&self) -> u64 {
+        self.guard
+    }
+
     /// Returns memory property flags for parent memory object.
     #[inline(always)]
     pub fn props(&self) -> MemoryPropertyFlags {
@@ -127,9 +429,10 @@ impl<M> MemoryBlock<M> {
     /// the user must round down the start of the range to the nearest multiple of `non_coherent_atom_size`,
     /// and round the end of the range up to the nearest multiple of `non_coherent_atom_size`.
     ///
-    /// # Panics
-    ///
-    /// This function panics if block is currently mapped.
+    /// Returns `MapError::AlreadyMapped` if block is currently mapped,
+    /// `MapError::OutOfBounds` if range is not within the block
+    /// and `MapError::Unaddressable` if range of sub-allocated block
+    /// can't be addressed by host pointer.
     ///
     /// # Safety
     ///
@@ -144,29 +447,29 @@ impl<M> MemoryBlock<M> {
     where
         MD: MemoryDevice<M>,
     {
-        let size_u64 = u64::try_from(size).expect("`size` doesn't fit device address space");
-        assert!(offset < self.size, "`offset` is out of memory block bounds");
-        assert!(
-            size_u64 <= self.size - offset,
-            "`offset + size` is out of memory block bounds"
-        );
+        let size_u64 = match u64::try_from(size) {
+            Ok(size) if offset < self.size && size <= self.size - offset => size,
+            _ => return Err(MapError::OutOfBounds),
+        };
 
-        let ptr = match &mut self.flavor {
-            MemoryBlockFlavor::Dedicated { memory } => {
-                let end = align_up(offset + size_u64, self.atom_mask)
-                    .expect("mapping end doesn't fit device address space");
-                let aligned_offset = align_down(offset, self.atom_mask);
+        // Range extended to atom boundaries is flushed and invalidated later.
+        let (atom_offset, atom_size) = self
+            .atom_range(offset, size_u64)
+            .ok_or(MapError::OutOfBounds)?;
 
-                if !acquire_mapping(&mut self.mapped) {
+        let ptr = match &mut self.flavor {
+            MemoryBlockFlavor::Dedicated { memory, ranges } => {
+                if ranges.is_some() || !acquire_mapping(&mut self.mapped) {
                     return Err(MapError::AlreadyMapped);
                 }
-                let result =
-                    device.as_ref().map_memory(memory, self.offset + aligned_offset, end - aligned_offset);
+
+                profile_scope!("MemoryBlock::map");
+                let result = device.as_ref().map_memory(memory, atom_offset, atom_size);
 
                 match result {
                     // the overflow is checked in `Self::new()`
                     Ok(ptr) => {
-                        let ptr_offset = (offset - aligned_offset) as isize;
+                        let ptr_offset = (offset - align_down(offset, self.atom_mask())) as isize;
                         ptr.as_ptr().offset(ptr_offset)
                     }
                     Err(err) => {
@@ -176,13 +479,13 @@ impl<M> MemoryBlock<M> {
                 }
             }
             MemoryBlockFlavor::FreeList { ptr: Some(ptr), .. }
-            | MemoryBlockFlavor::Buddy { ptr: Some(ptr), .. } => {
+            | MemoryBlockFlavor::Buddy { ptr: Some(ptr), .. }
+            | MemoryBlockFlavor::Ring { ptr: Some(ptr), .. } => {
+                let offset = host_offset(offset, size_u64)?;
                 if !acquire_mapping(&mut self.mapped) {
                     return Err(MapError::AlreadyMapped);
                 }
-                let offset_isize = isize::try_from(offset)
-                    .expect("Buddy and linear block should fit host address space");
-                ptr.as_ptr().offset(offset_isize)
+                ptr.as_ptr().add(offset)
             }
             _ => return Err(MapError::NonHostVisible),
         };
@@ -193,9 +496,7 @@ impl<M> MemoryBlock<M> {
     /// Unmaps memory range of this block that was previously mapped with `Block::map`.
     /// This block becomes unmapped.
     ///
-    /// # Panics
-    ///
-    /// This function panics if this block is not currently mapped.
+    /// Returns `false` and does nothing if this block is not currently mapped.
     ///
     /// # Safety
     ///
@@ -209,21 +510,145 @@ impl<M> MemoryBlock<M> {
             return false;
         }
         match &mut self.flavor {
-            MemoryBlockFlavor::Dedicated { memory } => {
+            MemoryBlockFlavor::Dedicated { memory, .. } => {
+                profile_scope!("MemoryBlock::unmap");
                 device.as_ref().unmap_memory(memory);
             }
             MemoryBlockFlavor::Buddy { .. } => {}
             MemoryBlockFlavor::FreeList { .. } => {}
+            MemoryBlockFlavor::Ring { .. } => {}
         }
         true
     }
 
+    /// Returns pointer to memory range of this block,
+    /// allowing other disjoint ranges of the block to be mapped at the same time.
+    /// Range stays mapped until unmapped with [`MemoryBlock::unmap_range`].
+    ///
+    /// Dedicated block is mapped by the device as a whole when first range is mapped
+    /// and unmapped when last range is unmapped,
+    /// so that backends that can't map memory object twice are supported.
+    /// Sub-allocated blocks share persistent mapping of their memory object
+    /// and their ranges are not tracked.
+    ///
+    /// Requirements for returned pointer are the same as for [`MemoryBlock::map`].
+    ///
+    /// Returns `MapError::AlreadyMapped` if block is mapped with [`MemoryBlock::map`]
+    /// or if the range overlaps or starts at the same offset as another mapped range,
+    /// `MapError::OutOfBounds` if range is not within the block
+    /// and `MapError::Unaddressable` if the block can't be mapped as a whole
+    /// into host address space.
+    ///
+    /// # Safety
+    ///
+    /// `block` must have been allocated from specified `device`.
+    pub unsafe fn map_range<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        offset: u64,
+        size: usize,
+    ) -> Result<NonNull<u8>, MapError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        let size = match u64::try_from(size) {
+            Ok(size) if offset < self.size && size <= self.size - offset => size,
+            _ => return Err(MapError::OutOfBounds),
+        };
+
+        if self.mapped {
+            return Err(MapError::AlreadyMapped);
+        }
+
+        let ptr = match &mut self.flavor {
+            MemoryBlockFlavor::Dedicated { memory, ranges } => {
+                // Whole block is mapped, not just the range.
+                let host_offset = host_offset(offset, self.size - offset)?;
+                let mapped = match ranges {
+                    Some(mapped) => mapped,
+                    None => {
+                        profile_scope!("MemoryBlock::map_range");
+                        let ptr = device.as_ref().map_memory(memory, self.offset, self.size)?;
+                        ranges.insert(Box::new(MappedRanges {
+                            ptr,
+                            ranges: Vec::new(),
+                        }))
+                    }
+                };
+
+                let index = mapped.ranges.partition_point(|&(start, _)| start < offset);
+                if index > 0 {
+                    let (start, size) = mapped.ranges[index - 1];
+                    if start + size > offset {
+                        return Err(MapError::AlreadyMapped);
+                    }
+                }
+                if let Some(&(start, _)) = mapped.ranges.get(index) {
+                    if start == offset || start < offset + size {
+                        return Err(MapError::AlreadyMapped);
+                    }
+                }
+
+                mapped.ranges.insert(index, (offset, size));
+                mapped.ptr.as_ptr().add(host_offset)
+            }
+            MemoryBlockFlavor::FreeList { ptr: Some(ptr), .. }
+            | MemoryBlockFlavor::Buddy { ptr: Some(ptr), .. }
+            | MemoryBlockFlavor::Ring { ptr: Some(ptr), .. } => {
+                ptr.as_ptr().add(host_offset(offset, size)?)
+            }
+            _ => return Err(MapError::NonHostVisible),
+        };
+
+        Ok(NonNull::new_unchecked(ptr))
+    }
+
+    /// Unmaps memory range starting at `offset` that was previously mapped with [`MemoryBlock::map_range`].
+    ///
+    /// Returns `false` and does nothing if dedicated block has no mapped range starting at `offset`.
+    /// Always returns `true` for sub-allocated blocks.
+    ///
+    /// # Safety
+    ///
+    /// `block` must have been allocated from specified `device`.
+    pub unsafe fn unmap_range<MD>(&mut self, device: &impl AsRef<MD>, offset: u64) -> bool
+    where
+        MD: MemoryDevice<M>,
+    {
+        match &mut self.flavor {
+            MemoryBlockFlavor::Dedicated { memory, ranges } => {
+                let mapped = match ranges {
+                    Some(mapped) => mapped,
+                    None => return false,
+                };
+
+                match mapped
+                    .ranges
+                    .binary_search_by_key(&offset, |&(start, _)| start)
+                {
+                    Ok(index) => {
+                        mapped.ranges.remove(index);
+                    }
+                    Err(_) => return false,
+                }
+
+                if mapped.ranges.is_empty() {
+                    *ranges = None;
+                    profile_scope!("MemoryBlock::unmap_range");
+                    device.as_ref().unmap_memory(memory);
+                }
+                true
+            }
+            MemoryBlockFlavor::Buddy { .. }
+            | MemoryBlockFlavor::FreeList { .. }
+            | MemoryBlockFlavor::Ring { .. } => true,
+        }
+    }
+
     /// Transiently maps block memory range and copies specified data
     /// to the mapped memory range.
     ///
-    /// # Panics
-    ///
-    /// This function panics if block is currently mapped.
+    /// Fails with the same errors as [`MemoryBlock::map`].
     ///
     /// # Safety
     ///
@@ -243,29 +668,319 @@ impl<M> MemoryBlock<M> {
         let ptr = self.map(device, offset, size)?;
 
         copy_nonoverlapping(data.as_ptr(), ptr.as_ptr(), size);
-        let result = if !self.coherent() {
-            let aligned_offset = align_down(offset, self.atom_mask);
-            let end = align_up(offset + data.len() as u64, self.atom_mask).unwrap();
+        let result = self.flush_range(device.as_ref(), offset, data.len() as u64);
+
+        self.unmap(device);
+        result.map_err(Into::into)
+    }
+
+    /// Transiently maps block memory range once and copies specified slices
+    /// to consecutive ranges starting at `offset`.
+    /// Written range is flushed with single call when memory is not coherent.
+    ///
+    /// Fails with the same errors as [`MemoryBlock::map`]
+    /// for the range covering all slices.
+    ///
+    /// # Safety
+    ///
+    /// `block` must have been allocated from specified `device`.
+    /// The caller must guarantee that any previously submitted command that reads or writes to the range has completed.
+    #[cfg(feature = "std")]
+    pub unsafe fn write_bytes_vectored<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        offset: u64,
+        iov: &[std::io::IoSlice<'_>],
+    ) -> Result<(), MapError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        let size = iov
+            .iter()
+            .try_fold(0usize, |size, data| size.checked_add(data.len()))
+            .ok_or(MapError::OutOfBounds)?;
+        let ptr = self.map(device, offset, size)?;
+
+        let mut dst = ptr.as_ptr();
+        for data in iov {
+            copy_nonoverlapping(data.as_ptr(), dst, data.len());
+            dst = dst.add(data.len());
+        }
+        let result = self.flush_range(device.as_ref(), offset, size as u64);
+
+        self.unmap(device);
+        result.map_err(Into::into)
+    }
+
+    /// Transiently maps block memory range once and copies each slice
+    /// to the range at offset paired with it.
+    /// Later writes overwrite earlier ones where they overlap.
+    /// Range spanning all writes is flushed with single call when memory is not coherent.
+    ///
+    /// Fails with the same errors as [`MemoryBlock::map`]
+    /// for the range spanning all writes.
+    ///
+    /// # Safety
+    ///
+    /// `block` must have been allocated from specified `device`.
+    /// The caller must guarantee that any previously submitted command that reads or writes to the range has completed.
+    pub unsafe fn write_bytes_scattered<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        writes: &[(u64, &[u8])],
+    ) -> Result<(), MapError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        let mut start = u64::MAX;
+        let mut end = 0;
+        for &(offset, data) in writes {
+            let write_end = offset
+                .checked_add(data.len() as u64)
+                .ok_or(MapError::OutOfBounds)?;
+            start = start.min(offset);
+            end = end.max(write_end);
+        }
+
+        if start >= end {
+            return Ok(());
+        }
+
+        let size = usize::try_from(end - start).map_err(|_| MapError::Unaddressable)?;
+        let ptr = self.map(device, start, size)?;
+
+        for &(offset, data) in writes {
+            let dst = ptr.as_ptr().add((offset - start) as usize);
+            copy_nonoverlapping(data.as_ptr(), dst, data.len());
+        }
+        let result = self.flush_range(device.as_ref(), start, end - start);
+
+        self.unmap(device);
+        result.map_err(Into::into)
+    }
+
+    /// Transiently maps block memory range and sets `len` bytes at `offset` to `byte`,
+    /// flushing the range when memory is not coherent.
+    ///
+    /// Fails with the same errors as [`MemoryBlock::map`].
+    ///
+    /// # Safety
+    ///
+    /// `block` must have been allocated from specified `device`.
+    /// The caller must guarantee that any previously submitted command that reads or writes to this range has completed.
+    pub unsafe fn fill<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        offset: u64,
+        len: usize,
+        byte: u8,
+    ) -> Result<(), MapError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        let ptr = self.map(device, offset, len)?;
+
+        write_bytes(ptr.as_ptr(), byte, len);
+        let result = self.flush_range(device.as_ref(), offset, len as u64);
+
+        self.unmap(device);
+        result.map_err(Into::into)
+    }
+
+    /// Transiently maps block memory range and repeats `pattern` over `len` bytes at `offset`,
+    /// flushing the range when memory is not coherent.
+    /// Last repetition is truncated if `len` is not multiple of pattern length.
+    ///
+    /// Useful to fill memory with sentinel values for debugging.
+    ///
+    /// Fails with the same errors as [`MemoryBlock::map`].
+    ///
+    /// # Safety
+    ///
+    /// `block` must have been allocated from specified `device`.
+    /// The caller must guarantee that any previously submitted command that reads or writes to this range has completed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pattern` is empty and `len` is not zero.
+    pub unsafe fn fill_pattern<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        offset: u64,
+        len: usize,
+        pattern: &[u8],
+    ) -> Result<(), MapError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        assert!(
+            !pattern.is_empty() || len == 0,
+            "Fill pattern must not be empty"
+        );
+
+        let ptr = self.map(device, offset, len)?;
+
+        let mut filled = 0;
+        while filled < len {
+            let size = pattern.len().min(len - filled);
+            copy_nonoverlapping(pattern.as_ptr(), ptr.as_ptr().add(filled), size);
+            filled += size;
+        }
+        let result = self.flush_range(device.as_ref(), offset, len as u64);
+
+        self.unmap(device);
+        result.map_err(Into::into)
+    }
 
-            device.as_ref().flush_memory_ranges(&[MappedMemoryRange {
+    /// Copies specified data to memory range of this block
+    /// without bounds checks, mapping state tracking and alignment of flushed range.
+    ///
+    /// Intended for hot upload loops that validate ranges once up front.
+    /// Prefer [`MemoryBlock::write_bytes`] otherwise.
+    ///
+    /// Sub-allocated blocks are written through persistent mapping of their memory object.
+    /// Dedicated block is written through mapping created by [`MemoryBlock::map_range`]
+    /// if any range of it is mapped, otherwise it is mapped and unmapped on every call.
+    /// Keep a range of dedicated block mapped for the duration of the loop to avoid that.
+    ///
+    /// Fails only if device fails to map or flush memory.
+    ///
+    /// # Safety
+    ///
+    /// All requirements of [`MemoryBlock::write_bytes`] apply. In addition:
+    ///
+    /// * Block must be host visible and not currently mapped with [`MemoryBlock::map`].
+    /// * Range `offset..offset + data.len()` must be within the block.
+    /// * For memory without `HOST_COHERENT` property flag,
+    ///   `offset` and `data.len()` must be multiples of `non_coherent_atom_size`.
+    #[inline(always)]
+    pub unsafe fn write_bytes_unchecked<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        offset: u64,
+        data: &[u8],
+    ) -> Result<(), MapError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        debug_assert!(
+            offset <= self.size && data.len() as u64 <= self.size - offset,
+            "Range is out of block bounds"
+        );
+        debug_assert!(
+            self.coherent() || (offset | data.len() as u64) & self.atom_mask() == 0,
+            "Range is not aligned to non-coherent atom size"
+        );
+
+        let size = data.len() as u64;
+        let device = device.as_ref();
+
+        match &mut self.flavor {
+            // Whole block is mapped while any range is mapped with `map_range`,
+            // and memory object must not be mapped twice.
+            MemoryBlockFlavor::Dedicated {
+                ranges: Some(mapped),
+                ..
+            } => {
+                let ptr = mapped.ptr.as_ptr().add(host_offset(offset, size)?);
+                copy_nonoverlapping(data.as_ptr(), ptr, data.len());
+            }
+            MemoryBlockFlavor::Dedicated { memory, .. } => {
+                let ptr = device.map_memory(memory, self.offset + offset, size)?;
+                copy_nonoverlapping(data.as_ptr(), ptr.as_ptr(), data.len());
+            }
+            MemoryBlockFlavor::FreeList { ptr, .. }
+            | MemoryBlockFlavor::Buddy { ptr, .. }
+            | MemoryBlockFlavor::Ring { ptr, .. } => {
+                debug_assert!(ptr.is_some(), "Block is not host visible");
+                let offset = host_offset(offset, size)?;
+                let ptr = ptr.unwrap_unchecked().as_ptr().add(offset);
+                copy_nonoverlapping(data.as_ptr(), ptr, data.len());
+            }
+        }
+
+        let result = if self.coherent() {
+            Ok(())
+        } else {
+            device.flush_memory_ranges(&[MappedMemoryRange {
                 memory: self.memory(),
-                offset: self.offset + aligned_offset,
-                size: end - aligned_offset,
+                offset: self.offset + offset,
+                size,
             }])
-        } else {
-            Ok(())
         };
 
-        self.unmap(device);
+        if let MemoryBlockFlavor::Dedicated {
+            memory,
+            ranges: None,
+        } = &mut self.flavor
+        {
+            device.unmap_memory(memory);
+        }
+
         result.map_err(Into::into)
     }
 
+    /// Flushes range of this block extended to non-coherent atom boundaries.
+    /// Does nothing for coherent memory.
+    pub(crate) unsafe fn flush_range(
+        &self,
+        device: &impl MemoryDevice<M>,
+        offset: u64,
+        size: u64,
+    ) -> Result<(), OutOfMemory> {
+        if self.coherent() {
+            return Ok(());
+        }
+
+        let (offset, size) = self
+            .atom_range(offset, size)
+            .expect("Range is checked when mapped");
+
+        device.flush_memory_ranges(&[MappedMemoryRange {
+            memory: self.memory(),
+            offset,
+            size,
+        }])
+    }
+
+    /// Invalidates range of this block extended to non-coherent atom boundaries.
+    /// Does nothing for coherent memory.
+    unsafe fn invalidate_range(
+        &self,
+        device: &impl MemoryDevice<M>,
+        offset: u64,
+        size: u64,
+    ) -> Result<(), OutOfMemory> {
+        if self.coherent() {
+            return Ok(());
+        }
+
+        let (offset, size) = self
+            .atom_range(offset, size)
+            .expect("Range is checked when mapped");
+
+        device.invalidate_memory_ranges(&[MappedMemoryRange {
+            memory: self.memory(),
+            offset,
+            size,
+        }])
+    }
+
+    /// Returns offset and size of the range in memory object
+    /// extended to non-coherent atom boundaries, or `None` on overflow.
+    fn atom_range(&self, offset: u64, size: u64) -> Option<(u64, u64)> {
+        let aligned_offset = align_down(offset, self.atom_mask());
+        let end = align_up(offset.checked_add(size)?, self.atom_mask())?;
+        Some((
+            self.offset.checked_add(aligned_offset)?,
+            end - aligned_offset,
+        ))
+    }
+
     /// Transiently maps block memory range and copies specified data
     /// from the mapped memory range.
     ///
-    /// # Panics
-    ///
-    /// This function panics if block is currently mapped.
+    /// Fails with the same errors as [`MemoryBlock::map`].
     ///
     /// # Safety
     ///
@@ -290,18 +1005,7 @@ impl<M> MemoryBlock<M> {
 
         let size = data.len();
         let ptr = self.map(device, offset, size)?;
-        let result = if !self.coherent() {
-            let aligned_offset = align_down(offset, self.atom_mask);
-            let end = align_up(offset + data.len() as u64, self.atom_mask).unwrap();
-
-            device.as_ref().invalidate_memory_ranges(&[MappedMemoryRange {
-                memory: self.memory(),
-                offset: self.offset + aligned_offset,
-                size: end - aligned_offset,
-            }])
-        } else {
-            Ok(())
-        };
+        let result = self.invalidate_range(device.as_ref(), offset, size as u64);
         if result.is_ok() {
             copy_nonoverlapping(ptr.as_ptr(), data.as_mut_ptr(), size);
         }
@@ -310,6 +1014,54 @@ impl<M> MemoryBlock<M> {
         result.map_err(Into::into)
     }
 
+    /// Copies `size` bytes from this block at `src_offset`
+    /// to `dst` block at `dst_offset` on the host.
+    ///
+    /// Both blocks are transiently mapped, unless their memory is mapped persistently.
+    /// Source range is invalidated before copy and destination range is flushed after,
+    /// when memory is not coherent.
+    ///
+    /// Fails with the same errors as [`MemoryBlock::map`] for either block.
+    ///
+    /// # Safety
+    ///
+    /// Both blocks must have been allocated from specified `device`.
+    /// The caller must guarantee that any previously submitted command that writes to source range
+    /// or reads or writes to destination range has completed.
+    pub unsafe fn copy_to<MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        dst: &mut MemoryBlock<M>,
+        src_offset: u64,
+        dst_offset: u64,
+        size: u64,
+    ) -> Result<(), MapError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        let size_usize = usize::try_from(size).map_err(|_| MapError::Unaddressable)?;
+        let src_ptr = self.map(device, src_offset, size_usize)?;
+        let dst_ptr = match dst.map(device, dst_offset, size_usize) {
+            Ok(ptr) => ptr,
+            Err(err) => {
+                self.unmap(device);
+                return Err(err);
+            }
+        };
+
+        let result = self
+            .invalidate_range(device.as_ref(), src_offset, size)
+            .and_then(|()| {
+                // Blocks never overlap, even when they share memory object.
+                copy_nonoverlapping(src_ptr.as_ptr(), dst_ptr.as_ptr(), size_usize);
+                dst.flush_range(device.as_ref(), dst_offset, size)
+            });
+
+        dst.unmap(device);
+        self.unmap(device);
+        result.map_err(Into::into)
+    }
+
     fn coherent(&self) -> bool {
         self.props.contains(MemoryPropertyFlags::HOST_COHERENT)
     }
@@ -320,6 +1072,17 @@ impl<M> MemoryBlock<M> {
     }
 }
 
+/// Converts offset of mapped range into host pointer offset.
+///
+/// Pointer offsets are limited to `isize::MAX`,
+/// so on 32-bit hosts ranges that end beyond 2 GiB can't be addressed.
+fn host_offset(offset: u64, size: u64) -> Result<usize, MapError> {
+    match offset.checked_add(size) {
+        Some(end) if host_addressable(end) => Ok(offset as usize),
+        _ => Err(MapError::Unaddressable),
+    }
+}
+
 fn acquire_mapping(mapped: &mut bool) -> bool {
     if *mapped {
         false
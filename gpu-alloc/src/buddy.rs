@@ -1,10 +1,18 @@
 use {
     crate::{
-        align_up, error::AllocationError, heap::Heap, slab::Slab, unreachable_unchecked,
-        util::try_arc_unwrap, MemoryBounds,
+        align_up,
+        dump::ChunkKind,
+        error::AllocationError,
+        heap::Heap,
+        observer::{self, Observer},
+        slab::Slab,
+        stats::{FragmentationStats, StrategyStats},
+        unreachable_unchecked,
+        util::{host_addressable, try_arc_unwrap},
+        MemoryBounds,
     },
     alloc::{sync::Arc, vec::Vec},
-    core::{convert::TryFrom as _, mem::replace, ptr::NonNull},
+    core::{convert::TryFrom as _, fmt, mem::replace, ptr::NonNull},
     gpu_alloc_types::{AllocationFlags, DeviceMapError, MemoryDevice, MemoryPropertyFlags},
 };
 
@@ -21,25 +29,24 @@ pub(crate) struct BuddyBlock<M> {
 unsafe impl<M> Sync for BuddyBlock<M> where M: Sync {}
 unsafe impl<M> Send for BuddyBlock<M> where M: Send {}
 
+/// Index of absent pair or block.
+const NONE: u32 = !0;
+
 #[derive(Clone, Copy, Debug)]
 enum PairState {
     Exhausted,
-    Ready {
-        ready: Side,
-        next: usize,
-        prev: usize,
-    },
+    Ready { ready: Side, next: u32, prev: u32 },
 }
 
 impl PairState {
-    unsafe fn replace_next(&mut self, value: usize) -> usize {
+    unsafe fn replace_next(&mut self, value: u32) -> u32 {
         match self {
             PairState::Exhausted => unreachable_unchecked(),
             PairState::Ready { next, .. } => replace(next, value),
         }
     }
 
-    unsafe fn replace_prev(&mut self, value: usize) -> usize {
+    unsafe fn replace_prev(&mut self, value: u32) -> u32 {
         match self {
             PairState::Exhausted => unreachable_unchecked(),
             PairState::Ready { prev, .. } => replace(prev, value),
@@ -54,12 +61,18 @@ enum Side {
 }
 use Side::*;
 
+/// Pair of buddy blocks of the same size.
+///
+/// There may be hundreds of thousands of pairs, so indices are 32-bit
+/// and offset is stored in units of pair size.
+/// Ready pairs form intrusive circular list through `next` and `prev` indices.
 #[derive(Debug)]
 struct PairEntry {
     state: PairState,
-    chunk: usize,
-    offset: u64,
-    parent: Option<usize>,
+    chunk: u32,
+    offset: u32,
+    /// Index of parent block or `NONE` for pair that spans whole chunk.
+    parent: u32,
 }
 
 struct SizeBlockEntry {
@@ -70,7 +83,9 @@ struct SizeBlockEntry {
 
 #[derive(Debug)]
 struct Size {
-    next_ready: usize,
+    /// Binary logarithm of pair size.
+    pair_shift: u32,
+    next_ready: u32,
     pairs: Slab<PairEntry>,
 }
 #[derive(Debug)]
@@ -81,37 +96,55 @@ enum Release {
 }
 
 impl Size {
-    fn new() -> Self {
+    fn new(block_size: u64) -> Self {
         Size {
+            pair_shift: block_size.trailing_zeros() + 1,
             pairs: Slab::new(),
             next_ready: 0,
         }
     }
 
+    fn len(&self) -> u32 {
+        self.pairs.len() as u32
+    }
+
+    fn block_size(&self) -> u64 {
+        1 << (self.pair_shift - 1)
+    }
+
+    fn pair_offset(&self, entry: &PairEntry) -> u64 {
+        u64::from(entry.offset) << self.pair_shift
+    }
+
     unsafe fn add_pair_and_acquire_left(
         &mut self,
         chunk: usize,
         offset: u64,
         parent: Option<usize>,
     ) -> SizeBlockEntry {
-        if self.next_ready < self.pairs.len() {
+        if self.next_ready < self.len() {
             unreachable_unchecked()
         }
 
         let index = self.pairs.insert(PairEntry {
             state: PairState::Exhausted,
-            chunk,
-            offset,
-            parent,
+            chunk: chunk as u32,
+            offset: (offset >> self.pair_shift) as u32,
+            parent: parent.map_or(NONE, |parent| parent as u32),
         });
 
+        assert!(
+            index < (NONE >> 1) as usize,
+            "Too many buddy blocks of the same size"
+        );
+
         let entry = self.pairs.get_unchecked_mut(index);
         entry.state = PairState::Ready {
-            next: index,
-            prev: index,
+            next: index as u32,
+            prev: index as u32,
             ready: Right, // Left is allocated.
         };
-        self.next_ready = index;
+        self.next_ready = index as u32;
 
         SizeBlockEntry {
             chunk,
@@ -120,16 +153,18 @@ impl Size {
         }
     }
 
-    fn acquire(&mut self, size: u64) -> Option<SizeBlockEntry> {
-        if self.next_ready >= self.pairs.len() {
+    fn acquire(&mut self) -> Option<SizeBlockEntry> {
+        if self.next_ready >= self.len() {
             return None;
         }
 
         let ready = self.next_ready;
+        let len = self.len();
+        let block_size = self.block_size();
 
-        let entry = unsafe { self.pairs.get_unchecked_mut(ready) };
-        let chunk = entry.chunk;
-        let offset = entry.offset;
+        let entry = unsafe { self.pairs.get_unchecked_mut(ready as usize) };
+        let chunk = entry.chunk as usize;
+        let offset = u64::from(entry.offset) << self.pair_shift;
 
         let bit = match entry.state {
             PairState::Exhausted => unsafe { unreachable_unchecked() },
@@ -139,13 +174,13 @@ impl Size {
                 if prev == self.next_ready {
                     // The only ready entry.
                     debug_assert_eq!(next, self.next_ready);
-                    self.next_ready = self.pairs.len();
+                    self.next_ready = len;
                 } else {
-                    let prev_entry = unsafe { self.pairs.get_unchecked_mut(prev) };
+                    let prev_entry = unsafe { self.pairs.get_unchecked_mut(prev as usize) };
                     let prev_next = unsafe { prev_entry.state.replace_next(next) };
                     debug_assert_eq!(prev_next, self.next_ready);
 
-                    let next_entry = unsafe { self.pairs.get_unchecked_mut(next) };
+                    let next_entry = unsafe { self.pairs.get_unchecked_mut(next as usize) };
                     let next_prev = unsafe { next_entry.state.replace_prev(prev) };
                     debug_assert_eq!(next_prev, self.next_ready);
 
@@ -161,24 +196,57 @@ impl Size {
 
         Some(SizeBlockEntry {
             chunk,
-            offset: offset + bit as u64 * size,
-            index: (ready << 1) | bit as usize,
+            offset: offset + bit as u64 * block_size,
+            index: ((ready as usize) << 1) | bit as usize,
+        })
+    }
+
+    /// Returns iterator over chunk indices and offsets of free blocks.
+    fn free_entries(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        let block_size = self.block_size();
+        self.pairs.entries().filter_map(move |entry| match entry {
+            Ok(
+                entry @ PairEntry {
+                    state: PairState::Ready { ready, .. },
+                    ..
+                },
+            ) => Some((
+                entry.chunk as usize,
+                self.pair_offset(entry) + (*ready == Right) as u64 * block_size,
+            )),
+            _ => None,
         })
     }
 
+    /// Returns number of pairs with one free block.
+    fn free_blocks(&self) -> u64 {
+        self.pairs
+            .entries()
+            .filter(|entry| {
+                matches!(
+                    entry,
+                    Ok(PairEntry {
+                        state: PairState::Ready { .. },
+                        ..
+                    })
+                )
+            })
+            .count() as u64
+    }
+
     fn release(&mut self, index: usize) -> Release {
         let side = match index & 1 {
             0 => Side::Left,
             1 => Side::Right,
             _ => unsafe { unreachable_unchecked() },
         };
-        let entry_index = index >> 1;
+        let entry_index = (index >> 1) as u32;
 
-        let len = self.pairs.len();
+        let len = self.len();
 
-        let entry = self.pairs.get_mut(entry_index);
+        let entry = self.pairs.get_mut(entry_index as usize);
 
-        let chunk = entry.chunk;
+        let chunk = entry.chunk as usize;
         let offset = entry.offset;
         let parent = entry.parent;
 
@@ -195,14 +263,14 @@ impl Size {
                     debug_assert!(self.next_ready < len);
 
                     let next = self.next_ready;
-                    let next_entry = unsafe { self.pairs.get_unchecked_mut(next) };
+                    let next_entry = unsafe { self.pairs.get_unchecked_mut(next as usize) };
                     let prev = unsafe { next_entry.state.replace_prev(entry_index) };
 
-                    let prev_entry = unsafe { self.pairs.get_unchecked_mut(prev) };
+                    let prev_entry = unsafe { self.pairs.get_unchecked_mut(prev as usize) };
                     let prev_next = unsafe { prev_entry.state.replace_next(entry_index) };
                     debug_assert_eq!(prev_next, next);
 
-                    let entry = unsafe { self.pairs.get_unchecked_mut(entry_index) };
+                    let entry = unsafe { self.pairs.get_unchecked_mut(entry_index as usize) };
                     entry.state = PairState::Ready {
                         ready: side,
                         next,
@@ -218,30 +286,29 @@ impl Size {
 
             PairState::Ready { next, prev, .. } => {
                 unsafe {
-                    self.pairs.remove_unchecked(entry_index);
+                    self.pairs.remove_unchecked(entry_index as usize);
                 }
 
                 if prev == entry_index {
                     debug_assert_eq!(next, entry_index);
-                    self.next_ready = self.pairs.len();
+                    self.next_ready = self.len();
                 } else {
-                    let prev_entry = unsafe { self.pairs.get_unchecked_mut(prev) };
+                    let prev_entry = unsafe { self.pairs.get_unchecked_mut(prev as usize) };
                     let prev_next = unsafe { prev_entry.state.replace_next(next) };
                     debug_assert_eq!(prev_next, entry_index);
 
-                    let next_entry = unsafe { self.pairs.get_unchecked_mut(next) };
+                    let next_entry = unsafe { self.pairs.get_unchecked_mut(next as usize) };
                     let next_prev = unsafe { next_entry.state.replace_prev(prev) };
                     debug_assert_eq!(next_prev, entry_index);
 
                     self.next_ready = next;
                 }
 
-                match parent {
-                    Some(parent) => Release::Parent(parent),
-                    None => {
-                        debug_assert_eq!(offset, 0);
-                        Release::Chunk(chunk)
-                    }
+                if parent == NONE {
+                    debug_assert_eq!(offset, 0);
+                    Release::Chunk(chunk)
+                } else {
+                    Release::Parent(parent as usize)
                 }
             }
         }
@@ -263,11 +330,31 @@ pub(crate) struct BuddyAllocator<M> {
     memory_type: u32,
     props: MemoryPropertyFlags,
     atom_mask: u64,
+
+    /// Number of cleanups each emptied chunk survives.
+    keep_alive: u32,
+    /// Empty chunks kept alive and cleanups remaining until they are destroyed.
+    retained: Vec<(usize, u32)>,
+
+    /// Number of cleanups so far.
+    cleanups: u64,
+    /// Size of the most recently destroyed chunk and cleanup it was destroyed after.
+    destroyed: Option<(u64, u64)>,
+    /// Number of chunks created no later than one cleanup after chunk of the same size was destroyed.
+    oscillations: u64,
+
+    memory_bytes: u64,
 }
 
 unsafe impl<M> Sync for BuddyAllocator<M> where M: Sync {}
 unsafe impl<M> Send for BuddyAllocator<M> where M: Send {}
 
+/// Returns size of chunk split into `sizes` size classes, or `None` on overflow.
+fn chunk_size(minimal_size: u64, sizes: usize) -> Option<u64> {
+    let shift = u32::try_from(sizes).ok()?;
+    1u64.checked_shl(minimal_size.trailing_zeros().checked_add(shift)?)
+}
+
 impl<M> BuddyAllocator<M>
 where
     M: MemoryBounds + 'static,
@@ -278,6 +365,7 @@ where
         memory_type: u32,
         props: MemoryPropertyFlags,
         atom_mask: u64,
+        keep_alive: u32,
     ) -> Self {
         assert!(
             minimal_size.is_power_of_two(),
@@ -295,14 +383,32 @@ where
         BuddyAllocator {
             minimal_size,
             chunks: Slab::new(),
-            sizes: (0..initial_sizes).map(|_| Size::new()).collect(),
+            sizes: (0..initial_sizes)
+                .map(|index| Size::new(minimal_size << index))
+                .collect(),
             memory_type,
             props,
             atom_mask: atom_mask | (minimal_size - 1),
+
+            keep_alive,
+            retained: Vec::new(),
+
+            cleanups: 0,
+            destroyed: None,
+            oscillations: 0,
+
+            memory_bytes: 0,
         }
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    /// Returns size of block that would be allocated for the request.
+    pub fn block_size(&self, size: u64, align_mask: u64) -> Option<u64> {
+        let size = align_up(size, align_mask | self.atom_mask)?.checked_next_power_of_two()?;
+        Some(size.max(self.minimal_size))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device, observer)))]
     pub unsafe fn alloc(
         &mut self,
         device: &impl MemoryDevice<M>,
@@ -311,69 +417,72 @@ where
         flags: AllocationFlags,
         heap: &mut Heap,
         allocations_remains: &mut u32,
+        mut observer: Observer<'_>,
     ) -> Result<BuddyBlock<M>, AllocationError> {
-        let align_mask = align_mask | self.atom_mask;
-
-        let size = align_up(size, align_mask)
-            .and_then(|size| size.checked_next_power_of_two())
-            .ok_or(AllocationError::OutOfDeviceMemory)?;
-
-        let size = size.max(self.minimal_size);
+        let size = self
+            .block_size(size, align_mask)
+            .ok_or(AllocationError::SizeOverflow)?;
 
         let size_index = size.trailing_zeros() - self.minimal_size.trailing_zeros();
         let size_index =
             usize::try_from(size_index).map_err(|_| AllocationError::OutOfDeviceMemory)?;
 
-        while self.sizes.len() <= size_index {
-            self.sizes.push(Size::new());
+        // Reject before size classes grow, so that larger chunks are not attempted later.
+        if chunk_size(self.minimal_size, size_index + 1).is_none() {
+            return Err(AllocationError::SizeOverflow);
         }
 
-        let host_visible = self.host_visible();
+        while self.sizes.len() <= size_index {
+            self.sizes
+                .push(Size::new(self.minimal_size << self.sizes.len()));
+        }
 
         let mut candidate_size_index = size_index;
 
-        let (mut entry, entry_size_index) = loop {
+        let (entry, entry_size_index) = loop {
             let sizes_len = self.sizes.len();
 
             let candidate_size_entry = &mut self.sizes[candidate_size_index];
-            let candidate_size = self.minimal_size << candidate_size_index;
 
-            if let Some(entry) = candidate_size_entry.acquire(candidate_size) {
+            if let Some(entry) = candidate_size_entry.acquire() {
                 break (entry, candidate_size_index);
             }
 
             if sizes_len == candidate_size_index + 1 {
                 // That's size of device allocation.
+                let chunk_size = chunk_size(self.minimal_size, candidate_size_index + 1)
+                    .ok_or(AllocationError::SizeOverflow)?;
+
+                let chunks = &self.chunks;
+                if let Some(retained) = self
+                    .retained
+                    .iter()
+                    .position(|&(chunk, _)| chunks.get(chunk).size == chunk_size)
+                {
+                    let (chunk, _) = self.retained.swap_remove(retained);
+                    let entry = candidate_size_entry.add_pair_and_acquire_left(chunk, 0, None);
+                    break (entry, candidate_size_index);
+                }
+
                 if *allocations_remains == 0 {
                     return Err(AllocationError::TooManyObjects);
                 }
 
-                let chunk_size = self.minimal_size << (candidate_size_index + 1);
-                let mut memory = device.allocate_memory(chunk_size, self.memory_type, flags)?;
-                *allocations_remains -= 1;
-                heap.alloc(chunk_size);
-
-                let ptr = if host_visible {
-                    match device.map_memory(&mut memory, 0, chunk_size) {
-                        Ok(ptr) => Some(ptr),
-                        Err(DeviceMapError::OutOfDeviceMemory) => {
-                            return Err(AllocationError::OutOfDeviceMemory)
-                        }
-                        Err(DeviceMapError::MapFailed) | Err(DeviceMapError::OutOfHostMemory) => {
-                            return Err(AllocationError::OutOfHostMemory)
-                        }
-                    }
-                } else {
-                    None
-                };
-
-                let chunk = self.chunks.insert(Chunk {
-                    memory: Arc::new(memory),
-                    ptr,
-                    size: chunk_size,
-                });
+                // Pair offsets are 32-bit in units of pair size.
+                if candidate_size_index >= 32 {
+                    return Err(AllocationError::OutOfDeviceMemory);
+                }
 
-                let entry = candidate_size_entry.add_pair_and_acquire_left(chunk, 0, None);
+                let chunk = self.create_chunk(
+                    device,
+                    chunk_size,
+                    flags,
+                    heap,
+                    allocations_remains,
+                    &mut observer,
+                )?;
+                let entry =
+                    self.sizes[candidate_size_index].add_pair_and_acquire_left(chunk, 0, None);
 
                 break (entry, candidate_size_index);
             }
@@ -381,23 +490,231 @@ where
             candidate_size_index += 1;
         };
 
-        for size_index in (size_index..entry_size_index).rev() {
+        let entry = self.split(entry, entry_size_index, size_index);
+        Ok(self.block(entry, size))
+    }
+
+    /// Creates empty chunks of current chunk size until at least `bytes` bytes
+    /// are retained for reuse.
+    ///
+    /// Chunks are retained as if they were just emptied,
+    /// but at least until the next cleanup.
+    pub unsafe fn preallocate(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        bytes: u64,
+        flags: AllocationFlags,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        mut observer: Observer<'_>,
+    ) -> Result<(), AllocationError> {
+        let size_index = self.sizes.len();
+        let chunk_size =
+            chunk_size(self.minimal_size, size_index).ok_or(AllocationError::SizeOverflow)?;
+
+        // Pair offsets are 32-bit in units of pair size.
+        if size_index > 32 {
+            return Err(AllocationError::OutOfDeviceMemory);
+        }
+
+        let chunks = &self.chunks;
+        let mut retained_bytes: u64 = self
+            .retained
+            .iter()
+            .map(|&(chunk, _)| chunks.get(chunk).size)
+            .filter(|&size| size == chunk_size)
+            .sum();
+
+        while retained_bytes < bytes {
+            if *allocations_remains == 0 {
+                return Err(AllocationError::TooManyObjects);
+            }
+
+            let chunk = self.create_chunk(
+                device,
+                chunk_size,
+                flags,
+                heap,
+                allocations_remains,
+                &mut observer,
+            )?;
+            self.retained.push((chunk, self.keep_alive.max(1)));
+            retained_bytes += chunk_size;
+        }
+
+        Ok(())
+    }
+
+    /// Allocates new memory object of `chunk_size` bytes
+    /// and returns index of the chunk.
+    unsafe fn create_chunk(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        chunk_size: u64,
+        flags: AllocationFlags,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        observer: &mut Observer<'_>,
+    ) -> Result<usize, AllocationError> {
+        // Whole chunk is mapped and blocks are addressed by pointer offsets.
+        if self.host_visible() && !host_addressable(chunk_size) {
+            return Err(AllocationError::OutOfHostMemory);
+        }
+
+        heap.reserve(chunk_size)?;
+
+        profile_scope!("BuddyAllocator::create_chunk");
+
+        let mut memory = match device.allocate_memory(chunk_size, self.memory_type, flags) {
+            Ok(memory) => memory,
+            Err(err) => {
+                heap.release(chunk_size);
+                return Err(err.into());
+            }
+        };
+        *allocations_remains -= 1;
+        heap.alloc(chunk_size);
+
+        let ptr = if self.host_visible() {
+            match device.map_memory(&mut memory, 0, chunk_size) {
+                Ok(ptr) => Some(ptr),
+                Err(err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!("Failed to map host-visible memory in buddy allocator");
+                    device.deallocate_memory(memory);
+                    *allocations_remains += 1;
+                    heap.dealloc(chunk_size);
+
+                    return Err(match err {
+                        DeviceMapError::OutOfDeviceMemory => AllocationError::OutOfDeviceMemory,
+                        DeviceMapError::MapFailed | DeviceMapError::OutOfHostMemory => {
+                            AllocationError::OutOfHostMemory
+                        }
+                    });
+                }
+            }
+        } else {
+            None
+        };
+
+        let chunk = self.chunks.insert(Chunk {
+            memory: Arc::new(memory),
+            ptr,
+            size: chunk_size,
+        });
+        self.memory_bytes += chunk_size;
+
+        // Chunk of the same size was destroyed no earlier than one cleanup ago.
+        if let Some((size, cleanups)) = self.destroyed {
+            if size == chunk_size && cleanups + 1 >= self.cleanups {
+                self.oscillations += 1;
+            }
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            memory_type = self.memory_type,
+            strategy = "buddy",
+            chunk,
+            size = chunk_size,
+            "chunk created"
+        );
+
+        observer::chunk_created(
+            observer,
+            self.memory_type,
+            ChunkKind::Buddy,
+            chunk as u64,
+            chunk_size,
+        );
+
+        Ok(chunk)
+    }
+
+    /// Returns size of the largest block between `min_size` and `max_size`
+    /// that can be allocated from free blocks of existing chunks.
+    pub fn existing_block_size(
+        &self,
+        min_size: u64,
+        max_size: u64,
+        align_mask: u64,
+    ) -> Option<u64> {
+        let (_, size_index) = self.find_existing(min_size, max_size, align_mask)?;
+        Some(self.minimal_size << size_index)
+    }
+
+    /// Allocates the largest block between `min_size` and `max_size`
+    /// from free blocks of existing chunks, without creating new chunk.
+    pub unsafe fn alloc_existing(
+        &mut self,
+        min_size: u64,
+        max_size: u64,
+        align_mask: u64,
+    ) -> Option<BuddyBlock<M>> {
+        let (entry_size_index, size_index) = self.find_existing(min_size, max_size, align_mask)?;
+        let entry = self.sizes[entry_size_index].acquire()?;
+        let entry = self.split(entry, entry_size_index, size_index);
+        Some(self.block(entry, self.minimal_size << size_index))
+    }
+
+    /// Returns size class of free block to split
+    /// and size class of the largest block between `min_size` and `max_size`.
+    fn find_existing(
+        &self,
+        min_size: u64,
+        max_size: u64,
+        align_mask: u64,
+    ) -> Option<(usize, usize)> {
+        let min_size = self.block_size(min_size, align_mask)?;
+        let min_index = (min_size.trailing_zeros() - self.minimal_size.trailing_zeros()) as usize;
+
+        // Largest size class that does not exceed `max_size`.
+        let max_index = match max_size {
+            0 => 0,
+            _ => (63 - max_size.leading_zeros()).saturating_sub(self.minimal_size.trailing_zeros())
+                as usize,
+        }
+        .max(min_index);
+
+        let ready = |index: &usize| {
+            let size = &self.sizes[*index];
+            size.next_ready < size.len()
+        };
+
+        if let Some(index) = (max_index..self.sizes.len()).find(ready) {
+            return Some((index, max_index));
+        }
+
+        (min_index..max_index.min(self.sizes.len()))
+            .rev()
+            .find(ready)
+            .map(|index| (index, index))
+    }
+
+    /// Splits block of `from` size class down to `to` size class.
+    unsafe fn split(
+        &mut self,
+        mut entry: SizeBlockEntry,
+        from: usize,
+        to: usize,
+    ) -> SizeBlockEntry {
+        for size_index in (to..from).rev() {
             let size_entry = &mut self.sizes[size_index];
             entry =
                 size_entry.add_pair_and_acquire_left(entry.chunk, entry.offset, Some(entry.index));
         }
+        entry
+    }
 
+    unsafe fn block(&self, entry: SizeBlockEntry, size: u64) -> BuddyBlock<M> {
         let chunk_entry = self.chunks.get_unchecked(entry.chunk);
 
         debug_assert!(
-            entry
-                .offset
-                .checked_add(size)
-                .map_or(false, |end| end <= chunk_entry.size),
+            matches!(entry.offset.checked_add(size), Some(end) if end <= chunk_entry.size),
             "Offset + size is not in chunk bounds"
         );
 
-        Ok(BuddyBlock {
+        BuddyBlock {
             memory: chunk_entry.memory.clone(),
             ptr: chunk_entry
                 .ptr
@@ -406,16 +723,17 @@ where
             size,
             chunk: entry.chunk,
             index: entry.index,
-        })
+        }
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device, observer)))]
     pub unsafe fn dealloc(
         &mut self,
         device: &impl MemoryDevice<M>,
         block: BuddyBlock<M>,
         heap: &mut Heap,
         allocations_remains: &mut u32,
+        observer: Observer<'_>,
     ) {
         debug_assert!(block.size.is_power_of_two());
 
@@ -437,16 +755,15 @@ where
                         self.chunks.get(chunk).size,
                         self.minimal_size << (release_size_index + 1)
                     );
-                    let chunk = self.chunks.remove(chunk);
                     drop(block);
 
-                    let memory = try_arc_unwrap(chunk.memory)
-                        .expect("Memory shared after last block deallocated");
-
-                    device.deallocate_memory(memory);
-                    *allocations_remains += 1;
-                    heap.dealloc(chunk.size);
+                    if self.keep_alive > 0 {
+                        // Keep chunk for reuse until it stays empty for `keep_alive` cleanups.
+                        self.retained.push((chunk, self.keep_alive));
+                        return;
+                    }
 
+                    self.destroy_chunk(device, chunk, heap, allocations_remains, observer);
                     return;
                 }
                 Release::None => return,
@@ -454,7 +771,279 @@ where
         }
     }
 
+    /// Counts cleanup for retained empty chunks, destroying those whose time is up.
+    pub unsafe fn cleanup(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        mut observer: Observer<'_>,
+    ) {
+        self.cleanups += 1;
+
+        let mut index = 0;
+        while index < self.retained.len() {
+            let (chunk, cleanups) = &mut self.retained[index];
+            *cleanups -= 1;
+            if *cleanups == 0 {
+                let chunk = *chunk;
+                self.retained.swap_remove(index);
+                self.destroy_chunk(
+                    device,
+                    chunk,
+                    heap,
+                    allocations_remains,
+                    observer.as_deref_mut(),
+                );
+            } else {
+                index += 1;
+            }
+        }
+    }
+
+    /// Destroys retained empty chunks until at most `keep_bytes` bytes are retained,
+    /// starting with chunks closest to being destroyed by cleanup.
+    ///
+    /// Returns number of bytes retained.
+    pub unsafe fn trim(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        keep_bytes: u64,
+        mut observer: Observer<'_>,
+    ) -> u64 {
+        self.retained
+            .sort_by_key(|&(_, cleanups)| core::cmp::Reverse(cleanups));
+
+        let chunks = &self.chunks;
+        let mut retained_bytes: u64 = self
+            .retained
+            .iter()
+            .map(|&(chunk, _)| chunks.get(chunk).size)
+            .sum();
+
+        while retained_bytes > keep_bytes {
+            let (chunk, _) = match self.retained.pop() {
+                Some(retained) => retained,
+                None => break,
+            };
+
+            retained_bytes -= self.chunks.get(chunk).size;
+            self.destroy_chunk(
+                device,
+                chunk,
+                heap,
+                allocations_remains,
+                observer.as_deref_mut(),
+            );
+        }
+
+        retained_bytes
+    }
+
+    /// Returns number of chunks created no later than one cleanup after chunk of the same size was destroyed.
+    pub fn oscillations(&self) -> u64 {
+        self.oscillations
+    }
+
+    unsafe fn destroy_chunk(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        index: usize,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        mut observer: Observer<'_>,
+    ) {
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            memory_type = self.memory_type,
+            strategy = "buddy",
+            chunk = index,
+            size = self.chunks.get(index).size,
+            "chunk destroyed"
+        );
+
+        profile_scope!("BuddyAllocator::destroy_chunk");
+
+        let chunk = self.chunks.remove(index);
+
+        let memory =
+            try_arc_unwrap(chunk.memory).expect("Memory shared after last block deallocated");
+
+        device.deallocate_memory(memory);
+        *allocations_remains += 1;
+        heap.dealloc(chunk.size);
+        self.memory_bytes -= chunk.size;
+        self.destroyed = Some((chunk.size, self.cleanups));
+
+        observer::chunk_destroyed(
+            &mut observer,
+            self.memory_type,
+            ChunkKind::Buddy,
+            index as u64,
+            chunk.size,
+        );
+    }
+
+    /// Fills memory object counters of `stats`.
+    pub(crate) fn memory_stats(&self, stats: &mut StrategyStats) {
+        stats.memory_objects = self.chunks.occupied() as u64;
+        stats.memory_bytes = self.memory_bytes;
+    }
+
+    /// Returns iterator over indices and sizes of chunks.
+    pub(crate) fn chunks(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.chunks
+            .entries()
+            .enumerate()
+            .filter_map(|(index, entry)| Some((index, entry.ok()?.size)))
+    }
+
+    /// Returns iterator over chunk indices, offsets and sizes of free blocks.
+    pub(crate) fn free_blocks(&self) -> impl Iterator<Item = (usize, u64, u64)> + '_ {
+        let minimal_size = self.minimal_size;
+        self.sizes
+            .iter()
+            .enumerate()
+            .flat_map(move |(index, size)| {
+                let block_size = minimal_size << index;
+                size.free_entries()
+                    .map(move |(chunk, offset)| (chunk, offset, block_size))
+            })
+            .chain(
+                self.retained
+                    .iter()
+                    .map(move |&(chunk, _)| (chunk, 0, self.chunks.get(chunk).size)),
+            )
+    }
+
+    /// Fills free space measures of `stats`.
+    pub(crate) fn fragmentation_stats(&self, stats: &mut FragmentationStats) {
+        stats.buddy_minimal_size = self.minimal_size;
+        stats.buddy_free_blocks = self.sizes.iter().map(Size::free_blocks).collect();
+        stats.buddy_chunks = self.chunks().count() as u64;
+
+        for index in 0..stats.buddy_free_blocks.len() {
+            let count = stats.buddy_free_blocks[index];
+            stats.free_region(self.minimal_size << index, count);
+        }
+
+        for &(chunk, _) in &self.retained {
+            let size = self.chunks.get(chunk).size;
+            let class = (size.trailing_zeros() - self.minimal_size.trailing_zeros()) as usize;
+            if stats.buddy_free_blocks.len() <= class {
+                stats.buddy_free_blocks.resize(class + 1, 0);
+            }
+            stats.buddy_free_blocks[class] += 1;
+            stats.free_region(size, 1);
+        }
+    }
+
     fn host_visible(&self) -> bool {
         self.props.contains(MemoryPropertyFlags::HOST_VISIBLE)
     }
 }
+
+fn fmt_link(f: &mut fmt::Formatter<'_>, index: usize) -> fmt::Result {
+    if index == !0 {
+        f.write_str("none")
+    } else {
+        write!(f, "{}", index)
+    }
+}
+
+impl<M> BuddyAllocator<M>
+where
+    M: MemoryBounds,
+{
+    /// Writes deterministic description of allocator state.
+    pub(crate) fn write_debug_state(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "buddy allocator for memory type {}: minimal size {}, atom mask {}",
+            self.memory_type, self.minimal_size, self.atom_mask
+        )?;
+
+        f.write_str("  chunks next vacant ")?;
+        fmt_link(f, self.chunks.next_vacant())?;
+        f.write_str("\n")?;
+
+        for &(chunk, cleanups) in &self.retained {
+            writeln!(f, "  retained chunk {} for {} cleanups", chunk, cleanups)?;
+        }
+
+        for (index, chunk) in self.chunks.entries().enumerate() {
+            match chunk {
+                Ok(chunk) => writeln!(
+                    f,
+                    "  chunk {}: memory {:?}, size {}, {}",
+                    index,
+                    chunk.memory,
+                    chunk.size,
+                    if chunk.ptr.is_some() {
+                        "mapped"
+                    } else {
+                        "unmapped"
+                    }
+                )?,
+                Err(next) => {
+                    write!(f, "  chunk {}: vacant, next ", index)?;
+                    fmt_link(f, next)?;
+                    f.write_str("\n")?;
+                }
+            }
+        }
+
+        for (index, size) in self.sizes.iter().enumerate() {
+            write!(
+                f,
+                "  size {} ({} bytes): next ready ",
+                index,
+                self.minimal_size << index
+            )?;
+            fmt_link(f, size.next_ready as usize)?;
+            f.write_str(", next vacant ")?;
+            fmt_link(f, size.pairs.next_vacant())?;
+            f.write_str("\n")?;
+
+            for (index, pair) in size.pairs.entries().enumerate() {
+                match pair {
+                    Ok(pair) => {
+                        write!(
+                            f,
+                            "    pair {}: chunk {}, offset {}, parent ",
+                            index,
+                            pair.chunk,
+                            size.pair_offset(pair)
+                        )?;
+                        match pair.parent {
+                            NONE => f.write_str("none")?,
+                            parent => write!(f, "{}", parent)?,
+                        }
+                        match pair.state {
+                            PairState::Exhausted => f.write_str(", exhausted\n")?,
+                            PairState::Ready { ready, next, prev } => writeln!(
+                                f,
+                                ", ready {}, next {}, prev {}",
+                                match ready {
+                                    Left => "left",
+                                    Right => "right",
+                                },
+                                next,
+                                prev
+                            )?,
+                        }
+                    }
+                    Err(next) => {
+                        write!(f, "    pair {}: vacant, next ", index)?;
+                        fmt_link(f, next)?;
+                        f.write_str("\n")?;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+use {
+    crate::{
+        atomic::{AtomicU32, AtomicU64, Relaxed},
+        AllocationError,
+    },
+    alloc::{boxed::Box, sync::Arc},
+    gpu_alloc_types::DeviceProperties,
+};
+
+#[derive(Debug)]
+struct TrackedHeap {
+    budget: u64,
+    used: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Tracker {
+    heaps: Box<[TrackedHeap]>,
+    allocations_remains: AtomicU32,
+}
+
+/// Heap budgets and memory object count shared by multiple [`GpuAllocator`] instances.
+///
+/// Each allocator assumes it owns the whole device.
+/// When several allocators work with one device, for example one per streaming thread,
+/// they can share tracker set with [`GpuAllocator::set_budget_tracker`].
+/// Allocators then reserve heap bytes and memory object in the tracker
+/// before allocating every memory object, and allocation fails with
+/// [`AllocationError::OutOfBudget`] or [`AllocationError::TooManyObjects`]
+/// when allocators together would exceed the limits.
+///
+/// Clones of the tracker share the same counters.
+///
+/// [`GpuAllocator`]: crate::GpuAllocator
+/// [`GpuAllocator::set_budget_tracker`]: crate::GpuAllocator::set_budget_tracker
+#[derive(Clone, Debug)]
+pub struct BudgetTracker {
+    tracker: Arc<Tracker>,
+}
+
+impl BudgetTracker {
+    /// Creates tracker limited by heap budgets and memory object count of the device.
+    ///
+    /// Heap budget is heap size unless device properties report budget and usage of the heap.
+    pub fn new(props: &DeviceProperties<'_>) -> Self {
+        BudgetTracker {
+            tracker: Arc::new(Tracker {
+                heaps: props
+                    .memory_heaps
+                    .iter()
+                    .map(|heap| TrackedHeap {
+                        budget: heap.available(),
+                        used: AtomicU64::new(0),
+                    })
+                    .collect(),
+                allocations_remains: AtomicU32::new(props.max_memory_allocation_count),
+            }),
+        }
+    }
+
+    /// Returns number of memory heaps tracked.
+    pub fn heap_count(&self) -> u32 {
+        self.tracker.heaps.len() as u32
+    }
+
+    /// Returns number of bytes all allocators together may allocate from the heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `heap` is out of bounds.
+    pub fn heap_budget(&self, heap: u32) -> u64 {
+        self.tracker.heaps[heap as usize].budget
+    }
+
+    /// Returns number of bytes all allocators together allocated from the heap.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `heap` is out of bounds.
+    pub fn heap_usage(&self, heap: u32) -> u64 {
+        self.tracker.heaps[heap as usize].used.load(Relaxed)
+    }
+
+    /// Returns number of memory objects all allocators together may still allocate.
+    pub fn remaining_allocations(&self) -> u32 {
+        self.tracker.allocations_remains.load(Relaxed)
+    }
+
+    /// Reserves memory object of `size` bytes from the heap.
+    pub(crate) fn reserve(&self, heap: u32, size: u64) -> Result<(), AllocationError> {
+        let tracker = &*self.tracker;
+        if tracker
+            .allocations_remains
+            .fetch_update(Relaxed, Relaxed, |remains| remains.checked_sub(1))
+            .is_err()
+        {
+            return Err(AllocationError::TooManyObjects);
+        }
+
+        let heap = &tracker.heaps[heap as usize];
+        let budget = heap.budget;
+        let reserved = heap.used.fetch_update(Relaxed, Relaxed, |used| {
+            used.checked_add(size).filter(|&used| used <= budget)
+        });
+
+        if reserved.is_err() {
+            tracker.allocations_remains.fetch_add(1, Relaxed);
+            return Err(AllocationError::OutOfBudget);
+        }
+        Ok(())
+    }
+
+    /// Accounts memory objects of `size` bytes in total allocated without reservation,
+    /// even if that exceeds the limits.
+    pub(crate) fn charge(&self, heap: u32, size: u64, objects: u32) {
+        let tracker = &*self.tracker;
+        let _ = tracker
+            .allocations_remains
+            .fetch_update(Relaxed, Relaxed, |remains| {
+                Some(remains.saturating_sub(objects))
+            });
+        tracker.heaps[heap as usize].used.fetch_add(size, Relaxed);
+    }
+
+    /// Reverts [`BudgetTracker::charge`].
+    pub(crate) fn discharge(&self, heap: u32, size: u64, objects: u32) {
+        let tracker = &*self.tracker;
+        tracker.allocations_remains.fetch_add(objects, Relaxed);
+        tracker.heaps[heap as usize].used.fetch_sub(size, Relaxed);
+    }
+
+    /// Releases memory object of `size` bytes reserved or charged before.
+    pub(crate) fn release(&self, heap: u32, size: u64) {
+        let tracker = &*self.tracker;
+        tracker.allocations_remains.fetch_add(1, Relaxed);
+        tracker.heaps[heap as usize].used.fetch_sub(size, Relaxed);
+    }
+}
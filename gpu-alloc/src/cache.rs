@@ -0,0 +1,76 @@
+use {
+    crate::{buddy::BuddyBlock, util::Table},
+    alloc::vec::Vec,
+};
+
+/// Recently freed buddy blocks kept for reuse
+/// by requests of the same memory type and size class,
+/// bypassing buddy splitting and merging.
+///
+/// Cached blocks are neither allocated nor free in buddy allocator,
+/// they are accounted as free memory and keep their chunks alive
+/// until reused or returned on cleanup.
+#[derive(Debug)]
+pub(crate) struct BlockCache<M> {
+    capacity: usize,
+
+    /// Blocks per memory type indexed by binary logarithm of block size.
+    types: Table<Vec<Vec<BuddyBlock<M>>>>,
+}
+
+impl<M> BlockCache<M> {
+    pub fn new(memory_types: usize) -> Self {
+        BlockCache {
+            capacity: 0,
+            types: (0..memory_types).map(|_| Vec::new()).collect(),
+        }
+    }
+
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+    }
+
+    /// Takes most recently cached block of `size` bytes.
+    pub fn take(&mut self, memory_type: u32, size: u64) -> Option<BuddyBlock<M>> {
+        let class = size.trailing_zeros() as usize;
+        let block = self.types[memory_type as usize].get_mut(class)?.pop()?;
+        debug_assert_eq!(block.size, size);
+        Some(block)
+    }
+
+    /// Caches the block if there is room for its size class.
+    /// Otherwise returns block back.
+    pub fn put(&mut self, memory_type: u32, block: BuddyBlock<M>) -> Option<BuddyBlock<M>> {
+        debug_assert!(block.size.is_power_of_two());
+
+        if self.capacity == 0 {
+            return Some(block);
+        }
+
+        let classes = &mut self.types[memory_type as usize];
+        let class = block.size.trailing_zeros() as usize;
+        if classes.len() <= class {
+            classes.resize_with(class + 1, Vec::new);
+        }
+
+        let blocks = &mut classes[class];
+        if blocks.len() < self.capacity {
+            blocks.push(block);
+            None
+        } else {
+            Some(block)
+        }
+    }
+
+    /// Removes all cached blocks of the memory type.
+    pub fn drain(&mut self, memory_type: u32) -> impl Iterator<Item = BuddyBlock<M>> + '_ {
+        self.types[memory_type as usize]
+            .iter_mut()
+            .flat_map(|blocks| blocks.drain(..))
+    }
+
+    /// Returns iterator over cached blocks of the memory type.
+    pub fn blocks(&self, memory_type: u32) -> impl Iterator<Item = &BuddyBlock<M>> + '_ {
+        self.types[memory_type as usize].iter().flatten()
+    }
+}
@@ -0,0 +1,98 @@
+use {
+    crate::{GpuAllocator, MemoryBounds},
+    core::{
+        fmt,
+        mem::ManuallyDrop,
+        ops::{Deref, DerefMut},
+        ptr,
+    },
+    gpu_alloc_types::MemoryDevice,
+};
+
+/// Guard that owns [`GpuAllocator`] and deallocates its leftover memory objects
+/// with the device when dropped.
+///
+/// Created with [`GpuAllocator::cleanup_on_drop`].
+/// Dereferences to the wrapped allocator.
+pub struct CleanupOnDrop<'a, M, MD>
+where
+    M: MemoryBounds + 'static,
+    MD: MemoryDevice<M>,
+{
+    allocator: ManuallyDrop<GpuAllocator<M>>,
+    device: &'a MD,
+}
+
+impl<'a, M, MD> CleanupOnDrop<'a, M, MD>
+where
+    M: MemoryBounds + 'static,
+    MD: MemoryDevice<M>,
+{
+    pub(crate) fn new(allocator: GpuAllocator<M>, device: &'a MD) -> Self {
+        CleanupOnDrop {
+            allocator: ManuallyDrop::new(allocator),
+            device,
+        }
+    }
+
+    /// Returns device used for cleanup.
+    pub fn device(&self) -> &'a MD {
+        self.device
+    }
+
+    /// Returns wrapped allocator without cleaning it up.
+    pub fn into_inner(self) -> GpuAllocator<M> {
+        let guard = ManuallyDrop::new(self);
+
+        // Guard is never dropped, so allocator is moved out exactly once.
+        unsafe { ptr::read(&*guard.allocator) }
+    }
+}
+
+impl<M, MD> Drop for CleanupOnDrop<'_, M, MD>
+where
+    M: MemoryBounds + 'static,
+    MD: MemoryDevice<M>,
+{
+    fn drop(&mut self) {
+        // Safety requirements are upheld by creator of the guard.
+        unsafe {
+            self.allocator.cleanup_internal(self.device);
+            ManuallyDrop::drop(&mut self.allocator);
+        }
+    }
+}
+
+impl<M, MD> Deref for CleanupOnDrop<'_, M, MD>
+where
+    M: MemoryBounds + 'static,
+    MD: MemoryDevice<M>,
+{
+    type Target = GpuAllocator<M>;
+
+    fn deref(&self) -> &GpuAllocator<M> {
+        &self.allocator
+    }
+}
+
+impl<M, MD> DerefMut for CleanupOnDrop<'_, M, MD>
+where
+    M: MemoryBounds + 'static,
+    MD: MemoryDevice<M>,
+{
+    fn deref_mut(&mut self) -> &mut GpuAllocator<M> {
+        &mut self.allocator
+    }
+}
+
+impl<M, MD> fmt::Debug for CleanupOnDrop<'_, M, MD>
+where
+    M: MemoryBounds + 'static,
+    MD: MemoryDevice<M>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CleanupOnDrop")
+            .field("allocator", &*self.allocator)
+            .finish_non_exhaustive()
+    }
+}
@@ -35,6 +35,51 @@ pub struct Config {
     /// Initial memory object size for buddy allocator.
     /// If less than `minimal_buddy_size` then `minimal_buddy_size` is used instead.
     pub initial_buddy_dedicated_size: u64,
+
+    /// Number of [`GpuAllocator::cleanup`] calls each emptied buddy chunk is kept alive for.
+    /// Chunk is released only after it stayed empty across that many cleanups,
+    /// unless request that needs new chunk of the same size reuses it first.
+    ///
+    /// This stops freeing and reallocating chunks every few frames
+    /// when usage oscillates around chunk boundary.
+    /// How often it happens is reported in [`TypeStats::buddy_chunk_oscillations`].
+    /// Zero releases emptied chunks immediately.
+    ///
+    /// [`GpuAllocator::cleanup`]: crate::GpuAllocator::cleanup
+    /// [`TypeStats::buddy_chunk_oscillations`]: crate::TypeStats::buddy_chunk_oscillations
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub buddy_chunk_keep_alive: u32,
+
+    /// Serve every request with dedicated memory object,
+    /// subject to the limit of memory objects.
+    ///
+    /// Makes each resource its own memory object, so GPU debuggers and driver tools
+    /// attribute memory precisely, for example while hunting memory corruption.
+    /// Not intended for shipping builds.
+    ///
+    /// Does not affect [`GpuAllocator::alloc_group`] whose blocks share memory object by design.
+    ///
+    /// [`GpuAllocator::alloc_group`]: crate::GpuAllocator::alloc_group
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub force_dedicated: bool,
+
+    /// Size in bytes of the largest heap with `DEVICE_LOCAL | HOST_VISIBLE` memory types
+    /// that is treated as small BAR window, typically 256MB when resizable BAR is disabled.
+    ///
+    /// Memory types of such heaps are reserved for fast uploads,
+    /// requests with both `FAST_DEVICE_ACCESS` and `HOST_ACCESS` usage.
+    /// Other requests use them only when no other memory type is compatible,
+    /// instead of filling the window with arbitrary host-accessed data.
+    /// Zero disables the policy.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bar_heap_size: u64,
+
+    /// Percentage of small BAR heap that fast uploads may use.
+    /// When heap usage reaches it, fast uploads fall back to other host-visible memory types.
+    ///
+    /// See [`Config::bar_heap_size`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bar_usage_limit: u8,
 }
 
 impl Config {
@@ -59,6 +104,10 @@ impl Config {
             final_free_list_chunk: potato.final_free_list_chunk * 1024,
             minimal_buddy_size: potato.minimal_buddy_size * 1024,
             initial_buddy_dedicated_size: potato.initial_buddy_dedicated_size * 1024,
+            buddy_chunk_keep_alive: potato.buddy_chunk_keep_alive,
+            force_dedicated: potato.force_dedicated,
+            bar_heap_size: 256 * 1024 * 1024,
+            bar_usage_limit: potato.bar_usage_limit,
         }
     }
 
@@ -72,6 +121,10 @@ impl Config {
             final_free_list_chunk: 128 * 1024,
             minimal_buddy_size: 1,
             initial_buddy_dedicated_size: 8 * 1024,
+            buddy_chunk_keep_alive: 0,
+            force_dedicated: false,
+            bar_heap_size: 0,
+            bar_usage_limit: 75,
         }
     }
 }
@@ -0,0 +1,57 @@
+use {crate::block::MemoryBlock, alloc::vec::Vec};
+
+/// Defragmentation in progress, returned by [`GpuAllocator::defragment`].
+///
+/// Holds blocks passed to `defragment` in their order,
+/// some of them with new block allocated to move content to.
+///
+/// Caller copies content of every moved block to its new block
+/// and then calls [`GpuAllocator::finish_defragmentation`],
+/// or calls [`GpuAllocator::cancel_defragmentation`] to keep old blocks.
+/// Blocks must be returned to the allocator with either of them.
+///
+/// [`GpuAllocator::defragment`]: crate::GpuAllocator::defragment
+/// [`GpuAllocator::finish_defragmentation`]: crate::GpuAllocator::finish_defragmentation
+/// [`GpuAllocator::cancel_defragmentation`]: crate::GpuAllocator::cancel_defragmentation
+#[derive(Debug)]
+#[must_use = "Blocks must be returned with `finish_defragmentation` or `cancel_defragmentation`"]
+pub struct Defragmentation<M> {
+    pub(crate) blocks: Vec<(MemoryBlock<M>, Option<MemoryBlock<M>>)>,
+}
+
+impl<M> Defragmentation<M> {
+    /// Returns index in blocks passed to `defragment`, old block and new block
+    /// of every move.
+    pub fn moves(&self) -> impl Iterator<Item = (usize, &MemoryBlock<M>, &MemoryBlock<M>)> + '_ {
+        self.blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (old, new))| Some((index, old, new.as_ref()?)))
+    }
+
+    /// Returns index in blocks passed to `defragment`, old block and new block
+    /// of every move, mutably to copy content through mapping.
+    pub fn moves_mut(
+        &mut self,
+    ) -> impl Iterator<Item = (usize, &mut MemoryBlock<M>, &mut MemoryBlock<M>)> + '_ {
+        self.blocks
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, (old, new))| Some((index, old, new.as_mut()?)))
+    }
+
+    /// Returns number of moves.
+    pub fn len(&self) -> usize {
+        self.blocks.iter().filter(|(_, new)| new.is_some()).count()
+    }
+
+    /// Returns `true` if nothing is moved.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns number of bytes to copy.
+    pub fn moved_bytes(&self) -> u64 {
+        self.moves().map(|(_, old, _)| old.size()).sum()
+    }
+}
@@ -0,0 +1,146 @@
+use {
+    crate::{
+        pool::PoolId,
+        stats::{HeapStats, TagStats, TypeStats},
+    },
+    alloc::{borrow::Cow, vec::Vec},
+    gpu_alloc_types::MemoryPropertyFlags,
+};
+
+/// Detailed description of allocator state returned by [`GpuAllocator::dump`].
+///
+/// Contains every chunk and live block, so it is expensive to produce.
+/// Intended for visualization and attaching to bug reports.
+///
+/// [`GpuAllocator::dump`]: crate::GpuAllocator::dump
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct AllocatorDump {
+    /// Statistics per memory heap.
+    pub heaps: Vec<HeapStats>,
+
+    /// Memory types with their chunks.
+    pub types: Vec<MemoryTypeDump>,
+
+    /// Statistics per tag.
+    pub tags: Vec<TagStats>,
+}
+
+/// Description of single memory type in [`AllocatorDump`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct MemoryTypeDump {
+    /// Index of the memory type.
+    pub index: u32,
+
+    /// Index of the heap this memory type belongs to.
+    pub heap: u32,
+
+    /// Property flags of the memory type.
+    pub props: MemoryPropertyFlags,
+
+    /// Statistics of the memory type.
+    pub stats: TypeStats,
+
+    /// Memory objects allocated from this memory type,
+    /// ordered by strategy and id.
+    pub chunks: Vec<ChunkDump>,
+}
+
+/// Strategy that owns memory object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ChunkKind {
+    /// Memory object of single dedicated or imported block.
+    Dedicated,
+
+    /// Chunk of buddy allocator.
+    Buddy,
+
+    /// Chunk of free-list allocator.
+    FreeList,
+
+    /// Chunk of ring allocator for per-frame blocks.
+    Ring,
+}
+
+/// Description of single memory object in [`AllocatorDump`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ChunkDump {
+    /// Strategy that owns this memory object.
+    pub kind: ChunkKind,
+
+    /// Id of this memory object, unique among ones of the same kind, pool and memory type.
+    pub id: u64,
+
+    /// Pool that owns this memory object, `None` for chunks of the allocator itself.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub pool: Option<PoolId>,
+
+    /// Size of memory object.
+    pub size: u64,
+
+    /// Live blocks ordered by offset.
+    pub blocks: Vec<BlockDump>,
+
+    /// Free regions ordered by offset.
+    pub free: Vec<RegionDump>,
+}
+
+/// Description of single live block in [`AllocatorDump`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct BlockDump {
+    /// Offset of the block in memory object.
+    pub offset: u64,
+
+    /// Size of the block.
+    pub size: u64,
+
+    /// Size requested for the block.
+    pub requested_size: u64,
+
+    /// Tag the block was allocated with.
+    pub tag: Option<Cow<'static, str>>,
+
+    /// Source location of the call that allocated the block, as `file:line:column`.
+    /// Recorded only with `track-caller` feature.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub location: Option<Cow<'static, str>>,
+}
+
+/// Description of free region of memory object in [`AllocatorDump`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct RegionDump {
+    /// Offset of the region in memory object.
+    pub offset: u64,
+
+    /// Size of the region.
+    pub size: u64,
+}
+
+impl ChunkDump {
+    pub(crate) fn new(kind: ChunkKind, id: u64, pool: Option<PoolId>, size: u64) -> Self {
+        ChunkDump {
+            kind,
+            id,
+            pool,
+            size,
+            blocks: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+}
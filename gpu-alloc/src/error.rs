@@ -26,6 +26,21 @@ pub enum AllocationError {
     /// If this error is returned when memory heaps are far from exhausted
     /// `Config` should be tweaked to allocate larger memory objects.
     TooManyObjects,
+
+    /// Allocation would exceed heap budget tracked by allocator,
+    /// so device was not asked for memory.\
+    /// Unlike `OutOfDeviceMemory` this error is not reported by backend.
+    /// Deallocating memory from the same heap, for example evicting caches,
+    /// makes room for another allocation.
+    OutOfBudget,
+
+    /// `Request::align_mask` is not of form `2^n - 1`.
+    InvalidAlignment,
+
+    /// Size of the block computed for the request overflows `u64`,
+    /// for example when size is rounded up to huge alignment.\
+    /// Such request cannot be served regardless of memory usage.
+    SizeOverflow,
 }
 
 impl From<OutOfMemory> for AllocationError {
@@ -48,6 +63,13 @@ impl Display for AllocationError {
             AllocationError::TooManyObjects => {
                 fmt.write_str("Reached limit on allocated memory objects count")
             }
+            AllocationError::OutOfBudget => fmt.write_str("Heap budget exhausted"),
+            AllocationError::InvalidAlignment => {
+                fmt.write_str("Alignment mask is not one less than power of two")
+            }
+            AllocationError::SizeOverflow => {
+                fmt.write_str("Block size computed for the request overflows")
+            }
         }
     }
 }
@@ -79,6 +101,14 @@ pub enum MapError {
 
     /// Mapping failed due to block being already mapped.
     AlreadyMapped,
+
+    /// Requested range is not within memory block.
+    OutOfBounds,
+
+    /// Requested range can't be addressed by host pointer.\
+    /// Happens on 32-bit hosts for ranges of large blocks
+    /// that lie beyond `isize::MAX` bytes from start of mapping.
+    Unaddressable,
 }
 
 impl From<DeviceMapError> for MapError {
@@ -108,6 +138,10 @@ impl Display for MapError {
             MapError::MapFailed => fmt.write_str("Failed to map memory object"),
             MapError::NonHostVisible => fmt.write_str("Impossible to map non-host-visible memory"),
             MapError::AlreadyMapped => fmt.write_str("Block is already mapped"),
+            MapError::OutOfBounds => fmt.write_str("Mapped range is out of block bounds"),
+            MapError::Unaddressable => {
+                fmt.write_str("Mapped range can't be addressed in host address space")
+            }
         }
     }
 }
@@ -0,0 +1,251 @@
+use {
+    crate::{allocator::Dedicated, dump::ChunkKind, error::AllocationError, util::Table, Request},
+    core::{cell::Cell, fmt, ptr::NonNull},
+    gpu_alloc_types::{
+        AllocationFlags, DeviceDiagnostic, DeviceMapError, MappedMemoryRange, MemoryDevice,
+        OutOfMemory,
+    },
+};
+
+/// Outcome of trying one memory type for failed request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AttemptOutcome {
+    /// Request is larger than memory heap of the memory type.
+    HeapTooSmall,
+
+    /// Strategy chosen for the request failed with the error.
+    Failed {
+        /// Strategy chosen for the request.
+        strategy: ChunkKind,
+
+        /// Error returned by the strategy.
+        error: AllocationError,
+    },
+}
+
+/// Memory type tried for failed request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct MemoryTypeAttempt {
+    /// Index of memory type.
+    pub memory_type: u32,
+
+    /// Index of memory heap of the memory type.
+    pub heap: u32,
+
+    /// Why memory type did not serve the request.
+    pub outcome: AttemptOutcome,
+}
+
+/// Usage of memory heap at the moment of failure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct HeapBudget {
+    /// Size of the heap.
+    pub size: u64,
+
+    /// Bytes allocator may allocate from the heap.
+    pub budget: u64,
+
+    /// Bytes allocated from the heap.
+    pub used: u64,
+}
+
+impl HeapBudget {
+    /// Returns bytes left in the budget of the heap.
+    pub fn remaining(&self) -> u64 {
+        self.budget.saturating_sub(self.used)
+    }
+}
+
+/// Diagnostic context of failed allocation request
+/// returned by [`GpuAllocator::last_failure`].
+///
+/// Its `Display` implementation is meant to be attached to bug reports.
+///
+/// [`GpuAllocator::last_failure`]: crate::GpuAllocator::last_failure
+#[derive(Clone, Debug)]
+pub struct FailureDetails {
+    pub(crate) request: Request,
+    pub(crate) dedicated: Option<Dedicated>,
+    pub(crate) tag: Option<&'static str>,
+    pub(crate) error: AllocationError,
+    pub(crate) attempts: Table<MemoryTypeAttempt>,
+    pub(crate) heaps: Table<HeapBudget>,
+    pub(crate) remaining_allocations: u32,
+    pub(crate) diagnostic: Option<DeviceDiagnostic>,
+}
+
+impl FailureDetails {
+    /// Returns failed request.
+    pub fn request(&self) -> Request {
+        self.request
+    }
+
+    /// Returns dedicated memory hint of the request.
+    pub fn dedicated(&self) -> Option<Dedicated> {
+        self.dedicated
+    }
+
+    /// Returns tag of the request.
+    pub fn tag(&self) -> Option<&'static str> {
+        self.tag
+    }
+
+    /// Returns error returned for the request.
+    pub fn error(&self) -> AllocationError {
+        self.error
+    }
+
+    /// Returns memory types tried in order of preference.
+    ///
+    /// Empty if request was rejected before any memory type was tried.
+    pub fn attempts(&self) -> &[MemoryTypeAttempt] {
+        &self.attempts
+    }
+
+    /// Returns usage of every memory heap, indexed by heap index.
+    pub fn heaps(&self) -> &[HeapBudget] {
+        &self.heaps
+    }
+
+    /// Returns number of memory objects that could still be allocated.
+    pub fn remaining_allocations(&self) -> u32 {
+        self.remaining_allocations
+    }
+
+    /// Returns diagnostic provided by the device for the last failed memory object allocation.
+    ///
+    /// `None` if device was not asked, succeeded or provides no diagnostics,
+    /// which means failure was caused by allocator limits or fragmentation.
+    pub fn device_diagnostic(&self) -> Option<&DeviceDiagnostic> {
+        self.diagnostic.as_ref()
+    }
+}
+
+impl fmt::Display for FailureDetails {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: request of {} bytes, align mask {:#x}, usage {:?}, memory types {:#b}, dedicated {:?}, tag {}",
+            self.error,
+            self.request.size,
+            self.request.align_mask,
+            self.request.usage,
+            self.request.memory_types,
+            self.dedicated,
+            self.tag.unwrap_or("<untagged>"),
+        )?;
+
+        for attempt in &self.attempts {
+            write!(
+                f,
+                "\n  memory type {} (heap {}): ",
+                attempt.memory_type, attempt.heap
+            )?;
+            match attempt.outcome {
+                AttemptOutcome::HeapTooSmall => f.write_str("heap too small")?,
+                AttemptOutcome::Failed { strategy, error } => {
+                    write!(f, "{:?} failed: {}", strategy, error)?
+                }
+            }
+        }
+
+        for (index, heap) in self.heaps.iter().enumerate() {
+            write!(
+                f,
+                "\n  heap {}: {} of {} bytes used, {} remaining",
+                index,
+                heap.used,
+                heap.size,
+                heap.remaining()
+            )?;
+        }
+
+        write!(
+            f,
+            "\n  memory objects remaining: {}",
+            self.remaining_allocations
+        )?;
+
+        if let Some(diagnostic) = &self.diagnostic {
+            write!(f, "\n  device: {}", diagnostic)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Device wrapper that keeps diagnostic of the last failed `MemoryDevice::allocate_memory`.
+pub(crate) struct DiagnosedDevice<'a, D> {
+    device: &'a D,
+    diagnostic: Cell<Option<DeviceDiagnostic>>,
+}
+
+impl<'a, D> DiagnosedDevice<'a, D> {
+    pub fn new(device: &'a D) -> Self {
+        DiagnosedDevice {
+            device,
+            diagnostic: Cell::new(None),
+        }
+    }
+
+    pub fn take_diagnostic(&self) -> Option<DeviceDiagnostic> {
+        self.diagnostic.take()
+    }
+}
+
+impl<M, D> MemoryDevice<M> for DiagnosedDevice<'_, D>
+where
+    D: MemoryDevice<M>,
+{
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<M, OutOfMemory> {
+        let result = self.device.allocate_memory(size, memory_type, flags);
+        if result.is_err() {
+            self.diagnostic
+                .set(self.device.take_allocation_diagnostic());
+        }
+        result
+    }
+
+    unsafe fn deallocate_memory(&self, memory: M) {
+        self.device.deallocate_memory(memory)
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut M,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        self.device.map_memory(memory, offset, size)
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut M) {
+        self.device.unmap_memory(memory)
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.invalidate_memory_ranges(ranges)
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.flush_memory_ranges(ranges)
+    }
+
+    fn take_allocation_diagnostic(&self) -> Option<DeviceDiagnostic> {
+        self.take_diagnostic()
+    }
+}
@@ -1,16 +1,39 @@
 use {
     crate::{
         align_down, align_up,
+        dump::ChunkKind,
         error::AllocationError,
         heap::Heap,
-        util::{arc_unwrap, is_arc_unique},
+        observer::{self, Observer},
+        stats::{FragmentationStats, StrategyStats},
+        util::{arc_unwrap, host_addressable, is_arc_unique},
         MemoryBounds,
     },
     alloc::{sync::Arc, vec::Vec},
-    core::{cmp::Ordering, ptr::NonNull},
+    core::{cmp::Ordering, fmt, ptr::NonNull},
     gpu_alloc_types::{AllocationFlags, DeviceMapError, MemoryDevice, MemoryPropertyFlags},
 };
 
+/// Minimal number of chunks pinned by single live block
+/// before `TRANSIENT` requests spill to buddy allocator.
+const SPILL_PINNED_CHUNKS: usize = 4;
+
+/// Live blocks of one chunk.
+#[derive(Debug)]
+struct ChunkUsage {
+    chunk: u64,
+    size: u64,
+    blocks: u32,
+    bytes: u64,
+}
+
+impl ChunkUsage {
+    /// Returns `true` if single block keeps significant part of the chunk from being released.
+    fn pinned(&self) -> bool {
+        self.blocks == 1 && self.bytes <= self.size - self.size / 4
+    }
+}
+
 unsafe fn opt_ptr_add(ptr: Option<NonNull<u8>>, size: u64) -> Option<NonNull<u8>> {
     ptr.map(|ptr| {
         // Size is within memory region started at `ptr`.
@@ -33,16 +56,13 @@ impl<M> FreeList<M> {
         }
     }
 
-    pub fn get_block_from_new_memory(
+    /// Adds region spanning whole new memory object and returns its index.
+    fn push_new_memory(
         &mut self,
         memory: Arc<M>,
         memory_size: u64,
         ptr: Option<NonNull<u8>>,
-        align_mask: u64,
-        size: u64,
-    ) -> FreeListBlock<M> {
-        debug_assert!(size <= memory_size);
-
+    ) -> usize {
         self.counter += 1;
         self.array.push(FreeListRegion {
             memory,
@@ -51,7 +71,7 @@ impl<M> FreeList<M> {
             start: 0,
             end: memory_size,
         });
-        self.get_block_at(self.array.len() - 1, align_mask, size)
+        self.array.len() - 1
     }
 
     pub fn get_block(&mut self, align_mask: u64, size: u64) -> Option<FreeListBlock<M>> {
@@ -68,6 +88,37 @@ impl<M> FreeList<M> {
         Some(self.get_block_at(index, align_mask, size))
     }
 
+    /// Returns index of region and size of the largest block
+    /// between `min_size` and `max_size` that fits into free regions.
+    /// Later regions are preferred among equal sizes.
+    fn find_largest(&self, align_mask: u64, min_size: u64, max_size: u64) -> Option<(usize, u64)> {
+        let mut largest = None;
+        let mut largest_size = 0;
+        for (index, region) in self.array.iter().enumerate() {
+            let available = match align_up(region.start, align_mask) {
+                Some(start) if start <= region.end => region.end - start,
+                _ => continue,
+            };
+
+            let size = max_size.min(available);
+            if size >= min_size && size >= largest_size {
+                largest = Some((index, size));
+                largest_size = size;
+            }
+        }
+        largest
+    }
+
+    pub fn get_largest_block(
+        &mut self,
+        align_mask: u64,
+        min_size: u64,
+        max_size: u64,
+    ) -> Option<FreeListBlock<M>> {
+        let (index, size) = self.find_largest(align_mask, min_size, max_size)?;
+        Some(self.get_block_at(index, align_mask, size))
+    }
+
     fn get_block_at(&mut self, index: usize, align_mask: u64, size: u64) -> FreeListBlock<M> {
         let region = &mut self.array[index];
 
@@ -93,7 +144,9 @@ impl<M> FreeList<M> {
         }
     }
 
-    pub fn insert_block(&mut self, block: FreeListBlock<M>) {
+    /// Returns block to the free list, merging it with adjacent regions,
+    /// and returns index of the region that contains the block.
+    pub fn insert_block(&mut self, block: FreeListBlock<M>) -> usize {
         match self.array.binary_search_by(|b| b.cmp(&block)) {
             Ok(_) => {
                 panic!("Overlapping block found in free list");
@@ -108,6 +161,7 @@ impl<M> FreeList<M> {
                     } else {
                         self.array.insert(0, FreeListRegion::from_block(block));
                     }
+                    0
                 }
                 [.., prev, next] => {
                     debug_assert!(!prev.is_prefix_block(&block));
@@ -120,16 +174,24 @@ impl<M> FreeList<M> {
                             let next = self.array.remove(index);
                             let prev = &mut self.array[index - 1];
                             prev.merge(next);
+                            index - 1
+                        } else {
+                            index
                         }
                     } else if prev.is_suffix_block(&block) {
                         prev.merge_suffix_block(block);
+                        index - 1
                     } else {
                         self.array.insert(index, FreeListRegion::from_block(block));
+                        index
                     }
                 }
             },
             Err(_) => match &mut self.array[..] {
-                [] => self.array.push(FreeListRegion::from_block(block)),
+                [] => {
+                    self.array.push(FreeListRegion::from_block(block));
+                    0
+                }
                 [.., prev] => {
                     debug_assert!(!prev.is_prefix_block(&block));
                     if prev.is_suffix_block(&block) {
@@ -137,37 +199,71 @@ impl<M> FreeList<M> {
                     } else {
                         self.array.push(FreeListRegion::from_block(block));
                     }
+                    self.array.len() - 1
                 }
             },
         }
     }
 
-    pub fn drain(&mut self, keep_last: bool) -> Option<impl Iterator<Item = (M, u64)> + '_> {
-        // Time to deallocate
+    /// Removes region at `index` if it spans whole chunk other than the newest one
+    /// and returns memory object, size and id of that chunk.
+    ///
+    /// The newest chunk is kept to avoid reallocating memory
+    /// when blocks are allocated and deallocated repeatedly.
+    pub fn release(&mut self, index: usize) -> Option<(M, u64, u64)> {
+        let region = &mut self.array[index];
+        if region.chunk == self.counter || !is_arc_unique(&mut region.memory) {
+            return None;
+        }
 
-        let len = self.array.len();
+        Some(self.array.remove(index).into_chunk())
+    }
 
-        let mut del = 0;
-        {
-            let regions = &mut self.array[..];
+    /// Removes region of the newest chunk if it spans whole chunk
+    /// and returns memory object, size and id of that chunk.
+    pub fn release_newest(&mut self) -> Option<(M, u64, u64)> {
+        let region = self.array.last_mut()?;
+        if region.chunk != self.counter || !is_arc_unique(&mut region.memory) {
+            return None;
+        }
 
-            for i in 0..len {
-                if (i < len - 1 || !keep_last) && is_arc_unique(&mut regions[i].memory) {
-                    del += 1;
-                } else if del > 0 {
-                    regions.swap(i - del, i);
+        Some(self.array.pop()?.into_chunk())
+    }
+
+    /// Returns total size of regions that span whole chunk.
+    pub fn free_chunks_size(&mut self) -> u64 {
+        self.array
+            .iter_mut()
+            .filter_map(|region| {
+                if is_arc_unique(&mut region.memory) {
+                    Some(region.end - region.start)
+                } else {
+                    None
                 }
-            }
-        }
+            })
+            .sum()
+    }
 
-        if del > 0 {
-            Some(self.array.drain(len - del..).map(move |region| {
-                debug_assert_eq!(region.start, 0);
-                (unsafe { arc_unwrap(region.memory) }, region.end)
-            }))
-        } else {
-            None
-        }
+    /// Removes first region that spans whole chunk
+    /// and returns memory object, size and id of that chunk.
+    pub fn release_first_free(&mut self) -> Option<(M, u64, u64)> {
+        let index = self
+            .array
+            .iter_mut()
+            .position(|region| is_arc_unique(&mut region.memory))?;
+
+        Some(self.array.remove(index).into_chunk())
+    }
+
+    /// Removes last region that spans whole chunk
+    /// and returns memory object, size and id of that chunk.
+    pub fn release_last_free(&mut self) -> Option<(M, u64, u64)> {
+        let index = self
+            .array
+            .iter_mut()
+            .rposition(|region| is_arc_unique(&mut region.memory))?;
+
+        Some(self.array.remove(index).into_chunk())
     }
 }
 
@@ -225,6 +321,12 @@ impl<M> FreeListRegion<M> {
         }
     }
 
+    /// Returns memory object, size and id of the chunk this region spans.
+    fn into_chunk(self) -> (M, u64, u64) {
+        debug_assert_eq!(self.start, 0);
+        (unsafe { arc_unwrap(self.memory) }, self.end, self.chunk)
+    }
+
     fn consecutive(&self, other: &Self) -> bool {
         if self.chunk != other.chunk {
             return false;
@@ -310,6 +412,14 @@ pub(crate) struct FreeListAllocator<M> {
 
     total_allocations: u64,
     total_deallocations: u64,
+
+    memory_objects: u64,
+    memory_bytes: u64,
+
+    /// Live blocks of each chunk, sorted by chunk id.
+    usage: Vec<ChunkUsage>,
+    /// Whether most chunks are pinned by single long-lived block.
+    spilling: bool,
 }
 
 impl<M> Drop for FreeListAllocator<M> {
@@ -348,10 +458,10 @@ where
             starting_chunk_size
         );
 
-        let starting_chunk_size = min(starting_chunk_size, isize::max_value());
+        let starting_chunk_size = min(starting_chunk_size, isize::MAX);
 
         debug_assert_eq!(align_down(final_chunk_size, atom_mask), final_chunk_size);
-        let final_chunk_size = min(final_chunk_size, isize::max_value());
+        let final_chunk_size = min(final_chunk_size, isize::MAX);
 
         FreeListAllocator {
             freelist: FreeList::new(),
@@ -363,10 +473,17 @@ where
 
             total_allocations: 0,
             total_deallocations: 0,
+
+            memory_objects: 0,
+            memory_bytes: 0,
+
+            usage: Vec::new(),
+            spilling: false,
         }
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device, observer)))]
     pub unsafe fn alloc(
         &mut self,
         device: &impl MemoryDevice<M>,
@@ -375,30 +492,38 @@ where
         flags: AllocationFlags,
         heap: &mut Heap,
         allocations_remains: &mut u32,
+        mut observer: Observer<'_>,
     ) -> Result<FreeListBlock<M>, AllocationError> {
         debug_assert!(
             self.final_chunk_size >= size,
             "GpuAllocator must not request allocations equal or greater to chunks size"
         );
 
-        let size = align_up(size, self.atom_mask).expect(
-            "Any value not greater than final chunk size (which is aligned) has to fit for alignment",
-        );
+        let size = align_up(size, self.atom_mask).ok_or(AllocationError::SizeOverflow)?;
 
         let align_mask = align_mask | self.atom_mask;
-        let host_visible = self.host_visible();
 
         if size <= self.chunk_size {
             // Otherwise there can't be any sufficiently large free blocks
             if let Some(block) = self.freelist.get_block(align_mask, size) {
                 self.total_allocations += 1;
+                self.count_usage(block.chunk, block.size, true);
                 return Ok(block);
             }
         }
 
         // New allocation is required.
-        if *allocations_remains == 0 {
-            return Err(AllocationError::TooManyObjects);
+        // Newest chunk is too small for the request if it is free, so it can be released.
+        if let Some((memory, size, chunk)) = self.freelist.release_newest() {
+            self.destroy_chunk(
+                device,
+                memory,
+                size,
+                chunk,
+                heap,
+                allocations_remains,
+                &mut observer,
+            );
         }
 
         if size > self.chunk_size {
@@ -408,68 +533,255 @@ where
             self.chunk_size = (self.chunk_size * multiple).min(self.final_chunk_size);
         }
 
-        let mut memory = device.allocate_memory(self.chunk_size, self.memory_type, flags)?;
+        let index = self.create_chunk(
+            device,
+            self.chunk_size,
+            flags,
+            heap,
+            allocations_remains,
+            &mut observer,
+        )?;
+        let block = self.freelist.get_block_at(index, align_mask, size);
+
+        if self.chunk_size < self.final_chunk_size {
+            // Double next chunk size
+            // Limit to final value.
+            self.chunk_size = (self.chunk_size * 2).min(self.final_chunk_size);
+        }
+
+        self.total_allocations += 1;
+        self.count_usage(block.chunk, block.size, true);
+        Ok(block)
+    }
+
+    /// Allocates blocks of given sizes and alignment masks from one new memory object.
+    ///
+    /// Blocks are returned in order of `requests`.
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device, observer)))]
+    pub unsafe fn alloc_group(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        requests: &[(u64, u64)],
+        max_chunk_size: u64,
+        flags: AllocationFlags,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        mut observer: Observer<'_>,
+    ) -> Result<Vec<FreeListBlock<M>>, AllocationError> {
+        // Blocks are taken from the end of the region,
+        // so layout is computed as distances from the end of the memory object.
+        // Memory object size is aligned to every alignment mask,
+        // making aligned offsets and aligned distances interchangeable.
+        let mut chunk_align_mask = self.atom_mask;
+        let mut end = 0u64;
+        for &(size, align_mask) in requests {
+            let size =
+                align_up(size.max(1), self.atom_mask).ok_or(AllocationError::SizeOverflow)?;
+            let align_mask = align_mask | self.atom_mask;
+            chunk_align_mask |= align_mask;
+            end = end
+                .checked_add(size)
+                .and_then(|end| align_up(end, align_mask))
+                .ok_or(AllocationError::SizeOverflow)?;
+        }
+
+        let chunk_size = align_up(end, chunk_align_mask).ok_or(AllocationError::SizeOverflow)?;
+        if chunk_size > min(max_chunk_size, isize::MAX) {
+            return Err(AllocationError::OutOfDeviceMemory);
+        }
+
+        let index = self.create_chunk(
+            device,
+            chunk_size,
+            flags,
+            heap,
+            allocations_remains,
+            &mut observer,
+        )?;
+
+        let blocks: Vec<FreeListBlock<M>> = requests
+            .iter()
+            .map(|&(size, align_mask)| {
+                let size = align_up(size.max(1), self.atom_mask).expect("Checked above");
+                self.freelist
+                    .get_block_at(index, align_mask | self.atom_mask, size)
+            })
+            .collect();
+
+        self.total_allocations += requests.len() as u64;
+        for block in &blocks {
+            self.count_usage(block.chunk, block.size, true);
+        }
+        Ok(blocks)
+    }
+
+    /// Allocates new memory object of `chunk_size` bytes
+    /// and returns index of free region spanning it.
+    unsafe fn create_chunk(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        chunk_size: u64,
+        flags: AllocationFlags,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        observer: &mut Observer<'_>,
+    ) -> Result<usize, AllocationError> {
+        if *allocations_remains == 0 {
+            return Err(AllocationError::TooManyObjects);
+        }
+
+        // Whole chunk is mapped and blocks are addressed by pointer offsets.
+        if self.host_visible() && !host_addressable(chunk_size) {
+            return Err(AllocationError::OutOfHostMemory);
+        }
+
+        profile_scope!("FreeListAllocator::create_chunk");
+
+        heap.reserve(chunk_size)?;
+
+        let mut memory = match device.allocate_memory(chunk_size, self.memory_type, flags) {
+            Ok(memory) => memory,
+            Err(err) => {
+                heap.release(chunk_size);
+                return Err(err.into());
+            }
+        };
         *allocations_remains -= 1;
-        heap.alloc(self.chunk_size);
+        heap.alloc(chunk_size);
 
         // Map host visible allocations
-        let ptr = if host_visible {
-            match device.map_memory(&mut memory, 0, self.chunk_size) {
+        let ptr = if self.host_visible() {
+            match device.map_memory(&mut memory, 0, chunk_size) {
                 Ok(ptr) => Some(ptr),
-                Err(DeviceMapError::MapFailed) => {
+                Err(err) => {
                     #[cfg(feature = "tracing")]
                     tracing::error!("Failed to map host-visible memory in linear allocator");
                     device.deallocate_memory(memory);
                     *allocations_remains += 1;
-                    heap.dealloc(self.chunk_size);
+                    heap.dealloc(chunk_size);
 
-                    return Err(AllocationError::OutOfHostMemory);
-                }
-                Err(DeviceMapError::OutOfDeviceMemory) => {
-                    return Err(AllocationError::OutOfDeviceMemory);
-                }
-                Err(DeviceMapError::OutOfHostMemory) => {
-                    return Err(AllocationError::OutOfHostMemory);
+                    return Err(match err {
+                        DeviceMapError::OutOfDeviceMemory => AllocationError::OutOfDeviceMemory,
+                        DeviceMapError::MapFailed | DeviceMapError::OutOfHostMemory => {
+                            AllocationError::OutOfHostMemory
+                        }
+                    });
                 }
             }
         } else {
             None
         };
 
-        let memory = Arc::new(memory);
-        let block =
-            self.freelist
-                .get_block_from_new_memory(memory, self.chunk_size, ptr, align_mask, size);
+        self.memory_objects += 1;
+        self.memory_bytes += chunk_size;
+
+        let index = self
+            .freelist
+            .push_new_memory(Arc::new(memory), chunk_size, ptr);
+        let chunk = self.freelist.array[index].chunk;
+        self.usage.push(ChunkUsage {
+            chunk,
+            size: chunk_size,
+            blocks: 0,
+            bytes: 0,
+        });
+        self.update_spilling();
 
-        if self.chunk_size < self.final_chunk_size {
-            // Double next chunk size
-            // Limit to final value.
-            self.chunk_size = (self.chunk_size * 2).min(self.final_chunk_size);
-        }
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            memory_type = self.memory_type,
+            strategy = "free-list",
+            chunk,
+            size = chunk_size,
+            "chunk created"
+        );
+
+        observer::chunk_created(
+            observer,
+            self.memory_type,
+            ChunkKind::FreeList,
+            chunk,
+            chunk_size,
+        );
 
+        Ok(index)
+    }
+
+    /// Returns size of the largest block between `min_size` and `max_size`
+    /// that can be allocated from free regions of existing chunks.
+    pub fn existing_block_size(
+        &self,
+        min_size: u64,
+        max_size: u64,
+        align_mask: u64,
+    ) -> Option<u64> {
+        let (min_size, max_size, align_mask) =
+            self.existing_range(min_size, max_size, align_mask)?;
+        let (_, size) = self.freelist.find_largest(align_mask, min_size, max_size)?;
+        Some(size)
+    }
+
+    /// Allocates the largest block between `min_size` and `max_size`
+    /// from free regions of existing chunks, without creating new chunk.
+    pub fn alloc_existing(
+        &mut self,
+        min_size: u64,
+        max_size: u64,
+        align_mask: u64,
+    ) -> Option<FreeListBlock<M>> {
+        let (min_size, max_size, align_mask) =
+            self.existing_range(min_size, max_size, align_mask)?;
+        let block = self
+            .freelist
+            .get_largest_block(align_mask, min_size, max_size)?;
         self.total_allocations += 1;
-        Ok(block)
+        self.count_usage(block.chunk, block.size, true);
+        Some(block)
     }
 
-    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    /// Aligns size range and alignment mask to atoms.
+    fn existing_range(
+        &self,
+        min_size: u64,
+        max_size: u64,
+        align_mask: u64,
+    ) -> Option<(u64, u64, u64)> {
+        let min_size = align_up(min_size.max(1), self.atom_mask)?;
+        let max_size = align_down(max_size, self.atom_mask).max(min_size);
+        Some((min_size, max_size, align_mask | self.atom_mask))
+    }
+
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device, observer)))]
     pub unsafe fn dealloc(
         &mut self,
         device: &impl MemoryDevice<M>,
         block: FreeListBlock<M>,
         heap: &mut Heap,
         allocations_remains: &mut u32,
+        mut observer: Observer<'_>,
     ) {
-        debug_assert!(block.size < self.chunk_size);
         debug_assert_ne!(block.size, 0);
-        self.freelist.insert_block(block);
+        self.count_usage(block.chunk, block.size, false);
+        let index = self.freelist.insert_block(block);
         self.total_deallocations += 1;
 
-        if let Some(memory) = self.freelist.drain(true) {
-            memory.for_each(|(memory, size)| {
-                device.deallocate_memory(memory);
-                *allocations_remains += 1;
-                heap.dealloc(size);
-            });
+        if let Some((memory, size, chunk)) = self.freelist.release(index) {
+            self.destroy_chunk(
+                device,
+                memory,
+                size,
+                chunk,
+                heap,
+                allocations_remains,
+                &mut observer,
+            );
+        }
+
+        // Pinned chunks are counted on growth.
+        // While spilling, check if long-lived blocks were released.
+        if self.spilling {
+            self.update_spilling();
         }
     }
 
@@ -481,21 +793,28 @@ where
     /// * `device` must be one with `DeviceProperties` that were provided to create this `GpuAllocator` instance
     /// * Same `device` instance must be used for all interactions with one `GpuAllocator` instance
     ///   and memory blocks allocated from it
-    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device)))]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device, observer)))]
     pub unsafe fn cleanup(
         &mut self,
         device: &impl MemoryDevice<M>,
         heap: &mut Heap,
         allocations_remains: &mut u32,
+        mut observer: Observer<'_>,
     ) {
-        if let Some(memory) = self.freelist.drain(false) {
-            memory.for_each(|(memory, size)| {
-                device.deallocate_memory(memory);
-                *allocations_remains += 1;
-                heap.dealloc(size);
-            });
+        while let Some((memory, size, chunk)) = self.freelist.release_last_free() {
+            self.destroy_chunk(
+                device,
+                memory,
+                size,
+                chunk,
+                heap,
+                allocations_remains,
+                &mut observer,
+            );
         }
 
+        self.update_spilling();
+
         #[cfg(feature = "tracing")]
         {
             if self.total_allocations == self.total_deallocations && !self.freelist.array.is_empty()
@@ -511,6 +830,152 @@ where
         }
     }
 
+    /// Destroys empty chunks, oldest first, until at most `keep_bytes` bytes are kept in them.
+    ///
+    /// Returns number of bytes kept in empty chunks.
+    pub unsafe fn trim(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        keep_bytes: u64,
+        mut observer: Observer<'_>,
+    ) -> u64 {
+        let mut free_bytes = self.freelist.free_chunks_size();
+
+        while free_bytes > keep_bytes {
+            let (memory, size, chunk) = match self.freelist.release_first_free() {
+                Some(released) => released,
+                None => break,
+            };
+
+            free_bytes -= size;
+            self.destroy_chunk(
+                device,
+                memory,
+                size,
+                chunk,
+                heap,
+                allocations_remains,
+                &mut observer,
+            );
+        }
+
+        self.update_spilling();
+        free_bytes
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    unsafe fn destroy_chunk(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        memory: M,
+        size: u64,
+        chunk: u64,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        observer: &mut Observer<'_>,
+    ) {
+        profile_scope!("FreeListAllocator::destroy_chunk");
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            memory_type = self.memory_type,
+            strategy = "free-list",
+            chunk,
+            size,
+            "chunk destroyed"
+        );
+
+        device.deallocate_memory(memory);
+        *allocations_remains += 1;
+        heap.dealloc(size);
+        observer::chunk_destroyed(observer, self.memory_type, ChunkKind::FreeList, chunk, size);
+        self.memory_objects -= 1;
+        self.memory_bytes -= size;
+
+        if let Ok(index) = self.usage.binary_search_by_key(&chunk, |usage| usage.chunk) {
+            debug_assert_eq!(self.usage[index].blocks, 0);
+            self.usage.remove(index);
+        }
+    }
+
+    /// Counts block of `size` bytes allocated from or returned to the chunk.
+    fn count_usage(&mut self, chunk: u64, size: u64, allocated: bool) {
+        let index = self
+            .usage
+            .binary_search_by_key(&chunk, |usage| usage.chunk)
+            .expect("Chunk of the block should be live");
+
+        let usage = &mut self.usage[index];
+        if allocated {
+            usage.blocks += 1;
+            usage.bytes += size;
+        } else {
+            usage.blocks -= 1;
+            usage.bytes -= size;
+        }
+    }
+
+    /// Starts or stops spilling depending on number of chunks,
+    /// other than the newest one, that are pinned by single live block.
+    fn update_spilling(&mut self) {
+        let newest = self.freelist.counter;
+        let pinned = self
+            .usage
+            .iter()
+            .filter(|usage| usage.chunk != newest && usage.pinned())
+            .count();
+
+        let spilling = pinned >= SPILL_PINNED_CHUNKS && pinned * 2 >= self.usage.len();
+
+        #[cfg(feature = "tracing")]
+        if spilling && !self.spilling {
+            tracing::warn!(
+                memory_type = self.memory_type,
+                pinned,
+                chunks = self.usage.len(),
+                "Linear allocator chunks are pinned by long-lived blocks, \
+                serving further `TRANSIENT` requests from buddy allocator. \
+                Check that long-lived resources are not allocated with `TRANSIENT` usage."
+            );
+        }
+
+        self.spilling = spilling;
+    }
+
+    /// Returns `true` if most chunks are pinned by single long-lived block
+    /// and further requests should be served by another allocator.
+    pub fn spilling(&self) -> bool {
+        self.spilling
+    }
+
+    /// Fills memory object counters of `stats`.
+    pub(crate) fn memory_stats(&self, stats: &mut StrategyStats) {
+        stats.memory_objects = self.memory_objects;
+        stats.memory_bytes = self.memory_bytes;
+    }
+
+    /// Returns iterator over ids and sizes of chunks.
+    pub(crate) fn chunks(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.usage.iter().map(|usage| (usage.chunk, usage.size))
+    }
+
+    /// Returns iterator over chunk ids, offsets and sizes of free regions.
+    pub(crate) fn free_regions(&self) -> impl Iterator<Item = (u64, u64, u64)> + '_ {
+        self.freelist
+            .array
+            .iter()
+            .map(|region| (region.chunk, region.start, region.end - region.start))
+    }
+
+    /// Fills free space measures of `stats`.
+    pub(crate) fn fragmentation_stats(&self, stats: &mut FragmentationStats) {
+        for region in &self.freelist.array {
+            stats.free_region(region.end - region.start, 1);
+        }
+    }
+
     fn host_visible(&self) -> bool {
         self.props.contains(MemoryPropertyFlags::HOST_VISIBLE)
     }
@@ -526,3 +991,40 @@ where
         Err(_) => l,
     }
 }
+
+impl<M> FreeListAllocator<M>
+where
+    M: MemoryBounds,
+{
+    /// Writes deterministic description of allocator state.
+    pub(crate) fn write_debug_state(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "free-list allocator for memory type {}: chunk size {}, final chunk size {}, atom mask {}",
+            self.memory_type, self.chunk_size, self.final_chunk_size, self.atom_mask
+        )?;
+        writeln!(
+            f,
+            "  allocations {}, deallocations {}, chunks created {}",
+            self.total_allocations, self.total_deallocations, self.freelist.counter
+        )?;
+
+        for region in &self.freelist.array {
+            writeln!(
+                f,
+                "  region: chunk {}, memory {:?}, {}..{}, {}",
+                region.chunk,
+                region.memory,
+                region.start,
+                region.end,
+                if region.ptr.is_some() {
+                    "mapped"
+                } else {
+                    "unmapped"
+                }
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,160 @@
+use {
+    crate::align_up,
+    core::ptr::NonNull,
+    gpu_alloc_types::{MappedMemoryRange, MemoryDevice},
+};
+
+/// Byte written into guard bands around sub-allocations
+/// when `guard-bands` feature is enabled.
+pub const GUARD_BYTE: u8 = 0xFD;
+
+/// Returns size of guard band rounded up to satisfy `align_mask`,
+/// so that block placed after the leading band keeps its alignment.
+/// Returns `None` on overflow.
+pub(crate) fn band(guard_band: u64, align_mask: u64) -> Option<u64> {
+    if guard_band == 0 {
+        Some(0)
+    } else {
+        align_up(guard_band, align_mask)
+    }
+}
+
+/// Returns pointer to the start of the allocation from pointer to guarded block.
+pub(crate) unsafe fn outer_ptr(ptr: Option<NonNull<u8>>, guard: u64) -> Option<NonNull<u8>> {
+    ptr.map(|ptr| NonNull::new_unchecked(ptr.as_ptr().sub(guard as usize)))
+}
+
+/// Returns pointer to guarded block from pointer to the start of the allocation.
+pub(crate) unsafe fn inner_ptr(ptr: Option<NonNull<u8>>, guard: u64) -> Option<NonNull<u8>> {
+    ptr.map(|ptr| NonNull::new_unchecked(ptr.as_ptr().add(guard as usize)))
+}
+
+/// Sub-allocation with guard bands.
+pub(crate) struct Guarded<'a, M> {
+    /// Memory object of the allocation.
+    pub memory: &'a M,
+
+    /// Pointer to the start of the allocation if memory object is mapped.
+    pub ptr: Option<NonNull<u8>>,
+
+    /// Offset of the allocation in memory object.
+    pub offset: u64,
+
+    /// Size of the allocation including both guard bands.
+    pub size: u64,
+
+    /// Size of each guard band.
+    pub guard: u64,
+}
+
+/// Fills leading and trailing guard bands of mapped allocation with [`GUARD_BYTE`].
+///
+/// # Safety
+///
+/// `ptr` must point to mapped range of `size` bytes at `offset` in `memory`.
+/// For non-coherent memory `offset`, `size` and `guard` must be aligned to non-coherent atom size.
+pub(crate) unsafe fn fill<M>(
+    device: &impl MemoryDevice<M>,
+    guarded: &Guarded<'_, M>,
+    non_coherent: bool,
+) {
+    let Guarded {
+        memory,
+        ptr,
+        offset,
+        size,
+        guard,
+    } = *guarded;
+
+    let ptr = match ptr {
+        Some(ptr) if guard > 0 => ptr,
+        _ => return,
+    };
+
+    let tail = size - guard;
+    core::ptr::write_bytes(ptr.as_ptr(), GUARD_BYTE, guard as usize);
+    core::ptr::write_bytes(ptr.as_ptr().add(tail as usize), GUARD_BYTE, guard as usize);
+
+    if non_coherent {
+        // Failure to flush only weakens the check.
+        let _ = device.flush_memory_ranges(&[
+            MappedMemoryRange {
+                memory,
+                offset,
+                size: guard,
+            },
+            MappedMemoryRange {
+                memory,
+                offset: offset + tail,
+                size: guard,
+            },
+        ]);
+    }
+}
+
+/// Checks that guard bands filled by [`fill`] are intact.
+///
+/// # Panics
+///
+/// Panics if any byte of guard bands was overwritten.
+///
+/// # Safety
+///
+/// Same as for [`fill`].
+pub(crate) unsafe fn check<M>(
+    device: &impl MemoryDevice<M>,
+    guarded: &Guarded<'_, M>,
+    non_coherent: bool,
+    memory_type: u32,
+) {
+    let Guarded {
+        memory,
+        ptr,
+        offset,
+        size,
+        guard,
+    } = *guarded;
+
+    let ptr = match ptr {
+        Some(ptr) if guard > 0 => ptr,
+        _ => return,
+    };
+
+    let tail = size - guard;
+
+    if non_coherent {
+        let _ = device.invalidate_memory_ranges(&[
+            MappedMemoryRange {
+                memory,
+                offset,
+                size: guard,
+            },
+            MappedMemoryRange {
+                memory,
+                offset: offset + tail,
+                size: guard,
+            },
+        ]);
+    }
+
+    let leading = core::slice::from_raw_parts(ptr.as_ptr(), guard as usize);
+    let trailing = core::slice::from_raw_parts(ptr.as_ptr().add(tail as usize), guard as usize);
+
+    if let Some(pos) = leading.iter().position(|&byte| byte != GUARD_BYTE) {
+        panic!(
+            "Guard band before memory block at offset {} in memory type {} is overwritten {} bytes before the block",
+            offset + guard,
+            memory_type,
+            guard - pos as u64,
+        );
+    }
+
+    if let Some(pos) = trailing.iter().position(|&byte| byte != GUARD_BYTE) {
+        panic!(
+            "Guard band after memory block at offset {} in memory type {} is overwritten {} bytes past the block end",
+            offset + guard,
+            memory_type,
+            pos,
+        );
+    }
+}
@@ -1,32 +1,211 @@
+use crate::{
+    atomic::{AtomicU64, Relaxed},
+    AllocationError,
+};
+
+#[cfg(all(
+    target_has_atomic = "ptr",
+    any(target_has_atomic = "64", feature = "portable-atomic")
+))]
+use crate::budget::BudgetTracker;
+
+/// Memory heap accounting.
+///
+/// Counters are atomic so that dedicated allocations can be accounted
+/// through shared reference, while exclusive access updates them without synchronization.
 #[derive(Debug)]
 pub(crate) struct Heap {
     size: u64,
-    used: u64,
-    allocated: u128,
-    deallocated: u128,
+    budget: u64,
+    used: AtomicU64,
+    peak: AtomicU64,
+    watermark: AtomicU64,
+    allocated: AtomicU64,
+    deallocated: AtomicU64,
+    #[cfg(all(
+        target_has_atomic = "ptr",
+        any(target_has_atomic = "64", feature = "portable-atomic")
+    ))]
+    tracker: Option<(BudgetTracker, u32)>,
 }
 
 impl Heap {
-    pub(crate) fn new(size: u64) -> Self {
+    pub(crate) fn new(size: u64, budget: u64) -> Self {
         Heap {
             size,
-            used: 0,
-            allocated: 0,
-            deallocated: 0,
+            budget,
+            used: AtomicU64::new(0),
+            peak: AtomicU64::new(0),
+            watermark: AtomicU64::new(0),
+            allocated: AtomicU64::new(0),
+            deallocated: AtomicU64::new(0),
+            #[cfg(all(
+                target_has_atomic = "ptr",
+                any(target_has_atomic = "64", feature = "portable-atomic")
+            ))]
+            tracker: None,
+        }
+    }
+
+    /// Makes the heap reserve memory objects in `tracker` as heap with `index`,
+    /// charging it with `objects` already allocated from the heap.
+    #[cfg(all(
+        target_has_atomic = "ptr",
+        any(target_has_atomic = "64", feature = "portable-atomic")
+    ))]
+    pub(crate) fn set_tracker(&mut self, tracker: BudgetTracker, index: u32, objects: u32) {
+        if let Some((tracker, index)) = &self.tracker {
+            tracker.discharge(*index, self.used(), objects);
         }
+        tracker.charge(index, self.used(), objects);
+        self.tracker = Some((tracker, index));
     }
 
-    pub(crate) fn size(&mut self) -> u64 {
+    pub(crate) fn size(&self) -> u64 {
         self.size
     }
 
+    /// Returns number of bytes allocator may allocate from the heap.
+    pub(crate) fn budget(&self) -> u64 {
+        self.budget
+    }
+
+    pub(crate) fn set_budget(&mut self, budget: u64) {
+        self.budget = budget;
+    }
+
+    /// Reserves budget for memory object of `size` bytes
+    /// in this heap and in shared budget tracker, if any.
+    ///
+    /// Reservation must be followed by [`Heap::alloc`] or reverted by [`Heap::release`].
+    pub(crate) fn reserve(&self, size: u64) -> Result<(), AllocationError> {
+        if !matches!(self.used().checked_add(size), Some(used) if used <= self.budget()) {
+            return Err(AllocationError::OutOfBudget);
+        }
+
+        #[cfg(all(
+            target_has_atomic = "ptr",
+            any(target_has_atomic = "64", feature = "portable-atomic")
+        ))]
+        if let Some((tracker, index)) = &self.tracker {
+            tracker.reserve(*index, size)?;
+        }
+        Ok(())
+    }
+
+    /// Releases reservation made by [`Heap::reserve`] in shared budget tracker, if any,
+    /// when memory object is deallocated or was not allocated after all.
+    pub(crate) fn release(&self, size: u64) {
+        #[cfg(all(
+            target_has_atomic = "ptr",
+            any(target_has_atomic = "64", feature = "portable-atomic")
+        ))]
+        if let Some((tracker, index)) = &self.tracker {
+            tracker.release(*index, size);
+        }
+        #[cfg(not(all(
+            target_has_atomic = "ptr",
+            any(target_has_atomic = "64", feature = "portable-atomic")
+        )))]
+        let _ = size;
+    }
+
+    pub(crate) fn used(&self) -> u64 {
+        self.used.load(Relaxed)
+    }
+
+    pub(crate) fn peak(&self) -> u64 {
+        self.peak.load(Relaxed)
+    }
+
+    pub(crate) fn watermark(&self) -> u64 {
+        self.watermark.load(Relaxed)
+    }
+
+    pub(crate) fn reset_watermark(&mut self) {
+        *self.watermark.get_mut() = *self.used.get_mut();
+    }
+
     pub(crate) fn alloc(&mut self, size: u64) {
-        self.used += size;
-        self.allocated += u128::from(size);
+        let used = self.used.get_mut();
+        *used += size;
+        let peak = self.peak.get_mut();
+        *peak = (*peak).max(*used);
+        let watermark = self.watermark.get_mut();
+        *watermark = (*watermark).max(*used);
+        *self.allocated.get_mut() += size;
+    }
+
+    /// Same as [`Heap::alloc`] for memory object allocated without reservation.
+    pub(crate) fn import(&mut self, size: u64) {
+        #[cfg(all(
+            target_has_atomic = "ptr",
+            any(target_has_atomic = "64", feature = "portable-atomic")
+        ))]
+        if let Some((tracker, index)) = &self.tracker {
+            tracker.charge(*index, size, 1);
+        }
+        self.alloc(size);
     }
 
     pub(crate) fn dealloc(&mut self, size: u64) {
-        self.used -= size;
-        self.deallocated += u128::from(size);
+        *self.used.get_mut() -= size;
+        *self.deallocated.get_mut() += size;
+        self.release(size);
+    }
+
+    /// Same as [`Heap::reserve`] followed by [`Heap::alloc`] but through shared reference.
+    /// Leaves counters intact if allocation would exceed heap budget.
+    #[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+    pub(crate) fn try_alloc_concurrent(&self, size: u64) -> Result<(), AllocationError> {
+        let budget = self.budget();
+        let used = self
+            .used
+            .fetch_update(Relaxed, Relaxed, |used| {
+                used.checked_add(size).filter(|&used| used <= budget)
+            })
+            .map_err(|_| AllocationError::OutOfBudget)?;
+
+        #[cfg(target_has_atomic = "ptr")]
+        if let Some((tracker, index)) = &self.tracker {
+            if let Err(err) = tracker.reserve(*index, size) {
+                self.used.fetch_sub(size, Relaxed);
+                return Err(err);
+            }
+        }
+
+        self.peak.fetch_max(used + size, Relaxed);
+        self.watermark.fetch_max(used + size, Relaxed);
+        self.allocated.fetch_add(size, Relaxed);
+        Ok(())
+    }
+
+    /// Reverts [`Heap::try_alloc_concurrent`] when memory object was not allocated after all.
+    #[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+    pub(crate) fn cancel_concurrent(&self, size: u64) {
+        self.used.fetch_sub(size, Relaxed);
+        self.allocated.fetch_sub(size, Relaxed);
+        self.release(size);
+    }
+
+    /// Same as [`Heap::dealloc`] but through shared reference.
+    #[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+    pub(crate) fn dealloc_concurrent(&self, size: u64) {
+        self.used.fetch_sub(size, Relaxed);
+        self.deallocated.fetch_add(size, Relaxed);
+        self.release(size);
+    }
+}
+
+impl core::fmt::Display for Heap {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "size {}, used {}, allocated {}, deallocated {}",
+            self.size,
+            self.used(),
+            self.allocated.load(Relaxed),
+            self.deallocated.load(Relaxed)
+        )
     }
 }
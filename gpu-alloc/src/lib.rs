@@ -53,19 +53,139 @@ macro_rules! report_error_on_drop {
     }};
 }
 
+/// Opens profiler scope until the end of the enclosing block
+/// when `profiling` feature is enabled.
+#[cfg(feature = "profiling")]
+macro_rules! profile_scope {
+    ($name:literal) => {
+        profiling::scope!($name)
+    };
+}
+
+#[cfg(not(feature = "profiling"))]
+macro_rules! profile_scope {
+    ($name:literal) => {};
+}
+
+mod advisor;
 mod allocator;
+mod atomic;
 mod block;
 mod buddy;
+#[cfg(all(
+    target_has_atomic = "ptr",
+    any(target_has_atomic = "64", feature = "portable-atomic")
+))]
+mod budget;
+mod cache;
+mod cleanup;
 mod config;
+mod defrag;
+mod dump;
 mod error;
+mod failure;
 mod freelist;
+#[cfg(feature = "guard-bands")]
+mod guard;
 mod heap;
+mod mapped;
+mod memory_report;
+mod observer;
+#[cfg(feature = "bytemuck")]
+mod pod;
+#[cfg(feature = "poison")]
+mod poison;
+mod pool;
+#[cfg(feature = "raw-allocators")]
+mod raw;
+mod rebalance;
+mod registry;
+mod report;
+mod ring;
+mod selector;
+#[cfg(feature = "std")]
+mod shared;
 mod slab;
+mod snapshot;
+mod stats;
+mod suballoc;
+#[cfg(feature = "svg")]
+mod svg;
+#[cfg(feature = "metrics")]
+mod telemetry;
+#[cfg(feature = "std")]
+mod time;
+#[cfg(feature = "chrome-trace")]
+mod trace;
+#[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+mod transient;
 mod usage;
 mod util;
+mod virtual_alloc;
+
+#[cfg(feature = "chrome-trace")]
+pub use self::trace::AllocationTrace;
+
+#[cfg(all(
+    target_has_atomic = "ptr",
+    any(target_has_atomic = "64", feature = "portable-atomic")
+))]
+pub use self::budget::BudgetTracker;
+
+#[cfg(feature = "poison")]
+pub use self::poison::POISON_BYTE;
+
+#[cfg(feature = "guard-bands")]
+pub use self::guard::GUARD_BYTE;
+
+#[cfg(feature = "metrics")]
+pub use self::telemetry::{
+    METRIC_DEVICE_ALLOCATIONS, METRIC_FAILED_ALLOCATIONS, METRIC_HEAP_USED_BYTES,
+    METRIC_LIVE_BLOCKS,
+};
+
+#[cfg(feature = "raw-allocators")]
+pub use self::raw::{BuddyAllocator, BuddyBlock, LinearAllocator, LinearBlock};
+
+#[cfg(feature = "std")]
+pub use self::observer::AllocEvent;
+
+#[cfg(feature = "std")]
+pub use self::shared::{GpuMemory, SharedAllocator};
+
+#[cfg(all(
+    feature = "std",
+    any(target_has_atomic = "64", feature = "portable-atomic")
+))]
+pub use self::shared::AllocScope;
+
+#[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+pub use self::transient::{TransientAllocator, TransientBlock};
 
 pub use {
-    self::{allocator::*, block::MemoryBlock, config::*, error::*, usage::*},
+    self::{
+        advisor::{Advice, AdvisorConfig},
+        allocator::*,
+        block::{MemoryBlock, MemoryBlockToken, RawMemoryBlock},
+        cleanup::CleanupOnDrop,
+        config::*,
+        defrag::Defragmentation,
+        dump::*,
+        error::*,
+        failure::*,
+        mapped::MappedGuard,
+        memory_report::{DeviceMemoryReport, MemoryReportEvent, ReportMismatch, ReportedObject},
+        observer::{AllocatorObserver, BlockEvent, ChunkEvent, FailureEvent},
+        pool::{PoolConfig, PoolId},
+        rebalance::BlockMove,
+        report::{AllocatorReport, HeapReport, TypeReport},
+        selector::{MemoryTypeCandidate, MemoryTypeSelector},
+        snapshot::*,
+        stats::*,
+        suballoc::BlockSuballocator,
+        usage::*,
+        virtual_alloc::{VirtualAllocation, VirtualAllocator},
+    },
     gpu_alloc_types::*,
 };
 
@@ -78,6 +198,8 @@ pub struct Request {
     pub size: u64,
 
     /// Minimal alignment mask required.
+    /// Must be one less than power of two, otherwise allocation fails with
+    /// `AllocationError::InvalidAlignment`.
     /// Returned block may have larger alignment,
     /// use `MemoryBlock::align` to learn actual alignment of returned block.
     pub align_mask: u64,
@@ -91,6 +213,11 @@ pub struct Request {
     /// Returned block will be from memory type corresponding to one of set bits,
     /// use `MemoryBlock::memory_type` to learn memory type index of returned block.
     pub memory_types: u32,
+
+    /// Kind of resource that will be bound to the block.
+    /// Blocks for non-linear resources are padded to `DeviceProperties::buffer_image_granularity`
+    /// so they never share a page with linear resources.
+    pub kind: ResourceKind,
 }
 
 /// Aligns `value` up to `align_mask`
@@ -0,0 +1,141 @@
+use {
+    crate::{block::MemoryBlock, error::MapError},
+    core::{
+        convert::TryFrom as _,
+        fmt,
+        mem::ManuallyDrop,
+        ops::{Deref, DerefMut},
+        ptr::NonNull,
+        slice,
+    },
+    gpu_alloc_types::{DeviceRef, MemoryDevice, OutOfMemory},
+};
+
+/// Guard that keeps whole [`MemoryBlock`] mapped.
+///
+/// Created with [`MemoryBlock::map_persistent`].
+/// Dereferences to bytes of the block.
+/// When dropped, non-coherent memory of the block is flushed
+/// and the block is unmapped, which unmaps memory object only for dedicated blocks.
+/// Use [`MappedGuard::finish`] to observe flush errors.
+pub struct MappedGuard<'a, M, MD>
+where
+    MD: MemoryDevice<M>,
+{
+    block: &'a mut MemoryBlock<M>,
+    device: &'a MD,
+    ptr: NonNull<u8>,
+    len: usize,
+}
+
+impl<M> MemoryBlock<M> {
+    /// Maps whole block until returned guard is dropped.
+    ///
+    /// Convenient for persistently mapped uniform rings and staging buffers,
+    /// as flush and unmap are performed by the guard.
+    ///
+    /// Requirements for mapped memory are the same as for [`MemoryBlock::map`],
+    /// and it fails with the same errors.
+    ///
+    /// # Safety
+    ///
+    /// `block` must have been allocated from specified `device`.
+    pub unsafe fn map_persistent<'a, MD>(
+        &'a mut self,
+        device: &'a impl AsRef<MD>,
+    ) -> Result<MappedGuard<'a, M, MD>, MapError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        let len = usize::try_from(self.size()).map_err(|_| MapError::Unaddressable)?;
+        let ptr = self.map(device, 0, len)?;
+
+        Ok(MappedGuard {
+            block: self,
+            device: device.as_ref(),
+            ptr,
+            len,
+        })
+    }
+}
+
+impl<M, MD> MappedGuard<'_, M, MD>
+where
+    MD: MemoryDevice<M>,
+{
+    /// Returns mapped block.
+    pub fn block(&self) -> &MemoryBlock<M> {
+        self.block
+    }
+
+    /// Flushes whole block if its memory is not coherent,
+    /// making host writes done so far visible to the device.
+    pub fn flush(&self) -> Result<(), OutOfMemory> {
+        // Block was allocated from the device, as required by `map_persistent`.
+        unsafe { self.block.flush_range(self.device, 0, self.block.size()) }
+    }
+
+    /// Flushes and unmaps the block, returning flush error if any.
+    pub fn finish(self) -> Result<(), OutOfMemory> {
+        let mut guard = ManuallyDrop::new(self);
+        let result = guard.flush();
+        let device = DeviceRef::new(guard.device);
+
+        unsafe {
+            guard.block.unmap(&device);
+        }
+        result
+    }
+}
+
+impl<M, MD> Drop for MappedGuard<'_, M, MD>
+where
+    MD: MemoryDevice<M>,
+{
+    fn drop(&mut self) {
+        let result = self.flush();
+
+        unsafe {
+            self.block.unmap(&DeviceRef::new(self.device));
+        }
+
+        if result.is_err() {
+            report_error_on_drop!("Failed to flush persistently mapped memory block");
+        }
+    }
+}
+
+impl<M, MD> Deref for MappedGuard<'_, M, MD>
+where
+    MD: MemoryDevice<M>,
+{
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        // Whole block is mapped for the guard lifetime.
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<M, MD> DerefMut for MappedGuard<'_, M, MD>
+where
+    MD: MemoryDevice<M>,
+{
+    fn deref_mut(&mut self) -> &mut [u8] {
+        // Guard borrows the block mutably, so mapped bytes are not aliased.
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+}
+
+impl<M, MD> fmt::Debug for MappedGuard<'_, M, MD>
+where
+    M: fmt::Debug,
+    MD: MemoryDevice<M>,
+{
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("MappedGuard")
+            .field("block", &self.block)
+            .field("ptr", &self.ptr)
+            .finish()
+    }
+}
@@ -0,0 +1,252 @@
+use {
+    crate::{GpuAllocator, MemoryBounds},
+    alloc::{collections::BTreeMap, vec::Vec},
+    core::fmt,
+};
+
+/// Event about device memory object reported by the driver,
+/// for example through `VK_EXT_device_memory_report` callback.
+///
+/// Events should be reported only for memory objects that can be allocated
+/// by [`MemoryDevice::allocate_memory`], not for driver internal allocations.
+///
+/// [`MemoryDevice::allocate_memory`]: gpu_alloc_types::MemoryDevice::allocate_memory
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MemoryReportEvent {
+    /// Memory object was allocated.
+    Allocate {
+        /// Id of memory object, unique within device lifetime.
+        id: u64,
+        /// Raw handle of memory object.
+        handle: u64,
+        /// Size of memory object.
+        size: u64,
+        /// Index of memory heap.
+        heap: u32,
+    },
+
+    /// Memory object was freed.
+    Free {
+        /// Id of memory object.
+        id: u64,
+    },
+
+    /// External memory object was imported.
+    Import {
+        /// Id of memory object, unique within device lifetime.
+        id: u64,
+        /// Raw handle of memory object.
+        handle: u64,
+        /// Size of memory object.
+        size: u64,
+        /// Index of memory heap.
+        heap: u32,
+    },
+
+    /// Imported memory object was released.
+    Unimport {
+        /// Id of memory object.
+        id: u64,
+    },
+
+    /// Driver failed to allocate memory object.
+    AllocationFailed {
+        /// Requested size of memory object.
+        size: u64,
+        /// Index of memory heap.
+        heap: u32,
+    },
+}
+
+/// Live memory object reported by the driver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ReportedObject {
+    /// Id of memory object, unique within device lifetime.
+    pub id: u64,
+
+    /// Raw handle of memory object.
+    pub handle: u64,
+
+    /// Size of memory object.
+    pub size: u64,
+
+    /// Index of memory heap.
+    pub heap: u32,
+
+    /// Whether memory object was imported rather than allocated.
+    pub imported: bool,
+}
+
+/// Live memory objects reported by the driver,
+/// used to cross-check allocator accounting.
+///
+/// Driver callbacks may be invoked from any thread,
+/// so report is usually kept behind a mutex.
+#[derive(Clone, Debug, Default)]
+pub struct DeviceMemoryReport {
+    objects: BTreeMap<u64, ReportedObject>,
+    allocation_failures: u64,
+}
+
+impl DeviceMemoryReport {
+    /// Returns empty report.
+    pub fn new() -> Self {
+        DeviceMemoryReport::default()
+    }
+
+    /// Records event reported by the driver.
+    pub fn record(&mut self, event: MemoryReportEvent) {
+        match event {
+            MemoryReportEvent::Allocate {
+                id,
+                handle,
+                size,
+                heap,
+            } => {
+                self.insert(id, handle, size, heap, false);
+            }
+            MemoryReportEvent::Import {
+                id,
+                handle,
+                size,
+                heap,
+            } => {
+                self.insert(id, handle, size, heap, true);
+            }
+            MemoryReportEvent::Free { id } | MemoryReportEvent::Unimport { id } => {
+                self.objects.remove(&id);
+            }
+            MemoryReportEvent::AllocationFailed { .. } => {
+                self.allocation_failures += 1;
+            }
+        }
+    }
+
+    fn insert(&mut self, id: u64, handle: u64, size: u64, heap: u32, imported: bool) {
+        self.objects.insert(
+            id,
+            ReportedObject {
+                id,
+                handle,
+                size,
+                heap,
+                imported,
+            },
+        );
+    }
+
+    /// Returns live memory objects ordered by id.
+    pub fn objects(&self) -> impl Iterator<Item = &ReportedObject> + '_ {
+        self.objects.values()
+    }
+
+    /// Returns number of bytes in live memory objects of the heap.
+    pub fn heap_usage(&self, heap: u32) -> u64 {
+        self.objects
+            .values()
+            .filter(|object| object.heap == heap)
+            .map(|object| object.size)
+            .sum()
+    }
+
+    /// Returns number of failed allocations reported by the driver.
+    pub fn allocation_failures(&self) -> u64 {
+        self.allocation_failures
+    }
+
+    /// Compares memory objects reported by the driver with ones accounted by `allocator`
+    /// in every memory heap.
+    ///
+    /// Returns heaps where they disagree.
+    /// Reported memory that allocator does not account was allocated outside of it,
+    /// by the application, another allocator instance or a library.
+    /// Accounted memory that driver does not report points to accounting bug
+    /// or to report missing events.
+    pub fn reconcile<M>(&self, allocator: &GpuAllocator<M>) -> Vec<ReportMismatch>
+    where
+        M: MemoryBounds + 'static,
+    {
+        let stats = allocator.stats();
+
+        let mut reported: BTreeMap<u32, (u64, u64)> = BTreeMap::new();
+        for object in self.objects.values() {
+            let (bytes, objects) = reported.entry(object.heap).or_default();
+            *bytes += object.size;
+            *objects += 1;
+        }
+
+        let heaps = stats.heaps.len().max(
+            reported
+                .keys()
+                .next_back()
+                .map_or(0, |&heap| heap as usize + 1),
+        );
+
+        (0..heaps as u32)
+            .filter_map(|heap| {
+                let (reported_bytes, reported_objects) =
+                    reported.get(&heap).copied().unwrap_or_default();
+                let (accounted_bytes, accounted_objects) = match stats.heaps.get(heap as usize) {
+                    Some(stats) => (stats.used, stats.total().memory_objects),
+                    None => (0, 0),
+                };
+
+                if reported_bytes == accounted_bytes && reported_objects == accounted_objects {
+                    None
+                } else {
+                    Some(ReportMismatch {
+                        heap,
+                        reported_bytes,
+                        reported_objects,
+                        accounted_bytes,
+                        accounted_objects,
+                    })
+                }
+            })
+            .collect()
+    }
+}
+
+/// Disagreement between memory objects reported by the driver in memory heap
+/// and memory objects accounted by allocator,
+/// returned by [`DeviceMemoryReport::reconcile`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ReportMismatch {
+    /// Index of memory heap.
+    pub heap: u32,
+
+    /// Bytes in memory objects reported by the driver.
+    pub reported_bytes: u64,
+
+    /// Number of memory objects reported by the driver.
+    pub reported_objects: u64,
+
+    /// Bytes in memory objects accounted by allocator.
+    pub accounted_bytes: u64,
+
+    /// Number of memory objects accounted by allocator.
+    pub accounted_objects: u64,
+}
+
+impl ReportMismatch {
+    /// Returns number of reported bytes that allocator does not account.
+    pub fn untracked_bytes(&self) -> u64 {
+        self.reported_bytes.saturating_sub(self.accounted_bytes)
+    }
+}
+
+impl fmt::Display for ReportMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "heap {}: driver reports {} bytes in {} memory objects, allocator accounts {} bytes in {} memory objects",
+            self.heap,
+            self.reported_bytes,
+            self.reported_objects,
+            self.accounted_bytes,
+            self.accounted_objects
+        )
+    }
+}
@@ -0,0 +1,292 @@
+use {
+    crate::{dump::ChunkKind, error::AllocationError, registry::BlockRecord, Dedicated, Request},
+    alloc::boxed::Box,
+    core::fmt,
+};
+
+#[cfg(feature = "std")]
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+/// Memory block allocated or deallocated by [`GpuAllocator`].
+///
+/// [`GpuAllocator`]: crate::GpuAllocator
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct BlockEvent {
+    /// Id of the block, unique among live blocks of the allocator.
+    pub id: usize,
+
+    /// Memory type of the block.
+    pub memory_type: u32,
+
+    /// Strategy that allocated the block.
+    pub kind: ChunkKind,
+
+    /// Id of the memory object the block belongs to.
+    /// Matches [`ChunkEvent::id`]. For dedicated blocks this is block id.
+    pub chunk: u64,
+
+    /// Offset of the block in the memory object.
+    pub offset: u64,
+
+    /// Size of the block.
+    pub size: u64,
+
+    /// Size requested for the block.
+    pub requested_size: u64,
+
+    /// Tag of the block.
+    pub tag: Option<&'static str>,
+}
+
+impl BlockEvent {
+    pub(crate) fn new(id: usize, record: &BlockRecord) -> Self {
+        BlockEvent {
+            id,
+            memory_type: record.memory_type,
+            kind: record.chunk.kind(),
+            chunk: record.chunk.id(id),
+            offset: record.offset,
+            size: record.size,
+            requested_size: record.requested_size,
+            tag: record.tag,
+        }
+    }
+}
+
+/// Memory object allocated or deallocated from device by [`GpuAllocator`].
+///
+/// [`GpuAllocator`]: crate::GpuAllocator
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct ChunkEvent {
+    /// Memory type of the memory object.
+    pub memory_type: u32,
+
+    /// Strategy that owns the memory object.
+    pub kind: ChunkKind,
+
+    /// Id of the memory object, unique among live memory objects of the same kind and memory type.
+    pub id: u64,
+
+    /// Size of the memory object.
+    pub size: u64,
+}
+
+/// Allocation request that [`GpuAllocator`] failed to serve.
+///
+/// [`GpuAllocator`]: crate::GpuAllocator
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct FailureEvent {
+    /// Failed request.
+    pub request: Request,
+
+    /// Dedicated memory hint of the request.
+    pub dedicated: Option<Dedicated>,
+
+    /// Tag of the request.
+    pub tag: Option<&'static str>,
+
+    /// Returned error.
+    pub error: AllocationError,
+}
+
+/// User callbacks invoked by [`GpuAllocator`] on allocation events.
+///
+/// Set with [`GpuAllocator::set_observer`].
+/// All methods do nothing by default.
+///
+/// Callbacks are invoked while allocator is borrowed,
+/// so they can only record events or act on state outside of the allocator.
+///
+/// [`GpuAllocator`]: crate::GpuAllocator
+/// [`GpuAllocator::set_observer`]: crate::GpuAllocator::set_observer
+pub trait AllocatorObserver: Send + Sync {
+    /// Called after memory block is allocated or imported.
+    fn on_alloc(&mut self, event: &BlockEvent) {
+        let _ = event;
+    }
+
+    /// Called when memory block is deallocated, before its memory object is released.
+    fn on_dealloc(&mut self, event: &BlockEvent) {
+        let _ = event;
+    }
+
+    /// Called after memory object is allocated from device.
+    fn on_chunk_created(&mut self, event: &ChunkEvent) {
+        let _ = event;
+    }
+
+    /// Called after memory object is deallocated.
+    fn on_chunk_destroyed(&mut self, event: &ChunkEvent) {
+        let _ = event;
+    }
+
+    /// Called when allocation request fails.
+    fn on_failure(&mut self, event: &FailureEvent) {
+        let _ = event;
+    }
+}
+
+/// Event sent to receivers returned by [`GpuAllocator::subscribe`].
+///
+/// [`GpuAllocator::subscribe`]: crate::GpuAllocator::subscribe
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum AllocEvent {
+    /// Memory block was allocated or imported.
+    Alloc(BlockEvent),
+
+    /// Memory block was deallocated.
+    Dealloc(BlockEvent),
+
+    /// Memory object was allocated from device.
+    ChunkCreated(ChunkEvent),
+
+    /// Memory object was deallocated.
+    ChunkDestroyed(ChunkEvent),
+
+    /// Allocation request failed.
+    Failure(FailureEvent),
+}
+
+/// Optional observer and event subscribers owned by allocator.
+#[derive(Default)]
+pub(crate) struct ObserverSlot {
+    observer: Option<Box<dyn AllocatorObserver>>,
+
+    // Senders are only used through exclusive reference,
+    // mutex makes them `Sync` without locking.
+    #[cfg(feature = "std")]
+    subscribers: std::vec::Vec<std::sync::Mutex<Sender<AllocEvent>>>,
+}
+
+impl ObserverSlot {
+    pub fn set(&mut self, observer: Option<Box<dyn AllocatorObserver>>) {
+        self.observer = observer;
+    }
+
+    pub fn take(&mut self) -> Option<Box<dyn AllocatorObserver>> {
+        self.observer.take()
+    }
+
+    #[cfg(feature = "std")]
+    pub fn subscribe(&mut self) -> Receiver<AllocEvent> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(std::sync::Mutex::new(sender));
+        receiver
+    }
+
+    pub fn get(&mut self) -> Observer<'_> {
+        #[cfg(feature = "std")]
+        if !self.subscribers.is_empty() {
+            return Some(self);
+        }
+
+        self.observer.as_deref_mut()
+    }
+
+    /// Sends event to every subscriber, dropping ones whose receiver is gone.
+    #[cfg(feature = "std")]
+    fn send(&mut self, event: AllocEvent) {
+        self.subscribers.retain(|sender| match sender.lock() {
+            Ok(sender) => sender.send(event).is_ok(),
+            Err(_) => false,
+        });
+    }
+}
+
+#[cfg(feature = "std")]
+impl AllocatorObserver for ObserverSlot {
+    fn on_alloc(&mut self, event: &BlockEvent) {
+        if let Some(observer) = &mut self.observer {
+            observer.on_alloc(event);
+        }
+        self.send(AllocEvent::Alloc(*event));
+    }
+
+    fn on_dealloc(&mut self, event: &BlockEvent) {
+        if let Some(observer) = &mut self.observer {
+            observer.on_dealloc(event);
+        }
+        self.send(AllocEvent::Dealloc(*event));
+    }
+
+    fn on_chunk_created(&mut self, event: &ChunkEvent) {
+        if let Some(observer) = &mut self.observer {
+            observer.on_chunk_created(event);
+        }
+        self.send(AllocEvent::ChunkCreated(*event));
+    }
+
+    fn on_chunk_destroyed(&mut self, event: &ChunkEvent) {
+        if let Some(observer) = &mut self.observer {
+            observer.on_chunk_destroyed(event);
+        }
+        self.send(AllocEvent::ChunkDestroyed(*event));
+    }
+
+    fn on_failure(&mut self, event: &FailureEvent) {
+        if let Some(observer) = &mut self.observer {
+            observer.on_failure(event);
+        }
+        self.send(AllocEvent::Failure(*event));
+    }
+}
+
+impl fmt::Debug for ObserverSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.observer {
+            Some(_) => f.write_str("Some(AllocatorObserver)")?,
+            None => f.write_str("None")?,
+        }
+
+        #[cfg(feature = "std")]
+        if !self.subscribers.is_empty() {
+            write!(f, " with {} subscribers", self.subscribers.len())?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Observer borrowed from allocator for the duration of single operation.
+pub(crate) type Observer<'a> = Option<&'a mut (dyn AllocatorObserver + 'static)>;
+
+/// Notifies observer, if any, about chunk creation.
+pub(crate) fn chunk_created(
+    observer: &mut Observer<'_>,
+    memory_type: u32,
+    kind: ChunkKind,
+    id: u64,
+    size: u64,
+) {
+    if let Some(observer) = observer {
+        observer.on_chunk_created(&ChunkEvent {
+            memory_type,
+            kind,
+            id,
+            size,
+        });
+    }
+}
+
+/// Notifies observer, if any, about chunk destruction.
+pub(crate) fn chunk_destroyed(
+    observer: &mut Observer<'_>,
+    memory_type: u32,
+    kind: ChunkKind,
+    id: u64,
+    size: u64,
+) {
+    if let Some(observer) = observer {
+        observer.on_chunk_destroyed(&ChunkEvent {
+            memory_type,
+            kind,
+            id,
+            size,
+        });
+    }
+}
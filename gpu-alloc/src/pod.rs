@@ -0,0 +1,102 @@
+use {
+    crate::{block::MemoryBlock, error::MapError},
+    bytemuck::Pod,
+    gpu_alloc_types::MemoryDevice,
+};
+
+/// Typed access to memory of the block.
+///
+/// Values are copied bytewise, so mapped memory
+/// doesn't have to be aligned for `T`.
+///
+/// Available with `bytemuck` feature.
+impl<M> MemoryBlock<M> {
+    /// Transiently maps block memory range and copies specified values
+    /// to the mapped memory range, flushing it if memory is not coherent.
+    ///
+    /// Fails with the same errors as [`MemoryBlock::write_bytes`].
+    ///
+    /// # Safety
+    ///
+    /// Same as for [`MemoryBlock::write_bytes`].
+    #[inline(always)]
+    pub unsafe fn write_slice<T, MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        offset: u64,
+        data: &[T],
+    ) -> Result<(), MapError>
+    where
+        T: Pod,
+        MD: MemoryDevice<M>,
+    {
+        self.write_bytes(device, offset, bytemuck::cast_slice(data))
+    }
+
+    /// Transiently maps block memory range and copies single value
+    /// to the mapped memory range, flushing it if memory is not coherent.
+    ///
+    /// Fails with the same errors as [`MemoryBlock::write_bytes`].
+    ///
+    /// # Safety
+    ///
+    /// Same as for [`MemoryBlock::write_bytes`].
+    #[inline(always)]
+    pub unsafe fn write_struct<T, MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        offset: u64,
+        value: &T,
+    ) -> Result<(), MapError>
+    where
+        T: Pod,
+        MD: MemoryDevice<M>,
+    {
+        self.write_bytes(device, offset, bytemuck::bytes_of(value))
+    }
+
+    /// Transiently maps block memory range, invalidates it if memory is not coherent
+    /// and copies values from the mapped memory range.
+    ///
+    /// Fails with the same errors as [`MemoryBlock::read_bytes`].
+    ///
+    /// # Safety
+    ///
+    /// Same as for [`MemoryBlock::read_bytes`].
+    #[inline(always)]
+    pub unsafe fn read_slice<T, MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        offset: u64,
+        data: &mut [T],
+    ) -> Result<(), MapError>
+    where
+        T: Pod,
+        MD: MemoryDevice<M>,
+    {
+        self.read_bytes(device, offset, bytemuck::cast_slice_mut(data))
+    }
+
+    /// Transiently maps block memory range, invalidates it if memory is not coherent
+    /// and reads single value from the mapped memory range.
+    ///
+    /// Fails with the same errors as [`MemoryBlock::read_bytes`].
+    ///
+    /// # Safety
+    ///
+    /// Same as for [`MemoryBlock::read_bytes`].
+    #[inline(always)]
+    pub unsafe fn read_struct<T, MD>(
+        &mut self,
+        device: &impl AsRef<MD>,
+        offset: u64,
+    ) -> Result<T, MapError>
+    where
+        T: Pod,
+        MD: MemoryDevice<M>,
+    {
+        let mut value = T::zeroed();
+        self.read_bytes(device, offset, bytemuck::bytes_of_mut(&mut value))?;
+        Ok(value)
+    }
+}
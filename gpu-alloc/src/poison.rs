@@ -0,0 +1,48 @@
+use {
+    core::ptr::NonNull,
+    gpu_alloc_types::{MappedMemoryRange, MemoryDevice},
+};
+
+/// Byte written over freed sub-allocations of mapped memory objects
+/// when `poison` feature is enabled.
+pub const POISON_BYTE: u8 = 0xDD;
+
+/// Fills freed block with [`POISON_BYTE`] if its memory object is persistently mapped.
+///
+/// Range is flushed for non-coherent memory so that device observes the pattern too.
+///
+/// # Safety
+///
+/// `ptr` must point to mapped range of `size` bytes at `offset` in `memory`.
+/// For non-coherent memory `offset` and `size` must be aligned to non-coherent atom size.
+pub(crate) unsafe fn poison<M>(
+    device: &impl MemoryDevice<M>,
+    memory: &M,
+    ptr: Option<NonNull<u8>>,
+    offset: u64,
+    size: u64,
+    non_coherent: bool,
+) {
+    let ptr = match ptr {
+        Some(ptr) => ptr,
+        None => return,
+    };
+
+    core::ptr::write_bytes(ptr.as_ptr(), POISON_BYTE, size as usize);
+
+    if non_coherent {
+        let result = device.flush_memory_ranges(&[MappedMemoryRange {
+            memory,
+            offset,
+            size,
+        }]);
+
+        #[cfg(feature = "tracing")]
+        if result.is_err() {
+            tracing::warn!("Failed to flush poisoned memory block");
+        }
+
+        #[cfg(not(feature = "tracing"))]
+        let _ = result;
+    }
+}
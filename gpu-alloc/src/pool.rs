@@ -0,0 +1,140 @@
+use {
+    crate::{
+        buddy::BuddyAllocator,
+        config::Config,
+        freelist::FreeListAllocator,
+        ring::RingAllocator,
+        stats::{StrategyStats, TypeStats},
+        util::Table,
+        MemoryBounds,
+    },
+    core::fmt,
+};
+
+/// Identifier of memory pool created with [`GpuAllocator::create_pool`].
+///
+/// [`GpuAllocator::create_pool`]: crate::GpuAllocator::create_pool
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PoolId(pub(crate) u32);
+
+impl fmt::Display for PoolId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "pool {}", self.0)
+    }
+}
+
+/// Configuration of memory pool.
+///
+/// Blocks of the pool are sub-allocated from chunks owned by the pool,
+/// isolated from chunks of other pools and of the allocator itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PoolConfig {
+    /// Thresholds and chunk sizes of the pool.
+    ///
+    /// Small BAR policy is shared by all pools and uses configuration of the allocator.
+    pub config: Config,
+
+    /// Bitset of memory types the pool may allocate from,
+    /// intersected with `Request::memory_types` of each request.
+    pub memory_types: u32,
+
+    /// Maximal number of bytes occupied by live blocks of the pool.
+    /// Requests beyond it fail with [`AllocationError::OutOfBudget`].
+    ///
+    /// [`AllocationError::OutOfBudget`]: crate::AllocationError::OutOfBudget
+    pub budget: Option<u64>,
+}
+
+impl PoolConfig {
+    /// Returns pool configuration with all memory types allowed and no budget.
+    pub fn new(config: Config) -> Self {
+        PoolConfig {
+            config,
+            memory_types: !0,
+            budget: None,
+        }
+    }
+}
+
+/// Strategy allocators and their configuration,
+/// owned either by the allocator or by a pool.
+#[derive(Debug)]
+pub(crate) struct Strategies<M> {
+    pub dedicated_threshold: u64,
+    pub preferred_dedicated_threshold: u64,
+    pub transient_dedicated_threshold: u64,
+    pub starting_free_list_chunk: u64,
+    pub final_free_list_chunk: u64,
+    pub minimal_buddy_size: u64,
+    pub initial_buddy_dedicated_size: u64,
+    pub buddy_chunk_keep_alive: u32,
+    pub force_dedicated: bool,
+
+    pub buddy_allocators: Table<Option<BuddyAllocator<M>>>,
+    pub freelist_allocators: Table<Option<FreeListAllocator<M>>>,
+    pub ring_allocators: Table<Option<RingAllocator<M>>>,
+}
+
+impl<M> Strategies<M> {
+    pub fn new(config: &Config, memory_types: usize) -> Self {
+        Strategies {
+            dedicated_threshold: config.dedicated_threshold,
+            preferred_dedicated_threshold: config
+                .preferred_dedicated_threshold
+                .min(config.dedicated_threshold),
+            transient_dedicated_threshold: config
+                .transient_dedicated_threshold
+                .max(config.dedicated_threshold),
+            starting_free_list_chunk: config.starting_free_list_chunk,
+            final_free_list_chunk: config.final_free_list_chunk,
+            minimal_buddy_size: config.minimal_buddy_size,
+            initial_buddy_dedicated_size: config.initial_buddy_dedicated_size,
+            buddy_chunk_keep_alive: config.buddy_chunk_keep_alive,
+            force_dedicated: config.force_dedicated,
+
+            buddy_allocators: (0..memory_types).map(|_| None).collect(),
+            freelist_allocators: (0..memory_types).map(|_| None).collect(),
+            ring_allocators: (0..memory_types).map(|_| None).collect(),
+        }
+    }
+}
+
+impl<M> Strategies<M>
+where
+    M: MemoryBounds + 'static,
+{
+    /// Adds memory object counters of strategy allocators for `memory_type` to `stats`.
+    pub fn add_memory_stats(&self, memory_type: usize, stats: &mut TypeStats) {
+        let mut counters = StrategyStats::default();
+        if let Some(buddy) = &self.buddy_allocators[memory_type] {
+            buddy.memory_stats(&mut counters);
+            add_memory_objects(&mut stats.buddy, &counters);
+        }
+        if let Some(freelist) = &self.freelist_allocators[memory_type] {
+            freelist.memory_stats(&mut counters);
+            add_memory_objects(&mut stats.free_list, &counters);
+        }
+        if let Some(ring) = &self.ring_allocators[memory_type] {
+            ring.memory_stats(&mut counters);
+            add_memory_objects(&mut stats.ring, &counters);
+        }
+    }
+}
+
+fn add_memory_objects(stats: &mut StrategyStats, counters: &StrategyStats) {
+    stats.memory_objects += counters.memory_objects;
+    stats.memory_bytes += counters.memory_bytes;
+}
+
+/// Memory pool with its own strategy allocators.
+#[derive(Debug)]
+pub(crate) struct Pool<M> {
+    pub strategies: Strategies<M>,
+    pub memory_types: u32,
+    pub budget: Option<u64>,
+    /// Bytes occupied by live blocks.
+    pub used: u64,
+    pub blocks: usize,
+}
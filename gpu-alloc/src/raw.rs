@@ -0,0 +1,334 @@
+use {
+    crate::{
+        align_down, allocator::valid_align_mask, buddy, error::AllocationError, freelist,
+        heap::Heap, MemoryBounds,
+    },
+    core::ptr::NonNull,
+    gpu_alloc_types::{AllocationFlags, MemoryDevice, MemoryPropertyFlags},
+};
+
+/// Buddy sub-allocator used by [`GpuAllocator`] for general purpose blocks,
+/// usable standalone to build custom allocation policy.
+///
+/// Serves blocks of power of two sizes from chunks of single memory type,
+/// splitting chunks in halves recursively and merging freed halves back.
+/// Memory of host-visible chunks is mapped persistently.
+///
+/// Unlike [`GpuAllocator`] it does not track heap budget or memory object limit.
+/// [`BuddyAllocator::cleanup`] must be called before dropping,
+/// otherwise leftover memory objects are reported.
+///
+/// Available with `raw-allocators` feature.
+///
+/// [`GpuAllocator`]: crate::GpuAllocator
+#[derive(Debug)]
+pub struct BuddyAllocator<M> {
+    inner: buddy::BuddyAllocator<M>,
+    heap: Heap,
+    allocations_remains: u32,
+}
+
+/// Memory block allocated by [`BuddyAllocator`].
+#[derive(Debug)]
+pub struct BuddyBlock<M> {
+    inner: buddy::BuddyBlock<M>,
+}
+
+impl<M> BuddyBlock<M> {
+    /// Returns reference to parent memory object.
+    #[inline(always)]
+    pub fn memory(&self) -> &M {
+        &self.inner.memory
+    }
+
+    /// Returns offset in bytes from start of memory object to start of this block.
+    #[inline(always)]
+    pub fn offset(&self) -> u64 {
+        self.inner.offset
+    }
+
+    /// Returns size of this memory block.
+    #[inline(always)]
+    pub fn size(&self) -> u64 {
+        self.inner.size
+    }
+
+    /// Returns pointer to mapped memory of this block,
+    /// if memory type is host-visible.
+    #[inline(always)]
+    pub fn ptr(&self) -> Option<NonNull<u8>> {
+        self.inner.ptr
+    }
+}
+
+impl<M> BuddyAllocator<M>
+where
+    M: MemoryBounds + 'static,
+{
+    /// Creates buddy allocator for memory type with index `memory_type` and properties `props`.
+    ///
+    /// Blocks are at least `minimal_size` bytes.
+    /// Chunks are `chunk_size` bytes and grow when larger block is requested.
+    /// Blocks are aligned to `atom_mask`, which should be non-coherent atom size minus one
+    /// for host-visible non-coherent memory and zero otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `minimal_size` or `chunk_size` is not power of two.
+    pub fn new(
+        minimal_size: u64,
+        chunk_size: u64,
+        memory_type: u32,
+        props: MemoryPropertyFlags,
+        atom_mask: u64,
+    ) -> Self {
+        BuddyAllocator {
+            inner: buddy::BuddyAllocator::new(
+                minimal_size,
+                chunk_size,
+                memory_type,
+                props,
+                atom_mask,
+                0,
+            ),
+            heap: Heap::new(u64::MAX, u64::MAX),
+            allocations_remains: u32::MAX,
+        }
+    }
+
+    /// Allocates block of at least `size` bytes aligned to `align_mask`.
+    ///
+    /// # Safety
+    ///
+    /// Same `device` instance must be used for all interactions with one `BuddyAllocator` instance
+    /// and memory blocks allocated from it.
+    pub unsafe fn alloc(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        size: u64,
+        align_mask: u64,
+        flags: AllocationFlags,
+    ) -> Result<BuddyBlock<M>, AllocationError> {
+        if !valid_align_mask(align_mask) {
+            return Err(AllocationError::InvalidAlignment);
+        }
+
+        let inner = self.inner.alloc(
+            device,
+            size,
+            align_mask,
+            flags,
+            &mut self.heap,
+            &mut self.allocations_remains,
+            None,
+        )?;
+
+        Ok(BuddyBlock { inner })
+    }
+
+    /// Deallocates block, destroying its chunk if it becomes empty.
+    ///
+    /// # Safety
+    ///
+    /// * `block` must have been allocated from this allocator.
+    /// * `device` must be the one used to allocate `block`.
+    /// * The caller must guarantee that device does not access the block anymore.
+    pub unsafe fn dealloc(&mut self, device: &impl MemoryDevice<M>, block: BuddyBlock<M>) {
+        self.inner.dealloc(
+            device,
+            block.inner,
+            &mut self.heap,
+            &mut self.allocations_remains,
+            None,
+        )
+    }
+
+    /// Returns number of bytes in memory objects allocated by this allocator.
+    pub fn memory_bytes(&self) -> u64 {
+        self.heap.used()
+    }
+
+    /// Deallocates leftover memory objects.
+    ///
+    /// # Safety
+    ///
+    /// `device` must be the one used to allocate blocks.
+    pub unsafe fn cleanup(&mut self, device: &impl MemoryDevice<M>) {
+        self.inner
+            .cleanup(device, &mut self.heap, &mut self.allocations_remains, None)
+    }
+}
+
+/// Free-list sub-allocator used by [`GpuAllocator`] for transient blocks,
+/// usable standalone to build custom allocation policy.
+///
+/// Carves blocks of any size from chunks of single memory type linearly,
+/// reusing freed ranges and merging adjacent ones.
+/// Chunk sizes double from starting to final size as chunks are allocated.
+/// Memory of host-visible chunks is mapped persistently.
+///
+/// Unlike [`GpuAllocator`] it does not track heap budget or memory object limit.
+/// [`LinearAllocator::cleanup`] must be called before dropping,
+/// otherwise leftover memory objects are reported.
+///
+/// Available with `raw-allocators` feature.
+///
+/// [`GpuAllocator`]: crate::GpuAllocator
+#[derive(Debug)]
+pub struct LinearAllocator<M> {
+    inner: freelist::FreeListAllocator<M>,
+    final_chunk_size: u64,
+    heap: Heap,
+    allocations_remains: u32,
+}
+
+/// Memory block allocated by [`LinearAllocator`].
+#[derive(Debug)]
+pub struct LinearBlock<M> {
+    inner: freelist::FreeListBlock<M>,
+}
+
+impl<M> LinearBlock<M> {
+    /// Returns reference to parent memory object.
+    #[inline(always)]
+    pub fn memory(&self) -> &M {
+        &self.inner.memory
+    }
+
+    /// Returns offset in bytes from start of memory object to start of this block.
+    #[inline(always)]
+    pub fn offset(&self) -> u64 {
+        self.inner.offset
+    }
+
+    /// Returns size of this memory block.
+    #[inline(always)]
+    pub fn size(&self) -> u64 {
+        self.inner.size
+    }
+
+    /// Returns pointer to mapped memory of this block,
+    /// if memory type is host-visible.
+    #[inline(always)]
+    pub fn ptr(&self) -> Option<NonNull<u8>> {
+        self.inner.ptr
+    }
+}
+
+impl<M> LinearAllocator<M>
+where
+    M: MemoryBounds + 'static,
+{
+    /// Creates free-list allocator for memory type with index `memory_type` and properties `props`.
+    ///
+    /// Chunks start at `starting_chunk_size` bytes and grow up to `final_chunk_size` bytes.
+    /// Blocks are aligned to `atom_mask`, which should be non-coherent atom size minus one
+    /// for host-visible non-coherent memory and zero otherwise.
+    ///
+    /// # Panics
+    ///
+    /// Panics if chunk sizes are not multiples of `atom_mask + 1`
+    /// or `starting_chunk_size` is zero.
+    pub fn new(
+        starting_chunk_size: u64,
+        final_chunk_size: u64,
+        memory_type: u32,
+        props: MemoryPropertyFlags,
+        atom_mask: u64,
+    ) -> Self {
+        assert_ne!(starting_chunk_size, 0, "Chunk size must not be zero");
+        assert_eq!(
+            align_down(starting_chunk_size, atom_mask),
+            starting_chunk_size,
+            "Chunk size must be multiple of atom size"
+        );
+        assert_eq!(
+            align_down(final_chunk_size, atom_mask),
+            final_chunk_size,
+            "Chunk size must be multiple of atom size"
+        );
+
+        let final_chunk_size = final_chunk_size.max(starting_chunk_size);
+
+        LinearAllocator {
+            inner: freelist::FreeListAllocator::new(
+                starting_chunk_size,
+                final_chunk_size,
+                memory_type,
+                props,
+                atom_mask,
+            ),
+            final_chunk_size,
+            heap: Heap::new(u64::MAX, u64::MAX),
+            allocations_remains: u32::MAX,
+        }
+    }
+
+    /// Allocates block of at least `size` bytes aligned to `align_mask`.
+    ///
+    /// Returns `AllocationError::OutOfDeviceMemory` if `size` exceeds final chunk size.
+    ///
+    /// # Safety
+    ///
+    /// Same `device` instance must be used for all interactions with one `LinearAllocator` instance
+    /// and memory blocks allocated from it.
+    pub unsafe fn alloc(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        size: u64,
+        align_mask: u64,
+        flags: AllocationFlags,
+    ) -> Result<LinearBlock<M>, AllocationError> {
+        if !valid_align_mask(align_mask) {
+            return Err(AllocationError::InvalidAlignment);
+        }
+
+        if size > self.final_chunk_size {
+            return Err(AllocationError::OutOfDeviceMemory);
+        }
+
+        let inner = self.inner.alloc(
+            device,
+            size.max(1),
+            align_mask,
+            flags,
+            &mut self.heap,
+            &mut self.allocations_remains,
+            None,
+        )?;
+
+        Ok(LinearBlock { inner })
+    }
+
+    /// Deallocates block, destroying its chunk if it becomes empty and is not the newest one.
+    ///
+    /// # Safety
+    ///
+    /// * `block` must have been allocated from this allocator.
+    /// * `device` must be the one used to allocate `block`.
+    /// * The caller must guarantee that device does not access the block anymore.
+    pub unsafe fn dealloc(&mut self, device: &impl MemoryDevice<M>, block: LinearBlock<M>) {
+        self.inner.dealloc(
+            device,
+            block.inner,
+            &mut self.heap,
+            &mut self.allocations_remains,
+            None,
+        )
+    }
+
+    /// Returns number of bytes in memory objects allocated by this allocator.
+    pub fn memory_bytes(&self) -> u64 {
+        self.heap.used()
+    }
+
+    /// Deallocates leftover memory objects.
+    ///
+    /// # Safety
+    ///
+    /// `device` must be the one used to allocate blocks.
+    pub unsafe fn cleanup(&mut self, device: &impl MemoryDevice<M>) {
+        self.inner
+            .cleanup(device, &mut self.heap, &mut self.allocations_remains, None)
+    }
+}
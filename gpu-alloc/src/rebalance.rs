@@ -0,0 +1,26 @@
+/// Suggested move of live block to another memory type,
+/// returned by [`GpuAllocator::plan_rebalance`].
+///
+/// Allocator does not move anything by itself.
+/// Caller allocates new block from `to` memory type, copies content,
+/// rebinds resources and deallocates old block.
+///
+/// [`GpuAllocator::plan_rebalance`]: crate::GpuAllocator::plan_rebalance
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct BlockMove {
+    /// Id of the block to move.
+    /// Matches [`MemoryBlock::id`].
+    ///
+    /// [`MemoryBlock::id`]: crate::MemoryBlock::id
+    pub id: usize,
+
+    /// Memory type the block is allocated from.
+    pub from: u32,
+
+    /// Memory type allowed by original request to allocate the block from instead.
+    pub to: u32,
+
+    /// Size of the block.
+    pub size: u64,
+}
@@ -0,0 +1,160 @@
+use {
+    crate::{dump::ChunkKind, pool::PoolId, slab::Slab, usage::UsageFlags, util::Generation},
+    gpu_alloc_types::ResourceKind,
+};
+
+/// Memory object a live block belongs to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum ChunkId {
+    /// Block owns its memory object.
+    Dedicated,
+
+    /// Index of chunk in buddy allocator.
+    Buddy(usize),
+
+    /// Id of chunk in free-list allocator.
+    FreeList(u64),
+
+    /// Id of chunk in ring allocator.
+    Ring(u64),
+}
+
+impl ChunkId {
+    /// Returns name of the strategy that owns the chunk.
+    #[cfg(feature = "tracing")]
+    pub fn strategy(&self) -> &'static str {
+        match self {
+            ChunkId::Dedicated => "dedicated",
+            ChunkId::Buddy(_) => "buddy",
+            ChunkId::FreeList(_) => "free-list",
+            ChunkId::Ring(_) => "ring",
+        }
+    }
+
+    /// Returns strategy that owns the chunk.
+    pub fn kind(&self) -> ChunkKind {
+        match self {
+            ChunkId::Dedicated => ChunkKind::Dedicated,
+            ChunkId::Buddy(_) => ChunkKind::Buddy,
+            ChunkId::FreeList(_) => ChunkKind::FreeList,
+            ChunkId::Ring(_) => ChunkKind::Ring,
+        }
+    }
+
+    /// Returns id of the chunk. Dedicated blocks use block id.
+    pub fn id(&self, block: usize) -> u64 {
+        match *self {
+            ChunkId::Dedicated => block as u64,
+            ChunkId::Buddy(chunk) => chunk as u64,
+            ChunkId::FreeList(chunk) => chunk,
+            ChunkId::Ring(chunk) => chunk,
+        }
+    }
+}
+
+/// Bookkeeping of single live block.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct BlockRecord {
+    pub memory_type: u32,
+    pub chunk: ChunkId,
+    pub offset: u64,
+    pub size: u64,
+    pub requested_size: u64,
+    pub align_mask: u64,
+    pub usage: UsageFlags,
+    pub memory_types: u32,
+    pub resource: ResourceKind,
+    pub tag: Option<&'static str>,
+    pub pool: Option<PoolId>,
+    #[cfg(feature = "std")]
+    pub created: crate::time::Instant,
+    #[cfg(feature = "track-caller")]
+    pub location: &'static core::panic::Location<'static>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    generation: Generation,
+    record: BlockRecord,
+}
+
+/// Registry of all live blocks of an allocator.
+///
+/// Block ids are reused after deallocation,
+/// so each registration also gets unique generation
+/// that tells apart blocks that occupied the same id.
+#[derive(Debug)]
+pub(crate) struct Registry {
+    blocks: Slab<Entry>,
+    next_generation: Generation,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry {
+            blocks: Slab::new(),
+            next_generation: 0,
+        }
+    }
+
+    /// Registers live block and returns its id and generation.
+    pub fn insert(&mut self, record: BlockRecord) -> (usize, Generation) {
+        let generation = self.next_generation;
+        self.next_generation = self.next_generation.wrapping_add(1);
+        (self.blocks.insert(Entry { generation, record }), generation)
+    }
+
+    /// Unregisters live block.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `id` and `generation` do not belong to live block,
+    /// which happens when block is deallocated twice
+    /// or was allocated by another allocator.
+    pub fn remove(&mut self, id: usize, generation: Generation) -> BlockRecord {
+        match self.blocks.try_get(id) {
+            Some(entry) if entry.generation == generation => self.blocks.remove(id).record,
+            _ => panic!(
+                "Memory block {} of generation {} is not allocated. It was either deallocated already or allocated by another allocator",
+                id, generation
+            ),
+        }
+    }
+
+    /// Returns record of live block.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `id` and `generation` do not belong to live block.
+    pub fn get(&self, id: usize, generation: Generation) -> &BlockRecord {
+        match self.blocks.try_get(id) {
+            Some(entry) if entry.generation == generation => &entry.record,
+            _ => panic!(
+                "Memory block {} of generation {} is not allocated by this allocator",
+                id, generation
+            ),
+        }
+    }
+
+    /// Returns record of the block if it is live.
+    pub fn try_get(&self, id: usize, generation: Generation) -> Option<&BlockRecord> {
+        match self.blocks.try_get(id) {
+            Some(entry) if entry.generation == generation => Some(&entry.record),
+            _ => None,
+        }
+    }
+
+    /// Returns number of live blocks.
+    #[cfg(feature = "std")]
+    pub fn len(&self) -> usize {
+        self.blocks.occupied()
+    }
+
+    /// Returns iterator over ids and records of live blocks.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &BlockRecord)> + '_ {
+        self.blocks
+            .entries()
+            .enumerate()
+            .filter_map(|(id, entry)| Some((id, &entry.ok()?.record)))
+    }
+}
@@ -0,0 +1,127 @@
+use {alloc::vec::Vec, core::fmt};
+
+/// Summary of memory held by allocator returned by [`GpuAllocator::report`].
+///
+/// Unlike [`AllocatorStats`] it is flat, one row per heap and memory type,
+/// and includes free space measures, so it answers where memory of a heap goes
+/// when budget is exceeded.
+///
+/// [`GpuAllocator::report`]: crate::GpuAllocator::report
+/// [`AllocatorStats`]: crate::AllocatorStats
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct AllocatorReport {
+    /// Counters per memory heap.
+    pub heaps: Vec<HeapReport>,
+
+    /// Counters per memory type.
+    pub types: Vec<TypeReport>,
+
+    /// Number of live memory objects allocated from device.
+    pub memory_objects: u64,
+
+    /// Number of memory objects that can be allocated before device limit is reached.
+    pub remaining_allocations: u32,
+}
+
+/// Counters of single memory heap in [`AllocatorReport`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct HeapReport {
+    /// Index of the heap.
+    pub index: u32,
+
+    /// Size of the heap.
+    pub size: u64,
+
+    /// Bytes of memory objects allocated from device.
+    pub allocated_bytes: u64,
+
+    /// Bytes occupied by live blocks.
+    pub used_bytes: u64,
+
+    /// Number of live blocks.
+    pub blocks: u64,
+
+    /// Number of chunks of sub-allocators.
+    pub chunks: u64,
+
+    /// Number of memory objects allocated from device, chunks and dedicated ones.
+    pub memory_objects: u64,
+
+    /// Size of the largest free region among memory types of the heap.
+    pub largest_free_region: u64,
+}
+
+/// Counters of single memory type in [`AllocatorReport`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct TypeReport {
+    /// Index of the memory type.
+    pub index: u32,
+
+    /// Index of the heap this memory type belongs to.
+    pub heap: u32,
+
+    /// Bytes of memory objects allocated from device.
+    pub allocated_bytes: u64,
+
+    /// Bytes occupied by live blocks.
+    pub used_bytes: u64,
+
+    /// Number of live blocks.
+    pub blocks: u64,
+
+    /// Number of chunks of sub-allocators.
+    pub chunks: u64,
+
+    /// Number of memory objects allocated from device, chunks and dedicated ones.
+    pub memory_objects: u64,
+
+    /// Size of the largest free region in chunks of sub-allocators.
+    pub largest_free_region: u64,
+}
+
+impl fmt::Display for AllocatorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} memory objects, {} more can be allocated",
+            self.memory_objects, self.remaining_allocations
+        )?;
+
+        for heap in &self.heaps {
+            writeln!(
+                f,
+                "heap {}: {} of {} bytes allocated, {} used by {} blocks, {} memory objects ({} chunks), largest free region {}",
+                heap.index,
+                heap.allocated_bytes,
+                heap.size,
+                heap.used_bytes,
+                heap.blocks,
+                heap.memory_objects,
+                heap.chunks,
+                heap.largest_free_region,
+            )?;
+
+            for memory_type in self.types.iter().filter(|t| t.heap == heap.index) {
+                writeln!(
+                    f,
+                    "  type {}: {} bytes allocated, {} used by {} blocks, {} memory objects ({} chunks), largest free region {}",
+                    memory_type.index,
+                    memory_type.allocated_bytes,
+                    memory_type.used_bytes,
+                    memory_type.blocks,
+                    memory_type.memory_objects,
+                    memory_type.chunks,
+                    memory_type.largest_free_region,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
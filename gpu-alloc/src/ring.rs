@@ -0,0 +1,536 @@
+use {
+    crate::{
+        align_up,
+        dump::ChunkKind,
+        error::AllocationError,
+        heap::Heap,
+        observer::{self, Observer},
+        stats::StrategyStats,
+        util::{arc_unwrap, host_addressable, is_arc_unique},
+        MemoryBounds,
+    },
+    alloc::{collections::VecDeque, sync::Arc, vec::Vec},
+    core::{fmt, ptr::NonNull},
+    gpu_alloc_types::{AllocationFlags, DeviceMapError, MemoryDevice, MemoryPropertyFlags},
+};
+
+/// Memory object ring buffer is carved from.
+#[derive(Debug)]
+struct RingChunk<M> {
+    id: u64,
+    memory: Arc<M>,
+    ptr: Option<NonNull<u8>>,
+    size: u64,
+}
+
+/// Contiguous range of chunk used by blocks of single frame.
+#[derive(Debug)]
+struct Region {
+    frame: u64,
+    chunk: u64,
+    start: u64,
+    end: u64,
+    blocks: u32,
+}
+
+#[derive(Debug)]
+pub(crate) struct RingBlock<M> {
+    pub memory: Arc<M>,
+    pub ptr: Option<NonNull<u8>>,
+    pub chunk: u64,
+    pub offset: u64,
+    pub size: u64,
+}
+
+unsafe impl<M> Sync for RingBlock<M> where M: Sync {}
+unsafe impl<M> Send for RingBlock<M> where M: Send {}
+
+/// Allocator for per-frame transient blocks.
+///
+/// Blocks are carved from the newest chunk one after another, wrapping around its end.
+/// Space is reclaimed in FIFO order when frames are released,
+/// so short-lived data never fragments the chunk.
+/// When the newest chunk is full a larger one is created,
+/// older chunks are destroyed once all their frames are released.
+#[derive(Debug)]
+pub(crate) struct RingAllocator<M> {
+    /// Chunks sorted by id, the last one is current.
+    chunks: Vec<RingChunk<M>>,
+    /// Regions in allocation order.
+    regions: VecDeque<Region>,
+    chunk_size: u64,
+    final_chunk_size: u64,
+    memory_type: u32,
+    props: MemoryPropertyFlags,
+    atom_mask: u64,
+    counter: u64,
+
+    total_allocations: u64,
+    total_deallocations: u64,
+
+    memory_objects: u64,
+    memory_bytes: u64,
+}
+
+unsafe impl<M> Sync for RingAllocator<M> where M: Sync {}
+unsafe impl<M> Send for RingAllocator<M> where M: Send {}
+
+impl<M> Drop for RingAllocator<M> {
+    fn drop(&mut self) {
+        if self.total_allocations != self.total_deallocations {
+            report_error_on_drop!("Not all blocks were deallocated")
+        }
+
+        if !self.chunks.is_empty() {
+            report_error_on_drop!(
+                "RingAllocator has chunks on drop. Frames should be released and allocator should be cleaned"
+            );
+        }
+    }
+}
+
+impl<M> RingAllocator<M>
+where
+    M: MemoryBounds + 'static,
+{
+    pub fn new(
+        starting_chunk_size: u64,
+        final_chunk_size: u64,
+        memory_type: u32,
+        props: MemoryPropertyFlags,
+        atom_mask: u64,
+    ) -> Self {
+        RingAllocator {
+            chunks: Vec::new(),
+            regions: VecDeque::new(),
+            chunk_size: starting_chunk_size,
+            final_chunk_size: final_chunk_size.max(starting_chunk_size),
+            memory_type,
+            props,
+            atom_mask,
+            counter: 0,
+
+            total_allocations: 0,
+            total_deallocations: 0,
+
+            memory_objects: 0,
+            memory_bytes: 0,
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self, device, observer)))]
+    pub unsafe fn alloc(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        frame: u64,
+        size: u64,
+        align_mask: u64,
+        flags: AllocationFlags,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        mut observer: Observer<'_>,
+    ) -> Result<RingBlock<M>, AllocationError> {
+        let size = align_up(size.max(1), self.atom_mask).ok_or(AllocationError::SizeOverflow)?;
+        let align_mask = align_mask | self.atom_mask;
+
+        if let Some(offset) = self.fit(size, align_mask) {
+            return Ok(self.place(frame, offset, size));
+        }
+
+        if size > self.chunk_size {
+            let multiple = (size - 1) / self.chunk_size + 1;
+            let multiple = multiple.next_power_of_two();
+
+            self.chunk_size = (self.chunk_size * multiple).min(self.final_chunk_size);
+            if size > self.chunk_size {
+                return Err(AllocationError::OutOfDeviceMemory);
+            }
+        }
+
+        self.create_chunk(
+            device,
+            self.chunk_size,
+            flags,
+            heap,
+            allocations_remains,
+            &mut observer,
+        )?;
+
+        // Previous chunk is retired and is destroyed once its frames are released.
+        self.destroy_unused(device, heap, allocations_remains, &mut observer, false);
+
+        if self.chunk_size < self.final_chunk_size {
+            self.chunk_size = (self.chunk_size * 2).min(self.final_chunk_size);
+        }
+
+        Ok(self.place(frame, 0, size))
+    }
+
+    /// Returns offset in current chunk where block of `size` bytes fits.
+    fn fit(&self, size: u64, align_mask: u64) -> Option<u64> {
+        let current = self.chunks.last()?;
+
+        // Only current chunk receives new regions, so its regions are the newest.
+        let mut regions = self
+            .regions
+            .iter()
+            .skip_while(|region| region.chunk != current.id);
+
+        let oldest = match regions.next() {
+            None => return Some(0).filter(|_| size <= current.size),
+            Some(oldest) => oldest,
+        };
+        let newest = self.regions.back().expect("Region exists");
+
+        let head = align_up(newest.end, align_mask)?;
+        let tail = oldest.start;
+
+        if newest.start >= oldest.start {
+            // Free space is after head and before tail.
+            if matches!(head.checked_add(size), Some(end) if end <= current.size) {
+                Some(head)
+            } else if size <= tail {
+                Some(0)
+            } else {
+                None
+            }
+        } else {
+            // Wrapped around, free space is between head and tail.
+            match head.checked_add(size) {
+                Some(end) if end <= tail => Some(head),
+                _ => None,
+            }
+        }
+    }
+
+    /// Carves block at `offset` of current chunk.
+    fn place(&mut self, frame: u64, offset: u64, size: u64) -> RingBlock<M> {
+        let current = self.chunks.last().expect("Current chunk exists");
+
+        match self.regions.back_mut() {
+            Some(region)
+                if region.frame == frame && region.chunk == current.id && offset >= region.end =>
+            {
+                region.end = offset + size;
+                region.blocks += 1;
+            }
+            _ => self.regions.push_back(Region {
+                frame,
+                chunk: current.id,
+                start: offset,
+                end: offset + size,
+                blocks: 1,
+            }),
+        }
+
+        self.total_allocations += 1;
+
+        RingBlock {
+            memory: current.memory.clone(),
+            ptr: current.ptr.map(|ptr| {
+                // Offset is within chunk that fits `isize`.
+                unsafe { NonNull::new_unchecked(ptr.as_ptr().add(offset as usize)) }
+            }),
+            chunk: current.id,
+            offset,
+            size,
+        }
+    }
+
+    /// Returns block to its region.
+    /// Space is reclaimed when the frame of the region is released.
+    pub fn dealloc(&mut self, block: RingBlock<M>) {
+        let region = self
+            .regions
+            .iter_mut()
+            .find(|region| {
+                region.chunk == block.chunk
+                    && region.start <= block.offset
+                    && block.offset < region.end
+            })
+            .expect("Ring block belongs to a region");
+
+        region.blocks -= 1;
+        self.total_deallocations += 1;
+    }
+
+    /// Reclaims space of frames up to `frame` inclusive, in FIFO order.
+    /// Stops at the first region that still has live blocks.
+    /// Destroys retired chunks that are no longer used.
+    pub unsafe fn release(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        frame: u64,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        mut observer: Observer<'_>,
+    ) {
+        while let Some(region) = self.regions.front() {
+            if region.frame > frame || region.blocks > 0 {
+                break;
+            }
+            self.regions.pop_front();
+        }
+
+        self.destroy_unused(device, heap, allocations_remains, &mut observer, false);
+    }
+
+    /// Destroys chunks without regions, including current one.
+    pub unsafe fn cleanup(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        mut observer: Observer<'_>,
+    ) {
+        self.destroy_unused(device, heap, allocations_remains, &mut observer, true);
+    }
+
+    /// Destroys chunks without regions, oldest first,
+    /// until at most `keep_bytes` bytes are kept in them.
+    ///
+    /// Returns number of bytes kept in chunks without regions.
+    pub unsafe fn trim(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        keep_bytes: u64,
+        mut observer: Observer<'_>,
+    ) -> u64 {
+        let regions = &self.regions;
+        let mut unused_bytes: u64 = self
+            .chunks
+            .iter_mut()
+            .filter_map(|chunk| {
+                if !regions.iter().any(|region| region.chunk == chunk.id)
+                    && is_arc_unique(&mut chunk.memory)
+                {
+                    Some(chunk.size)
+                } else {
+                    None
+                }
+            })
+            .sum();
+
+        let mut index = 0;
+        while unused_bytes > keep_bytes && index < self.chunks.len() {
+            let id = self.chunks[index].id;
+            if self.regions.iter().any(|region| region.chunk == id)
+                || !is_arc_unique(&mut self.chunks[index].memory)
+            {
+                index += 1;
+                continue;
+            }
+
+            let chunk = self.chunks.remove(index);
+            unused_bytes -= chunk.size;
+            self.destroy_chunk(device, chunk, heap, allocations_remains, &mut observer);
+        }
+
+        unused_bytes
+    }
+
+    /// Destroys chunks without regions.
+    /// Current chunk is kept for reuse unless `current` is `true`.
+    unsafe fn destroy_unused(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        observer: &mut Observer<'_>,
+        current: bool,
+    ) {
+        let keep = if current { 0 } else { 1 };
+        let mut index = 0;
+        while index + keep < self.chunks.len() {
+            let id = self.chunks[index].id;
+            if self.regions.iter().any(|region| region.chunk == id) {
+                index += 1;
+                continue;
+            }
+
+            let mut chunk = self.chunks.remove(index);
+            if !is_arc_unique(&mut chunk.memory) {
+                // Blocks of released frame are still referenced.
+                self.chunks.insert(index, chunk);
+                index += 1;
+                continue;
+            }
+
+            self.destroy_chunk(device, chunk, heap, allocations_remains, observer);
+        }
+    }
+
+    /// Allocates new memory object of `chunk_size` bytes and makes it current.
+    unsafe fn create_chunk(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        chunk_size: u64,
+        flags: AllocationFlags,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        observer: &mut Observer<'_>,
+    ) -> Result<(), AllocationError> {
+        if *allocations_remains == 0 {
+            return Err(AllocationError::TooManyObjects);
+        }
+
+        // Whole chunk is mapped and blocks are addressed by pointer offsets.
+        if self.host_visible() && !host_addressable(chunk_size) {
+            return Err(AllocationError::OutOfHostMemory);
+        }
+
+        profile_scope!("RingAllocator::create_chunk");
+
+        heap.reserve(chunk_size)?;
+
+        let mut memory = match device.allocate_memory(chunk_size, self.memory_type, flags) {
+            Ok(memory) => memory,
+            Err(err) => {
+                heap.release(chunk_size);
+                return Err(err.into());
+            }
+        };
+        *allocations_remains -= 1;
+        heap.alloc(chunk_size);
+
+        let ptr = if self.host_visible() {
+            match device.map_memory(&mut memory, 0, chunk_size) {
+                Ok(ptr) => Some(ptr),
+                Err(err) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::error!("Failed to map host-visible memory in ring allocator");
+                    device.deallocate_memory(memory);
+                    *allocations_remains += 1;
+                    heap.dealloc(chunk_size);
+
+                    return Err(match err {
+                        DeviceMapError::OutOfDeviceMemory => AllocationError::OutOfDeviceMemory,
+                        DeviceMapError::MapFailed | DeviceMapError::OutOfHostMemory => {
+                            AllocationError::OutOfHostMemory
+                        }
+                    });
+                }
+            }
+        } else {
+            None
+        };
+
+        self.counter += 1;
+        self.memory_objects += 1;
+        self.memory_bytes += chunk_size;
+
+        self.chunks.push(RingChunk {
+            id: self.counter,
+            memory: Arc::new(memory),
+            ptr,
+            size: chunk_size,
+        });
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            memory_type = self.memory_type,
+            strategy = "ring",
+            chunk = self.counter,
+            size = chunk_size,
+            "chunk created"
+        );
+
+        observer::chunk_created(
+            observer,
+            self.memory_type,
+            ChunkKind::Ring,
+            self.counter,
+            chunk_size,
+        );
+
+        Ok(())
+    }
+
+    unsafe fn destroy_chunk(
+        &mut self,
+        device: &impl MemoryDevice<M>,
+        chunk: RingChunk<M>,
+        heap: &mut Heap,
+        allocations_remains: &mut u32,
+        observer: &mut Observer<'_>,
+    ) {
+        profile_scope!("RingAllocator::destroy_chunk");
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            memory_type = self.memory_type,
+            strategy = "ring",
+            chunk = chunk.id,
+            size = chunk.size,
+            "chunk destroyed"
+        );
+
+        device.deallocate_memory(arc_unwrap(chunk.memory));
+        *allocations_remains += 1;
+        heap.dealloc(chunk.size);
+        observer::chunk_destroyed(
+            observer,
+            self.memory_type,
+            ChunkKind::Ring,
+            chunk.id,
+            chunk.size,
+        );
+        self.memory_objects -= 1;
+        self.memory_bytes -= chunk.size;
+    }
+
+    /// Fills memory object counters of `stats`.
+    pub(crate) fn memory_stats(&self, stats: &mut StrategyStats) {
+        stats.memory_objects = self.memory_objects;
+        stats.memory_bytes = self.memory_bytes;
+    }
+
+    /// Returns iterator over ids and sizes of chunks.
+    pub(crate) fn chunks(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.chunks.iter().map(|chunk| (chunk.id, chunk.size))
+    }
+
+    fn host_visible(&self) -> bool {
+        self.props.contains(MemoryPropertyFlags::HOST_VISIBLE)
+    }
+}
+
+impl<M> RingAllocator<M>
+where
+    M: MemoryBounds,
+{
+    /// Writes deterministic description of allocator state.
+    pub(crate) fn write_debug_state(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "ring allocator for memory type {}: chunk size {}, final chunk size {}, atom mask {}",
+            self.memory_type, self.chunk_size, self.final_chunk_size, self.atom_mask
+        )?;
+        writeln!(
+            f,
+            "  allocations {}, deallocations {}, chunks created {}",
+            self.total_allocations, self.total_deallocations, self.counter
+        )?;
+
+        for chunk in &self.chunks {
+            writeln!(
+                f,
+                "  chunk {}: memory {:?}, size {}",
+                chunk.id, chunk.memory, chunk.size
+            )?;
+        }
+
+        for region in &self.regions {
+            writeln!(
+                f,
+                "  region: frame {}, chunk {}, {}..{}, blocks {}",
+                region.frame, region.chunk, region.start, region.end, region.blocks
+            )?;
+        }
+
+        Ok(())
+    }
+}
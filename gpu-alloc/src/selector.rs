@@ -0,0 +1,89 @@
+use {crate::Request, core::fmt, gpu_alloc_types::MemoryPropertyFlags};
+
+/// Memory type that can serve allocation request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct MemoryTypeCandidate {
+    /// Index of memory type.
+    pub memory_type: u32,
+
+    /// Properties of memory type.
+    pub props: MemoryPropertyFlags,
+
+    /// Index of memory heap of the memory type.
+    pub heap: u32,
+
+    /// Size of the heap.
+    pub heap_size: u64,
+
+    /// Bytes allocated from the heap.
+    pub heap_used: u64,
+}
+
+/// Custom placement policy that can veto or re-rank memory types per request.
+///
+/// Set with [`GpuAllocator::set_memory_type_selector`].
+/// Implemented for closures with matching signature.
+///
+/// [`GpuAllocator::set_memory_type_selector`]: crate::GpuAllocator::set_memory_type_selector
+pub trait MemoryTypeSelector: Send + Sync {
+    /// Returns rank of memory type `candidate` for the `request` or `None` to veto it.
+    ///
+    /// Candidates are offered in default order of preference
+    /// and tried in ascending order of rank.
+    /// Candidates of equal rank keep default order.
+    fn rank(&mut self, request: &Request, candidate: &MemoryTypeCandidate) -> Option<u32>;
+}
+
+impl<F> MemoryTypeSelector for F
+where
+    F: FnMut(&Request, &MemoryTypeCandidate) -> Option<u32> + Send + Sync,
+{
+    fn rank(&mut self, request: &Request, candidate: &MemoryTypeCandidate) -> Option<u32> {
+        self(request, candidate)
+    }
+}
+
+/// Memory types chosen for request, in order they are tried.
+pub(crate) struct Selection {
+    /// Rank, position in default order and index of memory type.
+    types: [(u32, u32, u32); 32],
+    len: usize,
+}
+
+impl Selection {
+    pub fn new() -> Self {
+        Selection {
+            types: [(0, 0, 0); 32],
+            len: 0,
+        }
+    }
+
+    pub fn push(&mut self, rank: u32, memory_type: u32) {
+        self.types[self.len] = (rank, self.len as u32, memory_type);
+        self.len += 1;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns memory types sorted by rank.
+    pub fn sorted(&mut self) -> impl Iterator<Item = u32> + '_ {
+        self.types[..self.len].sort_unstable();
+        self.types[..self.len].iter().map(|&(_, _, index)| index)
+    }
+}
+
+/// Slot for selector that implements `Debug`.
+#[derive(Default)]
+pub(crate) struct SelectorSlot(pub Option<alloc::boxed::Box<dyn MemoryTypeSelector>>);
+
+impl fmt::Debug for SelectorSlot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(_) => f.write_str("Some(MemoryTypeSelector)"),
+            None => f.write_str("None"),
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use {
+    crate::{
+        AllocationError, Dedicated, GpuAllocator, MapError, MemoryBlock, MemoryBounds, Request,
+    },
+    alloc::sync::Arc,
+    core::{fmt, mem::ManuallyDrop, ops::Deref, ptr::NonNull},
+    gpu_alloc_types::{DeviceRef, MemoryDevice},
+    std::sync::{Mutex, MutexGuard},
+};
+
+#[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+use {
+    crate::{TransientAllocator, TransientBlock, UsageFlags},
+    gpu_alloc_types::OutOfMemory,
+};
+
+struct Shared<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    allocator: Mutex<GpuAllocator<M>>,
+    device: D,
+}
+
+impl<M, D> Shared<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    fn lock(&self) -> MutexGuard<'_, GpuAllocator<M>> {
+        // Allocator state is consistent between calls, even if locking thread panicked.
+        match self.allocator.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl<M, D> Drop for Shared<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    fn drop(&mut self) {
+        let allocator = match self.allocator.get_mut() {
+            Ok(allocator) => allocator,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        // Device is the one allocator was created for, as required by `SharedAllocator::new`.
+        unsafe { allocator.cleanup(&DeviceRef::new(&self.device)) }
+    }
+}
+
+/// Allocator shared between threads that owns both [`GpuAllocator`] and the device
+/// and returns memory blocks that deallocate themselves on drop.
+///
+/// Memory objects are released when the last clone of `SharedAllocator`
+/// and the last [`GpuMemory`] allocated from it are dropped.
+pub struct SharedAllocator<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    shared: Arc<Shared<M, D>>,
+}
+
+impl<M, D> Clone for SharedAllocator<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    fn clone(&self) -> Self {
+        SharedAllocator {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<M, D> fmt::Debug for SharedAllocator<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedAllocator")
+            .field("allocator", &*self.shared.lock())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<M, D> SharedAllocator<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    /// Wraps allocator and device.
+    ///
+    /// # Safety
+    ///
+    /// * `device` must be one with `DeviceProperties` that were provided to create `allocator`.
+    /// * `allocator` must have no live memory blocks allocated from another device instance.
+    pub unsafe fn new(allocator: GpuAllocator<M>, device: D) -> Self {
+        SharedAllocator {
+            shared: Arc::new(Shared {
+                allocator: Mutex::new(allocator),
+                device,
+            }),
+        }
+    }
+
+    /// Returns the device.
+    pub fn device(&self) -> &D {
+        &self.shared.device
+    }
+
+    /// Locks and returns the allocator, for example to query its statistics.
+    ///
+    /// Blocks can't be allocated or deallocated by other threads while the guard is held.
+    pub fn allocator(&self) -> MutexGuard<'_, GpuAllocator<M>> {
+        self.shared.lock()
+    }
+
+    /// Allocates memory block according to the `request`.
+    /// Same as [`GpuAllocator::alloc`].
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub fn alloc(&self, request: Request) -> Result<GpuMemory<M, D>, AllocationError> {
+        let device = DeviceRef::new(&self.shared.device);
+        let block = unsafe { self.shared.lock().alloc(&device, request) }?;
+        Ok(self.wrap(block))
+    }
+
+    /// Allocates memory block according to the `request` with forced allocation strategy.
+    /// Same as [`GpuAllocator::alloc_with_dedicated`].
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub fn alloc_with_dedicated(
+        &self,
+        request: Request,
+        dedicated: Dedicated,
+    ) -> Result<GpuMemory<M, D>, AllocationError> {
+        let device = DeviceRef::new(&self.shared.device);
+        let block = unsafe {
+            self.shared
+                .lock()
+                .alloc_with_dedicated(&device, request, dedicated)
+        }?;
+        Ok(self.wrap(block))
+    }
+
+    /// Allocates memory block according to the `request` and marks it with `tag`.
+    /// Same as [`GpuAllocator::alloc_tagged`].
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub fn alloc_tagged(
+        &self,
+        request: Request,
+        tag: &'static str,
+    ) -> Result<GpuMemory<M, D>, AllocationError> {
+        let device = DeviceRef::new(&self.shared.device);
+        let block = unsafe { self.shared.lock().alloc_tagged(&device, request, tag) }?;
+        Ok(self.wrap(block))
+    }
+
+    /// Creates linear allocator of transient blocks for single thread,
+    /// for example one per command recording thread.
+    ///
+    /// Scope allocates chunks of `chunk_size` bytes from memory types in `memory_types` mask
+    /// suitable for `usage`, see [`TransientAllocator::new`].
+    /// No memory is allocated until first block is requested.
+    #[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+    pub fn scope(
+        &self,
+        chunk_size: u64,
+        align_mask: u64,
+        usage: UsageFlags,
+        memory_types: u32,
+    ) -> AllocScope<M, D> {
+        AllocScope {
+            transient: ManuallyDrop::new(TransientAllocator::new(
+                chunk_size,
+                align_mask,
+                usage,
+                memory_types,
+            )),
+            shared: self.shared.clone(),
+        }
+    }
+
+    fn wrap(&self, block: MemoryBlock<M>) -> GpuMemory<M, D> {
+        GpuMemory {
+            block: ManuallyDrop::new(block),
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+/// Linear allocator of transient blocks owned by single thread,
+/// created with [`SharedAllocator::scope`].
+///
+/// Blocks are carved from chunk owned by the scope, so shared allocator
+/// is locked only to replace exhausted chunk and threads recording commands concurrently
+/// do not contend for it.
+/// Chunks are handed back to shared allocator by [`AllocScope::reset`]
+/// and when scope is dropped, so device must finish all commands that access
+/// blocks of the scope before that.
+#[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+pub struct AllocScope<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    transient: ManuallyDrop<TransientAllocator<M>>,
+    shared: Arc<Shared<M, D>>,
+}
+
+#[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+impl<M, D> AllocScope<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    /// Allocates block of `size` bytes,
+    /// replacing exhausted chunk with new one from shared allocator if needed.
+    ///
+    /// Blocks larger than chunk size are never allocated,
+    /// such requests fail with [`AllocationError::SizeOverflow`].
+    #[cfg_attr(feature = "track-caller", track_caller)]
+    pub fn alloc(&mut self, size: u64) -> Result<TransientBlock<'_, M>, AllocationError> {
+        if !self.transient.fits_current(size) {
+            if !self.transient.fits_chunk(size) {
+                return Err(AllocationError::SizeOverflow);
+            }
+
+            let device = DeviceRef::new(&self.shared.device);
+            unsafe { self.transient.refill(&mut self.shared.lock(), &device) }?;
+        }
+
+        Ok(self
+            .transient
+            .alloc(size)
+            .expect("Block must fit into the rest of the chunk"))
+    }
+
+    /// Flushes memory written to blocks of this scope
+    /// if it was allocated without `HOST_COHERENT` property.
+    /// Same as [`TransientAllocator::flush`].
+    pub fn flush(&self) -> Result<(), OutOfMemory> {
+        unsafe { self.transient.flush(&DeviceRef::new(&self.shared.device)) }
+    }
+
+    /// Frees all blocks allocated from this scope.
+    /// Exhausted chunks are handed back to shared allocator, current chunk is kept for reuse.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that device finished all commands that access allocated blocks.
+    pub unsafe fn reset(&mut self) {
+        let device = DeviceRef::new(&self.shared.device);
+        self.transient.reset(&mut self.shared.lock(), &device)
+    }
+}
+
+#[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+impl<M, D> fmt::Debug for AllocScope<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AllocScope")
+            .field("transient", &*self.transient)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+impl<M, D> Drop for AllocScope<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    fn drop(&mut self) {
+        // Transient allocator is taken exactly once, here.
+        let transient = unsafe { ManuallyDrop::take(&mut self.transient) };
+        let device = DeviceRef::new(&self.shared.device);
+        unsafe { transient.dispose(&mut self.shared.lock(), &device) }
+    }
+}
+
+/// Memory block allocated from [`SharedAllocator`]
+/// that is deallocated when dropped.
+///
+/// Dereferences to [`MemoryBlock`].
+pub struct GpuMemory<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    block: ManuallyDrop<MemoryBlock<M>>,
+    shared: Arc<Shared<M, D>>,
+}
+
+impl<M, D> GpuMemory<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    /// Returns the device this memory was allocated from.
+    pub fn device(&self) -> &D {
+        &self.shared.device
+    }
+
+    /// Maps memory range of this block.
+    /// Same as [`MemoryBlock::map`].
+    ///
+    /// # Safety
+    ///
+    /// Same as for [`MemoryBlock::map`] except that device is always correct.
+    pub unsafe fn map(&mut self, offset: u64, size: usize) -> Result<NonNull<u8>, MapError> {
+        self.block
+            .map(&DeviceRef::new(&self.shared.device), offset, size)
+    }
+
+    /// Unmaps memory range of this block.
+    /// Same as [`MemoryBlock::unmap`].
+    ///
+    /// # Safety
+    ///
+    /// Same as for [`MemoryBlock::unmap`] except that device is always correct.
+    pub unsafe fn unmap(&mut self) -> bool {
+        self.block.unmap(&DeviceRef::new(&self.shared.device))
+    }
+
+    /// Copies specified data to memory range of this block.
+    /// Same as [`MemoryBlock::write_bytes`].
+    ///
+    /// # Safety
+    ///
+    /// Same as for [`MemoryBlock::write_bytes`] except that device is always correct.
+    pub unsafe fn write_bytes(&mut self, offset: u64, data: &[u8]) -> Result<(), MapError> {
+        self.block
+            .write_bytes(&DeviceRef::new(&self.shared.device), offset, data)
+    }
+
+    /// Copies memory range of this block to specified buffer.
+    /// Same as [`MemoryBlock::read_bytes`].
+    ///
+    /// # Safety
+    ///
+    /// Same as for [`MemoryBlock::read_bytes`] except that device is always correct.
+    pub unsafe fn read_bytes(&mut self, offset: u64, data: &mut [u8]) -> Result<(), MapError> {
+        self.block
+            .read_bytes(&DeviceRef::new(&self.shared.device), offset, data)
+    }
+}
+
+impl<M, D> Deref for GpuMemory<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    type Target = MemoryBlock<M>;
+
+    fn deref(&self) -> &MemoryBlock<M> {
+        &self.block
+    }
+}
+
+impl<M, D> fmt::Debug for GpuMemory<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GpuMemory")
+            .field("block", &*self.block)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<M, D> Drop for GpuMemory<M, D>
+where
+    M: MemoryBounds + 'static,
+    D: MemoryDevice<M>,
+{
+    fn drop(&mut self) {
+        // Block is taken exactly once, here.
+        let block = unsafe { ManuallyDrop::take(&mut self.block) };
+        let device = DeviceRef::new(&self.shared.device);
+        unsafe { self.shared.lock().dealloc(&device, block) }
+    }
+}
@@ -8,6 +8,7 @@ enum Entry<T> {
 #[derive(Debug)]
 pub(crate) struct Slab<T> {
     next_vacant: usize,
+    occupied: usize,
     entries: Vec<Entry<T>>,
 }
 
@@ -15,6 +16,7 @@ impl<T> Slab<T> {
     pub fn new() -> Self {
         Slab {
             next_vacant: !0,
+            occupied: 0,
             entries: Vec::new(),
         }
     }
@@ -22,6 +24,7 @@ impl<T> Slab<T> {
     /// Inserts value into this linked vec and returns index
     /// at which value can be accessed in constant time.
     pub fn insert(&mut self, value: T) -> usize {
+        self.occupied += 1;
         if self.next_vacant >= self.entries.len() {
             self.entries.push(Entry::Occupied(value));
             self.entries.len() - 1
@@ -42,6 +45,11 @@ impl<T> Slab<T> {
         self.entries.len()
     }
 
+    /// Returns number of occupied entries.
+    pub fn occupied(&self) -> usize {
+        self.occupied
+    }
+
     pub unsafe fn get_unchecked(&self, index: usize) -> &T {
         debug_assert!(index < self.len());
 
@@ -67,6 +75,13 @@ impl<T> Slab<T> {
         }
     }
 
+    pub fn try_get(&self, index: usize) -> Option<&T> {
+        match self.entries.get(index) {
+            Some(Entry::Occupied(value)) => Some(value),
+            _ => None,
+        }
+    }
+
     pub fn get_mut(&mut self, index: usize) -> &mut T {
         match self.entries.get_mut(index) {
             Some(Entry::Occupied(value)) => value,
@@ -81,6 +96,7 @@ impl<T> Slab<T> {
         );
 
         self.next_vacant = index;
+        self.occupied -= 1;
 
         match entry {
             Entry::Occupied(value) => value,
@@ -88,6 +104,19 @@ impl<T> Slab<T> {
         }
     }
 
+    /// Returns iterator over all entries in index order.
+    /// Vacant entries are returned as index of the next vacant entry.
+    pub fn entries(&self) -> impl Iterator<Item = Result<&T, usize>> + '_ {
+        self.entries.iter().map(|entry| match entry {
+            Entry::Occupied(value) => Ok(value),
+            Entry::Vacant(next_vacant) => Err(*next_vacant),
+        })
+    }
+
+    pub fn next_vacant(&self) -> usize {
+        self.next_vacant
+    }
+
     pub fn remove(&mut self, index: usize) -> T {
         match self.entries.get_mut(index) {
             Some(Entry::Occupied(_)) => unsafe { self.remove_unchecked(index) },
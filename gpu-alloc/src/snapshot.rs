@@ -0,0 +1,51 @@
+use {
+    crate::{dump::ChunkKind, usage::UsageFlags},
+    alloc::vec::Vec,
+    gpu_alloc_types::ResourceKind,
+};
+
+/// Logical layout of live blocks returned by [`GpuAllocator::snapshot`].
+///
+/// Captures what is needed to allocate equivalent blocks again
+/// after all device memory was released, for example when mobile application is suspended.
+///
+/// [`GpuAllocator::snapshot`]: crate::GpuAllocator::snapshot
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct AllocatorSnapshot {
+    /// Live blocks ordered by memory type, strategy, memory object and offset.
+    pub blocks: Vec<SnapshotBlock>,
+}
+
+/// Single block in [`AllocatorSnapshot`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct SnapshotBlock {
+    /// Id of the block at the moment of snapshot.
+    /// Matches [`MemoryBlock::id`].
+    ///
+    /// [`MemoryBlock::id`]: crate::MemoryBlock::id
+    pub id: usize,
+
+    /// Memory type of the block.
+    pub memory_type: u32,
+
+    /// Strategy that allocated the block.
+    pub kind: ChunkKind,
+
+    /// Size requested for the block.
+    pub size: u64,
+
+    /// Alignment mask requested for the block.
+    pub align_mask: u64,
+
+    /// Usage requested for the block.
+    /// Empty for imported blocks.
+    pub usage: UsageFlags,
+
+    /// Kind of resource the block was requested for.
+    pub resource: ResourceKind,
+
+    /// Tag of the block.
+    pub tag: Option<&'static str>,
+}
@@ -0,0 +1,893 @@
+use {
+    crate::atomic::{AtomicU64, Relaxed},
+    alloc::{borrow::Cow, vec::Vec},
+    core::fmt,
+};
+
+#[cfg(feature = "std")]
+use core::time::Duration;
+
+/// Statistics of blocks and memory objects of single allocation strategy.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct StrategyStats {
+    /// Number of live memory blocks.
+    pub blocks: u64,
+
+    /// Sum of sizes requested for live memory blocks.
+    pub requested_bytes: u64,
+
+    /// Sum of sizes of live memory blocks.
+    /// Can be larger than `requested_bytes` due to alignment and size rounding.
+    pub allocated_bytes: u64,
+
+    /// Number of memory objects allocated from device.
+    /// For dedicated strategy this is equal to `blocks`,
+    /// for sub-allocators this is number of chunks.
+    pub memory_objects: u64,
+
+    /// Sum of sizes of memory objects allocated from device.
+    pub memory_bytes: u64,
+}
+
+impl StrategyStats {
+    /// Returns bytes lost to alignment padding and size rounding of live blocks.
+    pub fn rounding_bytes(&self) -> u64 {
+        self.allocated_bytes.saturating_sub(self.requested_bytes)
+    }
+
+    /// Returns bytes of memory objects not occupied by live blocks.
+    pub fn slack_bytes(&self) -> u64 {
+        self.memory_bytes.saturating_sub(self.allocated_bytes)
+    }
+
+    /// Returns ratio of requested bytes to bytes of memory objects allocated from device.
+    /// One means that no memory is lost to rounding, padding and chunk slack.
+    ///
+    /// Returns one if no memory is allocated.
+    pub fn efficiency(&self) -> f64 {
+        if self.memory_bytes == 0 {
+            1.0
+        } else {
+            self.requested_bytes as f64 / self.memory_bytes as f64
+        }
+    }
+
+    pub(crate) fn alloc(&mut self, requested: u64, size: u64) {
+        self.blocks += 1;
+        self.requested_bytes += requested;
+        self.allocated_bytes += size;
+    }
+
+    pub(crate) fn dealloc(&mut self, requested: u64, size: u64) {
+        self.blocks -= 1;
+        self.requested_bytes -= requested;
+        self.allocated_bytes -= size;
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.blocks += other.blocks;
+        self.requested_bytes += other.requested_bytes;
+        self.allocated_bytes += other.allocated_bytes;
+        self.memory_objects += other.memory_objects;
+        self.memory_bytes += other.memory_bytes;
+    }
+}
+
+/// Counters of dedicated blocks allocated through shared reference.
+/// Merged into [`TypeStats::dedicated`] when statistics are gathered.
+#[derive(Debug, Default)]
+pub(crate) struct ConcurrentStats {
+    blocks: AtomicU64,
+    bytes: AtomicU64,
+}
+
+impl ConcurrentStats {
+    #[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+    pub(crate) fn alloc(&self, size: u64) {
+        self.blocks.fetch_add(1, Relaxed);
+        self.bytes.fetch_add(size, Relaxed);
+    }
+
+    #[cfg(any(target_has_atomic = "64", feature = "portable-atomic"))]
+    pub(crate) fn dealloc(&self, size: u64) {
+        self.blocks.fetch_sub(1, Relaxed);
+        self.bytes.fetch_sub(size, Relaxed);
+    }
+
+    pub(crate) fn add_to(&self, stats: &mut StrategyStats) {
+        let blocks = self.blocks.load(Relaxed);
+        let bytes = self.bytes.load(Relaxed);
+        stats.add(&StrategyStats {
+            blocks,
+            requested_bytes: bytes,
+            allocated_bytes: bytes,
+            memory_objects: blocks,
+            memory_bytes: bytes,
+        });
+    }
+}
+
+/// Statistics of single memory type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct TypeStats {
+    /// Index of the heap this memory type belongs to.
+    pub heap: u32,
+
+    /// Blocks allocated as dedicated memory objects,
+    /// including imported memory and blocks allocated through shared reference.
+    pub dedicated: StrategyStats,
+
+    /// Blocks allocated by buddy allocator.
+    pub buddy: StrategyStats,
+
+    /// Blocks allocated by free-list allocator.
+    pub free_list: StrategyStats,
+
+    /// Blocks allocated by ring allocator during frames.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ring: StrategyStats,
+
+    /// Number of buddy chunks created no later than one `GpuAllocator::cleanup` call
+    /// after chunk of the same size was released.
+    ///
+    /// Growing value suggests increasing [`Config::buddy_chunk_keep_alive`].
+    ///
+    /// [`Config::buddy_chunk_keep_alive`]: crate::Config::buddy_chunk_keep_alive
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub buddy_chunk_oscillations: u64,
+}
+
+impl TypeStats {
+    /// Returns statistics summed over all strategies.
+    pub fn total(&self) -> StrategyStats {
+        let mut total = self.dedicated;
+        total.add(&self.buddy);
+        total.add(&self.free_list);
+        total.add(&self.ring);
+        total
+    }
+}
+
+/// Statistics of single memory heap summed over its memory types.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct HeapStats {
+    /// Size of the heap.
+    pub size: u64,
+
+    /// Number of bytes allocator may allocate from the heap.
+    ///
+    /// Equals `size` unless budget is reported by device properties
+    /// or set with [`GpuAllocator::set_heap_budget`].
+    ///
+    /// [`GpuAllocator::set_heap_budget`]: crate::GpuAllocator::set_heap_budget
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub budget: u64,
+
+    /// Number of bytes allocated from device in this heap.
+    pub used: u64,
+
+    /// Maximum of `used` since allocator creation.
+    pub peak_used: u64,
+
+    /// Maximum of `used` since last [`GpuAllocator::reset_watermarks`] call.
+    ///
+    /// [`GpuAllocator::reset_watermarks`]: crate::GpuAllocator::reset_watermarks
+    pub watermark_used: u64,
+
+    /// Blocks allocated as dedicated memory objects, including imported memory.
+    pub dedicated: StrategyStats,
+
+    /// Blocks allocated by buddy allocator.
+    pub buddy: StrategyStats,
+
+    /// Blocks allocated by free-list allocator.
+    pub free_list: StrategyStats,
+
+    /// Blocks allocated by ring allocator during frames.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub ring: StrategyStats,
+}
+
+impl HeapStats {
+    /// Returns statistics summed over all strategies.
+    pub fn total(&self) -> StrategyStats {
+        let mut total = self.dedicated;
+        total.add(&self.buddy);
+        total.add(&self.free_list);
+        total.add(&self.ring);
+        total
+    }
+
+    pub(crate) fn add(&mut self, stats: &TypeStats) {
+        self.dedicated.add(&stats.dedicated);
+        self.buddy.add(&stats.buddy);
+        self.free_list.add(&stats.free_list);
+        self.ring.add(&stats.ring);
+    }
+}
+
+/// Disagreement between usage counter of memory heap
+/// and memory objects alive in that heap,
+/// returned by [`GpuAllocator::reconcile`].
+///
+/// [`GpuAllocator::reconcile`]: crate::GpuAllocator::reconcile
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct AccountingDrift {
+    /// Index of memory heap.
+    pub heap: u32,
+
+    /// Bytes used according to heap counter.
+    pub accounted: u64,
+
+    /// Bytes in chunks and dedicated memory objects of the heap.
+    pub derived: u64,
+}
+
+impl fmt::Display for AccountingDrift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "heap {} accounts {} bytes used, but live memory objects sum up to {} bytes",
+            self.heap, self.accounted, self.derived
+        )
+    }
+}
+
+/// Statistics report of [`GpuAllocator`] returned by [`GpuAllocator::stats`].
+///
+/// [`GpuAllocator`]: crate::GpuAllocator
+/// [`GpuAllocator::stats`]: crate::GpuAllocator::stats
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct AllocatorStats {
+    /// Statistics per memory heap.
+    pub heaps: Vec<HeapStats>,
+
+    /// Statistics per memory type.
+    pub types: Vec<TypeStats>,
+
+    /// Statistics per tag, sorted by tag.
+    /// Only blocks allocated with tag are accounted here.
+    pub tags: Vec<TagStats>,
+}
+
+/// Statistics of blocks allocated with the same tag.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct TagStats {
+    /// Tag of the blocks.
+    pub tag: Cow<'static, str>,
+
+    /// Number of live blocks with this tag.
+    pub blocks: u64,
+
+    /// Sum of sizes of live blocks with this tag.
+    pub bytes: u64,
+
+    /// Maximum of `bytes` since allocator creation.
+    pub peak_bytes: u64,
+
+    /// Maximum of `bytes` since last [`GpuAllocator::reset_watermarks`] call.
+    ///
+    /// [`GpuAllocator::reset_watermarks`]: crate::GpuAllocator::reset_watermarks
+    pub watermark_bytes: u64,
+}
+
+/// Live blocks with the same tag, allocation call site and memory type
+/// in [`LeakReport`].
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct LiveBlocks {
+    /// Tag of the blocks, `None` for untagged blocks.
+    pub tag: Option<&'static str>,
+
+    /// Call site, recorded with `track-caller` feature.
+    pub location: Option<&'static core::panic::Location<'static>>,
+
+    /// Memory type of the blocks.
+    pub memory_type: u32,
+
+    /// Number of live blocks.
+    pub blocks: u64,
+
+    /// Sum of sizes of live blocks.
+    pub bytes: u64,
+
+    /// Size of the smallest block.
+    pub min_size: u64,
+
+    /// Size of the largest block.
+    pub max_size: u64,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for LiveBlocks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.tag.unwrap_or("<untagged>"))?;
+        if let Some(location) = self.location {
+            write!(f, " at {}", location)?;
+        }
+        write!(
+            f,
+            ": memory type {}, {} blocks, {} bytes, sizes {}..={}",
+            self.memory_type, self.blocks, self.bytes, self.min_size, self.max_size
+        )
+    }
+}
+
+/// Live blocks grouped by tag returned by [`GpuAllocator::leak_report`].
+///
+/// Its `Display` implementation is the report printed when allocator is dropped with live blocks.
+///
+/// [`GpuAllocator::leak_report`]: crate::GpuAllocator::leak_report
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct LeakReport {
+    /// Number of live blocks.
+    pub blocks: u64,
+
+    /// Sum of sizes of live blocks.
+    pub bytes: u64,
+
+    /// Live blocks grouped by tag, allocation call site and memory type, largest groups first.
+    pub groups: Vec<LiveBlocks>,
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for LeakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} live blocks, {} bytes total:",
+            self.blocks, self.bytes
+        )?;
+        for group in &self.groups {
+            write!(f, "\n  {}", group)?;
+        }
+        Ok(())
+    }
+}
+
+/// Ages of live blocks returned by [`GpuAllocator::block_ages`].
+///
+/// Age of a block is time passed since it was allocated.
+///
+/// [`GpuAllocator::block_ages`]: crate::GpuAllocator::block_ages
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct BlockAges {
+    /// Number of live blocks.
+    pub blocks: u64,
+
+    /// Median age of live blocks.
+    pub p50: Duration,
+
+    /// 90th percentile of live block ages.
+    pub p90: Duration,
+
+    /// 99th percentile of live block ages.
+    pub p99: Duration,
+
+    /// Age of the oldest live block.
+    pub max: Duration,
+
+    /// Oldest live blocks, oldest first.
+    pub oldest: Vec<BlockAge>,
+}
+
+/// Age of single live block in [`BlockAges`].
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct BlockAge {
+    /// Time passed since the block was allocated.
+    pub age: Duration,
+
+    /// Memory type of the block.
+    pub memory_type: u32,
+
+    /// Size of the block.
+    pub size: u64,
+
+    /// Tag of the block.
+    pub tag: Option<&'static str>,
+}
+
+#[cfg(feature = "std")]
+impl BlockAges {
+    /// Computes percentiles of `ages` and keeps `oldest` of them.
+    pub(crate) fn new(mut ages: Vec<BlockAge>, oldest: usize) -> Self {
+        if ages.is_empty() {
+            return BlockAges::default();
+        }
+
+        ages.sort_unstable_by_key(|age| core::cmp::Reverse(age.age));
+
+        // Nearest-rank percentile over ages sorted from the oldest.
+        let percentile = |p: usize| ages[(ages.len() * (100 - p) / 100).min(ages.len() - 1)].age;
+
+        BlockAges {
+            blocks: ages.len() as u64,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max: ages[0].age,
+            oldest: ages.into_iter().take(oldest).collect(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for BlockAges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ages of {} live blocks: p50 {:?}, p90 {:?}, p99 {:?}, max {:?}",
+            self.blocks, self.p50, self.p90, self.p99, self.max
+        )?;
+
+        for block in &self.oldest {
+            write!(f, "\n  {}", block)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for BlockAge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: memory type {}, {} bytes, allocated {:?} ago",
+            self.tag.unwrap_or("<untagged>"),
+            self.memory_type,
+            self.size,
+            self.age
+        )
+    }
+}
+
+/// Usage counters of single tag kept by allocator.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct TagUsage {
+    blocks: u64,
+    bytes: u64,
+    peak_bytes: u64,
+    watermark_bytes: u64,
+}
+
+impl TagUsage {
+    pub(crate) fn alloc(&mut self, size: u64) {
+        self.blocks += 1;
+        self.bytes += size;
+        self.peak_bytes = self.peak_bytes.max(self.bytes);
+        self.watermark_bytes = self.watermark_bytes.max(self.bytes);
+    }
+
+    pub(crate) fn dealloc(&mut self, size: u64) {
+        self.blocks -= 1;
+        self.bytes -= size;
+    }
+
+    pub(crate) fn reset_watermark(&mut self) {
+        self.watermark_bytes = self.bytes;
+    }
+
+    pub(crate) fn blocks(&self) -> u64 {
+        self.blocks
+    }
+
+    pub(crate) fn stats(&self, tag: &'static str) -> TagStats {
+        TagStats {
+            tag: Cow::Borrowed(tag),
+            blocks: self.blocks,
+            bytes: self.bytes,
+            peak_bytes: self.peak_bytes,
+            watermark_bytes: self.watermark_bytes,
+        }
+    }
+}
+
+/// Fragmentation measures of single memory type
+/// returned by [`GpuAllocator::fragmentation`].
+///
+/// Only memory of buddy and free-list allocators can be fragmented,
+/// dedicated allocations contribute only to `unusable_bytes`.
+///
+/// [`GpuAllocator::fragmentation`]: crate::GpuAllocator::fragmentation
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct FragmentationStats {
+    /// Number of bytes in memory objects of sub-allocators
+    /// that are not occupied by any block.
+    pub free_bytes: u64,
+
+    /// Size of the largest free contiguous region.
+    /// Sub-allocators can serve requests up to this size
+    /// without allocating new memory object.
+    pub largest_free_region: u64,
+
+    /// Size of the smallest buddy allocator size class.
+    pub buddy_minimal_size: u64,
+
+    /// Number of free blocks in buddy allocator per size class.
+    /// Element with index `i` counts free blocks of size `buddy_minimal_size << i`.
+    pub buddy_free_blocks: Vec<u64>,
+
+    /// Number of memory objects of buddy allocator,
+    /// including emptied chunk kept alive.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub buddy_chunks: u64,
+
+    /// Number of bytes in live blocks that are not usable
+    /// because requested sizes were rounded up.
+    pub unusable_bytes: u64,
+
+    /// Part of `unusable_bytes` lost to rounding sizes of buddy blocks up to power of two.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub buddy_rounding_bytes: u64,
+
+    /// Number of free regions per log2 size class.
+    /// Element with index `i` counts free regions of size in `2^i..2^(i+1)`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub free_region_histogram: Vec<u64>,
+}
+
+impl FragmentationStats {
+    /// Returns ratio of free bytes that are not in the largest free region.
+    /// Zero means that all free space is contiguous.
+    pub fn ratio(&self) -> f64 {
+        if self.free_bytes == 0 {
+            0.0
+        } else {
+            1.0 - self.largest_free_region as f64 / self.free_bytes as f64
+        }
+    }
+
+    /// Returns block size and number of free blocks of each buddy allocator size class,
+    /// from the smallest to the largest.
+    ///
+    /// Shows whether free buddy memory can serve request of particular size
+    /// or is split into smaller blocks.
+    pub fn buddy_size_classes(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        let minimal_size = self.buddy_minimal_size;
+        self.buddy_free_blocks
+            .iter()
+            .enumerate()
+            .map(move |(index, &count)| (minimal_size << index, count))
+    }
+
+    /// Returns lower bound of region sizes and number of free regions of each histogram bucket,
+    /// from the smallest to the largest.
+    pub fn free_region_buckets(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.free_region_histogram
+            .iter()
+            .enumerate()
+            .map(|(index, &count)| (1 << index, count))
+    }
+
+    /// Accounts `count` free regions of `size` bytes.
+    pub(crate) fn free_region(&mut self, size: u64, count: u64) {
+        if count == 0 {
+            return;
+        }
+
+        self.free_bytes += size * count;
+        self.largest_free_region = self.largest_free_region.max(size);
+
+        let bucket = (63 - size.leading_zeros()) as usize;
+        if self.free_region_histogram.len() <= bucket {
+            self.free_region_histogram.resize(bucket + 1, 0);
+        }
+        self.free_region_histogram[bucket] += count;
+    }
+
+    /// Adds free space measures of `other` sub-allocators,
+    /// rebasing buddy size classes onto the smaller minimal size.
+    pub(crate) fn merge(&mut self, other: &FragmentationStats) {
+        self.free_bytes += other.free_bytes;
+        self.largest_free_region = self.largest_free_region.max(other.largest_free_region);
+        self.buddy_chunks += other.buddy_chunks;
+
+        if self.free_region_histogram.len() < other.free_region_histogram.len() {
+            self.free_region_histogram
+                .resize(other.free_region_histogram.len(), 0);
+        }
+        for (bucket, &count) in other.free_region_histogram.iter().enumerate() {
+            self.free_region_histogram[bucket] += count;
+        }
+
+        if other.buddy_free_blocks.is_empty() {
+            return;
+        }
+
+        if other.buddy_minimal_size < self.buddy_minimal_size {
+            let shift = (self.buddy_minimal_size.trailing_zeros()
+                - other.buddy_minimal_size.trailing_zeros()) as usize;
+            if !self.buddy_free_blocks.is_empty() {
+                self.buddy_free_blocks.splice(0..0, (0..shift).map(|_| 0));
+            }
+            self.buddy_minimal_size = other.buddy_minimal_size;
+        }
+
+        let shift = (other.buddy_minimal_size.trailing_zeros()
+            - self.buddy_minimal_size.trailing_zeros()) as usize;
+        for (index, &count) in other.buddy_free_blocks.iter().enumerate() {
+            let class = index + shift;
+            if self.buddy_free_blocks.len() <= class {
+                self.buddy_free_blocks.resize(class + 1, 0);
+            }
+            self.buddy_free_blocks[class] += count;
+        }
+    }
+}
+
+/// Number of buckets in [`SizeHistogram`].
+#[cfg(feature = "stats")]
+pub const SIZE_HISTOGRAM_BUCKETS: usize = 64;
+
+/// Histogram of request sizes with log2 buckets.
+///
+/// Bucket with index `i` counts requests with size in `(2^(i-1), 2^i]`,
+/// bucket `0` counts requests of size `1` or less
+/// and the last bucket counts all requests larger than `2^62`.
+#[cfg(feature = "stats")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SizeHistogram {
+    buckets: [u64; SIZE_HISTOGRAM_BUCKETS],
+}
+
+#[cfg(feature = "stats")]
+impl Default for SizeHistogram {
+    fn default() -> Self {
+        SizeHistogram {
+            buckets: [0; SIZE_HISTOGRAM_BUCKETS],
+        }
+    }
+}
+
+#[cfg(feature = "stats")]
+impl SizeHistogram {
+    /// Returns index of the bucket for request of specified `size`.
+    pub fn bucket(size: u64) -> usize {
+        let index = 64 - size.saturating_sub(1).leading_zeros() as usize;
+        index.min(SIZE_HISTOGRAM_BUCKETS - 1)
+    }
+
+    /// Returns inclusive upper bound of request sizes counted in bucket with specified `index`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `index` is not less than [`SIZE_HISTOGRAM_BUCKETS`].
+    pub fn bucket_upper_bound(index: usize) -> u64 {
+        assert!(index < SIZE_HISTOGRAM_BUCKETS, "Invalid bucket index");
+        if index == SIZE_HISTOGRAM_BUCKETS - 1 {
+            u64::MAX
+        } else {
+            1 << index
+        }
+    }
+
+    /// Returns number of requests per bucket.
+    pub fn buckets(&self) -> &[u64; SIZE_HISTOGRAM_BUCKETS] {
+        &self.buckets
+    }
+
+    /// Returns total number of recorded requests.
+    pub fn total(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    pub(crate) fn record(&mut self, size: u64) {
+        self.buckets[Self::bucket(size)] += 1;
+    }
+}
+
+/// Number of buckets in [`LatencyHistogram`].
+#[cfg(all(feature = "stats", feature = "std"))]
+pub const LATENCY_HISTOGRAM_BUCKETS: usize = 32;
+
+/// Histogram of durations with log2 buckets of microseconds.
+///
+/// Bucket with index `i` counts durations in `[2^(i-1), 2^i)` microseconds,
+/// bucket `0` counts durations shorter than one microsecond
+/// and the last bucket counts all durations of `2^30` microseconds or longer.
+#[cfg(all(feature = "stats", feature = "std"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LatencyHistogram {
+    buckets: [u64; LATENCY_HISTOGRAM_BUCKETS],
+    max: Duration,
+}
+
+#[cfg(all(feature = "stats", feature = "std"))]
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        LatencyHistogram {
+            buckets: [0; LATENCY_HISTOGRAM_BUCKETS],
+            max: Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(all(feature = "stats", feature = "std"))]
+impl LatencyHistogram {
+    /// Returns index of the bucket for specified `duration`.
+    pub fn bucket(duration: Duration) -> usize {
+        let micros: u64 =
+            core::convert::TryFrom::try_from(duration.as_micros()).unwrap_or(u64::MAX);
+        let index = 64 - micros.leading_zeros() as usize;
+        index.min(LATENCY_HISTOGRAM_BUCKETS - 1)
+    }
+
+    /// Returns exclusive upper bound of durations counted in bucket with specified `index`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `index` is not less than [`LATENCY_HISTOGRAM_BUCKETS`].
+    pub fn bucket_upper_bound(index: usize) -> Duration {
+        assert!(index < LATENCY_HISTOGRAM_BUCKETS, "Invalid bucket index");
+        if index == LATENCY_HISTOGRAM_BUCKETS - 1 {
+            Duration::MAX
+        } else {
+            Duration::from_micros(1 << index)
+        }
+    }
+
+    /// Returns number of recorded durations per bucket.
+    pub fn buckets(&self) -> &[u64; LATENCY_HISTOGRAM_BUCKETS] {
+        &self.buckets
+    }
+
+    /// Returns total number of recorded durations.
+    pub fn total(&self) -> u64 {
+        self.buckets.iter().sum()
+    }
+
+    /// Returns longest recorded duration.
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// Returns number of recorded durations that are certainly not shorter than `threshold`.
+    ///
+    /// Counts buckets whose lower bound is not less than `threshold`.
+    pub fn at_least(&self, threshold: Duration) -> u64 {
+        let bucket = Self::bucket(threshold);
+        let lower_bound = match bucket {
+            0 => Duration::ZERO,
+            _ => Self::bucket_upper_bound(bucket - 1),
+        };
+        let first = if lower_bound == threshold {
+            bucket
+        } else {
+            bucket + 1
+        };
+        self.buckets.iter().skip(first).sum()
+    }
+
+    pub(crate) fn record(&mut self, duration: Duration) {
+        self.buckets[Self::bucket(duration)] += 1;
+        self.max = self.max.max(duration);
+    }
+}
+
+/// Latency of [`GpuAllocator::alloc`] calls.
+///
+/// [`GpuAllocator::alloc`]: crate::GpuAllocator::alloc
+#[cfg(all(feature = "stats", feature = "std"))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct AllocLatency {
+    /// Duration of each allocation call, successful or not.
+    pub alloc: LatencyHistogram,
+
+    /// Time spent in `MemoryDevice::allocate_memory` by each allocation call that reached the device.
+    pub device: LatencyHistogram,
+
+    /// Duration of allocation calls that allocated new device memory.
+    ///
+    /// Difference from `alloc` shows which hitches correlate with chunk creation.
+    pub device_alloc: LatencyHistogram,
+}
+
+#[cfg(all(feature = "stats", feature = "std"))]
+impl AllocLatency {
+    pub(crate) fn record(&mut self, alloc: Duration, device: Option<Duration>) {
+        self.alloc.record(alloc);
+        if let Some(device) = device {
+            self.device.record(device);
+            self.device_alloc.record(alloc);
+        }
+    }
+}
+
+/// Device wrapper that measures time spent in `MemoryDevice::allocate_memory`.
+#[cfg(all(feature = "stats", feature = "std"))]
+pub(crate) struct TimedDevice<'a, D> {
+    device: &'a D,
+    elapsed: core::cell::Cell<Option<Duration>>,
+}
+
+#[cfg(all(feature = "stats", feature = "std"))]
+impl<'a, D> TimedDevice<'a, D> {
+    pub fn new(device: &'a D) -> Self {
+        TimedDevice {
+            device,
+            elapsed: core::cell::Cell::new(None),
+        }
+    }
+
+    /// Returns total time spent in `MemoryDevice::allocate_memory`
+    /// or `None` if it was never called.
+    pub fn elapsed(&self) -> Option<Duration> {
+        self.elapsed.get()
+    }
+}
+
+#[cfg(all(feature = "stats", feature = "std"))]
+impl<M, D> gpu_alloc_types::MemoryDevice<M> for TimedDevice<'_, D>
+where
+    D: gpu_alloc_types::MemoryDevice<M>,
+{
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: gpu_alloc_types::AllocationFlags,
+    ) -> Result<M, gpu_alloc_types::OutOfMemory> {
+        let start = crate::time::Instant::now();
+        let result = self.device.allocate_memory(size, memory_type, flags);
+        let elapsed = start.elapsed();
+        self.elapsed
+            .set(Some(self.elapsed.get().unwrap_or(Duration::ZERO) + elapsed));
+        result
+    }
+
+    unsafe fn deallocate_memory(&self, memory: M) {
+        self.device.deallocate_memory(memory)
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut M,
+        offset: u64,
+        size: u64,
+    ) -> Result<core::ptr::NonNull<u8>, gpu_alloc_types::DeviceMapError> {
+        self.device.map_memory(memory, offset, size)
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut M) {
+        self.device.unmap_memory(memory)
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[gpu_alloc_types::MappedMemoryRange<'_, M>],
+    ) -> Result<(), gpu_alloc_types::OutOfMemory> {
+        self.device.invalidate_memory_ranges(ranges)
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[gpu_alloc_types::MappedMemoryRange<'_, M>],
+    ) -> Result<(), gpu_alloc_types::OutOfMemory> {
+        self.device.flush_memory_ranges(ranges)
+    }
+
+    fn take_allocation_diagnostic(&self) -> Option<gpu_alloc_types::DeviceDiagnostic> {
+        self.device.take_allocation_diagnostic()
+    }
+}
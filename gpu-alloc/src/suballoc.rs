@@ -0,0 +1,97 @@
+use crate::{
+    block::MemoryBlock,
+    error::AllocationError,
+    virtual_alloc::{VirtualAllocation, VirtualAllocator},
+};
+
+/// Hands out aligned sub-ranges of single [`MemoryBlock`].
+///
+/// Intended for binding one large buffer to the block and partitioning it,
+/// for example into vertex and index ranges of many meshes.
+/// Offsets of returned ranges are relative to start of the block,
+/// which is also start of the buffer bound to it.
+/// Alignment of a range relative to memory object is not greater than alignment of the block.
+#[derive(Debug)]
+pub struct BlockSuballocator<M> {
+    block: MemoryBlock<M>,
+    ranges: VirtualAllocator,
+}
+
+impl<M> BlockSuballocator<M> {
+    /// Creates sub-allocator over the whole `block`, entirely free.
+    pub fn new(block: MemoryBlock<M>) -> Self {
+        let ranges = VirtualAllocator::new(block.size());
+        BlockSuballocator { block, ranges }
+    }
+
+    /// Returns reference to the underlying block.
+    #[inline(always)]
+    pub fn block(&self) -> &MemoryBlock<M> {
+        &self.block
+    }
+
+    /// Returns mutable reference to the underlying block,
+    /// for example to map it or write bytes to sub-range.
+    #[inline(always)]
+    pub fn block_mut(&mut self) -> &mut MemoryBlock<M> {
+        &mut self.block
+    }
+
+    /// Returns number of bytes in live sub-ranges.
+    pub fn used(&self) -> u64 {
+        self.ranges.used()
+    }
+
+    /// Returns number of live sub-ranges.
+    pub fn allocations(&self) -> u64 {
+        self.ranges.allocations()
+    }
+
+    /// Returns `true` if there are no live sub-ranges.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns size of the largest free sub-range.
+    pub fn largest_free_region(&self) -> u64 {
+        self.ranges.largest_free_region()
+    }
+
+    /// Allocates sub-range of `size` bytes with offset from start of the block aligned to `align_mask`.
+    ///
+    /// Fails with the same errors as [`VirtualAllocator::alloc`].
+    pub fn alloc(
+        &mut self,
+        size: u64,
+        align_mask: u64,
+    ) -> Result<VirtualAllocation, AllocationError> {
+        self.ranges.alloc(size, align_mask)
+    }
+
+    /// Returns sub-range to the free-list.
+    ///
+    /// # Panics
+    ///
+    /// Panics if sub-range was not allocated from this sub-allocator or is deallocated twice.
+    pub fn dealloc(&mut self, range: VirtualAllocation) {
+        self.ranges.dealloc(range)
+    }
+
+    /// Returns offset of sub-range from start of the memory object.
+    #[inline(always)]
+    pub fn memory_offset(&self, range: &VirtualAllocation) -> u64 {
+        self.block.offset() + range.offset
+    }
+
+    /// Frees all sub-ranges at once.
+    pub fn reset(&mut self) {
+        self.ranges.reset()
+    }
+
+    /// Returns the underlying block to be deallocated.
+    ///
+    /// Sub-ranges that are still live must not be used afterwards.
+    pub fn into_block(self) -> MemoryBlock<M> {
+        self.block
+    }
+}
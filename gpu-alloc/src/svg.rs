@@ -0,0 +1,171 @@
+use {
+    crate::dump::{AllocatorDump, BlockDump, ChunkDump, ChunkKind},
+    alloc::string::String,
+    core::fmt::{self, Write as _},
+};
+
+const WIDTH: u64 = 1024;
+const LABEL_WIDTH: u64 = 160;
+const ROW_HEIGHT: u64 = 18;
+const ROW_GAP: u64 = 4;
+const HEADER_HEIGHT: u64 = 24;
+const FREE_COLOR: &str = "#e8e8e8";
+
+impl AllocatorDump {
+    /// Renders chunk and block layout of every memory type into self-contained SVG image.
+    ///
+    /// Each memory object is drawn as a row scaled relatively to the largest one.
+    /// Blocks are colored by tag, untagged blocks are colored by strategy,
+    /// free regions are light gray. Hovering a block shows its offset, size and tag.
+    pub fn to_svg(&self) -> String {
+        let mut out = String::new();
+        self.write_svg(&mut out)
+            .expect("Writing to string never fails");
+        out
+    }
+
+    fn write_svg(&self, out: &mut String) -> fmt::Result {
+        let largest = self
+            .types
+            .iter()
+            .flat_map(|memory_type| &memory_type.chunks)
+            .map(|chunk| chunk.size)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+
+        let rows: u64 = self
+            .types
+            .iter()
+            .filter(|memory_type| !memory_type.chunks.is_empty())
+            .map(|memory_type| memory_type.chunks.len() as u64 + 1)
+            .sum();
+
+        let height = rows * (ROW_HEIGHT + ROW_GAP) + ROW_GAP;
+
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}" font-family="monospace" font-size="12">"#,
+            w = LABEL_WIDTH + WIDTH,
+            h = height.max(HEADER_HEIGHT),
+        )?;
+
+        let mut y = ROW_GAP;
+
+        for memory_type in &self.types {
+            if memory_type.chunks.is_empty() {
+                continue;
+            }
+
+            writeln!(
+                out,
+                r#"<text x="4" y="{}">memory type {} (heap {}, {:?})</text>"#,
+                y + ROW_HEIGHT - 5,
+                memory_type.index,
+                memory_type.heap,
+                memory_type.props,
+            )?;
+            y += ROW_HEIGHT + ROW_GAP;
+
+            for chunk in &memory_type.chunks {
+                write_chunk(out, chunk, y, largest)?;
+                y += ROW_HEIGHT + ROW_GAP;
+            }
+        }
+
+        writeln!(out, "</svg>")
+    }
+}
+
+fn write_chunk(out: &mut String, chunk: &ChunkDump, y: u64, largest: u64) -> fmt::Result {
+    let kind = match chunk.kind {
+        ChunkKind::Dedicated => "dedicated",
+        ChunkKind::Buddy => "buddy",
+        ChunkKind::FreeList => "free-list",
+        ChunkKind::Ring => "ring",
+    };
+
+    writeln!(
+        out,
+        r#"<text x="4" y="{}">{} #{}</text>"#,
+        y + ROW_HEIGHT - 5,
+        kind,
+        chunk.id
+    )?;
+
+    let scale = |bytes: u64| (u128::from(bytes) * u128::from(WIDTH) / u128::from(largest)) as u64;
+
+    writeln!(
+        out,
+        r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}" stroke="gray"><title>{} #{}: {} bytes</title></rect>"#,
+        LABEL_WIDTH,
+        y,
+        scale(chunk.size).max(1),
+        ROW_HEIGHT,
+        FREE_COLOR,
+        kind,
+        chunk.id,
+        chunk.size,
+    )?;
+
+    for block in &chunk.blocks {
+        write!(
+            out,
+            r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"><title>offset {}, size {}, requested {}"#,
+            LABEL_WIDTH + scale(block.offset),
+            y,
+            scale(block.size).max(1),
+            ROW_HEIGHT,
+            BlockColor(block, chunk.kind),
+            block.offset,
+            block.size,
+            block.requested_size,
+        )?;
+
+        if let Some(tag) = &block.tag {
+            out.push_str(", tag ");
+            write_escaped(out, tag);
+        }
+
+        writeln!(out, "</title></rect>")?;
+    }
+
+    Ok(())
+}
+
+/// Writes `text` escaping characters reserved in XML.
+fn write_escaped(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+}
+
+/// Fill color of a block.
+struct BlockColor<'a>(&'a BlockDump, ChunkKind);
+
+impl fmt::Display for BlockColor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.0.tag {
+            Some(tag) => {
+                // FNV-1a hash gives stable color per tag.
+                let hash = tag.bytes().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+                    (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+                });
+                write!(f, "hsl({}, 65%, 55%)", hash % 360)
+            }
+            None => f.write_str(match self.1 {
+                ChunkKind::Dedicated => "#4682b4",
+                ChunkKind::Buddy => "#6b8e23",
+                ChunkKind::FreeList => "#cd853f",
+                ChunkKind::Ring => "#9370db",
+            }),
+        }
+    }
+}
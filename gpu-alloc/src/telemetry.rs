@@ -0,0 +1,65 @@
+//! Publishing of allocator health through `metrics` facade.
+
+use {
+    crate::error::AllocationError,
+    alloc::string::ToString as _,
+    metrics::{counter, gauge, Key, Label, Level, Metadata},
+};
+
+/// Name of the gauge with bytes allocated from device per heap.
+pub const METRIC_HEAP_USED_BYTES: &str = "gpu_alloc_heap_used_bytes";
+
+/// Name of the gauge with number of live memory blocks.
+pub const METRIC_LIVE_BLOCKS: &str = "gpu_alloc_live_blocks";
+
+/// Name of the gauge with number of live memory objects allocated from device.
+pub const METRIC_DEVICE_ALLOCATIONS: &str = "gpu_alloc_device_allocations";
+
+/// Name of the counter of failed allocation requests.
+pub const METRIC_FAILED_ALLOCATIONS: &str = "gpu_alloc_failed_allocations_total";
+
+static METADATA: Metadata<'static> =
+    Metadata::new(module_path!(), Level::INFO, Some(module_path!()));
+
+macro_rules! heap_keys {
+    ($($index:literal $heap:literal)*) => {
+        static HEAP_LABELS: [[Label; 1]; 16] = [$([Label::from_static_parts("heap", $heap)]),*];
+
+        /// Keys of heap usage gauges for heaps that Vulkan allows,
+        /// so that publishing usage does not allocate label strings.
+        static HEAP_USED_KEYS: [Key; 16] =
+            [$(Key::from_static_parts(METRIC_HEAP_USED_BYTES, &HEAP_LABELS[$index])),*];
+    };
+}
+
+heap_keys!(0 "0" 1 "1" 2 "2" 3 "3" 4 "4" 5 "5" 6 "6" 7 "7" 8 "8" 9 "9" 10 "10" 11 "11" 12 "12" 13 "13" 14 "14" 15 "15");
+
+pub(crate) fn heap_used(heap: u32, used: u64) {
+    match HEAP_USED_KEYS.get(heap as usize) {
+        Some(key) => metrics::with_recorder(|recorder| {
+            recorder.register_gauge(key, &METADATA).set(used as f64)
+        }),
+        None => gauge!(METRIC_HEAP_USED_BYTES, "heap" => heap.to_string()).set(used as f64),
+    }
+}
+
+pub(crate) fn live_blocks(count: usize) {
+    gauge!(METRIC_LIVE_BLOCKS).set(count as f64);
+}
+
+pub(crate) fn device_allocations(count: u64) {
+    gauge!(METRIC_DEVICE_ALLOCATIONS).set(count as f64);
+}
+
+pub(crate) fn failed_allocation(err: AllocationError) {
+    let reason = match err {
+        AllocationError::OutOfDeviceMemory => "out_of_device_memory",
+        AllocationError::OutOfHostMemory => "out_of_host_memory",
+        AllocationError::NoCompatibleMemoryTypes => "no_compatible_memory_types",
+        AllocationError::TooManyObjects => "too_many_objects",
+        AllocationError::OutOfBudget => "out_of_budget",
+        AllocationError::InvalidAlignment => "invalid_alignment",
+        AllocationError::SizeOverflow => "size_overflow",
+    };
+    counter!(METRIC_FAILED_ALLOCATIONS, "reason" => reason).increment(1);
+}
@@ -0,0 +1,33 @@
+//! Monotonic clock for block ages and latency statistics.
+//!
+//! `std::time::Instant::now` panics on `wasm32-unknown-unknown`,
+//! where there is no clock without JavaScript bindings.
+//! There instant is a placeholder and all durations are zero.
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) use std::time::Instant;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub(crate) use self::wasm::Instant;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+mod wasm {
+    use core::time::Duration;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+    pub(crate) struct Instant;
+
+    impl Instant {
+        pub(crate) fn now() -> Self {
+            Instant
+        }
+
+        pub(crate) fn elapsed(&self) -> Duration {
+            Duration::ZERO
+        }
+
+        pub(crate) fn saturating_duration_since(&self, _earlier: Instant) -> Duration {
+            Duration::ZERO
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use {
+    crate::time::Instant,
+    alloc::{string::String, vec::Vec},
+    core::fmt::{self, Write as _},
+    std::time::Duration,
+};
+
+#[derive(Clone, Copy, Debug)]
+enum Phase {
+    Begin {
+        id: usize,
+        memory_type: u32,
+        size: u64,
+        tag: Option<&'static str>,
+    },
+    End {
+        id: usize,
+        memory_type: u32,
+        tag: Option<&'static str>,
+    },
+    HeapUsage {
+        heap: u32,
+        used: u64,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Event {
+    time: Duration,
+    phase: Phase,
+}
+
+/// Allocation timeline recorded by [`GpuAllocator::start_trace`].
+///
+/// Contains lifetimes of blocks allocated and deallocated while recording
+/// and heap usage changes.
+///
+/// [`GpuAllocator::start_trace`]: crate::GpuAllocator::start_trace
+#[derive(Clone, Debug)]
+pub struct AllocationTrace {
+    start: Instant,
+    events: Vec<Event>,
+}
+
+impl AllocationTrace {
+    pub(crate) fn new() -> Self {
+        AllocationTrace {
+            start: Instant::now(),
+            events: Vec::new(),
+        }
+    }
+
+    pub(crate) fn alloc(
+        &mut self,
+        id: usize,
+        memory_type: u32,
+        size: u64,
+        tag: Option<&'static str>,
+    ) {
+        self.push(Phase::Begin {
+            id,
+            memory_type,
+            size,
+            tag,
+        });
+    }
+
+    pub(crate) fn dealloc(&mut self, id: usize, memory_type: u32, tag: Option<&'static str>) {
+        self.push(Phase::End {
+            id,
+            memory_type,
+            tag,
+        });
+    }
+
+    pub(crate) fn heap_usage(&mut self, heap: u32, used: u64) {
+        self.push(Phase::HeapUsage { heap, used });
+    }
+
+    fn push(&mut self, phase: Phase) {
+        self.events.push(Event {
+            time: self.start.elapsed(),
+            phase,
+        });
+    }
+
+    /// Returns number of recorded events.
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Returns `true` if no events were recorded.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Returns trace in Chrome trace event format
+    /// that can be loaded into `chrome://tracing` or Perfetto.
+    ///
+    /// Block lifetimes are async events grouped by memory type
+    /// and named by tag. Heap usage is emitted as counters.
+    pub fn to_chrome_json(&self) -> String {
+        let mut out = String::new();
+        self.write_chrome_json(&mut out)
+            .expect("Writing to string never fails");
+        out
+    }
+
+    fn write_chrome_json(&self, out: &mut String) -> fmt::Result {
+        out.push_str("{\"traceEvents\":[");
+
+        for (index, event) in self.events.iter().enumerate() {
+            if index > 0 {
+                out.push(',');
+            }
+            out.push_str("\n{");
+
+            let ts = event.time.as_secs_f64() * 1_000_000.0;
+
+            match event.phase {
+                Phase::Begin {
+                    id,
+                    memory_type,
+                    size,
+                    tag,
+                } => {
+                    write_name(out, tag);
+                    write!(
+                        out,
+                        ",\"cat\":\"gpu-alloc\",\"ph\":\"b\",\"ts\":{:.3},\"pid\":0,\"tid\":{},\"id\":{},\"args\":{{\"size\":{},\"memory_type\":{}}}",
+                        ts, memory_type, id, size, memory_type
+                    )?;
+                }
+                Phase::End {
+                    id,
+                    memory_type,
+                    tag,
+                } => {
+                    write_name(out, tag);
+                    write!(
+                        out,
+                        ",\"cat\":\"gpu-alloc\",\"ph\":\"e\",\"ts\":{:.3},\"pid\":0,\"tid\":{},\"id\":{}",
+                        ts, memory_type, id
+                    )?;
+                }
+                Phase::HeapUsage { heap, used } => {
+                    write!(
+                        out,
+                        "\"name\":\"heap {}\",\"cat\":\"gpu-alloc\",\"ph\":\"C\",\"ts\":{:.3},\"pid\":0,\"args\":{{\"used\":{}}}",
+                        heap, ts, used
+                    )?;
+                }
+            }
+
+            out.push('}');
+        }
+
+        out.push_str("\n],\"displayTimeUnit\":\"ms\"}\n");
+        Ok(())
+    }
+}
+
+/// Writes `"name":"<tag>"` escaping tag as JSON string.
+fn write_name(out: &mut String, tag: Option<&'static str>) {
+    out.push_str("\"name\":\"");
+    for c in tag.unwrap_or("untagged").chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
@@ -0,0 +1,310 @@
+use {
+    crate::{
+        align_up,
+        allocator::GpuAllocator,
+        atomic::{AtomicUsize, Relaxed},
+        block::MemoryBlock,
+        error::AllocationError,
+        MapError, MemoryBounds, Request, ResourceKind, UsageFlags,
+    },
+    alloc::vec::Vec,
+    core::{
+        convert::TryFrom as _,
+        ptr::{copy_nonoverlapping, NonNull},
+    },
+    gpu_alloc_types::{MemoryDevice, OutOfMemory},
+};
+
+/// Mapped chunk carved by [`TransientAllocator`].
+#[derive(Debug)]
+struct Chunk<M> {
+    block: MemoryBlock<M>,
+    ptr: NonNull<u8>,
+    capacity: usize,
+    cursor: AtomicUsize,
+}
+
+impl<M> Chunk<M> {
+    /// Returns number of bytes carved from this chunk.
+    fn used(&self) -> usize {
+        self.cursor.load(Relaxed).min(self.capacity)
+    }
+}
+
+/// Lock-free bump allocator for short-lived host-visible memory,
+/// such as per-draw uniform data that lives for a single frame.
+///
+/// Memory is carved from persistently mapped chunk allocated from [`GpuAllocator`].
+/// [`TransientAllocator::alloc`] takes shared reference and costs single atomic addition,
+/// so worker threads can allocate concurrently without locks and without touching [`GpuAllocator`].
+///
+/// When chunk is exhausted [`TransientAllocator::alloc`] returns `None`
+/// and [`TransientAllocator::refill`] should be called to replace it with a new one.
+/// Blocks are never freed individually, [`TransientAllocator::reset`] frees them all at once,
+/// typically when GPU finished the frame that used them.
+///
+/// Available on targets with native 64-bit atomics
+/// or with `portable-atomic` feature enabled.
+#[derive(Debug)]
+pub struct TransientAllocator<M> {
+    request: Request,
+    align_mask: u64,
+    current: Option<Chunk<M>>,
+    retired: Vec<Chunk<M>>,
+}
+
+unsafe impl<M> Sync for TransientAllocator<M> where M: Sync {}
+unsafe impl<M> Send for TransientAllocator<M> where M: Send {}
+
+/// Memory block allocated by [`TransientAllocator`].
+///
+/// Borrows the allocator, so it cannot outlive [`TransientAllocator::reset`].
+#[derive(Debug)]
+pub struct TransientBlock<'a, M> {
+    memory: &'a M,
+    offset: u64,
+    size: u64,
+    ptr: NonNull<u8>,
+}
+
+unsafe impl<M> Sync for TransientBlock<'_, M> where M: Sync {}
+unsafe impl<M> Send for TransientBlock<'_, M> where M: Sync {}
+
+impl<'a, M> TransientBlock<'a, M> {
+    /// Returns reference to parent memory object.
+    #[inline(always)]
+    pub fn memory(&self) -> &'a M {
+        self.memory
+    }
+
+    /// Returns offset in bytes from start of memory object to start of this block.
+    #[inline(always)]
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Returns size of this memory block.
+    #[inline(always)]
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns pointer to mapped memory of this block.
+    #[inline(always)]
+    pub fn ptr(&self) -> NonNull<u8> {
+        self.ptr
+    }
+
+    /// Copies specified data to the block at `offset`.
+    ///
+    /// For memory without `HOST_COHERENT` property written data
+    /// becomes visible to device after [`TransientAllocator::flush`].
+    ///
+    /// Returns `MapError::OutOfBounds` if data does not fit into the block.
+    ///
+    /// # Safety
+    ///
+    /// The caller must guarantee that no command that accesses this range is in flight.
+    #[inline(always)]
+    pub unsafe fn write_bytes(&self, offset: u64, data: &[u8]) -> Result<(), MapError> {
+        if offset > self.size || data.len() as u64 > self.size - offset {
+            return Err(MapError::OutOfBounds);
+        }
+
+        copy_nonoverlapping(
+            data.as_ptr(),
+            self.ptr.as_ptr().add(offset as usize),
+            data.len(),
+        );
+        Ok(())
+    }
+}
+
+impl<M> TransientAllocator<M>
+where
+    M: MemoryBounds + 'static,
+{
+    /// Creates new transient allocator that allocates chunks of `chunk_size` bytes
+    /// from memory types in `memory_types` mask suitable for `usage`.
+    ///
+    /// All blocks are aligned to `align_mask`.
+    /// `HOST_ACCESS` and `TRANSIENT` usage flags are implied.
+    ///
+    /// No memory is allocated until [`TransientAllocator::refill`] is called.
+    pub fn new(chunk_size: u64, align_mask: u64, usage: UsageFlags, memory_types: u32) -> Self {
+        assert!(
+            (align_mask + 1).is_power_of_two(),
+            "`align_mask + 1` must be power of two"
+        );
+
+        TransientAllocator {
+            request: Request {
+                size: chunk_size,
+                align_mask,
+                usage: usage | UsageFlags::HOST_ACCESS | UsageFlags::TRANSIENT,
+                memory_types,
+                kind: ResourceKind::Linear,
+            },
+            align_mask,
+            current: None,
+            retired: Vec::new(),
+        }
+    }
+
+    /// Allocates block of `size` bytes from current chunk.
+    ///
+    /// Returns `None` if there is no chunk or it is exhausted,
+    /// in which case [`TransientAllocator::refill`] should be called.
+    /// Requests larger than chunk size never succeed.
+    #[inline]
+    pub fn alloc(&self, size: u64) -> Option<TransientBlock<'_, M>> {
+        let chunk = self.current.as_ref()?;
+
+        let aligned = usize::try_from(align_up(size, self.align_mask)?).ok()?;
+        if aligned > chunk.capacity || chunk.cursor.load(Relaxed) > chunk.capacity {
+            // Exhausted chunk is not touched, so cursor cannot overflow
+            // until chunk is refilled.
+            return None;
+        }
+
+        let offset = chunk.cursor.fetch_add(aligned, Relaxed);
+        if offset > chunk.capacity - aligned {
+            return None;
+        }
+
+        Some(TransientBlock {
+            memory: chunk.block.memory(),
+            offset: chunk.block.offset() + offset as u64,
+            size,
+            ptr: unsafe { NonNull::new_unchecked(chunk.ptr.as_ptr().add(offset)) },
+        })
+    }
+
+    /// Returns `true` if block of `size` bytes fits into empty chunk.
+    #[cfg(feature = "std")]
+    pub(crate) fn fits_chunk(&self, size: u64) -> bool {
+        matches!(align_up(size, self.align_mask), Some(aligned) if aligned <= self.request.size)
+    }
+
+    /// Returns `true` if block of `size` bytes fits into the rest of current chunk.
+    #[cfg(feature = "std")]
+    pub(crate) fn fits_current(&self, size: u64) -> bool {
+        match (&self.current, align_up(size, self.align_mask)) {
+            (Some(chunk), Some(aligned)) => {
+                let cursor = chunk.cursor.load(Relaxed);
+                cursor <= chunk.capacity && aligned <= (chunk.capacity - cursor) as u64
+            }
+            _ => false,
+        }
+    }
+
+    /// Allocates and maps new chunk replacing current one.
+    /// Blocks carved from previous chunk stay valid until [`TransientAllocator::reset`].
+    ///
+    /// # Safety
+    ///
+    /// * `allocator` and `device` must be the same for all calls to this transient allocator.
+    /// * See [`GpuAllocator::alloc`].
+    pub unsafe fn refill<MD>(
+        &mut self,
+        allocator: &mut GpuAllocator<M>,
+        device: &impl AsRef<MD>,
+    ) -> Result<(), AllocationError>
+    where
+        MD: MemoryDevice<M>,
+    {
+        let mut block = allocator.alloc(device, self.request)?;
+
+        let capacity = usize::try_from(block.size()).unwrap_or(usize::MAX);
+        let ptr = match block.map(device, 0, capacity) {
+            Ok(ptr) => ptr,
+            Err(err) => {
+                allocator.dealloc(device, block);
+                return Err(match err {
+                    MapError::OutOfDeviceMemory => AllocationError::OutOfDeviceMemory,
+                    _ => AllocationError::OutOfHostMemory,
+                });
+            }
+        };
+
+        let chunk = Chunk {
+            block,
+            ptr,
+            capacity,
+            cursor: AtomicUsize::new(0),
+        };
+
+        if let Some(current) = self.current.replace(chunk) {
+            self.retired.push(current);
+        }
+
+        Ok(())
+    }
+
+    /// Flushes memory written to blocks of this allocator
+    /// if it was allocated without `HOST_COHERENT` property.
+    ///
+    /// # Safety
+    ///
+    /// `device` must be one used to refill this transient allocator.
+    pub unsafe fn flush<MD>(&self, device: &impl AsRef<MD>) -> Result<(), OutOfMemory>
+    where
+        MD: MemoryDevice<M>,
+    {
+        for chunk in self.retired.iter().chain(&self.current) {
+            let used = chunk.used() as u64;
+            if used > 0 {
+                chunk.block.flush_range(device.as_ref(), 0, used)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Frees all blocks allocated so far.
+    /// Retired chunks are returned to `allocator`, current chunk is kept for reuse.
+    ///
+    /// # Safety
+    ///
+    /// * The caller must guarantee that device finished all commands that access allocated blocks.
+    /// * `allocator` and `device` must be ones used to refill this transient allocator.
+    pub unsafe fn reset<MD>(&mut self, allocator: &mut GpuAllocator<M>, device: &impl AsRef<MD>)
+    where
+        MD: MemoryDevice<M>,
+    {
+        for chunk in self.retired.drain(..) {
+            release(chunk, allocator, device);
+        }
+
+        if let Some(current) = &mut self.current {
+            *current.cursor.get_mut() = 0;
+        }
+    }
+
+    /// Returns all chunks to `allocator`.
+    ///
+    /// # Safety
+    ///
+    /// Same as for [`TransientAllocator::reset`].
+    pub unsafe fn dispose<MD>(mut self, allocator: &mut GpuAllocator<M>, device: &impl AsRef<MD>)
+    where
+        MD: MemoryDevice<M>,
+    {
+        self.reset(allocator, device);
+
+        if let Some(current) = self.current.take() {
+            release(current, allocator, device);
+        }
+    }
+}
+
+unsafe fn release<M, MD>(
+    mut chunk: Chunk<M>,
+    allocator: &mut GpuAllocator<M>,
+    device: &impl AsRef<MD>,
+) where
+    M: MemoryBounds + 'static,
+    MD: MemoryDevice<M>,
+{
+    chunk.block.unmap(device);
+    allocator.dealloc(device, chunk.block);
+}
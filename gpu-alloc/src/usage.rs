@@ -34,6 +34,8 @@ bitflags::bitflags! {
         /// allowing to use faster algorithm with less memory overhead.
         /// If use holds returned memory block for too long then
         /// effective memory overhead increases instead.
+        /// When most chunks are held by single long-lived block,
+        /// further transient requests are served as regular ones.
         /// Best use case is for staging buffer for single batch of operations.
         const TRANSIENT = 0x10;
 
@@ -43,11 +45,56 @@ bitflags::bitflags! {
     }
 }
 
+/// Ranks memory types compatible with each usage.
+///
+/// Set with [`GpuAllocator::set_memory_type_scoring`].
+/// Implemented for closures with matching signature.
+///
+/// Scores are computed once for every combination of usage flags and memory type,
+/// so, unlike [`MemoryTypeSelector`], scoring cannot depend on allocator state.
+///
+/// [`GpuAllocator::set_memory_type_scoring`]: crate::GpuAllocator::set_memory_type_scoring
+/// [`MemoryTypeSelector`]: crate::MemoryTypeSelector
+pub trait MemoryTypeScoring {
+    /// Returns score of memory type with `props` for `usage`.
+    /// Memory types with lesser score are tried first.
+    /// Memory types with equal score are tried in order of their indices.
+    ///
+    /// Called only for memory types compatible with the usage.
+    /// `usage` includes flags implied by other flags,
+    /// e.g. `HOST_ACCESS` is set when `UPLOAD` or `DOWNLOAD` is.
+    fn score(&self, usage: UsageFlags, props: MemoryPropertyFlags) -> u32;
+}
+
+impl<F> MemoryTypeScoring for F
+where
+    F: Fn(UsageFlags, MemoryPropertyFlags) -> u32,
+{
+    fn score(&self, usage: UsageFlags, props: MemoryPropertyFlags) -> u32 {
+        self(usage, props)
+    }
+}
+
+/// Scoring used by default.
+///
+/// Prefers `DEVICE_LOCAL` memory for `FAST_DEVICE_ACCESS` and empty usage,
+/// `HOST_VISIBLE` memory only when host access is required,
+/// `HOST_CACHED` memory for `DOWNLOAD` and `HOST_COHERENT` memory for `UPLOAD` and `DOWNLOAD`,
+/// in that order of importance.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DefaultScoring;
+
+impl MemoryTypeScoring for DefaultScoring {
+    fn score(&self, usage: UsageFlags, props: MemoryPropertyFlags) -> u32 {
+        reverse_priority(usage, props)
+    }
+}
+
 #[derive(Clone, Copy, Debug)]
 struct MemoryForOneUsage {
     mask: u32,
-    types: [u32; 32],
-    types_count: u32,
+    types: [u8; 32],
+    types_count: u8,
 }
 
 pub(crate) struct MemoryForUsage {
@@ -63,7 +110,7 @@ impl Debug for MemoryForUsage {
 }
 
 impl MemoryForUsage {
-    pub fn new(memory_types: &[MemoryType]) -> Self {
+    pub fn new(memory_types: &[MemoryType], scoring: &dyn MemoryTypeScoring) -> Self {
         assert!(
             memory_types.len() <= 32,
             "Only up to 32 memory types supported"
@@ -79,7 +126,7 @@ impl MemoryForUsage {
 
         for usage in 0..64 {
             mfu.usages[usage as usize] =
-                one_usage(UsageFlags::from_bits_truncate(usage), memory_types);
+                one_usage(UsageFlags::from_bits_truncate(usage), memory_types, scoring);
         }
 
         mfu
@@ -91,27 +138,37 @@ impl MemoryForUsage {
         self.usages[usage.bits() as usize].mask
     }
 
-    /// Returns slice of memory type indices that support the usage.
+    /// Returns memory type indices that support the usage.
     /// Earlier memory type has priority over later.
-    pub fn types(&self, usage: UsageFlags) -> &[u32] {
+    pub fn types(&self, usage: UsageFlags) -> impl Iterator<Item = u32> + '_ {
         let usage = &self.usages[usage.bits() as usize];
-        &usage.types[..usage.types_count as usize]
+        usage.types[..usage.types_count as usize]
+            .iter()
+            .map(|&index| u32::from(index))
     }
 }
 
-fn one_usage(usage: UsageFlags, memory_types: &[MemoryType]) -> MemoryForOneUsage {
+fn one_usage(
+    usage: UsageFlags,
+    memory_types: &[MemoryType],
+    scoring: &dyn MemoryTypeScoring,
+) -> MemoryForOneUsage {
     let mut types = [0; 32];
     let mut types_count = 0;
 
     for (index, mt) in memory_types.iter().enumerate() {
         if compatible(usage, mt.props) {
-            types[types_count as usize] = index as u32;
+            types[types_count as usize] = index as u8;
             types_count += 1;
         }
     }
 
-    types[..types_count as usize]
-        .sort_unstable_by_key(|&index| reverse_priority(usage, memory_types[index as usize].props));
+    types[..types_count as usize].sort_unstable_by_key(|&index| {
+        (
+            scoring.score(usage, memory_types[index as usize].props),
+            index,
+        )
+    });
 
     let mask = types[..types_count as usize]
         .iter()
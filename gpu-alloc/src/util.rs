@@ -1,4 +1,59 @@
-use alloc::sync::Arc;
+use {alloc::sync::Arc, smallvec::SmallVec};
+
+/// Table indexed by memory type or memory heap.
+///
+/// Devices rarely expose more than 8 of either,
+/// so tables are stored inline and looked up without pointer chasing.
+pub(crate) type Table<T> = SmallVec<[T; 8]>;
+
+/// Integer type of block ids and buddy indices stored in every memory block.
+///
+/// With `compact-metadata` feature it is 32-bit,
+/// limiting number of live blocks to `u32::MAX`.
+#[cfg(feature = "compact-metadata")]
+pub(crate) type Compact = u32;
+
+#[cfg(not(feature = "compact-metadata"))]
+pub(crate) type Compact = usize;
+
+/// Generation of registered block.
+///
+/// With `compact-metadata` feature it is 32-bit and wraps around,
+/// so double free detection may miss block deallocated
+/// after `u32::MAX` other registrations.
+#[cfg(feature = "compact-metadata")]
+pub(crate) type Generation = u32;
+
+#[cfg(not(feature = "compact-metadata"))]
+pub(crate) type Generation = u64;
+
+#[cfg(feature = "compact-metadata")]
+pub(crate) fn compact(value: usize) -> Compact {
+    core::convert::TryFrom::try_from(value)
+        .expect("Index does not fit into `compact-metadata` block")
+}
+
+#[cfg(not(feature = "compact-metadata"))]
+pub(crate) fn compact(value: usize) -> Compact {
+    value
+}
+
+#[cfg(feature = "compact-metadata")]
+pub(crate) fn uncompact(value: Compact) -> usize {
+    value as usize
+}
+
+#[cfg(not(feature = "compact-metadata"))]
+pub(crate) fn uncompact(value: Compact) -> usize {
+    value
+}
+
+/// Returns `true` if memory region of `size` bytes can be addressed
+/// with host pointer offsets, which is not the case for large regions
+/// on 32-bit targets such as `wasm32`.
+pub(crate) fn host_addressable(size: u64) -> bool {
+    size <= isize::MAX as u64
+}
 
 /// Guarantees uniqueness only if `Weak` pointers are never created
 /// from this `Arc` or clones.
@@ -42,3 +97,36 @@ pub(crate) unsafe fn try_arc_unwrap<M>(mut arc: Arc<M>) -> Option<M> {
         None
     }
 }
+
+/// Returns random id for new allocator instance.
+///
+/// Ids are distinct within the process as they are derived from a counter,
+/// and scrambled so that instances from different runs are unlikely to match.
+#[cfg(feature = "debug-checks")]
+pub(crate) fn instance_id() -> u64 {
+    use core::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let count = COUNTER.fetch_add(1, Relaxed) as u64;
+
+    #[cfg(feature = "std")]
+    {
+        use std::{
+            collections::hash_map::RandomState,
+            hash::{BuildHasher, Hasher},
+        };
+
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u64(count);
+        hasher.finish()
+    }
+
+    #[cfg(not(feature = "std"))]
+    {
+        // SplitMix64 finalizer.
+        let mut z = count.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
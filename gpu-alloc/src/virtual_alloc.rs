@@ -0,0 +1,201 @@
+use {
+    crate::{align_up, allocator::valid_align_mask, error::AllocationError},
+    alloc::vec::Vec,
+};
+
+/// Range allocated from [`VirtualAllocator`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VirtualAllocation {
+    /// Offset in bytes from start of the address space.
+    pub offset: u64,
+
+    /// Size of the range in bytes.
+    pub size: u64,
+}
+
+/// Offset bookkeeping over user-defined address space without device memory behind it.
+///
+/// Useful to sub-allocate inside one large buffer,
+/// for example vertex pools or bindless descriptor heaps.
+/// Free ranges are kept sorted and merged on deallocation,
+/// allocation picks the smallest free range that fits.
+#[derive(Clone, Debug)]
+pub struct VirtualAllocator {
+    size: u64,
+    /// Free ranges as `(start, end)` pairs, sorted and never adjacent.
+    free: Vec<(u64, u64)>,
+    used: u64,
+    allocations: u64,
+}
+
+impl VirtualAllocator {
+    /// Creates allocator for address space of `size` bytes, entirely free.
+    pub fn new(size: u64) -> Self {
+        let mut free = Vec::new();
+        if size > 0 {
+            free.push((0, size));
+        }
+
+        VirtualAllocator {
+            size,
+            free,
+            used: 0,
+            allocations: 0,
+        }
+    }
+
+    /// Returns size of the address space.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns number of bytes in live allocations.
+    pub fn used(&self) -> u64 {
+        self.used
+    }
+
+    /// Returns number of live allocations.
+    pub fn allocations(&self) -> u64 {
+        self.allocations
+    }
+
+    /// Returns `true` if there are no live allocations.
+    pub fn is_empty(&self) -> bool {
+        self.allocations == 0
+    }
+
+    /// Returns size of the largest free range.
+    pub fn largest_free_region(&self) -> u64 {
+        self.free
+            .iter()
+            .map(|&(start, end)| end - start)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Allocates range of `size` bytes with offset aligned to `align_mask`.
+    /// Zero-sized ranges are one byte long.
+    ///
+    /// Returns `AllocationError::InvalidAlignment` if `align_mask` is not of form `2^n - 1`
+    /// and `AllocationError::OutOfDeviceMemory` if no free range fits.
+    pub fn alloc(
+        &mut self,
+        size: u64,
+        align_mask: u64,
+    ) -> Result<VirtualAllocation, AllocationError> {
+        if !valid_align_mask(align_mask) {
+            return Err(AllocationError::InvalidAlignment);
+        }
+
+        let size = size.max(1);
+
+        // Smallest free range that fits, with aligned offset in it.
+        let mut best: Option<(usize, u64)> = None;
+        for (index, &(start, end)) in self.free.iter().enumerate() {
+            let offset = match align_up(start, align_mask) {
+                Some(offset) => offset,
+                None => continue,
+            };
+
+            if !matches!(offset.checked_add(size), Some(block_end) if block_end <= end) {
+                continue;
+            }
+
+            if let Some((best, _)) = best {
+                let (best_start, best_end) = self.free[best];
+                if best_end - best_start <= end - start {
+                    continue;
+                }
+            }
+
+            best = Some((index, offset));
+        }
+
+        let (index, offset) = best.ok_or(AllocationError::OutOfDeviceMemory)?;
+        let (start, end) = self.free[index];
+        let block_end = offset + size;
+
+        // Alignment padding before the range stays free.
+        match (start < offset, block_end < end) {
+            (false, false) => {
+                self.free.remove(index);
+            }
+            (true, false) => self.free[index].1 = offset,
+            (false, true) => self.free[index].0 = block_end,
+            (true, true) => {
+                self.free[index].1 = offset;
+                self.free.insert(index + 1, (block_end, end));
+            }
+        }
+
+        self.used += size;
+        self.allocations += 1;
+
+        Ok(VirtualAllocation { offset, size })
+    }
+
+    /// Returns range to the allocator, merging it with adjacent free ranges.
+    ///
+    /// # Panics
+    ///
+    /// Panics if range is out of bounds or overlaps free range,
+    /// which happens when it is deallocated twice or was not allocated from this allocator.
+    pub fn dealloc(&mut self, allocation: VirtualAllocation) {
+        let VirtualAllocation { offset, size } = allocation;
+
+        let end = match offset.checked_add(size) {
+            Some(end) if size > 0 && end <= self.size => end,
+            _ => panic!(
+                "Virtual allocation {{ offset: {}, size: {} }} is out of bounds",
+                offset, size
+            ),
+        };
+
+        let index = self.free.partition_point(|&(start, _)| start < offset);
+
+        let merge_prev = index > 0 && {
+            let prev_end = self.free[index - 1].1;
+            assert!(
+                prev_end <= offset,
+                "Virtual allocation {{ offset: {}, size: {} }} overlaps free range",
+                offset,
+                size
+            );
+            prev_end == offset
+        };
+
+        let merge_next = index < self.free.len() && {
+            let next_start = self.free[index].0;
+            assert!(
+                end <= next_start,
+                "Virtual allocation {{ offset: {}, size: {} }} overlaps free range",
+                offset,
+                size
+            );
+            end == next_start
+        };
+
+        match (merge_prev, merge_next) {
+            (false, false) => self.free.insert(index, (offset, end)),
+            (true, false) => self.free[index - 1].1 = end,
+            (false, true) => self.free[index].0 = offset,
+            (true, true) => {
+                self.free[index - 1].1 = self.free[index].1;
+                self.free.remove(index);
+            }
+        }
+
+        self.used -= size;
+        self.allocations -= 1;
+    }
+
+    /// Frees all allocations at once.
+    pub fn reset(&mut self) {
+        self.free.clear();
+        if self.size > 0 {
+            self.free.push((0, self.size));
+        }
+        self.used = 0;
+        self.allocations = 0;
+    }
+}
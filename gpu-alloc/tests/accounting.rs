@@ -0,0 +1,51 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, UsageFlags},
+    harness::{device_properties, ops, Harness, Op},
+    proptest::prelude::*,
+};
+
+fn config_with_low_thresholds() -> Config {
+    let mut config = Config::i_am_potato();
+    config.dedicated_threshold = 4096;
+    config.preferred_dedicated_threshold = 1024;
+    config.transient_dedicated_threshold = 8192;
+    config
+}
+
+proptest! {
+    #![proptest_config(harness::proptest_config())]
+
+    #[test]
+    fn accounting_matches_device(ops in ops(4 * 1024 * 1024, 128)) {
+        let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+        harness.run(&ops)?;
+        harness.finish()?;
+    }
+
+    #[test]
+    fn accounting_matches_device_small_objects_limit(ops in ops(64 * 1024, 128)) {
+        let mut harness = Harness::new(config_with_low_thresholds(), device_properties(8));
+        harness.run(&ops)?;
+        harness.finish()?;
+    }
+
+    #[test]
+    fn accounting_matches_device_transient(ops in ops(256 * 1024, 128)) {
+        let ops: Vec<Op> = ops
+            .into_iter()
+            .map(|op| match op {
+                Op::Alloc { mut request, dedicated } => {
+                    request.usage |= UsageFlags::TRANSIENT | UsageFlags::HOST_ACCESS;
+                    Op::Alloc { request, dedicated }
+                }
+                op => op,
+            })
+            .collect();
+
+        let mut harness = Harness::new(Config::i_am_prototyping(), device_properties(32));
+        harness.run(&ops)?;
+        harness.finish()?;
+    }
+}
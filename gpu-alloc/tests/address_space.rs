@@ -0,0 +1,116 @@
+//! Mapping blocks whose ranges can't be addressed by host pointers.
+//!
+//! Tests with huge blocks run on any host, tests with blocks of few GiB
+//! only on 32-bit hosts like wasm32 or ILP32 targets.
+
+#[cfg(target_pointer_width = "32")]
+use gpu_alloc::AllocationError;
+use gpu_alloc::{
+    Config, Dedicated, GpuAllocator, MapError, MemoryBlock, NoopMemory, NoopMemoryDevice, Request,
+    ResourceKind, UsageFlags,
+};
+
+fn allocator() -> GpuAllocator<NoopMemory> {
+    GpuAllocator::new(Config::i_am_potato(), NoopMemoryDevice::properties(!0))
+}
+
+fn alloc_dedicated(
+    allocator: &mut GpuAllocator<NoopMemory>,
+    device: &NoopMemoryDevice,
+    size: u64,
+) -> MemoryBlock<NoopMemory> {
+    let request = Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::UPLOAD,
+        memory_types: !0,
+        kind: ResourceKind::Linear,
+    };
+    unsafe { allocator.alloc_with_dedicated(device, request, Dedicated::Required) }.unwrap()
+}
+
+#[test]
+fn huge_dedicated_block_cannot_be_mapped_as_whole() {
+    let size = isize::MAX as u64 + 4096;
+    let device = NoopMemoryDevice::new();
+    let mut allocator = allocator();
+    let mut block = alloc_dedicated(&mut allocator, &device, size);
+
+    // `map_range` maps whole dedicated block that doesn't fit host address space.
+    let err = unsafe { block.map_range(&device, 0, 16) }.unwrap_err();
+    assert_eq!(err, MapError::Unaddressable);
+
+    let err = unsafe { block.map_range(&device, size - 16, 16) }.unwrap_err();
+    assert_eq!(err, MapError::Unaddressable);
+
+    // Failed mapping leaves no mapped ranges behind.
+    assert!(!unsafe { block.unmap_range(&device, 0) });
+
+    unsafe { allocator.dealloc(&device, block) }
+    unsafe { allocator.cleanup(&device) }
+    assert_eq!(device.live_allocations(), 0);
+}
+
+#[test]
+fn copy_of_unaddressable_size_fails() {
+    let size = isize::MAX as u64 + 4096;
+    let device = NoopMemoryDevice::new();
+    let mut allocator = allocator();
+    let mut src = alloc_dedicated(&mut allocator, &device, size);
+    let mut dst = alloc_dedicated(&mut allocator, &device, 4096);
+
+    let result = unsafe { src.copy_to(&device, &mut dst, 0, 0, u64::MAX) };
+    if cfg!(target_pointer_width = "64") {
+        assert_eq!(result, Err(MapError::OutOfBounds));
+    } else {
+        assert_eq!(result, Err(MapError::Unaddressable));
+    }
+
+    unsafe { allocator.dealloc(&device, src) }
+    unsafe { allocator.dealloc(&device, dst) }
+    unsafe { allocator.cleanup(&device) }
+}
+
+#[cfg(target_pointer_width = "32")]
+#[test]
+fn ranges_beyond_2gib_are_unaddressable_on_32bit() {
+    let size = 3 * 1024 * 1024 * 1024;
+    let device = NoopMemoryDevice::new();
+    let mut allocator = allocator();
+    let mut block = alloc_dedicated(&mut allocator, &device, size);
+
+    let err = unsafe { block.map_range(&device, 0, 16) }.unwrap_err();
+    assert_eq!(err, MapError::Unaddressable);
+
+    let err = unsafe { block.map_range(&device, size - 16, 16) }.unwrap_err();
+    assert_eq!(err, MapError::Unaddressable);
+
+    unsafe { allocator.dealloc(&device, block) }
+    unsafe { allocator.cleanup(&device) }
+}
+
+#[cfg(target_pointer_width = "32")]
+#[test]
+fn chunks_beyond_2gib_are_not_created_on_32bit() {
+    let device = NoopMemoryDevice::new();
+    let mut allocator = GpuAllocator::new(
+        Config {
+            starting_free_list_chunk: 3 * 1024 * 1024 * 1024,
+            final_free_list_chunk: 3 * 1024 * 1024 * 1024,
+            ..Config::i_am_potato()
+        },
+        NoopMemoryDevice::properties(!0),
+    );
+
+    // Linear allocator maps whole chunk, which doesn't fit host address space.
+    let request = Request {
+        size: 1024,
+        align_mask: 0,
+        usage: UsageFlags::UPLOAD | UsageFlags::TRANSIENT,
+        memory_types: !0,
+        kind: ResourceKind::Linear,
+    };
+    let err = unsafe { allocator.alloc(&device, request) }.unwrap_err();
+    assert_eq!(err, AllocationError::OutOfHostMemory);
+    assert_eq!(device.total_allocations(), 0);
+}
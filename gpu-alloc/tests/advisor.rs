@@ -0,0 +1,182 @@
+mod harness;
+
+use {
+    gpu_alloc::{Advice, AdvisorConfig, Config, Dedicated, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn harness() -> Harness {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    harness.allocator.enable_advisor(AdvisorConfig {
+        transient_max_frames: 2,
+        small_dedicated_per_frame: 4,
+        repeated_size_per_frame: 8,
+        chunk_churn_per_frame: 2,
+    });
+    harness
+}
+
+fn request(size: u64, usage: UsageFlags) -> Request {
+    Request {
+        size,
+        align_mask: 255,
+        usage,
+        memory_types: 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn long_lived_transient() {
+    let mut harness = harness();
+
+    let block = unsafe {
+        harness.allocator.alloc(
+            &harness.device,
+            request(1000, UsageFlags::FAST_DEVICE_ACCESS | UsageFlags::TRANSIENT),
+        )
+    }
+    .unwrap();
+
+    harness.allocator.end_frame();
+    harness.allocator.end_frame();
+    assert_eq!(harness.allocator.take_advice(), []);
+
+    harness.allocator.end_frame();
+    harness.allocator.end_frame();
+    assert_eq!(
+        harness.allocator.take_advice(),
+        [Advice::LongLivedTransient {
+            memory_type: 0,
+            size: 1000,
+            frames: 2,
+        }],
+        "transient block must be reported once"
+    );
+
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+    harness.finish().unwrap();
+}
+
+#[test]
+fn frequent_small_dedicated() {
+    let mut harness = harness();
+
+    for _ in 0..4 {
+        let block = unsafe {
+            harness.allocator.alloc_with_dedicated(
+                &harness.device,
+                request(2048, UsageFlags::FAST_DEVICE_ACCESS),
+                Dedicated::Preferred,
+            )
+        }
+        .unwrap();
+        harness
+            .blocks
+            .push((block, request(2048, UsageFlags::FAST_DEVICE_ACCESS)));
+    }
+
+    harness.allocator.end_frame();
+
+    let advice = harness.allocator.take_advice();
+    assert_eq!(
+        advice,
+        [Advice::FrequentSmallDedicated {
+            memory_type: 0,
+            count: 4,
+            largest: 2048,
+            dedicated_threshold: Config::i_am_potato().dedicated_threshold,
+        }]
+    );
+    assert!(advice[0]
+        .to_string()
+        .contains("Config::preferred_dedicated_threshold"));
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn repeated_size() {
+    let mut harness = harness();
+
+    // Keeps buddy chunk alive so only the size pattern is reported.
+    let keep = unsafe {
+        harness
+            .allocator
+            .alloc(&harness.device, request(64, UsageFlags::FAST_DEVICE_ACCESS))
+    }
+    .unwrap();
+
+    for _ in 0..8 {
+        let block = unsafe {
+            harness.allocator.alloc(
+                &harness.device,
+                request(256, UsageFlags::FAST_DEVICE_ACCESS),
+            )
+        }
+        .unwrap();
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    harness.allocator.end_frame();
+    assert_eq!(
+        harness.allocator.take_advice(),
+        [Advice::RepeatedSize {
+            memory_type: 0,
+            size: 256,
+            count: 8,
+        }]
+    );
+
+    unsafe { harness.allocator.dealloc(&harness.device, keep) }
+    harness.finish().unwrap();
+}
+
+#[test]
+fn chunk_thrashing() {
+    let mut harness = harness();
+
+    for _ in 0..3 {
+        let block = unsafe {
+            harness.allocator.alloc(
+                &harness.device,
+                request(1000, UsageFlags::FAST_DEVICE_ACCESS),
+            )
+        }
+        .unwrap();
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    harness.allocator.end_frame();
+    assert_eq!(
+        harness.allocator.take_advice(),
+        [Advice::ChunkThrashing {
+            memory_type: 0,
+            created: 3,
+            destroyed: 3,
+        }]
+    );
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn disabled_by_default() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    for _ in 0..16 {
+        let block = unsafe {
+            harness.allocator.alloc(
+                &harness.device,
+                request(1000, UsageFlags::FAST_DEVICE_ACCESS),
+            )
+        }
+        .unwrap();
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    harness.allocator.end_frame();
+    assert_eq!(harness.allocator.take_advice(), []);
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,71 @@
+mod harness;
+
+use {
+    gpu_alloc::{AllocationError, Config, Dedicated, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(align_mask: u64, usage: UsageFlags) -> Request {
+    Request {
+        size: 100,
+        align_mask,
+        usage,
+        memory_types: !0,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn invalid_align_mask_rejected() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    for &align_mask in &[2, 5, 0b1011, 1 << 20, u64::MAX - 1] {
+        for &usage in &[UsageFlags::empty(), UsageFlags::TRANSIENT] {
+            let request = request(align_mask, usage);
+
+            let err = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap_err();
+            assert_eq!(err, AllocationError::InvalidAlignment);
+
+            let err = unsafe {
+                harness.allocator.alloc_with_dedicated(
+                    &harness.device,
+                    request,
+                    Dedicated::Required,
+                )
+            }
+            .unwrap_err();
+            assert_eq!(err, AllocationError::InvalidAlignment);
+
+            let err = unsafe {
+                harness
+                    .allocator
+                    .alloc_dedicated_concurrent(&harness.device, request)
+            }
+            .unwrap_err();
+            assert_eq!(err, AllocationError::InvalidAlignment);
+        }
+    }
+
+    let details = harness.allocator.last_failure().unwrap();
+    assert!(details.attempts().is_empty());
+    assert_eq!(harness.device.total_allocations(), 0);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn valid_align_mask_respected() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    for &align_mask in &[0, 1, 3, 255, 4095] {
+        for &usage in &[UsageFlags::empty(), UsageFlags::TRANSIENT] {
+            let request = request(align_mask, usage);
+            let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+            assert_eq!(block.offset() & align_mask, 0);
+            harness.blocks.push((block, request));
+        }
+    }
+
+    harness.check().unwrap();
+    harness.finish().unwrap();
+}
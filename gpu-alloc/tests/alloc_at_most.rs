@@ -0,0 +1,96 @@
+mod harness;
+
+use {
+    gpu_alloc::{AllocationError, Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+const HEAP_SIZE: u64 = 16 * 1024 * 1024;
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn request_is_halved_under_budget_pressure() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let large = request(HEAP_SIZE / 2 + HEAP_SIZE / 4);
+    let block = unsafe { harness.allocator.alloc(&harness.device, large) }.unwrap();
+    harness.blocks.push((block, large));
+
+    // Only a quarter of the heap is left.
+    let (block, size) = unsafe {
+        harness
+            .allocator
+            .alloc_at_most(&harness.device, request(HEAP_SIZE), 1024)
+    }
+    .unwrap();
+    assert_eq!(size, HEAP_SIZE / 4);
+    assert_eq!(block.size(), size);
+    harness.blocks.push((block, request(size)));
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn full_size_is_granted_when_it_fits() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let (block, size) = unsafe {
+        harness
+            .allocator
+            .alloc_at_most(&harness.device, request(4096), 1024)
+    }
+    .unwrap();
+    assert_eq!(size, 4096);
+    harness.blocks.push((block, request(size)));
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn gives_up_below_minimal_size() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let large = request(HEAP_SIZE - HEAP_SIZE / 8);
+    let block = unsafe { harness.allocator.alloc(&harness.device, large) }.unwrap();
+    harness.blocks.push((block, large));
+
+    let err = unsafe {
+        harness
+            .allocator
+            .alloc_at_most(&harness.device, request(HEAP_SIZE), HEAP_SIZE / 4)
+    }
+    .unwrap_err();
+    assert_eq!(err, AllocationError::OutOfBudget);
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn other_errors_are_not_retried() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let request = Request {
+        memory_types: 0,
+        ..request(4096)
+    };
+    let err = unsafe {
+        harness
+            .allocator
+            .alloc_at_most(&harness.device, request, 1024)
+    }
+    .unwrap_err();
+    assert_eq!(err, AllocationError::NoCompatibleMemoryTypes);
+
+    harness.finish().unwrap();
+}
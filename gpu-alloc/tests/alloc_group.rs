@@ -0,0 +1,173 @@
+mod harness;
+
+use {
+    gpu_alloc::{AllocationError, Config, MemoryTypeCandidate, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64, align_mask: u64, memory_types: u32) -> Request {
+    Request {
+        size,
+        align_mask,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn blocks_share_memory_object() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    // Non-coherent memory type makes blocks aligned to atoms as well.
+    let requests = [
+        request(100, 0, 1 << 3),
+        request(4096, 4095, (1 << 1) | (1 << 3)),
+        request(1, 255, 1 << 3),
+        request(1024 * 1024, 0, 1 << 3),
+    ];
+
+    let blocks = unsafe { harness.allocator.alloc_group(&harness.device, &requests) }.unwrap();
+    assert_eq!(blocks.len(), requests.len());
+    assert_eq!(harness.device.live_allocations(), 1);
+
+    let memory = *blocks[0].memory();
+    let mut ranges = Vec::new();
+    for (block, request) in blocks.iter().zip(&requests) {
+        assert_eq!(*block.memory(), memory);
+        assert_eq!(block.memory_type(), 3);
+        assert!(block.size() >= request.size);
+        assert_eq!(block.offset() & (request.align_mask | 63), 0);
+        ranges.push((block.offset(), block.offset() + block.size()));
+    }
+
+    ranges.sort_unstable();
+    for pair in ranges.windows(2) {
+        assert!(pair[0].1 <= pair[1].0, "blocks overlap: {:?}", pair);
+    }
+
+    harness
+        .blocks
+        .extend(blocks.into_iter().zip(requests.iter().copied()));
+    harness.check().unwrap();
+
+    // Blocks are freed individually while the memory object stays alive.
+    let (block, _) = harness.blocks.remove(1);
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+    harness.check().unwrap();
+    assert_eq!(harness.device.live_allocations(), 1);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn group_gets_own_memory_object() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let single = request(256, 0, 1 << 1);
+    let block = unsafe { harness.allocator.alloc(&harness.device, single) }.unwrap();
+    harness.blocks.push((block, single));
+
+    let requests = [request(256, 0, 1 << 1), request(512, 0, 1 << 1)];
+    let blocks = unsafe { harness.allocator.alloc_group(&harness.device, &requests) }.unwrap();
+    assert_eq!(harness.device.live_allocations(), 2);
+    assert_ne!(*blocks[0].memory(), *harness.blocks[0].0.memory());
+    assert_eq!(*blocks[0].memory(), *blocks[1].memory());
+
+    harness
+        .blocks
+        .extend(blocks.into_iter().zip(requests.iter().copied()));
+    harness.check().unwrap();
+    harness.finish().unwrap();
+}
+
+#[test]
+fn incompatible_requests_fail() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let requests = [request(256, 0, 1 << 1), request(256, 0, 1 << 3)];
+    let err = unsafe { harness.allocator.alloc_group(&harness.device, &requests) }.unwrap_err();
+    assert_eq!(err, AllocationError::NoCompatibleMemoryTypes);
+
+    let requests = [request(256, 0, 1 << 1), request(256, 2, 1 << 1)];
+    let err = unsafe { harness.allocator.alloc_group(&harness.device, &requests) }.unwrap_err();
+    assert_eq!(err, AllocationError::InvalidAlignment);
+
+    assert_eq!(harness.device.total_allocations(), 0);
+    harness.finish().unwrap();
+}
+
+#[test]
+fn group_larger_than_memory_object_fails() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let requests = [
+        request(10 * 1024 * 1024, 0, 1 << 1),
+        request(10 * 1024 * 1024, 0, 1 << 1),
+    ];
+    let err = unsafe { harness.allocator.alloc_group(&harness.device, &requests) }.unwrap_err();
+    assert_eq!(err, AllocationError::OutOfDeviceMemory);
+    assert_eq!(harness.device.total_allocations(), 0);
+
+    let blocks = unsafe { harness.allocator.alloc_group(&harness.device, &[]) }.unwrap();
+    assert!(blocks.is_empty());
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn selector_ranks_and_vetoes_memory_types() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let requests = [
+        request(256, 0, (1 << 1) | (1 << 3)),
+        request(512, 0, (1 << 1) | (1 << 3)),
+    ];
+
+    // Prefer memory types with higher index.
+    harness.allocator.set_memory_type_selector(Box::new(
+        |_: &Request, candidate: &MemoryTypeCandidate| Some(u32::MAX - candidate.memory_type),
+    ));
+    let blocks = unsafe { harness.allocator.alloc_group(&harness.device, &requests) }.unwrap();
+    assert!(blocks.iter().all(|block| block.memory_type() == 3));
+    harness
+        .blocks
+        .extend(blocks.into_iter().zip(requests.iter().copied()));
+
+    // Selector sees the whole group as one request.
+    harness.allocator.set_memory_type_selector(Box::new(
+        |request: &Request, candidate: &MemoryTypeCandidate| {
+            assert_eq!(request.size, 768);
+            (candidate.memory_type != 3).then_some(0)
+        },
+    ));
+    let blocks = unsafe { harness.allocator.alloc_group(&harness.device, &requests) }.unwrap();
+    assert!(blocks.iter().all(|block| block.memory_type() == 1));
+    harness
+        .blocks
+        .extend(blocks.into_iter().zip(requests.iter().copied()));
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn failure_is_recorded() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let requests = [request(256, 0, 1 << 1), request(512, 255, 1 << 1)];
+
+    // Vetoing all compatible memory types fails the group.
+    harness
+        .allocator
+        .set_memory_type_selector(Box::new(|_: &Request, _: &MemoryTypeCandidate| None));
+    let err = unsafe { harness.allocator.alloc_group(&harness.device, &requests) }.unwrap_err();
+    assert_eq!(err, AllocationError::NoCompatibleMemoryTypes);
+
+    let details = harness.allocator.last_failure().unwrap();
+    assert_eq!(details.error(), AllocationError::NoCompatibleMemoryTypes);
+    assert_eq!(details.request().size, 768);
+    assert_eq!(details.request().align_mask, 255);
+    assert_eq!(details.request().memory_types, 1 << 1);
+    assert_eq!(harness.device.total_allocations(), 0);
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,104 @@
+mod harness;
+
+use {
+    gpu_alloc::{AllocationError, Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64, usage: UsageFlags) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+fn alloc_in_range(harness: &mut Harness, request: Request, max_size: u64) -> u64 {
+    let (block, granted) = harness.allocator.alloc_in_range(request, max_size).unwrap();
+
+    assert!(granted >= request.size);
+    assert!(granted <= max_size);
+    assert!(block.size() >= granted);
+
+    harness.blocks.push((
+        block,
+        Request {
+            size: granted,
+            ..request
+        },
+    ));
+    harness.check().unwrap();
+    granted
+}
+
+#[test]
+fn fills_free_list_region() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let small = request(1024, UsageFlags::TRANSIENT);
+    let block = unsafe { harness.allocator.alloc(&harness.device, small) }.unwrap();
+    let memory = *block.memory();
+    harness.blocks.push((block, small));
+    assert_eq!(harness.device.total_allocations(), 1);
+
+    let granted = alloc_in_range(&mut harness, request(256, UsageFlags::TRANSIENT), 1 << 20);
+    assert!(granted > 1024);
+    assert_eq!(*harness.blocks[1].0.memory(), memory);
+    assert_eq!(harness.device.total_allocations(), 1);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn takes_largest_free_buddy() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let small = request(256, UsageFlags::FAST_DEVICE_ACCESS);
+    let block = unsafe { harness.allocator.alloc(&harness.device, small) }.unwrap();
+    harness.blocks.push((block, small));
+
+    let granted = alloc_in_range(
+        &mut harness,
+        request(100, UsageFlags::FAST_DEVICE_ACCESS),
+        100_000,
+    );
+    assert!(granted > 256);
+    assert!(granted.is_power_of_two());
+    assert_eq!(harness.device.total_allocations(), 1);
+
+    // Larger free buddy is split down to the largest block not exceeding maximal size.
+    let granted = alloc_in_range(
+        &mut harness,
+        request(100, UsageFlags::FAST_DEVICE_ACCESS),
+        300,
+    );
+    assert_eq!(granted, 256);
+    assert_eq!(harness.device.total_allocations(), 1);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn fails_without_free_space() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let result = harness
+        .allocator
+        .alloc_in_range(request(1024, UsageFlags::HOST_ACCESS), 4096);
+    assert_eq!(result.unwrap_err(), AllocationError::OutOfDeviceMemory);
+    assert_eq!(harness.device.total_allocations(), 0);
+
+    // Chunk is full, so nothing fits even minimal size.
+    let full = request(1 << 20, UsageFlags::FAST_DEVICE_ACCESS);
+    let block = unsafe { harness.allocator.alloc(&harness.device, full) }.unwrap();
+    harness.blocks.push((block, full));
+    let result = harness
+        .allocator
+        .alloc_in_range(request(1 << 20, UsageFlags::FAST_DEVICE_ACCESS), 1 << 21);
+    assert_eq!(result.unwrap_err(), AllocationError::OutOfDeviceMemory);
+    assert_eq!(harness.device.total_allocations(), 1);
+
+    harness.finish().unwrap();
+}
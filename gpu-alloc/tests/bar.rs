@@ -0,0 +1,104 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, MemoryHeap, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+    std::borrow::Cow,
+};
+
+const BAR_SIZE: u64 = 1024 * 1024;
+
+/// Moves `DEVICE_LOCAL | HOST_VISIBLE` memory type 2 into its own small heap.
+fn bar_harness(bar_heap_size: u64) -> Harness {
+    let mut props = device_properties(64);
+    let mut memory_types = props.memory_types.into_owned();
+    memory_types[2].heap = 2;
+    props.memory_types = Cow::Owned(memory_types);
+    props.memory_heaps = Cow::Owned(vec![
+        MemoryHeap {
+            size: 64 * 1024 * 1024,
+            budget: None,
+            usage: None,
+        },
+        MemoryHeap {
+            size: 16 * 1024 * 1024,
+            budget: None,
+            usage: None,
+        },
+        MemoryHeap {
+            size: BAR_SIZE,
+            budget: None,
+            usage: None,
+        },
+    ]);
+
+    let mut config = Config::i_am_potato();
+    config.bar_heap_size = bar_heap_size;
+    config.bar_usage_limit = 50;
+    Harness::new(config, props)
+}
+
+fn alloc(harness: &mut Harness, request: Request) -> u32 {
+    let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    let memory_type = block.memory_type();
+    harness.blocks.push((block, request));
+    memory_type
+}
+
+#[test]
+fn fast_uploads_fall_back_at_limit() {
+    let mut harness = bar_harness(BAR_SIZE);
+
+    let request = Request {
+        size: BAR_SIZE / 8,
+        align_mask: 0,
+        usage: UsageFlags::UPLOAD | UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: !0,
+        kind: ResourceKind::Linear,
+    };
+
+    for _ in 0..4 {
+        assert_eq!(alloc(&mut harness, request), 2);
+    }
+
+    // Half of BAR heap is used.
+    assert_eq!(alloc(&mut harness, request), 1);
+    harness.check().unwrap();
+
+    // Room is left again.
+    let (block, _) = harness.blocks.remove(0);
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+    assert_eq!(alloc(&mut harness, request), 2);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn bar_reserved_for_fast_uploads() {
+    // Device access to host-visible memory ranks BAR first.
+    let request = Request {
+        size: 1024,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: (1 << 1) | (1 << 2),
+        kind: ResourceKind::Linear,
+    };
+
+    let mut harness = bar_harness(0);
+    assert_eq!(alloc(&mut harness, request), 2, "policy is disabled");
+    harness.finish().unwrap();
+
+    let mut harness = bar_harness(BAR_SIZE);
+    assert_eq!(alloc(&mut harness, request), 1);
+
+    let bar_only = Request {
+        memory_types: 1 << 2,
+        ..request
+    };
+    assert_eq!(
+        alloc(&mut harness, bar_only),
+        2,
+        "BAR is used when nothing else is compatible"
+    );
+    harness.finish().unwrap();
+}
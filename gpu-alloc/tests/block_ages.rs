@@ -0,0 +1,60 @@
+#![cfg(feature = "std")]
+
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+    std::{thread::sleep, time::Duration},
+};
+
+const REQUEST: Request = Request {
+    size: 1000,
+    align_mask: 255,
+    usage: UsageFlags::FAST_DEVICE_ACCESS,
+    memory_types: 1,
+    kind: ResourceKind::Linear,
+};
+
+fn alloc(harness: &mut Harness, tag: &'static str) {
+    let block = unsafe {
+        harness
+            .allocator
+            .alloc_tagged(&harness.device, REQUEST, tag)
+    }
+    .unwrap();
+    harness.blocks.push((block, REQUEST));
+}
+
+#[test]
+fn oldest_blocks_first() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let ages = harness.allocator.block_ages(3);
+    assert_eq!(ages.blocks, 0);
+    assert!(ages.oldest.is_empty());
+
+    alloc(&mut harness, "old");
+    sleep(Duration::from_millis(20));
+    for _ in 0..3 {
+        alloc(&mut harness, "new");
+    }
+
+    let ages = harness.allocator.block_ages(2);
+    assert_eq!(ages.blocks, 4);
+    assert_eq!(ages.oldest.len(), 2);
+    assert_eq!(ages.oldest[0].tag, Some("old"));
+    assert_eq!(ages.oldest[0].age, ages.max);
+    assert_eq!(ages.oldest[0].size, harness.blocks[0].0.size());
+    assert_eq!(ages.oldest[1].tag, Some("new"));
+
+    assert!(ages.max >= Duration::from_millis(20));
+    assert!(ages.p50 < Duration::from_millis(20));
+    assert!(ages.p50 <= ages.p90 && ages.p90 <= ages.p99 && ages.p99 <= ages.max);
+
+    let report = ages.to_string();
+    assert!(report.starts_with("ages of 4 live blocks"), "{}", report);
+    assert!(report.contains("\n  old: memory type 0"), "{}", report);
+
+    harness.finish().unwrap();
+}
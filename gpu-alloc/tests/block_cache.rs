@@ -0,0 +1,109 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, ops, Harness},
+    proptest::prelude::*,
+};
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+/// Allocates and frees block of the same size few times,
+/// returning number of memory objects allocated.
+fn churn(harness: &mut Harness) -> u64 {
+    let before = harness.device.total_allocations();
+    for _ in 0..4 {
+        let block = unsafe { harness.allocator.alloc(&harness.device, request(256)) }.unwrap();
+        harness.blocks.push((block, request(256)));
+        harness.check().unwrap();
+        let (block, _) = harness.blocks.pop().unwrap();
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+        harness.check().unwrap();
+    }
+    harness.device.total_allocations() - before
+}
+
+#[test]
+fn disabled_by_default() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    // Buddy chunk is released as soon as its only block is freed.
+    assert_eq!(churn(&mut harness), 4);
+    assert_eq!(harness.device.live_allocations(), 0);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn freed_block_is_reused() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    harness.allocator.set_block_cache(4);
+
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(256)) }.unwrap();
+    let (memory, offset) = (*block.memory(), block.offset());
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+
+    // Cached block keeps its chunk alive.
+    assert_eq!(harness.device.live_allocations(), 1);
+    harness.check().unwrap();
+
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(200)) }.unwrap();
+    assert_eq!((*block.memory(), block.offset()), (memory, offset));
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+
+    assert_eq!(churn(&mut harness), 0);
+
+    unsafe { harness.allocator.cleanup(&harness.device) }
+    assert_eq!(
+        harness.device.live_allocations(),
+        0,
+        "cleanup returns cached blocks"
+    );
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn cache_capacity_is_per_size_class() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    harness.allocator.set_block_cache(1);
+
+    for &size in &[256, 256, 1024] {
+        let block = unsafe { harness.allocator.alloc(&harness.device, request(size)) }.unwrap();
+        harness.blocks.push((block, request(size)));
+    }
+    harness.check().unwrap();
+
+    while let Some((block, _)) = harness.blocks.pop() {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+        harness.check().unwrap();
+    }
+
+    // Second 256 bytes block can't merge with its cached buddy.
+    assert_eq!(harness.device.live_allocations(), 1);
+
+    harness.allocator.set_block_cache(0);
+    assert_eq!(churn(&mut harness), 0, "cached blocks keep chunk alive");
+
+    harness.finish().unwrap();
+}
+
+proptest! {
+    #![proptest_config(harness::proptest_config())]
+
+    #[test]
+    fn cached_blocks_accounted_as_free(ops in ops(64 * 1024, 128)) {
+        let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+        harness.allocator.set_block_cache(2);
+        harness.run(&ops)?;
+        harness.finish()?;
+    }
+}
@@ -0,0 +1,123 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, ops, Harness},
+    proptest::prelude::*,
+};
+
+fn harness(keep_alive: u32) -> Harness {
+    let mut config = Config::i_am_potato();
+    config.buddy_chunk_keep_alive = keep_alive;
+    Harness::new(config, device_properties(64))
+}
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+/// Allocates and frees one block, calling cleanup after,
+/// as one frame of application would.
+fn frame(harness: &mut Harness, size: u64) {
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(size)) }.unwrap();
+    harness.blocks.push((block, request(size)));
+    harness.check().unwrap();
+
+    let (block, _) = harness.blocks.pop().unwrap();
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+    harness.check().unwrap();
+
+    unsafe { harness.allocator.cleanup(&harness.device) }
+    harness.check().unwrap();
+}
+
+#[test]
+fn emptied_chunk_released_immediately_by_default() {
+    let mut harness = harness(0);
+
+    for _ in 0..4 {
+        frame(&mut harness, 256);
+    }
+    assert_eq!(harness.device.total_allocations(), 4);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn emptied_chunk_reused_across_frames() {
+    let mut harness = harness(2);
+
+    for _ in 0..4 {
+        frame(&mut harness, 256);
+    }
+    assert_eq!(harness.device.total_allocations(), 1);
+    assert_eq!(harness.device.live_allocations(), 1);
+
+    // Chunk emptied in the last frame is released on the second cleanup.
+    unsafe { harness.allocator.cleanup(&harness.device) }
+    assert_eq!(harness.device.live_allocations(), 0);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn every_emptied_chunk_retained() {
+    let mut harness = harness(2);
+
+    // Sizes above initial chunk size grow chunks, so each block gets own chunk.
+    for &size in &[8 * 1024, 16 * 1024] {
+        let block = unsafe { harness.allocator.alloc(&harness.device, request(size)) }.unwrap();
+        harness.blocks.push((block, request(size)));
+    }
+    assert_eq!(harness.device.live_allocations(), 2);
+
+    while let Some((block, _)) = harness.blocks.pop() {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+        harness.check().unwrap();
+    }
+    assert_eq!(harness.device.live_allocations(), 2);
+
+    unsafe { harness.allocator.cleanup(&harness.device) }
+    harness.check().unwrap();
+    assert_eq!(harness.device.live_allocations(), 2);
+
+    // Both chunks stayed empty across two cleanups.
+    unsafe { harness.allocator.cleanup(&harness.device) }
+    harness.check().unwrap();
+    assert_eq!(harness.device.live_allocations(), 0);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn oscillations_counted() {
+    for &(keep_alive, oscillations) in &[(0, 3), (2, 0)] {
+        let mut harness = harness(keep_alive);
+        for _ in 0..4 {
+            frame(&mut harness, 256);
+        }
+        assert_eq!(
+            harness.allocator.stats().types[1].buddy_chunk_oscillations,
+            oscillations
+        );
+        harness.finish().unwrap();
+    }
+}
+
+proptest! {
+    #![proptest_config(harness::proptest_config())]
+
+    #[test]
+    fn retained_chunks_accounted(ops in ops(64 * 1024, 128)) {
+        let mut harness = harness(1);
+        harness.allocator.set_block_cache(2);
+        harness.run(&ops)?;
+        harness.finish()?;
+    }
+}
@@ -0,0 +1,49 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+fn alloc(harness: &mut Harness, size: u64) {
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(size)) }.unwrap();
+    harness.blocks.push((block, request(size)));
+    harness.check().unwrap();
+}
+
+#[test]
+fn free_buddies_listed_per_size_class() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    // Splitting 8KiB chunk leaves one free buddy of every size from 256 bytes to 4KiB.
+    alloc(&mut harness, 256);
+
+    let stats = harness.allocator.fragmentation(1);
+    assert_eq!(stats.buddy_chunks, 1);
+
+    let free: Vec<(u64, u64)> = stats
+        .buddy_size_classes()
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    assert_eq!(free, [(256, 1), (512, 1), (1024, 1), (2048, 1), (4096, 1)]);
+
+    // No free buddy is large enough despite 7936 bytes being free.
+    assert_eq!(stats.free_bytes, 8192 - 256);
+    alloc(&mut harness, 8192);
+
+    let stats = harness.allocator.fragmentation(1);
+    assert_eq!(stats.buddy_chunks, 2);
+    assert_eq!(harness.device.live_allocations(), 2);
+
+    harness.finish().unwrap();
+}
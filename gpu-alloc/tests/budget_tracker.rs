@@ -0,0 +1,114 @@
+mod harness;
+
+use {
+    gpu_alloc::{
+        AllocationError, BudgetTracker, Config, Dedicated, MemoryBlock, Request, ResourceKind,
+        UsageFlags,
+    },
+    harness::{device_properties, Harness},
+};
+
+fn alloc(
+    harness: &mut Harness,
+    size: u64,
+    memory_type: u32,
+) -> Result<MemoryBlock<usize>, AllocationError> {
+    let request = Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::empty(),
+        memory_types: 1 << memory_type,
+        kind: ResourceKind::Linear,
+    };
+    unsafe {
+        harness
+            .allocator
+            .alloc_with_dedicated(&harness.device, request, Dedicated::Required)
+    }
+}
+
+fn dealloc(harness: &mut Harness, block: MemoryBlock<usize>) {
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+}
+
+#[test]
+fn heap_budget_is_shared_between_allocators() {
+    let tracker = BudgetTracker::new(&device_properties(64));
+    let mut first = Harness::new(Config::i_am_potato(), device_properties(64));
+    let mut second = Harness::new(Config::i_am_potato(), device_properties(64));
+    first.allocator.set_budget_tracker(tracker.clone());
+    second.allocator.set_budget_tracker(tracker.clone());
+
+    // Heap 1 is 16 MiB, each allocator alone fits 10 MiB.
+    let block = alloc(&mut first, 10 << 20, 1).unwrap();
+    assert_eq!(tracker.heap_usage(1), 10 << 20);
+
+    let err = alloc(&mut second, 10 << 20, 1).unwrap_err();
+    assert_eq!(err, AllocationError::OutOfBudget);
+    assert_eq!(tracker.heap_usage(1), 10 << 20);
+
+    dealloc(&mut first, block);
+    assert_eq!(tracker.heap_usage(1), 0);
+
+    let block = alloc(&mut second, 10 << 20, 1).unwrap();
+    assert_eq!(tracker.heap_usage(1), 10 << 20);
+    dealloc(&mut second, block);
+
+    assert_eq!(tracker.heap_usage(1), 0);
+    assert_eq!(tracker.remaining_allocations(), 64);
+
+    first.finish().unwrap();
+    second.finish().unwrap();
+}
+
+#[test]
+fn memory_object_count_is_shared_between_allocators() {
+    let tracker = BudgetTracker::new(&device_properties(3));
+    let mut first = Harness::new(Config::i_am_potato(), device_properties(3));
+    let mut second = Harness::new(Config::i_am_potato(), device_properties(3));
+    first.allocator.set_budget_tracker(tracker.clone());
+    second.allocator.set_budget_tracker(tracker.clone());
+
+    let a = alloc(&mut first, 1024, 0).unwrap();
+    let b = alloc(&mut first, 1024, 0).unwrap();
+    let c = alloc(&mut second, 1024, 0).unwrap();
+    assert_eq!(tracker.remaining_allocations(), 0);
+
+    let err = alloc(&mut second, 1024, 0).unwrap_err();
+    assert_eq!(err, AllocationError::TooManyObjects);
+
+    dealloc(&mut first, a);
+    let d = alloc(&mut second, 1024, 0).unwrap();
+
+    dealloc(&mut first, b);
+    dealloc(&mut second, c);
+    dealloc(&mut second, d);
+    assert_eq!(tracker.remaining_allocations(), 3);
+
+    first.finish().unwrap();
+    second.finish().unwrap();
+}
+
+#[test]
+fn existing_memory_objects_are_charged() {
+    let tracker = BudgetTracker::new(&device_properties(64));
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let block = alloc(&mut harness, 4096, 0).unwrap();
+    harness.allocator.set_budget_tracker(tracker.clone());
+    assert_eq!(tracker.heap_usage(0), 4096);
+    assert_eq!(tracker.remaining_allocations(), 63);
+
+    // Moving to another tracker discharges the previous one.
+    let other = BudgetTracker::new(&device_properties(64));
+    harness.allocator.set_budget_tracker(other.clone());
+    assert_eq!(tracker.heap_usage(0), 0);
+    assert_eq!(tracker.remaining_allocations(), 64);
+    assert_eq!(other.heap_usage(0), 4096);
+
+    dealloc(&mut harness, block);
+    assert_eq!(other.heap_usage(0), 0);
+    assert_eq!(other.remaining_allocations(), 64);
+
+    harness.finish().unwrap();
+}
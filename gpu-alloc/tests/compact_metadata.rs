@@ -0,0 +1,57 @@
+#![cfg(feature = "compact-metadata")]
+
+mod harness;
+
+use {
+    gpu_alloc::{Config, MemoryBlock, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, ops, Harness},
+    proptest::prelude::*,
+};
+
+#[test]
+fn block_is_smaller() {
+    // Without optional per-block fields `MemoryBlock` takes 104 bytes on 64-bit targets.
+    if cfg!(all(
+        target_pointer_width = "64",
+        not(feature = "debug-checks"),
+        not(feature = "guard-bands")
+    )) {
+        assert!(core::mem::size_of::<MemoryBlock<usize>>() <= 88);
+    }
+}
+
+#[test]
+fn ids_survive_compaction() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let request = Request {
+        size: 256,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    };
+
+    for _ in 0..4 {
+        let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+        harness.blocks.push((block, request));
+    }
+
+    let mut ids: Vec<_> = harness.blocks.iter().map(|(block, _)| block.id()).collect();
+    ids.sort_unstable();
+    assert_eq!(ids, [0, 1, 2, 3]);
+
+    harness.check().unwrap();
+    harness.finish().unwrap();
+}
+
+proptest! {
+    #![proptest_config(harness::proptest_config())]
+
+    #[test]
+    fn compact_blocks_accounted(ops in ops(64 * 1024, 128)) {
+        let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+        harness.run(&ops)?;
+        harness.finish()?;
+    }
+}
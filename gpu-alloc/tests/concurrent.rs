@@ -0,0 +1,149 @@
+mod harness;
+
+use {
+    gpu_alloc::{
+        AllocationError, AllocationFlags, Config, DeviceMapError, MappedMemoryRange, MemoryDevice,
+        OutOfMemory, Request, ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    harness::{device_properties, Harness},
+    std::{ptr::NonNull, sync::Mutex, thread},
+};
+
+/// Mock device shared between threads.
+struct SharedDevice(Mutex<MockMemoryDevice>);
+
+impl AsRef<SharedDevice> for SharedDevice {
+    fn as_ref(&self) -> &SharedDevice {
+        self
+    }
+}
+
+impl MemoryDevice<usize> for SharedDevice {
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<usize, OutOfMemory> {
+        self.0
+            .lock()
+            .unwrap()
+            .allocate_memory(size, memory_type, flags)
+    }
+
+    unsafe fn deallocate_memory(&self, memory: usize) {
+        self.0.lock().unwrap().deallocate_memory(memory)
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut usize,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        self.0.lock().unwrap().map_memory(memory, offset, size)
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut usize) {
+        self.0.lock().unwrap().unmap_memory(memory)
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, usize>],
+    ) -> Result<(), OutOfMemory> {
+        self.0.lock().unwrap().invalidate_memory_ranges(ranges)
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, usize>],
+    ) -> Result<(), OutOfMemory> {
+        self.0.lock().unwrap().flush_memory_ranges(ranges)
+    }
+}
+
+const REQUEST: Request = Request {
+    size: 4096,
+    align_mask: 255,
+    usage: UsageFlags::FAST_DEVICE_ACCESS,
+    memory_types: 1,
+    kind: ResourceKind::Linear,
+};
+
+#[test]
+fn concurrent_dedicated() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(8));
+    let device = SharedDevice(Mutex::new(MockMemoryDevice::new(harness.device.props())));
+
+    let allocator = &harness.allocator;
+    let results: Vec<_> = thread::scope(|scope| {
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                let device = &device;
+                scope.spawn(move || {
+                    (0..4)
+                        .map(|_| unsafe { allocator.alloc_dedicated_concurrent(device, REQUEST) })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
+        threads
+            .into_iter()
+            .flat_map(|thread| thread.join().unwrap())
+            .collect()
+    });
+
+    let mut blocks = Vec::new();
+    for result in results {
+        match result {
+            Ok(block) => blocks.push(block),
+            Err(err) => assert_eq!(err, AllocationError::TooManyObjects),
+        }
+    }
+
+    assert_eq!(blocks.len(), 8, "memory object limit is respected");
+    assert_eq!(harness.allocator.remaining_allocations(), 0);
+    assert_eq!(harness.allocator.heap_usage(0), 8 * 4096);
+    assert_eq!(device.0.lock().unwrap().allocated_memory(0), 8 * 4096);
+    assert!(blocks.iter().all(|block| block.size() == 4096));
+
+    let stats = harness.allocator.stats().types[0].dedicated;
+    assert_eq!(
+        stats.blocks, 8,
+        "concurrent blocks are counted as dedicated"
+    );
+    assert_eq!(stats.memory_objects, 8);
+    assert_eq!(stats.memory_bytes, 8 * 4096);
+    assert_eq!(harness.allocator.reconcile(), Ok(()));
+
+    // Half of blocks are deallocated concurrently, the rest with exclusive access.
+    let exclusive = blocks.split_off(4);
+    let allocator = &harness.allocator;
+    thread::scope(|scope| {
+        for block in blocks {
+            let device = &device;
+            scope.spawn(move || unsafe { allocator.dealloc_concurrent(device, block) });
+        }
+    });
+    for block in exclusive {
+        unsafe { harness.allocator.dealloc(&device, block) }
+    }
+
+    assert_eq!(harness.allocator.remaining_allocations(), 8);
+    assert_eq!(harness.allocator.heap_usage(0), 0);
+    assert_eq!(device.0.lock().unwrap().live_allocations(), 0);
+    assert_eq!(harness.allocator.stats().types[0].dedicated.blocks, 0);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "must be deallocated with `GpuAllocator::dealloc`")]
+fn exclusive_block_dealloc_concurrent() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(8));
+    let block = unsafe { harness.allocator.alloc(&harness.device, REQUEST) }.unwrap();
+    unsafe { harness.allocator.dealloc_concurrent(&harness.device, block) }
+}
@@ -0,0 +1,60 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 255,
+        usage: UsageFlags::empty(),
+        memory_types: 1 << 0,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn deferred_blocks_are_released_after_completion() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    for (value, size) in [(3, 100), (1, 4000), (2, 50000)] {
+        let block = unsafe { harness.allocator.alloc(&harness.device, request(size)) }.unwrap();
+        harness.allocator.dealloc_deferred(block, value);
+    }
+    assert_eq!(harness.allocator.deferred_blocks(), 3);
+
+    unsafe { harness.allocator.flush_deferred(&harness.device, 0) };
+    assert_eq!(harness.allocator.deferred_blocks(), 3);
+    assert_eq!(harness.allocator.stats().types[0].total().blocks, 3);
+
+    unsafe { harness.allocator.flush_deferred(&harness.device, 2) };
+    assert_eq!(harness.allocator.deferred_blocks(), 1);
+    assert_eq!(harness.allocator.stats().types[0].total().blocks, 1);
+
+    unsafe { harness.allocator.flush_deferred(&harness.device, 3) };
+    assert_eq!(harness.allocator.deferred_blocks(), 0);
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn deferred_memory_is_not_reused_before_completion() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let request = request(1000);
+    let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    let range = (*block.memory(), block.offset());
+    harness.allocator.dealloc_deferred(block, 1);
+
+    let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    assert_ne!((*block.memory(), block.offset()), range);
+    harness.blocks.push((block, request));
+
+    unsafe { harness.allocator.flush_deferred(&harness.device, 1) };
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
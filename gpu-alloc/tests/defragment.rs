@@ -0,0 +1,205 @@
+mod harness;
+
+use {
+    gpu_alloc::{
+        AllocatorObserver, BlockEvent, Config, MemoryBlock, Request, ResourceKind, UsageFlags,
+    },
+    harness::{device_properties, Harness},
+    std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+/// Allocates many small blocks and keeps every fourth one,
+/// leaving chunks sparsely occupied.
+fn fragment(harness: &mut Harness) -> Vec<MemoryBlock<usize>> {
+    let all: Vec<_> = (0..64)
+        .map(|_| unsafe { harness.allocator.alloc(&harness.device, request(1024)) }.unwrap())
+        .collect();
+
+    let mut blocks = Vec::new();
+    for (index, mut block) in (0u32..).zip(all) {
+        if index % 4 == 0 {
+            unsafe { block.write_bytes(&harness.device, 0, &index.to_le_bytes()) }.unwrap();
+            blocks.push(block);
+        } else {
+            unsafe { harness.allocator.dealloc(&harness.device, block) }
+        }
+    }
+    blocks
+}
+
+#[test]
+fn sparse_chunks_are_evacuated() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let blocks = fragment(&mut harness);
+    let memory_objects = harness.device.live_allocations();
+    assert!(memory_objects > 1);
+
+    let mut defragmentation = unsafe { harness.allocator.defragment(&harness.device, blocks) };
+    assert!(!defragmentation.is_empty());
+    assert_eq!(
+        defragmentation.moved_bytes(),
+        defragmentation.len() as u64 * 1024
+    );
+
+    for (_, old, new) in defragmentation.moves_mut() {
+        assert_ne!(old.memory(), new.memory());
+        let mut data = [0; 4];
+        unsafe { old.read_bytes(&harness.device, 0, &mut data) }.unwrap();
+        unsafe { new.write_bytes(&harness.device, 0, &data) }.unwrap();
+    }
+
+    let blocks = unsafe {
+        harness
+            .allocator
+            .finish_defragmentation(&harness.device, defragmentation)
+    };
+    assert_eq!(blocks.len(), 16);
+
+    for (index, mut block) in blocks.into_iter().enumerate() {
+        let mut data = [0; 4];
+        unsafe { block.read_bytes(&harness.device, 0, &mut data) }.unwrap();
+        assert_eq!(u32::from_le_bytes(data), index as u32 * 4);
+        harness.blocks.push((block, request(1024)));
+    }
+
+    unsafe { harness.allocator.cleanup(&harness.device) }
+    assert!(harness.device.live_allocations() < memory_objects);
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn cancelled_defragmentation_keeps_blocks() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let blocks = fragment(&mut harness);
+    let memory_objects = harness.device.live_allocations();
+    let placement: Vec<_> = blocks
+        .iter()
+        .map(|block| (*block.memory(), block.offset()))
+        .collect();
+
+    let defragmentation = unsafe { harness.allocator.defragment(&harness.device, blocks) };
+    assert!(!defragmentation.is_empty());
+
+    let blocks = unsafe {
+        harness
+            .allocator
+            .cancel_defragmentation(&harness.device, defragmentation)
+    };
+    let kept: Vec<_> = blocks
+        .iter()
+        .map(|block| (*block.memory(), block.offset()))
+        .collect();
+    assert_eq!(kept, placement);
+    assert_eq!(harness.device.live_allocations(), memory_objects);
+
+    for block in blocks {
+        harness.blocks.push((block, request(1024)));
+    }
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn chunks_with_other_live_blocks_are_kept() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let mut blocks = fragment(&mut harness);
+
+    // Every chunk keeps a block that is not passed to `defragment`.
+    let mut pinned = Vec::new();
+    let mut index = 0;
+    while index < blocks.len() {
+        let memory = *blocks[index].memory();
+        if pinned
+            .iter()
+            .any(|block: &MemoryBlock<usize>| *block.memory() == memory)
+        {
+            index += 1;
+        } else {
+            pinned.push(blocks.remove(index));
+        }
+    }
+
+    let defragmentation = unsafe { harness.allocator.defragment(&harness.device, blocks) };
+    assert!(defragmentation.is_empty());
+
+    let blocks = unsafe {
+        harness
+            .allocator
+            .finish_defragmentation(&harness.device, defragmentation)
+    };
+    for block in blocks.into_iter().chain(pinned) {
+        harness.blocks.push((block, request(1024)));
+    }
+
+    harness.finish().unwrap();
+}
+
+/// Counts block events reported to observer.
+#[derive(Clone, Default)]
+struct Counter {
+    allocs: Arc<AtomicUsize>,
+    deallocs: Arc<AtomicUsize>,
+}
+
+impl AllocatorObserver for Counter {
+    fn on_alloc(&mut self, _event: &BlockEvent) {
+        self.allocs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn on_dealloc(&mut self, _event: &BlockEvent) {
+        self.deallocs.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[test]
+fn probe_blocks_bypass_block_cache() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    harness.allocator.set_block_cache(16);
+    let blocks = fragment(&mut harness);
+    let live = harness.allocator.stats().types[1].buddy.blocks;
+
+    let counter = Counter::default();
+    harness.allocator.set_observer(Box::new(counter.clone()));
+
+    let defragmentation = unsafe { harness.allocator.defragment(&harness.device, blocks) };
+    assert!(!defragmentation.is_empty());
+
+    // Only new blocks of moves are allocated, nothing is freed or cached.
+    assert_eq!(
+        counter.allocs.load(Ordering::Relaxed),
+        defragmentation.len()
+    );
+    assert_eq!(counter.deallocs.load(Ordering::Relaxed), 0);
+    assert_eq!(
+        harness.allocator.stats().types[1].buddy.blocks,
+        live + defragmentation.len() as u64
+    );
+
+    let blocks = unsafe {
+        harness
+            .allocator
+            .finish_defragmentation(&harness.device, defragmentation)
+    };
+    for block in blocks {
+        harness.blocks.push((block, request(1024)));
+    }
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,68 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Dedicated, MemoryBlock, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+const REQUEST: Request = Request {
+    size: 1024,
+    align_mask: 255,
+    usage: UsageFlags::FAST_DEVICE_ACCESS,
+    memory_types: 1,
+    kind: ResourceKind::Linear,
+};
+
+/// Allocates dedicated block. Memory of dedicated blocks in mock device is plain index,
+/// so block can be duplicated bitwise.
+fn alloc_dedicated(harness: &mut Harness) -> MemoryBlock<usize> {
+    unsafe {
+        harness
+            .allocator
+            .alloc_with_dedicated(&harness.device, REQUEST, Dedicated::Required)
+    }
+    .unwrap()
+}
+
+#[test]
+#[should_panic(expected = "It was either deallocated already or allocated by another allocator")]
+fn double_dealloc() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let block = alloc_dedicated(&mut harness);
+    let copy = unsafe { core::ptr::read(&block) };
+
+    unsafe {
+        harness.allocator.dealloc(&harness.device, block);
+        harness.allocator.dealloc(&harness.device, copy);
+    }
+}
+
+#[test]
+#[should_panic(expected = "It was either deallocated already or allocated by another allocator")]
+fn stale_dealloc() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let block = alloc_dedicated(&mut harness);
+    let stale = unsafe { core::ptr::read(&block) };
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+
+    // New block reuses id of the deallocated one.
+    let block = alloc_dedicated(&mut harness);
+    harness.blocks.push((block, REQUEST));
+
+    unsafe { harness.allocator.dealloc(&harness.device, stale) }
+}
+
+#[test]
+fn reused_id_deallocates() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    for _ in 0..4 {
+        let block = alloc_dedicated(&mut harness);
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+        harness.check().unwrap();
+    }
+
+    harness.finish().unwrap();
+}
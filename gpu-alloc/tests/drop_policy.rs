@@ -0,0 +1,60 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, GpuAllocator, Request, ResourceKind, UsageFlags},
+    gpu_alloc_mock::MockMemoryDevice,
+    harness::device_properties,
+};
+
+fn request() -> Request {
+    Request {
+        size: 1024,
+        align_mask: 0,
+        usage: UsageFlags::TRANSIENT,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn guard_cleans_up_on_drop() {
+    let device = MockMemoryDevice::new(device_properties(64));
+    let allocator = GpuAllocator::new(Config::i_am_potato(), device_properties(64));
+    let mut allocator = unsafe { allocator.cleanup_on_drop(&device) };
+
+    let block = unsafe { allocator.alloc(&device, request()) }.unwrap();
+    unsafe { allocator.dealloc(&device, block) }
+    assert_eq!(device.live_allocations(), 1);
+
+    drop(allocator);
+    assert_eq!(device.live_allocations(), 0);
+    device.check_invariants();
+}
+
+#[test]
+fn guard_releases_allocator() {
+    let device = MockMemoryDevice::new(device_properties(64));
+    let allocator = GpuAllocator::new(Config::i_am_potato(), device_properties(64));
+    let mut allocator = unsafe { allocator.cleanup_on_drop(&device) };
+
+    let block = unsafe { allocator.alloc(&device, request()) }.unwrap();
+    unsafe { allocator.dealloc(&device, block) }
+
+    let mut allocator = allocator.into_inner();
+    assert_eq!(device.live_allocations(), 1);
+
+    unsafe { allocator.cleanup(&device) }
+    assert_eq!(device.live_allocations(), 0);
+}
+
+#[test]
+fn leak_all_forgets_bookkeeping() {
+    let device = MockMemoryDevice::new(device_properties(64));
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device_properties(64));
+
+    let block = unsafe { allocator.alloc(&device, request()) }.unwrap();
+    core::mem::forget(block);
+
+    allocator.leak_all();
+    assert_eq!(device.live_allocations(), 1);
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "serde")]
+
+mod harness;
+
+use {
+    gpu_alloc::{AllocatorDump, ChunkKind, Config, Dedicated, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64, memory_types: u32) -> Request {
+    Request {
+        size,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn json_dump_round_trip() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let blocks = unsafe {
+        vec![
+            harness
+                .allocator
+                .alloc_tagged(&harness.device, request(1000, 1), "buddy")
+                .unwrap(),
+            harness
+                .allocator
+                .alloc(&harness.device, request(300, 1))
+                .unwrap(),
+            harness
+                .allocator
+                .alloc_with_dedicated_tagged(
+                    &harness.device,
+                    request(4096, 1),
+                    Dedicated::Required,
+                    "dedicated",
+                )
+                .unwrap(),
+        ]
+    };
+
+    let json = harness.allocator.dump_json();
+    let dump: AllocatorDump = serde_json::from_str(&json).unwrap();
+    assert_eq!(dump, harness.allocator.dump());
+
+    let chunks = &dump.types[0].chunks;
+    assert_eq!(chunks.len(), 2);
+
+    assert_eq!(chunks[0].kind, ChunkKind::Dedicated);
+    assert_eq!(chunks[0].size, 4096);
+    assert_eq!(chunks[0].blocks.len(), 1);
+    assert_eq!(chunks[0].blocks[0].tag.as_deref(), Some("dedicated"));
+
+    assert_eq!(chunks[1].kind, ChunkKind::Buddy);
+    assert_eq!(chunks[1].blocks.len(), 2);
+    assert!(chunks[1]
+        .blocks
+        .iter()
+        .any(|block| block.tag.as_deref() == Some("buddy") && block.requested_size == 1000));
+    assert!(!chunks[1].free.is_empty());
+
+    for block in blocks {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    assert!(harness
+        .allocator
+        .dump()
+        .types
+        .iter()
+        .all(|memory_type| memory_type.chunks.is_empty()));
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,112 @@
+mod harness;
+
+use {
+    core::ptr::NonNull,
+    gpu_alloc::{
+        AllocationFlags, Config, DeviceMapError, DeviceRef, DynMemoryDevice, MappedMemoryRange,
+        MemoryDevice, OutOfMemory, Request, ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    harness::{device_properties, Harness},
+};
+
+/// Device defined by application without `AsRef` implementation.
+struct AppDevice<'a>(&'a MockMemoryDevice);
+
+impl MemoryDevice<usize> for AppDevice<'_> {
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<usize, OutOfMemory> {
+        self.0.allocate_memory(size, memory_type, flags)
+    }
+
+    unsafe fn deallocate_memory(&self, memory: usize) {
+        self.0.deallocate_memory(memory)
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut usize,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        self.0.map_memory(memory, offset, size)
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut usize) {
+        self.0.unmap_memory(memory)
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, usize>],
+    ) -> Result<(), OutOfMemory> {
+        self.0.invalidate_memory_ranges(ranges)
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, usize>],
+    ) -> Result<(), OutOfMemory> {
+        self.0.flush_memory_ranges(ranges)
+    }
+}
+
+#[test]
+fn allocator_accepts_device_trait_object() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let device: &dyn MemoryDevice<usize> = &harness.device;
+    let device = DynMemoryDevice::new(device);
+
+    let request = Request {
+        size: 1024,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    };
+
+    let mut block = unsafe { harness.allocator.alloc(&device, request) }.unwrap();
+    unsafe { block.write_bytes(&device, 0, &[1, 2, 3, 4]) }.unwrap();
+
+    let mut data = [0; 4];
+    unsafe { block.read_bytes(&device, 0, &mut data) }.unwrap();
+    assert_eq!(data, [1, 2, 3, 4]);
+
+    unsafe { harness.allocator.dealloc(&device, block) }
+    unsafe { harness.allocator.cleanup(&device) }
+    assert_eq!(harness.device.live_allocations(), 0);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn allocator_accepts_device_ref() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let app_device = AppDevice(&harness.device);
+    let device = DeviceRef::new(&app_device);
+
+    let request = Request {
+        size: 1024,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    };
+
+    let mut block = unsafe { harness.allocator.alloc(&device, request) }.unwrap();
+    unsafe { block.write_bytes(&device, 0, &[1, 2, 3, 4]) }.unwrap();
+
+    let mut data = [0; 4];
+    unsafe { block.read_bytes(&device, 0, &mut data) }.unwrap();
+    assert_eq!(data, [1, 2, 3, 4]);
+
+    unsafe { harness.allocator.dealloc(&device, block) }
+    unsafe { harness.allocator.cleanup(&device) }
+    assert_eq!(harness.device.live_allocations(), 0);
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,50 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Dedicated, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1 << 0,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn efficiency_accounts_rounding_and_slack() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    assert_eq!(harness.allocator.stats().types[0].total().efficiency(), 1.0);
+
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(600)) }.unwrap();
+    harness.blocks.push((block, request(600)));
+
+    let buddy = harness.allocator.stats().types[0].buddy;
+    assert_eq!(buddy.requested_bytes, 600);
+    assert_eq!(buddy.rounding_bytes(), 1024 - 600);
+    assert_eq!(buddy.slack_bytes(), buddy.memory_bytes - 1024);
+    assert_eq!(
+        buddy.efficiency(),
+        600.0 / buddy.memory_bytes as f64,
+        "efficiency counts chunk slack"
+    );
+
+    let block = unsafe {
+        harness
+            .allocator
+            .alloc_with_dedicated(&harness.device, request(1000), Dedicated::Required)
+    }
+    .unwrap();
+    harness.blocks.push((block, request(1000)));
+
+    let dedicated = harness.allocator.stats().types[0].dedicated;
+    assert_eq!(dedicated.rounding_bytes(), 0);
+    assert_eq!(dedicated.slack_bytes(), 0);
+    assert_eq!(dedicated.efficiency(), 1.0);
+
+    harness.finish().unwrap();
+}
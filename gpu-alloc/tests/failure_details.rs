@@ -0,0 +1,163 @@
+mod harness;
+
+use {
+    gpu_alloc::{
+        AllocationError, AttemptOutcome, ChunkKind, Config, GpuAllocator, MemoryHeap, Request,
+        ResourceKind, UsageFlags,
+    },
+    harness::{device_properties, Harness},
+    std::borrow::Cow,
+};
+
+const HEAP_SIZE: u64 = 16 * 1024 * 1024;
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: (1 << 1) | (1 << 3),
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn exhausted_heap_lists_tried_types() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    assert!(harness.allocator.last_failure().is_none());
+
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(HEAP_SIZE)) }.unwrap();
+    harness.blocks.push((block, request(HEAP_SIZE)));
+
+    let err = unsafe { harness.allocator.alloc(&harness.device, request(HEAP_SIZE)) }.unwrap_err();
+    assert_eq!(err, AllocationError::OutOfBudget);
+
+    let details = harness.allocator.last_failure().unwrap();
+    assert_eq!(details.error(), AllocationError::OutOfBudget);
+    assert_eq!(details.request(), request(HEAP_SIZE));
+
+    let mut tried: Vec<u32> = details
+        .attempts()
+        .iter()
+        .map(|attempt| attempt.memory_type)
+        .collect();
+    tried.sort_unstable();
+    assert_eq!(tried, [1, 3]);
+
+    for attempt in details.attempts() {
+        assert_eq!(attempt.heap, 1);
+        assert_eq!(
+            attempt.outcome,
+            AttemptOutcome::Failed {
+                strategy: ChunkKind::Dedicated,
+                error: AllocationError::OutOfBudget,
+            }
+        );
+    }
+
+    assert_eq!(details.heaps().len(), 2);
+    assert_eq!(details.heaps()[1].used, HEAP_SIZE);
+    assert_eq!(details.heaps()[1].remaining(), 0);
+    assert_eq!(details.remaining_allocations(), 63);
+    assert!(
+        details.device_diagnostic().is_none(),
+        "device was not asked"
+    );
+
+    let report = details.to_string();
+    assert!(report.contains("memory type 1 (heap 1): Dedicated failed"));
+    assert!(report.contains("heap 1: 16777216 of 16777216 bytes used, 0 remaining"));
+
+    // Successful allocation keeps details of the last failure.
+    let other_heap = Request {
+        memory_types: 1 << 2,
+        ..request(1024)
+    };
+    let block = unsafe { harness.allocator.alloc(&harness.device, other_heap) }.unwrap();
+    harness.blocks.push((block, other_heap));
+    assert!(harness.allocator.last_failure().is_some());
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn too_many_objects_reports_strategy() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(1));
+
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(1024)) }.unwrap();
+    harness.blocks.push((block, request(1024)));
+
+    let err = unsafe {
+        harness
+            .allocator
+            .alloc(&harness.device, request(1024 * 1024))
+    }
+    .unwrap_err();
+    assert_eq!(err, AllocationError::TooManyObjects);
+
+    let details = harness.allocator.last_failure().unwrap();
+    assert_eq!(details.remaining_allocations(), 0);
+    assert_eq!(
+        details.attempts()[0].outcome,
+        AttemptOutcome::Failed {
+            strategy: ChunkKind::Dedicated,
+            error: AllocationError::TooManyObjects,
+        }
+    );
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn rejected_request_has_no_attempts() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let request = Request {
+        memory_types: 1 << 0,
+        ..request(1024)
+    };
+    let err = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap_err();
+    assert_eq!(err, AllocationError::NoCompatibleMemoryTypes);
+
+    let details = harness.allocator.last_failure().unwrap();
+    assert_eq!(details.error(), AllocationError::NoCompatibleMemoryTypes);
+    assert!(details.attempts().is_empty());
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn device_refusal_keeps_device_diagnostic() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    // Allocator believes heap is twice as large as device can provide.
+    let mut props = device_properties(64);
+    props.memory_heaps = Cow::Owned(vec![
+        MemoryHeap {
+            size: 64 * 1024 * 1024,
+            budget: None,
+            usage: None,
+        },
+        MemoryHeap {
+            size: 2 * HEAP_SIZE,
+            budget: None,
+            usage: None,
+        },
+    ]);
+    harness.allocator = GpuAllocator::new(Config::i_am_potato(), props);
+
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(HEAP_SIZE)) }.unwrap();
+    harness.blocks.push((block, request(HEAP_SIZE)));
+
+    let err = unsafe { harness.allocator.alloc(&harness.device, request(HEAP_SIZE)) }.unwrap_err();
+    assert_eq!(err, AllocationError::OutOfDeviceMemory);
+
+    let details = harness.allocator.last_failure().unwrap();
+    let diagnostic = details.device_diagnostic().unwrap();
+    assert_eq!(diagnostic.code, -2);
+    assert!(details
+        .to_string()
+        .contains("device: Mock heap capacity exhausted (code -2)"));
+
+    harness.finish().unwrap();
+}
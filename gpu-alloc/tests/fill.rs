@@ -0,0 +1,42 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, MapError, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+#[test]
+fn fill_byte_and_pattern() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    // Non-coherent memory type requires flushing.
+    let request = Request {
+        size: 256,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: 1 << 3,
+        kind: ResourceKind::Linear,
+    };
+    let mut block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+
+    unsafe { block.fill(&harness.device, 0, 64, 0xAB) }.unwrap();
+    unsafe { block.fill_pattern(&harness.device, 5, 10, &[0xDE, 0xAD, 0xBE, 0xEF]) }.unwrap();
+
+    let mut read = [0; 64];
+    unsafe { block.read_bytes(&harness.device, 0, &mut read) }.unwrap();
+    assert_eq!(read[..5], [0xAB; 5]);
+    assert_eq!(
+        read[5..15],
+        [0xDE, 0xAD, 0xBE, 0xEF, 0xDE, 0xAD, 0xBE, 0xEF, 0xDE, 0xAD]
+    );
+    assert_eq!(read[15..], [0xAB; 49]);
+
+    let size = block.size() as usize;
+    assert_eq!(
+        unsafe { block.fill(&harness.device, 1, size, 0) },
+        Err(MapError::OutOfBounds)
+    );
+
+    harness.blocks.push((block, request));
+    harness.finish().unwrap();
+}
@@ -0,0 +1,129 @@
+mod harness;
+
+use {
+    gpu_alloc::{
+        AllocationError, Config, Dedicated, DedicatedAllocationInfo, Request, ResourceKind,
+        UsageFlags,
+    },
+    harness::{device_properties, ops, Harness},
+    proptest::prelude::*,
+};
+
+fn harness(max_memory_allocation_count: u32) -> Harness {
+    let mut config = Config::i_am_potato();
+    config.force_dedicated = true;
+    Harness::new(config, device_properties(max_memory_allocation_count))
+}
+
+#[test]
+fn every_block_gets_own_memory_object() {
+    let mut harness = harness(64);
+
+    for &usage in &[
+        UsageFlags::FAST_DEVICE_ACCESS,
+        UsageFlags::TRANSIENT,
+        UsageFlags::HOST_ACCESS,
+    ] {
+        let request = Request {
+            size: 256,
+            align_mask: 0,
+            usage,
+            memory_types: 1 << 1,
+            kind: ResourceKind::Linear,
+        };
+        let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+        assert_eq!(block.offset(), 0);
+        assert_eq!(block.size(), 256);
+        harness.blocks.push((block, request));
+        harness.check().unwrap();
+    }
+
+    assert_eq!(harness.device.live_allocations(), 3);
+    let stats = harness.allocator.stats();
+    assert_eq!(stats.types[1].dedicated.blocks, 3);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn respects_memory_object_limit() {
+    let mut harness = harness(1);
+
+    let request = Request {
+        size: 256,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    };
+    let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    harness.blocks.push((block, request));
+
+    let err = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap_err();
+    assert_eq!(err, AllocationError::TooManyObjects);
+
+    harness.finish().unwrap();
+}
+
+proptest! {
+    #![proptest_config(harness::proptest_config())]
+
+    #[test]
+    fn forced_dedicated_accounted(ops in ops(64 * 1024, 64)) {
+        let mut harness = harness(1024);
+        harness.run(&ops)?;
+        harness.finish()?;
+    }
+}
+
+#[test]
+fn dedicated_allocation_info_reaches_device() {
+    let mut props = device_properties(64);
+    props.buffer_image_granularity = 1024;
+    let mut harness = Harness::new(Config::i_am_potato(), props);
+
+    for (info, kind) in [
+        (DedicatedAllocationInfo::Buffer(42), ResourceKind::Linear),
+        (DedicatedAllocationInfo::Image(7), ResourceKind::NonLinear),
+    ] {
+        let request = Request {
+            size: 100,
+            align_mask: 0,
+            usage: UsageFlags::FAST_DEVICE_ACCESS,
+            memory_types: 1 << 0,
+            kind,
+        };
+        let block = unsafe {
+            harness
+                .allocator
+                .alloc_dedicated_for(&harness.device, request, info)
+        }
+        .unwrap();
+
+        // Memory object is allocated with exact size of the resource.
+        assert_eq!(harness.device.memory_size(*block.memory()), Some(100));
+        assert_eq!(harness.device.dedicated_info(*block.memory()), Some(info));
+        harness.blocks.push((block, request));
+        harness.check().unwrap();
+    }
+
+    // Regular allocations carry no dedicated info.
+    let request = Request {
+        size: 100,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1 << 0,
+        kind: ResourceKind::Linear,
+    };
+    let block = unsafe {
+        harness
+            .allocator
+            .alloc_with_dedicated(&harness.device, request, Dedicated::Required)
+    }
+    .unwrap();
+    assert_eq!(harness.device.dedicated_info(*block.memory()), None);
+    harness.blocks.push((block, request));
+
+    assert_eq!(harness.allocator.stats().types[0].dedicated.blocks, 3);
+    harness.finish().unwrap();
+}
@@ -0,0 +1,63 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64, usage: UsageFlags) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+fn alloc(harness: &mut Harness, size: u64, usage: UsageFlags) {
+    let request = request(size, usage);
+    let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    harness.blocks.push((block, request));
+    harness.check().unwrap();
+}
+
+#[test]
+fn report_covers_every_memory_type() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    alloc(&mut harness, 300, UsageFlags::FAST_DEVICE_ACCESS);
+
+    let report = harness.allocator.fragmentation_report();
+    assert_eq!(report.len(), 4);
+    assert_eq!(report[1], harness.allocator.fragmentation(1));
+    assert!(report
+        .iter()
+        .enumerate()
+        .filter(|&(index, _)| index != 1)
+        .all(|(_, stats)| stats.free_bytes == 0 && stats.free_region_histogram.is_empty()));
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn buddy_rounding_is_reported() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    // 300 bytes are rounded up to 512 bytes buddy block.
+    alloc(&mut harness, 300, UsageFlags::FAST_DEVICE_ACCESS);
+
+    let stats = harness.allocator.fragmentation(1);
+    assert_eq!(stats.buddy_rounding_bytes, 512 - 300);
+    assert_eq!(stats.unusable_bytes, 512 - 300);
+
+    // Free buddies of 512 bytes to 4KiB are left after splitting 8KiB chunk.
+    let free: Vec<(u64, u64)> = stats
+        .free_region_buckets()
+        .filter(|&(_, count)| count > 0)
+        .collect();
+    assert_eq!(free, [(512, 1), (1024, 1), (2048, 1), (4096, 1)]);
+    assert_eq!(stats.free_bytes, 8192 - 512);
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,115 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn harness(dedicated_threshold: u64) -> Harness {
+    let mut config = Config::i_am_potato();
+    config.dedicated_threshold = dedicated_threshold;
+    config.preferred_dedicated_threshold = dedicated_threshold;
+    config.transient_dedicated_threshold = dedicated_threshold;
+    config.starting_free_list_chunk = 1024;
+    config.final_free_list_chunk = 1024;
+    Harness::new(config, device_properties(64))
+}
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::TRANSIENT,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn free_chunks_released_on_dealloc() {
+    // Chunks grow to at most 1025 bytes, so each block takes whole chunk.
+    let mut harness = harness(1025);
+
+    let blocks: Vec<_> = (0..32)
+        .map(|_| unsafe { harness.allocator.alloc(&harness.device, request(1024)) }.unwrap())
+        .collect();
+    assert_eq!(harness.device.live_allocations(), 32);
+
+    for (index, block) in blocks.into_iter().enumerate() {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+
+        // The newest chunk is kept for reuse.
+        let expected = (31 - index as u64).max(1);
+        assert_eq!(harness.device.live_allocations(), expected);
+    }
+
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(1024)) }.unwrap();
+    assert_eq!(
+        harness.device.total_allocations(),
+        32,
+        "newest chunk is reused"
+    );
+    harness.blocks.push((block, request(1024)));
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn outgrown_newest_chunk_released() {
+    let mut harness = harness(4096);
+
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(1024)) }.unwrap();
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+    assert_eq!(harness.device.live_allocations(), 1);
+
+    // Request does not fit the free 1024 bytes chunk, so it is replaced.
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(2048)) }.unwrap();
+    assert_eq!(harness.device.live_allocations(), 1);
+    assert_eq!(harness.device.total_allocations(), 2);
+    harness.blocks.push((block, request(2048)));
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn pinned_chunks_spill_to_buddy() {
+    // Rest of a chunk is too small for another block,
+    // so each block pins new chunk.
+    let mut harness = harness(1025);
+
+    for _ in 0..5 {
+        let block = unsafe { harness.allocator.alloc(&harness.device, request(600)) }.unwrap();
+        harness.blocks.push((block, request(600)));
+    }
+    assert_eq!(harness.device.live_allocations(), 5);
+    assert_eq!(harness.allocator.stats().types[1].buddy.blocks, 0);
+
+    // Four chunks besides the newest one are pinned.
+    for _ in 0..2 {
+        let block = unsafe { harness.allocator.alloc(&harness.device, request(600)) }.unwrap();
+        harness.blocks.push((block, request(600)));
+    }
+
+    let stats = harness.allocator.stats().types[1];
+    assert_eq!(stats.free_list.blocks, 5);
+    assert_eq!(
+        stats.buddy.blocks, 2,
+        "transient requests are served by buddy allocator"
+    );
+    harness.check().unwrap();
+
+    // Releasing long-lived blocks stops spilling.
+    let pinned: Vec<_> = harness.blocks.drain(..4).collect();
+    for (block, _) in pinned {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(600)) }.unwrap();
+    harness.blocks.push((block, request(600)));
+    let stats = harness.allocator.stats().types[1];
+    assert_eq!(stats.free_list.blocks, 2);
+    assert_eq!(stats.buddy.blocks, 2);
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,122 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+const GRANULARITY: u64 = 1024;
+
+fn harness() -> Harness {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+    harness.device.set_buffer_image_granularity(GRANULARITY);
+    harness
+}
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: !0,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn resources_of_same_kind_share_page() {
+    let mut harness = harness();
+
+    let blocks: Vec<_> = (0..4)
+        .map(|_| unsafe { harness.allocator.alloc(&harness.device, request(256)) }.unwrap())
+        .collect();
+
+    let resources: Vec<_> = blocks
+        .iter()
+        .map(|block| {
+            harness.device.bind_resource(
+                *block.memory(),
+                block.offset(),
+                block.size(),
+                ResourceKind::Linear,
+            )
+        })
+        .collect();
+
+    for resource in resources {
+        harness.device.unbind_resource(resource);
+    }
+
+    for block in blocks {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    harness.finish().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "shares granularity page")]
+fn linear_and_non_linear_resources_conflict() {
+    let mut harness = harness();
+
+    // Blocks are not tracked by harness as unwinding leaves resources bound.
+    let linear = unsafe { harness.allocator.alloc(&harness.device, request(256)) }.unwrap();
+    let non_linear = unsafe { harness.allocator.alloc(&harness.device, request(256)) }.unwrap();
+
+    assert_eq!(linear.memory(), non_linear.memory());
+    assert!(linear.offset() / GRANULARITY == non_linear.offset() / GRANULARITY);
+
+    harness.device.bind_resource(
+        *linear.memory(),
+        linear.offset(),
+        linear.size(),
+        ResourceKind::Linear,
+    );
+
+    harness.device.bind_resource(
+        *non_linear.memory(),
+        non_linear.offset(),
+        non_linear.size(),
+        ResourceKind::NonLinear,
+    );
+}
+
+#[test]
+fn non_linear_blocks_do_not_share_page_with_linear_ones() {
+    let mut props = device_properties(32);
+    props.buffer_image_granularity = GRANULARITY;
+    let mut harness = Harness::new(Config::i_am_potato(), props);
+
+    let mut resources = Vec::new();
+    for index in 0..8 {
+        let kind = if index % 2 == 0 {
+            ResourceKind::Linear
+        } else {
+            ResourceKind::NonLinear
+        };
+        let request = Request {
+            kind,
+            ..request(256)
+        };
+
+        let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+        if kind == ResourceKind::NonLinear {
+            assert_eq!(block.offset() % GRANULARITY, 0);
+            assert_eq!(block.size() % GRANULARITY, 0);
+        }
+
+        resources.push(harness.device.bind_resource(
+            *block.memory(),
+            block.offset(),
+            block.size(),
+            kind,
+        ));
+        harness.blocks.push((block, request));
+    }
+    harness.check().unwrap();
+
+    for resource in resources {
+        harness.device.unbind_resource(resource);
+    }
+    harness.finish().unwrap();
+}
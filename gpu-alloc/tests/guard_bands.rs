@@ -0,0 +1,105 @@
+#![cfg(feature = "guard-bands")]
+
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, ops, Harness},
+    proptest::prelude::*,
+};
+
+fn guarded_harness() -> Harness {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+    harness.allocator.set_guard_band(64);
+    harness
+}
+
+/// Allocates block, fills it completely and deallocates it.
+fn fill_whole_block(usage: UsageFlags, memory_types: u32) {
+    let mut harness = guarded_harness();
+
+    let request = Request {
+        size: 200,
+        align_mask: 255,
+        usage: usage | UsageFlags::HOST_ACCESS,
+        memory_types,
+        kind: ResourceKind::Linear,
+    };
+
+    let mut block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    assert_eq!(block.offset() & 255, 0, "guard band must keep alignment");
+    assert!(block.size() >= 200);
+
+    let size = block.size() as usize;
+    unsafe { block.write_bytes(&harness.device, 0, &vec![0x11; size]) }.unwrap();
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn buddy_in_bounds() {
+    fill_whole_block(UsageFlags::empty(), 1 << 1);
+    fill_whole_block(UsageFlags::empty(), 1 << 3);
+}
+
+#[test]
+fn free_list_in_bounds() {
+    fill_whole_block(UsageFlags::TRANSIENT, 1 << 1);
+    fill_whole_block(UsageFlags::TRANSIENT, 1 << 3);
+}
+
+/// Writes single byte at `offset` relative to the block start
+/// bypassing bounds checks and deallocates the block.
+fn write_out_of_bounds(usage: UsageFlags, offset: isize) {
+    let mut harness = guarded_harness();
+
+    let request = Request {
+        size: 256,
+        align_mask: 63,
+        usage: usage | UsageFlags::HOST_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    };
+
+    let mut block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    let size = block.size();
+
+    unsafe {
+        let ptr = block.map(&harness.device, 0, size as usize).unwrap();
+        let end = if offset < 0 { 0 } else { size as isize };
+        ptr.as_ptr().offset(end + offset).write(0);
+        block.unmap(&harness.device);
+    }
+
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+}
+
+#[test]
+#[should_panic(expected = "Guard band after memory block")]
+fn buddy_overrun() {
+    write_out_of_bounds(UsageFlags::empty(), 0);
+}
+
+#[test]
+#[should_panic(expected = "Guard band before memory block")]
+fn buddy_underrun() {
+    write_out_of_bounds(UsageFlags::empty(), -1);
+}
+
+#[test]
+#[should_panic(expected = "Guard band after memory block")]
+fn free_list_overrun() {
+    write_out_of_bounds(UsageFlags::TRANSIENT, 63);
+}
+
+proptest! {
+    #![proptest_config(harness::proptest_config())]
+
+    #[test]
+    fn guarded_accounting(ops in ops(4 * 1024 * 1024, 128)) {
+        let mut harness = guarded_harness();
+        harness.run(&ops)?;
+        harness.finish()?;
+    }
+}
@@ -0,0 +1,375 @@
+//!
+//! Reusable harness for property-based tests of `GpuAllocator`.
+//!
+//! [`Harness`] runs [`Op`]s against `MockMemoryDevice` and
+//! [`Harness::check`] verifies that allocator bookkeeping agrees with the device.
+//! New properties can be added by generating different operation streams
+//! with [`ops`] or by extending [`Harness::check`].
+//!
+
+#![allow(dead_code)]
+
+use {
+    gpu_alloc::{
+        ChunkKind, Config, Dedicated, DeviceProperties, GpuAllocator, MemoryBlock, MemoryHeap,
+        MemoryPropertyFlags, MemoryType, Request, ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    proptest::{
+        prelude::*,
+        test_runner::{Config as ProptestConfig, TestCaseError},
+    },
+    std::borrow::Cow,
+};
+
+/// Proptest configuration for allocator tests.
+///
+/// Under Miri number of cases is reduced and failure persistence
+/// is disabled as it requires file system access.
+pub fn proptest_config() -> ProptestConfig {
+    if cfg!(miri) {
+        ProptestConfig {
+            cases: 4,
+            failure_persistence: None,
+            ..ProptestConfig::default()
+        }
+    } else {
+        ProptestConfig::default()
+    }
+}
+
+/// Single operation applied to the allocator.
+#[derive(Clone, Copy, Debug)]
+pub enum Op {
+    /// Allocate memory block.
+    Alloc {
+        request: Request,
+        dedicated: Option<Dedicated>,
+    },
+
+    /// Deallocate live block with index wrapped around number of live blocks.
+    Dealloc { index: usize },
+
+    /// Call `GpuAllocator::cleanup`.
+    Cleanup,
+}
+
+/// Device with device-local, host-visible, shared and non-coherent cached memory types
+/// spread over two heaps.
+pub fn device_properties(max_memory_allocation_count: u32) -> DeviceProperties<'static> {
+    DeviceProperties {
+        memory_types: Cow::Owned(vec![
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+            },
+            MemoryType {
+                heap: 0,
+                props: MemoryPropertyFlags::DEVICE_LOCAL
+                    | MemoryPropertyFlags::HOST_VISIBLE
+                    | MemoryPropertyFlags::HOST_COHERENT,
+            },
+            MemoryType {
+                heap: 1,
+                props: MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_CACHED,
+            },
+        ]),
+        memory_heaps: Cow::Owned(vec![
+            MemoryHeap {
+                size: 64 * 1024 * 1024,
+                budget: None,
+                usage: None,
+            },
+            MemoryHeap {
+                size: 16 * 1024 * 1024,
+                budget: None,
+                usage: None,
+            },
+        ]),
+        max_memory_allocation_count,
+        max_memory_allocation_size: 16 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_image_granularity: 1,
+        buffer_device_address: true,
+    }
+}
+
+/// Strategy producing requests up to `max_size` bytes.
+pub fn request(max_size: u64) -> impl Strategy<Value = Request> {
+    (1..=max_size, 0u32..10, any::<u8>(), 1u32..16).prop_map(
+        |(size, align_log2, usage, memory_types)| Request {
+            size,
+            align_mask: (1 << align_log2) - 1,
+            usage: UsageFlags::from_bits_truncate(usage),
+            memory_types,
+            kind: ResourceKind::Linear,
+        },
+    )
+}
+
+/// Strategy producing single operation.
+pub fn op(max_size: u64) -> impl Strategy<Value = Op> {
+    let dedicated = prop_oneof![
+        Just(None),
+        Just(Some(Dedicated::Required)),
+        Just(Some(Dedicated::Preferred)),
+    ];
+
+    prop_oneof![
+        6 => (request(max_size), dedicated)
+            .prop_map(|(request, dedicated)| Op::Alloc { request, dedicated }),
+        4 => any::<usize>().prop_map(|index| Op::Dealloc { index }),
+        1 => Just(Op::Cleanup),
+    ]
+}
+
+/// Strategy producing sequence of up to `max_len` operations.
+pub fn ops(max_size: u64, max_len: usize) -> impl Strategy<Value = Vec<Op>> {
+    proptest::collection::vec(op(max_size), 0..max_len)
+}
+
+/// Allocator driven against mock device.
+pub struct Harness {
+    pub device: MockMemoryDevice,
+    pub allocator: GpuAllocator<usize>,
+    pub blocks: Vec<(MemoryBlock<usize>, Request)>,
+}
+
+impl Harness {
+    pub fn new(config: Config, props: DeviceProperties<'static>) -> Self {
+        let device = MockMemoryDevice::new(props);
+        let allocator = GpuAllocator::new(config, device.props());
+
+        Harness {
+            device,
+            allocator,
+            blocks: Vec::new(),
+        }
+    }
+
+    /// Applies operation. Failed allocations are ignored.
+    pub fn apply(&mut self, op: Op) {
+        match op {
+            Op::Alloc { request, dedicated } => {
+                let result = unsafe {
+                    match dedicated {
+                        None => self.allocator.alloc(&self.device, request),
+                        Some(dedicated) => {
+                            self.allocator
+                                .alloc_with_dedicated(&self.device, request, dedicated)
+                        }
+                    }
+                };
+
+                if let Ok(block) = result {
+                    self.blocks.push((block, request));
+                }
+            }
+            Op::Dealloc { index } => {
+                if !self.blocks.is_empty() {
+                    let (block, _) = self.blocks.swap_remove(index % self.blocks.len());
+                    unsafe { self.allocator.dealloc(&self.device, block) }
+                }
+            }
+            Op::Cleanup => unsafe { self.allocator.cleanup(&self.device) },
+        }
+    }
+
+    /// Applies all operations checking invariants after each one.
+    pub fn run(&mut self, ops: &[Op]) -> Result<(), TestCaseError> {
+        for &op in ops {
+            self.apply(op);
+            self.check()?;
+        }
+        Ok(())
+    }
+
+    /// Checks that allocator accounting matches the device
+    /// and that live blocks are placed correctly.
+    pub fn check(&self) -> Result<(), TestCaseError> {
+        self.device.check_invariants();
+
+        let props = self.device.props();
+
+        for heap in 0..props.memory_heaps.len() as u32 {
+            prop_assert_eq!(
+                self.allocator.heap_usage(heap),
+                self.device.allocated_memory(heap),
+                "heap {} usage diverged from device",
+                heap
+            );
+        }
+
+        prop_assert_eq!(self.allocator.reconcile(), Ok(()));
+
+        prop_assert_eq!(
+            u64::from(self.allocator.remaining_allocations()) + self.device.live_allocations(),
+            u64::from(props.max_memory_allocation_count),
+            "memory objects count diverged from device"
+        );
+
+        let stats = self.allocator.stats();
+
+        for (heap, heap_stats) in stats.heaps.iter().enumerate() {
+            prop_assert_eq!(heap_stats.used, self.device.allocated_memory(heap as u32));
+            prop_assert_eq!(heap_stats.total().memory_bytes, heap_stats.used);
+        }
+
+        for (index, type_stats) in stats.types.iter().enumerate() {
+            let fragmentation = self.allocator.fragmentation(index as u32);
+            let total = type_stats.total();
+            prop_assert!(total.requested_bytes <= total.allocated_bytes);
+            prop_assert_eq!(
+                total.requested_bytes + total.rounding_bytes() + total.slack_bytes(),
+                total.memory_bytes,
+                "requested bytes and overhead of memory type {} do not add up",
+                index
+            );
+            prop_assert_eq!(
+                fragmentation.free_bytes
+                    + type_stats.buddy.allocated_bytes
+                    + type_stats.free_list.allocated_bytes,
+                type_stats.buddy.memory_bytes + type_stats.free_list.memory_bytes,
+                "free and allocated bytes of memory type {} do not add up",
+                index
+            );
+            prop_assert!(fragmentation.largest_free_region <= fragmentation.free_bytes);
+            prop_assert!(fragmentation.buddy_rounding_bytes <= fragmentation.unusable_bytes);
+            prop_assert_eq!(
+                fragmentation
+                    .free_region_buckets()
+                    .filter(|&(_, count)| count > 0)
+                    .last()
+                    .map(|(size, _)| size),
+                Some(fragmentation.largest_free_region)
+                    .filter(|&size| size > 0)
+                    .map(|size| 1 << (63 - size.leading_zeros())),
+                "largest free region of memory type {} is not in the last histogram bucket",
+                index
+            );
+            prop_assert_eq!(fragmentation.buddy_chunks, type_stats.buddy.memory_objects);
+            prop_assert_eq!(
+                fragmentation.unusable_bytes,
+                total.allocated_bytes - total.requested_bytes
+            );
+        }
+
+        let dump = self.allocator.dump();
+        let mut dumped_blocks = 0;
+
+        for memory_type in &dump.types {
+            for chunk in &memory_type.chunks {
+                dumped_blocks += chunk.blocks.len();
+
+                let mut ranges: Vec<(u64, u64)> = chunk
+                    .blocks
+                    .iter()
+                    .map(|block| (block.offset, block.size))
+                    .chain(chunk.free.iter().map(|region| (region.offset, region.size)))
+                    .collect();
+                ranges.sort_unstable();
+
+                if chunk.kind != ChunkKind::Dedicated {
+                    let mut end = 0;
+                    for (offset, size) in ranges {
+                        prop_assert_eq!(offset, end, "gap or overlap in chunk {:?}", chunk);
+                        end = offset + size;
+                    }
+                    prop_assert_eq!(end, chunk.size, "chunk {:?} is not covered", chunk);
+                }
+            }
+        }
+
+        prop_assert_eq!(dumped_blocks, self.blocks.len());
+
+        let total_objects: u64 = stats.heaps.iter().map(|h| h.total().memory_objects).sum();
+        prop_assert_eq!(total_objects, self.device.live_allocations());
+
+        let total_blocks: u64 = stats.types.iter().map(|t| t.total().blocks).sum();
+        prop_assert_eq!(total_blocks, self.blocks.len() as u64);
+
+        let total_requested: u64 = stats.types.iter().map(|t| t.total().requested_bytes).sum();
+        let total_allocated: u64 = stats.types.iter().map(|t| t.total().allocated_bytes).sum();
+        prop_assert_eq!(
+            total_requested,
+            self.blocks.iter().map(|(_, r)| r.size).sum::<u64>()
+        );
+        prop_assert_eq!(
+            total_allocated,
+            self.blocks
+                .iter()
+                .map(|(b, _)| allocated_size(b))
+                .sum::<u64>()
+        );
+
+        let mut ranges = Vec::with_capacity(self.blocks.len());
+
+        for (block, request) in &self.blocks {
+            prop_assert_ne!(request.memory_types & (1 << block.memory_type()), 0);
+            prop_assert!(block.size() >= request.size);
+            prop_assert_eq!(block.offset() & request.align_mask, 0);
+
+            let memory = *block.memory();
+            let memory_size = self.device.memory_size(memory);
+            prop_assert!(memory_size.is_some(), "block references freed memory");
+            prop_assert!(block.offset() + block.size() <= memory_size.unwrap());
+
+            ranges.push((memory, block.offset(), block.offset() + block.size()));
+        }
+
+        ranges.sort_unstable();
+
+        for pair in ranges.windows(2) {
+            if pair[0].0 == pair[1].0 {
+                prop_assert!(
+                    pair[0].2 <= pair[1].1,
+                    "blocks {:?} and {:?} overlap",
+                    pair[0],
+                    pair[1]
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deallocates all live blocks and cleans allocator up,
+    /// checking that no memory objects remain.
+    pub fn finish(mut self) -> Result<(), TestCaseError> {
+        for (block, _) in self.blocks.drain(..) {
+            unsafe { self.allocator.dealloc(&self.device, block) }
+        }
+
+        unsafe { self.allocator.cleanup(&self.device) }
+        self.check()?;
+
+        prop_assert_eq!(self.device.live_allocations(), 0, "memory objects leaked");
+        Ok(())
+    }
+}
+
+impl Drop for Harness {
+    fn drop(&mut self) {
+        // Release blocks left after failed check to avoid drop-bomb noise.
+        for (block, _) in self.blocks.drain(..) {
+            unsafe { self.allocator.dealloc(&self.device, block) }
+        }
+    }
+}
+
+/// Returns size of memory occupied by the block, including guard bands.
+fn allocated_size(block: &MemoryBlock<usize>) -> u64 {
+    #[cfg(feature = "guard-bands")]
+    {
+        block.size() + 2 * block.guard_band()
+    }
+
+    #[cfg(not(feature = "guard-bands"))]
+    {
+        block.size()
+    }
+}
@@ -0,0 +1,65 @@
+#![cfg(feature = "stats")]
+
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, SizeHistogram, UsageFlags, SIZE_HISTOGRAM_BUCKETS},
+    harness::{device_properties, Harness},
+};
+
+#[test]
+fn bucket_bounds() {
+    assert_eq!(SizeHistogram::bucket(0), 0);
+    assert_eq!(SizeHistogram::bucket(1), 0);
+    assert_eq!(SizeHistogram::bucket(2), 1);
+    assert_eq!(SizeHistogram::bucket(3), 2);
+    assert_eq!(SizeHistogram::bucket(4), 2);
+    assert_eq!(SizeHistogram::bucket(5), 3);
+    assert_eq!(SizeHistogram::bucket(u64::MAX), SIZE_HISTOGRAM_BUCKETS - 1);
+
+    for index in 0..SIZE_HISTOGRAM_BUCKETS - 1 {
+        let bound = SizeHistogram::bucket_upper_bound(index);
+        assert_eq!(SizeHistogram::bucket(bound), index);
+        assert_eq!(SizeHistogram::bucket(bound + 1), index + 1);
+    }
+}
+
+#[test]
+fn requests_recorded_per_memory_type() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let sizes = [100, 128, 129, 4096, 1 << 20];
+
+    let blocks: Vec<_> = sizes
+        .iter()
+        .map(|&size| {
+            let request = Request {
+                size,
+                align_mask: 0,
+                usage: UsageFlags::FAST_DEVICE_ACCESS,
+                memory_types: 1,
+                kind: ResourceKind::Linear,
+            };
+            unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap()
+        })
+        .collect();
+
+    let histogram = harness.allocator.size_histogram(0);
+    assert_eq!(histogram.total(), sizes.len() as u64);
+    assert_eq!(histogram.buckets()[7], 2);
+    assert_eq!(histogram.buckets()[8], 1);
+    assert_eq!(histogram.buckets()[12], 1);
+    assert_eq!(histogram.buckets()[20], 1);
+    assert_eq!(harness.allocator.size_histogram(1).total(), 0);
+
+    for block in blocks {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    assert_eq!(
+        harness.allocator.size_histogram(0).total(),
+        sizes.len() as u64
+    );
+
+    harness.finish().unwrap();
+}
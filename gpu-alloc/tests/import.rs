@@ -0,0 +1,75 @@
+mod harness;
+
+use {
+    gpu_alloc::{
+        AllocationFlags, Config, MemoryDevice, MemoryPropertyFlags, Request, ResourceKind,
+        UsageFlags,
+    },
+    harness::{device_properties, Harness},
+};
+
+const SIZE: u64 = 8192;
+
+fn import(harness: &mut Harness, memory_type: u32, props: MemoryPropertyFlags) {
+    let memory = unsafe {
+        harness
+            .device
+            .allocate_memory(SIZE, memory_type, AllocationFlags::empty())
+    }
+    .unwrap();
+
+    let block = unsafe {
+        harness
+            .allocator
+            .import_memory(memory, memory_type, props, 0, SIZE)
+    };
+    assert_eq!(block.memory_type(), memory_type);
+    assert_eq!(block.size(), SIZE);
+
+    // Imported block is accounted as requested in full.
+    let request = Request {
+        size: SIZE,
+        align_mask: 0,
+        usage: UsageFlags::empty(),
+        memory_types: 1 << memory_type,
+        kind: ResourceKind::Linear,
+    };
+    harness.blocks.push((block, request));
+    harness.check().unwrap();
+}
+
+#[test]
+fn imported_memory_is_tracked_and_deallocated() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let remaining = harness.allocator.remaining_allocations();
+
+    import(&mut harness, 0, MemoryPropertyFlags::DEVICE_LOCAL);
+
+    let stats = harness.allocator.stats();
+    assert_eq!(stats.types[0].dedicated.memory_objects, 1);
+    assert_eq!(stats.types[0].dedicated.memory_bytes, SIZE);
+    assert_eq!(stats.heaps[0].used, SIZE);
+    assert_eq!(harness.allocator.remaining_allocations(), remaining - 1);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn imported_memory_can_be_mapped() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    import(
+        &mut harness,
+        1,
+        MemoryPropertyFlags::HOST_VISIBLE | MemoryPropertyFlags::HOST_COHERENT,
+    );
+
+    let (block, _) = &mut harness.blocks[0];
+    unsafe { block.write_bytes(&harness.device, 100, &[1, 2, 3, 4]) }.unwrap();
+
+    let mut bytes = [0; 4];
+    unsafe { block.read_bytes(&harness.device, 100, &mut bytes) }.unwrap();
+    assert_eq!(bytes, [1, 2, 3, 4]);
+
+    harness.finish().unwrap();
+}
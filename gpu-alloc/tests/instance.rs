@@ -0,0 +1,40 @@
+#![cfg(feature = "debug-checks")]
+
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+const REQUEST: Request = Request {
+    size: 1024,
+    align_mask: 255,
+    usage: UsageFlags::FAST_DEVICE_ACCESS,
+    memory_types: 1,
+    kind: ResourceKind::Linear,
+};
+
+#[test]
+#[should_panic(expected = "Memory block is deallocated into allocator instance")]
+fn wrong_instance() {
+    let mut first = Harness::new(Config::i_am_potato(), device_properties(32));
+    let mut second = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    // Both allocators have live buddy chunk with the same index.
+    let kept = unsafe { second.allocator.alloc(&second.device, REQUEST) }.unwrap();
+    second.blocks.push((kept, REQUEST));
+
+    let block = unsafe { first.allocator.alloc(&first.device, REQUEST) }.unwrap();
+    unsafe { second.allocator.dealloc(&second.device, block) }
+}
+
+#[test]
+fn same_instance() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let block = unsafe { harness.allocator.alloc(&harness.device, REQUEST) }.unwrap();
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,100 @@
+#![cfg(all(feature = "stats", feature = "std"))]
+
+mod harness;
+
+use {
+    core::time::Duration,
+    gpu_alloc::{
+        Config, LatencyHistogram, Request, ResourceKind, UsageFlags, LATENCY_HISTOGRAM_BUCKETS,
+    },
+    harness::{device_properties, Harness},
+};
+
+#[test]
+fn bucket_bounds() {
+    assert_eq!(LatencyHistogram::bucket(Duration::ZERO), 0);
+    assert_eq!(LatencyHistogram::bucket(Duration::from_nanos(999)), 0);
+    assert_eq!(LatencyHistogram::bucket(Duration::from_micros(1)), 1);
+    assert_eq!(LatencyHistogram::bucket(Duration::from_micros(3)), 2);
+    assert_eq!(LatencyHistogram::bucket(Duration::from_micros(4)), 3);
+    assert_eq!(
+        LatencyHistogram::bucket(Duration::MAX),
+        LATENCY_HISTOGRAM_BUCKETS - 1
+    );
+
+    for index in 0..LATENCY_HISTOGRAM_BUCKETS - 1 {
+        let bound = LatencyHistogram::bucket_upper_bound(index);
+        assert_eq!(LatencyHistogram::bucket(bound), index + 1);
+        assert_eq!(
+            LatencyHistogram::bucket(bound - Duration::from_nanos(1)),
+            index
+        );
+    }
+}
+
+#[test]
+fn allocations_recorded() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let request = Request {
+        size: 256,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    };
+
+    for _ in 0..3 {
+        let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+        harness.blocks.push((block, request));
+    }
+    assert_eq!(harness.device.total_allocations(), 1);
+
+    let latency = harness.allocator.alloc_latency();
+    assert_eq!(latency.alloc.total(), 3);
+    assert_eq!(latency.device.total(), 1);
+    assert_eq!(latency.device_alloc.total(), 1);
+    assert!(latency.device.max() <= latency.device_alloc.max());
+    assert!(latency.device_alloc.max() <= latency.alloc.max());
+    assert_eq!(latency.alloc.at_least(Duration::ZERO), 3);
+
+    // Failed allocations are recorded too.
+    let huge = Request {
+        size: 1 << 40,
+        ..request
+    };
+    unsafe { harness.allocator.alloc(&harness.device, huge) }.unwrap_err();
+    assert_eq!(harness.allocator.alloc_latency().alloc.total(), 4);
+
+    harness.allocator.reset_alloc_latency();
+    assert_eq!(harness.allocator.alloc_latency().alloc.total(), 0);
+
+    harness.check().unwrap();
+    harness.finish().unwrap();
+}
+
+#[test]
+fn at_least_rounds_to_buckets() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let histogram = harness.allocator.alloc_latency().alloc;
+    assert_eq!(histogram.at_least(Duration::from_millis(2)), 0);
+
+    let request = Request {
+        size: 256,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    };
+    let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    harness.blocks.push((block, request));
+
+    let histogram = harness.allocator.alloc_latency().alloc;
+    assert_eq!(
+        histogram.at_least(Duration::from_nanos(1)),
+        histogram.total() - histogram.buckets()[0]
+    );
+    assert_eq!(histogram.at_least(Duration::MAX), 0);
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,330 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Dedicated, MapError, MemoryBlock, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn host_request(size: u64, usage: UsageFlags) -> Request {
+    Request {
+        size,
+        align_mask: 63,
+        usage: usage | UsageFlags::HOST_ACCESS,
+        memory_types: !0,
+        kind: ResourceKind::Linear,
+    }
+}
+
+/// Writes unique pattern into each block and reads it back after all writes are done,
+/// so that any overlap or out-of-bounds pointer arithmetic is observed.
+fn write_read_back(harness: &mut Harness, blocks: &mut [MemoryBlock<usize>]) {
+    for (index, block) in blocks.iter_mut().enumerate() {
+        let data: Vec<u8> = (0..block.size())
+            .map(|i| (i as u8) ^ (index as u8))
+            .collect();
+        unsafe { block.write_bytes(&harness.device, 0, &data) }.unwrap();
+    }
+
+    for (index, block) in blocks.iter_mut().enumerate() {
+        let mut data = vec![0; block.size() as usize];
+        unsafe { block.read_bytes(&harness.device, 0, &mut data) }.unwrap();
+
+        for (i, &byte) in data.iter().enumerate() {
+            assert_eq!(byte, (i as u8) ^ (index as u8));
+        }
+    }
+}
+
+fn check_strategy(usage: UsageFlags, dedicated: Option<Dedicated>) {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let alloc = |harness: &mut Harness, size| {
+        let request = host_request(size, usage);
+        unsafe {
+            match dedicated {
+                None => harness.allocator.alloc(&harness.device, request),
+                Some(dedicated) => {
+                    harness
+                        .allocator
+                        .alloc_with_dedicated(&harness.device, request, dedicated)
+                }
+            }
+        }
+        .unwrap()
+    };
+
+    let mut blocks: Vec<MemoryBlock<usize>> = [100, 64, 1000, 4096, 10, 300]
+        .iter()
+        .map(|&size| alloc(&mut harness, size))
+        .collect();
+
+    write_read_back(&mut harness, &mut blocks);
+
+    // Free every other block and reuse freed space.
+    let mut index = 0;
+    let (mut blocks, freed): (Vec<_>, Vec<_>) = blocks.into_iter().partition(|_| {
+        index += 1;
+        index % 2 == 0
+    });
+
+    for block in freed {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    for &size in &[200, 50, 2000] {
+        blocks.push(alloc(&mut harness, size));
+    }
+    write_read_back(&mut harness, &mut blocks);
+
+    for block in blocks {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    harness.check().unwrap();
+    harness.finish().unwrap();
+}
+
+#[test]
+fn buddy_mapping() {
+    check_strategy(UsageFlags::empty(), None);
+}
+
+#[test]
+fn freelist_mapping() {
+    check_strategy(UsageFlags::TRANSIENT, None);
+}
+
+#[test]
+fn dedicated_mapping() {
+    check_strategy(UsageFlags::empty(), Some(Dedicated::Required));
+}
+
+#[test]
+fn misuse_returns_errors() {
+    for &dedicated in &[None, Some(Dedicated::Required)] {
+        let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+        let request = host_request(256, UsageFlags::empty());
+        let mut block = unsafe {
+            match dedicated {
+                None => harness.allocator.alloc(&harness.device, request),
+                Some(dedicated) => {
+                    harness
+                        .allocator
+                        .alloc_with_dedicated(&harness.device, request, dedicated)
+                }
+            }
+        }
+        .unwrap();
+
+        for &(offset, size) in &[(256, 0), (0, 257), (255, 2), (u64::MAX, 1)] {
+            assert_eq!(
+                unsafe { block.map(&harness.device, offset, size) }.err(),
+                Some(MapError::OutOfBounds)
+            );
+        }
+        assert_eq!(
+            unsafe { block.write_bytes(&harness.device, 200, &[0; 100]) },
+            Err(MapError::OutOfBounds)
+        );
+
+        unsafe { block.map(&harness.device, 0, 256) }.unwrap();
+        assert_eq!(
+            unsafe { block.map(&harness.device, 0, 1) }.err(),
+            Some(MapError::AlreadyMapped)
+        );
+        assert!(unsafe { block.unmap(&harness.device) });
+        assert!(!unsafe { block.unmap(&harness.device) });
+
+        harness.blocks.push((block, request));
+        harness.finish().unwrap();
+    }
+}
+
+#[test]
+fn copy_between_blocks() {
+    for &memory_types in &[1 << 1, 1 << 3] {
+        let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+        let request = Request {
+            memory_types,
+            ..host_request(1000, UsageFlags::empty())
+        };
+
+        let mut blocks: Vec<MemoryBlock<usize>> = [None, Some(Dedicated::Required)]
+            .iter()
+            .map(|&dedicated| unsafe {
+                match dedicated {
+                    None => harness.allocator.alloc(&harness.device, request),
+                    Some(dedicated) => {
+                        harness
+                            .allocator
+                            .alloc_with_dedicated(&harness.device, request, dedicated)
+                    }
+                }
+            })
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        let data: Vec<u8> = (0..1000).map(|i| i as u8).collect();
+        unsafe { blocks[0].write_bytes(&harness.device, 0, &data) }.unwrap();
+
+        let (src, dst) = blocks.split_at_mut(1);
+        unsafe { src[0].copy_to(&harness.device, &mut dst[0], 100, 500, 300) }.unwrap();
+
+        let mut copied = [0; 300];
+        unsafe { dst[0].read_bytes(&harness.device, 500, &mut copied) }.unwrap();
+        assert_eq!(&copied[..], &data[100..400]);
+
+        // Failed copy leaves both blocks unmapped.
+        assert_eq!(
+            unsafe { src[0].copy_to(&harness.device, &mut dst[0], 0, 900, 200) },
+            Err(MapError::OutOfBounds)
+        );
+        assert!(!unsafe { src[0].unmap(&harness.device) });
+        assert!(!unsafe { dst[0].unmap(&harness.device) });
+
+        for block in blocks {
+            harness.blocks.push((block, request));
+        }
+        harness.finish().unwrap();
+    }
+}
+
+#[test]
+fn unchecked_writes() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    for &(usage, dedicated) in &[
+        (UsageFlags::FAST_DEVICE_ACCESS, None),
+        (UsageFlags::TRANSIENT, None),
+        (UsageFlags::empty(), Some(Dedicated::Required)),
+    ] {
+        // Non-coherent memory type with atom size of 64.
+        let request = Request {
+            memory_types: 1 << 3,
+            ..host_request(256, usage)
+        };
+
+        let mut block = unsafe {
+            match dedicated {
+                None => harness.allocator.alloc(&harness.device, request),
+                Some(dedicated) => {
+                    harness
+                        .allocator
+                        .alloc_with_dedicated(&harness.device, request, dedicated)
+                }
+            }
+        }
+        .unwrap();
+
+        for chunk in 0..4u8 {
+            let data = [chunk + 1; 64];
+            unsafe { block.write_bytes_unchecked(&harness.device, chunk as u64 * 64, &data) }
+                .unwrap();
+        }
+        harness.device.check_invariants();
+
+        let mut data = [0; 256];
+        unsafe { block.read_bytes(&harness.device, 0, &mut data) }.unwrap();
+        for (i, &byte) in data.iter().enumerate() {
+            assert_eq!(byte, i as u8 / 64 + 1);
+        }
+
+        harness.blocks.push((block, request));
+    }
+
+    harness.check().unwrap();
+    harness.finish().unwrap();
+}
+
+#[test]
+fn disjoint_ranges_of_dedicated_block() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let request = Request {
+        memory_types: 1 << 1,
+        ..host_request(1024, UsageFlags::empty())
+    };
+    let mut block = unsafe {
+        harness
+            .allocator
+            .alloc_with_dedicated(&harness.device, request, Dedicated::Required)
+    }
+    .unwrap();
+
+    let first = unsafe { block.map_range(&harness.device, 0, 256) }.unwrap();
+    let second = unsafe { block.map_range(&harness.device, 512, 512) }.unwrap();
+    assert_eq!(harness.device.mapped_count(), 1);
+
+    unsafe {
+        first.as_ptr().write_bytes(1, 256);
+        second.as_ptr().write_bytes(2, 512);
+    }
+
+    // Overlapping ranges and whole block mapping are rejected.
+    for &(offset, size) in &[(0, 1), (255, 2), (256, 257), (1000, 10)] {
+        assert_eq!(
+            unsafe { block.map_range(&harness.device, offset, size) },
+            Err(MapError::AlreadyMapped)
+        );
+    }
+    assert_eq!(
+        unsafe { block.map(&harness.device, 256, 256) },
+        Err(MapError::AlreadyMapped)
+    );
+    assert_eq!(
+        unsafe { block.map_range(&harness.device, 512, 1024) },
+        Err(MapError::OutOfBounds)
+    );
+
+    let third = unsafe { block.map_range(&harness.device, 256, 256) }.unwrap();
+    unsafe { third.as_ptr().write_bytes(3, 256) };
+
+    assert!(unsafe { block.unmap_range(&harness.device, 0) });
+    assert!(!unsafe { block.unmap_range(&harness.device, 0) });
+    assert!(unsafe { block.unmap_range(&harness.device, 256) });
+    assert_eq!(harness.device.mapped_count(), 1);
+    assert!(unsafe { block.unmap_range(&harness.device, 512) });
+    assert_eq!(harness.device.mapped_count(), 0);
+
+    let mut data = vec![0; 1024];
+    unsafe { block.read_bytes(&harness.device, 0, &mut data) }.unwrap();
+    for (i, &byte) in data.iter().enumerate() {
+        assert_eq!(byte, [1, 3, 2, 2][i / 256]);
+    }
+
+    harness.blocks.push((block, request));
+    harness.finish().unwrap();
+}
+
+#[test]
+fn unchecked_write_reuses_range_mapping() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let request = Request {
+        memory_types: 1 << 1,
+        ..host_request(1024, UsageFlags::empty())
+    };
+    let mut block = unsafe {
+        harness
+            .allocator
+            .alloc_with_dedicated(&harness.device, request, Dedicated::Required)
+    }
+    .unwrap();
+
+    unsafe { block.map_range(&harness.device, 0, 16) }.unwrap();
+    assert_eq!(harness.device.mapped_count(), 1);
+
+    // Memory object is not mapped again.
+    unsafe { block.write_bytes_unchecked(&harness.device, 512, &[5; 256]) }.unwrap();
+    assert_eq!(harness.device.mapped_count(), 1);
+
+    assert!(unsafe { block.unmap_range(&harness.device, 0) });
+    assert_eq!(harness.device.mapped_count(), 0);
+
+    let mut data = [0; 256];
+    unsafe { block.read_bytes(&harness.device, 512, &mut data) }.unwrap();
+    assert_eq!(data, [5; 256]);
+
+    harness.blocks.push((block, request));
+    harness.finish().unwrap();
+}
@@ -0,0 +1,113 @@
+mod harness;
+
+use {
+    gpu_alloc::{
+        Config, Dedicated, DeviceMemoryReport, MemoryReportEvent, Request, ResourceKind, UsageFlags,
+    },
+    harness::{device_properties, Harness},
+};
+
+#[test]
+fn reconcile_flags_memory_allocated_outside_allocator() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let mut report = DeviceMemoryReport::new();
+
+    let request = Request {
+        size: 1 << 20,
+        align_mask: 0,
+        usage: UsageFlags::empty(),
+        memory_types: 1 << 0,
+        kind: ResourceKind::Linear,
+    };
+    let block = unsafe {
+        harness
+            .allocator
+            .alloc_with_dedicated(&harness.device, request, Dedicated::Required)
+    }
+    .unwrap();
+
+    report.record(MemoryReportEvent::Allocate {
+        id: 1,
+        handle: *block.memory() as u64,
+        size: 1 << 20,
+        heap: 0,
+    });
+    assert_eq!(report.reconcile(&harness.allocator), []);
+
+    // Memory object allocated by someone else.
+    report.record(MemoryReportEvent::Allocate {
+        id: 2,
+        handle: 0xdead,
+        size: 512 << 10,
+        heap: 0,
+    });
+    report.record(MemoryReportEvent::AllocationFailed {
+        size: 1 << 30,
+        heap: 0,
+    });
+
+    let mismatches = report.reconcile(&harness.allocator);
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].heap, 0);
+    assert_eq!(mismatches[0].reported_objects, 2);
+    assert_eq!(mismatches[0].accounted_objects, 1);
+    assert_eq!(mismatches[0].untracked_bytes(), 512 << 10);
+    assert_eq!(report.heap_usage(0), (1 << 20) + (512 << 10));
+    assert_eq!(report.allocation_failures(), 1);
+
+    let untracked: Vec<u64> = report
+        .objects()
+        .filter(|object| object.handle != *block.memory() as u64)
+        .map(|object| object.handle)
+        .collect();
+    assert_eq!(untracked, [0xdead]);
+
+    report.record(MemoryReportEvent::Free { id: 2 });
+    assert_eq!(report.reconcile(&harness.allocator), []);
+
+    // Accounted memory object that driver did not report.
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+    let mismatches = report.reconcile(&harness.allocator);
+    assert_eq!(mismatches.len(), 1);
+    assert_eq!(mismatches[0].reported_bytes, 1 << 20);
+    assert_eq!(mismatches[0].accounted_bytes, 0);
+
+    report.record(MemoryReportEvent::Free { id: 1 });
+    assert_eq!(report.reconcile(&harness.allocator), []);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn reconcile_counts_chunks_of_suballocators() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let mut report = DeviceMemoryReport::new();
+
+    let request = Request {
+        size: 256,
+        align_mask: 0,
+        usage: UsageFlags::empty(),
+        memory_types: 1 << 0,
+        kind: ResourceKind::Linear,
+    };
+    let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+
+    // Report chunk the block was sub-allocated from.
+    let stats = harness.allocator.stats();
+    let heap = &stats.heaps[0];
+    assert_eq!(heap.total().memory_objects, 1);
+    report.record(MemoryReportEvent::Allocate {
+        id: 7,
+        handle: *block.memory() as u64,
+        size: heap.used,
+        heap: 0,
+    });
+    assert_eq!(report.reconcile(&harness.allocator), []);
+
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+    unsafe { harness.allocator.cleanup(&harness.device) }
+    report.record(MemoryReportEvent::Free { id: 7 });
+    assert_eq!(report.reconcile(&harness.allocator), []);
+
+    harness.finish().unwrap();
+}
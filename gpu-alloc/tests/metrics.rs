@@ -0,0 +1,133 @@
+#![cfg(feature = "metrics")]
+
+mod harness;
+
+use {
+    gpu_alloc::{
+        AllocationError, Config, Dedicated, Request, ResourceKind, UsageFlags,
+        METRIC_DEVICE_ALLOCATIONS, METRIC_FAILED_ALLOCATIONS, METRIC_HEAP_USED_BYTES,
+        METRIC_LIVE_BLOCKS,
+    },
+    harness::{device_properties, Harness},
+    metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter},
+    std::collections::BTreeMap,
+};
+
+/// Returns last values of all metrics keyed by name and labels.
+fn values(snapshotter: &Snapshotter) -> BTreeMap<String, f64> {
+    snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| {
+            let key = key.key();
+            let mut name = key.name().to_owned();
+            for label in key.labels() {
+                name.push_str(&format!("{{{}={}}}", label.key(), label.value()));
+            }
+            let value = match value {
+                DebugValue::Counter(value) => value as f64,
+                DebugValue::Gauge(value) => value.into_inner(),
+                DebugValue::Histogram(_) => unreachable!("Allocator publishes no histograms"),
+            };
+            (name, value)
+        })
+        .collect()
+}
+
+#[test]
+fn gauges_follow_allocations() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+
+    metrics::with_local_recorder(&recorder, || {
+        let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+        let request = Request {
+            size: 1000,
+            align_mask: 255,
+            usage: UsageFlags::FAST_DEVICE_ACCESS,
+            memory_types: 1,
+            kind: ResourceKind::Linear,
+        };
+
+        let shared = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+        let dedicated = unsafe {
+            harness
+                .allocator
+                .alloc_with_dedicated(&harness.device, request, Dedicated::Required)
+        }
+        .unwrap();
+
+        let heap_key = format!("{}{{heap=0}}", METRIC_HEAP_USED_BYTES);
+
+        let live = values(&snapshotter);
+        assert_eq!(live[METRIC_LIVE_BLOCKS], 2.0);
+        assert_eq!(live[METRIC_DEVICE_ALLOCATIONS], 2.0);
+        assert_eq!(
+            live[&heap_key],
+            harness.allocator.heap_usage(0) as f64,
+            "heap gauge diverged from allocator"
+        );
+
+        unsafe {
+            harness.allocator.dealloc(&harness.device, shared);
+            harness.allocator.dealloc(&harness.device, dedicated);
+        }
+
+        harness.finish().unwrap();
+
+        let released = values(&snapshotter);
+        assert_eq!(released[METRIC_LIVE_BLOCKS], 0.0);
+        assert_eq!(released[METRIC_DEVICE_ALLOCATIONS], 0.0);
+        assert_eq!(released[&heap_key], 0.0);
+    });
+}
+
+#[test]
+fn failures_are_counted_by_reason() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+
+    metrics::with_local_recorder(&recorder, || {
+        let mut harness = Harness::new(Config::i_am_potato(), device_properties(0));
+
+        let request = Request {
+            size: 1000,
+            align_mask: 255,
+            usage: UsageFlags::FAST_DEVICE_ACCESS,
+            memory_types: 1,
+            kind: ResourceKind::Linear,
+        };
+
+        for _ in 0..3 {
+            let err = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap_err();
+            assert_eq!(err, AllocationError::TooManyObjects);
+        }
+
+        let err = unsafe {
+            harness.allocator.alloc(
+                &harness.device,
+                Request {
+                    memory_types: 0,
+                    ..request
+                },
+            )
+        }
+        .unwrap_err();
+        assert_eq!(err, AllocationError::NoCompatibleMemoryTypes);
+
+        let failed = values(&snapshotter);
+        assert_eq!(
+            failed[&format!("{}{{reason=too_many_objects}}", METRIC_FAILED_ALLOCATIONS)],
+            3.0
+        );
+        assert_eq!(
+            failed[&format!(
+                "{}{{reason=no_compatible_memory_types}}",
+                METRIC_FAILED_ALLOCATIONS
+            )],
+            1.0
+        );
+    });
+}
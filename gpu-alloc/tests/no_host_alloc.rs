@@ -0,0 +1,138 @@
+//!
+//! Checks that allocator performs no host allocations in steady state,
+//! once chunks and bookkeeping are warmed up.
+//!
+
+mod harness;
+
+use {
+    gpu_alloc::{Config, MemoryBlock, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+    std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+    },
+};
+
+/// System allocator counting allocations made by current thread.
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<u64> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn allocations() -> u64 {
+    ALLOCATIONS.with(Cell::get)
+}
+
+const REQUESTS: [Request; 4] = [
+    Request {
+        size: 256,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1,
+        kind: ResourceKind::Linear,
+    },
+    Request {
+        size: 3000,
+        align_mask: 63,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1,
+        kind: ResourceKind::Linear,
+    },
+    Request {
+        size: 1000,
+        align_mask: 15,
+        usage: UsageFlags::TRANSIENT,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    },
+    Request {
+        size: 5000,
+        align_mask: 255,
+        usage: UsageFlags::TRANSIENT,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    },
+];
+
+/// Small deterministic generator to pick blocks to replace.
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0 as usize
+    }
+}
+
+#[test]
+fn steady_state() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let mut blocks: Vec<(MemoryBlock<usize>, Request)> = (0..32)
+        .map(|index| {
+            let request = REQUESTS[index % REQUESTS.len()];
+            let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+            (block, request)
+        })
+        .collect();
+
+    // Blocks are replaced one by one, so chunks always have other live blocks.
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+    let mut replace = |harness: &mut Harness, blocks: &mut Vec<(MemoryBlock<usize>, Request)>| {
+        let index = rng.next() % blocks.len();
+        let request = blocks[index].1;
+        let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+        let (old, _) = std::mem::replace(&mut blocks[index], (block, request));
+        unsafe { harness.allocator.dealloc(&harness.device, old) }
+    };
+
+    // Warm up bookkeeping.
+    for _ in 0..1000 {
+        replace(&mut harness, &mut blocks);
+    }
+
+    let before = allocations();
+    let device_allocations = harness.device.total_allocations();
+    for _ in 0..1000 {
+        replace(&mut harness, &mut blocks);
+    }
+    assert_eq!(harness.device.total_allocations(), device_allocations);
+    assert_eq!(
+        allocations() - before,
+        0,
+        "Host allocations in steady state"
+    );
+
+    for (block, _) in blocks.drain(..) {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    let before = allocations();
+    unsafe { harness.allocator.cleanup(&harness.device) }
+    assert_eq!(allocations() - before, 0, "Host allocations in cleanup");
+
+    harness.finish().unwrap();
+}
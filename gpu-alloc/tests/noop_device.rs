@@ -0,0 +1,38 @@
+use gpu_alloc::{Config, GpuAllocator, NoopMemoryDevice, Request, ResourceKind, UsageFlags};
+
+#[test]
+fn allocate_write_read() {
+    let device = NoopMemoryDevice::new();
+    let mut allocator = GpuAllocator::new(
+        Config::i_am_potato(),
+        NoopMemoryDevice::properties(64 * 1024 * 1024),
+    );
+
+    let mut blocks = Vec::new();
+    for size in [100, 4096, 1024 * 1024] {
+        let request = Request {
+            size,
+            align_mask: 0,
+            usage: UsageFlags::UPLOAD | UsageFlags::DOWNLOAD,
+            memory_types: !0,
+            kind: ResourceKind::Linear,
+        };
+        let mut block = unsafe { allocator.alloc(&device, request) }.unwrap();
+
+        let data = vec![size as u8; size as usize];
+        unsafe { block.write_bytes(&device, 0, &data) }.unwrap();
+        let mut read = vec![0; size as usize];
+        unsafe { block.read_bytes(&device, 0, &mut read) }.unwrap();
+        assert_eq!(read, data);
+
+        blocks.push(block);
+    }
+    assert!(device.live_allocations() > 0);
+    assert_eq!(device.total_allocations(), device.live_allocations());
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) }
+    }
+    unsafe { allocator.cleanup(&device) }
+    assert_eq!(device.live_allocations(), 0);
+}
@@ -0,0 +1,222 @@
+mod harness;
+
+use {
+    gpu_alloc::{
+        AllocEvent, AllocationError, AllocatorObserver, BlockEvent, ChunkEvent, ChunkKind, Config,
+        Dedicated, FailureEvent, Request, ResourceKind, UsageFlags,
+    },
+    harness::{device_properties, ops, Harness},
+    proptest::prelude::*,
+    std::{
+        collections::BTreeSet,
+        sync::{Arc, Mutex},
+    },
+};
+
+#[derive(Clone, Debug, PartialEq)]
+enum Event {
+    Alloc(BlockEvent),
+    Dealloc(BlockEvent),
+    ChunkCreated(ChunkEvent),
+    ChunkDestroyed(ChunkEvent),
+    Failure(FailureEvent),
+}
+
+#[derive(Clone, Default)]
+struct Recorder {
+    events: Arc<Mutex<Vec<Event>>>,
+}
+
+impl AllocatorObserver for Recorder {
+    fn on_alloc(&mut self, event: &BlockEvent) {
+        self.events.lock().unwrap().push(Event::Alloc(*event));
+    }
+
+    fn on_dealloc(&mut self, event: &BlockEvent) {
+        self.events.lock().unwrap().push(Event::Dealloc(*event));
+    }
+
+    fn on_chunk_created(&mut self, event: &ChunkEvent) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(Event::ChunkCreated(*event));
+    }
+
+    fn on_chunk_destroyed(&mut self, event: &ChunkEvent) {
+        self.events
+            .lock()
+            .unwrap()
+            .push(Event::ChunkDestroyed(*event));
+    }
+
+    fn on_failure(&mut self, event: &FailureEvent) {
+        self.events.lock().unwrap().push(Event::Failure(*event));
+    }
+}
+
+#[test]
+fn events_in_order() {
+    let recorder = Recorder::default();
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+    harness.allocator.set_observer(Box::new(recorder.clone()));
+
+    let request = Request {
+        size: 1000,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1,
+        kind: ResourceKind::Linear,
+    };
+
+    let block = unsafe {
+        harness
+            .allocator
+            .alloc_tagged(&harness.device, request, "mesh")
+    }
+    .unwrap();
+    let dedicated = unsafe {
+        harness
+            .allocator
+            .alloc_with_dedicated(&harness.device, request, Dedicated::Required)
+    }
+    .unwrap();
+
+    let failed = Request {
+        memory_types: 0,
+        ..request
+    };
+    let err = unsafe { harness.allocator.alloc(&harness.device, failed) }.unwrap_err();
+
+    unsafe {
+        harness.allocator.dealloc(&harness.device, block);
+        harness.allocator.dealloc(&harness.device, dedicated);
+    }
+
+    let events = recorder.events.lock().unwrap().clone();
+    let kinds: Vec<&str> = events
+        .iter()
+        .map(|event| match event {
+            Event::Alloc(_) => "alloc",
+            Event::Dealloc(_) => "dealloc",
+            Event::ChunkCreated(_) => "chunk created",
+            Event::ChunkDestroyed(_) => "chunk destroyed",
+            Event::Failure(_) => "failure",
+        })
+        .collect();
+
+    assert_eq!(
+        kinds,
+        [
+            "chunk created",
+            "alloc",
+            "chunk created",
+            "alloc",
+            "failure",
+            "dealloc",
+            "chunk destroyed",
+            "dealloc",
+            "chunk destroyed",
+        ]
+    );
+
+    match (&events[0], &events[1]) {
+        (Event::ChunkCreated(chunk), Event::Alloc(block)) => {
+            assert_eq!(chunk.kind, ChunkKind::Buddy);
+            assert_eq!(block.kind, ChunkKind::Buddy);
+            assert_eq!(block.chunk, chunk.id);
+            assert_eq!(block.requested_size, 1000);
+            assert_eq!(block.tag, Some("mesh"));
+        }
+        events => panic!("Unexpected events {:?}", events),
+    }
+
+    match &events[4] {
+        Event::Failure(failure) => {
+            assert_eq!(failure.request, failed);
+            assert_eq!(failure.error, err);
+            assert_eq!(failure.error, AllocationError::NoCompatibleMemoryTypes);
+        }
+        event => panic!("Unexpected event {:?}", event),
+    }
+
+    assert!(harness.allocator.take_observer().is_some());
+    harness.finish().unwrap();
+    assert_eq!(recorder.events.lock().unwrap().len(), events.len());
+}
+
+#[test]
+fn subscribers_receive_events() {
+    let recorder = Recorder::default();
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+    harness.allocator.set_observer(Box::new(recorder.clone()));
+
+    let first = harness.allocator.subscribe();
+    let second = harness.allocator.subscribe();
+
+    let request = Request {
+        size: 1000,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1,
+        kind: ResourceKind::Linear,
+    };
+    let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+
+    // Dropped receiver does not stop events for others.
+    drop(second);
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+
+    let events: Vec<AllocEvent> = first.try_iter().collect();
+    match events[..] {
+        [AllocEvent::ChunkCreated(chunk), AllocEvent::Alloc(alloc), AllocEvent::Dealloc(dealloc), AllocEvent::ChunkDestroyed(destroyed)] =>
+        {
+            assert_eq!(alloc.chunk, chunk.id);
+            assert_eq!(alloc, dealloc);
+            assert_eq!(chunk, destroyed);
+        }
+        _ => panic!("Unexpected events {:?}", events),
+    }
+
+    // Observer keeps receiving events alongside subscribers.
+    assert_eq!(recorder.events.lock().unwrap().len(), 4);
+
+    harness.finish().unwrap();
+}
+
+proptest! {
+    #![proptest_config(harness::proptest_config())]
+
+    #[test]
+    fn events_match_device(ops in ops(4 * 1024 * 1024, 128)) {
+        let recorder = Recorder::default();
+        let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+        harness.allocator.set_observer(Box::new(recorder.clone()));
+
+        let mut chunks = BTreeSet::new();
+        let mut blocks = BTreeSet::new();
+
+        for op in ops {
+            harness.apply(op);
+            harness.check()?;
+
+            for event in recorder.events.lock().unwrap().drain(..) {
+                match event {
+                    Event::ChunkCreated(e) => prop_assert!(chunks.insert((e.memory_type, e.kind, e.id))),
+                    Event::ChunkDestroyed(e) => prop_assert!(chunks.remove(&(e.memory_type, e.kind, e.id))),
+                    Event::Alloc(e) => {
+                        prop_assert!(chunks.contains(&(e.memory_type, e.kind, e.chunk)));
+                        prop_assert!(blocks.insert(e.id));
+                    }
+                    Event::Dealloc(e) => prop_assert!(blocks.remove(&e.id)),
+                    Event::Failure(_) => {}
+                }
+            }
+
+            prop_assert_eq!(chunks.len() as u64, harness.device.live_allocations());
+            prop_assert_eq!(blocks.len(), harness.blocks.len());
+        }
+
+        harness.finish()?;
+    }
+}
@@ -0,0 +1,156 @@
+mod harness;
+
+use {
+    gpu_alloc::{
+        AllocationError, Config, GpuAllocator, MemoryHeap, Request, ResourceKind, UsageFlags,
+    },
+    harness::{device_properties, Harness},
+    std::borrow::Cow,
+};
+
+const HEAP_SIZE: u64 = 16 * 1024 * 1024;
+
+fn request(size: u64, usage: UsageFlags) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: usage | UsageFlags::HOST_ACCESS,
+        memory_types: (1 << 1) | (1 << 3),
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn dedicated_allocation_over_budget() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let large = request(HEAP_SIZE / 2 + 1, UsageFlags::empty());
+    let block = unsafe { harness.allocator.alloc(&harness.device, large) }.unwrap();
+    harness.blocks.push((block, large));
+
+    let err = unsafe { harness.allocator.alloc(&harness.device, large) }.unwrap_err();
+    assert_eq!(err, AllocationError::OutOfBudget);
+    assert_eq!(
+        harness.device.total_allocations(),
+        1,
+        "device was not asked"
+    );
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn chunk_allocation_over_budget() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let large = request(HEAP_SIZE - 1024, UsageFlags::empty());
+    let block = unsafe { harness.allocator.alloc(&harness.device, large) }.unwrap();
+    harness.blocks.push((block, large));
+
+    for &usage in &[UsageFlags::empty(), UsageFlags::TRANSIENT] {
+        let err = unsafe {
+            harness
+                .allocator
+                .alloc(&harness.device, request(4096, usage))
+        }
+        .unwrap_err();
+        assert_eq!(err, AllocationError::OutOfBudget);
+    }
+    assert_eq!(
+        harness.device.total_allocations(),
+        1,
+        "device was not asked"
+    );
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn device_refusal_is_out_of_device_memory() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    // Allocator believes heap is twice as large as device can provide.
+    let mut props = device_properties(64);
+    props.memory_heaps = Cow::Owned(vec![
+        MemoryHeap {
+            size: 64 * 1024 * 1024,
+            budget: None,
+            usage: None,
+        },
+        MemoryHeap {
+            size: 2 * HEAP_SIZE,
+            budget: None,
+            usage: None,
+        },
+    ]);
+    harness.allocator = GpuAllocator::new(Config::i_am_potato(), props);
+
+    let large = request(HEAP_SIZE, UsageFlags::empty());
+    let block = unsafe { harness.allocator.alloc(&harness.device, large) }.unwrap();
+    harness.blocks.push((block, large));
+
+    let err = unsafe { harness.allocator.alloc(&harness.device, large) }.unwrap_err();
+    assert_eq!(err, AllocationError::OutOfDeviceMemory);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn reported_heap_budget_limits_allocations() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    // Driver reports that a quarter of the heap is available to the process
+    // and other allocations already use half of that.
+    let mut props = device_properties(64);
+    props.memory_heaps.to_mut()[1].budget = Some(HEAP_SIZE / 4);
+    props.memory_heaps.to_mut()[1].usage = Some(HEAP_SIZE / 8);
+    harness.allocator = GpuAllocator::new(Config::i_am_potato(), props);
+    assert_eq!(harness.allocator.stats().heaps[1].budget, HEAP_SIZE / 8);
+    assert_eq!(harness.allocator.stats().heaps[0].budget, 64 * 1024 * 1024);
+
+    let large = request(HEAP_SIZE / 8 + 1, UsageFlags::empty());
+    let err = unsafe { harness.allocator.alloc(&harness.device, large) }.unwrap_err();
+    assert_eq!(err, AllocationError::OutOfBudget);
+    assert_eq!(
+        harness.device.total_allocations(),
+        0,
+        "device was not asked"
+    );
+
+    let fits = request(HEAP_SIZE / 8, UsageFlags::empty());
+    let block = unsafe { harness.allocator.alloc(&harness.device, fits) }.unwrap();
+    harness.blocks.push((block, fits));
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn heap_budget_can_be_updated() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let large = request(HEAP_SIZE / 4, UsageFlags::empty());
+    let block = unsafe { harness.allocator.alloc(&harness.device, large) }.unwrap();
+    harness.blocks.push((block, large));
+
+    // Budget shrinks below memory already allocated.
+    harness.allocator.set_heap_budget(1, HEAP_SIZE / 8);
+    assert_eq!(harness.allocator.stats().heaps[1].budget, HEAP_SIZE / 8);
+
+    let err = unsafe { harness.allocator.alloc(&harness.device, large) }.unwrap_err();
+    assert_eq!(err, AllocationError::OutOfBudget);
+    let details = harness.allocator.last_failure().unwrap();
+    assert_eq!(details.heaps()[1].budget, HEAP_SIZE / 8);
+    assert_eq!(details.heaps()[1].remaining(), 0);
+    harness.check().unwrap();
+
+    // Budget grows back.
+    harness.allocator.set_heap_budget(1, HEAP_SIZE / 2);
+    let block = unsafe { harness.allocator.alloc(&harness.device, large) }.unwrap();
+    harness.blocks.push((block, large));
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,64 @@
+mod harness;
+
+use {
+    gpu_alloc::{AllocationError, Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64, align_mask: u64) -> Request {
+    Request {
+        size,
+        align_mask,
+        usage: UsageFlags::empty(),
+        memory_types: 1 << 0,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn huge_alignment_overflows() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    for &align_mask in &[!0, !0 >> 1] {
+        let err = unsafe {
+            harness
+                .allocator
+                .alloc(&harness.device, request(256, align_mask))
+        }
+        .unwrap_err();
+        assert_eq!(err, AllocationError::SizeOverflow);
+        harness.check().unwrap();
+    }
+
+    // Rejected request leaves memory type usable.
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(256, 255)) }.unwrap();
+    harness.blocks.push((block, request(256, 255)));
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn mapping_range_overflow_is_out_of_bounds() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let request = Request {
+        memory_types: 1 << 3,
+        usage: UsageFlags::HOST_ACCESS,
+        ..request(1024, 0)
+    };
+    let mut block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+
+    let err = unsafe { block.map(&harness.device, !0, 16) }.unwrap_err();
+    assert_eq!(err, gpu_alloc::MapError::OutOfBounds);
+
+    let err = unsafe { block.map(&harness.device, 512, usize::MAX) }.unwrap_err();
+    assert_eq!(err, gpu_alloc::MapError::OutOfBounds);
+
+    let mut data = [0u8; 16];
+    let err = unsafe { block.read_bytes(&harness.device, !0 - 8, &mut data) }.unwrap_err();
+    assert_eq!(err, gpu_alloc::MapError::OutOfBounds);
+
+    harness.blocks.push((block, request));
+    harness.finish().unwrap();
+}
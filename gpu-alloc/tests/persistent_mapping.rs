@@ -0,0 +1,65 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Dedicated, OutOfMemory, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn host_request(size: u64, memory_type: u32) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: 1 << memory_type,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn guard_unmaps_dedicated_block() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let request = host_request(1000, 1);
+    let mut block = unsafe {
+        harness
+            .allocator
+            .alloc_with_dedicated(&harness.device, request, Dedicated::Required)
+    }
+    .unwrap();
+
+    {
+        let mut guard = unsafe { block.map_persistent(&harness.device) }.unwrap();
+        assert_eq!(guard.len(), 1000);
+        assert_eq!(harness.device.mapped_count(), 1);
+        guard[10..20].copy_from_slice(&[7; 10]);
+    }
+    assert_eq!(harness.device.mapped_count(), 0);
+
+    let mut data = [0; 10];
+    unsafe { block.read_bytes(&harness.device, 10, &mut data) }.unwrap();
+    assert_eq!(data, [7; 10]);
+
+    harness.blocks.push((block, request));
+    harness.finish().unwrap();
+}
+
+#[test]
+fn finish_reports_flush_failure() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+    harness.device.fail_flush_after(0);
+
+    // Non-coherent memory type requires flushing.
+    let request = host_request(1000, 3);
+    let mut block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+
+    let mut guard = unsafe { block.map_persistent(&harness.device) }.unwrap();
+    guard.fill(1);
+    assert!(matches!(guard.finish(), Err(OutOfMemory::OutOfHostMemory)));
+
+    // Block is unmapped after failure.
+    unsafe { block.map(&harness.device, 0, 3) }.unwrap();
+    assert!(unsafe { block.unmap(&harness.device) });
+
+    harness.blocks.push((block, request));
+    harness.finish().unwrap();
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "bytemuck")]
+
+mod harness;
+
+use {
+    gpu_alloc::{Config, MapError, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+#[test]
+fn typed_round_trip() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    // Non-coherent memory type requires flushing and invalidation.
+    let request = Request {
+        size: 256,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: 1 << 3,
+        kind: ResourceKind::Linear,
+    };
+    let mut block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+
+    let values = [1.5f32, -2.0, 3.25, 1e6];
+    unsafe { block.write_slice(&harness.device, 3, &values) }.unwrap();
+    unsafe { block.write_struct(&harness.device, 64, &[7u32, 8, 9]) }.unwrap();
+
+    let mut read = [0f32; 4];
+    unsafe { block.read_slice(&harness.device, 3, &mut read) }.unwrap();
+    assert_eq!(read, values);
+
+    let read: [u32; 3] = unsafe { block.read_struct(&harness.device, 64) }.unwrap();
+    assert_eq!(read, [7, 8, 9]);
+
+    assert_eq!(
+        unsafe { block.write_slice(&harness.device, 250, &[0u16; 4]) },
+        Err(MapError::OutOfBounds)
+    );
+
+    harness.blocks.push((block, request));
+    harness.finish().unwrap();
+}
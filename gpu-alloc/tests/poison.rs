@@ -0,0 +1,61 @@
+#![cfg(feature = "poison")]
+
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags, POISON_BYTE},
+    harness::{device_properties, Harness},
+};
+
+/// Checks that reallocated block of the same size observes poison pattern
+/// instead of data written before deallocation.
+fn check_poisoned(usage: UsageFlags, memory_types: u32) {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let request = Request {
+        size: 256,
+        align_mask: 63,
+        usage: usage | UsageFlags::HOST_ACCESS,
+        memory_types,
+        kind: ResourceKind::Linear,
+    };
+
+    // Keeps chunk alive so freed block is not returned to device.
+    let keep = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+
+    let mut block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    let offset = block.offset();
+    unsafe { block.write_bytes(&harness.device, 0, &[0x11; 256]) }.unwrap();
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+
+    let mut block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    assert_eq!(block.offset(), offset, "freed block should be reused");
+
+    let mut data = [0; 256];
+    unsafe { block.read_bytes(&harness.device, 0, &mut data) }.unwrap();
+    assert!(data.iter().all(|&byte| byte == POISON_BYTE));
+
+    harness.blocks.push((keep, request));
+    harness.blocks.push((block, request));
+    harness.finish().unwrap();
+}
+
+#[test]
+fn buddy_coherent() {
+    check_poisoned(UsageFlags::empty(), 1 << 1);
+}
+
+#[test]
+fn buddy_non_coherent() {
+    check_poisoned(UsageFlags::empty(), 1 << 3);
+}
+
+#[test]
+fn free_list_coherent() {
+    check_poisoned(UsageFlags::TRANSIENT, 1 << 1);
+}
+
+#[test]
+fn free_list_non_coherent() {
+    check_poisoned(UsageFlags::TRANSIENT, 1 << 3);
+}
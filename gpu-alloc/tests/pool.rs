@@ -0,0 +1,190 @@
+mod harness;
+
+use {
+    gpu_alloc::{AllocationError, Config, PoolConfig, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64, memory_types: u32) -> Request {
+    Request {
+        size,
+        align_mask: 255,
+        usage: UsageFlags::empty(),
+        memory_types,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn pool_blocks_use_separate_chunks() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let pool = harness
+        .allocator
+        .create_pool(PoolConfig::new(Config::i_am_potato()));
+
+    let request = request(1000, 1 << 0);
+    let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    harness.blocks.push((block, request));
+    let block = unsafe {
+        harness
+            .allocator
+            .alloc_in_pool(&harness.device, pool, request)
+    }
+    .unwrap();
+    harness.blocks.push((block, request));
+
+    assert_ne!(
+        harness.blocks[0].0.memory(),
+        harness.blocks[1].0.memory(),
+        "pool block shares chunk with allocator"
+    );
+    assert_eq!(harness.allocator.pool_usage(pool), 1024);
+
+    let chunks = &harness.allocator.dump().types[0].chunks;
+    assert!(chunks.iter().any(|chunk| chunk.pool.is_none()));
+    assert!(chunks.iter().any(|chunk| chunk.pool == Some(pool)));
+    harness.check().unwrap();
+
+    let (block, _) = harness.blocks.pop().unwrap();
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+    assert_eq!(harness.allocator.pool_usage(pool), 0);
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn pool_budget_and_memory_types_are_enforced() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let pool = harness.allocator.create_pool(PoolConfig {
+        memory_types: 1 << 2,
+        budget: Some(4096),
+        ..PoolConfig::new(Config::i_am_potato())
+    });
+
+    let result = unsafe {
+        harness
+            .allocator
+            .alloc_in_pool(&harness.device, pool, request(16, 1 << 0))
+    };
+    assert!(matches!(
+        result,
+        Err(AllocationError::NoCompatibleMemoryTypes)
+    ));
+
+    let request = request(2048, !0);
+    for _ in 0..2 {
+        let block = unsafe {
+            harness
+                .allocator
+                .alloc_in_pool(&harness.device, pool, request)
+        }
+        .unwrap();
+        assert_eq!(block.memory_type(), 2);
+        harness.blocks.push((block, request));
+    }
+
+    let result = unsafe {
+        harness
+            .allocator
+            .alloc_in_pool(&harness.device, pool, request)
+    };
+    assert!(matches!(result, Err(AllocationError::OutOfBudget)));
+
+    // Size that overflows pool usage is over budget too.
+    let huge = Request {
+        size: u64::MAX,
+        ..request
+    };
+    let result = unsafe { harness.allocator.alloc_in_pool(&harness.device, pool, huge) };
+    assert!(matches!(result, Err(AllocationError::OutOfBudget)));
+
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn pool_budget_accounts_rounded_size() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let pool = harness.allocator.create_pool(PoolConfig {
+        budget: Some(2000),
+        ..PoolConfig::new(Config::i_am_potato())
+    });
+
+    let request = request(1000, 1 << 1);
+    let block = unsafe {
+        harness
+            .allocator
+            .alloc_in_pool(&harness.device, pool, request)
+    }
+    .unwrap();
+    harness.blocks.push((block, request));
+    assert_eq!(harness.allocator.pool_usage(pool), 1024);
+
+    // 1024 + 900 fits the budget, but block is rounded up to 1024 bytes.
+    let result = unsafe {
+        harness.allocator.alloc_in_pool(
+            &harness.device,
+            pool,
+            Request {
+                size: 900,
+                ..request
+            },
+        )
+    };
+    assert!(matches!(result, Err(AllocationError::OutOfBudget)));
+    assert_eq!(harness.allocator.pool_usage(pool), 1024);
+
+    harness.check().unwrap();
+    harness.finish().unwrap();
+}
+
+#[test]
+fn destroyed_pool_releases_memory() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let pool = harness
+        .allocator
+        .create_pool(PoolConfig::new(Config::i_am_potato()));
+
+    let mut blocks = Vec::new();
+    for size in [100, 3000, 20000] {
+        let block = unsafe {
+            harness
+                .allocator
+                .alloc_in_pool(&harness.device, pool, request(size, 1 << 1))
+        }
+        .unwrap();
+        blocks.push(block);
+    }
+    assert!(harness.device.live_allocations() > 0);
+
+    for block in blocks {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+    unsafe { harness.allocator.destroy_pool(&harness.device, pool) };
+
+    assert_eq!(harness.device.live_allocations(), 0);
+    harness.check().unwrap();
+    harness.finish().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "must be deallocated")]
+fn destroying_pool_with_live_blocks_panics() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let pool = harness
+        .allocator
+        .create_pool(PoolConfig::new(Config::i_am_potato()));
+
+    let request = request(100, 1 << 1);
+    let block = unsafe {
+        harness
+            .allocator
+            .alloc_in_pool(&harness.device, pool, request)
+    }
+    .unwrap();
+    harness.blocks.push((block, request));
+
+    unsafe { harness.allocator.destroy_pool(&harness.device, pool) };
+}
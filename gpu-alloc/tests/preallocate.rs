@@ -0,0 +1,98 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn preallocated_chunks_serve_blocks() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    unsafe {
+        harness
+            .allocator
+            .preallocate(&harness.device, 1 << 1, 32 * 1024)
+    }
+    .unwrap();
+    harness.check().unwrap();
+
+    // Potato config starts with 8 KiB buddy chunks.
+    assert_eq!(harness.device.total_allocations(), 4);
+
+    for _ in 0..8 {
+        let block = unsafe { harness.allocator.alloc(&harness.device, request(4096)) }.unwrap();
+        harness.blocks.push((block, request(4096)));
+    }
+    harness.check().unwrap();
+    assert_eq!(harness.device.total_allocations(), 4);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn retained_bytes_counted() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    unsafe { harness.allocator.preallocate(&harness.device, 1 << 1, 1) }.unwrap();
+    assert_eq!(harness.device.total_allocations(), 1);
+
+    // Chunk already retained covers requested bytes.
+    unsafe {
+        harness
+            .allocator
+            .preallocate(&harness.device, 1 << 1, 8 * 1024)
+    }
+    .unwrap();
+    assert_eq!(harness.device.total_allocations(), 1);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn every_memory_type_in_mask() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    unsafe {
+        harness
+            .allocator
+            .preallocate(&harness.device, 0b1011, 8 * 1024)
+    }
+    .unwrap();
+    harness.check().unwrap();
+
+    let report = harness.allocator.report();
+    for index in [0, 1, 3] {
+        assert_eq!(report.types[index].allocated_bytes, 8 * 1024);
+    }
+    assert_eq!(report.types[2].allocated_bytes, 0);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn unused_chunks_released_by_cleanup() {
+    let mut config = Config::i_am_potato();
+    config.buddy_chunk_keep_alive = 2;
+    let mut harness = Harness::new(config, device_properties(64));
+
+    unsafe { harness.allocator.preallocate(&harness.device, 1 << 1, 1) }.unwrap();
+
+    unsafe { harness.allocator.cleanup(&harness.device) }
+    assert_eq!(harness.device.live_allocations(), 1);
+
+    unsafe { harness.allocator.cleanup(&harness.device) }
+    assert_eq!(harness.device.live_allocations(), 0);
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,102 @@
+mod harness;
+
+use {
+    gpu_alloc::{AllocationError, Config, Dedicated, MapError, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn harness() -> Harness {
+    Harness::new(Config::i_am_potato(), device_properties(32))
+}
+
+fn host_request(size: u64, usage: UsageFlags, memory_type: u32) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: usage | UsageFlags::HOST_ACCESS,
+        memory_types: 1 << memory_type,
+        kind: ResourceKind::Linear,
+    }
+}
+
+fn check_map_failure(usage: UsageFlags) {
+    let mut harness = harness();
+    harness.device.set_max_simultaneous_mappings(1);
+
+    let request = host_request(100, usage, 1);
+    let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    harness.blocks.push((block, request));
+
+    // Memory object for another memory type cannot be mapped.
+    let result = unsafe {
+        harness
+            .allocator
+            .alloc(&harness.device, host_request(100, usage, 3))
+    };
+    assert_eq!(result.unwrap_err(), AllocationError::OutOfHostMemory);
+    assert_eq!(harness.device.live_allocations(), 1);
+
+    harness.check().unwrap();
+    harness.finish().unwrap();
+}
+
+#[test]
+fn buddy_map_failure_does_not_leak() {
+    check_map_failure(UsageFlags::empty());
+}
+
+#[test]
+fn freelist_map_failure_does_not_leak() {
+    check_map_failure(UsageFlags::TRANSIENT);
+}
+
+#[test]
+fn minimally_aligned_mappings() {
+    let mut harness = harness();
+    harness.device.set_min_memory_map_alignment(64);
+
+    let request = host_request(1000, UsageFlags::empty(), 1);
+    let mut block = unsafe {
+        harness
+            .allocator
+            .alloc_with_dedicated(&harness.device, request, Dedicated::Required)
+    }
+    .unwrap();
+
+    let ptr = unsafe { block.map(&harness.device, 0, 1000) }.unwrap();
+    assert_eq!(ptr.as_ptr() as usize % 64, 0);
+    assert_ne!(ptr.as_ptr() as usize % 128, 0);
+    unsafe { block.unmap(&harness.device) };
+
+    let data: Vec<u8> = (0..255).collect();
+    unsafe { block.write_bytes(&harness.device, 3, &data) }.unwrap();
+
+    let mut read = vec![0; 255];
+    unsafe { block.read_bytes(&harness.device, 3, &mut read) }.unwrap();
+    assert_eq!(data, read);
+
+    harness.blocks.push((block, request));
+    harness.finish().unwrap();
+}
+
+#[test]
+fn flush_failure_is_reported() {
+    let mut harness = harness();
+    harness.device.fail_flush_after(1);
+
+    // Non-coherent memory type requires flushing.
+    let request = host_request(1000, UsageFlags::empty(), 3);
+    let mut block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+
+    unsafe { block.write_bytes(&harness.device, 0, &[1, 2, 3]) }.unwrap();
+
+    let result = unsafe { block.write_bytes(&harness.device, 0, &[4, 5, 6]) };
+    assert_eq!(result.unwrap_err(), MapError::OutOfHostMemory);
+
+    // Block is unmapped after failure.
+    unsafe { block.map(&harness.device, 0, 3) }.unwrap();
+    assert!(unsafe { block.unmap(&harness.device) });
+
+    harness.blocks.push((block, request));
+    harness.finish().unwrap();
+}
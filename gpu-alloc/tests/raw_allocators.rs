@@ -0,0 +1,87 @@
+#![cfg(feature = "raw-allocators")]
+
+mod harness;
+
+use {
+    gpu_alloc::{
+        AllocationError, AllocationFlags, BuddyAllocator, LinearAllocator, MemoryPropertyFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    harness::device_properties,
+};
+
+const HOST_VISIBLE: MemoryPropertyFlags = MemoryPropertyFlags::from_bits_truncate(
+    MemoryPropertyFlags::HOST_VISIBLE.bits() | MemoryPropertyFlags::HOST_COHERENT.bits(),
+);
+
+#[test]
+fn buddy_blocks_share_chunk() {
+    let device = MockMemoryDevice::new(device_properties(64));
+    let mut allocator = BuddyAllocator::new(256, 4096, 1, HOST_VISIBLE, 0);
+
+    let blocks: Vec<_> = (0..4)
+        .map(|_| unsafe { allocator.alloc(&device, 1000, 255, AllocationFlags::empty()) }.unwrap())
+        .collect();
+    assert_eq!(device.total_allocations(), 1);
+    assert_eq!(allocator.memory_bytes(), 4096);
+
+    for (index, block) in blocks.iter().enumerate() {
+        assert_eq!(block.size(), 1024);
+        assert_eq!(block.offset() % 256, 0);
+        assert!(block.ptr().is_some());
+        assert!(std::ptr::eq(block.memory(), blocks[0].memory()));
+
+        for other in &blocks[index + 1..] {
+            assert!(
+                block.offset() + block.size() <= other.offset()
+                    || other.offset() + other.size() <= block.offset()
+            );
+        }
+    }
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) }
+    }
+    assert_eq!(device.live_allocations(), 0);
+
+    unsafe { allocator.cleanup(&device) }
+    assert_eq!(allocator.memory_bytes(), 0);
+}
+
+#[test]
+fn linear_newest_chunk_kept_until_cleanup() {
+    let device = MockMemoryDevice::new(device_properties(64));
+    let mut allocator = LinearAllocator::new(1024, 4096, 0, MemoryPropertyFlags::DEVICE_LOCAL, 0);
+
+    let first = unsafe { allocator.alloc(&device, 1000, 0, AllocationFlags::empty()) }.unwrap();
+    let second = unsafe { allocator.alloc(&device, 1000, 0, AllocationFlags::empty()) }.unwrap();
+    assert_eq!(device.total_allocations(), 2);
+    assert!(first.ptr().is_none());
+
+    unsafe {
+        allocator.dealloc(&device, first);
+        allocator.dealloc(&device, second);
+    }
+    assert_eq!(device.live_allocations(), 1);
+
+    unsafe { allocator.cleanup(&device) }
+    assert_eq!(device.live_allocations(), 0);
+    assert_eq!(allocator.memory_bytes(), 0);
+}
+
+#[test]
+fn invalid_requests_rejected() {
+    let device = MockMemoryDevice::new(device_properties(64));
+    let mut buddy = BuddyAllocator::new(256, 4096, 1, HOST_VISIBLE, 0);
+    let mut linear = LinearAllocator::new(1024, 4096, 1, HOST_VISIBLE, 0);
+
+    assert_eq!(
+        unsafe { buddy.alloc(&device, 16, 3 << 4, AllocationFlags::empty()) }.unwrap_err(),
+        AllocationError::InvalidAlignment
+    );
+    assert_eq!(
+        unsafe { linear.alloc(&device, 8192, 0, AllocationFlags::empty()) }.unwrap_err(),
+        AllocationError::OutOfDeviceMemory
+    );
+    assert_eq!(device.total_allocations(), 0);
+}
@@ -0,0 +1,56 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Dedicated, MemoryBlock, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+#[test]
+fn raw_parts_round_trip() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let request = Request {
+        size: 1024,
+        align_mask: 63,
+        usage: UsageFlags::UPLOAD | UsageFlags::HOST_ACCESS,
+        memory_types: !0,
+        kind: ResourceKind::Linear,
+    };
+
+    for dedicated in [None, Some(Dedicated::Required)] {
+        let mut block = unsafe {
+            match dedicated {
+                None => harness.allocator.alloc(&harness.device, request),
+                Some(dedicated) => {
+                    harness
+                        .allocator
+                        .alloc_with_dedicated(&harness.device, request, dedicated)
+                }
+            }
+        }
+        .unwrap();
+        unsafe { block.write_bytes(&harness.device, 0, &[42; 1024]) }.unwrap();
+
+        let (memory, offset, size, memory_type) = (
+            *block.memory(),
+            block.offset(),
+            block.size(),
+            block.memory_type(),
+        );
+
+        let raw = block.into_raw();
+        assert_eq!(*raw.token.memory(), memory);
+        assert_eq!(raw.offset, offset);
+        assert_eq!(raw.size, size);
+        assert_eq!(raw.memory_type, memory_type);
+
+        let mut block = unsafe { MemoryBlock::from_raw(raw) };
+        let mut data = [0; 1024];
+        unsafe { block.read_bytes(&harness.device, 0, &mut data) }.unwrap();
+        assert!(data.iter().all(|&byte| byte == 42));
+
+        unsafe { harness.allocator.dealloc(&harness.device, block) };
+    }
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,82 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Dedicated, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+const MB: u64 = 1024 * 1024;
+
+fn request(size: u64, memory_types: u32) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types,
+        kind: ResourceKind::Linear,
+    }
+}
+
+fn alloc(harness: &mut Harness, request: Request, dedicated: Option<Dedicated>) -> usize {
+    let block = unsafe {
+        match dedicated {
+            None => harness.allocator.alloc(&harness.device, request),
+            Some(dedicated) => {
+                harness
+                    .allocator
+                    .alloc_with_dedicated(&harness.device, request, dedicated)
+            }
+        }
+    }
+    .unwrap();
+    let id = block.id();
+    harness.blocks.push((block, request));
+    id
+}
+
+#[test]
+fn nothing_to_move_within_budget() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    alloc(&mut harness, request(4 * MB, !0), None);
+
+    assert!(harness
+        .allocator
+        .plan_rebalance(&[64 * MB, 16 * MB])
+        .is_empty());
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn moves_compatible_blocks_to_other_heap() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let pinned = alloc(&mut harness, request(8 * MB, 1 << 0), None);
+    let small = alloc(&mut harness, request(MB, !0), Some(Dedicated::Required));
+    let large = alloc(&mut harness, request(4 * MB, !0), Some(Dedicated::Required));
+    let suballocated = alloc(&mut harness, request(256, !0), None);
+    assert_eq!(harness.allocator.heap_usage(0), 13 * MB + 8 * 1024);
+
+    // Heap 0 should shrink by 4MB, heap 1 can take 6MB.
+    let moves = harness
+        .allocator
+        .plan_rebalance(&[9 * MB + 8 * 1024, 6 * MB]);
+    assert_eq!(moves.len(), 1);
+    assert_eq!(moves[0].id, large);
+    assert_eq!(moves[0].size, 4 * MB);
+    assert_eq!(moves[0].from, 0);
+    assert!(moves[0].to == 1 || moves[0].to == 3);
+
+    // Moving only large block is not enough, but other heap has no room for more.
+    let moves = harness.allocator.plan_rebalance(&[8 * MB, 4 * MB]);
+    let ids: Vec<usize> = moves.iter().map(|m| m.id).collect();
+    assert_eq!(ids, [large]);
+
+    // With room on other heap dedicated block goes first, then sub-allocated block.
+    let moves = harness.allocator.plan_rebalance(&[8 * MB, 16 * MB]);
+    let ids: Vec<usize> = moves.iter().map(|m| m.id).collect();
+    assert_eq!(ids, [large, small, suballocated]);
+    assert!(!ids.contains(&pinned));
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,82 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Dedicated, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64, memory_type: u32) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::empty(),
+        memory_types: 1 << memory_type,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn every_strategy_reconciles() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let requests = [
+        (request(256, 0), None),
+        (request(64 * 1024, 0), None),
+        (request(1024, 1), Some(Dedicated::Required)),
+        (request(512, 2), Some(Dedicated::Preferred)),
+        (request(512, 3), None),
+    ];
+
+    for &(request, dedicated) in &requests {
+        let block = unsafe {
+            match dedicated {
+                None => harness.allocator.alloc(&harness.device, request),
+                Some(dedicated) => {
+                    harness
+                        .allocator
+                        .alloc_with_dedicated(&harness.device, request, dedicated)
+                }
+            }
+        }
+        .unwrap();
+        harness.blocks.push((block, request));
+        harness.check().unwrap();
+    }
+
+    while let Some((block, _)) = harness.blocks.pop() {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+        assert_eq!(harness.allocator.reconcile(), Ok(()));
+    }
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn concurrent_dedicated_reconciles() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let concurrent = unsafe {
+        harness
+            .allocator
+            .alloc_dedicated_concurrent(&harness.device, request(4096, 0))
+    }
+    .unwrap();
+
+    let block = unsafe { harness.allocator.alloc(&harness.device, request(256, 0)) }.unwrap();
+    harness.blocks.push((block, request(256, 0)));
+
+    assert_eq!(harness.allocator.reconcile(), Ok(()));
+    assert_eq!(
+        harness.allocator.heap_usage(0),
+        harness.device.allocated_memory(0)
+    );
+
+    unsafe {
+        harness
+            .allocator
+            .dealloc_concurrent(&harness.device, concurrent)
+    }
+    assert_eq!(harness.allocator.reconcile(), Ok(()));
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,69 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64, usage: UsageFlags) -> Request {
+    Request {
+        size,
+        align_mask: 255,
+        usage,
+        memory_types: !0,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn report_matches_allocated_blocks() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    for (size, usage) in [
+        (1000, UsageFlags::FAST_DEVICE_ACCESS),
+        (3000, UsageFlags::FAST_DEVICE_ACCESS),
+        (100_000, UsageFlags::FAST_DEVICE_ACCESS),
+        (2000, UsageFlags::HOST_ACCESS | UsageFlags::TRANSIENT),
+    ] {
+        let request = request(size, usage);
+        let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+        harness.blocks.push((block, request));
+    }
+    harness.check().unwrap();
+
+    let report = harness.allocator.report();
+    assert_eq!(report.memory_objects, harness.device.live_allocations());
+    assert_eq!(
+        u64::from(report.remaining_allocations) + report.memory_objects,
+        64
+    );
+
+    let blocks: u64 = report.types.iter().map(|t| t.blocks).sum();
+    assert_eq!(blocks, 4);
+
+    for heap in &report.heaps {
+        assert_eq!(
+            heap.allocated_bytes,
+            harness.device.allocated_memory(heap.index)
+        );
+        assert!(heap.used_bytes <= heap.allocated_bytes);
+
+        let types = report.types.iter().filter(|t| t.heap == heap.index);
+        assert_eq!(
+            types.clone().map(|t| t.allocated_bytes).sum::<u64>(),
+            heap.allocated_bytes
+        );
+        assert_eq!(types.map(|t| t.chunks).sum::<u64>(), heap.chunks);
+    }
+
+    // Dedicated block is not a chunk.
+    let device_local = &report.types[0];
+    assert_eq!(device_local.memory_objects, device_local.chunks + 1);
+    assert!(device_local.largest_free_region > 0);
+
+    let text = report.to_string();
+    assert!(text.contains("heap 0:"));
+    assert!(text.contains("  type 0:"));
+
+    harness.finish().unwrap();
+}
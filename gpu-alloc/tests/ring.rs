@@ -0,0 +1,139 @@
+mod harness;
+
+use {
+    gpu_alloc::{ChunkKind, Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn transient(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 255,
+        usage: UsageFlags::HOST_ACCESS | UsageFlags::TRANSIENT,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+fn alloc(harness: &mut Harness, request: Request) {
+    let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    harness.blocks.push((block, request));
+}
+
+fn dealloc_all(harness: &mut Harness) {
+    for (block, _) in harness.blocks.drain(..) {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+}
+
+#[test]
+fn transient_blocks_of_frame_are_served_by_ring() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    harness.allocator.begin_frame(0);
+    for _ in 0..4 {
+        alloc(&mut harness, transient(1000));
+    }
+    harness.allocator.end_frame();
+
+    // Outside of frame transient blocks use free-list allocator.
+    alloc(&mut harness, transient(1000));
+
+    let stats = harness.allocator.stats();
+    assert_eq!(stats.types[1].ring.blocks, 4);
+    assert_eq!(stats.types[1].free_list.blocks, 1);
+    assert!(harness.allocator.dump().types[1]
+        .chunks
+        .iter()
+        .any(|chunk| chunk.kind == ChunkKind::Ring && chunk.blocks.len() == 4));
+    harness.check().unwrap();
+
+    dealloc_all(&mut harness);
+    unsafe { harness.allocator.release_frame(&harness.device, 0) };
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn ring_memory_is_reused_across_frames() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let mut in_flight = Vec::new();
+    for frame in 0..32 {
+        harness.allocator.begin_frame(frame);
+        let mut blocks = Vec::new();
+        for _ in 0..3 {
+            let block =
+                unsafe { harness.allocator.alloc(&harness.device, transient(1000)) }.unwrap();
+            blocks.push(block);
+        }
+        harness.allocator.end_frame();
+        in_flight.push((frame, blocks));
+
+        // Two frames in flight.
+        if in_flight.len() > 2 {
+            let (done, blocks) = in_flight.remove(0);
+            for block in blocks {
+                unsafe { harness.allocator.dealloc(&harness.device, block) }
+            }
+            unsafe { harness.allocator.release_frame(&harness.device, done) };
+        }
+    }
+
+    assert_eq!(harness.device.live_allocations(), 1);
+
+    for (_, blocks) in in_flight {
+        for block in blocks {
+            harness.blocks.push((block, transient(1000)));
+        }
+    }
+    harness.check().unwrap();
+
+    dealloc_all(&mut harness);
+    unsafe { harness.allocator.release_frame(&harness.device, 31) };
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn frames_are_reclaimed_in_order() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    harness.allocator.begin_frame(1);
+    let first = unsafe { harness.allocator.alloc(&harness.device, transient(6000)) }.unwrap();
+    harness.allocator.end_frame();
+
+    harness.allocator.begin_frame(2);
+    let second = unsafe { harness.allocator.alloc(&harness.device, transient(6000)) }.unwrap();
+    harness.allocator.end_frame();
+    assert_ne!(first.memory(), second.memory());
+    assert_eq!(harness.device.live_allocations(), 2);
+
+    // Second frame is done, but first frame still has live block.
+    unsafe {
+        harness.allocator.dealloc(&harness.device, second);
+        harness.allocator.release_frame(&harness.device, 2);
+    }
+    assert_eq!(harness.device.live_allocations(), 2);
+
+    unsafe {
+        harness.allocator.dealloc(&harness.device, first);
+        harness.allocator.release_frame(&harness.device, 2);
+    }
+
+    // Retired chunk is released, current one is kept for next frames.
+    assert_eq!(harness.device.live_allocations(), 1);
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+#[should_panic(expected = "must be greater")]
+fn frame_indices_must_increase() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    harness.allocator.begin_frame(2);
+    harness.allocator.end_frame();
+    harness.allocator.begin_frame(2);
+}
@@ -0,0 +1,83 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, MapError, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+    std::io::IoSlice,
+};
+
+fn harness() -> Harness {
+    Harness::new(Config::i_am_potato(), device_properties(32))
+}
+
+/// Request for non-coherent memory type that requires flushing.
+fn non_coherent_request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: 1 << 3,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn vectored_write_flushes_once() {
+    let mut harness = harness();
+    harness.device.fail_flush_after(1);
+
+    let request = non_coherent_request(1000);
+    let mut block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+
+    let iov = [
+        IoSlice::new(&[1, 2, 3]),
+        IoSlice::new(&[]),
+        IoSlice::new(&[4, 5]),
+    ];
+    unsafe { block.write_bytes_vectored(&harness.device, 10, &iov) }.unwrap();
+
+    let mut read = [0; 5];
+    unsafe { block.read_bytes(&harness.device, 10, &mut read) }.unwrap();
+    assert_eq!(read, [1, 2, 3, 4, 5]);
+
+    assert_eq!(
+        unsafe { block.write_bytes_vectored(&harness.device, block.size() - 2, &iov) },
+        Err(MapError::OutOfBounds)
+    );
+
+    harness.blocks.push((block, request));
+    harness.finish().unwrap();
+}
+
+#[test]
+fn scattered_write_flushes_once() {
+    let mut harness = harness();
+    harness.device.fail_flush_after(1);
+
+    let request = non_coherent_request(1000);
+    let mut block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+
+    let writes: [(u64, &[u8]); 3] = [(500, &[9; 4]), (20, &[1, 2]), (501, &[7])];
+    unsafe { block.write_bytes_scattered(&harness.device, &writes) }.unwrap();
+
+    let mut read = [0; 4];
+    unsafe { block.read_bytes(&harness.device, 500, &mut read) }.unwrap();
+    assert_eq!(read, [9, 7, 9, 9]);
+    let mut read = [0; 2];
+    unsafe { block.read_bytes(&harness.device, 20, &mut read) }.unwrap();
+    assert_eq!(read, [1, 2]);
+
+    // Only one flush succeeds.
+    assert_eq!(
+        unsafe { block.write_bytes_scattered(&harness.device, &[(0, &[1])]) },
+        Err(MapError::OutOfHostMemory)
+    );
+    let end = block.size() - 1;
+    assert_eq!(
+        unsafe { block.write_bytes_scattered(&harness.device, &[(end, &[1, 2])]) },
+        Err(MapError::OutOfBounds)
+    );
+
+    harness.blocks.push((block, request));
+    harness.finish().unwrap();
+}
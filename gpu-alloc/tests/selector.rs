@@ -0,0 +1,132 @@
+mod harness;
+
+use {
+    gpu_alloc::{
+        AllocationError, Config, DefaultScoring, MemoryPropertyFlags, MemoryTypeCandidate,
+        MemoryTypeScoring, Request, ResourceKind, UsageFlags,
+    },
+    harness::{device_properties, Harness},
+    std::sync::{Arc, Mutex},
+};
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS,
+        memory_types: !0,
+        kind: ResourceKind::Linear,
+    }
+}
+
+fn alloc(harness: &mut Harness, request: Request) -> u32 {
+    let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    let memory_type = block.memory_type();
+    harness.blocks.push((block, request));
+    harness.check().unwrap();
+    memory_type
+}
+
+#[test]
+fn selector_reranks_candidates() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let default = alloc(&mut harness, request(1024));
+
+    // Prefer memory types with higher index.
+    harness.allocator.set_memory_type_selector(Box::new(
+        |_: &Request, candidate: &MemoryTypeCandidate| Some(u32::MAX - candidate.memory_type),
+    ));
+    assert_eq!(alloc(&mut harness, request(1024)), 3);
+    assert_ne!(default, 3);
+
+    // Equal ranks keep default order.
+    harness
+        .allocator
+        .set_memory_type_selector(Box::new(|_: &Request, _: &MemoryTypeCandidate| Some(0)));
+    assert_eq!(alloc(&mut harness, request(1024)), default);
+
+    assert!(harness.allocator.take_memory_type_selector().is_some());
+    assert!(harness.allocator.take_memory_type_selector().is_none());
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn selector_vetoes_candidates() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let offered = Arc::new(Mutex::new(Vec::new()));
+    let record = offered.clone();
+    harness.allocator.set_memory_type_selector(Box::new(
+        move |request: &Request, candidate: &MemoryTypeCandidate| {
+            record.lock().unwrap().push((request.size, *candidate));
+            if candidate.heap == 0 {
+                None
+            } else {
+                Some(0)
+            }
+        },
+    ));
+
+    let memory_type = alloc(&mut harness, request(1024));
+    assert_eq!(
+        device_properties(64).memory_types[memory_type as usize].heap,
+        1
+    );
+
+    let offered = offered.lock().unwrap();
+    let mut types: Vec<u32> = offered
+        .iter()
+        .map(|(_, candidate)| candidate.memory_type)
+        .collect();
+    types.sort_unstable();
+    assert_eq!(types, [1, 2, 3]);
+    for (size, candidate) in offered.iter() {
+        assert_eq!(*size, 1024);
+        assert_eq!(candidate.heap_used, 0);
+    }
+    drop(offered);
+
+    // Vetoing all compatible memory types fails the request.
+    let err = unsafe {
+        harness.allocator.alloc(
+            &harness.device,
+            Request {
+                memory_types: 1 << 2,
+                ..request(1024)
+            },
+        )
+    }
+    .unwrap_err();
+    assert_eq!(err, AllocationError::NoCompatibleMemoryTypes);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn scoring_reorders_memory_types() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+    let upload = Request {
+        usage: UsageFlags::UPLOAD,
+        ..request(1024)
+    };
+    assert_eq!(
+        alloc(&mut harness, upload),
+        1,
+        "uncached memory for uploads"
+    );
+
+    // Prefer cached memory above anything else, as on some UMA platforms.
+    harness
+        .allocator
+        .set_memory_type_scoring(&|usage: UsageFlags, props: MemoryPropertyFlags| {
+            let uncached = !props.contains(MemoryPropertyFlags::HOST_CACHED) as u32;
+            uncached * 16 + DefaultScoring.score(usage, props)
+        });
+    assert_eq!(alloc(&mut harness, upload), 3);
+
+    harness.allocator.set_memory_type_scoring(&DefaultScoring);
+    assert_eq!(alloc(&mut harness, upload), 1);
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,111 @@
+#![cfg(feature = "std")]
+
+mod harness;
+
+use {
+    gpu_alloc::{
+        AllocationError, Config, Dedicated, DynMemoryDevice, GpuAllocator, Request, ResourceKind,
+        SharedAllocator, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    harness::device_properties,
+};
+
+fn request(usage: UsageFlags) -> Request {
+    Request {
+        size: 1024,
+        align_mask: 0,
+        usage: usage | UsageFlags::HOST_ACCESS,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn memory_deallocated_on_drop() {
+    let device = MockMemoryDevice::new(device_properties(64));
+    let allocator = GpuAllocator::new(Config::i_am_potato(), device_properties(64));
+    let shared = unsafe { SharedAllocator::new(allocator, DynMemoryDevice::new(&device)) };
+
+    let mut memory = shared.alloc(request(UsageFlags::empty())).unwrap();
+    let mut dedicated = shared
+        .alloc_with_dedicated(request(UsageFlags::empty()), Dedicated::Required)
+        .unwrap();
+    let tagged = shared
+        .clone()
+        .alloc_tagged(request(UsageFlags::TRANSIENT), "tagged")
+        .unwrap();
+    assert_eq!(tagged.tag(), Some("tagged"));
+    assert_eq!(device.live_allocations(), 3);
+
+    unsafe { memory.write_bytes(0, &[1, 2, 3, 4]) }.unwrap();
+    let mut data = [0; 4];
+    unsafe { memory.read_bytes(0, &mut data) }.unwrap();
+    assert_eq!(data, [1, 2, 3, 4]);
+
+    let ptr = unsafe { dedicated.map(0, 16) }.unwrap();
+    unsafe { ptr.as_ptr().write_bytes(5, 16) };
+    assert!(unsafe { dedicated.unmap() });
+
+    drop(dedicated);
+    assert_eq!(device.live_allocations(), 2);
+    assert_eq!(shared.allocator().stats().types[1].total().blocks, 2);
+
+    drop(memory);
+    drop(tagged);
+    assert_eq!(shared.allocator().stats().types[1].total().blocks, 0);
+    device.check_invariants();
+}
+
+#[test]
+fn memory_objects_released_with_last_handle() {
+    let device = MockMemoryDevice::new(device_properties(64));
+    let allocator = GpuAllocator::new(Config::i_am_potato(), device_properties(64));
+    let shared = unsafe { SharedAllocator::new(allocator, DynMemoryDevice::new(&device)) };
+
+    let memory = shared.alloc(request(UsageFlags::TRANSIENT)).unwrap();
+    drop(shared);
+    assert_eq!(device.live_allocations(), 1);
+
+    drop(memory);
+    assert_eq!(device.live_allocations(), 0);
+    device.check_invariants();
+}
+
+#[test]
+fn scope_hands_chunks_back() {
+    let device = MockMemoryDevice::new(device_properties(64));
+    let allocator = GpuAllocator::new(Config::i_am_potato(), device_properties(64));
+    let shared = unsafe { SharedAllocator::new(allocator, DynMemoryDevice::new(&device)) };
+
+    let mut scope = shared.scope(4096, 255, UsageFlags::empty(), 1 << 1);
+    assert_eq!(shared.allocator().stats().types[1].total().blocks, 0);
+
+    let block = scope.alloc(1000).unwrap();
+    unsafe { block.write_bytes(0, &[1, 2, 3, 4]) }.unwrap();
+    assert_eq!(block.size(), 1000);
+
+    // Aligned sizes fill the chunk exactly.
+    scope.alloc(3000).unwrap();
+    assert_eq!(shared.allocator().stats().types[1].total().blocks, 1);
+
+    // Exhausted chunk is replaced.
+    scope.alloc(100).unwrap();
+    assert_eq!(shared.allocator().stats().types[1].total().blocks, 2);
+
+    assert_eq!(
+        scope.alloc(5000).unwrap_err(),
+        AllocationError::SizeOverflow
+    );
+    scope.flush().unwrap();
+
+    unsafe { scope.reset() };
+    assert_eq!(shared.allocator().stats().types[1].total().blocks, 1);
+
+    drop(scope);
+    assert_eq!(shared.allocator().stats().types[1].total().blocks, 0);
+
+    drop(shared);
+    assert_eq!(device.live_allocations(), 0);
+    device.check_invariants();
+}
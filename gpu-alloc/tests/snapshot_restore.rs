@@ -0,0 +1,162 @@
+mod harness;
+
+use {
+    gpu_alloc::{AllocationError, ChunkKind, Config, Dedicated, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, ops, Harness},
+    proptest::prelude::*,
+};
+
+fn request(size: u64, align_mask: u64, usage: UsageFlags) -> Request {
+    Request {
+        size,
+        align_mask,
+        usage,
+        memory_types: (1 << 1) | (1 << 2),
+        kind: ResourceKind::Linear,
+    }
+}
+
+/// Releases all blocks and device memory, as application does on suspend.
+fn suspend(harness: &mut Harness) {
+    for (block, _) in harness.blocks.drain(..) {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+    unsafe { harness.allocator.cleanup(&harness.device) }
+    assert_eq!(harness.device.live_allocations(), 0);
+}
+
+#[test]
+fn restored_blocks_match_snapshot() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+
+    let requests = [
+        (
+            request(256, 255, UsageFlags::HOST_ACCESS),
+            None,
+            Some("mesh"),
+        ),
+        (request(100, 0, UsageFlags::TRANSIENT), None, None),
+        (
+            request(4096, 0, UsageFlags::empty()),
+            Some(Dedicated::Required),
+            Some("texture"),
+        ),
+    ];
+
+    for &(request, dedicated, tag) in &requests {
+        let block = unsafe {
+            match (dedicated, tag) {
+                (None, None) => harness.allocator.alloc(&harness.device, request),
+                (None, Some(tag)) => harness
+                    .allocator
+                    .alloc_tagged(&harness.device, request, tag),
+                (Some(dedicated), None) => {
+                    harness
+                        .allocator
+                        .alloc_with_dedicated(&harness.device, request, dedicated)
+                }
+                (Some(dedicated), Some(tag)) => harness.allocator.alloc_with_dedicated_tagged(
+                    &harness.device,
+                    request,
+                    dedicated,
+                    tag,
+                ),
+            }
+        }
+        .unwrap();
+        harness.blocks.push((block, request));
+    }
+
+    let snapshot = harness.allocator.snapshot();
+    assert_eq!(snapshot.blocks.len(), 3);
+
+    let old: Vec<_> = harness
+        .blocks
+        .iter()
+        .map(|(block, request)| (block.id(), block.memory_type(), block.tag(), *request))
+        .collect();
+
+    suspend(&mut harness);
+
+    let restored = unsafe { harness.allocator.restore(&harness.device, &snapshot) }.unwrap();
+    assert_eq!(restored.len(), 3);
+
+    for (id, block) in restored {
+        let &(_, memory_type, tag, request) = old.iter().find(|old| old.0 == id).unwrap();
+        assert_eq!(block.memory_type(), memory_type);
+        assert_eq!(block.tag(), tag);
+        assert!(block.size() >= request.size);
+        assert_eq!(block.offset() & request.align_mask, 0);
+
+        let snapshot_block = snapshot.blocks.iter().find(|block| block.id == id).unwrap();
+        if snapshot_block.kind == ChunkKind::Dedicated {
+            assert_eq!(block.offset(), 0);
+        }
+
+        harness.blocks.push((block, request));
+    }
+    harness.check().unwrap();
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn failed_restore_releases_blocks() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(2));
+
+    for _ in 0..2 {
+        let request = request(4096, 0, UsageFlags::empty());
+        let block = unsafe {
+            harness
+                .allocator
+                .alloc_with_dedicated(&harness.device, request, Dedicated::Required)
+        }
+        .unwrap();
+        harness.blocks.push((block, request));
+    }
+    let snapshot = harness.allocator.snapshot();
+    suspend(&mut harness);
+
+    // Another block takes memory object slot before restore.
+    let request = request(4096, 0, UsageFlags::empty());
+    let block = unsafe {
+        harness
+            .allocator
+            .alloc_with_dedicated(&harness.device, request, Dedicated::Required)
+    }
+    .unwrap();
+    harness.blocks.push((block, request));
+
+    let err = unsafe { harness.allocator.restore(&harness.device, &snapshot) }.unwrap_err();
+    assert_eq!(err, AllocationError::TooManyObjects);
+    assert_eq!(harness.device.live_allocations(), 1);
+
+    harness.finish().unwrap();
+}
+
+proptest! {
+    #![proptest_config(harness::proptest_config())]
+
+    #[test]
+    fn restore_after_random_ops(ops in ops(256 * 1024, 64)) {
+        let mut harness = Harness::new(Config::i_am_potato(), device_properties(64));
+        harness.run(&ops)?;
+
+        let snapshot = harness.allocator.snapshot();
+        prop_assert_eq!(snapshot.blocks.len(), harness.blocks.len());
+
+        let mut requests: Vec<_> = harness.blocks.iter().map(|(block, request)| (block.id(), *request)).collect();
+        requests.sort_unstable_by_key(|&(id, _)| id);
+        suspend(&mut harness);
+
+        let restored = unsafe { harness.allocator.restore(&harness.device, &snapshot) }
+            .map_err(|err| TestCaseError::fail(err.to_string()))?;
+
+        for (id, block) in restored {
+            let index = requests.binary_search_by_key(&id, |&(id, _)| id).unwrap();
+            harness.blocks.push((block, requests[index].1));
+        }
+        harness.check()?;
+        harness.finish()?;
+    }
+}
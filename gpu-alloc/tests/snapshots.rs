@@ -0,0 +1,167 @@
+//!
+//! Snapshot tests of allocator internal state.
+//!
+//! Each script is run step by step and `GpuAllocator::debug_state` is recorded after every step.
+//! Result is compared with `tests/snapshots/<script>.txt`.
+//! Set `GPU_ALLOC_UPDATE_SNAPSHOTS=1` to overwrite snapshots with current output
+//! after verifying that change of the state is intended.
+//!
+
+mod harness;
+
+use {
+    gpu_alloc::{Config, Dedicated, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness, Op},
+    std::{fmt::Write as _, path::PathBuf},
+};
+
+fn alloc(size: u64, align_mask: u64, usage: UsageFlags, memory_type: u32) -> Op {
+    Op::Alloc {
+        request: Request {
+            size,
+            align_mask,
+            usage,
+            memory_types: 1 << memory_type,
+            kind: ResourceKind::Linear,
+        },
+        dedicated: None,
+    }
+}
+
+fn dealloc(index: usize) -> Op {
+    Op::Dealloc { index }
+}
+
+fn check_snapshot(name: &str, config: Config, ops: &[Op]) {
+    let mut harness = Harness::new(config, device_properties(32));
+    let mut output = String::new();
+
+    writeln!(output, "== initial").unwrap();
+    writeln!(output, "{}", harness.allocator.debug_state()).unwrap();
+
+    for (step, &op) in ops.iter().enumerate() {
+        harness.apply(op);
+        harness.check().unwrap();
+
+        writeln!(output, "== step {}: {:?}", step, op).unwrap();
+        writeln!(output, "{}", harness.allocator.debug_state()).unwrap();
+    }
+
+    harness.finish().unwrap();
+
+    let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "snapshots"]
+        .iter()
+        .collect::<PathBuf>()
+        .join(format!("{}.txt", name));
+
+    if std::env::var_os("GPU_ALLOC_UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, &output).unwrap();
+        return;
+    }
+
+    let expected = std::fs::read_to_string(&path).unwrap_or_else(|err| {
+        panic!(
+            "Failed to read snapshot {}: {}. Run with `GPU_ALLOC_UPDATE_SNAPSHOTS=1` to create it",
+            path.display(),
+            err
+        )
+    });
+
+    if expected != output {
+        let line = expected
+            .lines()
+            .zip(output.lines())
+            .position(|(expected, actual)| expected != actual)
+            .unwrap_or_else(|| expected.lines().count().min(output.lines().count()));
+
+        panic!(
+            "Allocator state diverged from snapshot {} at line {}:\nexpected: {:?}\nactual:   {:?}",
+            path.display(),
+            line + 1,
+            expected.lines().nth(line),
+            output.lines().nth(line),
+        );
+    }
+}
+
+#[test]
+fn buddy_split_and_merge() {
+    let usage = UsageFlags::FAST_DEVICE_ACCESS;
+
+    check_snapshot(
+        "buddy_split_and_merge",
+        Config::i_am_potato(),
+        &[
+            alloc(100, 0, usage, 0),
+            alloc(1000, 0, usage, 0),
+            alloc(3000, 255, usage, 0),
+            alloc(10, 0, usage, 0),
+            dealloc(1),
+            alloc(500, 0, usage, 0),
+            dealloc(0),
+            dealloc(2),
+            alloc(8192, 0, usage, 0),
+            dealloc(0),
+            dealloc(1),
+            dealloc(0),
+            Op::Cleanup,
+        ],
+    );
+}
+
+#[test]
+fn freelist_regions() {
+    let usage = UsageFlags::TRANSIENT | UsageFlags::HOST_ACCESS;
+
+    check_snapshot(
+        "freelist_regions",
+        Config::i_am_potato(),
+        &[
+            alloc(1000, 63, usage, 1),
+            alloc(2000, 63, usage, 1),
+            alloc(3000, 255, usage, 1),
+            alloc(6000, 0, usage, 1),
+            dealloc(1),
+            alloc(500, 0, usage, 1),
+            dealloc(0),
+            dealloc(0),
+            Op::Cleanup,
+            dealloc(0),
+            dealloc(0),
+            Op::Cleanup,
+        ],
+    );
+}
+
+#[test]
+fn mixed_strategies() {
+    let usage = UsageFlags::HOST_ACCESS;
+
+    check_snapshot(
+        "mixed_strategies",
+        Config::i_am_potato(),
+        &[
+            alloc(100, 0, usage, 1),
+            alloc(100, 0, usage | UsageFlags::TRANSIENT, 1),
+            Op::Alloc {
+                request: Request {
+                    size: 100,
+                    align_mask: 0,
+                    usage,
+                    memory_types: 1 << 1,
+                    kind: ResourceKind::Linear,
+                },
+                dedicated: Some(Dedicated::Required),
+            },
+            alloc(64 * 1024, 0, usage, 1),
+            alloc(5000, 0, UsageFlags::empty(), 3),
+            dealloc(0),
+            dealloc(0),
+            Op::Cleanup,
+            dealloc(0),
+            dealloc(0),
+            dealloc(0),
+            Op::Cleanup,
+        ],
+    );
+}
@@ -0,0 +1,48 @@
+mod harness;
+
+use {
+    gpu_alloc::{BlockSuballocator, Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+#[test]
+fn sub_ranges_within_block() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let request = Request {
+        size: 4096,
+        align_mask: 255,
+        usage: UsageFlags::UPLOAD | UsageFlags::HOST_ACCESS,
+        memory_types: !0,
+        kind: ResourceKind::Linear,
+    };
+    let block = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+    let block_offset = block.offset();
+    let mut suballocator = BlockSuballocator::new(block);
+
+    let a = suballocator.alloc(100, 0).unwrap();
+    let b = suballocator.alloc(1000, 63).unwrap();
+    assert_eq!(a.offset, 0);
+    assert_eq!(b.offset, 128);
+    assert_eq!(suballocator.memory_offset(&b), block_offset + 128);
+    assert_eq!(suballocator.used(), 1100);
+
+    let data = [7u8; 1000];
+    unsafe {
+        suballocator
+            .block_mut()
+            .write_bytes(&harness.device, b.offset, &data)
+    }
+    .unwrap();
+
+    assert!(suballocator.alloc(4096, 0).is_err());
+
+    suballocator.dealloc(a);
+    suballocator.dealloc(b);
+    assert!(suballocator.is_empty());
+    assert!(suballocator.largest_free_region() >= 4096);
+
+    let block = suballocator.into_block();
+    unsafe { harness.allocator.dealloc(&harness.device, block) };
+    harness.finish().unwrap();
+}
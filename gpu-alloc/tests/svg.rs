@@ -0,0 +1,46 @@
+#![cfg(feature = "svg")]
+
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+#[test]
+fn svg_memory_map() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let request = Request {
+        size: 1000,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1,
+        kind: ResourceKind::Linear,
+    };
+
+    let blocks = unsafe {
+        vec![
+            harness
+                .allocator
+                .alloc_tagged(&harness.device, request, "<shadows & lights>")
+                .unwrap(),
+            harness.allocator.alloc(&harness.device, request).unwrap(),
+        ]
+    };
+
+    let svg = harness.allocator.dump().to_svg();
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    assert!(svg.contains("memory type 0"));
+    assert!(svg.contains("buddy #0"));
+    assert!(svg.contains("tag &lt;shadows &amp; lights&gt;"));
+    assert!(!svg.contains("<shadows"));
+    assert_eq!(svg.matches("requested 1000").count(), 2);
+
+    for block in blocks {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    harness.finish().unwrap();
+}
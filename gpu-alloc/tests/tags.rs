@@ -0,0 +1,133 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: !0,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn usage_by_tag_breakdown() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let mut blocks = Vec::new();
+    for (tag, size, count) in [
+        ("ui", 1024, 3),
+        ("shadows", 65536, 2),
+        ("streaming", 4096, 1),
+    ] {
+        for _ in 0..count {
+            let block = unsafe {
+                harness
+                    .allocator
+                    .alloc_tagged(&harness.device, request(size), tag)
+            }
+            .unwrap();
+            blocks.push(block);
+        }
+    }
+    blocks.push(unsafe { harness.allocator.alloc(&harness.device, request(512)) }.unwrap());
+
+    let usage: Vec<_> = harness
+        .allocator
+        .usage_by_tag()
+        .map(|usage| (usage.tag.into_owned(), usage.blocks, usage.bytes))
+        .collect();
+
+    assert_eq!(
+        usage,
+        [
+            ("shadows".to_owned(), 2, 2 * 65536),
+            ("streaming".to_owned(), 1, 4096),
+            ("ui".to_owned(), 3, 3 * 1024),
+        ]
+    );
+
+    // Release all "streaming" blocks, the tag disappears from breakdown.
+    let index = blocks
+        .iter()
+        .position(|block| block.tag() == Some("streaming"))
+        .unwrap();
+    let block = blocks.swap_remove(index);
+    unsafe { harness.allocator.dealloc(&harness.device, block) }
+
+    let tags: Vec<_> = harness
+        .allocator
+        .usage_by_tag()
+        .map(|usage| usage.tag)
+        .collect();
+    assert_eq!(tags, ["shadows", "ui"]);
+
+    for block in blocks {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    assert_eq!(harness.allocator.usage_by_tag().count(), 0);
+    harness.finish().unwrap();
+}
+
+#[test]
+fn leak_report_groups_live_blocks_by_tag() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    for (tag, size, count) in [
+        (Some("ui"), 1024, 3),
+        (Some("shadows"), 65536, 2),
+        (None, 512, 1),
+    ] {
+        let request = Request {
+            memory_types: 1 << 0,
+            ..request(size)
+        };
+        for _ in 0..count {
+            let block = unsafe {
+                match tag {
+                    Some(tag) => harness
+                        .allocator
+                        .alloc_tagged(&harness.device, request, tag),
+                    None => harness.allocator.alloc(&harness.device, request),
+                }
+            }
+            .unwrap();
+            harness.blocks.push((block, request));
+        }
+    }
+
+    let report = harness.allocator.leak_report();
+    assert_eq!(report.blocks, 6);
+    assert_eq!(report.bytes, 3 * 1024 + 2 * 65536 + 512);
+
+    let groups: Vec<_> = report
+        .groups
+        .iter()
+        .map(|group| (group.tag, group.memory_type, group.blocks, group.bytes))
+        .collect();
+    assert_eq!(
+        groups,
+        [
+            (Some("shadows"), 0, 2, 2 * 65536),
+            (Some("ui"), 0, 3, 3 * 1024),
+            (None, 0, 1, 512),
+        ]
+    );
+
+    let text = report.to_string();
+    assert!(text.starts_with("6 live blocks"), "{}", text);
+    assert!(text.contains("<untagged>"), "{}", text);
+
+    for (block, _) in harness.blocks.drain(..) {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+    assert_eq!(harness.allocator.leak_report(), Default::default());
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "chrome-trace")]
+
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+#[test]
+fn chrome_trace_records_lifetimes() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let request = Request {
+        size: 1000,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1,
+        kind: ResourceKind::Linear,
+    };
+
+    let before = unsafe { harness.allocator.alloc(&harness.device, request) }.unwrap();
+
+    assert!(harness.allocator.stop_trace().is_none());
+    harness.allocator.start_trace();
+
+    let tagged = unsafe {
+        harness
+            .allocator
+            .alloc_tagged(&harness.device, request, "\"quoted\"")
+    }
+    .unwrap();
+
+    unsafe {
+        harness.allocator.dealloc(&harness.device, tagged);
+    }
+
+    let trace = harness.allocator.stop_trace().unwrap();
+    assert!(harness.allocator.stop_trace().is_none());
+
+    let json: serde_json::Value = serde_json::from_str(&trace.to_chrome_json()).unwrap();
+    let events = json["traceEvents"].as_array().unwrap();
+
+    let phases: Vec<&str> = events
+        .iter()
+        .map(|event| event["ph"].as_str().unwrap())
+        .collect();
+
+    // Initial heap usage counters, then begin and end with counters after each.
+    assert_eq!(phases, ["C", "C", "b", "C", "e", "C"]);
+
+    assert_eq!(events[2]["name"], "\"quoted\"");
+    assert_eq!(events[2]["id"], events[4]["id"]);
+    assert_eq!(events[2]["args"]["memory_type"], 0);
+    assert!(events[2]["args"]["size"].as_u64().unwrap() >= 1000);
+
+    unsafe {
+        harness.allocator.dealloc(&harness.device, before);
+    }
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "tracing")]
+
+mod harness;
+
+use {
+    gpu_alloc::{Config, Dedicated, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+    std::{
+        collections::BTreeMap,
+        fmt,
+        sync::{Arc, Mutex},
+    },
+    tracing::{
+        field::{Field, Visit},
+        span, Event, Level, Metadata, Subscriber,
+    },
+};
+
+type Fields = BTreeMap<&'static str, String>;
+
+/// Subscriber that records fields of allocator events.
+#[derive(Clone, Default)]
+struct Recorder {
+    events: Arc<Mutex<Vec<Fields>>>,
+}
+
+struct Visitor<'a>(&'a mut Fields);
+
+impl Visit for Visitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.insert(field.name(), format!("{:?}", value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name(), value.to_owned());
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        *metadata.level() >= Level::DEBUG
+    }
+
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::new();
+        event.record(&mut Visitor(&mut fields));
+        if fields.contains_key("strategy") {
+            self.events.lock().unwrap().push(fields);
+        }
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+#[test]
+fn chunk_and_block_events() {
+    let recorder = Recorder::default();
+
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+        let request = Request {
+            size: 1000,
+            align_mask: 255,
+            usage: UsageFlags::FAST_DEVICE_ACCESS,
+            memory_types: 1,
+            kind: ResourceKind::Linear,
+        };
+
+        let block = unsafe {
+            harness
+                .allocator
+                .alloc_tagged(&harness.device, request, "ui")
+        }
+        .unwrap();
+        let dedicated = unsafe {
+            harness
+                .allocator
+                .alloc_with_dedicated(&harness.device, request, Dedicated::Required)
+        }
+        .unwrap();
+
+        unsafe {
+            harness.allocator.dealloc(&harness.device, block);
+            harness.allocator.dealloc(&harness.device, dedicated);
+        }
+
+        harness.finish().unwrap();
+    });
+
+    let events = recorder.events.lock().unwrap();
+
+    let summary: Vec<(&str, &str)> = events
+        .iter()
+        .map(|fields| (&*fields["message"], &*fields["strategy"]))
+        .collect();
+
+    assert_eq!(
+        summary,
+        [
+            ("chunk created", "buddy"),
+            ("block allocated", "buddy"),
+            ("chunk created", "dedicated"),
+            ("block allocated", "dedicated"),
+            ("block deallocated", "buddy"),
+            ("chunk destroyed", "buddy"),
+            ("block deallocated", "dedicated"),
+            ("chunk destroyed", "dedicated"),
+        ]
+    );
+
+    let allocated = &events[1];
+    assert_eq!(allocated["memory_type"], "0");
+    assert_eq!(allocated["requested_size"], "1000");
+    assert_eq!(allocated["tag"], "ui");
+    assert_eq!(allocated["chunk"], events[0]["chunk"]);
+    assert_eq!(allocated["id"], events[4]["id"]);
+}
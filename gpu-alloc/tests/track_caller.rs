@@ -0,0 +1,55 @@
+#![cfg(feature = "track-caller")]
+
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+const REQUEST: Request = Request {
+    size: 1000,
+    align_mask: 0,
+    usage: UsageFlags::FAST_DEVICE_ACCESS,
+    memory_types: !0,
+    kind: ResourceKind::Linear,
+};
+
+#[test]
+fn dump_records_call_sites() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let line = line!() + 1;
+    let block = unsafe { harness.allocator.alloc(&harness.device, REQUEST) }.unwrap();
+    harness.blocks.push((block, REQUEST));
+
+    let requests = [REQUEST; 2];
+    let group_line = line!() + 1;
+    let group = unsafe { harness.allocator.alloc_group(&harness.device, &requests) }.unwrap();
+    harness
+        .blocks
+        .extend(group.into_iter().map(|block| (block, REQUEST)));
+
+    let mut locations: Vec<_> = harness
+        .allocator
+        .dump()
+        .types
+        .iter()
+        .flat_map(|memory_type| &memory_type.chunks)
+        .flat_map(|chunk| &chunk.blocks)
+        .map(|block| block.location.clone().unwrap())
+        .collect();
+    locations.sort();
+
+    let site = |line: u32| format!("{}:{}:", file!(), line);
+    assert_eq!(locations.len(), 3);
+    assert!(locations[0].starts_with(&site(line)), "{:?}", locations);
+    assert!(
+        locations[1].starts_with(&site(group_line)),
+        "{:?}",
+        locations
+    );
+    assert_eq!(locations[1], locations[2]);
+
+    harness.finish().unwrap();
+}
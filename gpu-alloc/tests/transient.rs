@@ -0,0 +1,93 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, MapError, TransientAllocator, UsageFlags},
+    harness::{device_properties, Harness},
+    std::thread,
+};
+
+const CHUNK_SIZE: u64 = 64 * 1024;
+
+#[test]
+fn concurrent_alloc() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+    let mut transient = TransientAllocator::new(CHUNK_SIZE, 255, UsageFlags::UPLOAD, !0);
+
+    assert!(transient.alloc(16).is_none(), "no chunk before refill");
+    unsafe { transient.refill(&mut harness.allocator, &harness.device) }.unwrap();
+
+    let mut blocks: Vec<(usize, u64, u64)> = thread::scope(|scope| {
+        let threads: Vec<_> = (0..4u8)
+            .map(|index| {
+                let transient = &transient;
+                scope.spawn(move || {
+                    let mut blocks = Vec::new();
+                    while let Some(block) = transient.alloc(100 + index as u64) {
+                        unsafe { block.write_bytes(0, &vec![index; block.size() as usize]) }
+                            .unwrap();
+                        blocks.push((*block.memory(), block.offset(), block.size()));
+                    }
+                    blocks
+                })
+            })
+            .collect();
+
+        threads
+            .into_iter()
+            .flat_map(|thread| thread.join().unwrap())
+            .collect()
+    });
+
+    assert_eq!(blocks.len() as u64, CHUNK_SIZE / 256);
+
+    blocks.sort_unstable();
+    for (_, offset, _) in &blocks {
+        assert_eq!(offset & 255, 0);
+    }
+    for pair in blocks.windows(2) {
+        assert_eq!(pair[0].0, pair[1].0, "all blocks come from single chunk");
+        assert!(
+            pair[0].1 + pair[0].2 <= pair[1].1,
+            "blocks {:?} overlap",
+            pair
+        );
+    }
+
+    unsafe { transient.dispose(&mut harness.allocator, &harness.device) }
+    harness.finish().unwrap();
+}
+
+#[test]
+fn refill_and_reset() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+    let mut transient = TransientAllocator::new(CHUNK_SIZE, 63, UsageFlags::UPLOAD, 1 << 3);
+
+    unsafe { transient.refill(&mut harness.allocator, &harness.device) }.unwrap();
+    assert!(transient.alloc(CHUNK_SIZE + 1).is_none());
+
+    let first = *transient.alloc(CHUNK_SIZE).unwrap().memory();
+    assert!(transient.alloc(1).is_none());
+
+    unsafe { transient.refill(&mut harness.allocator, &harness.device) }.unwrap();
+    let block = transient.alloc(1000).unwrap();
+    assert_ne!(*block.memory(), first);
+    unsafe { block.write_bytes(0, &[1; 1000]) }.unwrap();
+    assert_eq!(
+        unsafe { block.write_bytes(1, &[1; 1000]) },
+        Err(MapError::OutOfBounds)
+    );
+    unsafe { transient.flush(&harness.device) }.unwrap();
+
+    unsafe { transient.reset(&mut harness.allocator, &harness.device) }
+    assert_eq!(
+        harness.device.live_allocations(),
+        1,
+        "retired chunk is freed"
+    );
+
+    let block = transient.alloc(CHUNK_SIZE).unwrap();
+    assert_eq!(block.offset() & 63, 0);
+
+    unsafe { transient.dispose(&mut harness.allocator, &harness.device) }
+    harness.finish().unwrap();
+}
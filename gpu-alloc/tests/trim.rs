@@ -0,0 +1,129 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn harness() -> Harness {
+    let mut config = Config::i_am_potato();
+    config.buddy_chunk_keep_alive = 8;
+    config.starting_free_list_chunk = 1024;
+    config.final_free_list_chunk = 1024;
+    Harness::new(config, device_properties(64))
+}
+
+fn request(size: u64, usage: UsageFlags) -> Request {
+    Request {
+        size,
+        align_mask: 0,
+        usage,
+        memory_types: 1 << 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+/// Allocates blocks of `sizes` and frees them, leaving their chunks empty.
+fn alloc_and_free(harness: &mut Harness, sizes: &[u64], usage: UsageFlags) {
+    for &size in sizes {
+        let block = unsafe {
+            harness
+                .allocator
+                .alloc(&harness.device, request(size, usage))
+        }
+        .unwrap();
+        harness.blocks.push((block, request(size, usage)));
+    }
+    harness.check().unwrap();
+
+    while let Some((block, _)) = harness.blocks.pop() {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+    harness.check().unwrap();
+}
+
+#[test]
+fn retained_buddy_chunks_released() {
+    let mut harness = harness();
+
+    alloc_and_free(
+        &mut harness,
+        &[8 * 1024, 16 * 1024],
+        UsageFlags::FAST_DEVICE_ACCESS,
+    );
+    assert_eq!(harness.device.live_allocations(), 2);
+
+    unsafe { harness.allocator.trim(&harness.device, 0) }
+    assert_eq!(harness.device.live_allocations(), 0);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn keeps_requested_bytes() {
+    let mut harness = harness();
+
+    alloc_and_free(
+        &mut harness,
+        &[8 * 1024, 16 * 1024],
+        UsageFlags::FAST_DEVICE_ACCESS,
+    );
+    let allocated = harness.allocator.report().types[1].allocated_bytes;
+
+    unsafe { harness.allocator.trim(&harness.device, allocated - 1) }
+    assert_eq!(harness.device.live_allocations(), 1);
+    assert!(harness.allocator.report().types[1].allocated_bytes < allocated);
+    harness.check().unwrap();
+
+    // Trimming within the limit releases nothing.
+    unsafe { harness.allocator.trim(&harness.device, allocated) }
+    assert_eq!(harness.device.live_allocations(), 1);
+
+    unsafe { harness.allocator.trim(&harness.device, 0) }
+    assert_eq!(harness.device.live_allocations(), 0);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn empty_free_list_chunk_released() {
+    let mut harness = harness();
+
+    alloc_and_free(&mut harness, &[1024, 1024], UsageFlags::TRANSIENT);
+
+    // The newest chunk is kept for reuse on dealloc.
+    assert_eq!(harness.device.live_allocations(), 1);
+
+    unsafe { harness.allocator.trim(&harness.device, 0) }
+    assert_eq!(harness.device.live_allocations(), 0);
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn chunks_with_live_blocks_kept() {
+    let mut harness = harness();
+
+    for usage in [UsageFlags::FAST_DEVICE_ACCESS, UsageFlags::TRANSIENT] {
+        let block = unsafe {
+            harness
+                .allocator
+                .alloc(&harness.device, request(256, usage))
+        }
+        .unwrap();
+        harness.blocks.push((block, request(256, usage)));
+    }
+    let live = harness.device.live_allocations();
+
+    unsafe { harness.allocator.trim(&harness.device, 0) }
+    assert_eq!(harness.device.live_allocations(), live);
+    harness.check().unwrap();
+
+    while let Some((block, _)) = harness.blocks.pop() {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+    unsafe { harness.allocator.trim(&harness.device, 0) }
+    assert_eq!(harness.device.live_allocations(), 0);
+
+    harness.finish().unwrap();
+}
@@ -0,0 +1,127 @@
+mod harness;
+
+use {
+    gpu_alloc::{AllocationError, VirtualAllocation, VirtualAllocator},
+    proptest::prelude::*,
+};
+
+#[test]
+fn allocations_aligned_and_disjoint() {
+    let mut allocator = VirtualAllocator::new(1024);
+
+    let a = allocator.alloc(100, 0).unwrap();
+    let b = allocator.alloc(100, 255).unwrap();
+    assert_eq!(
+        a,
+        VirtualAllocation {
+            offset: 0,
+            size: 100
+        }
+    );
+    assert_eq!(
+        b,
+        VirtualAllocation {
+            offset: 256,
+            size: 100
+        }
+    );
+    assert_eq!(allocator.used(), 200);
+    assert_eq!(allocator.allocations(), 2);
+
+    // Alignment padding stays free.
+    let c = allocator.alloc(150, 0).unwrap();
+    assert_eq!(c.offset, 100);
+
+    allocator.dealloc(a);
+    allocator.dealloc(b);
+    allocator.dealloc(c);
+    assert!(allocator.is_empty());
+    assert_eq!(allocator.largest_free_region(), 1024);
+}
+
+#[test]
+fn smallest_fitting_range_chosen() {
+    let mut allocator = VirtualAllocator::new(1000);
+
+    let blocks: Vec<_> = [100, 300, 50, 550]
+        .iter()
+        .map(|&size| allocator.alloc(size, 0).unwrap())
+        .collect();
+    assert_eq!(allocator.largest_free_region(), 0);
+
+    allocator.dealloc(blocks[1]);
+    allocator.dealloc(blocks[3]);
+
+    let block = allocator.alloc(200, 0).unwrap();
+    assert_eq!(block.offset, blocks[1].offset);
+}
+
+#[test]
+fn exhausted_space_reported() {
+    let mut allocator = VirtualAllocator::new(256);
+
+    allocator.alloc(200, 0).unwrap();
+    assert_eq!(
+        allocator.alloc(100, 0),
+        Err(AllocationError::OutOfDeviceMemory)
+    );
+    assert_eq!(
+        allocator.alloc(8, 2),
+        Err(AllocationError::InvalidAlignment)
+    );
+
+    allocator.reset();
+    assert!(allocator.is_empty());
+    assert_eq!(allocator.alloc(256, 0).unwrap().offset, 0);
+}
+
+#[test]
+#[should_panic(expected = "overlaps free range")]
+fn double_free_detected() {
+    let mut allocator = VirtualAllocator::new(256);
+
+    let block = allocator.alloc(64, 0).unwrap();
+    allocator.alloc(64, 0).unwrap();
+    allocator.dealloc(block);
+    allocator.dealloc(block);
+}
+
+proptest! {
+    #![proptest_config(harness::proptest_config())]
+
+    #[test]
+    fn bookkeeping_consistent(ops in proptest::collection::vec((0u64..512, 0u32..8, any::<bool>()), 1..128)) {
+        let mut allocator = VirtualAllocator::new(4096);
+        let mut live: Vec<VirtualAllocation> = Vec::new();
+
+        for (size, align, free) in ops {
+            if free && !live.is_empty() {
+                let block = live.swap_remove(size as usize % live.len());
+                allocator.dealloc(block);
+                continue;
+            }
+
+            let align_mask = (1u64 << align) - 1;
+            if let Ok(block) = allocator.alloc(size, align_mask) {
+                prop_assert_eq!(block.offset & align_mask, 0);
+                prop_assert!(block.size >= size);
+                prop_assert!(block.offset + block.size <= 4096);
+                for other in &live {
+                    prop_assert!(
+                        block.offset + block.size <= other.offset
+                            || other.offset + other.size <= block.offset
+                    );
+                }
+                live.push(block);
+            }
+
+            prop_assert_eq!(allocator.used(), live.iter().map(|block| block.size).sum::<u64>());
+        }
+
+        for block in live {
+            allocator.dealloc(block);
+        }
+        prop_assert!(allocator.is_empty());
+        prop_assert_eq!(allocator.largest_free_region(), 4096);
+    }
+}
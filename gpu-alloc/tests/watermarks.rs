@@ -0,0 +1,114 @@
+mod harness;
+
+use {
+    gpu_alloc::{Config, Dedicated, Request, ResourceKind, UsageFlags},
+    harness::{device_properties, Harness},
+};
+
+fn request(size: u64) -> Request {
+    Request {
+        size,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: 1,
+        kind: ResourceKind::Linear,
+    }
+}
+
+#[test]
+fn heap_watermarks() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let blocks: Vec<_> = (0..4)
+        .map(|_| {
+            unsafe {
+                harness.allocator.alloc_with_dedicated(
+                    &harness.device,
+                    request(4096),
+                    Dedicated::Required,
+                )
+            }
+            .unwrap()
+        })
+        .collect();
+
+    let stats = harness.allocator.stats();
+    assert_eq!(stats.heaps[0].used, 4 * 4096);
+    assert_eq!(stats.heaps[0].peak_used, 4 * 4096);
+    assert_eq!(stats.heaps[0].watermark_used, 4 * 4096);
+
+    let mut blocks = blocks.into_iter();
+    for block in blocks.by_ref().take(3) {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    harness.allocator.reset_watermarks();
+
+    let stats = harness.allocator.stats();
+    assert_eq!(stats.heaps[0].used, 4096);
+    assert_eq!(stats.heaps[0].peak_used, 4 * 4096);
+    assert_eq!(stats.heaps[0].watermark_used, 4096);
+
+    for block in blocks {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    harness.finish().unwrap();
+}
+
+#[test]
+fn tag_watermarks() {
+    let mut harness = Harness::new(Config::i_am_potato(), device_properties(32));
+
+    let textures: Vec<_> = (0..3)
+        .map(|_| {
+            unsafe {
+                harness
+                    .allocator
+                    .alloc_tagged(&harness.device, request(1024), "textures")
+            }
+            .unwrap()
+        })
+        .collect();
+
+    let untagged = unsafe { harness.allocator.alloc(&harness.device, request(1024)) }.unwrap();
+    assert_eq!(untagged.tag(), None);
+
+    let size: u64 = textures.iter().map(|block| block.size()).sum();
+    assert!(textures.iter().all(|block| block.tag() == Some("textures")));
+
+    for block in textures {
+        unsafe { harness.allocator.dealloc(&harness.device, block) }
+    }
+
+    let buffer = unsafe {
+        harness
+            .allocator
+            .alloc_tagged(&harness.device, request(256), "buffers")
+    }
+    .unwrap();
+
+    let stats = harness.allocator.stats();
+    assert_eq!(stats.tags.len(), 2);
+    assert_eq!(stats.tags[0].tag, "buffers");
+    assert_eq!(stats.tags[0].blocks, 1);
+    assert_eq!(stats.tags[0].bytes, buffer.size());
+    assert_eq!(stats.tags[1].tag, "textures");
+    assert_eq!(stats.tags[1].blocks, 0);
+    assert_eq!(stats.tags[1].bytes, 0);
+    assert_eq!(stats.tags[1].peak_bytes, size);
+    assert_eq!(stats.tags[1].watermark_bytes, size);
+
+    harness.allocator.reset_watermarks();
+
+    let stats = harness.allocator.stats();
+    assert_eq!(stats.tags[1].peak_bytes, size);
+    assert_eq!(stats.tags[1].watermark_bytes, 0);
+
+    unsafe {
+        harness.allocator.dealloc(&harness.device, buffer);
+        harness.allocator.dealloc(&harness.device, untagged);
+    }
+
+    harness.finish().unwrap();
+}
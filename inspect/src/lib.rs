@@ -0,0 +1,300 @@
+//! Summaries of [`gpu-alloc`] state dumps and allocation traces.
+//!
+//! Loads JSON produced by `GpuAllocator::dump_json`
+//! or Chrome trace produced by `AllocationTrace::to_chrome_json`
+//! and prints heap usage, top consumers by tag and fragmentation per memory type.
+//!
+//! [`gpu-alloc`]: gpu_alloc
+
+use {
+    gpu_alloc::{AllocatorDump, ChunkKind},
+    serde_json::Value,
+    std::{
+        cmp::Reverse,
+        collections::{BTreeMap, HashMap},
+        fmt::{self, Write as _},
+    },
+};
+
+/// Number of top consumers printed by default.
+pub const DEFAULT_TOP: usize = 10;
+
+/// Error returned when input is neither allocator dump nor allocation trace.
+#[derive(Debug)]
+pub struct InspectError(serde_json::Error);
+
+impl fmt::Display for InspectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Input is neither allocator dump nor allocation trace: {}",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for InspectError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+/// Detects kind of JSON input and returns its summary
+/// listing at most `top` entries in each ranking.
+pub fn inspect(json: &str, top: usize) -> Result<String, InspectError> {
+    let value: Value = serde_json::from_str(json).map_err(InspectError)?;
+
+    if value.get("traceEvents").is_some() {
+        return Ok(trace_report(&value, top));
+    }
+
+    let dump = serde_json::from_value(value).map_err(InspectError)?;
+    Ok(dump_report(&dump, top))
+}
+
+/// Returns summary of allocator dump.
+pub fn dump_report(dump: &AllocatorDump, top: usize) -> String {
+    let mut out = String::new();
+    write_dump_report(&mut out, dump, top).expect("Writing to string never fails");
+    out
+}
+
+/// Returns summary of allocation trace in Chrome trace format.
+pub fn trace_report(trace: &Value, top: usize) -> String {
+    let mut out = String::new();
+    write_trace_report(&mut out, trace, top).expect("Writing to string never fails");
+    out
+}
+
+/// Live blocks sharing the same tag.
+#[derive(Default)]
+struct Consumer {
+    blocks: u64,
+    bytes: u64,
+    peak_bytes: Option<u64>,
+}
+
+fn write_dump_report(out: &mut String, dump: &AllocatorDump, top: usize) -> fmt::Result {
+    writeln!(out, "Heaps:")?;
+    for (index, heap) in dump.heaps.iter().enumerate() {
+        let total = heap.total();
+        writeln!(
+            out,
+            "  heap {}: used {} of {} (peak {}), {} memory objects, {} blocks",
+            index,
+            Bytes(heap.used),
+            Bytes(heap.size),
+            Bytes(heap.peak_used),
+            total.memory_objects,
+            total.blocks,
+        )?;
+        writeln!(
+            out,
+            "    dedicated {}, buddy {}, free-list {}",
+            Bytes(heap.dedicated.allocated_bytes),
+            Bytes(heap.buddy.allocated_bytes),
+            Bytes(heap.free_list.allocated_bytes),
+        )?;
+    }
+
+    let mut consumers: BTreeMap<Option<&str>, Consumer> = BTreeMap::new();
+    for chunk in dump
+        .types
+        .iter()
+        .flat_map(|memory_type| &memory_type.chunks)
+    {
+        for block in &chunk.blocks {
+            let consumer = consumers.entry(block.tag.as_deref()).or_default();
+            consumer.blocks += 1;
+            consumer.bytes += block.size;
+        }
+    }
+    for tag in &dump.tags {
+        consumers.entry(Some(&tag.tag)).or_default().peak_bytes = Some(tag.peak_bytes);
+    }
+
+    let mut consumers: Vec<_> = consumers.into_iter().collect();
+    consumers.sort_by_key(|(_, consumer)| Reverse(consumer.bytes));
+
+    writeln!(out, "Top consumers:")?;
+    for (tag, consumer) in consumers.iter().take(top) {
+        write!(
+            out,
+            "  {}: {} in {} blocks",
+            tag.unwrap_or("<untagged>"),
+            Bytes(consumer.bytes),
+            consumer.blocks
+        )?;
+        match consumer.peak_bytes {
+            Some(peak) => writeln!(out, " (peak {})", Bytes(peak))?,
+            None => writeln!(out)?,
+        }
+    }
+    if consumers.len() > top {
+        writeln!(out, "  ... {} more", consumers.len() - top)?;
+    }
+
+    writeln!(out, "Fragmentation:")?;
+    for memory_type in &dump.types {
+        if memory_type.chunks.is_empty() {
+            continue;
+        }
+
+        let mut free_bytes = 0;
+        let mut largest_free_region = 0;
+        let mut unusable_bytes = 0;
+        let mut chunks = [0; 4];
+
+        for chunk in &memory_type.chunks {
+            chunks[match chunk.kind {
+                ChunkKind::Dedicated => 0,
+                ChunkKind::Buddy => 1,
+                ChunkKind::FreeList => 2,
+                ChunkKind::Ring => 3,
+            }] += 1;
+
+            for region in &chunk.free {
+                free_bytes += region.size;
+                largest_free_region = largest_free_region.max(region.size);
+            }
+
+            for block in &chunk.blocks {
+                unusable_bytes += block.size - block.requested_size;
+            }
+        }
+
+        let ratio = if free_bytes == 0 {
+            0.0
+        } else {
+            1.0 - largest_free_region as f64 / free_bytes as f64
+        };
+
+        write!(
+            out,
+            "  memory type {} (heap {}, {:?}): {} dedicated, {} buddy, {} free-list",
+            memory_type.index, memory_type.heap, memory_type.props, chunks[0], chunks[1], chunks[2],
+        )?;
+        if chunks[3] > 0 {
+            write!(out, ", {} ring", chunks[3])?;
+        }
+        writeln!(out, " chunks")?;
+        writeln!(
+            out,
+            "    free {}, largest free region {}, fragmentation {:.1}%, unusable {}",
+            Bytes(free_bytes),
+            Bytes(largest_free_region),
+            ratio * 100.0,
+            Bytes(unusable_bytes),
+        )?;
+    }
+
+    Ok(())
+}
+
+fn write_trace_report(out: &mut String, trace: &Value, top: usize) -> fmt::Result {
+    let events = trace["traceEvents"]
+        .as_array()
+        .map_or(&[][..], Vec::as_slice);
+
+    let mut allocations: HashMap<&str, Consumer> = HashMap::new();
+    let mut live: HashMap<(u64, u64), (&str, u64)> = HashMap::new();
+    let mut heaps: BTreeMap<&str, u64> = BTreeMap::new();
+    let mut duration = 0.0f64;
+
+    for event in events {
+        let name = event["name"].as_str().unwrap_or("");
+        let id = event["id"].as_u64().unwrap_or(0);
+        let memory_type = event["tid"].as_u64().unwrap_or(0);
+        duration = duration.max(event["ts"].as_f64().unwrap_or(0.0));
+
+        match event["ph"].as_str() {
+            Some("b") => {
+                let size = event["args"]["size"].as_u64().unwrap_or(0);
+                let consumer = allocations.entry(name).or_default();
+                consumer.blocks += 1;
+                consumer.bytes += size;
+                live.insert((memory_type, id), (name, size));
+            }
+            Some("e") => {
+                live.remove(&(memory_type, id));
+            }
+            Some("C") => {
+                let used = event["args"]["used"].as_u64().unwrap_or(0);
+                let peak = heaps.entry(name).or_default();
+                *peak = (*peak).max(used);
+            }
+            _ => {}
+        }
+    }
+
+    writeln!(
+        out,
+        "Trace: {} events over {:.3} ms",
+        events.len(),
+        duration / 1000.0
+    )?;
+
+    writeln!(out, "Peak heap usage:")?;
+    for (heap, peak) in &heaps {
+        writeln!(out, "  {}: {}", heap, Bytes(*peak))?;
+    }
+
+    let mut allocations: Vec<_> = allocations.into_iter().collect();
+    allocations.sort_by(|(lhs_tag, lhs), (rhs_tag, rhs)| {
+        rhs.bytes.cmp(&lhs.bytes).then(lhs_tag.cmp(rhs_tag))
+    });
+
+    writeln!(out, "Top allocators:")?;
+    for (tag, consumer) in allocations.iter().take(top) {
+        writeln!(
+            out,
+            "  {}: {} in {} allocations",
+            tag_name(tag),
+            Bytes(consumer.bytes),
+            consumer.blocks
+        )?;
+    }
+    if allocations.len() > top {
+        writeln!(out, "  ... {} more", allocations.len() - top)?;
+    }
+
+    let live_bytes: u64 = live.values().map(|&(_, size)| size).sum();
+    writeln!(
+        out,
+        "Not deallocated by the end of trace: {} in {} blocks",
+        Bytes(live_bytes),
+        live.len()
+    )?;
+
+    Ok(())
+}
+
+/// Trace names untagged allocations with empty string.
+fn tag_name(tag: &str) -> &str {
+    if tag.is_empty() {
+        "<untagged>"
+    } else {
+        tag
+    }
+}
+
+/// Formats byte count with binary unit.
+struct Bytes(u64);
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+
+        if self.0 < 1024 {
+            return write!(f, "{} B", self.0);
+        }
+
+        let mut value = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < UNITS.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        write!(f, "{:.1} {}", value, UNITS[unit])
+    }
+}
@@ -0,0 +1,61 @@
+use {
+    gpu_alloc_inspect::{inspect, DEFAULT_TOP},
+    std::{env, fs, process},
+};
+
+const USAGE: &str = "Usage: gpu-alloc-inspect [--top <N>] <FILE>...
+
+Prints summary of gpu-alloc state dumps (`GpuAllocator::dump_json`)
+and allocation traces (`AllocationTrace::to_chrome_json`).
+
+Options:
+    --top <N>    Number of top consumers to print [default: 10]
+    -h, --help   Print this message";
+
+fn main() {
+    let mut top = DEFAULT_TOP;
+    let mut files = Vec::new();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match &*arg {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            "--top" => match args.next().and_then(|value| value.parse().ok()) {
+                Some(value) => top = value,
+                None => fail("`--top` expects a number"),
+            },
+            _ => files.push(arg),
+        }
+    }
+
+    if files.is_empty() {
+        fail("No input files");
+    }
+
+    for (index, file) in files.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+
+        let json = match fs::read_to_string(file) {
+            Ok(json) => json,
+            Err(err) => fail(&format!("Failed to read `{}`: {}", file, err)),
+        };
+
+        match inspect(&json, top) {
+            Ok(report) => {
+                println!("{}:", file);
+                print!("{}", report);
+            }
+            Err(err) => fail(&format!("Failed to inspect `{}`: {}", file, err)),
+        }
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}\n\n{}", message, USAGE);
+    process::exit(1)
+}
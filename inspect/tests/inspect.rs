@@ -0,0 +1,108 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryBlock, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, Request, ResourceKind, UsageFlags,
+    },
+    gpu_alloc_inspect::inspect,
+    gpu_alloc_mock::MockMemoryDevice,
+    std::borrow::Cow,
+};
+
+fn device() -> MockMemoryDevice {
+    MockMemoryDevice::new(DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::DEVICE_LOCAL,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 64 * 1024 * 1024,
+            budget: None,
+            usage: None,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 8,
+        buffer_image_granularity: 1,
+        buffer_device_address: false,
+    })
+}
+
+fn alloc(
+    allocator: &mut GpuAllocator<usize>,
+    device: &MockMemoryDevice,
+    size: u64,
+    tag: &'static str,
+) -> MemoryBlock<usize> {
+    let request = Request {
+        size,
+        align_mask: 255,
+        usage: UsageFlags::FAST_DEVICE_ACCESS,
+        memory_types: !0,
+        kind: ResourceKind::Linear,
+    };
+    unsafe { allocator.alloc_tagged(device, request, tag) }.unwrap()
+}
+
+#[test]
+fn dump_summary() {
+    let device = device();
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    let blocks = vec![
+        alloc(&mut allocator, &device, 1000, "small"),
+        alloc(&mut allocator, &device, 3000, "large"),
+        alloc(&mut allocator, &device, 3000, "large"),
+    ];
+
+    let report = inspect(&allocator.dump_json(), 1).unwrap();
+
+    for block in blocks {
+        unsafe { allocator.dealloc(&device, block) }
+    }
+    unsafe { allocator.cleanup(&device) }
+
+    assert!(report.contains("heap 0: used"), "{}", report);
+    assert!(report.contains("large: "), "{}", report);
+    assert!(!report.contains("small: "), "{}", report);
+    assert!(report.contains("... 1 more"), "{}", report);
+    assert!(report.contains("memory type 0 (heap 0"), "{}", report);
+}
+
+#[test]
+fn trace_summary() {
+    let device = device();
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), device.props());
+
+    allocator.start_trace();
+    let freed = alloc(&mut allocator, &device, 1000, "freed");
+    let leaked = alloc(&mut allocator, &device, 2048, "leaked");
+    unsafe { allocator.dealloc(&device, freed) }
+    let trace = allocator.stop_trace().unwrap().to_chrome_json();
+
+    let report = inspect(&trace, 10).unwrap();
+
+    unsafe { allocator.dealloc(&device, leaked) }
+    unsafe { allocator.cleanup(&device) }
+
+    assert!(
+        report.contains("leaked: 2.0 KiB in 1 allocations"),
+        "{}",
+        report
+    );
+    assert!(
+        report.contains("freed: 1.0 KiB in 1 allocations"),
+        "{}",
+        report
+    );
+    assert!(
+        report.contains("Not deallocated by the end of trace: 2.0 KiB in 1 blocks"),
+        "{}",
+        report
+    );
+}
+
+#[test]
+fn invalid_input() {
+    assert!(inspect("{\"heaps\": 1}", 10).is_err());
+    assert!(inspect("not json", 10).is_err());
+}
@@ -1,27 +1,107 @@
 use {
     gpu_alloc_types::{
-        AllocationFlags, DeviceMapError, DeviceProperties, MappedMemoryRange, MemoryDevice,
-        MemoryHeap, MemoryPropertyFlags, MemoryType, OutOfMemory,
+        AllocationFlags, DedicatedAllocationInfo, DeviceDiagnostic, DeviceMapError,
+        DeviceProperties, MappedMemoryRange, MemoryDevice, MemoryHeap, MemoryPropertyFlags,
+        MemoryType, OutOfMemory,
     },
     slab::Slab,
     std::{
         borrow::Cow,
-        cell::{Cell, RefCell, UnsafeCell},
+        cell::{Cell, RefCell},
         convert::TryFrom as _,
-        mem::transmute,
-        ptr::NonNull,
+        ptr::{slice_from_raw_parts_mut, NonNull},
     },
 };
 
+pub use gpu_alloc_types::ResourceKind;
+
+/// Host memory backing memory object.
+///
+/// Content is kept as raw pointer so that pointers handed out by `map_memory`
+/// stay valid while `MockMemory` is moved around inside the slab.
+/// It is allocated on first mapping and kept until memory object is deallocated,
+/// preserving content between mappings like real device memory does.
+struct HostMemory {
+    ptr: NonNull<u8>,
+    size: usize,
+    start: usize,
+}
+
+// Host memory is owned exclusively by its memory object.
+unsafe impl Send for HostMemory {}
+
+impl HostMemory {
+    /// Allocates host memory for memory object of specified size.
+    /// If `alignment` is specified, start of the memory is aligned to it
+    /// but not to twice of it.
+    fn new(size: usize, alignment: Option<usize>) -> Self {
+        let padding = alignment.map_or(0, |alignment| alignment * 2);
+        let content = Box::into_raw(vec![0u8; size + padding].into_boxed_slice());
+
+        // Box pointer is never null.
+        let ptr = unsafe { NonNull::new_unchecked(content as *mut u8) };
+
+        let start = match alignment {
+            None => 0,
+            Some(alignment) => {
+                let start = ptr.as_ptr().align_offset(alignment);
+                let over_aligned =
+                    unsafe { ptr.as_ptr().add(start) }.align_offset(alignment * 2) == 0;
+
+                if over_aligned {
+                    start + alignment
+                } else {
+                    start
+                }
+            }
+        };
+
+        HostMemory {
+            ptr,
+            size: size + padding,
+            start,
+        }
+    }
+
+    /// Returns pointer to the byte at specified offset from memory object start.
+    ///
+    /// # Safety
+    ///
+    /// `offset` must be within memory object.
+    unsafe fn at(&self, offset: usize) -> NonNull<u8> {
+        NonNull::new_unchecked(self.ptr.as_ptr().add(self.start + offset))
+    }
+}
+
+impl Drop for HostMemory {
+    fn drop(&mut self) {
+        unsafe {
+            drop(Box::from_raw(slice_from_raw_parts_mut(
+                self.ptr.as_ptr(),
+                self.size,
+            )));
+        }
+    }
+}
+
 struct MemoryMapping {
-    content: Box<UnsafeCell<[u8]>>,
     offset: u64,
+    size: u64,
 }
 
 struct MockMemory {
     memory_type: u32,
     size: u64,
+    content: Option<HostMemory>,
     mapped: Option<MemoryMapping>,
+    dedicated: Option<DedicatedAllocationInfo>,
+}
+
+struct BoundResource {
+    memory: usize,
+    offset: u64,
+    size: u64,
+    kind: ResourceKind,
 }
 
 pub struct MockMemoryDevice {
@@ -36,8 +116,18 @@ pub struct MockMemoryDevice {
     memory_heaps_remaining_capacity: Box<[Cell<u64>]>,
     allocations: RefCell<Slab<MockMemory>>,
 
+    buffer_image_granularity: u64,
+    resources: RefCell<Slab<BoundResource>>,
+
+    min_memory_map_alignment: Option<usize>,
+    max_simultaneous_mappings: Option<u32>,
+    mapped_count: Cell<u32>,
+    flushes_before_failure: Option<Cell<u64>>,
+
     total_allocations_counter: Cell<u64>,
     total_deallocations_counter: Cell<u64>,
+
+    diagnostic: Cell<Option<DeviceDiagnostic>>,
 }
 
 impl MockMemoryDevice {
@@ -60,7 +150,16 @@ impl MockMemoryDevice {
             allocations_remains: Cell::new(props.max_memory_allocation_count),
             allocations: RefCell::new(Slab::new()),
 
+            buffer_image_granularity: props.buffer_image_granularity,
+            resources: RefCell::new(Slab::new()),
+
+            min_memory_map_alignment: None,
+            max_simultaneous_mappings: None,
+            mapped_count: Cell::new(0),
+            flushes_before_failure: None,
+
             total_allocations_counter: Cell::new(0),
+            diagnostic: Cell::new(None),
             total_deallocations_counter: Cell::new(0),
         }
     }
@@ -72,10 +171,135 @@ impl MockMemoryDevice {
             max_memory_allocation_count: self.max_memory_allocation_count,
             max_memory_allocation_size: self.max_memory_allocation_size,
             non_coherent_atom_size: self.non_coherent_atom_size,
+            buffer_image_granularity: self.buffer_image_granularity,
             buffer_device_address: self.buffer_device_address,
         }
     }
 
+    /// Sets `bufferImageGranularity` emulated by this device.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `granularity` is not power of two.
+    pub fn set_buffer_image_granularity(&mut self, granularity: u64) {
+        assert!(
+            granularity.is_power_of_two(),
+            "Granularity must be power of two"
+        );
+        self.buffer_image_granularity = granularity;
+    }
+
+    /// Returns `bufferImageGranularity` emulated by this device.
+    pub fn buffer_image_granularity(&self) -> u64 {
+        self.buffer_image_granularity
+    }
+
+    /// Makes pointers returned from `map_memory` aligned to `alignment`
+    /// but never to twice of it, as minimally aligned mappings returned by some drivers.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `alignment` is not power of two.
+    pub fn set_min_memory_map_alignment(&mut self, alignment: usize) {
+        assert!(
+            alignment.is_power_of_two(),
+            "Alignment must be power of two"
+        );
+        self.min_memory_map_alignment = Some(alignment);
+    }
+
+    /// Makes `map_memory` fail with `DeviceMapError::MapFailed`
+    /// when `count` memory objects are already mapped,
+    /// as drivers with limited address space for mappings do.
+    pub fn set_max_simultaneous_mappings(&mut self, count: u32) {
+        self.max_simultaneous_mappings = Some(count);
+    }
+
+    /// Makes `flush_memory_ranges` succeed `count` times
+    /// and fail with `OutOfMemory::OutOfHostMemory` afterwards.
+    pub fn fail_flush_after(&mut self, count: u64) {
+        self.flushes_before_failure = Some(Cell::new(count));
+    }
+
+    /// Returns number of currently mapped memory objects.
+    pub fn mapped_count(&self) -> u32 {
+        self.mapped_count.get()
+    }
+
+    /// Emulates binding of a resource of specified kind to memory range.
+    /// Returns resource index that should be passed to [`unbind_resource`].
+    ///
+    /// # Panics
+    ///
+    /// This function panics if range is out of memory object bounds
+    /// or if linear and non-linear resources end up sharing a granularity page.
+    ///
+    /// [`unbind_resource`]: MockMemoryDevice::unbind_resource
+    pub fn bind_resource(
+        &self,
+        memory: usize,
+        offset: u64,
+        size: u64,
+        kind: ResourceKind,
+    ) -> usize {
+        assert_ne!(size, 0, "Resource size must be greater than 0");
+
+        let memory_size = self
+            .memory_size(memory)
+            .expect("Non-existing memory object");
+        assert!(
+            offset <= memory_size && size <= memory_size - offset,
+            "Resource range is out of memory object bounds"
+        );
+
+        let page = |offset: u64| offset / self.buffer_image_granularity;
+        let first_page = page(offset);
+        let last_page = page(offset + size - 1);
+
+        let mut resources = self.resources.borrow_mut();
+
+        for (_, other) in resources.iter() {
+            if other.memory != memory || other.kind == kind {
+                continue;
+            }
+
+            let other_first_page = page(other.offset);
+            let other_last_page = page(other.offset + other.size - 1);
+
+            assert!(
+                last_page < other_first_page || other_last_page < first_page,
+                "{:?} resource `{}..{}` shares granularity page with {:?} resource `{}..{}` in memory object {}",
+                kind,
+                offset,
+                offset + size,
+                other.kind,
+                other.offset,
+                other.offset + other.size,
+                memory,
+            );
+        }
+
+        resources.insert(BoundResource {
+            memory,
+            offset,
+            size,
+            kind,
+        })
+    }
+
+    /// Emulates destruction of resource bound with [`bind_resource`].
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `resource` is not bound.
+    ///
+    /// [`bind_resource`]: MockMemoryDevice::bind_resource
+    pub fn unbind_resource(&self, resource: usize) {
+        let mut resources = self.resources.borrow_mut();
+        assert!(resources.contains(resource), "Resource is not bound");
+        resources.remove(resource);
+    }
+
     pub fn total_allocations(&self) -> u64 {
         self.total_allocations_counter.get()
     }
@@ -83,6 +307,76 @@ impl MockMemoryDevice {
     pub fn total_deallocations(&self) -> u64 {
         self.total_deallocations_counter.get()
     }
+
+    /// Returns number of memory objects currently allocated.
+    pub fn live_allocations(&self) -> u64 {
+        self.total_allocations() - self.total_deallocations()
+    }
+
+    /// Returns number of bytes currently allocated from the heap.
+    pub fn allocated_memory(&self, heap: u32) -> u64 {
+        self.memory_heaps[heap as usize].size
+            - self.memory_heaps_remaining_capacity[heap as usize].get()
+    }
+
+    /// Returns size of the memory object.
+    /// Returns `None` if memory object does not exist.
+    pub fn memory_size(&self, memory: usize) -> Option<u64> {
+        Some(self.allocations.borrow().get(memory)?.size)
+    }
+
+    /// Returns resource the memory object was allocated for with `allocate_dedicated_memory`.
+    /// Returns `None` if memory object does not exist or is not dedicated.
+    pub fn dedicated_info(&self, memory: usize) -> Option<DedicatedAllocationInfo> {
+        self.allocations.borrow().get(memory)?.dedicated
+    }
+
+    /// Checks that device bookkeeping is consistent with live memory objects.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if any invariant is violated.
+    pub fn check_invariants(&self) {
+        let allocations = self.allocations.borrow();
+
+        assert_eq!(
+            allocations.len() as u64,
+            self.live_allocations(),
+            "Live memory objects count mismatch"
+        );
+
+        assert_eq!(
+            u64::from(self.max_memory_allocation_count - self.allocations_remains.get()),
+            self.live_allocations(),
+            "Remaining memory objects count mismatch"
+        );
+
+        assert_eq!(
+            allocations
+                .iter()
+                .filter(|(_, memory)| memory.mapped.is_some())
+                .count() as u32,
+            self.mapped_count.get(),
+            "Mapped memory objects count mismatch"
+        );
+
+        for (index, heap) in self.memory_heaps.iter().enumerate() {
+            let live: u64 = allocations
+                .iter()
+                .filter(|(_, memory)| {
+                    self.memory_types[memory.memory_type as usize].heap as usize == index
+                })
+                .map(|(_, memory)| memory.size)
+                .sum();
+
+            assert_eq!(
+                heap.size - self.memory_heaps_remaining_capacity[index].get(),
+                live,
+                "Heap {} capacity mismatch",
+                index
+            );
+        }
+    }
 }
 
 impl MemoryDevice<usize> for MockMemoryDevice {
@@ -106,14 +400,18 @@ impl MemoryDevice<usize> for MockMemoryDevice {
             allocations_remains > 0,
             "Allocator should not try to allocate too many objects"
         );
-        self.allocations_remains.set(allocations_remains - 1);
 
         let heap = &self.memory_heaps_remaining_capacity
             [self.memory_types[memory_type as usize].heap as usize];
         if heap.get() < size {
+            self.diagnostic.set(Some(DeviceDiagnostic {
+                code: -2, // VK_ERROR_OUT_OF_DEVICE_MEMORY
+                message: Cow::Borrowed("Mock heap capacity exhausted"),
+            }));
             return Err(OutOfMemory::OutOfDeviceMemory);
         }
         heap.set(heap.get() - size);
+        self.allocations_remains.set(allocations_remains - 1);
 
         tracing::info!("Memory object allocated");
 
@@ -123,18 +421,48 @@ impl MemoryDevice<usize> for MockMemoryDevice {
         Ok(self.allocations.borrow_mut().insert(MockMemory {
             memory_type,
             size,
+            content: None,
             mapped: None,
+            dedicated: None,
         }))
     }
 
+    #[tracing::instrument(skip(self))]
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        info: DedicatedAllocationInfo,
+    ) -> Result<usize, OutOfMemory> {
+        let memory = self.allocate_memory(size, memory_type, flags)?;
+        self.allocations.borrow_mut()[memory].dedicated = Some(info);
+        Ok(memory)
+    }
+
     #[tracing::instrument(skip(self))]
     unsafe fn deallocate_memory(&self, memory: usize) {
+        assert!(
+            !self
+                .resources
+                .borrow()
+                .iter()
+                .any(|(_, resource)| resource.memory == memory),
+            "Memory object deallocated while resources are bound to it"
+        );
+
         let memory = self.allocations.borrow_mut().remove(memory);
         self.allocations_remains
             .set(self.allocations_remains.get() + 1);
         let heap = &self.memory_heaps_remaining_capacity
             [self.memory_types[memory.memory_type as usize].heap as usize];
         heap.set(heap.get() + memory.size);
+
+        if memory.mapped.is_some() {
+            // Memory object is implicitly unmapped.
+            self.mapped_count.set(self.mapped_count.get() - 1);
+        }
+
         tracing::info!("Memory object deallocated");
 
         self.total_deallocations_counter
@@ -174,14 +502,29 @@ impl MemoryDevice<usize> for MockMemoryDevice {
             "size must be less than or equal to the size of the memory minus offset"
         );
 
-        let size_usize = usize::try_from(size).map_err(|_| DeviceMapError::OutOfHostMemory)?;
-        let mapping = memory.mapped.get_or_insert(MemoryMapping {
-            content: transmute(vec![0; size_usize].into_boxed_slice()),
-            offset,
-        });
+        if let Some(max) = self.max_simultaneous_mappings {
+            if self.mapped_count.get() >= max {
+                tracing::warn!("Too many memory objects are mapped");
+                return Err(DeviceMapError::MapFailed);
+            }
+        }
+
+        let content = match &mut memory.content {
+            Some(content) => content,
+            content => {
+                let size =
+                    usize::try_from(memory.size).map_err(|_| DeviceMapError::OutOfHostMemory)?;
+                content.get_or_insert(HostMemory::new(size, self.min_memory_map_alignment))
+            }
+        };
+
+        memory.mapped = Some(MemoryMapping { offset, size });
+        self.mapped_count.set(self.mapped_count.get() + 1);
 
         tracing::info!("Memory object mapped");
-        Ok(NonNull::from(&mut (&mut *mapping.content.get())[0]))
+
+        // `offset` is less than memory size which fits `usize`.
+        Ok(content.at(offset as usize))
     }
 
     unsafe fn unmap_memory(&self, memory: &mut usize) {
@@ -190,6 +533,7 @@ impl MemoryDevice<usize> for MockMemoryDevice {
             .get_mut(*memory)
             .expect("Non-existing memory object");
         assert!(memory.mapped.take().is_some(), "Was not mapped");
+        self.mapped_count.set(self.mapped_count.get() - 1);
     }
 
     unsafe fn invalidate_memory_ranges(
@@ -212,7 +556,7 @@ impl MemoryDevice<usize> for MockMemoryDevice {
                 tracing::warn!("Invalidating host-coherent memory");
             }
 
-            let mapped_size = (*mapped.content.get()).len() as u64;
+            let mapped_size = mapped.size;
 
             assert!(
                 range.offset >= mapped.offset,
@@ -223,7 +567,7 @@ impl MemoryDevice<usize> for MockMemoryDevice {
                 "range `offset` specifies range after mapped region"
             );
             assert!(
-                range.size < mapped_size - (range.offset - mapped.offset),
+                range.size <= mapped_size - (range.offset - mapped.offset),
                 "range `size` specifies range after mapped region"
             );
             assert_eq!(
@@ -245,6 +589,14 @@ impl MemoryDevice<usize> for MockMemoryDevice {
         &self,
         ranges: &[MappedMemoryRange<'_, usize>],
     ) -> Result<(), OutOfMemory> {
+        if let Some(remaining) = &self.flushes_before_failure {
+            if remaining.get() == 0 {
+                tracing::warn!("Flush failed");
+                return Err(OutOfMemory::OutOfHostMemory);
+            }
+            remaining.set(remaining.get() - 1);
+        }
+
         for range in ranges {
             let mut allocations = self.allocations.borrow_mut();
             let memory = allocations
@@ -258,10 +610,10 @@ impl MemoryDevice<usize> for MockMemoryDevice {
                 .contains(MemoryPropertyFlags::HOST_COHERENT);
 
             if coherent {
-                tracing::warn!("Invalidating host-coherent memory");
+                tracing::warn!("Flushing host-coherent memory");
             }
 
-            let mapped_size = (*mapped.content.get()).len() as u64;
+            let mapped_size = mapped.size;
 
             assert!(
                 range.offset >= mapped.offset,
@@ -272,7 +624,7 @@ impl MemoryDevice<usize> for MockMemoryDevice {
                 "`offset` specifies range after mapped region"
             );
             assert!(
-                range.size < mapped_size - (range.offset - mapped.offset),
+                range.size <= mapped_size - (range.offset - mapped.offset),
                 "`size` specifies range after mapped region"
             );
             assert_eq!(
@@ -288,6 +640,10 @@ impl MemoryDevice<usize> for MockMemoryDevice {
         }
         Ok(())
     }
+
+    fn take_allocation_diagnostic(&self) -> Option<DeviceDiagnostic> {
+        self.diagnostic.take()
+    }
 }
 
 // MockMemoryDevice is not a wrapper for external type in other crate,
@@ -0,0 +1,326 @@
+//! Profiling [`MemoryDevice`] wrapper for [`gpu-alloc`].
+//!
+//! [`ProfiledDevice`] wraps any device implementation and records
+//! number of calls, bytes and time spent in each device operation.
+//! Comparing time spent in the device with time spent in allocator calls
+//! shows whether the driver or the allocator is slow,
+//! without modifying backend crates.
+//!
+//! # Usage example
+//!
+//! ```ignore
+//! let device = ProfiledDevice::new(AshMemoryDevice::wrap(&device));
+//! let block = unsafe { allocator.alloc(&device, request) }?;
+//!
+//! let profile = device.profile();
+//! println!("{}", profile);
+//! ```
+//!
+//! [`gpu-alloc`]: https://docs.rs/gpu-alloc
+
+use {
+    gpu_alloc_types::{
+        AllocationFlags, DedicatedAllocationInfo, DeviceDiagnostic, DeviceMapError,
+        MappedMemoryRange, MemoryDevice, OutOfMemory,
+    },
+    std::{
+        fmt,
+        ptr::NonNull,
+        sync::{Mutex, MutexGuard},
+        time::{Duration, Instant},
+    },
+};
+
+/// Statistics of calls to one device operation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct OperationStats {
+    /// Number of calls.
+    pub calls: u64,
+
+    /// Number of calls that returned an error.
+    pub failures: u64,
+
+    /// Number of bytes passed to the calls.
+    ///
+    /// Sizes of allocated memory objects, mapped ranges and flushed or invalidated ranges.
+    /// Always zero for operations that don't take size.
+    pub bytes: u64,
+
+    /// Total time spent in the calls.
+    pub total_time: Duration,
+
+    /// Longest call.
+    pub max_time: Duration,
+}
+
+impl OperationStats {
+    /// Returns average duration of a call.
+    pub fn average_time(&self) -> Duration {
+        if self.calls == 0 {
+            Duration::ZERO
+        } else {
+            self.total_time / self.calls.min(u32::MAX as u64) as u32
+        }
+    }
+
+    fn record(&mut self, bytes: u64, time: Duration, failed: bool) {
+        self.calls += 1;
+        self.failures += failed as u64;
+        self.bytes = self.bytes.saturating_add(bytes);
+        self.total_time += time;
+        self.max_time = self.max_time.max(time);
+    }
+}
+
+/// Statistics of calls to device operations recorded by [`ProfiledDevice`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DeviceProfile {
+    /// Calls to `MemoryDevice::allocate_memory`.
+    pub allocate: OperationStats,
+
+    /// Calls to `MemoryDevice::deallocate_memory`.
+    pub deallocate: OperationStats,
+
+    /// Calls to `MemoryDevice::map_memory`.
+    pub map: OperationStats,
+
+    /// Calls to `MemoryDevice::unmap_memory`.
+    pub unmap: OperationStats,
+
+    /// Calls to `MemoryDevice::flush_memory_ranges`.
+    pub flush: OperationStats,
+
+    /// Calls to `MemoryDevice::invalidate_memory_ranges`.
+    pub invalidate: OperationStats,
+}
+
+impl DeviceProfile {
+    /// Returns total time spent in the device.
+    pub fn total_time(&self) -> Duration {
+        self.operations()
+            .iter()
+            .map(|(_, stats)| stats.total_time)
+            .sum()
+    }
+
+    /// Returns name and statistics of each operation.
+    pub fn operations(&self) -> [(&'static str, &OperationStats); 6] {
+        [
+            ("allocate", &self.allocate),
+            ("deallocate", &self.deallocate),
+            ("map", &self.map),
+            ("unmap", &self.unmap),
+            ("flush", &self.flush),
+            ("invalidate", &self.invalidate),
+        ]
+    }
+}
+
+impl fmt::Display for DeviceProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:<10} {:>8} {:>8} {:>14} {:>12} {:>12} {:>12}",
+            "operation", "calls", "failures", "bytes", "total", "average", "max"
+        )?;
+
+        for (name, stats) in self.operations().iter() {
+            write!(
+                f,
+                "\n{:<10} {:>8} {:>8} {:>14} {:>12?} {:>12?} {:>12?}",
+                name,
+                stats.calls,
+                stats.failures,
+                stats.bytes,
+                stats.total_time,
+                stats.average_time(),
+                stats.max_time
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Wrapper for [`MemoryDevice`] implementation
+/// that records statistics of every call to wrapped device.
+///
+/// Can be used with any backend and with allocator functions directly,
+/// as it implements `AsRef<Self>`.
+pub struct ProfiledDevice<D> {
+    device: D,
+    profile: Mutex<DeviceProfile>,
+}
+
+impl<D> ProfiledDevice<D> {
+    /// Wraps device implementation.
+    pub fn new(device: D) -> Self {
+        ProfiledDevice {
+            device,
+            profile: Mutex::new(DeviceProfile::default()),
+        }
+    }
+
+    /// Returns wrapped device.
+    pub fn inner(&self) -> &D {
+        &self.device
+    }
+
+    /// Unwraps device implementation.
+    pub fn into_inner(self) -> D {
+        self.device
+    }
+
+    /// Returns statistics recorded so far.
+    pub fn profile(&self) -> DeviceProfile {
+        *self.lock()
+    }
+
+    /// Returns statistics recorded so far and starts recording anew.
+    pub fn reset(&self) -> DeviceProfile {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, DeviceProfile> {
+        // Statistics are always consistent, even if recording thread panicked.
+        match self.profile.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn record(
+        &self,
+        operation: fn(&mut DeviceProfile) -> &mut OperationStats,
+        bytes: u64,
+        start: Instant,
+        failed: bool,
+    ) {
+        let time = start.elapsed();
+        operation(&mut self.lock()).record(bytes, time, failed);
+    }
+}
+
+impl<D> fmt::Debug for ProfiledDevice<D>
+where
+    D: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProfiledDevice")
+            .field("device", &self.device)
+            .field("profile", &self.profile())
+            .finish()
+    }
+}
+
+impl<D> AsRef<ProfiledDevice<D>> for ProfiledDevice<D> {
+    fn as_ref(&self) -> &ProfiledDevice<D> {
+        self
+    }
+}
+
+fn ranges_size<M>(ranges: &[MappedMemoryRange<'_, M>]) -> u64 {
+    ranges
+        .iter()
+        .fold(0u64, |bytes, range| bytes.saturating_add(range.size))
+}
+
+impl<M, D> MemoryDevice<M> for ProfiledDevice<D>
+where
+    D: MemoryDevice<M>,
+{
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<M, OutOfMemory> {
+        let start = Instant::now();
+        let result = self.device.allocate_memory(size, memory_type, flags);
+        self.record(
+            |profile| &mut profile.allocate,
+            size,
+            start,
+            result.is_err(),
+        );
+        result
+    }
+
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        info: DedicatedAllocationInfo,
+    ) -> Result<M, OutOfMemory> {
+        let start = Instant::now();
+        let result = self
+            .device
+            .allocate_dedicated_memory(size, memory_type, flags, info);
+        self.record(
+            |profile| &mut profile.allocate,
+            size,
+            start,
+            result.is_err(),
+        );
+        result
+    }
+
+    unsafe fn deallocate_memory(&self, memory: M) {
+        let start = Instant::now();
+        self.device.deallocate_memory(memory);
+        self.record(|profile| &mut profile.deallocate, 0, start, false);
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut M,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        let start = Instant::now();
+        let result = self.device.map_memory(memory, offset, size);
+        self.record(|profile| &mut profile.map, size, start, result.is_err());
+        result
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut M) {
+        let start = Instant::now();
+        self.device.unmap_memory(memory);
+        self.record(|profile| &mut profile.unmap, 0, start, false);
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        let start = Instant::now();
+        let result = self.device.invalidate_memory_ranges(ranges);
+        self.record(
+            |profile| &mut profile.invalidate,
+            ranges_size(ranges),
+            start,
+            result.is_err(),
+        );
+        result
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        let start = Instant::now();
+        let result = self.device.flush_memory_ranges(ranges);
+        self.record(
+            |profile| &mut profile.flush,
+            ranges_size(ranges),
+            start,
+            result.is_err(),
+        );
+        result
+    }
+
+    fn take_allocation_diagnostic(&self) -> Option<DeviceDiagnostic> {
+        self.device.take_allocation_diagnostic()
+    }
+}
@@ -0,0 +1,94 @@
+use {
+    gpu_alloc::{
+        Config, DeviceProperties, GpuAllocator, MemoryHeap, MemoryPropertyFlags, MemoryType,
+        Request, ResourceKind, UsageFlags,
+    },
+    gpu_alloc_mock::MockMemoryDevice,
+    gpu_alloc_profiler::ProfiledDevice,
+    std::borrow::Cow,
+};
+
+fn props() -> DeviceProperties<'static> {
+    DeviceProperties {
+        memory_types: Cow::Borrowed(&[MemoryType {
+            heap: 0,
+            props: MemoryPropertyFlags::HOST_VISIBLE,
+        }]),
+        memory_heaps: Cow::Borrowed(&[MemoryHeap {
+            size: 64 * 1024 * 1024,
+            budget: None,
+            usage: None,
+        }]),
+        max_memory_allocation_count: 32,
+        max_memory_allocation_size: 64 * 1024 * 1024,
+        non_coherent_atom_size: 64,
+        buffer_image_granularity: 1,
+        buffer_device_address: false,
+    }
+}
+
+#[test]
+fn device_calls_recorded() {
+    let device = ProfiledDevice::new(MockMemoryDevice::new(props()));
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), props());
+
+    let request = Request {
+        size: 1024,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS | UsageFlags::DOWNLOAD,
+        memory_types: 1,
+        kind: ResourceKind::Linear,
+    };
+
+    let mut block = unsafe { allocator.alloc(&device, request) }.unwrap();
+    unsafe { block.write_bytes(&device, 0, &[1, 2, 3, 4]) }.unwrap();
+
+    let profile = device.profile();
+    assert_eq!(profile.allocate.calls, 1);
+    assert_eq!(profile.allocate.failures, 0);
+    assert_eq!(profile.allocate.bytes, device.inner().allocated_memory(0));
+    assert_eq!(profile.map.calls, 1);
+    assert_eq!(profile.flush.calls, 1);
+    assert_eq!(profile.flush.bytes, 64);
+    assert!(profile.allocate.max_time <= profile.allocate.total_time);
+    assert!(profile.total_time() >= profile.allocate.total_time);
+
+    unsafe { allocator.dealloc(&device, block) }
+    unsafe { allocator.cleanup(&device) }
+
+    let profile = device.reset();
+    assert_eq!(profile.deallocate.calls, 1);
+    assert_eq!(device.profile().allocate.calls, 0);
+
+    let report = profile.to_string();
+    assert!(report.starts_with("operation"));
+    assert_eq!(report.lines().count(), 7);
+
+    assert_eq!(device.into_inner().live_allocations(), 0);
+}
+
+#[test]
+fn failures_recorded() {
+    let mut mock = MockMemoryDevice::new(props());
+    mock.fail_flush_after(0);
+    let device = ProfiledDevice::new(mock);
+    let mut allocator = GpuAllocator::new(Config::i_am_potato(), props());
+
+    let request = Request {
+        size: 1024,
+        align_mask: 0,
+        usage: UsageFlags::HOST_ACCESS | UsageFlags::DOWNLOAD,
+        memory_types: 1,
+        kind: ResourceKind::Linear,
+    };
+    let mut block = unsafe { allocator.alloc(&device, request) }.unwrap();
+    unsafe { block.write_bytes(&device, 0, &[1, 2, 3, 4]) }.unwrap_err();
+
+    let profile = device.profile();
+    assert_eq!(profile.flush.calls, 1);
+    assert_eq!(profile.flush.failures, 1);
+    assert_eq!(profile.allocate.failures, 0);
+
+    unsafe { allocator.dealloc(&device, block) }
+    unsafe { allocator.cleanup(&device) }
+}
@@ -1,7 +1,7 @@
 use {
     crate::types::{MemoryHeap, MemoryType},
     alloc::borrow::Cow,
-    core::ptr::NonNull,
+    core::{fmt, ptr::NonNull},
 };
 
 /// Memory exhausted error.
@@ -27,6 +27,26 @@ pub enum DeviceMapError {
     MapFailed,
 }
 
+/// Backend specific details of failed device call,
+/// such as raw `VkResult` or `HRESULT`.
+///
+/// Lets users tell driver errors from allocator running out of memory
+/// when reading failure details.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct DeviceDiagnostic {
+    /// Backend specific error code.
+    pub code: i64,
+
+    /// Description of the error.
+    pub message: Cow<'static, str>,
+}
+
+impl fmt::Display for DeviceDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
 /// Specifies range of the mapped memory region.
 #[derive(Debug)]
 pub struct MappedMemoryRange<'a, M> {
@@ -60,6 +80,11 @@ pub struct DeviceProperties<'a> {
     /// Atom size for host mappable non-coherent memory.
     pub non_coherent_atom_size: u64,
 
+    /// Granularity in bytes of pages that linear and non-linear resources must not share
+    /// within one memory object, `bufferImageGranularity` in Vulkan.
+    /// Must be power of two.
+    pub buffer_image_granularity: u64,
+
     /// Specifies if feature required to fetch device address is enabled.
     pub buffer_device_address: bool,
 }
@@ -75,6 +100,19 @@ bitflags::bitflags! {
     }
 }
 
+/// Resource that dedicated memory object is allocated for.
+///
+/// Handles are raw backend handles, such as `VkBuffer` and `VkImage` for Vulkan backends,
+/// which translate it into `VkMemoryDedicatedAllocateInfo`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DedicatedAllocationInfo {
+    /// Memory object is allocated for buffer with specified raw handle.
+    Buffer(u64),
+
+    /// Memory object is allocated for image with specified raw handle.
+    Image(u64),
+}
+
 /// Abstract device that can be used to allocate memory objects.
 pub trait MemoryDevice<M> {
     /// Allocates new memory object from device.
@@ -95,6 +133,26 @@ pub trait MemoryDevice<M> {
         flags: AllocationFlags,
     ) -> Result<M, OutOfMemory>;
 
+    /// Allocates new memory object dedicated to single resource described by `info`.
+    ///
+    /// Default implementation ignores `info` and calls [`MemoryDevice::allocate_memory`].
+    ///
+    /// # Safety
+    ///
+    /// Same as for [`MemoryDevice::allocate_memory`].
+    /// Additionally resource described by `info` must be valid,
+    /// must not be bound to memory and `size` must be equal to its memory requirements.
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        info: DedicatedAllocationInfo,
+    ) -> Result<M, OutOfMemory> {
+        let _ = info;
+        self.allocate_memory(size, memory_type, flags)
+    }
+
     /// Deallocate memory object.
     ///
     /// # Safety
@@ -155,4 +213,153 @@ pub trait MemoryDevice<M> {
         &self,
         ranges: &[MappedMemoryRange<'_, M>],
     ) -> Result<(), OutOfMemory>;
+
+    /// Takes diagnostic of the last failed [`MemoryDevice::allocate_memory`] call.
+    ///
+    /// Allocator calls this function on the same thread right after `allocate_memory`
+    /// returns an error and stores result with failure details.
+    /// Default implementation returns `None`.
+    fn take_allocation_diagnostic(&self) -> Option<DeviceDiagnostic> {
+        None
+    }
+}
+
+/// Reference to device as trait object that can be passed to allocator.
+///
+/// Allocator functions accept device through `AsRef` of sized device type.
+/// This wrapper allows plugin architectures and scripting layers
+/// to hold device as `&dyn MemoryDevice<M>` and still call allocator.
+pub struct DynMemoryDevice<'a, M> {
+    device: &'a dyn MemoryDevice<M>,
+}
+
+impl<'a, M> DynMemoryDevice<'a, M> {
+    /// Wraps device trait object.
+    pub fn new(device: &'a dyn MemoryDevice<M>) -> Self {
+        DynMemoryDevice { device }
+    }
+
+    /// Returns wrapped device trait object.
+    pub fn get(&self) -> &'a dyn MemoryDevice<M> {
+        self.device
+    }
+}
+
+impl<M> Clone for DynMemoryDevice<'_, M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M> Copy for DynMemoryDevice<'_, M> {}
+
+impl<M> fmt::Debug for DynMemoryDevice<'_, M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynMemoryDevice").finish_non_exhaustive()
+    }
+}
+
+impl<'a, M> AsRef<DynMemoryDevice<'a, M>> for DynMemoryDevice<'a, M> {
+    fn as_ref(&self) -> &DynMemoryDevice<'a, M> {
+        self
+    }
+}
+
+/// Reference to device that can be passed to allocator.
+///
+/// Backends implement `AsRef` of their device type for the device itself
+/// and for raw API handles. This wrapper provides it for any [`MemoryDevice`]
+/// implementation, such as device defined by application,
+/// without writing `AsRef` implementation.
+pub struct DeviceRef<'a, D> {
+    device: &'a D,
+}
+
+impl<'a, D> DeviceRef<'a, D> {
+    /// Wraps device reference.
+    pub fn new(device: &'a D) -> Self {
+        DeviceRef { device }
+    }
+
+    /// Returns wrapped device reference.
+    pub fn get(&self) -> &'a D {
+        self.device
+    }
+}
+
+impl<D> Clone for DeviceRef<'_, D> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<D> Copy for DeviceRef<'_, D> {}
+
+impl<D> fmt::Debug for DeviceRef<'_, D> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeviceRef").finish_non_exhaustive()
+    }
+}
+
+impl<D> AsRef<D> for DeviceRef<'_, D> {
+    fn as_ref(&self) -> &D {
+        self.device
+    }
+}
+
+impl<M> MemoryDevice<M> for DynMemoryDevice<'_, M> {
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+    ) -> Result<M, OutOfMemory> {
+        self.device.allocate_memory(size, memory_type, flags)
+    }
+
+    unsafe fn allocate_dedicated_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        flags: AllocationFlags,
+        info: DedicatedAllocationInfo,
+    ) -> Result<M, OutOfMemory> {
+        self.device
+            .allocate_dedicated_memory(size, memory_type, flags, info)
+    }
+
+    unsafe fn deallocate_memory(&self, memory: M) {
+        self.device.deallocate_memory(memory)
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut M,
+        offset: u64,
+        size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        self.device.map_memory(memory, offset, size)
+    }
+
+    unsafe fn unmap_memory(&self, memory: &mut M) {
+        self.device.unmap_memory(memory)
+    }
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.invalidate_memory_ranges(ranges)
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        ranges: &[MappedMemoryRange<'_, M>],
+    ) -> Result<(), OutOfMemory> {
+        self.device.flush_memory_ranges(ranges)
+    }
+
+    fn take_allocation_diagnostic(&self) -> Option<DeviceDiagnostic> {
+        self.device.take_allocation_diagnostic()
+    }
 }
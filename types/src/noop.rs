@@ -0,0 +1,166 @@
+use {
+    crate::{
+        device::{
+            AllocationFlags, DeviceMapError, DeviceProperties, MappedMemoryRange, MemoryDevice,
+            OutOfMemory,
+        },
+        types::{MemoryHeap, MemoryPropertyFlags, MemoryType},
+    },
+    alloc::{borrow::Cow, vec::Vec},
+    core::{
+        convert::TryFrom as _,
+        fmt,
+        ptr::NonNull,
+        sync::atomic::{AtomicUsize, Ordering::Relaxed},
+    },
+};
+
+static MEMORY_TYPES: [MemoryType; 1] = [MemoryType {
+    heap: 0,
+    props: MemoryPropertyFlags::DEVICE_LOCAL
+        .union(MemoryPropertyFlags::HOST_VISIBLE)
+        .union(MemoryPropertyFlags::HOST_COHERENT),
+}];
+
+/// Memory object allocated by [`NoopMemoryDevice`].
+///
+/// Host memory backing the object is allocated when it is mapped for the first time.
+pub struct NoopMemory {
+    size: u64,
+    memory_type: u32,
+    data: Vec<u8>,
+}
+
+impl NoopMemory {
+    /// Returns size of the memory object.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Returns index of memory type the memory object was allocated from.
+    pub fn memory_type(&self) -> u32 {
+        self.memory_type
+    }
+}
+
+impl fmt::Debug for NoopMemory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("NoopMemory")
+            .field("size", &self.size)
+            .field("memory_type", &self.memory_type)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Trivial device that hands out memory objects backed by host memory.
+///
+/// Intended for headless runs and unit tests of code that needs [`MemoryDevice`]
+/// without a real backend.
+/// Allocations are only counted, mapping returns pointers into host buffers
+/// and flushes and invalidations do nothing.
+/// Unlike `gpu-alloc-mock` it does not validate usage.
+#[derive(Debug, Default)]
+pub struct NoopMemoryDevice {
+    live: AtomicUsize,
+    total: AtomicUsize,
+}
+
+impl NoopMemoryDevice {
+    /// Returns new device.
+    pub fn new() -> Self {
+        NoopMemoryDevice::default()
+    }
+
+    /// Returns properties of device with single heap of `heap_size` bytes
+    /// and single `DEVICE_LOCAL | HOST_VISIBLE | HOST_COHERENT` memory type,
+    /// suitable for any request.
+    pub fn properties(heap_size: u64) -> DeviceProperties<'static> {
+        DeviceProperties {
+            memory_types: Cow::Borrowed(&MEMORY_TYPES),
+            memory_heaps: Cow::Owned(alloc::vec![MemoryHeap {
+                size: heap_size,
+                budget: None,
+                usage: None
+            }]),
+            max_memory_allocation_count: u32::MAX,
+            max_memory_allocation_size: heap_size,
+            non_coherent_atom_size: 1,
+            buffer_image_granularity: 1,
+            buffer_device_address: false,
+        }
+    }
+
+    /// Returns number of memory objects allocated and not yet deallocated.
+    pub fn live_allocations(&self) -> usize {
+        self.live.load(Relaxed)
+    }
+
+    /// Returns number of memory objects allocated over device lifetime.
+    pub fn total_allocations(&self) -> usize {
+        self.total.load(Relaxed)
+    }
+}
+
+impl AsRef<NoopMemoryDevice> for NoopMemoryDevice {
+    fn as_ref(&self) -> &NoopMemoryDevice {
+        self
+    }
+}
+
+impl MemoryDevice<NoopMemory> for NoopMemoryDevice {
+    unsafe fn allocate_memory(
+        &self,
+        size: u64,
+        memory_type: u32,
+        _flags: AllocationFlags,
+    ) -> Result<NoopMemory, OutOfMemory> {
+        self.live.fetch_add(1, Relaxed);
+        self.total.fetch_add(1, Relaxed);
+
+        Ok(NoopMemory {
+            size,
+            memory_type,
+            data: Vec::new(),
+        })
+    }
+
+    unsafe fn deallocate_memory(&self, memory: NoopMemory) {
+        drop(memory);
+        self.live.fetch_sub(1, Relaxed);
+    }
+
+    unsafe fn map_memory(
+        &self,
+        memory: &mut NoopMemory,
+        offset: u64,
+        _size: u64,
+    ) -> Result<NonNull<u8>, DeviceMapError> {
+        if memory.data.is_empty() {
+            let size = usize::try_from(memory.size).map_err(|_| DeviceMapError::OutOfHostMemory)?;
+            memory
+                .data
+                .try_reserve_exact(size)
+                .map_err(|_| DeviceMapError::OutOfHostMemory)?;
+            memory.data.resize(size, 0);
+        }
+
+        let ptr = memory.data.as_mut_ptr().add(offset as usize);
+        Ok(NonNull::new_unchecked(ptr))
+    }
+
+    unsafe fn unmap_memory(&self, _memory: &mut NoopMemory) {}
+
+    unsafe fn invalidate_memory_ranges(
+        &self,
+        _ranges: &[MappedMemoryRange<'_, NoopMemory>],
+    ) -> Result<(), OutOfMemory> {
+        Ok(())
+    }
+
+    unsafe fn flush_memory_ranges(
+        &self,
+        _ranges: &[MappedMemoryRange<'_, NoopMemory>],
+    ) -> Result<(), OutOfMemory> {
+        Ok(())
+    }
+}
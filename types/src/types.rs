@@ -52,4 +52,40 @@ pub struct MemoryType {
 pub struct MemoryHeap {
     /// Size of memory heap in bytes.
     pub size: u64,
+
+    /// Number of bytes the process may allocate from the heap as reported by the driver,
+    /// for example with `VK_EXT_memory_budget`.
+    /// `None` if not reported.
+    pub budget: Option<u64>,
+
+    /// Number of bytes the process already allocated from the heap as reported by the driver.
+    /// `None` if not reported.
+    pub usage: Option<u64>,
+}
+
+impl MemoryHeap {
+    /// Returns number of bytes that can be allocated from the heap.
+    ///
+    /// This is reported budget less reported usage,
+    /// or the whole heap size if budget is not reported.
+    pub fn available(&self) -> u64 {
+        match self.budget {
+            Some(budget) => budget.saturating_sub(self.usage.unwrap_or(0)),
+            None => self.size,
+        }
+    }
+}
+
+/// Kind of resource bound to memory block.
+///
+/// Linear and non-linear resources must not share a page of
+/// `bufferImageGranularity` size within the same memory object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ResourceKind {
+    /// Buffers and images with linear tiling.
+    Linear,
+
+    /// Images with optimal tiling.
+    NonLinear,
 }